@@ -0,0 +1,201 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix::prelude::*;
+use bean_factory::{bean, BeanFactory, FactoryData, Inject};
+
+use crate::audit::model::{AuditLogDo, AuditLogDto, AuditLogQueryParam};
+use crate::common::constant::AUDIT_LOG_TABLE_NAME;
+use crate::common::AppSysConfig;
+use crate::raft::db::route::TableRoute;
+use crate::raft::db::table::{
+    TableManager, TableManagerInnerReq, TableManagerReq, TableManagerResult,
+};
+
+/// 写入失败时缓存的最大记录数，超出后丢弃新记录，避免集群不可用时内存持续增长
+const MAX_BUFFER_SIZE: usize = 10000;
+const FLUSH_BATCH_SIZE: usize = 500;
+
+///
+/// 审计日志管理
+/// 记录先缓存在本地，每秒批量通过raft写入审计表
+#[bean(inject)]
+#[derive(Default)]
+pub struct AuditLogManager {
+    enable: bool,
+    node_id: u64,
+    seq: u64,
+    buffer: Vec<AuditLogDo>,
+    flushing: bool,
+    table_route: Option<Arc<TableRoute>>,
+    table_manager: Option<Addr<TableManager>>,
+}
+
+impl AuditLogManager {
+    pub fn new(sys_config: &AppSysConfig) -> Self {
+        Self {
+            enable: sys_config.audit_log_enable,
+            node_id: sys_config.raft_node_id,
+            ..Default::default()
+        }
+    }
+
+    fn add_record(&mut self, mut record: AuditLogDo) {
+        if !self.enable {
+            return;
+        }
+        record.node_id = self.node_id;
+        if self.buffer.len() >= MAX_BUFFER_SIZE {
+            log::warn!(
+                "audit log buffer is full, ignore record|{}|{}|{}",
+                &record.actor,
+                &record.resource_type,
+                &record.action
+            );
+            return;
+        }
+        self.buffer.push(record);
+    }
+
+    fn flush(&mut self, ctx: &mut Context<Self>) {
+        if self.flushing || self.buffer.is_empty() {
+            return;
+        }
+        let table_route = if let Some(table_route) = self.table_route.clone() {
+            table_route
+        } else {
+            return;
+        };
+        let size = self.buffer.len().min(FLUSH_BATCH_SIZE);
+        let records: Vec<AuditLogDo> = self.buffer.drain(..size).collect();
+        let items = records
+            .iter()
+            .map(|record| {
+                self.seq += 1;
+                (record.build_key(self.seq).into_bytes(), record.to_bytes())
+            })
+            .collect();
+        let req = TableManagerReq::BatchSet {
+            table_name: AUDIT_LOG_TABLE_NAME.clone(),
+            items,
+        };
+        self.flushing = true;
+        async move { table_route.request(req).await }
+            .into_actor(self)
+            .map(move |r, act, _ctx| {
+                act.flushing = false;
+                if let Err(err) = r {
+                    log::warn!("write audit log error,{}", err);
+                    //写入失败放回缓存，下次重试
+                    let mut buffer = records;
+                    buffer.append(&mut act.buffer);
+                    buffer.truncate(MAX_BUFFER_SIZE);
+                    act.buffer = buffer;
+                }
+            })
+            .spawn(ctx);
+    }
+
+    async fn query(
+        table_manager: Addr<TableManager>,
+        param: AuditLogQueryParam,
+    ) -> anyhow::Result<AuditLogResult> {
+        let offset = param.offset;
+        let limit = param.limit;
+        let filter = Box::new(move |key: &[u8], value: &[u8]| {
+            param.match_key(key)
+                && AuditLogDo::from_bytes(value)
+                    .map(|record| param.match_record(&record))
+                    .unwrap_or(false)
+        });
+        let req = TableManagerInnerReq::QueryFilterPageList {
+            table_name: AUDIT_LOG_TABLE_NAME.clone(),
+            filter,
+            offset,
+            limit,
+            is_rev: true,
+        };
+        if let TableManagerResult::PageListResult(total, list) = table_manager.send(req).await?? {
+            let list = list
+                .into_iter()
+                .filter_map(|(key, value)| {
+                    AuditLogDo::from_bytes(&value)
+                        .ok()
+                        .map(|record| AuditLogDto {
+                            id: String::from_utf8_lossy(&key).to_string(),
+                            record,
+                        })
+                })
+                .collect();
+            Ok(AuditLogResult::PageInfo(total, list))
+        } else {
+            Ok(AuditLogResult::PageInfo(0, vec![]))
+        }
+    }
+
+    fn heartbeat(&mut self, ctx: &mut Context<Self>) {
+        ctx.run_later(Duration::from_secs(1), |act, ctx| {
+            act.flush(ctx);
+            act.heartbeat(ctx);
+        });
+    }
+}
+
+impl Actor for AuditLogManager {
+    type Context = Context<Self>;
+
+    fn started(&mut self, _ctx: &mut Self::Context) {
+        log::info!("AuditLogManager actor started")
+    }
+}
+
+impl Inject for AuditLogManager {
+    type Context = Context<Self>;
+
+    fn inject(
+        &mut self,
+        factory_data: FactoryData,
+        _factory: BeanFactory,
+        ctx: &mut Self::Context,
+    ) {
+        self.table_route = factory_data.get_bean();
+        self.table_manager = factory_data.get_actor();
+        self.heartbeat(ctx);
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "anyhow::Result<AuditLogResult>")]
+pub enum AuditLogReq {
+    Record(AuditLogDo),
+    /// 查询本节点已同步的审计日志，按时间倒序
+    Query(AuditLogQueryParam),
+}
+
+pub enum AuditLogResult {
+    None,
+    PageInfo(usize, Vec<AuditLogDto>),
+}
+
+impl Handler<AuditLogReq> for AuditLogManager {
+    type Result = ResponseActFuture<Self, anyhow::Result<AuditLogResult>>;
+
+    fn handle(&mut self, msg: AuditLogReq, _ctx: &mut Self::Context) -> Self::Result {
+        let param = match msg {
+            AuditLogReq::Record(record) => {
+                self.add_record(record);
+                return Box::pin(fut::ready(Ok(AuditLogResult::None)));
+            }
+            AuditLogReq::Query(param) => param,
+        };
+        let table_manager = self.table_manager.clone();
+        let fut = async move {
+            if let Some(table_manager) = table_manager {
+                Self::query(table_manager, param).await
+            } else {
+                Ok(AuditLogResult::PageInfo(0, vec![]))
+            }
+        };
+        Box::pin(fut.into_actor(self).map(|r, _act, _ctx| r))
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use crate::audit::model::{AUDIT_SOURCE_GRPC, RESOURCE_CONFIG, RESOURCE_INSTANCE};
+use crate::audit::AuditContext;
+use crate::grpc::api_model::{
+    BatchInstanceRequest, ConfigPublishRequest, ConfigRemoveRequest, InstanceRequest,
+};
+use crate::grpc::handler::{
+    BATCH_INSTANCE_REQUEST, CONFIG_PUBLISH_REQUEST, CONFIG_REMOVE_REQUEST, INSTANCE_REQUEST,
+};
+use crate::grpc::nacos_proto::Payload;
+use crate::utils::get_md5;
+
+fn instance_action(request_type: &Option<String>, batch: bool) -> String {
+    let action = match request_type.as_ref().map(|v| v.as_str()) {
+        Some("registerInstance") => "register",
+        Some("deregisterInstance") => "deregister",
+        Some(v) => v,
+        None => "",
+    };
+    if batch {
+        format!("batch_{}", action)
+    } else {
+        action.to_owned()
+    }
+}
+
+fn instance_params(
+    namespace: Option<String>,
+    service_name: Option<String>,
+    group_name: Option<String>,
+) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+    params.insert("namespace".to_owned(), namespace.unwrap_or_default());
+    params.insert("serviceName".to_owned(), service_name.unwrap_or_default());
+    params.insert("groupName".to_owned(), group_name.unwrap_or_default());
+    params
+}
+
+///
+/// 构建gRPC写请求的审计信息，非变更请求返回None
+pub fn build_audit_context(
+    request_type: &str,
+    payload: &Payload,
+    instance_enable: bool,
+) -> Option<AuditContext> {
+    let body = &payload.body.as_ref()?.value;
+    let (mut audit, params) = match request_type {
+        CONFIG_PUBLISH_REQUEST | CONFIG_REMOVE_REQUEST => {
+            let (data_id, group, tenant, action) = if request_type == CONFIG_PUBLISH_REQUEST {
+                let request: ConfigPublishRequest = serde_json::from_slice(body).ok()?;
+                (request.data_id, request.group, request.tenant, "publish")
+            } else {
+                let request: ConfigRemoveRequest = serde_json::from_slice(body).ok()?;
+                (request.data_id, request.group, request.tenant, "remove")
+            };
+            let mut params = HashMap::new();
+            params.insert("dataId".to_owned(), data_id);
+            params.insert("group".to_owned(), group);
+            params.insert("tenant".to_owned(), tenant);
+            (
+                AuditContext::new(AUDIT_SOURCE_GRPC, RESOURCE_CONFIG, action),
+                params,
+            )
+        }
+        INSTANCE_REQUEST if instance_enable => {
+            let request: InstanceRequest = serde_json::from_slice(body).ok()?;
+            let action = instance_action(&request.r#type, false);
+            let mut params =
+                instance_params(request.namespace, request.service_name, request.group_name);
+            if let Some(instance) = request.instance {
+                params.insert(
+                    "ip".to_owned(),
+                    instance
+                        .ip
+                        .map(|v| v.as_ref().to_owned())
+                        .unwrap_or_default(),
+                );
+                params.insert("port".to_owned(), instance.port.to_string());
+            }
+            (
+                AuditContext::new(AUDIT_SOURCE_GRPC, RESOURCE_INSTANCE, &action),
+                params,
+            )
+        }
+        BATCH_INSTANCE_REQUEST if instance_enable => {
+            let request: BatchInstanceRequest = serde_json::from_slice(body).ok()?;
+            let action = instance_action(&request.r#type, true);
+            let params =
+                instance_params(request.namespace, request.service_name, request.group_name);
+            (
+                AuditContext::new(AUDIT_SOURCE_GRPC, RESOURCE_INSTANCE, &action),
+                params,
+            )
+        }
+        _ => return None,
+    };
+    audit.fill_resource(&params);
+    audit.submit_digest = get_md5(&String::from_utf8_lossy(body));
+    audit.record.path = request_type.to_owned();
+    Some(audit)
+}
@@ -0,0 +1,348 @@
+use std::collections::{BTreeMap, HashMap};
+use std::future::{ready, Ready};
+use std::sync::Arc;
+
+use actix_http::body::{EitherBody, MessageBody};
+use actix_http::HttpMessage;
+use actix_web::dev::{self, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::Error;
+use futures_util::future::LocalBoxFuture;
+
+use crate::audit::model::{
+    AUDIT_SOURCE_CONSOLE, AUDIT_SOURCE_OPENAPI, RESOURCE_ACCESS_KEY, RESOURCE_CONFIG,
+    RESOURCE_INSTANCE, RESOURCE_MCP_SERVER, RESOURCE_MCP_TOOL_SPEC, RESOURCE_NAMESPACE,
+    RESOURCE_OTHER, RESOURCE_ROLE, RESOURCE_SERVICE, RESOURCE_TRANSFER, RESOURCE_USER,
+    RESOURCE_WEBHOOK,
+};
+use crate::audit::AuditContext;
+use crate::common::appdata::AppShareData;
+use crate::common::model::{TokenSession, UserSession};
+use crate::common::web_utils::{get_content_type, parse_params, peek_request_body};
+use crate::utils::get_md5;
+
+lazy_static::lazy_static! {
+    /// 不属于资源变更的写请求
+    static ref IGNORE_AUDIT_PATH: Vec<&'static str> = vec![
+        "/login", "/logout", "/captcha", "/listener", "/beat", "/metrics", "/raft/", "/debug/",
+        "/rnacos/mcp/", "/timeline",
+    ];
+    /// 按路径片段识别资源类型，按顺序匹配
+    static ref AUDIT_RESOURCE_RULES: Vec<(&'static str, &'static str)> = vec![
+        ("/webhook/", RESOURCE_WEBHOOK),
+        ("/role/", RESOURCE_ROLE),
+        ("/accesskey/", RESOURCE_ACCESS_KEY),
+        ("/mcp/toolspec", RESOURCE_MCP_TOOL_SPEC),
+        ("/mcp/server", RESOURCE_MCP_SERVER),
+        ("/transfer/", RESOURCE_TRANSFER),
+        ("/namespace", RESOURCE_NAMESPACE),
+        ("/user", RESOURCE_USER),
+        ("/instance", RESOURCE_INSTANCE),
+        ("/service", RESOURCE_SERVICE),
+        ("/config", RESOURCE_CONFIG),
+    ];
+    static ref AUDIT_ACTIONS: Vec<&'static str> = vec![
+        "add", "update", "remove", "import", "rollback", "publish", "promote", "stop", "rotate",
+        "reset_password", "batch_update", "retry",
+    ];
+    /// 不参与提交内容摘要计算的敏感参数
+    static ref SENSITIVE_PARAMS: Vec<&'static str> = vec![
+        "password", "oldPassword", "newPassword", "old_password", "new_password", "accessToken",
+        "secret",
+    ];
+}
+
+const CONSOLE_PATH_PREFIX: &str = "/rnacos/api/console";
+
+///
+/// 识别写请求对应的资源类型与操作，返回None表示不需要审计
+fn classify(method: &str, path: &str) -> Option<(&'static str, &'static str, String)> {
+    let method_action = match method {
+        "POST" => "add",
+        "PUT" | "PATCH" => "update",
+        "DELETE" => "remove",
+        _ => return None,
+    };
+    if IGNORE_AUDIT_PATH.iter().any(|v| path.contains(v)) {
+        return None;
+    }
+    let source = if path.starts_with(CONSOLE_PATH_PREFIX) {
+        AUDIT_SOURCE_CONSOLE
+    } else {
+        AUDIT_SOURCE_OPENAPI
+    };
+    let resource_type = AUDIT_RESOURCE_RULES
+        .iter()
+        .find(|(fragment, _)| path.contains(fragment))
+        .map(|(_, resource_type)| *resource_type)
+        .unwrap_or(RESOURCE_OTHER);
+    let last_segment = path.trim_end_matches('/').rsplit('/').next().unwrap_or("");
+    let action = if AUDIT_ACTIONS.contains(&last_segment) {
+        if path.contains("/gray/") {
+            format!("gray_{}", last_segment)
+        } else {
+            last_segment.to_owned()
+        }
+    } else {
+        method_action.to_owned()
+    };
+    Some((source, resource_type, action))
+}
+
+fn submit_digest(params: &HashMap<String, String>) -> String {
+    let sorted: BTreeMap<&String, &String> = params
+        .iter()
+        .filter(|(k, _)| !SENSITIVE_PARAMS.contains(&k.as_str()))
+        .collect();
+    get_md5(&serde_json::to_string(&sorted).unwrap_or_default())
+}
+
+///
+/// 从响应内容判断处理结果，兼容控制台ApiResult与nacos v2 openapi返回结构
+fn parse_result(status: u16, content_type: &str, body: &[u8]) -> (bool, String, String) {
+    let http_success = status < 400;
+    if !content_type.contains("json") {
+        let message = if http_success {
+            String::new()
+        } else {
+            String::from_utf8_lossy(body).to_string()
+        };
+        return (http_success, status.to_string(), message);
+    }
+    let value: serde_json::Value = serde_json::from_slice(body).unwrap_or_default();
+    let message = value
+        .get("message")
+        .or_else(|| value.get("msg"))
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_owned();
+    if let Some(success) = value.get("success").and_then(|v| v.as_bool()) {
+        let code = value
+            .get("code")
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_owned())
+            .unwrap_or_else(|| status.to_string());
+        (http_success && success, code, message)
+    } else if let Some(code) = value.get("code").and_then(|v| v.as_i64()) {
+        (http_success && code == 0, code.to_string(), message)
+    } else {
+        (http_success, status.to_string(), message)
+    }
+}
+
+fn get_actor(request: &ServiceRequest) -> String {
+    let extensions = request.extensions();
+    if let Some(session) = extensions.get::<Arc<UserSession>>() {
+        session.username.as_ref().to_owned()
+    } else if let Some(session) = extensions.get::<Arc<TokenSession>>() {
+        session.username.as_ref().to_owned()
+    } else {
+        String::new()
+    }
+}
+
+fn get_client_ip(request: &ServiceRequest) -> String {
+    let conn_info = request.connection_info();
+    let addr = conn_info.realip_remote_addr().unwrap_or_default();
+    if let Ok(v) = addr.parse::<std::net::SocketAddr>() {
+        v.ip().to_string()
+    } else {
+        addr.to_owned()
+    }
+}
+
+///
+/// 记录控制台与openapi写请求的审计日志，需要在登录校验中间件之后执行
+#[derive(Clone)]
+pub struct AuditLog {
+    app_share_data: Arc<AppShareData>,
+}
+
+impl AuditLog {
+    pub fn new(app_share_data: Arc<AppShareData>) -> Self {
+        Self { app_share_data }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for AuditLog
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = AuditLogMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuditLogMiddleware {
+            service: Arc::new(service),
+            app_share_data: self.app_share_data.clone(),
+        }))
+    }
+}
+
+#[derive(Clone)]
+pub struct AuditLogMiddleware<S> {
+    service: Arc<S>,
+    app_share_data: Arc<AppShareData>,
+}
+
+impl<S, B> Service<ServiceRequest> for AuditLogMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    dev::forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let classify_result = if self.app_share_data.sys_config.audit_log_enable
+            && request.match_pattern().is_some()
+        {
+            classify(request.method().as_str(), request.path())
+        } else {
+            None
+        };
+        let (source, resource_type, action) = if let Some(v) = classify_result {
+            v
+        } else {
+            return Box::pin(async move {
+                service
+                    .call(request)
+                    .await
+                    .map(ServiceResponse::map_into_left_body)
+            });
+        };
+        let app_share_data = self.app_share_data.clone();
+        Box::pin(async move {
+            let mut request = request;
+            let mut audit = AuditContext::new(source, resource_type, &action);
+            let content_type = get_content_type(request.headers());
+            let body = peek_request_body(&mut request, &content_type).await;
+            let params = parse_params(request.query_string(), &content_type, &body);
+            audit.fill_resource(&params);
+            audit.submit_digest = submit_digest(&params);
+            audit.record.path = request.match_pattern().unwrap_or_default();
+            audit.set_actor(&get_actor(&request), get_client_ip(&request));
+            audit.fill_before_digest(&app_share_data).await;
+
+            let res = service.call(request).await?;
+            let status = res.status().as_u16();
+            let content_type = get_content_type(res.headers());
+            if !content_type.contains("json") {
+                let (success, code, message) = parse_result(status, &content_type, &[]);
+                audit.finish(&app_share_data, success, code, &message).await;
+                return Ok(res.map_into_left_body());
+            }
+            let (http_request, response) = res.into_parts();
+            let (response, body) = response.into_parts();
+            let body = actix_web::body::to_bytes(body).await.map_err(|e| {
+                let err: Box<dyn std::error::Error> = e.into();
+                actix_web::error::ErrorInternalServerError(err.to_string())
+            })?;
+            let (success, code, message) = parse_result(status, &content_type, &body);
+            audit.finish(&app_share_data, success, code, &message).await;
+            let response = response.set_body(body).map_into_boxed_body();
+            Ok(ServiceResponse::new(http_request, response).map_into_right_body())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditDigestTarget;
+
+    #[test]
+    fn classify_write_request() {
+        assert_eq!(
+            classify("POST", "/rnacos/api/console/v2/config/gray/publish"),
+            Some((
+                AUDIT_SOURCE_CONSOLE,
+                RESOURCE_CONFIG,
+                "gray_publish".to_owned()
+            ))
+        );
+        assert_eq!(
+            classify("DELETE", "/nacos/v1/ns/instance"),
+            Some((AUDIT_SOURCE_OPENAPI, RESOURCE_INSTANCE, "remove".to_owned()))
+        );
+        assert_eq!(
+            classify("POST", "/rnacos/api/console/v2/user/reset_password"),
+            Some((
+                AUDIT_SOURCE_CONSOLE,
+                RESOURCE_USER,
+                "reset_password".to_owned()
+            ))
+        );
+        assert_eq!(classify("GET", "/nacos/v1/cs/configs"), None);
+        assert_eq!(classify("PUT", "/nacos/v1/ns/instance/beat"), None);
+        assert_eq!(classify("POST", "/nacos/v1/cs/configs/listener"), None);
+        assert_eq!(classify("POST", "/rnacos/api/console/v2/login/login"), None);
+    }
+
+    #[test]
+    fn parse_params_and_result() {
+        let params = parse_params(
+            "tenant=dev",
+            "application/x-www-form-urlencoded",
+            b"dataId=app.yaml&group=DEFAULT_GROUP&content=a",
+        );
+        let mut audit = AuditContext::new(AUDIT_SOURCE_OPENAPI, RESOURCE_CONFIG, "add");
+        audit.fill_resource(&params);
+        assert_eq!(audit.record.namespace, "dev");
+        assert_eq!(audit.record.resource_key, "DEFAULT_GROUP@@app.yaml");
+        assert!(audit.config_key.is_some());
+
+        let params = parse_params(
+            "serviceName=DEFAULT_GROUP@@svc&ip=127.0.0.1&port=8080",
+            "application/x-www-form-urlencoded",
+            b"",
+        );
+        let mut audit = AuditContext::new(AUDIT_SOURCE_OPENAPI, RESOURCE_INSTANCE, "update");
+        audit.fill_resource(&params);
+        match audit.digest_target {
+            Some(AuditDigestTarget::Instance(instance)) => {
+                assert_eq!(instance.namespace_id.as_str(), "public");
+                assert_eq!(instance.group_name.as_str(), "DEFAULT_GROUP");
+                assert_eq!(instance.service_name.as_str(), "svc");
+                assert_eq!(instance.port, 8080);
+            }
+            _ => panic!("instance digest target is missing"),
+        }
+
+        let params = parse_params(
+            "",
+            "application/json",
+            br#"{"username":"u1","password":"p1"}"#,
+        );
+        let mut other = params.clone();
+        other.insert("password".to_owned(), "p2".to_owned());
+        assert_eq!(submit_digest(&params), submit_digest(&other));
+
+        assert_eq!(
+            parse_result(
+                200,
+                "application/json",
+                br#"{"success":false,"code":"NOT_FOUND","message":"not found"}"#
+            ),
+            (false, "NOT_FOUND".to_owned(), "not found".to_owned())
+        );
+        assert_eq!(
+            parse_result(
+                200,
+                "application/json",
+                br#"{"code":0,"message":"success"}"#
+            ),
+            (true, "0".to_owned(), "success".to_owned())
+        );
+        assert!(!parse_result(403, "text/plain", b"").0);
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::audit::core::AuditLogReq;
+use crate::audit::model::{
+    AuditLogDo, RESOURCE_ACCESS_KEY, RESOURCE_CONFIG, RESOURCE_INSTANCE, RESOURCE_MCP_SERVER,
+    RESOURCE_MCP_TOOL_SPEC, RESOURCE_NAMESPACE, RESOURCE_ROLE, RESOURCE_SERVICE, RESOURCE_USER,
+    RESOURCE_WEBHOOK,
+};
+use crate::common::appdata::AppShareData;
+use crate::config::core::{ConfigCmd, ConfigKey, ConfigResult};
+use crate::config::DEFAULT_TENANT;
+use crate::mcp::model::actor_model::{McpManagerReq, McpManagerResult};
+use crate::namespace::model::{NamespaceQueryReq, NamespaceQueryResult};
+use crate::naming::core::{NamingCmd, NamingResult};
+use crate::naming::model::Instance;
+use crate::naming::NamingUtils;
+use crate::user::role::{RoleManagerReq, RoleManagerResult};
+use crate::user::{UserManagerReq, UserManagerResult};
+use crate::utils::get_md5;
+use serde::Serialize;
+
+pub mod core;
+pub mod grpc;
+pub mod middle;
+pub mod model;
+
+///
+/// 一次变更操作的审计信息，请求处理前构建，处理完成后补充结果再写入
+pub struct AuditContext {
+    pub record: AuditLogDo,
+    /// 配置类操作记录变更前后存储值的md5
+    pub config_key: Option<ConfigKey>,
+    /// 实例、命名空间、用户、角色、MCP服务记录变更前后存储值的md5
+    pub digest_target: Option<AuditDigestTarget>,
+    /// 无法查询存储值的操作（服务、工具规范、webhook、access key、批量实例等），
+    /// 提交内容的md5作为after_digest，before_digest为空
+    pub submit_digest: String,
+}
+
+///
+/// 可查询存储值的非配置类审计资源
+#[derive(Debug, Clone)]
+pub enum AuditDigestTarget {
+    Instance(Instance),
+    Namespace(Arc<String>),
+    User(Arc<String>),
+    Role(String),
+    McpServer(u64),
+}
+
+impl AuditContext {
+    pub fn new(source: &str, resource_type: &str, action: &str) -> Self {
+        Self {
+            record: AuditLogDo {
+                timestamp: crate::now_millis_i64(),
+                source: source.to_owned(),
+                resource_type: resource_type.to_owned(),
+                action: action.to_owned(),
+                ..Default::default()
+            },
+            config_key: None,
+            digest_target: None,
+            submit_digest: String::new(),
+        }
+    }
+
+    ///
+    /// 从请求参数中提取命名空间与资源标识
+    pub fn fill_resource(&mut self, params: &HashMap<String, String>) {
+        let get = |names: &[&str]| -> String {
+            names
+                .iter()
+                .filter_map(|name| params.get(*name))
+                .find(|v| !v.is_empty())
+                .cloned()
+                .unwrap_or_default()
+        };
+        let namespace = get(&["namespaceId", "tenant", "namespace", "customNamespaceId"]);
+        let resource_key = match self.record.resource_type.as_str() {
+            RESOURCE_CONFIG => {
+                let data_id = get(&["dataId"]);
+                let group = get(&["group"]);
+                if !data_id.is_empty() {
+                    let tenant = if namespace == DEFAULT_TENANT {
+                        ""
+                    } else {
+                        namespace.as_str()
+                    };
+                    self.config_key = Some(ConfigKey::new(&data_id, &group, tenant));
+                }
+                join_key(&group, &data_id)
+            }
+            RESOURCE_SERVICE => join_key(&get(&["groupName"]), &get(&["serviceName"])),
+            RESOURCE_INSTANCE => {
+                let service = join_key(&get(&["groupName"]), &get(&["serviceName"]));
+                let ip = get(&["ip"]);
+                if ip.is_empty() {
+                    service
+                } else {
+                    let port = get(&["port"]);
+                    self.digest_target = build_instance_target(
+                        &namespace,
+                        get(&["groupName"]),
+                        &get(&["serviceName"]),
+                        &ip,
+                        &port,
+                    )
+                    .map(AuditDigestTarget::Instance);
+                    format!("{}#{}:{}", service, ip, port)
+                }
+            }
+            RESOURCE_NAMESPACE => {
+                if namespace.is_empty() {
+                    get(&["namespaceName"])
+                } else {
+                    self.digest_target =
+                        Some(AuditDigestTarget::Namespace(Arc::new(namespace.clone())));
+                    namespace.clone()
+                }
+            }
+            RESOURCE_USER => {
+                let username = get(&["username"]);
+                if !username.is_empty() {
+                    self.digest_target = Some(AuditDigestTarget::User(Arc::new(username.clone())));
+                }
+                username
+            }
+            RESOURCE_MCP_SERVER => {
+                if let Ok(id) = get(&["id"]).parse::<u64>() {
+                    self.digest_target = Some(AuditDigestTarget::McpServer(id));
+                }
+                get(&["uniqueKey", "id", "name"])
+            }
+            RESOURCE_MCP_TOOL_SPEC => join_key(&get(&["group"]), &get(&["toolName"])),
+            RESOURCE_WEBHOOK => get(&["id", "name"]),
+            RESOURCE_ROLE => {
+                let name = get(&["name"]);
+                if !name.is_empty() {
+                    self.digest_target = Some(AuditDigestTarget::Role(name.clone()));
+                }
+                name
+            }
+            RESOURCE_ACCESS_KEY => get(&["accessKey", "name"]),
+            _ => String::new(),
+        };
+        self.record.namespace = namespace;
+        self.record.resource_key = resource_key;
+    }
+
+    pub fn set_actor(&mut self, actor: &str, source_ip: String) {
+        self.record.actor = actor.to_owned();
+        self.record.source_ip = source_ip;
+    }
+
+    pub async fn fill_before_digest(&mut self, app: &Arc<AppShareData>) {
+        if let Some(config_key) = self.config_key.clone() {
+            self.record.before_digest = get_config_digest(app, config_key).await;
+        } else if let Some(target) = self.digest_target.as_ref() {
+            self.record.before_digest = get_target_digest(app, target).await;
+        }
+    }
+
+    ///
+    /// 补充处理结果并提交写入
+    pub async fn finish(
+        mut self,
+        app: &Arc<AppShareData>,
+        success: bool,
+        result_code: String,
+        message: &str,
+    ) {
+        self.record.success = success;
+        self.record.result_code = result_code;
+        self.record.set_message(message);
+        if let Some(config_key) = self.config_key.take() {
+            self.record.after_digest = get_config_digest(app, config_key).await;
+        } else if let Some(target) = self.digest_target.take() {
+            self.record.after_digest = get_target_digest(app, &target).await;
+        } else if success && !is_remove_action(&self.record.action) {
+            self.record.after_digest = self.submit_digest;
+        }
+        app.audit_log_manager
+            .do_send(AuditLogReq::Record(self.record));
+    }
+}
+
+fn build_instance_target(
+    namespace: &str,
+    group_name: String,
+    service_name: &str,
+    ip: &str,
+    port: &str,
+) -> Option<Instance> {
+    let port = port.parse::<u32>().ok()?;
+    let (group_name, service_name) = match NamingUtils::split_group_and_service_name(service_name) {
+        Some((group_name, service_name)) => (group_name, service_name),
+        None => (
+            NamingUtils::default_group(group_name),
+            service_name.to_owned(),
+        ),
+    };
+    if service_name.is_empty() {
+        return None;
+    }
+    let mut instance = Instance::new(ip.to_owned(), port);
+    instance.namespace_id = Arc::new(NamingUtils::default_namespace(namespace.to_owned()));
+    instance.group_name = Arc::new(group_name);
+    instance.service_name = Arc::new(service_name);
+    Some(instance)
+}
+
+fn join_key(group: &str, name: &str) -> String {
+    if group.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{}@@{}", group, name)
+    }
+}
+
+fn is_remove_action(action: &str) -> bool {
+    action.ends_with("remove") || action.ends_with("delete") || action.ends_with("deregister")
+}
+
+async fn get_config_digest(app: &Arc<AppShareData>, config_key: ConfigKey) -> String {
+    match app.config_addr.send(ConfigCmd::GET(config_key)).await {
+        Ok(Ok(ConfigResult::Data { md5, .. })) => md5.as_ref().to_owned(),
+        _ => String::new(),
+    }
+}
+
+fn value_digest<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value)
+        .map(|v| get_md5(&v))
+        .unwrap_or_default()
+}
+
+///
+/// 查询资源当前存储值的md5，资源不存在时为空
+async fn get_target_digest(app: &Arc<AppShareData>, target: &AuditDigestTarget) -> String {
+    match target {
+        AuditDigestTarget::Instance(instance) => {
+            match app
+                .naming_addr
+                .send(NamingCmd::Query(instance.clone()))
+                .await
+            {
+                // 健康状态与心跳时间随心跳变化，不计入摘要
+                Ok(Ok(NamingResult::Instance(v))) => value_digest(&(
+                    v.weight,
+                    v.enabled,
+                    v.ephemeral,
+                    &v.cluster_name,
+                    v.metadata.as_ref(),
+                )),
+                _ => String::new(),
+            }
+        }
+        AuditDigestTarget::Namespace(id) => {
+            match app
+                .namespace_addr
+                .send(NamespaceQueryReq::Info(id.clone()))
+                .await
+            {
+                Ok(Ok(NamespaceQueryResult::Info(v))) => value_digest(v.as_ref()),
+                _ => String::new(),
+            }
+        }
+        AuditDigestTarget::User(name) => {
+            let req = UserManagerReq::Query { name: name.clone() };
+            match app.user_manager.send(req).await {
+                Ok(Ok(UserManagerResult::QueryUser(Some(mut user)))) => {
+                    user.password = None;
+                    user.password_hash = None;
+                    value_digest(&user)
+                }
+                _ => String::new(),
+            }
+        }
+        AuditDigestTarget::Role(name) => {
+            match app.role_manager.send(RoleManagerReq::QueryList).await {
+                Ok(Ok(RoleManagerResult::List(list))) => list
+                    .iter()
+                    .find(|v| v.name.as_str() == name)
+                    .map(|v| value_digest(v.as_ref()))
+                    .unwrap_or_default(),
+                _ => String::new(),
+            }
+        }
+        AuditDigestTarget::McpServer(id) => {
+            match app.mcp_manager.send(McpManagerReq::GetServer(*id)).await {
+                Ok(Ok(McpManagerResult::ServerInfo(Some(v)))) => value_digest(v.as_ref()),
+                _ => String::new(),
+            }
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+pub const AUDIT_SOURCE_CONSOLE: &str = "console";
+pub const AUDIT_SOURCE_OPENAPI: &str = "openapi";
+pub const AUDIT_SOURCE_GRPC: &str = "grpc";
+
+pub const RESOURCE_CONFIG: &str = "config";
+pub const RESOURCE_SERVICE: &str = "service";
+pub const RESOURCE_INSTANCE: &str = "instance";
+pub const RESOURCE_NAMESPACE: &str = "namespace";
+pub const RESOURCE_USER: &str = "user";
+pub const RESOURCE_MCP_SERVER: &str = "mcp_server";
+pub const RESOURCE_MCP_TOOL_SPEC: &str = "mcp_tool_spec";
+pub const RESOURCE_TRANSFER: &str = "transfer";
+pub const RESOURCE_WEBHOOK: &str = "webhook";
+pub const RESOURCE_ROLE: &str = "role";
+pub const RESOURCE_ACCESS_KEY: &str = "access_key";
+pub const RESOURCE_OTHER: &str = "other";
+
+const DAY_MILLIS: i64 = 24 * 60 * 60 * 1000;
+const MAX_MESSAGE_LEN: usize = 256;
+
+///
+/// 变更操作审计记录
+/// before_digest/after_digest为变更前后内容的md5；
+/// 配置取存储值的md5，其它资源的after_digest取提交参数的md5，删除类操作after_digest为空
+#[derive(Clone, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogDo {
+    /// 时间戳，单位毫秒
+    #[prost(int64, tag = "1")]
+    pub timestamp: i64,
+    #[prost(uint64, tag = "2")]
+    pub node_id: u64,
+    /// 请求来源：console、openapi、grpc
+    #[prost(string, tag = "3")]
+    pub source: String,
+    #[prost(string, tag = "4")]
+    pub actor: String,
+    #[prost(string, tag = "5")]
+    pub source_ip: String,
+    #[prost(string, tag = "6")]
+    pub namespace: String,
+    #[prost(string, tag = "7")]
+    pub resource_type: String,
+    #[prost(string, tag = "8")]
+    pub resource_key: String,
+    #[prost(string, tag = "9")]
+    pub action: String,
+    #[prost(string, tag = "10")]
+    pub before_digest: String,
+    #[prost(string, tag = "11")]
+    pub after_digest: String,
+    #[prost(bool, tag = "12")]
+    pub success: bool,
+    /// http状态码或接口返回的错误码
+    #[prost(string, tag = "13")]
+    pub result_code: String,
+    #[prost(string, tag = "14")]
+    pub message: String,
+    /// http路由模板或gRPC请求类型
+    #[prost(string, tag = "15")]
+    pub path: String,
+}
+
+impl AuditLogDo {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::new();
+        prost::Message::encode(self, &mut v).unwrap_or_default();
+        v
+    }
+
+    pub fn from_bytes(v: &[u8]) -> anyhow::Result<Self> {
+        Ok(prost::Message::decode(v)?)
+    }
+
+    ///
+    /// 表中的key以时间戳开头，按key排序即按时间排序，便于按时间清理与分页
+    pub fn build_key(&self, seq: u64) -> String {
+        format!("{:013}_{}_{:010}", self.timestamp, self.node_id, seq)
+    }
+
+    pub fn set_message(&mut self, message: &str) {
+        let mut end = message.len().min(MAX_MESSAGE_LEN);
+        while !message.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.message = message[..end].to_owned();
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogDto {
+    pub id: String,
+    #[serde(flatten)]
+    pub record: AuditLogDo,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AuditLogQueryParam {
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    pub actor: Option<Arc<String>>,
+    pub source_ip: Option<Arc<String>>,
+    pub namespace: Option<Arc<String>>,
+    pub resource_type: Option<Arc<String>>,
+    /// 按包含匹配
+    pub resource_key: Option<Arc<String>>,
+    pub action: Option<Arc<String>>,
+    pub source: Option<Arc<String>>,
+    pub success: Option<bool>,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl AuditLogQueryParam {
+    /// 时间条件可以直接通过key的范围过滤
+    pub fn match_key(&self, key: &[u8]) -> bool {
+        if self.start_time.is_none() && self.end_time.is_none() {
+            return true;
+        }
+        let timestamp = parse_key_timestamp(key).unwrap_or_default();
+        if let Some(start_time) = self.start_time {
+            if timestamp < start_time {
+                return false;
+            }
+        }
+        if let Some(end_time) = self.end_time {
+            if timestamp > end_time {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn match_record(&self, record: &AuditLogDo) -> bool {
+        fn eq(v: &Option<Arc<String>>, value: &str) -> bool {
+            v.as_ref()
+                .is_none_or(|v| v.is_empty() || v.as_str() == value)
+        }
+        if let Some(resource_key) = &self.resource_key {
+            if !record.resource_key.contains(resource_key.as_str()) {
+                return false;
+            }
+        }
+        if let Some(success) = self.success {
+            if record.success != success {
+                return false;
+            }
+        }
+        eq(&self.actor, &record.actor)
+            && eq(&self.source_ip, &record.source_ip)
+            && eq(&self.namespace, &record.namespace)
+            && eq(&self.resource_type, &record.resource_type)
+            && eq(&self.action, &record.action)
+            && eq(&self.source, &record.source)
+    }
+}
+
+fn parse_key_timestamp(key: &[u8]) -> Option<i64> {
+    let end = key.iter().position(|c| *c == b'_').unwrap_or(key.len());
+    std::str::from_utf8(&key[..end]).ok()?.parse().ok()
+}
+
+///
+/// 以时间戳开头作为key的表(审计日志、webhook死信)的保留策略，在raft apply时执行，各节点按相同规则清理
+/// 按最新记录的时间清理超过保留天数的记录，再按条数删除最早的记录
+pub fn apply_retention(
+    table_data: &mut BTreeMap<Vec<u8>, Vec<u8>>,
+    max_count: usize,
+    retention_days: u32,
+) {
+    if retention_days > 0 {
+        let latest = table_data
+            .keys()
+            .next_back()
+            .and_then(|key| parse_key_timestamp(key));
+        if let Some(latest) = latest {
+            let cutoff = latest - retention_days as i64 * DAY_MILLIS;
+            if cutoff > 0 {
+                let cutoff_key = format!("{:013}", cutoff).into_bytes();
+                *table_data = table_data.split_off(&cutoff_key);
+            }
+        }
+    }
+    if max_count > 0 {
+        while table_data.len() > max_count {
+            table_data.pop_first();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_record(timestamp: i64) -> AuditLogDo {
+        AuditLogDo {
+            timestamp,
+            node_id: 1,
+            actor: "admin".to_owned(),
+            resource_type: RESOURCE_CONFIG.to_owned(),
+            resource_key: "DEFAULT_GROUP@@app.yaml".to_owned(),
+            action: "update".to_owned(),
+            success: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn retention_by_time_and_count() {
+        let mut table_data = BTreeMap::new();
+        let now = 1_700_000_000_000i64;
+        for (i, timestamp) in [now - 3 * DAY_MILLIS, now - DAY_MILLIS, now - 10, now]
+            .iter()
+            .enumerate()
+        {
+            let record = build_record(*timestamp);
+            table_data.insert(record.build_key(i as u64).into_bytes(), record.to_bytes());
+        }
+        apply_retention(&mut table_data, 10, 2);
+        assert_eq!(table_data.len(), 3);
+        apply_retention(&mut table_data, 2, 0);
+        assert_eq!(table_data.len(), 2);
+        let first = AuditLogDo::from_bytes(table_data.values().next().unwrap()).unwrap();
+        assert_eq!(first.timestamp, now - 10);
+    }
+
+    #[test]
+    fn query_param_match() {
+        let record = build_record(1_700_000_000_000);
+        let key = record.build_key(1).into_bytes();
+        let mut param = AuditLogQueryParam {
+            start_time: Some(1_600_000_000_000),
+            actor: Some(Arc::new("admin".to_owned())),
+            resource_key: Some(Arc::new("app".to_owned())),
+            ..Default::default()
+        };
+        assert!(param.match_key(&key));
+        assert!(param.match_record(&record));
+        param.end_time = Some(1_650_000_000_000);
+        assert!(!param.match_key(&key));
+        param.success = Some(false);
+        assert!(!param.match_record(&record));
+    }
+}
@@ -0,0 +1,344 @@
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use actix::prelude::*;
+use bean_factory::{bean, BeanFactory, FactoryData, Inject};
+use chrono::FixedOffset;
+
+use crate::backup::cron::CronSchedule;
+use crate::backup::model::{
+    build_backup_file_name, select_expired_files, BackupManagerReq, BackupManagerResult,
+    BackupResult, BackupS3Config, BackupStatus,
+};
+use crate::backup::target::{build_backup_target, BackupTarget};
+use crate::common::AppSysConfig;
+use crate::metrics::core::MetricsManager;
+use crate::metrics::metrics_key::MetricsKey;
+use crate::metrics::model::{MetricsItem, MetricsRecord, MetricsRequest};
+use crate::raft::NacosRaft;
+use crate::transfer::model::{
+    TransferBackupParam, TransferManagerAsyncRequest, TransferManagerResponse,
+};
+use crate::transfer::reader::TransferReader;
+use crate::transfer::writer::TransferWriterManager;
+use crate::{now_millis, now_millis_i64};
+
+/// 单次备份的超时时间，超时后结束本次备份，避免一直处于运行状态
+const BACKUP_TIMEOUT: Duration = Duration::from_secs(3600);
+
+///
+/// 定时备份管理
+/// 按cron表达式在raft主节点生成备份文件，校验后写入备份目标，并按保留策略清理历史备份
+#[bean(inject)]
+pub struct BackupManager {
+    schedule: Option<CronSchedule>,
+    target: Option<Arc<dyn BackupTarget>>,
+    retain_count: usize,
+    retain_days: u32,
+    node_id: u64,
+    offset: FixedOffset,
+    status: BackupStatus,
+    raft: Option<Weak<NacosRaft>>,
+    transfer_writer_manager: Option<Addr<TransferWriterManager>>,
+    metrics_manager: Option<Addr<MetricsManager>>,
+}
+
+impl BackupManager {
+    pub fn new(sys_config: &AppSysConfig) -> Self {
+        let offset = sys_config
+            .gmt_fixed_offset_hours
+            .and_then(|e| FixedOffset::east_opt(e * 3600))
+            .unwrap_or(*chrono::Local::now().offset());
+        let mut status = BackupStatus {
+            enable: sys_config.backup_enable,
+            cron: sys_config.backup_cron.as_ref().to_owned(),
+            target: sys_config.backup_target.as_ref().to_owned(),
+            ..Default::default()
+        };
+        let mut schedule = None;
+        let mut target = None;
+        if sys_config.backup_enable {
+            let s3_config = BackupS3Config {
+                endpoint: sys_config.backup_s3_endpoint.clone(),
+                region: sys_config.backup_s3_region.clone(),
+                bucket: sys_config.backup_s3_bucket.clone(),
+                prefix: sys_config.backup_s3_prefix.clone(),
+                access_key: sys_config.backup_s3_access_key.clone(),
+                secret_key: sys_config.backup_s3_secret_key.clone(),
+            };
+            match CronSchedule::parse(&sys_config.backup_cron).and_then(|v| {
+                build_backup_target(
+                    &sys_config.backup_target,
+                    &sys_config.backup_dir,
+                    s3_config,
+                    offset,
+                )
+                .map(|target| (v, target))
+            }) {
+                Ok((v, t)) => {
+                    schedule = Some(v);
+                    target = Some(t);
+                }
+                Err(err) => {
+                    log::error!("backup config is invalid, backup is disabled,{}", err);
+                    status.last_error = Some(err.to_string());
+                }
+            }
+        }
+        Self {
+            schedule,
+            target,
+            retain_count: sys_config.backup_retain_count,
+            retain_days: sys_config.backup_retain_days,
+            node_id: sys_config.raft_node_id,
+            offset,
+            status,
+            raft: None,
+            transfer_writer_manager: None,
+            metrics_manager: None,
+        }
+    }
+
+    fn is_leader(&self) -> bool {
+        self.raft
+            .as_ref()
+            .and_then(|e| e.upgrade())
+            .map(|raft| raft.metrics().borrow().current_leader == Some(self.node_id))
+            .unwrap_or(false)
+    }
+
+    fn schedule_next(&mut self, ctx: &mut Context<Self>) {
+        let schedule = if let Some(v) = &self.schedule {
+            v
+        } else {
+            return;
+        };
+        let now = now_millis_i64();
+        let local_now = chrono::DateTime::from_timestamp_millis(now)
+            .unwrap_or_default()
+            .with_timezone(&self.offset)
+            .naive_local();
+        let next_time = schedule
+            .next_after(local_now)
+            .and_then(|v| v.and_local_timezone(self.offset).single())
+            .map(|v| v.timestamp_millis());
+        self.status.next_time = next_time;
+        if let Some(next_time) = next_time {
+            let delay = (next_time - now).max(0) as u64;
+            ctx.run_later(Duration::from_millis(delay), |act, ctx| {
+                act.run_backup(ctx);
+                act.schedule_next(ctx);
+            });
+        } else {
+            log::warn!("backup cron expression has no next time");
+        }
+    }
+
+    fn run_backup(&mut self, ctx: &mut Context<Self>) {
+        if self.status.running || !self.is_leader() {
+            return;
+        }
+        let (target, writer_manager) =
+            match (self.target.clone(), self.transfer_writer_manager.clone()) {
+                (Some(target), Some(writer_manager)) => (target, writer_manager),
+                _ => return,
+            };
+        let start = now_millis();
+        self.status.running = true;
+        self.status.last_start_time = Some(start as i64);
+        let file_name = build_backup_file_name(
+            &chrono::DateTime::from_timestamp_millis(start as i64)
+                .unwrap_or_default()
+                .with_timezone(&self.offset)
+                .format("%Y%m%d%H%M%S")
+                .to_string(),
+        );
+        let retain_count = self.retain_count;
+        let retain_days = self.retain_days;
+        async move {
+            let result = match tokio::time::timeout(
+                BACKUP_TIMEOUT,
+                Self::do_backup(
+                    &target,
+                    &writer_manager,
+                    file_name,
+                    retain_count,
+                    retain_days,
+                ),
+            )
+            .await
+            {
+                Ok(v) => v,
+                Err(_) => Err(anyhow::anyhow!(
+                    "backup timeout after {} seconds",
+                    BACKUP_TIMEOUT.as_secs()
+                )),
+            };
+            (result, now_millis() - start)
+        }
+        .into_actor(self)
+        .map(|(result, duration), act, _ctx| {
+            act.status.running = false;
+            act.status.last_duration_ms = duration;
+            let count_key = if result.is_ok() {
+                MetricsKey::BackupSuccessCount
+            } else {
+                MetricsKey::BackupFailCount
+            };
+            if let Some(metrics_manager) = &act.metrics_manager {
+                metrics_manager.do_send(MetricsRequest::BatchRecord(vec![MetricsItem::new(
+                    count_key,
+                    MetricsRecord::CounterInc(1),
+                )]));
+            }
+            match result {
+                Ok(v) => {
+                    log::info!(
+                        "backup success,file:{},size:{},records:{}",
+                        &v.file_name,
+                        v.file_size,
+                        v.record_count
+                    );
+                    act.status.success_count += 1;
+                    act.status.last_success_time = Some(now_millis_i64());
+                    act.status.last_file = Some(v.file_name);
+                    act.status.last_file_size = v.file_size;
+                    act.status.last_record_count = v.record_count;
+                    act.status.last_error = None;
+                }
+                Err(err) => {
+                    log::error!("backup error,{}", err);
+                    act.status.fail_count += 1;
+                    act.status.last_error = Some(err.to_string());
+                }
+            }
+            act.record_metrics();
+        })
+        .spawn(ctx);
+    }
+
+    async fn do_backup(
+        target: &Arc<dyn BackupTarget>,
+        writer_manager: &Addr<TransferWriterManager>,
+        file_name: String,
+        retain_count: usize,
+        retain_days: u32,
+    ) -> anyhow::Result<BackupResult> {
+        let TransferManagerResponse::BackupFile(temp_file) = writer_manager
+            .send(TransferManagerAsyncRequest::Backup(
+                TransferBackupParam::all(),
+            ))
+            .await??;
+        let data = tokio::fs::read(&temp_file.path).await?;
+        drop(temp_file);
+        let record_count = Self::verify(data.clone())?;
+        let file_size = data.len() as u64;
+        target.upload(&file_name, data).await?;
+        let expired = select_expired_files(
+            target.list().await?,
+            retain_count,
+            retain_days,
+            now_millis_i64(),
+        );
+        for item in expired.iter().filter(|e| e.name != file_name) {
+            if let Err(err) = target.delete(&item.name).await {
+                log::warn!("delete expired backup {} error,{}", &item.name, err);
+            }
+        }
+        Ok(BackupResult {
+            file_name,
+            file_size,
+            record_count,
+        })
+    }
+
+    ///
+    /// 重新读取备份文件的全部记录，确认文件可用于导入
+    fn verify(data: Vec<u8>) -> anyhow::Result<u64> {
+        let mut reader = TransferReader::new(data)?;
+        let mut count = 0;
+        while reader.read_record()?.is_some() {
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn record_metrics(&self) {
+        let metrics_manager = if let Some(v) = &self.metrics_manager {
+            v
+        } else {
+            return;
+        };
+        let since_last_success = self
+            .status
+            .last_success_time
+            .map(|v| ((now_millis_i64() - v) / 1000) as f32)
+            .unwrap_or(-1f32);
+        metrics_manager.do_send(MetricsRequest::BatchRecord(vec![
+            MetricsItem::new(
+                MetricsKey::BackupLastDuration,
+                MetricsRecord::Gauge(self.status.last_duration_ms as f32),
+            ),
+            MetricsItem::new(
+                MetricsKey::BackupLastFileSize,
+                MetricsRecord::Gauge(self.status.last_file_size as f32),
+            ),
+            MetricsItem::new(
+                MetricsKey::BackupSinceLastSuccessSeconds,
+                MetricsRecord::Gauge(since_last_success),
+            ),
+        ]));
+    }
+
+    fn hb(&mut self, ctx: &mut Context<Self>) {
+        ctx.run_later(Duration::from_secs(60), |act, ctx| {
+            if act.status.success_count > 0 || act.status.fail_count > 0 {
+                act.record_metrics();
+            }
+            act.hb(ctx);
+        });
+    }
+}
+
+impl Actor for BackupManager {
+    type Context = Context<Self>;
+
+    fn started(&mut self, _ctx: &mut Self::Context) {
+        log::info!("BackupManager started");
+    }
+}
+
+impl Inject for BackupManager {
+    type Context = Context<Self>;
+
+    fn inject(
+        &mut self,
+        factory_data: FactoryData,
+        _factory: BeanFactory,
+        ctx: &mut Self::Context,
+    ) {
+        self.raft = factory_data
+            .get_bean::<NacosRaft>()
+            .map(|e| Arc::downgrade(&e));
+        self.transfer_writer_manager = factory_data.get_actor();
+        self.metrics_manager = factory_data.get_actor();
+        if self.schedule.is_some() {
+            self.schedule_next(ctx);
+            self.hb(ctx);
+        }
+    }
+}
+
+impl Handler<BackupManagerReq> for BackupManager {
+    type Result = anyhow::Result<BackupManagerResult>;
+
+    fn handle(&mut self, msg: BackupManagerReq, _ctx: &mut Self::Context) -> Self::Result {
+        match msg {
+            BackupManagerReq::QueryStatus => {
+                let mut status = self.status.clone();
+                status.leader = self.is_leader();
+                Ok(BackupManagerResult::Status(status))
+            }
+        }
+    }
+}
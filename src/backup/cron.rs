@@ -0,0 +1,167 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+
+///
+/// 5段cron表达式：`分 时 日 月 周`
+/// 每段支持`*`、`*/n`、`a`、`a-b`、`a-b/n`及逗号分隔的列表，周的取值0与7都表示周日
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// 日、周都指定时，满足其一即可(与crontab一致)
+    day_or_weekday: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> anyhow::Result<Self> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(anyhow::anyhow!(
+                "cron expression must have 5 fields: {}",
+                expr
+            ));
+        }
+        let mut weekdays = parse_field(fields[4], 0, 7)?;
+        if weekdays & (1 << 7) > 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)?,
+            days: parse_field(fields[2], 1, 31)?,
+            months: parse_field(fields[3], 1, 12)?,
+            weekdays,
+            day_or_weekday: fields[2] != "*" && fields[4] != "*",
+        })
+    }
+
+    ///
+    /// 计算指定时间之后的下一个触发时间(精确到分钟)
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut t = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        // 最多向后查找约5年，表达式如`0 0 31 2 *`永远不会触发
+        let end = t + Duration::days(366 * 5);
+        while t < end {
+            if !is_set(self.months, t.month()) {
+                let (year, month) = if t.month() == 12 {
+                    (t.year() + 1, 1)
+                } else {
+                    (t.year(), t.month() + 1)
+                };
+                t = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.match_day(&t) {
+                t = (t.date() + Duration::days(1)).and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !is_set(self.hours, t.hour()) {
+                t = t.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+            if !is_set(self.minutes, t.minute()) {
+                t += Duration::minutes(1);
+                continue;
+            }
+            return Some(t);
+        }
+        None
+    }
+
+    fn match_day(&self, t: &NaiveDateTime) -> bool {
+        let day = is_set(self.days, t.day());
+        let weekday = is_set(self.weekdays, t.weekday().num_days_from_sunday());
+        if self.day_or_weekday {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+}
+
+fn is_set(mask: u64, v: u32) -> bool {
+    mask & (1 << v) > 0
+}
+
+fn parse_field(field: &str, min: u32, max: u32) -> anyhow::Result<u64> {
+    let mut mask = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, parse_number(step)?),
+            None => (item, 1),
+        };
+        if step == 0 {
+            return Err(anyhow::anyhow!("cron step is invalid: {}", item));
+        }
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (parse_number(a)?, parse_number(b)?)
+        } else {
+            let v = parse_number(range)?;
+            // `a/n`表示从a开始到最大值
+            (v, if step > 1 { max } else { v })
+        };
+        if start < min || end > max || start > end {
+            return Err(anyhow::anyhow!("cron field is out of range: {}", item));
+        }
+        let mut v = start;
+        while v <= end {
+            mask |= 1 << v;
+            v += step;
+        }
+    }
+    Ok(mask)
+}
+
+fn parse_number(v: &str) -> anyhow::Result<u32> {
+    v.parse()
+        .map_err(|_| anyhow::anyhow!("cron field is invalid: {}", v))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(v: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(v, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn cron_next_time() {
+        let daily = CronSchedule::parse("0 3 * * *").unwrap();
+        assert_eq!(
+            daily.next_after(time("2024-01-31 03:00")),
+            Some(time("2024-02-01 03:00"))
+        );
+        let every = CronSchedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(
+            every.next_after(time("2024-01-01 10:50")),
+            Some(time("2024-01-01 11:00"))
+        );
+        // 2024-01-07为周日
+        let weekly = CronSchedule::parse("30 1 * * 7").unwrap();
+        assert_eq!(
+            weekly.next_after(time("2024-01-01 00:00")),
+            Some(time("2024-01-07 01:30"))
+        );
+        let leap = CronSchedule::parse("0 0 29 2 *").unwrap();
+        assert_eq!(
+            leap.next_after(time("2024-03-01 00:00")),
+            Some(time("2028-02-29 00:00"))
+        );
+        let never = CronSchedule::parse("0 0 31 2 *").unwrap();
+        assert_eq!(never.next_after(time("2024-01-01 00:00")), None);
+    }
+
+    #[test]
+    fn cron_parse_error() {
+        assert!(CronSchedule::parse("0 3 * *").is_err());
+        assert!(CronSchedule::parse("60 3 * * *").is_err());
+        assert!(CronSchedule::parse("*/0 3 * * *").is_err());
+        assert!(CronSchedule::parse("5-1 3 * * *").is_err());
+        assert!(CronSchedule::parse("0 1-5/2,23 1,15 * 1-5").is_ok());
+    }
+}
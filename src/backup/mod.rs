@@ -0,0 +1,4 @@
+pub mod core;
+pub mod cron;
+pub mod model;
+pub mod target;
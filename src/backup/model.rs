@@ -0,0 +1,147 @@
+use actix::Message;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// 备份文件名前缀，后接本地时间`yyyyMMddHHmmss`
+pub const BACKUP_FILE_PREFIX: &str = "rnacos_backup_";
+pub const BACKUP_FILE_SUFFIX: &str = ".data";
+
+#[derive(Debug, Clone, Default)]
+pub struct BackupS3Config {
+    pub endpoint: Arc<String>,
+    pub region: Arc<String>,
+    pub bucket: Arc<String>,
+    /// 对象key前缀
+    pub prefix: Arc<String>,
+    pub access_key: Arc<String>,
+    pub secret_key: Arc<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupFileInfo {
+    pub name: String,
+    /// 由文件名中的时间解析，单位毫秒
+    pub backup_time: i64,
+}
+
+///
+/// 定时备份运行状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupStatus {
+    pub enable: bool,
+    pub cron: String,
+    pub target: String,
+    /// 只有raft主节点执行定时备份
+    pub leader: bool,
+    pub running: bool,
+    pub next_time: Option<i64>,
+    pub last_start_time: Option<i64>,
+    pub last_success_time: Option<i64>,
+    pub last_file: Option<String>,
+    pub last_file_size: u64,
+    pub last_record_count: u64,
+    pub last_duration_ms: u64,
+    pub last_error: Option<String>,
+    pub success_count: u64,
+    pub fail_count: u64,
+}
+
+impl BackupStatus {
+    ///
+    /// 未开启或最近一次备份成功时为健康状态
+    pub fn is_healthy(&self) -> bool {
+        !self.enable || self.last_error.is_none()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BackupResult {
+    pub file_name: String,
+    pub file_size: u64,
+    pub record_count: u64,
+}
+
+#[derive(Message, Debug)]
+#[rtype(result = "anyhow::Result<BackupManagerResult>")]
+pub enum BackupManagerReq {
+    QueryStatus,
+}
+
+pub enum BackupManagerResult {
+    Status(BackupStatus),
+}
+
+pub fn build_backup_file_name(time_str: &str) -> String {
+    format!("{}{}{}", BACKUP_FILE_PREFIX, time_str, BACKUP_FILE_SUFFIX)
+}
+
+///
+/// 从备份文件名解析备份时间，不是备份文件时返回None
+pub fn parse_backup_file_name(name: &str, offset: &chrono::FixedOffset) -> Option<BackupFileInfo> {
+    let time_str = name
+        .strip_prefix(BACKUP_FILE_PREFIX)?
+        .strip_suffix(BACKUP_FILE_SUFFIX)?;
+    let time = chrono::NaiveDateTime::parse_from_str(time_str, "%Y%m%d%H%M%S").ok()?;
+    let backup_time = time
+        .and_local_timezone(*offset)
+        .single()?
+        .timestamp_millis();
+    Some(BackupFileInfo {
+        name: name.to_owned(),
+        backup_time,
+    })
+}
+
+///
+/// 按保留条数与保留天数计算需要删除的备份文件，0表示不限制
+pub fn select_expired_files(
+    mut files: Vec<BackupFileInfo>,
+    retain_count: usize,
+    retain_days: u32,
+    now: i64,
+) -> Vec<BackupFileInfo> {
+    files.sort_by_key(|e| std::cmp::Reverse(e.backup_time));
+    let min_time = now - retain_days as i64 * 24 * 3600 * 1000;
+    files
+        .into_iter()
+        .enumerate()
+        .filter(|(i, e)| {
+            (retain_count > 0 && *i >= retain_count)
+                || (retain_days > 0 && e.backup_time < min_time)
+        })
+        .map(|(_, e)| e)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backup_retention() {
+        let offset = chrono::FixedOffset::east_opt(8 * 3600).unwrap();
+        let names = [
+            "rnacos_backup_20240101030000.data",
+            "rnacos_backup_20240102030000.data",
+            "rnacos_backup_20240103030000.data",
+            "rnacos_backup_20240104030000.data",
+            "other.data",
+        ];
+        let files: Vec<BackupFileInfo> = names
+            .iter()
+            .filter_map(|e| parse_backup_file_name(e, &offset))
+            .collect();
+        assert_eq!(files.len(), 4);
+        let now = files[3].backup_time;
+        let expired = select_expired_files(files.clone(), 2, 0, now);
+        let mut expired: Vec<&str> = expired.iter().map(|e| e.name.as_str()).collect();
+        expired.sort();
+        assert_eq!(expired, vec![names[0], names[1]]);
+        let expired = select_expired_files(files.clone(), 0, 2, now);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].name, names[0]);
+        assert!(select_expired_files(files, 0, 0, now).is_empty());
+    }
+}
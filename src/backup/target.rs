@@ -0,0 +1,305 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{FixedOffset, Utc};
+use hmac::{Hmac, Mac};
+use regex::Regex;
+use sha2::{Digest, Sha256};
+
+use crate::backup::model::{parse_backup_file_name, BackupFileInfo, BackupS3Config};
+
+type HmacSha256 = Hmac<Sha256>;
+
+///
+/// 备份文件存储位置
+#[async_trait]
+pub trait BackupTarget: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn upload(&self, file_name: &str, data: Vec<u8>) -> anyhow::Result<()>;
+
+    /// 列出已有的备份文件，忽略非备份文件
+    async fn list(&self) -> anyhow::Result<Vec<BackupFileInfo>>;
+
+    async fn delete(&self, file_name: &str) -> anyhow::Result<()>;
+}
+
+pub struct LocalBackupTarget {
+    dir: PathBuf,
+    offset: FixedOffset,
+}
+
+impl LocalBackupTarget {
+    pub fn new(dir: &str, offset: FixedOffset) -> Self {
+        Self {
+            dir: PathBuf::from(dir),
+            offset,
+        }
+    }
+}
+
+#[async_trait]
+impl BackupTarget for LocalBackupTarget {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    async fn upload(&self, file_name: &str, data: Vec<u8>) -> anyhow::Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        // 先写临时文件再重命名，避免留下不完整的备份文件
+        let tmp_path = self.dir.join(format!(".{}.tmp", file_name));
+        tokio::fs::write(&tmp_path, &data).await?;
+        tokio::fs::rename(&tmp_path, self.dir.join(file_name)).await?;
+        Ok(())
+    }
+
+    async fn list(&self) -> anyhow::Result<Vec<BackupFileInfo>> {
+        let mut list = vec![];
+        if !self.dir.exists() {
+            return Ok(list);
+        }
+        let mut entries = tokio::fs::read_dir(&self.dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if let Some(info) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| parse_backup_file_name(name, &self.offset))
+            {
+                list.push(info);
+            }
+        }
+        Ok(list)
+    }
+
+    async fn delete(&self, file_name: &str) -> anyhow::Result<()> {
+        tokio::fs::remove_file(self.dir.join(file_name)).await?;
+        Ok(())
+    }
+}
+
+const S3_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// 单个请求(含上传备份文件)的超时时间
+const S3_REQUEST_TIMEOUT: Duration = Duration::from_secs(600);
+
+///
+/// S3兼容的对象存储，使用path-style地址与AWS Signature V4签名
+pub struct S3BackupTarget {
+    config: BackupS3Config,
+    client: reqwest::Client,
+    offset: FixedOffset,
+}
+
+impl S3BackupTarget {
+    pub fn new(config: BackupS3Config, offset: FixedOffset) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::builder()
+                .connect_timeout(S3_CONNECT_TIMEOUT)
+                .timeout(S3_REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            offset,
+        }
+    }
+
+    fn object_path(&self, file_name: &str) -> String {
+        format!(
+            "/{}/{}{}",
+            uri_encode(&self.config.bucket, true),
+            uri_encode(&self.config.prefix, false),
+            uri_encode(file_name, true)
+        )
+    }
+
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> anyhow::Result<reqwest::Response> {
+        let endpoint = self.config.endpoint.trim_end_matches('/');
+        let host = endpoint
+            .split_once("://")
+            .map_or(endpoint, |(_, v)| v)
+            .to_owned();
+        let mut query: Vec<(String, String)> = query
+            .iter()
+            .map(|(k, v)| (uri_encode(k, true), uri_encode(v, true)))
+            .collect();
+        query.sort();
+        let query_str = query
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("&");
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = to_hex(&Sha256::digest(&body));
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\nhost;x-amz-content-sha256;x-amz-date\n{}",
+            method.as_str(),
+            path,
+            &query_str,
+            &host,
+            &payload_hash,
+            &amz_date,
+            &payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", &date, &self.config.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            &amz_date,
+            &scope,
+            to_hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let mut key = hmac_sha256(
+            format!("AWS4{}", &self.config.secret_key).as_bytes(),
+            date.as_bytes(),
+        );
+        for v in [self.config.region.as_str(), "s3", "aws4_request"] {
+            key = hmac_sha256(&key, v.as_bytes());
+        }
+        let signature = to_hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={}",
+            &self.config.access_key, &scope, &signature
+        );
+        let url = if query_str.is_empty() {
+            format!("{}{}", endpoint, path)
+        } else {
+            format!("{}{}?{}", endpoint, path, &query_str)
+        };
+        let res = self
+            .client
+            .request(method, url)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("authorization", authorization)
+            .body(body)
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            let status = res.status();
+            let text = res.text().await.unwrap_or_default();
+            return Err(anyhow::anyhow!("s3 request error,{},{}", status, text));
+        }
+        Ok(res)
+    }
+}
+
+#[async_trait]
+impl BackupTarget for S3BackupTarget {
+    fn name(&self) -> &'static str {
+        "s3"
+    }
+
+    async fn upload(&self, file_name: &str, data: Vec<u8>) -> anyhow::Result<()> {
+        let path = self.object_path(file_name);
+        self.send(reqwest::Method::PUT, &path, &[], data).await?;
+        Ok(())
+    }
+
+    async fn list(&self) -> anyhow::Result<Vec<BackupFileInfo>> {
+        lazy_static::lazy_static! {
+            static ref KEY_REGEX: Regex = Regex::new(r"<Key>([^<]*)</Key>").unwrap();
+        }
+        let path = format!("/{}", uri_encode(&self.config.bucket, true));
+        let mut list = vec![];
+        let mut token: Option<String> = None;
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", self.config.prefix.as_str())];
+            if let Some(v) = &token {
+                query.push(("continuation-token", v.as_str()));
+            }
+            let text = self
+                .send(reqwest::Method::GET, &path, &query, vec![])
+                .await?
+                .text()
+                .await?;
+            for cap in KEY_REGEX.captures_iter(&text) {
+                let key = xml_unescape(&cap[1]);
+                let name = key
+                    .strip_prefix(self.config.prefix.as_str())
+                    .unwrap_or(&key);
+                if let Some(info) = parse_backup_file_name(name, &self.offset) {
+                    list.push(info);
+                }
+            }
+            token = xml_tag_value(&text, "NextContinuationToken");
+            if token.is_none() || xml_tag_value(&text, "IsTruncated").as_deref() != Some("true") {
+                break;
+            }
+        }
+        Ok(list)
+    }
+
+    async fn delete(&self, file_name: &str) -> anyhow::Result<()> {
+        let path = self.object_path(file_name);
+        self.send(reqwest::Method::DELETE, &path, &[], vec![])
+            .await?;
+        Ok(())
+    }
+}
+
+pub fn build_backup_target(
+    target: &str,
+    dir: &str,
+    s3_config: BackupS3Config,
+    offset: FixedOffset,
+) -> anyhow::Result<Arc<dyn BackupTarget>> {
+    match target {
+        "local" => Ok(Arc::new(LocalBackupTarget::new(dir, offset))),
+        "s3" => {
+            if s3_config.endpoint.is_empty() || s3_config.bucket.is_empty() {
+                return Err(anyhow::anyhow!("backup s3 endpoint or bucket is empty"));
+            }
+            Ok(Arc::new(S3BackupTarget::new(s3_config, offset)))
+        }
+        _ => Err(anyhow::anyhow!("unknown backup target: {}", target)),
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC can take key of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+///
+/// 按SigV4规则编码，非保留字符保持原样；encode_slash为false时保留`/`
+fn uri_encode(v: &str, encode_slash: bool) -> String {
+    let mut result = String::with_capacity(v.len());
+    for b in v.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                result.push(b as char)
+            }
+            b'/' if !encode_slash => result.push('/'),
+            _ => result.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    result
+}
+
+fn xml_tag_value(text: &str, tag: &str) -> Option<String> {
+    let start_tag = format!("<{}>", tag);
+    let start = text.find(&start_tag)? + start_tag.len();
+    let end = text[start..].find(&format!("</{}>", tag))? + start;
+    Some(xml_unescape(&text[start..end]))
+}
+
+fn xml_unescape(v: &str) -> String {
+    v.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
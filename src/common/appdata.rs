@@ -1,3 +1,5 @@
+use crate::audit::core::AuditLogManager;
+use crate::backup::core::BackupManager;
 use crate::cache::core::DirectCacheManager;
 use crate::common::AppSysConfig;
 use crate::config::core::ConfigActor;
@@ -24,7 +26,11 @@ use crate::sequence::core::SequenceDbManager;
 use crate::sequence::SequenceManager;
 use crate::transfer::reader::TransferImportManager;
 use crate::transfer::writer::TransferWriterManager;
+use crate::user::access_key::AccessKeyManager;
+use crate::user::jwt_auth::JwtAuthManager;
+use crate::user::role::RoleManager;
 use crate::user::UserManager;
+use crate::webhook::core::WebhookManager;
 use actix::Addr;
 use bean_factory::FactoryData;
 use chrono::FixedOffset;
@@ -62,5 +68,11 @@ pub struct AppShareData {
     pub sequence_manager: Addr<SequenceManager>,
     pub mcp_manager: Addr<McpManager>,
     pub sse_stream_manager: Addr<SseStreamManager>,
+    pub audit_log_manager: Addr<AuditLogManager>,
+    pub webhook_manager: Addr<WebhookManager>,
+    pub role_manager: Addr<RoleManager>,
+    pub access_key_manager: Addr<AccessKeyManager>,
+    pub jwt_auth_manager: Option<Arc<JwtAuthManager>>,
+    pub backup_manager: Addr<BackupManager>,
     pub common_client: reqwest::Client,
 }
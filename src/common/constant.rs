@@ -21,6 +21,8 @@ pub const GRPC_HEAD_KEY_SUB_NAME: &str = "sub_name";
 
 lazy_static::lazy_static! {
     pub static ref CONFIG_TREE_NAME: Arc<String> =  Arc::new("T_CONFIG".to_string());
+    /// 配置历史归档表
+    pub static ref CONFIG_HISTORY_TREE_NAME: Arc<String> =  Arc::new("T_CONFIG_HISTORY".to_string());
     pub static ref SEQUENCE_TREE_NAME: Arc<String> =  Arc::new("T_SEQUENCE".to_string());
     pub static ref USER_TREE_NAME: Arc<String> =  Arc::new("T_USER".to_string());
     /// 旧缓存表
@@ -37,4 +39,16 @@ lazy_static::lazy_static! {
     pub static ref DEFAULT_NAMESPACE_ARC_STRING: Arc<String> = Arc::new("".to_string());
     pub static ref EMPTY_CLIENT_VERSION: Arc<ClientVersion> = Arc::new(ClientVersion::default());
     pub static ref NAMING_INSTANCE_TABLE: Arc<String> = Arc::new("T_NAMING_INSTANCE".to_string());
+    /// 变更操作审计日志表
+    pub static ref AUDIT_LOG_TABLE_NAME: Arc<String> = Arc::new("T_AUDIT_LOG".to_string());
+    /// webhook订阅配置表
+    pub static ref WEBHOOK_TABLE_NAME: Arc<String> = Arc::new("T_WEBHOOK".to_string());
+    /// webhook推送失败的死信表
+    pub static ref WEBHOOK_DEAD_LETTER_TABLE_NAME: Arc<String> = Arc::new("T_WEBHOOK_DEAD_LETTER".to_string());
+    /// 自定义角色表
+    pub static ref ROLE_TABLE_NAME: Arc<String> = Arc::new("T_ROLE".to_string());
+    /// 访问密钥表
+    pub static ref ACCESS_KEY_TABLE_NAME: Arc<String> = Arc::new("T_ACCESS_KEY".to_string());
+    /// 访问密钥最近使用时间表
+    pub static ref ACCESS_KEY_USAGE_TABLE_NAME: Arc<String> = Arc::new("T_ACCESS_KEY_USAGE".to_string());
 }
@@ -15,6 +15,15 @@ pub fn encode_base64(data: &[u8]) -> String {
 /// 加密
 /// key,iv长度需要是16的倍数
 pub fn encrypt_aes128(key: &str, iv: &str, plain: &[u8]) -> anyhow::Result<Vec<u8>> {
+    encrypt_aes128_by_bytes(key.as_bytes(), iv, plain)
+}
+
+/// 加密
+/// key为16字节的原始密钥
+pub fn encrypt_aes128_by_bytes(key: &[u8], iv: &str, plain: &[u8]) -> anyhow::Result<Vec<u8>> {
+    if key.len() != 16 || iv.len() != 16 {
+        return Err(anyhow::anyhow!("aes128 key and iv length must be 16"));
+    }
     let pt_len = plain.len();
     let buf_len = if pt_len % 48 == 0 {
         pt_len
@@ -23,7 +32,7 @@ pub fn encrypt_aes128(key: &str, iv: &str, plain: &[u8]) -> anyhow::Result<Vec<u
     };
     let mut buf = vec![0u8; buf_len];
     (buf[..pt_len]).copy_from_slice(plain);
-    match Aes128CbcEnc::new(key.as_bytes().into(), iv.as_bytes().into())
+    match Aes128CbcEnc::new(key.into(), iv.as_bytes().into())
         .encrypt_padded_b2b_mut::<Pkcs7>(plain, &mut buf)
     {
         Ok(ct) => Ok(ct.to_vec()),
@@ -34,6 +43,15 @@ pub fn encrypt_aes128(key: &str, iv: &str, plain: &[u8]) -> anyhow::Result<Vec<u
 /// 解密
 /// key,iv长度需要是16的倍数
 pub fn decrypt_aes128(key: &str, iv: &str, cipher: &[u8]) -> anyhow::Result<Vec<u8>> {
+    decrypt_aes128_by_bytes(key.as_bytes(), iv, cipher)
+}
+
+/// 解密
+/// key为16字节的原始密钥
+pub fn decrypt_aes128_by_bytes(key: &[u8], iv: &str, cipher: &[u8]) -> anyhow::Result<Vec<u8>> {
+    if key.len() != 16 || iv.len() != 16 {
+        return Err(anyhow::anyhow!("aes128 key and iv length must be 16"));
+    }
     let cipher_len = cipher.len();
     let buf_len = if cipher_len % 48 == 0 {
         cipher_len
@@ -43,7 +61,7 @@ pub fn decrypt_aes128(key: &str, iv: &str, cipher: &[u8]) -> anyhow::Result<Vec<
     let mut buf = vec![0u8; buf_len];
     (buf[..cipher_len]).copy_from_slice(cipher);
 
-    match Aes128CbcDec::new(key.as_bytes().into(), iv.as_bytes().into())
+    match Aes128CbcDec::new(key.into(), iv.as_bytes().into())
         .decrypt_padded_b2b_mut::<Pkcs7>(cipher, &mut buf)
     {
         Ok(pt) => Ok(pt.to_vec()),
@@ -27,6 +27,14 @@ pub fn now_second_u32() -> u32 {
     since_epoch().as_secs() as u32
 }
 
+pub fn get_timestamp_str(millis: i64, offset: &FixedOffset) -> String {
+    DateTime::<Utc>::from_timestamp_millis(millis)
+        .unwrap_or_default()
+        .with_timezone(offset)
+        .format(DATETIME_TIMESTAMP_FMT)
+        .to_string()
+}
+
 pub fn get_now_timestamp_str(offset: &FixedOffset) -> String {
     DateTime::<Utc>::from(SystemTime::now())
         .with_timezone(offset)
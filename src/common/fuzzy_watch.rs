@@ -0,0 +1,251 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// nacos3 模糊监听协议常量
+pub const WATCH_TYPE_WATCH: &str = "WATCH";
+pub const WATCH_TYPE_CANCEL_WATCH: &str = "CANCEL_WATCH";
+
+pub const FUZZY_WATCH_INIT_NOTIFY: &str = "FUZZY_WATCH_INIT_NOTIFY";
+pub const FINISH_FUZZY_WATCH_INIT_NOTIFY: &str = "FINISH_FUZZY_WATCH_INIT_NOTIFY";
+pub const FUZZY_WATCH_DIFF_SYNC_NOTIFY: &str = "FUZZY_WATCH_DIFF_SYNC_NOTIFY";
+pub const FUZZY_WATCH_RESOURCE_CHANGED: &str = "FUZZY_WATCH_RESOURCE_CHANGED";
+
+pub const ADD_CONFIG: &str = "ADD_CONFIG";
+pub const DELETE_CONFIG: &str = "DELETE_CONFIG";
+pub const CONFIG_CHANGED: &str = "CONFIG_CHANGED";
+
+pub const ADD_SERVICE: &str = "ADD_SERVICE";
+pub const DELETE_SERVICE: &str = "DELETE_SERVICE";
+pub const INSTANCE_CHANGED: &str = "INSTANCE_CHANGED";
+
+/// 同步通知每批的资源数量
+pub const FUZZY_WATCH_SYNC_BATCH_SIZE: usize = 50;
+/// 单个客户端最多可监听的模式数量
+pub const FUZZY_WATCH_MAX_PATTERN_COUNT: usize = 50;
+
+const NAMESPACE_SPLITTER: &str = ">>";
+const GROUP_SPLITTER: &str = "@@";
+const DEFAULT_NAMESPACE: &str = "public";
+
+///
+/// 模糊监听请求参数
+#[derive(Debug, Clone, Default)]
+pub struct FuzzyWatchParam {
+    pub pattern: Arc<String>,
+    pub received_keys: HashSet<String>,
+    pub initializing: bool,
+    pub watch: bool,
+}
+
+///
+/// 模糊监听模式，格式为 `{namespace}>>{group}@@{resource}`，group与resource支持`*`通配符
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyWatchPattern {
+    pub pattern: Arc<String>,
+    /// 模式中的命名空间原始值，通知时按这个值返回资源key
+    pub namespace: Arc<String>,
+    pub group: String,
+    pub resource: String,
+}
+
+impl FuzzyWatchPattern {
+    pub fn parse(pattern: Arc<String>) -> Option<Self> {
+        let (namespace, other) = pattern.split_once(NAMESPACE_SPLITTER)?;
+        let (group, resource) = other.split_once(GROUP_SPLITTER)?;
+        if group.is_empty() || resource.is_empty() {
+            return None;
+        }
+        Some(Self {
+            namespace: Arc::new(namespace.to_owned()),
+            group: group.to_owned(),
+            resource: resource.to_owned(),
+            pattern,
+        })
+    }
+
+    pub fn is_match(&self, namespace: &str, group: &str, resource: &str) -> bool {
+        is_same_namespace(&self.namespace, namespace)
+            && wildcard_match(&self.group, group)
+            && wildcard_match(&self.resource, resource)
+    }
+}
+
+/// 默认命名空间有空值与public两种写法
+fn is_same_namespace(a: &str, b: &str) -> bool {
+    a == b || (is_default_namespace(a) && is_default_namespace(b))
+}
+
+fn is_default_namespace(v: &str) -> bool {
+    v.is_empty() || v == DEFAULT_NAMESPACE
+}
+
+/// `*` 匹配任意长度字符
+pub fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    if !value.starts_with(first) {
+        return false;
+    }
+    let mut rest = &value[first.len()..];
+    let parts: Vec<&str> = parts.collect();
+    if parts.is_empty() {
+        return rest.is_empty();
+    }
+    let last = parts[parts.len() - 1];
+    for part in &parts[..parts.len() - 1] {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+///
+/// 模糊监听模式索引，维护模式与客户端的对应关系
+#[derive(Default)]
+pub struct FuzzyWatchIndex {
+    patterns: HashMap<Arc<String>, (FuzzyWatchPattern, HashSet<Arc<String>>)>,
+    client_patterns: HashMap<Arc<String>, HashSet<Arc<String>>>,
+}
+
+impl FuzzyWatchIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_watch(
+        &mut self,
+        client_id: Arc<String>,
+        pattern: FuzzyWatchPattern,
+    ) -> anyhow::Result<()> {
+        let client_patterns = self.client_patterns.entry(client_id.clone()).or_default();
+        if !client_patterns.contains(&pattern.pattern)
+            && client_patterns.len() >= FUZZY_WATCH_MAX_PATTERN_COUNT
+        {
+            return Err(anyhow::anyhow!(
+                "fuzzy watch pattern count over limit {}",
+                FUZZY_WATCH_MAX_PATTERN_COUNT
+            ));
+        }
+        client_patterns.insert(pattern.pattern.clone());
+        self.patterns
+            .entry(pattern.pattern.clone())
+            .or_insert_with(|| (pattern, HashSet::new()))
+            .1
+            .insert(client_id);
+        Ok(())
+    }
+
+    pub fn remove_watch(&mut self, client_id: &Arc<String>, pattern: &Arc<String>) {
+        if let Some(set) = self.client_patterns.get_mut(client_id) {
+            set.remove(pattern);
+            if set.is_empty() {
+                self.client_patterns.remove(client_id);
+            }
+        }
+        self.remove_pattern_client(pattern, client_id);
+    }
+
+    pub fn remove_client(&mut self, client_id: &Arc<String>) {
+        if let Some(set) = self.client_patterns.remove(client_id) {
+            for pattern in &set {
+                self.remove_pattern_client(pattern, client_id);
+            }
+        }
+    }
+
+    fn remove_pattern_client(&mut self, pattern: &Arc<String>, client_id: &Arc<String>) {
+        if let Some((_, clients)) = self.patterns.get_mut(pattern) {
+            clients.remove(client_id);
+            if clients.is_empty() {
+                self.patterns.remove(pattern);
+            }
+        }
+    }
+
+    ///
+    /// 查询匹配资源的客户端，按模式中的命名空间原始值分组
+    pub fn match_clients(
+        &self,
+        namespace: &str,
+        group: &str,
+        resource: &str,
+    ) -> HashMap<Arc<String>, HashSet<Arc<String>>> {
+        let mut result: HashMap<Arc<String>, HashSet<Arc<String>>> = HashMap::new();
+        for (pattern, clients) in self.patterns.values() {
+            if pattern.is_match(namespace, group, resource) {
+                result
+                    .entry(pattern.namespace.clone())
+                    .or_default()
+                    .extend(clients.iter().cloned());
+            }
+        }
+        result
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    pub fn get_pattern_size(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn get_client_size(&self) -> usize {
+        self.client_patterns.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcard_match_test() {
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match("*", "abc"));
+        assert!(wildcard_match("routes-*", "routes-a"));
+        assert!(wildcard_match("routes-*", "routes-"));
+        assert!(!wildcard_match("routes-*", "route-a"));
+        assert!(wildcard_match("*-pay", "order-pay"));
+        assert!(wildcard_match("a*b*c", "a1b2c"));
+        assert!(!wildcard_match("a*b*c", "a1c2b"));
+        assert!(!wildcard_match("aa*aa", "aaa"));
+        assert!(wildcard_match("pay", "pay"));
+        assert!(!wildcard_match("pay", "pay1"));
+    }
+
+    #[test]
+    fn pattern_match_test() {
+        let pattern = FuzzyWatchPattern::parse(Arc::new("public>>routes-*@@*".to_owned())).unwrap();
+        assert!(pattern.is_match("", "routes-a", "app.yaml"));
+        assert!(pattern.is_match("public", "routes-b", "x"));
+        assert!(!pattern.is_match("dev", "routes-a", "x"));
+        assert!(!pattern.is_match("", "DEFAULT_GROUP", "x"));
+        assert!(FuzzyWatchPattern::parse(Arc::new("public@@a".to_owned())).is_none());
+        assert!(FuzzyWatchPattern::parse(Arc::new(">>@@a".to_owned())).is_none());
+    }
+
+    #[test]
+    fn index_test() {
+        let mut index = FuzzyWatchIndex::new();
+        let c1 = Arc::new("c1".to_owned());
+        let c2 = Arc::new("c2".to_owned());
+        let p1 = FuzzyWatchPattern::parse(Arc::new(">>DEFAULT_GROUP@@pay-*".to_owned())).unwrap();
+        let p2 = FuzzyWatchPattern::parse(Arc::new("public>>*@@pay-order".to_owned())).unwrap();
+        index.add_watch(c1.clone(), p1.clone()).unwrap();
+        index.add_watch(c2.clone(), p2.clone()).unwrap();
+        let clients = index.match_clients("public", "DEFAULT_GROUP", "pay-order");
+        assert_eq!(clients.len(), 2);
+        assert_eq!(
+            clients.get(&Arc::new("public".to_owned())).unwrap().len(),
+            1
+        );
+        index.remove_watch(&c1, &p1.pattern);
+        assert_eq!(index.get_pattern_size(), 1);
+        index.remove_client(&c2);
+        assert!(index.is_empty());
+        assert_eq!(index.get_client_size(), 0);
+    }
+}
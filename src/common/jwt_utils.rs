@@ -0,0 +1,336 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::RwLock;
+
+use base64::{engine::general_purpose, Engine};
+use reqwest::Client;
+use ring::signature;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// 时间校验允许的时钟偏差，单位秒
+pub const CLOCK_SKEW_SECONDS: i64 = 60;
+/// 重新加载公钥的最小间隔，单位秒，避免无效token频繁触发拉取
+const JWKS_MIN_REFRESH_SECONDS: i64 = 10;
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Jwk {
+    pub kty: String,
+    pub kid: Option<String>,
+    pub alg: Option<String>,
+    #[serde(rename = "use")]
+    pub key_use: Option<String>,
+    pub n: String,
+    pub e: String,
+    pub crv: String,
+    pub x: String,
+    pub y: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct JwkSet {
+    #[serde(default)]
+    pub keys: Vec<Jwk>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JwtHeader {
+    alg: String,
+    #[serde(default)]
+    kid: Option<String>,
+}
+
+///
+/// 公钥来源，`http://`或`https://`开头的按url拉取，其它按本地文件读取
+#[derive(Clone, Debug)]
+pub enum JwksSource {
+    Url(String),
+    File(String),
+}
+
+impl JwksSource {
+    pub fn parse(value: &str) -> Self {
+        if value.starts_with("http://") || value.starts_with("https://") {
+            Self::Url(value.to_owned())
+        } else {
+            Self::File(value.to_owned())
+        }
+    }
+
+    async fn load(&self, http_client: &Client) -> anyhow::Result<Vec<Jwk>> {
+        let jwks: JwkSet = match self {
+            Self::Url(url) => {
+                http_client
+                    .get(url)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?
+            }
+            Self::File(path) => serde_json::from_slice(&tokio::fs::read(path).await?)?,
+        };
+        Ok(jwks.keys)
+    }
+}
+
+///
+/// JWT签名公钥集合；
+/// 缓存中找不到可用公钥时重新加载，以支持签发方轮换密钥
+pub struct JwksKeySet {
+    source: JwksSource,
+    keys: RwLock<Vec<Jwk>>,
+    last_load_time: AtomicI64,
+}
+
+impl JwksKeySet {
+    pub fn new(source: JwksSource) -> Self {
+        Self {
+            source,
+            keys: RwLock::new(vec![]),
+            last_load_time: AtomicI64::new(0),
+        }
+    }
+
+    pub async fn load(source: JwksSource, http_client: &Client) -> anyhow::Result<Self> {
+        let key_set = Self::new(source);
+        key_set.reload(http_client).await?;
+        Ok(key_set)
+    }
+
+    pub async fn reload(&self, http_client: &Client) -> anyhow::Result<()> {
+        let keys = self.source.load(http_client).await?;
+        self.last_load_time
+            .store(crate::now_second_i32() as i64, Ordering::Relaxed);
+        if let Ok(mut cache) = self.keys.write() {
+            *cache = keys;
+        }
+        Ok(())
+    }
+
+    ///
+    /// 校验JWT签名，返回未校验的claims
+    pub async fn verify(
+        &self,
+        http_client: &Client,
+        token: &str,
+    ) -> anyhow::Result<Map<String, Value>> {
+        let parts: Vec<&str> = token.split('.').collect();
+        if parts.len() != 3 {
+            return Err(anyhow::anyhow!("jwt format is invalid"));
+        }
+        let header: JwtHeader = serde_json::from_slice(&decode_base64_url(parts[0])?)?;
+        let signing_input = &token[..parts[0].len() + 1 + parts[1].len()];
+        let signature = decode_base64_url(parts[2])?;
+        let verified = self.find_key(&header).is_some_and(|key| {
+            verify_signature(&header.alg, &key, signing_input.as_bytes(), &signature).is_ok()
+        });
+        if !verified {
+            // kid不在缓存中或签名校验不通过时，重新加载公钥后再校验一次
+            if !self.try_mark_reload() {
+                return Err(anyhow::anyhow!("jwt signature is invalid"));
+            }
+            self.reload(http_client).await?;
+            let key = self
+                .find_key(&header)
+                .ok_or_else(|| anyhow::anyhow!("jwt signing key not found"))?;
+            verify_signature(&header.alg, &key, signing_input.as_bytes(), &signature)?;
+        }
+        Ok(serde_json::from_slice(&decode_base64_url(parts[1])?)?)
+    }
+
+    fn try_mark_reload(&self) -> bool {
+        let now = crate::now_second_i32() as i64;
+        let last = self.last_load_time.load(Ordering::Relaxed);
+        now - last >= JWKS_MIN_REFRESH_SECONDS
+            && self
+                .last_load_time
+                .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+    }
+
+    fn find_key(&self, header: &JwtHeader) -> Option<Jwk> {
+        let keys = self.keys.read().ok()?;
+        keys.iter()
+            .filter(|e| e.key_use.as_deref().is_none_or(|v| v == "sig"))
+            .filter(|e| e.alg.as_deref().is_none_or(|v| v == header.alg))
+            .find(|e| match &header.kid {
+                Some(kid) => e.kid.as_ref() == Some(kid),
+                None => true,
+            })
+            .cloned()
+    }
+}
+
+///
+/// 由三段base64url组成的token视为JWT
+pub fn is_jwt_format(token: &str) -> bool {
+    let mut parts = token.split('.');
+    parts.next().is_some_and(|v| v.starts_with("eyJ")) && parts.count() == 2
+}
+
+pub fn is_same_issuer(a: &str, b: &str) -> bool {
+    a.trim_end_matches('/') == b.trim_end_matches('/')
+}
+
+pub fn decode_base64_url(v: &str) -> anyhow::Result<Vec<u8>> {
+    Ok(general_purpose::URL_SAFE_NO_PAD.decode(v.trim_end_matches('='))?)
+}
+
+pub fn verify_signature(alg: &str, key: &Jwk, message: &[u8], sig: &[u8]) -> anyhow::Result<()> {
+    let result = match (alg, key.kty.as_str()) {
+        ("RS256" | "RS384" | "RS512" | "PS256" | "PS384" | "PS512", "RSA") => {
+            let params: &signature::RsaParameters = match alg {
+                "RS256" => &signature::RSA_PKCS1_2048_8192_SHA256,
+                "RS384" => &signature::RSA_PKCS1_2048_8192_SHA384,
+                "RS512" => &signature::RSA_PKCS1_2048_8192_SHA512,
+                "PS256" => &signature::RSA_PSS_2048_8192_SHA256,
+                "PS384" => &signature::RSA_PSS_2048_8192_SHA384,
+                _ => &signature::RSA_PSS_2048_8192_SHA512,
+            };
+            let n = decode_base64_url(&key.n)?;
+            let e = decode_base64_url(&key.e)?;
+            signature::RsaPublicKeyComponents { n: &n, e: &e }.verify(params, message, sig)
+        }
+        ("ES256" | "ES384", "EC") => {
+            let params = match (alg, key.crv.as_str()) {
+                ("ES256", "P-256") => &signature::ECDSA_P256_SHA256_FIXED,
+                ("ES384", "P-384") => &signature::ECDSA_P384_SHA384_FIXED,
+                _ => return Err(anyhow::anyhow!("jwt curve is not supported")),
+            };
+            let mut point = vec![0x04u8];
+            point.extend(decode_base64_url(&key.x)?);
+            point.extend(decode_base64_url(&key.y)?);
+            signature::UnparsedPublicKey::new(params, point).verify(message, sig)
+        }
+        _ => {
+            return Err(anyhow::anyhow!(
+                "jwt signing algorithm is not supported: {}",
+                alg
+            ))
+        }
+    };
+    result.map_err(|_| anyhow::anyhow!("jwt signature is invalid"))
+}
+
+///
+/// 校验iss、aud、exp、iat等标准claims；audience必须配置，nonce为None时不校验nonce
+pub fn validate_claims(
+    claims: &Map<String, Value>,
+    issuer: &str,
+    audience: &str,
+    nonce: Option<&str>,
+    now: i64,
+) -> anyhow::Result<()> {
+    let iss = claims
+        .get("iss")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    if !is_same_issuer(iss, issuer) {
+        return Err(anyhow::anyhow!("jwt issuer is invalid: {}", iss));
+    }
+    if audience.is_empty() {
+        return Err(anyhow::anyhow!("jwt audience is not configured"));
+    }
+    let audiences: Vec<&str> = match claims.get("aud") {
+        Some(Value::String(v)) => vec![v.as_str()],
+        Some(Value::Array(list)) => list.iter().filter_map(|v| v.as_str()).collect(),
+        _ => vec![],
+    };
+    if !audiences.contains(&audience) {
+        return Err(anyhow::anyhow!("jwt audience is invalid"));
+    }
+    if audiences.len() > 1 {
+        if let Some(azp) = claims.get("azp").and_then(|v| v.as_str()) {
+            if azp != audience {
+                return Err(anyhow::anyhow!("jwt azp is invalid"));
+            }
+        }
+    }
+    let exp = claims
+        .get("exp")
+        .and_then(|v| v.as_i64())
+        .ok_or_else(|| anyhow::anyhow!("jwt exp is missing"))?;
+    if exp + CLOCK_SKEW_SECONDS < now {
+        return Err(anyhow::anyhow!("jwt is expired"));
+    }
+    if let Some(nbf) = claims.get("nbf").and_then(|v| v.as_i64()) {
+        if nbf > now + CLOCK_SKEW_SECONDS {
+            return Err(anyhow::anyhow!("jwt is not yet valid"));
+        }
+    }
+    if let Some(iat) = claims.get("iat").and_then(|v| v.as_i64()) {
+        if iat > now + CLOCK_SKEW_SECONDS {
+            return Err(anyhow::anyhow!("jwt iat is in the future"));
+        }
+    }
+    if let Some(nonce) = nonce {
+        if claims.get("nonce").and_then(|v| v.as_str()) != Some(nonce) {
+            return Err(anyhow::anyhow!("jwt nonce is invalid"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::signature::KeyPair;
+
+    fn build_claims(now: i64) -> Map<String, Value> {
+        serde_json::json!({
+            "iss": "https://idp.example.com/",
+            "aud": ["rnacos", "other"],
+            "azp": "rnacos",
+            "exp": now + 300,
+            "iat": now,
+            "nonce": "n1",
+            "sub": "u1",
+        })
+        .as_object()
+        .cloned()
+        .unwrap()
+    }
+
+    #[test]
+    fn jwt_claims_validate() {
+        let now = 1_700_000_000;
+        let claims = build_claims(now);
+        let issuer = "https://idp.example.com";
+        assert!(validate_claims(&claims, issuer, "rnacos", Some("n1"), now).is_ok());
+        assert!(validate_claims(&claims, issuer, "", None, now).is_err());
+        assert!(validate_claims(&claims, issuer, "rnacos", Some("n2"), now).is_err());
+        assert!(validate_claims(&claims, issuer, "other", None, now).is_err());
+        assert!(validate_claims(&claims, "https://evil.example.com", "rnacos", None, now).is_err());
+        assert!(validate_claims(&claims, issuer, "rnacos", None, now + 400).is_err());
+    }
+
+    #[test]
+    fn jwt_es256_signature() {
+        let rng = ring::rand::SystemRandom::new();
+        let alg = &signature::ECDSA_P256_SHA256_FIXED_SIGNING;
+        let pkcs8 = signature::EcdsaKeyPair::generate_pkcs8(alg, &rng).unwrap();
+        let key_pair = signature::EcdsaKeyPair::from_pkcs8(alg, pkcs8.as_ref(), &rng).unwrap();
+        let public_key = key_pair.public_key().as_ref();
+        let jwk = Jwk {
+            kty: "EC".to_owned(),
+            crv: "P-256".to_owned(),
+            x: general_purpose::URL_SAFE_NO_PAD.encode(&public_key[1..33]),
+            y: general_purpose::URL_SAFE_NO_PAD.encode(&public_key[33..]),
+            ..Default::default()
+        };
+        let message = b"header.payload";
+        let sig = key_pair.sign(&rng, message).unwrap();
+        assert!(verify_signature("ES256", &jwk, message, sig.as_ref()).is_ok());
+        assert!(verify_signature("ES256", &jwk, b"header.other", sig.as_ref()).is_err());
+        assert!(verify_signature("none", &jwk, message, sig.as_ref()).is_err());
+        assert!(verify_signature("HS256", &jwk, message, sig.as_ref()).is_err());
+    }
+
+    #[test]
+    fn jwt_format_check() {
+        assert!(is_jwt_format("eyJhbGciOiJFUzI1NiJ9.eyJzdWIiOiJ1MSJ9.c2ln"));
+        assert!(!is_jwt_format("rak_0123456789abcdef.secret"));
+        assert!(!is_jwt_format("0123456789abcdef0123456789abcdef"));
+    }
+}
@@ -54,6 +54,17 @@ macro_rules! user_namespace_privilege {
     }};
 }
 
+///
+/// 自定义角色授权的列表数据范围，为None时不限制
+#[macro_export]
+macro_rules! user_scope_filter {
+    ($req:expr,$resource:expr,$action:expr) => {{
+        $req.extensions()
+            .get::<std::sync::Arc<$crate::user::permission::UserPermission>>()
+            .and_then(|v| v.build_scope_filter($resource, $action))
+    }};
+}
+
 #[macro_export]
 macro_rules! user_no_namespace_permission {
     ($param:expr) => {{
@@ -1,9 +1,10 @@
 use crate::common::string_utils::StringUtils;
 use crate::ldap::model::LdapConfig;
 use crate::oauth2::model::OAuth2Config;
+use crate::user::jwt_auth::JwtAuthConfig;
 use crate::user::permission;
 use crate::user::permission::UserRoleHelper;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -16,7 +17,9 @@ pub mod cycle_queue;
 pub mod datetime_utils;
 pub mod delay_notify;
 pub mod error_code;
+pub mod fuzzy_watch;
 pub mod hash_utils;
+pub mod jwt_utils;
 pub mod limiter_utils;
 pub mod log_utils;
 pub mod macros;
@@ -30,6 +33,7 @@ pub mod sled_utils;
 pub mod sqlx_utils;
 pub mod string_utils;
 pub mod tempfile;
+pub mod tls;
 pub mod web_utils;
 /*
 use lazy_static::lazy_static;
@@ -97,6 +101,8 @@ pub struct AppSysConfig {
     pub metrics_collect_interval_second: u64,
     pub metrics_log_interval_second: u64,
     pub metrics_log_enable: bool,
+    /// 每个带标签指标最多保留的标签组合数，超出后归入__other__
+    pub metrics_label_max_series: usize,
     pub console_captcha_enable: bool,
     pub run_in_docker: bool,
     pub naming_health_timeout: u64,
@@ -122,11 +128,112 @@ pub struct AppSysConfig {
     pub oauth2_scopes: Arc<String>,
     pub oauth2_username_claim_name: Arc<String>,
     pub oauth2_nickname_claim_name: Arc<String>,
+    pub oauth2_groups_claim_name: Arc<String>,
     pub oauth2_user_default_role: Arc<String>,
+    pub oauth2_user_developer_groups: Arc<HashSet<String>>,
+    pub oauth2_user_admin_groups: Arc<HashSet<String>>,
+    pub oauth2_group_namespaces: Arc<HashMap<String, HashSet<String>>>,
+    pub oauth2_oidc_enable: bool,
+    pub oauth2_issuer: Arc<String>,
+    pub oauth2_pkce_enable: bool,
     pub oauth2_button: Arc<String>,
+    /// 是否允许openapi、gRPC使用外部身份提供方签发的JWT鉴权
+    pub jwt_auth_enable: bool,
+    /// JWT签名公钥，JWKS文件路径或url
+    pub jwt_auth_jwks: Arc<String>,
+    pub jwt_auth_issuer: Arc<String>,
+    pub jwt_auth_audience: Arc<String>,
+    pub jwt_auth_username_claim_name: Arc<String>,
+    pub jwt_auth_groups_claim_name: Arc<String>,
+    pub jwt_auth_default_role: Arc<String>,
+    pub jwt_auth_developer_groups: Arc<HashSet<String>>,
+    pub jwt_auth_admin_groups: Arc<HashSet<String>>,
+    pub jwt_auth_group_namespaces: Arc<HashMap<String, HashSet<String>>>,
     pub grpc_detection_timeout: u64,
     pub enable_grpc_detection_log: bool,
     pub naming_instance_metadata_persistence_enable: bool,
+    /// 需要落盘加密的配置dataId前缀
+    pub config_encrypt_data_id_prefix: Arc<String>,
+    /// 配置加密主密钥，为空表示不开启加密
+    pub config_encrypt_key: Arc<String>,
+    /// 密钥轮换前使用过的主密钥，只用于解密
+    pub config_encrypt_old_keys: Arc<Vec<String>>,
+    /// 发布配置时是否按配置类型校验内容格式
+    pub config_validate_enable: bool,
+    /// 是否开启服务发现DNS接口
+    pub naming_dns_enable: bool,
+    pub naming_dns_host: String,
+    pub naming_dns_port: u16,
+    /// 服务域名后缀，域名格式为 service.group.namespace.后缀
+    pub naming_dns_suffix: Arc<String>,
+    /// DNS记录默认TTL，单位秒
+    pub naming_dns_ttl: u32,
+    /// 服务实例变更后一个TTL周期内返回的短TTL，单位秒
+    pub naming_dns_changed_ttl: u32,
+    /// 服务端证书与私钥文件(PEM)，http、console、grpc端口共用
+    pub tls_cert_file: Arc<String>,
+    pub tls_key_file: Arc<String>,
+    /// 客户端证书CA文件，不为空时开启双向认证
+    pub tls_client_ca_file: Arc<String>,
+    /// 双向认证时是否强制要求客户端证书
+    pub tls_client_auth_required: bool,
+    pub tls_http_enable: bool,
+    pub tls_console_enable: bool,
+    pub tls_grpc_enable: bool,
+    /// 集群节点间请求是否使用TLS
+    pub tls_cluster_enable: bool,
+    /// 校验其它节点证书的CA文件
+    pub tls_cluster_ca_file: Arc<String>,
+    /// 集群请求使用的客户端证书与私钥文件，用于对端开启双向认证的场景
+    pub tls_cluster_cert_file: Arc<String>,
+    pub tls_cluster_key_file: Arc<String>,
+    /// 校验节点证书时使用的域名，为空时使用节点地址
+    pub tls_cluster_server_name: Arc<String>,
+    /// 证书文件变更检查间隔，单位秒，0表示不重新加载
+    pub tls_reload_interval_second: u64,
+    /// 是否记录变更操作审计日志
+    pub audit_log_enable: bool,
+    /// 审计日志最多保留条数
+    pub audit_log_max_count: usize,
+    /// 审计日志保留天数，0表示不按时间清理
+    pub audit_log_retention_days: u32,
+    /// 是否记录gRPC实例注册/注销的审计日志，客户端实例变动频繁时可关闭
+    pub audit_log_grpc_instance_enable: bool,
+    /// 是否开启webhook事件推送
+    pub webhook_enable: bool,
+    /// webhook推送失败最大重试次数，超过后转入死信
+    pub webhook_max_retry: u32,
+    /// webhook单次推送超时时间，单位毫秒
+    pub webhook_timeout_millis: u64,
+    /// webhook死信最多保留条数
+    pub webhook_dead_letter_max_count: usize,
+    /// webhook死信保留天数，0表示不按时间清理
+    pub webhook_dead_letter_retention_days: u32,
+    /// 是否开启定时备份，只在raft主节点执行
+    pub backup_enable: bool,
+    /// 定时备份cron表达式(分 时 日 月 周)，按服务时区计算
+    pub backup_cron: Arc<String>,
+    /// 备份目标，可选值：local、s3
+    pub backup_target: Arc<String>,
+    pub backup_dir: Arc<String>,
+    /// 备份最多保留个数，0表示不按个数清理
+    pub backup_retain_count: usize,
+    /// 备份保留天数，0表示不按时间清理
+    pub backup_retain_days: u32,
+    pub backup_s3_endpoint: Arc<String>,
+    pub backup_s3_region: Arc<String>,
+    pub backup_s3_bucket: Arc<String>,
+    pub backup_s3_prefix: Arc<String>,
+    pub backup_s3_access_key: Arc<String>,
+    pub backup_s3_secret_key: Arc<String>,
+    /// 是否开启gRPC连接自动均衡，连接数超过集群平均值时通知客户端迁移到连接较少的节点
+    pub grpc_conn_rebalance_enable: bool,
+    /// gRPC连接均衡检测间隔，单位秒
+    pub grpc_conn_rebalance_interval_second: u64,
+    /// 超过集群平均连接数的百分比阈值，超过后才触发迁移
+    pub grpc_conn_rebalance_threshold_percent: u64,
+    /// 单次最多迁移的连接数
+    pub grpc_conn_rebalance_batch_size: usize,
 }
 
 impl AppSysConfig {
@@ -239,6 +346,10 @@ impl AppSysConfig {
             .unwrap_or("false".to_owned())
             .parse()
             .unwrap_or(false);
+        let metrics_label_max_series = std::env::var("RNACOS_METRICS_LABEL_MAX_SERIES")
+            .unwrap_or("200".to_owned())
+            .parse()
+            .unwrap_or(200);
         let mut metrics_log_interval_second = std::env::var("RNACOS_METRICS_LOG_INTERVAL_SECOND")
             .unwrap_or("60".to_owned())
             .parse()
@@ -346,15 +457,67 @@ impl AppSysConfig {
         let oauth2_nickname_claim_name = std::env::var("RNACOS_OAUTH2_NICKNAME_CLAIM_NAME")
             .map(Arc::new)
             .unwrap_or_else(|_| Arc::new("name".to_string()));
+        let oauth2_groups_claim_name = std::env::var("RNACOS_OAUTH2_GROUPS_CLAIM_NAME")
+            .map(Arc::new)
+            .unwrap_or_else(|_| Arc::new("groups".to_string()));
         let oauth2_user_default_role = std::env::var("RNACOS_OAUTH2_USER_DEFAULT_ROLE")
             .map(|v| {
                 let upper = v.to_uppercase();
                 UserRoleHelper::get_role_by_name(&upper, permission::USER_ROLE_DEVELOPER.clone())
             })
             .unwrap_or(permission::USER_ROLE_DEVELOPER.clone());
+        let oauth2_user_developer_groups = Arc::new(StringUtils::split_to_hashset(
+            &std::env::var("RNACOS_OAUTH2_USER_DEVELOPER_GROUP").unwrap_or_default(),
+        ));
+        let oauth2_user_admin_groups = Arc::new(StringUtils::split_to_hashset(
+            &std::env::var("RNACOS_OAUTH2_USER_ADMIN_GROUP").unwrap_or_default(),
+        ));
+        let oauth2_group_namespaces = Arc::new(OAuth2Config::parse_group_namespaces(
+            &std::env::var("RNACOS_OAUTH2_GROUP_NAMESPACES").unwrap_or_default(),
+        ));
+        let oauth2_oidc_enable = std::env::var("RNACOS_OAUTH2_OIDC_ENABLE")
+            .unwrap_or("false".to_owned())
+            .parse()
+            .unwrap_or(false);
+        let oauth2_issuer = std::env::var("RNACOS_OAUTH2_ISSUER")
+            .map(Arc::new)
+            .unwrap_or(oauth2_server_url.clone());
+        let oauth2_pkce_enable = std::env::var("RNACOS_OAUTH2_PKCE_ENABLE")
+            .unwrap_or("true".to_owned())
+            .parse()
+            .unwrap_or(true);
         let oauth2_button = std::env::var("RNACOS_OAUTH2_BUTTON")
             .map(Arc::new)
             .unwrap_or_else(|_| Arc::new("OAuth2.0 登录".to_string()));
+        let jwt_auth_enable = std::env::var("RNACOS_JWT_AUTH_ENABLE")
+            .unwrap_or("false".to_owned())
+            .parse()
+            .unwrap_or(false);
+        let jwt_auth_jwks = Arc::new(std::env::var("RNACOS_JWT_AUTH_JWKS").unwrap_or_default());
+        let jwt_auth_issuer = Arc::new(std::env::var("RNACOS_JWT_AUTH_ISSUER").unwrap_or_default());
+        let jwt_auth_audience =
+            Arc::new(std::env::var("RNACOS_JWT_AUTH_AUDIENCE").unwrap_or_default());
+        let jwt_auth_username_claim_name = std::env::var("RNACOS_JWT_AUTH_USERNAME_CLAIM_NAME")
+            .map(Arc::new)
+            .unwrap_or_else(|_| Arc::new("sub".to_string()));
+        let jwt_auth_groups_claim_name = std::env::var("RNACOS_JWT_AUTH_GROUPS_CLAIM_NAME")
+            .map(Arc::new)
+            .unwrap_or_else(|_| Arc::new("groups".to_string()));
+        let jwt_auth_default_role = std::env::var("RNACOS_JWT_AUTH_DEFAULT_ROLE")
+            .map(|v| {
+                let upper = v.to_uppercase();
+                UserRoleHelper::get_role_by_name(&upper, permission::USER_ROLE_VISITOR.clone())
+            })
+            .unwrap_or(permission::USER_ROLE_VISITOR.clone());
+        let jwt_auth_developer_groups = Arc::new(StringUtils::split_to_hashset(
+            &std::env::var("RNACOS_JWT_AUTH_DEVELOPER_GROUP").unwrap_or_default(),
+        ));
+        let jwt_auth_admin_groups = Arc::new(StringUtils::split_to_hashset(
+            &std::env::var("RNACOS_JWT_AUTH_ADMIN_GROUP").unwrap_or_default(),
+        ));
+        let jwt_auth_group_namespaces = Arc::new(OAuth2Config::parse_group_namespaces(
+            &std::env::var("RNACOS_JWT_AUTH_GROUP_NAMESPACES").unwrap_or_default(),
+        ));
         let grpc_detection_timeout = std::env::var("RNACOS_GRPC_DETECTION_TIMEOUT_SECOND")
             .unwrap_or("15".to_owned())
             .parse()
@@ -369,6 +532,192 @@ impl AppSysConfig {
                 .unwrap_or("true".to_owned())
                 .parse()
                 .unwrap_or(true);
+        let config_encrypt_data_id_prefix = std::env::var("RNACOS_CONFIG_ENCRYPT_DATA_ID_PREFIX")
+            .map(Arc::new)
+            .unwrap_or_else(|_| Arc::new("cipher-".to_owned()));
+        let config_encrypt_key = Arc::new(Self::get_config_encrypt_key());
+        let config_encrypt_old_keys = Arc::new(
+            std::env::var("RNACOS_CONFIG_ENCRYPT_OLD_KEYS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|e| e.trim())
+                .filter(|e| !e.is_empty())
+                .map(|e| e.to_owned())
+                .collect(),
+        );
+        let config_validate_enable = std::env::var("RNACOS_CONFIG_VALIDATE_ENABLE")
+            .unwrap_or("false".to_owned())
+            .parse()
+            .unwrap_or(false);
+        let naming_dns_enable = std::env::var("RNACOS_NAMING_DNS_ENABLE")
+            .unwrap_or("false".to_owned())
+            .parse()
+            .unwrap_or(false);
+        let naming_dns_host =
+            std::env::var("RNACOS_NAMING_DNS_HOST").unwrap_or_else(|_| sdk_host.clone());
+        let naming_dns_port = std::env::var("RNACOS_NAMING_DNS_PORT")
+            .unwrap_or("8600".to_owned())
+            .parse()
+            .unwrap_or(8600);
+        let naming_dns_suffix = Arc::new(
+            std::env::var("RNACOS_NAMING_DNS_SUFFIX")
+                .unwrap_or("rnacos".to_owned())
+                .trim_matches('.')
+                .to_lowercase(),
+        );
+        let naming_dns_ttl = std::env::var("RNACOS_NAMING_DNS_TTL_SECOND")
+            .unwrap_or("10".to_owned())
+            .parse()
+            .unwrap_or(10);
+        let naming_dns_changed_ttl = std::env::var("RNACOS_NAMING_DNS_CHANGED_TTL_SECOND")
+            .unwrap_or("1".to_owned())
+            .parse()
+            .unwrap_or(1u32)
+            .min(naming_dns_ttl);
+        let tls_enable = std::env::var("RNACOS_TLS_ENABLE")
+            .unwrap_or("false".to_owned())
+            .parse()
+            .unwrap_or(false);
+        let tls_cert_file = Arc::new(std::env::var("RNACOS_TLS_CERT_FILE").unwrap_or_default());
+        let tls_key_file = Arc::new(std::env::var("RNACOS_TLS_KEY_FILE").unwrap_or_default());
+        let tls_client_ca_file =
+            Arc::new(std::env::var("RNACOS_TLS_CLIENT_CA_FILE").unwrap_or_default());
+        let tls_client_auth_required = std::env::var("RNACOS_TLS_CLIENT_AUTH_REQUIRED")
+            .unwrap_or("true".to_owned())
+            .parse()
+            .unwrap_or(true);
+        let tls_http_enable = std::env::var("RNACOS_TLS_HTTP_ENABLE")
+            .unwrap_or(tls_enable.to_string())
+            .parse()
+            .unwrap_or(tls_enable);
+        let tls_console_enable = std::env::var("RNACOS_TLS_CONSOLE_ENABLE")
+            .unwrap_or(tls_enable.to_string())
+            .parse()
+            .unwrap_or(tls_enable);
+        let tls_grpc_enable = std::env::var("RNACOS_TLS_GRPC_ENABLE")
+            .unwrap_or(tls_enable.to_string())
+            .parse()
+            .unwrap_or(tls_enable);
+        let tls_cluster_enable = std::env::var("RNACOS_TLS_CLUSTER_ENABLE")
+            .unwrap_or(tls_grpc_enable.to_string())
+            .parse()
+            .unwrap_or(tls_grpc_enable);
+        let tls_cluster_ca_file = Arc::new(
+            std::env::var("RNACOS_TLS_CLUSTER_CA_FILE")
+                .unwrap_or_else(|_| tls_client_ca_file.as_str().to_owned()),
+        );
+        let tls_cluster_cert_file = Arc::new(
+            std::env::var("RNACOS_TLS_CLUSTER_CERT_FILE")
+                .unwrap_or_else(|_| tls_cert_file.as_str().to_owned()),
+        );
+        let tls_cluster_key_file = Arc::new(
+            std::env::var("RNACOS_TLS_CLUSTER_KEY_FILE")
+                .unwrap_or_else(|_| tls_key_file.as_str().to_owned()),
+        );
+        let tls_cluster_server_name =
+            Arc::new(std::env::var("RNACOS_TLS_CLUSTER_SERVER_NAME").unwrap_or_default());
+        let tls_reload_interval_second = std::env::var("RNACOS_TLS_RELOAD_INTERVAL_SECOND")
+            .unwrap_or("60".to_owned())
+            .parse()
+            .unwrap_or(60);
+        let audit_log_enable = std::env::var("RNACOS_AUDIT_LOG_ENABLE")
+            .unwrap_or("true".to_owned())
+            .parse()
+            .unwrap_or(true);
+        let audit_log_max_count = std::env::var("RNACOS_AUDIT_LOG_MAX_COUNT")
+            .unwrap_or("100000".to_owned())
+            .parse()
+            .unwrap_or(100000);
+        let audit_log_retention_days = std::env::var("RNACOS_AUDIT_LOG_RETENTION_DAYS")
+            .unwrap_or("90".to_owned())
+            .parse()
+            .unwrap_or(90);
+        let audit_log_grpc_instance_enable = std::env::var("RNACOS_AUDIT_LOG_GRPC_INSTANCE_ENABLE")
+            .unwrap_or("true".to_owned())
+            .parse()
+            .unwrap_or(true);
+        let webhook_enable = std::env::var("RNACOS_WEBHOOK_ENABLE")
+            .unwrap_or("true".to_owned())
+            .parse()
+            .unwrap_or(true);
+        let webhook_max_retry = std::env::var("RNACOS_WEBHOOK_MAX_RETRY")
+            .unwrap_or("5".to_owned())
+            .parse()
+            .unwrap_or(5);
+        let webhook_timeout_millis = std::env::var("RNACOS_WEBHOOK_TIMEOUT_MILLIS")
+            .unwrap_or("3000".to_owned())
+            .parse()
+            .unwrap_or(3000);
+        let webhook_dead_letter_max_count = std::env::var("RNACOS_WEBHOOK_DEAD_LETTER_MAX_COUNT")
+            .unwrap_or("1000".to_owned())
+            .parse()
+            .unwrap_or(1000);
+        let webhook_dead_letter_retention_days =
+            std::env::var("RNACOS_WEBHOOK_DEAD_LETTER_RETENTION_DAYS")
+                .unwrap_or("30".to_owned())
+                .parse()
+                .unwrap_or(30);
+        let backup_enable = std::env::var("RNACOS_BACKUP_ENABLE")
+            .unwrap_or("false".to_owned())
+            .parse()
+            .unwrap_or(false);
+        let backup_cron = std::env::var("RNACOS_BACKUP_CRON")
+            .map(Arc::new)
+            .unwrap_or_else(|_| Arc::new("0 3 * * *".to_owned()));
+        let backup_target = std::env::var("RNACOS_BACKUP_TARGET")
+            .map(|v| Arc::new(v.to_lowercase()))
+            .unwrap_or_else(|_| Arc::new("local".to_owned()));
+        let backup_dir = std::env::var("RNACOS_BACKUP_DIR")
+            .map(Arc::new)
+            .unwrap_or_else(|_| {
+                Arc::new(
+                    std::path::Path::new(&local_db_dir)
+                        .join("backup")
+                        .to_string_lossy()
+                        .to_string(),
+                )
+            });
+        let backup_retain_count = std::env::var("RNACOS_BACKUP_RETAIN_COUNT")
+            .unwrap_or("7".to_owned())
+            .parse()
+            .unwrap_or(7);
+        let backup_retain_days = std::env::var("RNACOS_BACKUP_RETAIN_DAYS")
+            .unwrap_or("0".to_owned())
+            .parse()
+            .unwrap_or(0);
+        let backup_s3_endpoint =
+            Arc::new(std::env::var("RNACOS_BACKUP_S3_ENDPOINT").unwrap_or_default());
+        let backup_s3_region = std::env::var("RNACOS_BACKUP_S3_REGION")
+            .map(Arc::new)
+            .unwrap_or_else(|_| Arc::new("us-east-1".to_owned()));
+        let backup_s3_bucket =
+            Arc::new(std::env::var("RNACOS_BACKUP_S3_BUCKET").unwrap_or_default());
+        let backup_s3_prefix = std::env::var("RNACOS_BACKUP_S3_PREFIX")
+            .map(Arc::new)
+            .unwrap_or_else(|_| Arc::new("rnacos/".to_owned()));
+        let backup_s3_access_key =
+            Arc::new(std::env::var("RNACOS_BACKUP_S3_ACCESS_KEY").unwrap_or_default());
+        let backup_s3_secret_key =
+            Arc::new(std::env::var("RNACOS_BACKUP_S3_SECRET_KEY").unwrap_or_default());
+        let grpc_conn_rebalance_enable = std::env::var("RNACOS_GRPC_CONN_REBALANCE_ENABLE")
+            .unwrap_or("false".to_owned())
+            .parse()
+            .unwrap_or(false);
+        let grpc_conn_rebalance_interval_second =
+            std::env::var("RNACOS_GRPC_CONN_REBALANCE_INTERVAL_SECOND")
+                .unwrap_or("60".to_owned())
+                .parse()
+                .unwrap_or(60)
+                .max(5);
+        let grpc_conn_rebalance_threshold_percent =
+            std::env::var("RNACOS_GRPC_CONN_REBALANCE_THRESHOLD_PERCENT")
+                .unwrap_or("20".to_owned())
+                .parse()
+                .unwrap_or(20);
+        let grpc_conn_rebalance_batch_size = std::env::var("RNACOS_GRPC_CONN_REBALANCE_BATCH_SIZE")
+            .unwrap_or("20".to_owned())
+            .parse()
+            .unwrap_or(20);
         Self {
             local_db_dir,
             config_db_file,
@@ -397,6 +746,7 @@ impl AppSysConfig {
             init_admin_password,
             metrics_enable,
             metrics_log_enable,
+            metrics_label_max_series,
             metrics_collect_interval_second,
             metrics_log_interval_second,
             console_captcha_enable,
@@ -423,14 +773,97 @@ impl AppSysConfig {
             oauth2_scopes,
             oauth2_username_claim_name,
             oauth2_nickname_claim_name,
+            oauth2_groups_claim_name,
             oauth2_user_default_role,
+            oauth2_user_developer_groups,
+            oauth2_user_admin_groups,
+            oauth2_group_namespaces,
+            oauth2_oidc_enable,
+            oauth2_issuer,
+            oauth2_pkce_enable,
             oauth2_button,
+            jwt_auth_enable,
+            jwt_auth_jwks,
+            jwt_auth_issuer,
+            jwt_auth_audience,
+            jwt_auth_username_claim_name,
+            jwt_auth_groups_claim_name,
+            jwt_auth_default_role,
+            jwt_auth_developer_groups,
+            jwt_auth_admin_groups,
+            jwt_auth_group_namespaces,
             grpc_detection_timeout,
             enable_grpc_detection_log,
             naming_instance_metadata_persistence_enable,
+            config_encrypt_data_id_prefix,
+            config_encrypt_key,
+            config_encrypt_old_keys,
+            config_validate_enable,
+            naming_dns_enable,
+            naming_dns_host,
+            naming_dns_port,
+            naming_dns_suffix,
+            naming_dns_ttl,
+            naming_dns_changed_ttl,
+            tls_cert_file,
+            tls_key_file,
+            tls_client_ca_file,
+            tls_client_auth_required,
+            tls_http_enable,
+            tls_console_enable,
+            tls_grpc_enable,
+            tls_cluster_enable,
+            tls_cluster_ca_file,
+            tls_cluster_cert_file,
+            tls_cluster_key_file,
+            tls_cluster_server_name,
+            tls_reload_interval_second,
+            audit_log_enable,
+            audit_log_max_count,
+            audit_log_retention_days,
+            audit_log_grpc_instance_enable,
+            webhook_enable,
+            webhook_max_retry,
+            webhook_timeout_millis,
+            webhook_dead_letter_max_count,
+            webhook_dead_letter_retention_days,
+            backup_enable,
+            backup_cron,
+            backup_target,
+            backup_dir,
+            backup_retain_count,
+            backup_retain_days,
+            backup_s3_endpoint,
+            backup_s3_region,
+            backup_s3_bucket,
+            backup_s3_prefix,
+            backup_s3_access_key,
+            backup_s3_secret_key,
+            grpc_conn_rebalance_enable,
+            grpc_conn_rebalance_interval_second,
+            grpc_conn_rebalance_threshold_percent,
+            grpc_conn_rebalance_batch_size,
         }
     }
 
+    /// 获取配置加密主密钥，优先从环境变量取，其次从密钥文件读取
+    fn get_config_encrypt_key() -> String {
+        if let Ok(v) = std::env::var("RNACOS_CONFIG_ENCRYPT_KEY") {
+            if !v.is_empty() {
+                return v;
+            }
+        }
+        if let Ok(path) = std::env::var("RNACOS_CONFIG_ENCRYPT_KEY_FILE") {
+            match std::fs::read_to_string(&path) {
+                Ok(v) => return v.trim().to_owned(),
+                Err(err) => {
+                    log::error!("read config encrypt key file {} error,{}", &path, err);
+                }
+            }
+        }
+        String::new()
+    }
+
     /// 获取数据目录
     fn get_data_dir(run_in_docker: bool) -> String {
         if let Ok(v) = std::env::var("RNACOS_DATA_DIR") {
@@ -488,9 +921,44 @@ impl AppSysConfig {
             oauth2_scopes: self.oauth2_scopes.clone(),
             oauth2_username_claim_name: self.oauth2_username_claim_name.clone(),
             oauth2_nickname_claim_name: self.oauth2_nickname_claim_name.clone(),
+            oauth2_groups_claim_name: self.oauth2_groups_claim_name.clone(),
             oauth2_user_default_role: self.oauth2_user_default_role.clone(),
+            oauth2_user_developer_groups: self.oauth2_user_developer_groups.clone(),
+            oauth2_user_admin_groups: self.oauth2_user_admin_groups.clone(),
+            oauth2_group_namespaces: self.oauth2_group_namespaces.clone(),
+            oauth2_oidc_enable: self.oauth2_oidc_enable,
+            oauth2_issuer: self.oauth2_issuer.clone(),
+            oauth2_pkce_enable: self.oauth2_pkce_enable,
         })
     }
+
+    ///
+    /// 未开启或未配置公钥、issuer、audience时返回None(不开启JWT认证)
+    pub fn get_jwt_auth_config(&self) -> Option<Arc<JwtAuthConfig>> {
+        if !self.jwt_auth_enable {
+            return None;
+        }
+        if self.jwt_auth_jwks.is_empty()
+            || self.jwt_auth_issuer.is_empty()
+            || self.jwt_auth_audience.is_empty()
+        {
+            log::error!(
+                "jwt auth is enabled, but RNACOS_JWT_AUTH_JWKS, RNACOS_JWT_AUTH_ISSUER or RNACOS_JWT_AUTH_AUDIENCE is empty, jwt auth is disabled"
+            );
+            return None;
+        }
+        Some(Arc::new(JwtAuthConfig {
+            jwks: self.jwt_auth_jwks.clone(),
+            issuer: self.jwt_auth_issuer.clone(),
+            audience: self.jwt_auth_audience.clone(),
+            username_claim_name: self.jwt_auth_username_claim_name.clone(),
+            groups_claim_name: self.jwt_auth_groups_claim_name.clone(),
+            default_role: self.jwt_auth_default_role.clone(),
+            developer_groups: self.jwt_auth_developer_groups.clone(),
+            admin_groups: self.jwt_auth_admin_groups.clone(),
+            group_namespaces: self.jwt_auth_group_namespaces.clone(),
+        }))
+    }
 }
 
 /**
@@ -5,6 +5,7 @@ use std::{collections::HashMap, sync::Arc};
 
 pub use crate::common::model::client_version::ClientVersion;
 use crate::common::model::privilege::PrivilegeGroup;
+use crate::user::permission::RolePermission;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Default, Deserialize, Serialize)]
@@ -97,6 +98,9 @@ pub struct UserSession {
     pub extend_infos: HashMap<String, String>,
     /// 时间戳，单位秒
     pub refresh_time: u32,
+    /// LDAP/OAuth2用户所属的用户组，用于匹配用户组绑定的角色
+    #[serde(default)]
+    pub groups: Vec<Arc<String>>,
 }
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
@@ -104,4 +108,10 @@ pub struct TokenSession {
     pub username: Arc<String>,
     pub roles: Vec<Arc<String>>,
     pub extend_infos: HashMap<String, String>,
+    /// 访问密钥限定的授权范围，为空时不额外限制
+    #[serde(default)]
+    pub scopes: Vec<RolePermission>,
+    /// 外部JWT中的用户组，用于匹配绑定用户组的自定义角色
+    #[serde(default)]
+    pub groups: Vec<Arc<String>>,
 }
@@ -58,4 +58,32 @@ impl StringUtils {
             .filter(|s| !s.is_empty()) // 过滤空字符串
             .collect() // HashSet自动去重
     }
+
+    ///
+    /// 通配符匹配，`*`匹配任意多个字符，`?`匹配单个字符
+    pub fn glob_match(pattern: &str, value: &str) -> bool {
+        let p: Vec<char> = pattern.chars().collect();
+        let v: Vec<char> = value.chars().collect();
+        let (mut pi, mut vi) = (0, 0);
+        let mut star: Option<(usize, usize)> = None;
+        while vi < v.len() {
+            if pi < p.len() && (p[pi] == '?' || p[pi] == v[vi]) {
+                pi += 1;
+                vi += 1;
+            } else if pi < p.len() && p[pi] == '*' {
+                star = Some((pi, vi));
+                pi += 1;
+            } else if let Some((star_pi, star_vi)) = star {
+                pi = star_pi + 1;
+                vi = star_vi + 1;
+                star = Some((star_pi, star_vi + 1));
+            } else {
+                return false;
+            }
+        }
+        while pi < p.len() && p[pi] == '*' {
+            pi += 1;
+        }
+        pi == p.len()
+    }
 }
@@ -0,0 +1,590 @@
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant, SystemTime};
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::{ResolvesClientCert, WebPkiServerVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::server::danger::{ClientCertVerified, ClientCertVerifier};
+use rustls::server::{ClientHello, ResolvesServerCert, WebPkiClientVerifier};
+use rustls::sign::CertifiedKey;
+use rustls::{
+    ClientConfig, DigitallySignedStruct, DistinguishedName, RootCertStore, ServerConfig,
+    SignatureScheme,
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::transport::server::Connected;
+use tonic::transport::Uri;
+
+use crate::common::AppSysConfig;
+
+const ALPN_H2: &[u8] = b"h2";
+
+pub fn crypto_provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+type FileLoader<T> = Box<dyn Fn(&[String]) -> anyhow::Result<T> + Send + Sync>;
+
+///
+/// 证书文件热加载
+/// 每隔interval检查一次文件修改时间，有变更时重新加载；加载失败时继续使用旧值
+struct ReloadableFiles<T> {
+    files: Vec<String>,
+    interval: Duration,
+    loader: FileLoader<T>,
+    state: RwLock<ReloadState<T>>,
+}
+
+struct ReloadState<T> {
+    value: Arc<T>,
+    modified: Vec<Option<SystemTime>>,
+    checked_at: Instant,
+}
+
+impl<T> ReloadableFiles<T> {
+    fn new(files: Vec<String>, interval: Duration, loader: FileLoader<T>) -> anyhow::Result<Self> {
+        let modified = Self::files_modified(&files);
+        let value = Arc::new(loader(&files)?);
+        Ok(Self {
+            files,
+            interval,
+            loader,
+            state: RwLock::new(ReloadState {
+                value,
+                modified,
+                checked_at: Instant::now(),
+            }),
+        })
+    }
+
+    fn files_modified(files: &[String]) -> Vec<Option<SystemTime>> {
+        files
+            .iter()
+            .map(|f| std::fs::metadata(f).and_then(|m| m.modified()).ok())
+            .collect()
+    }
+
+    fn get(&self) -> Arc<T> {
+        if let Ok(state) = self.state.read() {
+            if self.interval.is_zero() || state.checked_at.elapsed() < self.interval {
+                return state.value.clone();
+            }
+        }
+        let mut state = match self.state.write() {
+            Ok(v) => v,
+            Err(e) => e.into_inner(),
+        };
+        if state.checked_at.elapsed() < self.interval {
+            return state.value.clone();
+        }
+        state.checked_at = Instant::now();
+        let modified = Self::files_modified(&self.files);
+        if modified != state.modified {
+            match (self.loader)(&self.files) {
+                Ok(v) => {
+                    log::info!("tls files reloaded: {:?}", &self.files);
+                    state.value = Arc::new(v);
+                    state.modified = modified;
+                }
+                Err(e) => {
+                    log::error!("tls files reload error, {:?}: {}", &self.files, e);
+                }
+            }
+        }
+        state.value.clone()
+    }
+}
+
+fn load_certified_key(files: &[String], provider: &CryptoProvider) -> anyhow::Result<CertifiedKey> {
+    let certs = CertificateDer::pem_file_iter(&files[0])
+        .map_err(|e| anyhow::anyhow!("read cert file {} error, {}", &files[0], e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| anyhow::anyhow!("parse cert file {} error, {}", &files[0], e))?;
+    if certs.is_empty() {
+        return Err(anyhow::anyhow!("cert file {} is empty", &files[0]));
+    }
+    let key = PrivateKeyDer::from_pem_file(&files[1])
+        .map_err(|e| anyhow::anyhow!("read key file {} error, {}", &files[1], e))?;
+    Ok(CertifiedKey::from_der(certs, key, provider)?)
+}
+
+fn load_root_store(file: &str) -> anyhow::Result<RootCertStore> {
+    let mut roots = RootCertStore::empty();
+    for cert in CertificateDer::pem_file_iter(file)
+        .map_err(|e| anyhow::anyhow!("read ca file {} error, {}", file, e))?
+    {
+        roots.add(cert.map_err(|e| anyhow::anyhow!("parse ca file {} error, {}", file, e))?)?;
+    }
+    if roots.is_empty() {
+        return Err(anyhow::anyhow!("ca file {} is empty", file));
+    }
+    Ok(roots)
+}
+
+fn reloadable_certified_key(
+    cert_file: &str,
+    key_file: &str,
+    interval: Duration,
+    provider: Arc<CryptoProvider>,
+) -> anyhow::Result<ReloadableFiles<CertifiedKey>> {
+    ReloadableFiles::new(
+        vec![cert_file.to_owned(), key_file.to_owned()],
+        interval,
+        Box::new(move |files| load_certified_key(files, &provider)),
+    )
+}
+
+pub struct ReloadableCertResolver {
+    inner: ReloadableFiles<CertifiedKey>,
+}
+
+impl Debug for ReloadableCertResolver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReloadableCertResolver")
+            .field("files", &self.inner.files)
+            .finish()
+    }
+}
+
+impl ResolvesServerCert for ReloadableCertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.inner.get())
+    }
+}
+
+impl ResolvesClientCert for ReloadableCertResolver {
+    fn resolve(
+        &self,
+        _root_hint_subjects: &[&[u8]],
+        _sigschemes: &[SignatureScheme],
+    ) -> Option<Arc<CertifiedKey>> {
+        Some(self.inner.get())
+    }
+
+    fn has_certs(&self) -> bool {
+        true
+    }
+}
+
+///
+/// 双向认证客户端证书校验，CA文件变更后自动重新加载
+pub struct ReloadableClientVerifier {
+    inner: ReloadableFiles<Arc<dyn ClientCertVerifier>>,
+    required: bool,
+}
+
+impl ReloadableClientVerifier {
+    fn new(
+        ca_file: &str,
+        required: bool,
+        interval: Duration,
+        provider: Arc<CryptoProvider>,
+    ) -> anyhow::Result<Self> {
+        let inner = ReloadableFiles::new(
+            vec![ca_file.to_owned()],
+            interval,
+            Box::new(move |files| {
+                let roots = Arc::new(load_root_store(&files[0])?);
+                let mut builder =
+                    WebPkiClientVerifier::builder_with_provider(roots, provider.clone());
+                if !required {
+                    builder = builder.allow_unauthenticated();
+                }
+                Ok(builder.build()?)
+            }),
+        )?;
+        Ok(Self { inner, required })
+    }
+}
+
+impl Debug for ReloadableClientVerifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReloadableClientVerifier")
+            .field("files", &self.inner.files)
+            .field("required", &self.required)
+            .finish()
+    }
+}
+
+impl ClientCertVerifier for ReloadableClientVerifier {
+    fn client_auth_mandatory(&self) -> bool {
+        self.required
+    }
+
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        // CA可能被重新加载，不返回提示列表
+        &[]
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        now: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        self.inner
+            .get()
+            .verify_client_cert(end_entity, intermediates, now)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.get().verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.get().verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.get().supported_verify_schemes()
+    }
+}
+
+///
+/// 集群节点服务端证书校验，CA文件变更后自动重新加载
+pub struct ReloadableServerVerifier {
+    inner: ReloadableFiles<Arc<WebPkiServerVerifier>>,
+}
+
+impl ReloadableServerVerifier {
+    fn new(
+        ca_file: &str,
+        interval: Duration,
+        provider: Arc<CryptoProvider>,
+    ) -> anyhow::Result<Self> {
+        let inner = ReloadableFiles::new(
+            vec![ca_file.to_owned()],
+            interval,
+            Box::new(move |files| {
+                let roots = Arc::new(load_root_store(&files[0])?);
+                Ok(WebPkiServerVerifier::builder_with_provider(roots, provider.clone()).build()?)
+            }),
+        )?;
+        Ok(Self { inner })
+    }
+}
+
+impl Debug for ReloadableServerVerifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReloadableServerVerifier")
+            .field("files", &self.inner.files)
+            .finish()
+    }
+}
+
+impl ServerCertVerifier for ReloadableServerVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        self.inner.get().verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        )
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.get().verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.get().verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.get().supported_verify_schemes()
+    }
+}
+
+fn reload_interval(sys_config: &AppSysConfig) -> Duration {
+    Duration::from_secs(sys_config.tls_reload_interval_second)
+}
+
+///
+/// 构建服务端TLS配置，http、console、grpc端口共用同一套证书
+pub fn build_server_config(sys_config: &AppSysConfig) -> anyhow::Result<ServerConfig> {
+    let provider = crypto_provider();
+    let interval = reload_interval(sys_config);
+    let resolver = ReloadableCertResolver {
+        inner: reloadable_certified_key(
+            &sys_config.tls_cert_file,
+            &sys_config.tls_key_file,
+            interval,
+            provider.clone(),
+        )?,
+    };
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?;
+    let config = if sys_config.tls_client_ca_file.is_empty() {
+        builder
+            .with_no_client_auth()
+            .with_cert_resolver(Arc::new(resolver))
+    } else {
+        let verifier = ReloadableClientVerifier::new(
+            &sys_config.tls_client_ca_file,
+            sys_config.tls_client_auth_required,
+            interval,
+            provider,
+        )?;
+        builder
+            .with_client_cert_verifier(Arc::new(verifier))
+            .with_cert_resolver(Arc::new(resolver))
+    };
+    Ok(config)
+}
+
+///
+/// 构建集群节点间请求使用的客户端TLS配置
+pub fn build_cluster_client_config(sys_config: &AppSysConfig) -> anyhow::Result<ClientConfig> {
+    if sys_config.tls_cluster_ca_file.is_empty() {
+        return Err(anyhow::anyhow!(
+            "RNACOS_TLS_CLUSTER_CA_FILE is required when cluster tls is enabled"
+        ));
+    }
+    let provider = crypto_provider();
+    let interval = reload_interval(sys_config);
+    let verifier =
+        ReloadableServerVerifier::new(&sys_config.tls_cluster_ca_file, interval, provider.clone())?;
+    let builder = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier));
+    let mut config = if sys_config.tls_cluster_cert_file.is_empty() {
+        builder.with_no_client_auth()
+    } else {
+        let resolver = ReloadableCertResolver {
+            inner: reloadable_certified_key(
+                &sys_config.tls_cluster_cert_file,
+                &sys_config.tls_cluster_key_file,
+                interval,
+                provider,
+            )?,
+        };
+        builder.with_client_cert_resolver(Arc::new(resolver))
+    };
+    config.alpn_protocols = vec![ALPN_H2.to_vec()];
+    Ok(config)
+}
+
+///
+/// grpc服务端TLS连接
+pub struct GrpcTlsStream {
+    inner: tokio_rustls::server::TlsStream<TcpStream>,
+    remote_addr: SocketAddr,
+}
+
+impl Connected for GrpcTlsStream {
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        Some(self.remote_addr)
+    }
+}
+
+impl AsyncRead for GrpcTlsStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for GrpcTlsStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// grpc TLS握手超时时间，避免慢连接长期占用
+const GRPC_TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// accept持续失败(如文件句柄耗尽)时的退避时间
+const GRPC_ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+const GRPC_ACCEPT_ERROR_MAX_BACKOFF: Duration = Duration::from_secs(1);
+
+///
+/// 监听grpc端口并完成TLS握手，握手失败或超时的连接直接丢弃
+pub async fn grpc_tls_incoming(
+    addr: SocketAddr,
+    mut config: ServerConfig,
+) -> anyhow::Result<ReceiverStream<std::io::Result<GrpcTlsStream>>> {
+    config.alpn_protocols = vec![ALPN_H2.to_vec()];
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+    let listener = TcpListener::bind(addr).await?;
+    let (tx, rx) = tokio::sync::mpsc::channel(128);
+    tokio::spawn(async move {
+        let mut backoff = GRPC_ACCEPT_ERROR_BACKOFF;
+        loop {
+            let (stream, remote_addr) = match listener.accept().await {
+                Ok(v) => {
+                    backoff = GRPC_ACCEPT_ERROR_BACKOFF;
+                    v
+                }
+                Err(e) => {
+                    log::warn!("grpc accept error: {}", e);
+                    tokio::time::sleep(backoff).await;
+                    backoff = std::cmp::min(backoff * 2, GRPC_ACCEPT_ERROR_MAX_BACKOFF);
+                    continue;
+                }
+            };
+            stream.set_nodelay(true).ok();
+            let acceptor = acceptor.clone();
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                match tokio::time::timeout(GRPC_TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream))
+                    .await
+                {
+                    Ok(Ok(inner)) => {
+                        tx.send(Ok(GrpcTlsStream { inner, remote_addr })).await.ok();
+                    }
+                    Ok(Err(e)) => {
+                        log::warn!("grpc tls handshake error from {}: {}", remote_addr, e);
+                    }
+                    Err(_) => {
+                        log::warn!("grpc tls handshake timeout from {}", remote_addr);
+                    }
+                }
+            });
+        }
+    });
+    Ok(ReceiverStream::new(rx))
+}
+
+///
+/// 集群节点grpc请求的TLS连接器
+#[derive(Clone)]
+pub struct ClusterTlsConnector {
+    connector: TlsConnector,
+    server_name: Option<ServerName<'static>>,
+}
+
+impl ClusterTlsConnector {
+    pub fn new(sys_config: &AppSysConfig) -> anyhow::Result<Self> {
+        let config = build_cluster_client_config(sys_config)?;
+        let server_name = if sys_config.tls_cluster_server_name.is_empty() {
+            None
+        } else {
+            Some(ServerName::try_from(
+                sys_config.tls_cluster_server_name.as_str().to_owned(),
+            )?)
+        };
+        Ok(Self {
+            connector: TlsConnector::from(Arc::new(config)),
+            server_name,
+        })
+    }
+}
+
+impl tower_service::Service<Uri> for ClusterTlsConnector {
+    type Response = tokio_rustls::client::TlsStream<TcpStream>;
+    type Error = std::io::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let connector = self.connector.clone();
+        let server_name = self.server_name.clone();
+        Box::pin(async move {
+            let invalid = |msg: String| std::io::Error::new(std::io::ErrorKind::InvalidInput, msg);
+            let host = uri
+                .host()
+                .ok_or_else(|| invalid(format!("uri has no host: {}", uri)))?
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .to_owned();
+            let port = uri.port_u16().unwrap_or(443);
+            let server_name = match server_name {
+                Some(v) => v,
+                None => ServerName::try_from(host.clone())
+                    .map_err(|e| invalid(format!("invalid server name {}: {}", &host, e)))?,
+            };
+            let stream = TcpStream::connect((host.as_str(), port)).await?;
+            stream.set_nodelay(true).ok();
+            connector.connect(server_name, stream).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reload_files_on_change() {
+        let dir = std::env::temp_dir().join(format!("rnacos_tls_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("value.txt").to_string_lossy().to_string();
+        std::fs::write(&file, "v1").unwrap();
+        let reloadable = ReloadableFiles::new(
+            vec![file.clone()],
+            Duration::from_millis(1),
+            Box::new(|files| Ok(std::fs::read_to_string(&files[0])?)),
+        )
+        .unwrap();
+        assert_eq!(reloadable.get().as_str(), "v1");
+        std::thread::sleep(Duration::from_millis(20));
+        std::fs::write(&file, "v2").unwrap();
+        let file_time = SystemTime::now() + Duration::from_secs(1);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(file_time)
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(reloadable.get().as_str(), "v2");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
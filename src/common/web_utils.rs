@@ -1,4 +1,9 @@
-use actix_web::web;
+use crate::openapi::middle::auth_middle::bytes_to_payload;
+use actix_web::dev::ServiceRequest;
+use actix_web::http::header::{HeaderMap, CONTENT_TYPE};
+use actix_web::{web, HttpRequest};
+use std::collections::HashMap;
+use std::net::SocketAddr;
 use tokio_stream::StreamExt;
 
 const MAX_SIZE: usize = 10485760;
@@ -15,3 +20,69 @@ pub async fn get_req_body(mut payload: web::Payload) -> anyhow::Result<Vec<u8>>
     }
     Ok(body.to_vec())
 }
+
+///
+/// 获取http请求的客户端ip,支持代理转发头
+pub fn get_client_ip(req: &HttpRequest) -> String {
+    let conn_info = req.connection_info();
+    let addr = conn_info.realip_remote_addr().unwrap_or_default();
+    if let Ok(v) = addr.parse::<SocketAddr>() {
+        v.ip().to_string()
+    } else {
+        addr.to_owned()
+    }
+}
+
+pub fn get_content_type(headers: &HeaderMap) -> String {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+///
+/// 在中间件中读取请求体，读取后重新放回请求中；multipart请求不读取
+pub async fn peek_request_body(request: &mut ServiceRequest, content_type: &str) -> web::Bytes {
+    if content_type.contains("multipart") {
+        return web::Bytes::new();
+    }
+    if let Ok(payload) = request.extract::<web::Payload>().await {
+        let body = payload.to_bytes().await.unwrap_or_default();
+        request.set_payload(bytes_to_payload(body.clone()));
+        body
+    } else {
+        web::Bytes::new()
+    }
+}
+
+///
+/// 合并query与请求体(json或表单)中的参数
+pub fn parse_params(query: &str, content_type: &str, body: &[u8]) -> HashMap<String, String> {
+    let mut params: HashMap<String, String> =
+        serde_urlencoded::from_str::<Vec<(String, String)>>(query)
+            .map(|v| v.into_iter().collect())
+            .unwrap_or_default();
+    if body.is_empty() {
+        return params;
+    }
+    if content_type.contains("json") {
+        if let Ok(serde_json::Value::Object(map)) = serde_json::from_slice(body) {
+            for (key, value) in map {
+                let value = match value {
+                    serde_json::Value::String(v) => v,
+                    serde_json::Value::Number(v) => v.to_string(),
+                    serde_json::Value::Bool(v) => v.to_string(),
+                    serde_json::Value::Null => continue,
+                    v => v.to_string(),
+                };
+                params.insert(key, value);
+            }
+        }
+    } else if content_type.contains("x-www-form-urlencoded") {
+        if let Ok(list) = serde_urlencoded::from_bytes::<Vec<(String, String)>>(body) {
+            params.extend(list);
+        }
+    }
+    params
+}
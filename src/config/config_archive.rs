@@ -0,0 +1,373 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Seek, Write};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use zip::write::FileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use crate::config::core::ConfigInfoDto;
+use crate::config::utils::param_utils;
+use crate::config::ConfigUtils;
+use crate::utils::get_md5;
+
+/// 配置元数据清单文件，与nacos导出格式一致
+pub const CONFIG_METADATA_FILE: &str = ".metadata.yml";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigMetadataItem {
+    pub group: String,
+    pub data_id: String,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub config_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desc: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_name: Option<String>,
+    /// 标签，多个用逗号分隔
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_tags: Option<String>,
+    /// 导入时校验内容md5，nacos导出的文件没有该字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub md5: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigMetadata {
+    #[serde(default)]
+    pub metadata: Vec<ConfigMetadataItem>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConfigArchiveItem {
+    pub group: Arc<String>,
+    pub data_id: Arc<String>,
+    pub content: Arc<String>,
+    pub config_type: Option<Arc<String>>,
+    pub desc: Option<Arc<String>>,
+    /// 清单中没有该配置时为None，导入时不修改原有标签
+    pub tags: Option<Vec<Arc<String>>>,
+}
+
+///
+/// 导入时已存在同名配置的处理策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ConfigImportPolicy {
+    /// 存在冲突时整体放弃导入
+    Abort,
+    /// 跳过已存在的配置
+    Skip,
+    /// 覆盖已存在的配置
+    #[default]
+    Overwrite,
+}
+
+impl ConfigImportPolicy {
+    pub fn from_name(name: &str) -> anyhow::Result<Self> {
+        match name.to_uppercase().as_str() {
+            "ABORT" => Ok(Self::Abort),
+            "SKIP" => Ok(Self::Skip),
+            "" | "OVERWRITE" => Ok(Self::Overwrite),
+            _ => Err(anyhow::anyhow!("unknown import policy: {}", name)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ConfigImportResultType {
+    Created,
+    Updated,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigImportItemResult {
+    pub group: Arc<String>,
+    pub data_id: Arc<String>,
+    pub result: ConfigImportResultType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl ConfigImportItemResult {
+    pub fn new(
+        group: Arc<String>,
+        data_id: Arc<String>,
+        result: ConfigImportResultType,
+        message: Option<String>,
+    ) -> Self {
+        Self {
+            group,
+            data_id,
+            result,
+            message,
+        }
+    }
+}
+
+///
+/// 配置导入结果报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigImportReport {
+    pub policy: ConfigImportPolicy,
+    pub dry_run: bool,
+    pub total: usize,
+    pub created_count: usize,
+    pub updated_count: usize,
+    pub skipped_count: usize,
+    pub failed_count: usize,
+    pub items: Vec<ConfigImportItemResult>,
+}
+
+impl ConfigImportReport {
+    pub fn new(
+        policy: ConfigImportPolicy,
+        dry_run: bool,
+        items: Vec<ConfigImportItemResult>,
+    ) -> Self {
+        let count = |t: ConfigImportResultType| items.iter().filter(|e| e.result == t).count();
+        Self {
+            policy,
+            dry_run,
+            total: items.len(),
+            created_count: count(ConfigImportResultType::Created),
+            updated_count: count(ConfigImportResultType::Updated),
+            skipped_count: count(ConfigImportResultType::Skipped),
+            failed_count: count(ConfigImportResultType::Failed),
+            items,
+        }
+    }
+}
+
+///
+/// 解析导入的zip文件，配置文件路径为`group/dataId`；
+/// 单个配置解析失败时记录到失败列表，不影响其它配置
+pub fn read_config_archive<R: Read + Seek>(
+    reader: R,
+) -> anyhow::Result<(Vec<ConfigArchiveItem>, Vec<ConfigImportItemResult>)> {
+    let mut archive = ZipArchive::new(reader)?;
+    let mut metadata = None;
+    let mut items = Vec::new();
+    let mut failed = Vec::new();
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        if file.is_dir() {
+            continue;
+        }
+        let name = file.name().to_owned();
+        if name == CONFIG_METADATA_FILE {
+            let mut text = String::new();
+            file.read_to_string(&mut text)?;
+            let v: ConfigMetadata = serde_yml::from_str(&text)
+                .map_err(|e| anyhow::anyhow!("{} is invalid,{}", CONFIG_METADATA_FILE, e))?;
+            metadata = Some(v);
+            continue;
+        }
+        let (group, data_id) = match name.split_once('/') {
+            Some((group, data_id)) if !data_id.contains('/') => (group, data_id),
+            // 根目录下的其它文件(如.ignore、.meta.yml)不是配置
+            _ => continue,
+        };
+        let group = Arc::new(group.to_owned());
+        let data_id = Arc::new(data_id.to_owned());
+        if !param_utils::is_valid(&group) || !param_utils::is_valid(&data_id) {
+            failed.push(ConfigImportItemResult::new(
+                group,
+                data_id,
+                ConfigImportResultType::Failed,
+                Some("invalid group or dataId".to_owned()),
+            ));
+            continue;
+        }
+        let mut content = String::new();
+        if let Err(err) = file.read_to_string(&mut content) {
+            failed.push(ConfigImportItemResult::new(
+                group,
+                data_id,
+                ConfigImportResultType::Failed,
+                Some(format!("read content error,{}", err)),
+            ));
+            continue;
+        }
+        items.push(ConfigArchiveItem {
+            group,
+            data_id,
+            content: Arc::new(content),
+            ..Default::default()
+        });
+    }
+    let metadata_map: HashMap<(String, String), ConfigMetadataItem> = metadata
+        .map(|v| v.metadata)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|e| ((e.group.clone(), e.data_id.clone()), e))
+        .collect();
+    let mut list = Vec::with_capacity(items.len());
+    for mut item in items {
+        if let Some(meta) = metadata_map.get(&(
+            item.group.as_ref().to_owned(),
+            item.data_id.as_ref().to_owned(),
+        )) {
+            if let Some(md5) = meta.md5.as_ref().filter(|v| !v.is_empty()) {
+                if *md5 != get_md5(item.content.as_str()) {
+                    failed.push(ConfigImportItemResult::new(
+                        item.group,
+                        item.data_id,
+                        ConfigImportResultType::Failed,
+                        Some("content md5 mismatch".to_owned()),
+                    ));
+                    continue;
+                }
+            }
+            item.config_type = meta
+                .config_type
+                .as_ref()
+                .filter(|v| !v.is_empty())
+                .map(|v| Arc::new(v.to_owned()));
+            item.desc = meta.desc.as_ref().map(|v| Arc::new(v.to_owned()));
+            item.tags = meta
+                .config_tags
+                .as_ref()
+                .map(|v| ConfigUtils::build_tags(v));
+        }
+        list.push(item);
+    }
+    Ok((list, failed))
+}
+
+///
+/// 按`group/dataId`写入配置内容，并在根目录写入元数据清单
+pub fn write_config_archive<W: Write + Seek>(
+    writer: W,
+    list: &[ConfigInfoDto],
+) -> anyhow::Result<()> {
+    let mut zip = ZipWriter::new(writer);
+    let options = FileOptions::default()
+        .compression_method(zip::CompressionMethod::Stored)
+        .unix_permissions(0o755);
+    let mut groups = HashSet::new();
+    let mut metadata = ConfigMetadata::default();
+    for item in list {
+        if groups.insert(item.group.clone()) {
+            zip.add_directory(item.group.as_str(), Default::default())
+                .ok();
+        }
+        let content = item.content.as_ref().map(|v| v.as_str()).unwrap_or("");
+        zip.start_file(
+            format!("{}/{}", &item.group.as_str(), &item.data_id.as_str()),
+            options,
+        )?;
+        zip.write_all(content.as_bytes())?;
+        metadata.metadata.push(ConfigMetadataItem {
+            group: item.group.as_ref().to_owned(),
+            data_id: item.data_id.as_ref().to_owned(),
+            config_type: item.config_type.as_ref().map(|v| v.as_ref().to_owned()),
+            desc: item.desc.as_ref().map(|v| v.as_ref().to_owned()),
+            app_name: None,
+            config_tags: if item.tags.is_empty() {
+                None
+            } else {
+                Some(ConfigUtils::tags_to_string(&item.tags))
+            },
+            md5: Some(get_md5(content)),
+        });
+    }
+    zip.start_file(CONFIG_METADATA_FILE, options)?;
+    zip.write_all(serde_yml::to_string(&metadata)?.as_bytes())?;
+    zip.finish()?;
+    Ok(())
+}
+
+///
+/// 按冲突策略计算每个配置的导入结果；ABORT策略下存在冲突时不导入任何配置
+pub fn plan_config_import(
+    items: &[ConfigArchiveItem],
+    exists: &HashSet<(Arc<String>, Arc<String>)>,
+    policy: ConfigImportPolicy,
+) -> Vec<ConfigImportResultType> {
+    let is_exist = |e: &ConfigArchiveItem| exists.contains(&(e.group.clone(), e.data_id.clone()));
+    let abort = policy == ConfigImportPolicy::Abort && items.iter().any(is_exist);
+    items
+        .iter()
+        .map(|e| match (is_exist(e), policy) {
+            (true, ConfigImportPolicy::Overwrite) => ConfigImportResultType::Updated,
+            (true, ConfigImportPolicy::Skip) => ConfigImportResultType::Skipped,
+            (true, ConfigImportPolicy::Abort) => ConfigImportResultType::Failed,
+            (false, _) if abort => ConfigImportResultType::Skipped,
+            (false, _) => ConfigImportResultType::Created,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn build_info(group: &str, data_id: &str, content: &str) -> ConfigInfoDto {
+        ConfigInfoDto {
+            group: Arc::new(group.to_owned()),
+            data_id: Arc::new(data_id.to_owned()),
+            content: Some(Arc::new(content.to_owned())),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn config_archive_round_trip() {
+        let mut info = build_info("DEFAULT_GROUP", "app.yaml", "a: 1");
+        info.config_type = Some(Arc::new("yaml".to_owned()));
+        info.desc = Some(Arc::new("app config".to_owned()));
+        info.tags = vec![Arc::new("t1".to_owned()), Arc::new("t2".to_owned())];
+        let list = vec![info, build_info("dev", "db.properties", "k=v")];
+        let mut buf = Cursor::new(Vec::new());
+        write_config_archive(&mut buf, &list).unwrap();
+        buf.set_position(0);
+        let (items, failed) = read_config_archive(buf).unwrap();
+        assert!(failed.is_empty());
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].data_id.as_str(), "app.yaml");
+        assert_eq!(items[0].content.as_str(), "a: 1");
+        assert_eq!(items[0].config_type.as_ref().unwrap().as_str(), "yaml");
+        assert_eq!(items[0].desc.as_ref().unwrap().as_str(), "app config");
+        assert_eq!(items[0].tags.as_ref().unwrap().len(), 2);
+        assert!(items[1].config_type.is_none());
+        assert!(read_config_archive(Cursor::new(b"not zip".to_vec())).is_err());
+    }
+
+    #[test]
+    fn config_import_plan() {
+        let items: Vec<ConfigArchiveItem> = ["a", "b"]
+            .iter()
+            .map(|e| ConfigArchiveItem {
+                group: Arc::new("g".to_owned()),
+                data_id: Arc::new(e.to_string()),
+                ..Default::default()
+            })
+            .collect();
+        let exists = HashSet::from([(Arc::new("g".to_owned()), Arc::new("a".to_owned()))]);
+        use ConfigImportResultType::*;
+        assert_eq!(
+            plan_config_import(&items, &exists, ConfigImportPolicy::Overwrite),
+            vec![Updated, Created]
+        );
+        assert_eq!(
+            plan_config_import(&items, &exists, ConfigImportPolicy::Skip),
+            vec![Skipped, Created]
+        );
+        assert_eq!(
+            plan_config_import(&items, &exists, ConfigImportPolicy::Abort),
+            vec![Failed, Skipped]
+        );
+        assert_eq!(
+            plan_config_import(&items, &HashSet::new(), ConfigImportPolicy::Abort),
+            vec![Created, Created]
+        );
+    }
+}
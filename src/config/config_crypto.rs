@@ -0,0 +1,355 @@
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+
+use rand::Rng;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::{hkdf, pbkdf2};
+
+use crate::common::crypto_utils::{decode_base64, encode_base64};
+use crate::common::AppSysConfig;
+use crate::config::core::ConfigKey;
+use crate::utils::get_md5;
+
+/// 加密后的配置内容前缀，格式: rnacos_enc:{key_id}:{base64(salt+nonce+密文+tag)}
+pub const ENCRYPT_CONTENT_PREFIX: &str = "rnacos_enc:";
+
+/// 不支持客户端加密插件上传的配置内容
+pub const ENCRYPTED_DATA_KEY_UNSUPPORTED: &str =
+    "encryptedDataKey is not supported, config content is encrypted on server side";
+
+const KEY_DERIVE_SALT: &[u8] = b"r-nacos-config-encrypt";
+const KEY_DERIVE_ITERATIONS: u32 = 100_000;
+const CONTENT_KEY_INFO: &[u8] = b"r-nacos-config-content";
+const CONTENT_SALT_LEN: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct ConfigCryptoKey {
+    pub id: Arc<String>,
+    key: [u8; 32],
+}
+
+impl ConfigCryptoKey {
+    /// 主密钥通过PBKDF2-HMAC-SHA256派生，前32字节作为根密钥，后4字节用于生成密钥id
+    pub fn new(master_key: &str) -> Self {
+        let mut derived = [0u8; 36];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            NonZeroU32::new(KEY_DERIVE_ITERATIONS).unwrap(),
+            KEY_DERIVE_SALT,
+            master_key.as_bytes(),
+            &mut derived,
+        );
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&derived[..32]);
+        let id: String = derived[32..].iter().map(|b| format!("{:02x}", b)).collect();
+        Self {
+            id: Arc::new(id),
+            key,
+        }
+    }
+
+    /// 每次加密使用随机salt经HKDF从根密钥派生AES-256-GCM密钥
+    fn content_key(&self, salt: &[u8]) -> anyhow::Result<LessSafeKey> {
+        let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, salt).extract(&self.key);
+        let okm = prk
+            .expand(&[CONTENT_KEY_INFO], &AES_256_GCM)
+            .map_err(|_| anyhow::anyhow!("derive config content key error"))?;
+        Ok(LessSafeKey::new(UnboundKey::from(okm)))
+    }
+}
+
+/// 配置标识作为附加认证数据，密文不能被挪用到其它配置
+fn build_aad(key: &ConfigKey) -> String {
+    format!("{}\n{}\n{}", &key.tenant, &key.group, &key.data_id)
+}
+
+///
+/// 配置内容落盘加密(AES-256-GCM)
+/// 配置内容在进入raft前加密，对外提供查询时解密；md5按明文计算，保证客户端监听一致
+#[derive(Debug, Clone, Default)]
+pub struct ConfigCryptoManager {
+    data_id_prefix: Arc<String>,
+    current_key: Option<ConfigCryptoKey>,
+    keys: HashMap<String, ConfigCryptoKey>,
+}
+
+impl ConfigCryptoManager {
+    pub fn new(data_id_prefix: Arc<String>, master_key: &str, old_keys: &[String]) -> Self {
+        let mut keys = HashMap::new();
+        for item in old_keys {
+            let key = ConfigCryptoKey::new(item);
+            keys.insert(key.id.to_string(), key);
+        }
+        let current_key = if master_key.is_empty() {
+            None
+        } else {
+            let key = ConfigCryptoKey::new(master_key);
+            keys.insert(key.id.to_string(), key.clone());
+            Some(key)
+        };
+        Self {
+            data_id_prefix,
+            current_key,
+            keys,
+        }
+    }
+
+    pub fn new_by_sys_config(sys_config: &AppSysConfig) -> Self {
+        Self::new(
+            sys_config.config_encrypt_data_id_prefix.clone(),
+            &sys_config.config_encrypt_key,
+            &sys_config.config_encrypt_old_keys,
+        )
+    }
+
+    pub fn is_enable(&self) -> bool {
+        self.current_key.is_some()
+    }
+
+    pub fn need_encrypt(&self, data_id: &str) -> bool {
+        self.is_enable()
+            && !self.data_id_prefix.is_empty()
+            && data_id.starts_with(self.data_id_prefix.as_str())
+    }
+
+    pub fn is_encrypted(content: &str) -> bool {
+        content.starts_with(ENCRYPT_CONTENT_PREFIX)
+    }
+
+    pub fn encrypt(&self, config_key: &ConfigKey, plain: &str) -> anyhow::Result<String> {
+        let key = self
+            .current_key
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("config encrypt key is not set"))?;
+        let mut header = [0u8; CONTENT_SALT_LEN + NONCE_LEN];
+        rand::thread_rng().fill(&mut header[..]);
+        let (salt, nonce) = header.split_at(CONTENT_SALT_LEN);
+        let mut data = plain.as_bytes().to_vec();
+        key.content_key(salt)?
+            .seal_in_place_append_tag(
+                Nonce::try_assume_unique_for_key(nonce)
+                    .map_err(|_| anyhow::anyhow!("config encrypt nonce error"))?,
+                Aad::from(build_aad(config_key).as_bytes()),
+                &mut data,
+            )
+            .map_err(|_| anyhow::anyhow!("config encrypt error"))?;
+        let mut buf = header.to_vec();
+        buf.extend_from_slice(&data);
+        Ok(format!(
+            "{}{}:{}",
+            ENCRYPT_CONTENT_PREFIX,
+            &key.id,
+            encode_base64(&buf)
+        ))
+    }
+
+    pub fn decrypt(&self, config_key: &ConfigKey, content: &str) -> anyhow::Result<String> {
+        let body = content
+            .strip_prefix(ENCRYPT_CONTENT_PREFIX)
+            .ok_or_else(|| anyhow::anyhow!("content is not encrypted"))?;
+        let (key_id, data) = body
+            .split_once(':')
+            .ok_or_else(|| anyhow::anyhow!("encrypted content format error"))?;
+        let key = self
+            .keys
+            .get(key_id)
+            .ok_or_else(|| anyhow::anyhow!("config encrypt key {} not found", key_id))?;
+        let mut data = decode_base64(data)?;
+        if data.len() < CONTENT_SALT_LEN + NONCE_LEN {
+            return Err(anyhow::anyhow!("encrypted content format error"));
+        }
+        let mut cipher = data.split_off(CONTENT_SALT_LEN + NONCE_LEN);
+        let (salt, nonce) = data.split_at(CONTENT_SALT_LEN);
+        let plain = key
+            .content_key(salt)?
+            .open_in_place(
+                Nonce::try_assume_unique_for_key(nonce)
+                    .map_err(|_| anyhow::anyhow!("encrypted content format error"))?,
+                Aad::from(build_aad(config_key).as_bytes()),
+                &mut cipher,
+            )
+            .map_err(|_| anyhow::anyhow!("config content authentication failed"))?;
+        Ok(String::from_utf8(plain.to_vec())?)
+    }
+
+    /// 写入raft前按dataId规则加密；发布时已拒绝带加密前缀的内容，这里不再跳过
+    pub fn encrypt_content(
+        &self,
+        config_key: &ConfigKey,
+        content: Arc<String>,
+    ) -> anyhow::Result<Arc<String>> {
+        if !self.need_encrypt(&config_key.data_id) {
+            return Ok(content);
+        }
+        Ok(Arc::new(self.encrypt(config_key, &content)?))
+    }
+
+    /// 对外提供配置内容时解密，只处理需要加密的dataId；
+    /// 开启加密前写入的明文原样返回，解密失败时返回错误，不能把密文当作配置内容
+    pub fn decrypt_content(
+        &self,
+        config_key: &ConfigKey,
+        content: &Arc<String>,
+    ) -> anyhow::Result<Arc<String>> {
+        if !self.need_encrypt(&config_key.data_id) || !Self::is_encrypted(content) {
+            return Ok(content.clone());
+        }
+        self.decrypt(config_key, content)
+            .map(Arc::new)
+            .map_err(|err| anyhow::anyhow!("decrypt config content error,{}", err))
+    }
+
+    /// 按明文计算md5
+    pub fn content_md5(
+        &self,
+        config_key: &ConfigKey,
+        content: &Arc<String>,
+    ) -> anyhow::Result<Arc<String>> {
+        Ok(Arc::new(get_md5(
+            &self.decrypt_content(config_key, content)?,
+        )))
+    }
+
+    /// raft apply时计算配置版本md5。
+    /// 本节点密钥配置错误导致解密失败时仍需保持状态一致，此时用密文md5作为版本标记；
+    /// 这类配置读取时会返回解密错误，不会把密文下发给客户端
+    pub fn version_md5(&self, config_key: &ConfigKey, content: &Arc<String>) -> Arc<String> {
+        match self.content_md5(config_key, content) {
+            Ok(v) => v,
+            Err(err) => {
+                log::error!("{}, the stored content can not be served", err);
+                Arc::new(get_md5(content))
+            }
+        }
+    }
+
+    /// 是否需要用当前密钥重新加密
+    pub fn need_rotate(&self, data_id: &str, content: &str) -> bool {
+        if !self.need_encrypt(data_id) {
+            return false;
+        }
+        match (
+            content.strip_prefix(ENCRYPT_CONTENT_PREFIX),
+            &self.current_key,
+        ) {
+            (Some(body), Some(key)) => !body.starts_with(&format!("{}:", &key.id)),
+            _ => true,
+        }
+    }
+
+    /// 用当前密钥重新加密
+    pub fn re_encrypt(
+        &self,
+        config_key: &ConfigKey,
+        content: &Arc<String>,
+    ) -> anyhow::Result<Arc<String>> {
+        let plain = if Self::is_encrypted(content) {
+            self.decrypt(config_key, content)?
+        } else {
+            content.as_ref().to_owned()
+        };
+        Ok(Arc::new(self.encrypt(config_key, &plain)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_and_rotate() {
+        let prefix = Arc::new("cipher-".to_owned());
+        let old = ConfigCryptoManager::new(prefix.clone(), "old_key", &[]);
+        let key = ConfigKey::new("cipher-app", "DEFAULT_GROUP", "");
+        let plain_key = ConfigKey::new("app", "DEFAULT_GROUP", "");
+        let content = Arc::new("a=1".to_owned());
+        let encrypted = old.encrypt_content(&key, content.clone()).unwrap();
+        assert!(ConfigCryptoManager::is_encrypted(&encrypted));
+        assert_eq!(
+            old.encrypt_content(&plain_key, content.clone()).unwrap(),
+            content
+        );
+        assert_eq!(old.decrypt_content(&key, &encrypted).unwrap(), content);
+        assert_eq!(
+            old.content_md5(&key, &encrypted).unwrap().as_str(),
+            get_md5(&content)
+        );
+        //相同内容每次加密结果不同
+        assert_ne!(
+            old.encrypt_content(&key, content.clone()).unwrap(),
+            encrypted
+        );
+
+        let current = ConfigCryptoManager::new(prefix, "new_key", &["old_key".to_owned()]);
+        assert!(current.need_rotate("cipher-app", &encrypted));
+        let rotated = current.re_encrypt(&key, &encrypted).unwrap();
+        assert!(!current.need_rotate("cipher-app", &rotated));
+        assert_eq!(current.decrypt_content(&key, &rotated).unwrap(), content);
+    }
+
+    #[test]
+    fn derive_key() {
+        let a = ConfigCryptoKey::new("master_key");
+        let b = ConfigCryptoKey::new("master_key");
+        let c = ConfigCryptoKey::new("other_key");
+        assert_eq!(a.key, b.key);
+        assert_eq!(a.id, b.id);
+        assert_ne!(a.key, c.key);
+        assert_eq!(a.id.len(), 8);
+        //派生密钥不再是md5十六进制文本
+        assert_ne!(&a.key[..16], &get_md5("master_key").as_bytes()[..16]);
+    }
+
+    #[test]
+    fn decrypt_with_wrong_key() {
+        let prefix = Arc::new("cipher-".to_owned());
+        let key = ConfigKey::new("cipher-app", "DEFAULT_GROUP", "");
+        let content = Arc::new("a=1".to_owned());
+        let manager = ConfigCryptoManager::new(prefix.clone(), "key_a", &[]);
+        let encrypted = manager.encrypt_content(&key, content).unwrap();
+        //轮换后丢失旧密钥
+        let other = ConfigCryptoManager::new(prefix, "key_b", &[]);
+        assert!(other.decrypt_content(&key, &encrypted).is_err());
+        assert!(other.content_md5(&key, &encrypted).is_err());
+        //损坏的密文
+        let broken = Arc::new(format!("{}x", encrypted.as_str()));
+        assert!(manager.decrypt_content(&key, &broken).is_err());
+        let body = encrypted.strip_prefix(ENCRYPT_CONTENT_PREFIX).unwrap();
+        let (key_id, data) = body.split_once(':').unwrap();
+        let mut data = decode_base64(data).unwrap();
+        let last = data.len() - 1;
+        data[last] ^= 1;
+        let tampered = Arc::new(format!(
+            "{}{}:{}",
+            ENCRYPT_CONTENT_PREFIX,
+            key_id,
+            encode_base64(&data)
+        ));
+        assert!(manager.decrypt_content(&key, &tampered).is_err());
+        assert!(manager
+            .decrypt_content(&key, &Arc::new("plain".to_owned()))
+            .is_ok());
+    }
+
+    #[test]
+    fn decrypt_bind_config_key() {
+        let prefix = Arc::new("cipher-".to_owned());
+        let manager = ConfigCryptoManager::new(prefix, "key_a", &[]);
+        let key = ConfigKey::new("cipher-app", "DEFAULT_GROUP", "");
+        let encrypted = manager
+            .encrypt_content(&key, Arc::new("a=1".to_owned()))
+            .unwrap();
+        //密文挪用到其它配置时认证失败
+        let other_key = ConfigKey::new("cipher-other", "DEFAULT_GROUP", "");
+        assert!(manager.decrypt_content(&other_key, &encrypted).is_err());
+        let other_tenant = ConfigKey::new("cipher-app", "DEFAULT_GROUP", "dev");
+        assert!(manager.decrypt_content(&other_tenant, &encrypted).is_err());
+        //不需要加密的dataId不解密
+        let plain_key = ConfigKey::new("app", "DEFAULT_GROUP", "");
+        assert_eq!(
+            manager.decrypt_content(&plain_key, &encrypted).unwrap(),
+            encrypted
+        );
+    }
+}
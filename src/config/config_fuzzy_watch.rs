@@ -0,0 +1,197 @@
+use std::{collections::HashSet, sync::Arc};
+
+use super::core::ConfigKey;
+use crate::common::fuzzy_watch::{
+    FuzzyWatchIndex, FuzzyWatchParam, FuzzyWatchPattern, ADD_CONFIG, DELETE_CONFIG,
+    FINISH_FUZZY_WATCH_INIT_NOTIFY, FUZZY_WATCH_DIFF_SYNC_NOTIFY, FUZZY_WATCH_INIT_NOTIFY,
+    FUZZY_WATCH_SYNC_BATCH_SIZE,
+};
+use crate::grpc::api_model::{
+    ConfigFuzzyWatchChangeNotifyRequest, ConfigFuzzyWatchContext, ConfigFuzzyWatchSyncRequest,
+};
+use crate::grpc::bistream_manage::{BiStreamManage, BiStreamManageCmd, FuzzyWatchNotify};
+use actix::prelude::*;
+
+///
+/// 配置模糊监听，按模式匹配配置的新增、删除与变更
+#[derive(Default)]
+pub struct ConfigFuzzyWatcher {
+    index: FuzzyWatchIndex,
+    conn_manage: Option<Addr<BiStreamManage>>,
+}
+
+impl ConfigFuzzyWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_conn_manage(&mut self, conn_manage: Addr<BiStreamManage>) {
+        self.conn_manage = Some(conn_manage);
+    }
+
+    ///
+    /// 添加模糊监听，并把当前匹配的配置与客户端已有的配置差异同步给客户端
+    pub fn watch<'a>(
+        &mut self,
+        client_id: Arc<String>,
+        param: FuzzyWatchParam,
+        keys: impl Iterator<Item = &'a ConfigKey>,
+    ) -> anyhow::Result<()> {
+        let pattern = FuzzyWatchPattern::parse(param.pattern.clone())
+            .ok_or_else(|| anyhow::anyhow!("invalid groupKeyPattern: {}", &param.pattern))?;
+        self.index.add_watch(client_id.clone(), pattern.clone())?;
+        let mut server_keys = HashSet::new();
+        let mut contexts = vec![];
+        for key in keys {
+            if !pattern.is_match(&key.tenant, &key.group, &key.data_id) {
+                continue;
+            }
+            let group_key = build_group_key(key, &pattern.namespace);
+            if !param.received_keys.contains(&group_key) {
+                contexts.push(ConfigFuzzyWatchContext {
+                    group_key: Arc::new(group_key.clone()),
+                    changed_type: ADD_CONFIG,
+                });
+            }
+            server_keys.insert(group_key);
+        }
+        for group_key in &param.received_keys {
+            if !server_keys.contains(group_key) {
+                contexts.push(ConfigFuzzyWatchContext {
+                    group_key: Arc::new(group_key.to_owned()),
+                    changed_type: DELETE_CONFIG,
+                });
+            }
+        }
+        self.sync(client_id, pattern.pattern, contexts, param.initializing);
+        Ok(())
+    }
+
+    fn sync(
+        &self,
+        client_id: Arc<String>,
+        pattern: Arc<String>,
+        contexts: Vec<ConfigFuzzyWatchContext>,
+        initializing: bool,
+    ) {
+        let conn_manage = if let Some(v) = &self.conn_manage {
+            v
+        } else {
+            return;
+        };
+        let mut client_set = HashSet::new();
+        client_set.insert(client_id);
+        let sync_type = if initializing {
+            FUZZY_WATCH_INIT_NOTIFY
+        } else {
+            FUZZY_WATCH_DIFF_SYNC_NOTIFY
+        };
+        let total_batch = contexts.len().div_ceil(FUZZY_WATCH_SYNC_BATCH_SIZE);
+        for (i, batch) in contexts.chunks(FUZZY_WATCH_SYNC_BATCH_SIZE).enumerate() {
+            let request = ConfigFuzzyWatchSyncRequest {
+                group_key_pattern: pattern.clone(),
+                contexts: batch.to_vec(),
+                total_batch,
+                current_batch: i + 1,
+                sync_type,
+                ..Default::default()
+            };
+            conn_manage.do_send(BiStreamManageCmd::NotifyFuzzyWatch(
+                client_set.clone(),
+                FuzzyWatchNotify::ConfigSync(request),
+            ));
+        }
+        if initializing {
+            let request = ConfigFuzzyWatchSyncRequest {
+                group_key_pattern: pattern,
+                sync_type: FINISH_FUZZY_WATCH_INIT_NOTIFY,
+                ..Default::default()
+            };
+            conn_manage.do_send(BiStreamManageCmd::NotifyFuzzyWatch(
+                client_set,
+                FuzzyWatchNotify::ConfigSync(request),
+            ));
+        }
+    }
+
+    pub fn cancel_watch(&mut self, client_id: &Arc<String>, pattern: &Arc<String>) {
+        self.index.remove_watch(client_id, pattern);
+    }
+
+    pub fn remove_client(&mut self, client_id: &Arc<String>) {
+        self.index.remove_client(client_id);
+    }
+
+    pub fn notify(&self, key: &ConfigKey, changed_type: &'static str) {
+        if self.index.is_empty() {
+            return;
+        }
+        if let Some(conn_manage) = &self.conn_manage {
+            for (namespace, client_set) in
+                self.index
+                    .match_clients(&key.tenant, &key.group, &key.data_id)
+            {
+                let request = ConfigFuzzyWatchChangeNotifyRequest {
+                    group_key: Arc::new(build_group_key(key, &namespace)),
+                    change_type: changed_type,
+                    ..Default::default()
+                };
+                conn_manage.do_send(BiStreamManageCmd::NotifyFuzzyWatch(
+                    client_set,
+                    FuzzyWatchNotify::ConfigChange(request),
+                ));
+            }
+        }
+    }
+
+    pub fn get_pattern_size(&self) -> usize {
+        self.index.get_pattern_size()
+    }
+
+    pub fn get_client_size(&self) -> usize {
+        self.index.get_client_size()
+    }
+}
+
+///
+/// 按nacos GroupKey格式生成配置key: `dataId+group+tenant`，默认命名空间使用客户端监听时的值
+pub fn build_group_key(key: &ConfigKey, namespace: &str) -> String {
+    let tenant = if key.tenant.is_empty() {
+        namespace
+    } else {
+        key.tenant.as_str()
+    };
+    let mut group_key = String::new();
+    encode_group_key_item(&key.data_id, &mut group_key);
+    group_key.push('+');
+    encode_group_key_item(&key.group, &mut group_key);
+    if !tenant.is_empty() {
+        group_key.push('+');
+        encode_group_key_item(tenant, &mut group_key);
+    }
+    group_key
+}
+
+fn encode_group_key_item(v: &str, buf: &mut String) {
+    for c in v.chars() {
+        match c {
+            '+' => buf.push_str("%2B"),
+            '%' => buf.push_str("%25"),
+            _ => buf.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_group_key_test() {
+        let key = ConfigKey::new("app.yaml", "routes-a", "");
+        assert_eq!(build_group_key(&key, ""), "app.yaml+routes-a");
+        assert_eq!(build_group_key(&key, "public"), "app.yaml+routes-a+public");
+        let key = ConfigKey::new("a+b%", "g", "dev");
+        assert_eq!(build_group_key(&key, "dev"), "a%2Bb%25+g+dev");
+    }
+}
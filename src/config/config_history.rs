@@ -0,0 +1,212 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use actix::Addr;
+use similar::TextDiff;
+
+use crate::common::constant::CONFIG_HISTORY_TREE_NAME;
+use crate::config::core::ConfigKey;
+use crate::config::model::{ConfigHistoryItemDO, HistoryItem};
+use crate::raft::filestore::model::SnapshotRecordDto;
+use crate::raft::filestore::raftsnapshot::{SnapshotWriterActor, SnapshotWriterRequest};
+
+/// 内存中每个配置保留的最近历史记录条数，更早的记录归档到历史表
+pub const MEMORY_HISTORY_MAX_COUNT: usize = 100;
+
+/// 命名空间未设置时默认保留的历史记录条数
+pub const DEFAULT_HISTORY_MAX_COUNT: u32 = 100;
+
+const DAY_MILLIS: i64 = 24 * 60 * 60 * 1000;
+
+///
+/// 配置历史记录保留策略
+/// max_count为0表示不限制条数，max_days为0表示不限制保留天数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigHistoryRetention {
+    pub max_count: u32,
+    pub max_days: u32,
+}
+
+impl Default for ConfigHistoryRetention {
+    fn default() -> Self {
+        Self {
+            max_count: DEFAULT_HISTORY_MAX_COUNT,
+            max_days: 0,
+        }
+    }
+}
+
+impl ConfigHistoryRetention {
+    pub fn new(max_count: Option<u32>, max_days: Option<u32>) -> Self {
+        Self {
+            max_count: max_count.unwrap_or(DEFAULT_HISTORY_MAX_COUNT),
+            max_days: max_days.unwrap_or_default(),
+        }
+    }
+}
+
+///
+/// 配置历史归档表
+/// 保存超出内存条数的历史记录，随raft snapshot持久化
+#[derive(Default)]
+pub struct ConfigHistoryArchive {
+    data: HashMap<ConfigKey, BTreeMap<u64, HistoryItem>>,
+    retention: HashMap<Arc<String>, ConfigHistoryRetention>,
+}
+
+impl ConfigHistoryArchive {
+    pub fn set_retention(&mut self, tenant: Arc<String>, retention: ConfigHistoryRetention) {
+        if retention == ConfigHistoryRetention::default() {
+            self.retention.remove(&tenant);
+        } else {
+            self.retention.insert(tenant, retention);
+        }
+    }
+
+    pub fn get_retention(&self, tenant: &Arc<String>) -> ConfigHistoryRetention {
+        self.retention.get(tenant).cloned().unwrap_or_default()
+    }
+
+    pub fn archive(&mut self, key: &ConfigKey, item: HistoryItem) {
+        if let Some(list) = self.data.get_mut(key) {
+            list.insert(item.id, item);
+        } else {
+            let mut list = BTreeMap::new();
+            list.insert(item.id, item);
+            self.data.insert(key.clone(), list);
+        }
+    }
+
+    ///
+    /// 按命名空间保留策略清理历史记录，当前版本始终保留
+    /// now使用raft日志中的操作时间，保证各节点清理结果一致
+    pub fn apply_retention(&mut self, key: &ConfigKey, histories: &mut Vec<HistoryItem>, now: i64) {
+        let retention = self.get_retention(&key.tenant);
+        let mut archived = self.data.remove(key).unwrap_or_default();
+        if retention.max_count > 0 {
+            let max_count = retention.max_count as usize;
+            while archived.len() + histories.len() > max_count {
+                if archived.pop_first().is_none() {
+                    if histories.len() <= 1 {
+                        break;
+                    }
+                    histories.remove(0);
+                }
+            }
+        }
+        if retention.max_days > 0 {
+            let expire_time = now - retention.max_days as i64 * DAY_MILLIS;
+            archived.retain(|_, v| v.modified_time >= expire_time);
+            while histories.len() > 1 && histories[0].modified_time < expire_time {
+                histories.remove(0);
+            }
+        }
+        if !archived.is_empty() {
+            self.data.insert(key.clone(), archived);
+        }
+    }
+
+    pub fn remove(&mut self, key: &ConfigKey) {
+        self.data.remove(key);
+    }
+
+    pub fn len(&self, key: &ConfigKey) -> usize {
+        self.data.get(key).map(|v| v.len()).unwrap_or_default()
+    }
+
+    pub fn get(&self, key: &ConfigKey, id: u64) -> Option<&HistoryItem> {
+        self.data.get(key).and_then(|v| v.get(&id))
+    }
+
+    /// 按从新到旧的顺序遍历归档记录
+    pub fn iter_rev(&self, key: &ConfigKey) -> impl Iterator<Item = &HistoryItem> {
+        self.data
+            .get(key)
+            .into_iter()
+            .flat_map(|v| v.values().rev())
+    }
+
+    pub fn build_snapshot(&self, writer: &Addr<SnapshotWriterActor>) -> anyhow::Result<()> {
+        for (key, list) in &self.data {
+            let config_key = key.build_key();
+            for item in list.values() {
+                let value_db: ConfigHistoryItemDO = item.clone().into();
+                let record = SnapshotRecordDto {
+                    tree: CONFIG_HISTORY_TREE_NAME.clone(),
+                    key: format!("{}\x03{}", &config_key, item.id).into_bytes(),
+                    value: value_db.to_bytes()?,
+                    op_type: 0,
+                };
+                writer.do_send(SnapshotWriterRequest::Record(record));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn load_snapshot_record(&mut self, record: SnapshotRecordDto) -> anyhow::Result<()> {
+        let key = String::from_utf8(record.key)?;
+        let config_key: ConfigKey = key.split('\x03').next().unwrap_or_default().into();
+        let item: HistoryItem = ConfigHistoryItemDO::from_bytes(&record.value)?.into();
+        self.archive(&config_key, item);
+        Ok(())
+    }
+}
+
+///
+/// 生成两个版本内容的unified diff
+pub fn build_unified_diff(from: &str, to: &str, from_name: &str, to_name: &str) -> String {
+    TextDiff::from_lines(from, to)
+        .unified_diff()
+        .context_radius(3)
+        .header(from_name, to_name)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_item(id: u64, modified_time: i64) -> HistoryItem {
+        HistoryItem {
+            id,
+            content: Arc::new(format!("v{}", id)),
+            modified_time,
+            op_user: None,
+            note: None,
+        }
+    }
+
+    #[test]
+    fn apply_retention() {
+        let key = ConfigKey::new("app", "DEFAULT_GROUP", "dev");
+        let mut archive = ConfigHistoryArchive::default();
+        archive.set_retention(
+            key.tenant.clone(),
+            ConfigHistoryRetention::new(Some(3), None),
+        );
+        for id in 1..=3 {
+            archive.archive(&key, build_item(id, id as i64 * DAY_MILLIS));
+        }
+        let mut histories = vec![build_item(4, 4 * DAY_MILLIS), build_item(5, 5 * DAY_MILLIS)];
+        archive.apply_retention(&key, &mut histories, 5 * DAY_MILLIS);
+        assert_eq!(archive.len(&key), 1);
+        assert!(archive.get(&key, 3).is_some());
+        assert_eq!(histories.len(), 2);
+
+        archive.set_retention(
+            key.tenant.clone(),
+            ConfigHistoryRetention::new(Some(0), Some(1)),
+        );
+        archive.apply_retention(&key, &mut histories, 10 * DAY_MILLIS);
+        assert_eq!(archive.len(&key), 0);
+        assert_eq!(histories.len(), 1);
+        assert_eq!(histories[0].id, 5);
+    }
+
+    #[test]
+    fn unified_diff() {
+        let diff = build_unified_diff("a=1\nb=2\n", "a=1\nb=3\n", "1", "2");
+        assert!(diff.contains("-b=2"));
+        assert!(diff.contains("+b=3"));
+    }
+}
@@ -3,9 +3,10 @@ use crate::common::string_utils::StringUtils;
 use crate::config::core::ConfigKey;
 use crate::namespace::model::{NamespaceActorReq, WeakNamespaceFromType, WeakNamespaceParam};
 use crate::namespace::NamespaceActor;
+use crate::user::permission::PermissionScopeFilter;
 use actix::Addr;
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     sync::Arc,
 };
 
@@ -18,13 +19,23 @@ pub struct ConfigQueryParam {
     pub data_id: Option<Arc<String>>,
     pub like_group: Option<String>,
     pub like_data_id: Option<String>,
+    /// 标签过滤，命中任意一个标签即可
+    pub tags: Vec<Arc<String>>,
     pub namespace_privilege: NamespacePrivilegeGroup,
+    /// 自定义角色授权的数据范围，为None时不限制
+    pub scope_filter: Option<Arc<PermissionScopeFilter>>,
     pub query_context: bool,
     pub offset: usize,
     pub limit: usize,
 }
 
 impl ConfigQueryParam {
+    pub fn match_scope(&self, tenant: &str, group: &str, data_id: &str) -> bool {
+        self.scope_filter
+            .as_ref()
+            .is_none_or(|v| v.is_match(tenant, group, data_id))
+    }
+
     pub fn match_group(&self, g: &Arc<String>) -> bool {
         if let Some(group) = &self.group {
             group.is_empty() || StringUtils::eq(g, group)
@@ -45,9 +56,13 @@ impl ConfigQueryParam {
     }
 }
 
+type TagGroupIndex = HashMap<Arc<String>, HashSet<Arc<String>>>;
+
 #[derive(Debug, Clone, Default)]
 pub struct ConfigIndex {
     pub(crate) group_data: BTreeMap<Arc<String>, BTreeSet<Arc<String>>>,
+    /// tag -> group -> data_id
+    pub(crate) tag_data: HashMap<Arc<String>, TagGroupIndex>,
 }
 
 impl ConfigIndex {
@@ -86,6 +101,56 @@ impl ConfigIndex {
         (b, self.group_data.len())
     }
 
+    pub(crate) fn insert_tags(
+        &mut self,
+        group: &Arc<String>,
+        config: &Arc<String>,
+        tags: &[Arc<String>],
+    ) {
+        for tag in tags {
+            self.tag_data
+                .entry(tag.clone())
+                .or_default()
+                .entry(group.clone())
+                .or_default()
+                .insert(config.clone());
+        }
+    }
+
+    pub(crate) fn remove_tags(
+        &mut self,
+        group: &Arc<String>,
+        config: &Arc<String>,
+        tags: &[Arc<String>],
+    ) {
+        for tag in tags {
+            if let Some(group_map) = self.tag_data.get_mut(tag) {
+                if let Some(set) = group_map.get_mut(group) {
+                    set.remove(config);
+                    if set.is_empty() {
+                        group_map.remove(group);
+                    }
+                }
+                if group_map.is_empty() {
+                    self.tag_data.remove(tag);
+                }
+            }
+        }
+    }
+
+    fn match_tags(&self, tags: &[Arc<String>], group: &Arc<String>, config: &Arc<String>) -> bool {
+        if tags.is_empty() {
+            return true;
+        }
+        tags.iter().any(|tag| {
+            self.tag_data
+                .get(tag)
+                .and_then(|group_map| group_map.get(group))
+                .map(|set| set.contains(config))
+                .unwrap_or(false)
+        })
+    }
+
     pub(crate) fn query_config_page(
         &self,
         tenant: &Arc<String>,
@@ -98,7 +163,10 @@ impl ConfigIndex {
         for (g, set) in &self.group_data {
             if param.match_group(g) {
                 for s in set {
-                    if param.match_data_id(s) {
+                    if param.match_data_id(s)
+                        && self.match_tags(&param.tags, g, s)
+                        && param.match_scope(tenant, g, s)
+                    {
                         if index >= param.offset && index < end_index {
                             let key = ConfigKey::new_by_arc(s.clone(), g.clone(), tenant.clone());
                             rlist.push(key);
@@ -197,6 +265,23 @@ impl TenantIndex {
         result
     }
 
+    ///
+    /// 更新配置标签索引，配置需要已在索引中
+    pub fn update_config_tags(
+        &mut self,
+        key: &ConfigKey,
+        old_tags: &[Arc<String>],
+        new_tags: &[Arc<String>],
+    ) {
+        if old_tags == new_tags {
+            return;
+        }
+        if let Some(config_index) = self.tenant_group.get_mut(&key.tenant) {
+            config_index.remove_tags(&key.group, &key.data_id, old_tags);
+            config_index.insert_tags(&key.group, &key.data_id, new_tags);
+        }
+    }
+
     fn notify_namespace_change(&self, param: WeakNamespaceParam, is_remove: bool) {
         if SYSCONFIG_NAMESPACE == param.namespace_id.as_str() {
             //历史系统命名空间跳过
@@ -315,3 +400,37 @@ fn query_service() {
     assert!(size == 0);
     assert!(list.is_empty());
 }
+
+#[test]
+fn query_by_tags() {
+    let mut index = TenantIndex::new();
+    let key1 = ConfigKey::new("1", "1", "1");
+    let key2 = ConfigKey::new("2", "1", "1");
+    let tag_a = Arc::new("a".to_owned());
+    let tag_b = Arc::new("b".to_owned());
+    index.insert_config(key1.clone());
+    index.insert_config(key2.clone());
+    index.update_config_tags(&key1, &[], &[tag_a.clone()]);
+    index.update_config_tags(&key2, &[], &[tag_a.clone(), tag_b.clone()]);
+
+    let mut param = ConfigQueryParam {
+        tenant: Some(Arc::new("1".to_owned())),
+        tags: vec![tag_b.clone()],
+        limit: 0xffff_ffff,
+        ..ConfigQueryParam::default()
+    };
+    let (size, list) = index.query_config_page(&param);
+    assert_eq!(size, 1);
+    assert_eq!(list[0], key2);
+
+    param.tags = vec![tag_a.clone()];
+    let (size, _) = index.query_config_page(&param);
+    assert_eq!(size, 2);
+
+    index.update_config_tags(&key2, &[tag_a.clone(), tag_b.clone()], &[]);
+    let (size, _) = index.query_config_page(&param);
+    assert_eq!(size, 1);
+    param.tags = vec![tag_b];
+    let (size, _) = index.query_config_page(&param);
+    assert_eq!(size, 0);
+}
@@ -4,13 +4,16 @@ use std::{
 };
 
 use super::core::{ConfigKey, ListenerItem};
+use super::model::ConfigClientInfo;
 use crate::grpc::bistream_manage::{BiStreamManage, BiStreamManageCmd};
 use actix::prelude::*;
 
 #[derive(Default)]
 pub struct Subscriber {
     listener: HashMap<ConfigKey, HashSet<Arc<String>>>,
-    client_keys: HashMap<Arc<String>, HashSet<ConfigKey>>,
+    /// 客户端监听的配置及最近一次上报的md5
+    client_keys: HashMap<Arc<String>, HashMap<ConfigKey, Arc<String>>>,
+    client_infos: HashMap<Arc<String>, ConfigClientInfo>,
     conn_manage: Option<Addr<BiStreamManage>>,
 }
 
@@ -19,6 +22,7 @@ impl Subscriber {
         Self {
             listener: Default::default(),
             client_keys: Default::default(),
+            client_infos: Default::default(),
             conn_manage: Default::default(),
         }
     }
@@ -27,7 +31,12 @@ impl Subscriber {
         self.conn_manage = Some(conn_manage);
     }
 
-    pub fn add_subscribe(&mut self, client_id: Arc<String>, items: Vec<ListenerItem>) {
+    pub fn add_subscribe(
+        &mut self,
+        client_id: Arc<String>,
+        client: ConfigClientInfo,
+        items: Vec<ListenerItem>,
+    ) {
         for item in &items {
             match self.listener.get_mut(&item.key) {
                 Some(set) => {
@@ -40,20 +49,11 @@ impl Subscriber {
                 }
             };
         }
-        match self.client_keys.get_mut(&client_id) {
-            Some(set) => {
-                for item in items {
-                    set.insert(item.key);
-                }
-            }
-            None => {
-                let mut set = HashSet::new();
-                for item in items {
-                    set.insert(item.key);
-                }
-                self.client_keys.insert(client_id, set);
-            }
+        let keys = self.client_keys.entry(client_id.clone()).or_default();
+        for item in items {
+            keys.insert(item.key, item.md5);
         }
+        self.client_infos.insert(client_id, client);
     }
 
     pub fn remove_subscribe(&mut self, client_id: Arc<String>, items: Vec<ListenerItem>) {
@@ -71,23 +71,25 @@ impl Subscriber {
         }
 
         let mut remove_empty_client = false;
-        if let Some(set) = self.client_keys.get_mut(&client_id) {
+        if let Some(keys) = self.client_keys.get_mut(&client_id) {
             for item in items {
-                set.remove(&item.key);
+                keys.remove(&item.key);
             }
-            if set.is_empty() {
+            if keys.is_empty() {
                 remove_empty_client = true;
             }
         };
         if remove_empty_client {
             self.client_keys.remove(&client_id);
+            self.client_infos.remove(&client_id);
         }
     }
 
     pub fn remove_client_subscribe(&mut self, client_id: Arc<String>) {
-        if let Some(set) = self.client_keys.remove(&client_id) {
+        self.client_infos.remove(&client_id);
+        if let Some(keys) = self.client_keys.remove(&client_id) {
             let mut remove_keys = vec![];
-            for key in set {
+            for key in keys.into_keys() {
                 if let Some(set) = self.listener.get_mut(&key) {
                     set.remove(&client_id);
                     if set.is_empty() {
@@ -105,15 +107,16 @@ impl Subscriber {
         if let Some(set) = self.listener.remove(&key) {
             let mut remove_keys = vec![];
             for client_id in set {
-                if let Some(set) = self.client_keys.get_mut(&client_id) {
-                    set.remove(&key);
-                    if set.is_empty() {
+                if let Some(keys) = self.client_keys.get_mut(&client_id) {
+                    keys.remove(&key);
+                    if keys.is_empty() {
                         remove_keys.push(client_id);
                     }
                 }
             }
             for key in &remove_keys {
                 self.client_keys.remove(key);
+                self.client_infos.remove(key);
             }
         }
     }
@@ -126,6 +129,47 @@ impl Subscriber {
         }
     }
 
+    ///
+    /// 查询监听指定配置的客户端，返回(连接id,客户端信息,上报的md5)
+    pub fn get_key_clients(
+        &self,
+        key: &ConfigKey,
+    ) -> Vec<(Arc<String>, &ConfigClientInfo, Arc<String>)> {
+        let mut list = vec![];
+        if let Some(set) = self.listener.get(key) {
+            for client_id in set {
+                if let (Some(keys), Some(client)) = (
+                    self.client_keys.get(client_id),
+                    self.client_infos.get(client_id),
+                ) {
+                    let md5 = keys.get(key).cloned().unwrap_or_default();
+                    list.push((client_id.clone(), client, md5));
+                }
+            }
+        }
+        list
+    }
+
+    ///
+    /// 查询指定ip的客户端监听的配置，返回(连接id,客户端信息,配置,上报的md5)
+    pub fn get_ip_keys(
+        &self,
+        ip: &str,
+    ) -> Vec<(Arc<String>, &ConfigClientInfo, ConfigKey, Arc<String>)> {
+        let mut list = vec![];
+        for (client_id, client) in &self.client_infos {
+            if client.ip.as_str() != ip {
+                continue;
+            }
+            if let Some(keys) = self.client_keys.get(client_id) {
+                for (key, md5) in keys {
+                    list.push((client_id.clone(), client, key.clone(), md5.clone()));
+                }
+            }
+        }
+        list
+    }
+
     pub fn get_listener_key_size(&self) -> usize {
         self.listener.len()
     }
@@ -149,4 +193,13 @@ impl Subscriber {
         }
         sum
     }
+
+    ///
+    /// 各客户端的订阅数量
+    pub fn get_client_key_sizes(&self) -> Vec<(Arc<String>, usize)> {
+        self.client_keys
+            .iter()
+            .map(|(k, v)| (k.clone(), v.len()))
+            .collect()
+    }
 }
@@ -0,0 +1,204 @@
+use crate::config::config_type::ConfigType;
+use thiserror::Error;
+
+///
+/// 配置内容校验失败
+/// line、column从1开始，无法定位时为None
+#[derive(Debug, Clone, Error)]
+#[error("{message}")]
+pub struct ConfigValidateError {
+    pub message: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl ConfigValidateError {
+    pub fn new(message: String, line: Option<usize>, column: Option<usize>) -> Self {
+        Self {
+            message,
+            line,
+            column,
+        }
+    }
+
+    fn with_position(config_type: &str, msg: &str, line: usize, column: usize) -> Self {
+        // 部分解析库的错误信息自带位置，去掉避免重复
+        let suffix = format!(" at line {} column {}", line, column);
+        let msg = msg.strip_suffix(suffix.as_str()).unwrap_or(msg);
+        Self::new(
+            format!(
+                "config content is not valid {}, {} at line {} column {}",
+                config_type, msg, line, column
+            ),
+            Some(line),
+            Some(column),
+        )
+    }
+}
+
+pub struct ConfigValidateUtils;
+
+impl ConfigValidateUtils {
+    ///
+    /// 按配置类型校验内容格式；text、html、properties不校验
+    pub fn validate_content(
+        config_type: &ConfigType,
+        content: &str,
+    ) -> Result<(), ConfigValidateError> {
+        match config_type {
+            ConfigType::Json => Self::parse_json(content).map(|_| ()),
+            ConfigType::Yaml => Self::parse_yaml(content).map(|_| ()),
+            ConfigType::Xml => Self::validate_xml(content),
+            ConfigType::Toml => Self::validate_toml(content),
+            ConfigType::Text | ConfigType::Html | ConfigType::Properties => Ok(()),
+        }
+    }
+
+    ///
+    /// 按命名空间配置的JSON Schema校验内容，只对json、yaml类型生效
+    pub fn validate_schema(
+        config_type: &ConfigType,
+        content: &str,
+        schema: &str,
+    ) -> Result<(), ConfigValidateError> {
+        let instance = match config_type {
+            ConfigType::Json => Self::parse_json(content)?,
+            ConfigType::Yaml => Self::yaml_to_json(Self::parse_yaml(content)?)?,
+            _ => return Ok(()),
+        };
+        let schema = Self::compile_schema(schema)?;
+        if let Err(mut errors) = schema.validate(&instance) {
+            if let Some(err) = errors.next() {
+                let path = err.instance_path.to_string();
+                let path = if path.is_empty() { "/" } else { &path };
+                return Err(ConfigValidateError::new(
+                    format!(
+                        "config content does not match the namespace schema, {} at {}",
+                        err, path
+                    ),
+                    None,
+                    None,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// 设置命名空间schema前校验schema本身
+    pub fn check_schema(schema: &str) -> Result<(), ConfigValidateError> {
+        Self::compile_schema(schema).map(|_| ())
+    }
+
+    fn compile_schema(schema: &str) -> Result<jsonschema::JSONSchema, ConfigValidateError> {
+        let value: serde_json::Value = serde_json::from_str(schema).map_err(|e| {
+            ConfigValidateError::new(
+                format!("config schema is not valid json, {}", e),
+                None,
+                None,
+            )
+        })?;
+        jsonschema::JSONSchema::compile(&value).map_err(|e| {
+            ConfigValidateError::new(format!("config schema is not valid, {}", e), None, None)
+        })
+    }
+
+    fn parse_json(content: &str) -> Result<serde_json::Value, ConfigValidateError> {
+        serde_json::from_str(content).map_err(|e| {
+            ConfigValidateError::with_position("json", &e.to_string(), e.line(), e.column())
+        })
+    }
+
+    fn parse_yaml(content: &str) -> Result<serde_yml::Value, ConfigValidateError> {
+        serde_yml::from_str(content).map_err(|e| {
+            let (line, column) = e
+                .location()
+                .map(|l| (l.line(), l.column()))
+                .unwrap_or_default();
+            ConfigValidateError::with_position("yaml", &e.to_string(), line, column)
+        })
+    }
+
+    /// yaml中非字符串key等无法转为json的内容，只在schema校验时报错
+    fn yaml_to_json(value: serde_yml::Value) -> Result<serde_json::Value, ConfigValidateError> {
+        serde_json::to_value(value).map_err(|e| {
+            ConfigValidateError::new(
+                format!("config content can't convert to json, {}", e),
+                None,
+                None,
+            )
+        })
+    }
+
+    fn validate_xml(content: &str) -> Result<(), ConfigValidateError> {
+        roxmltree::Document::parse(content).map_err(|e| {
+            let pos = e.pos();
+            ConfigValidateError::with_position(
+                "xml",
+                &e.to_string(),
+                pos.row as usize,
+                pos.col as usize,
+            )
+        })?;
+        Ok(())
+    }
+
+    fn validate_toml(content: &str) -> Result<(), ConfigValidateError> {
+        content.parse::<toml::Table>().map_err(|e| {
+            let (line, column) = e
+                .span()
+                .map(|span| Self::offset_position(content, span.start))
+                .unwrap_or_default();
+            ConfigValidateError::with_position("toml", e.message(), line, column)
+        })?;
+        Ok(())
+    }
+
+    fn offset_position(content: &str, offset: usize) -> (usize, usize) {
+        let offset = offset.min(content.len());
+        let before = &content.as_bytes()[..offset];
+        let line = before.iter().filter(|&&c| c == b'\n').count() + 1;
+        let line_start = before
+            .iter()
+            .rposition(|&c| c == b'\n')
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        (line, offset - line_start + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_content_position() {
+        assert!(ConfigValidateUtils::validate_content(&ConfigType::Json, "{\"a\":1}").is_ok());
+        let err =
+            ConfigValidateUtils::validate_content(&ConfigType::Json, "{\n\"a\":1,\n}").unwrap_err();
+        assert_eq!(err.line, Some(3));
+        let err =
+            ConfigValidateUtils::validate_content(&ConfigType::Toml, "a = 1\nb = ").unwrap_err();
+        assert_eq!(err.line, Some(2));
+        assert!(ConfigValidateUtils::validate_content(&ConfigType::Xml, "<a><b></a>").is_err());
+        assert!(ConfigValidateUtils::validate_content(&ConfigType::Yaml, "a: [1").is_err());
+        assert!(ConfigValidateUtils::validate_content(&ConfigType::Text, "{").is_ok());
+    }
+
+    #[test]
+    fn validate_schema() {
+        let schema =
+            r#"{"type":"object","required":["port"],"properties":{"port":{"type":"integer"}}}"#;
+        assert!(ConfigValidateUtils::check_schema(schema).is_ok());
+        assert!(
+            ConfigValidateUtils::validate_schema(&ConfigType::Yaml, "port: 80", schema).is_ok()
+        );
+        assert!(ConfigValidateUtils::validate_schema(
+            &ConfigType::Json,
+            "{\"port\":\"80\"}",
+            schema
+        )
+        .is_err());
+        assert!(ConfigValidateUtils::validate_schema(&ConfigType::Text, "port", schema).is_ok());
+    }
+}
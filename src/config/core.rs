@@ -9,22 +9,30 @@ use std::sync::Arc;
 use std::sync::Weak;
 use std::time::Duration;
 
-use crate::raft::store::ClientRequest;
+use crate::raft::store::{ClientRequest, ClientResponse};
 use crate::raft::NacosRaft;
 use crate::utils::get_md5;
 use serde::{Deserialize, Serialize};
 
 use crate::common::byte_utils::id_to_bin;
 use crate::common::constant::{CONFIG_TREE_NAME, SEQUENCE_TREE_NAME, SEQ_KEY_CONFIG};
+use crate::common::fuzzy_watch::{FuzzyWatchParam, ADD_CONFIG, CONFIG_CHANGED, DELETE_CONFIG};
 use crate::common::sequence_utils::SimpleSequence;
 use actix::prelude::*;
 
+use super::config_fuzzy_watch::ConfigFuzzyWatcher;
 use super::config_subscribe::Subscriber;
 use super::dal::ConfigHistoryParam;
+use crate::config::config_crypto::ConfigCryptoManager;
+use crate::config::config_history::{
+    ConfigHistoryArchive, ConfigHistoryRetention, MEMORY_HISTORY_MAX_COUNT,
+};
 use crate::config::config_index::{ConfigQueryParam, TenantIndex};
 use crate::config::config_type::ConfigType;
 use crate::config::model::{
-    ConfigRaftCmd, ConfigRaftResult, ConfigValueDO, HistoryItem, SetConfigParam,
+    ConfigCasConflictError, ConfigClientInfo, ConfigGrayInfoDto, ConfigGrayReq, ConfigGrayValue,
+    ConfigListenerInfo, ConfigListenerQueryParam, ConfigRaftCmd, ConfigRaftResult, ConfigValueDO,
+    HistoryItem, SetConfigParam,
 };
 use crate::config::utils::param_utils;
 use crate::namespace::NamespaceActor;
@@ -35,6 +43,7 @@ use crate::transfer::model::{
     TransferDataRequest, TransferDataResponse, TransferRecordDto, TransferWriterRequest,
 };
 use crate::transfer::writer::TransferWriterActor;
+use crate::webhook::core::{WebhookCmd, WebhookManager};
 
 #[derive(Debug, Eq, PartialEq, Clone, Hash)]
 pub struct ConfigKey {
@@ -117,6 +126,8 @@ pub struct ConfigValue {
     pub(crate) config_type: Option<Arc<String>>,
     pub(crate) desc: Option<Arc<String>>,
     pub(crate) last_modified: i64,
+    pub(crate) gray: Option<ConfigGrayValue>,
+    pub(crate) tags: Vec<Arc<String>>,
 }
 
 impl ConfigValue {
@@ -130,6 +141,8 @@ impl ConfigValue {
             config_type: None,
             desc: None,
             last_modified: now_millis_i64(),
+            gray: None,
+            tags: vec![],
         }
     }
 
@@ -154,10 +167,13 @@ impl ConfigValue {
                 content,
                 modified_time: op_time,
                 op_user,
+                note: None,
             }],
             config_type: None,
             desc: None,
             last_modified: op_time,
+            gray: None,
+            tags: vec![],
         }
     }
 
@@ -168,7 +184,7 @@ impl ConfigValue {
         op_time: i64,
         md5: Option<Arc<String>>,
         op_user: Option<Arc<String>>,
-    ) {
+    ) -> Option<HistoryItem> {
         let md5 = if let Some(v) = md5 {
             v
         } else {
@@ -182,12 +198,50 @@ impl ConfigValue {
             content,
             modified_time: op_time,
             op_user,
+            note: None,
+        };
+        //超出内存保留条数的历史记录返回给调用方归档
+        let overflow = if self.histories.len() >= MEMORY_HISTORY_MAX_COUNT {
+            Some(self.histories.remove(0))
+        } else {
+            None
         };
-        if self.histories.len() >= 100 {
-            self.histories.remove(0);
-        }
         self.last_modified = op_time;
         self.histories.push(item);
+        overflow
+    }
+
+    ///
+    /// 已提交到raft的配置md5;tmp值为转发写入后的临时内容,不参与比较
+    pub fn get_committed_md5(&self, key: &ConfigKey, crypto: &ConfigCryptoManager) -> Arc<String> {
+        if !self.tmp {
+            return self.md5.clone();
+        }
+        self.histories
+            .last()
+            .map(|e| crypto.version_md5(key, &e.content))
+            .unwrap_or_default()
+    }
+
+    ///
+    /// 获取客户端命中的灰度配置
+    pub fn get_gray_by_client(&self, client: &ConfigClientInfo) -> Option<&ConfigGrayValue> {
+        if let Some(gray) = &self.gray {
+            if gray.rule.match_client(client) {
+                return Some(gray);
+            }
+        }
+        None
+    }
+
+    ///
+    /// 获取客户端对应的md5，命中灰度时返回灰度配置md5
+    pub fn get_md5_by_client(&self, client: &ConfigClientInfo) -> &Arc<String> {
+        if let Some(gray) = self.get_gray_by_client(client) {
+            &gray.md5
+        } else {
+            &self.md5
+        }
     }
 }
 
@@ -199,7 +253,10 @@ pub struct ConfigInfoDto {
     pub data_id: Arc<String>,
     pub content: Option<Arc<String>>,
     pub md5: Option<Arc<String>>,
+    pub config_type: Option<Arc<String>>,
     pub desc: Option<Arc<String>>,
+    #[serde(default)]
+    pub tags: Vec<Arc<String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
@@ -212,6 +269,7 @@ pub struct ConfigHistoryInfoDto {
     pub content: Option<String>,
     pub modified_time: Option<i64>, //给历史记录使用
     pub op_user: Option<String>,
+    pub note: Option<String>,
 }
 
 #[derive(Debug)]
@@ -307,11 +365,23 @@ pub enum ListenerResult {
 type ListenerSenderType = tokio::sync::oneshot::Sender<ListenerResult>;
 //type ListenerReceiverType = tokio::sync::oneshot::Receiver<ListenerResult>;
 
+/// http长轮询客户端监听记录的过期时间，需大于长轮询最大超时时间
+const HTTP_LISTENER_EXPIRE_MILLIS: i64 = 150_000;
+
+///
+/// http长轮询客户端最近上报的监听配置及md5，按客户端ip记录
+struct HttpListenerClient {
+    client: ConfigClientInfo,
+    keys: HashMap<ConfigKey, (Arc<String>, i64)>,
+}
+
 pub(crate) struct ConfigListener {
     version: u64,
     listener: HashMap<ConfigKey, Vec<u64>>,
     time_listener: BTreeMap<i64, Vec<OnceListener>>,
     sender_map: HashMap<u64, ListenerSenderType>,
+    http_clients: HashMap<Arc<String>, HttpListenerClient>,
+    last_clear_time: i64,
 }
 
 impl ConfigListener {
@@ -321,9 +391,40 @@ impl ConfigListener {
             listener: Default::default(),
             time_listener: Default::default(),
             sender_map: Default::default(),
+            http_clients: Default::default(),
+            last_clear_time: 0,
+        }
+    }
+
+    fn record_client(&mut self, items: &[ListenerItem], client: &ConfigClientInfo) {
+        let now = now_millis_i64();
+        let record = self
+            .http_clients
+            .entry(client.ip.clone())
+            .or_insert_with(|| HttpListenerClient {
+                client: client.clone(),
+                keys: Default::default(),
+            });
+        record.client = client.clone();
+        for item in items {
+            record
+                .keys
+                .insert(item.key.clone(), (item.md5.clone(), now));
         }
     }
 
+    fn clear_expired_client(&mut self, now: i64) {
+        if now - self.last_clear_time < 10_000 {
+            return;
+        }
+        self.last_clear_time = now;
+        let min_time = now - HTTP_LISTENER_EXPIRE_MILLIS;
+        self.http_clients.retain(|_, record| {
+            record.keys.retain(|_, (_, time)| *time >= min_time);
+            !record.keys.is_empty()
+        });
+    }
+
     fn add(&mut self, items: Vec<ListenerItem>, sender: ListenerSenderType, time: i64) {
         self.version += 1;
         for item in &items {
@@ -382,6 +483,31 @@ impl ConfigListener {
         for key in keys {
             self.time_listener.remove(&key);
         }
+        self.clear_expired_client(current_time);
+    }
+
+    fn get_key_clients(&self, key: &ConfigKey) -> Vec<(&ConfigClientInfo, Arc<String>)> {
+        self.http_clients
+            .values()
+            .filter_map(|record| {
+                record
+                    .keys
+                    .get(key)
+                    .map(|(md5, _)| (&record.client, md5.clone()))
+            })
+            .collect()
+    }
+
+    fn get_ip_keys(&self, ip: &str) -> Vec<(&ConfigClientInfo, ConfigKey, Arc<String>)> {
+        if let Some(record) = self.http_clients.get(&Arc::new(ip.to_owned())) {
+            record
+                .keys
+                .iter()
+                .map(|(key, (md5, _))| (&record.client, key.clone(), md5.clone()))
+                .collect()
+        } else {
+            vec![]
+        }
     }
 
     pub(crate) fn get_listener_client_size(&self) -> usize {
@@ -398,10 +524,14 @@ pub struct ConfigActor {
     pub(crate) cache: HashMap<ConfigKey, ConfigValue>,
     pub(crate) listener: ConfigListener,
     pub(crate) subscriber: Subscriber,
+    pub(crate) fuzzy_watcher: ConfigFuzzyWatcher,
     pub(crate) tenant_index: TenantIndex,
     raft: Option<Weak<NacosRaft>>,
     namespace_actor: Option<Addr<NamespaceActor>>,
     sequence: SimpleSequence,
+    crypto: Arc<ConfigCryptoManager>,
+    history_archive: ConfigHistoryArchive,
+    webhook_manager: Option<Addr<WebhookManager>>,
 }
 
 impl Inject for ConfigActor {
@@ -417,7 +547,9 @@ impl Inject for ConfigActor {
         self.raft = raft.map(|e| Arc::downgrade(&e));
         self.namespace_actor = factory_data.get_actor();
         self.tenant_index.namespace_actor = self.namespace_actor.clone();
+        self.webhook_manager = factory_data.get_actor();
         if let Some(conn_manage) = factory_data.get_actor() {
+            self.fuzzy_watcher.set_conn_manage(conn_manage.clone());
             self.subscriber.set_conn_manage(conn_manage);
         }
         log::info!("ConfigActor inject complete");
@@ -432,14 +564,22 @@ impl Default for ConfigActor {
 
 impl ConfigActor {
     pub fn new() -> Self {
+        Self::new_with_crypto(Default::default())
+    }
+
+    pub fn new_with_crypto(crypto: Arc<ConfigCryptoManager>) -> Self {
         Self {
             cache: HashMap::new(),
             subscriber: Subscriber::new(),
+            fuzzy_watcher: ConfigFuzzyWatcher::new(),
             listener: ConfigListener::new(),
             tenant_index: TenantIndex::new(),
             raft: None,
             namespace_actor: None,
             sequence: SimpleSequence::new(0, 100),
+            crypto,
+            history_archive: Default::default(),
+            webhook_manager: None,
         }
     }
 
@@ -455,8 +595,24 @@ impl ConfigActor {
         }
     }
 
-    fn inner_set_config(&mut self, key: ConfigKey, value: ConfigValue) {
+    fn inner_set_config(&mut self, key: ConfigKey, mut value: ConfigValue) {
+        //加密内容的md5需要按明文计算
+        if ConfigCryptoManager::is_encrypted(&value.content) {
+            value.md5 = self.crypto.version_md5(&key, &value.content);
+        }
+        if let Some(gray) = value.gray.as_mut() {
+            if ConfigCryptoManager::is_encrypted(&gray.content) {
+                gray.md5 = self.crypto.version_md5(&key, &gray.content);
+            }
+        }
         self.tenant_index.insert_config(key.clone());
+        let old_tags = self
+            .cache
+            .get(&key)
+            .map(|v| v.tags.clone())
+            .unwrap_or_default();
+        self.tenant_index
+            .update_config_tags(&key, &old_tags, &value.tags);
         self.cache.insert(key, value);
     }
 
@@ -464,47 +620,160 @@ impl ConfigActor {
         if let Some(history_table_id) = param.history_table_id {
             self.sequence.set_valid_last_id(history_table_id);
         }
+        if let Some(cas_md5) = &param.cas_md5 {
+            let current_md5 = self
+                .cache
+                .get(&param.key)
+                .map(|v| v.get_committed_md5(&param.key, &self.crypto))
+                .unwrap_or_default();
+            if cas_md5 != &current_md5 {
+                return Err(ConfigCasConflictError.into());
+            }
+        }
+        let md5 = self.crypto.version_md5(&param.key, &param.value);
+        let mut changed_type = CONFIG_CHANGED;
         if let Some(v) = self.cache.get_mut(&param.key) {
-            let md5 = get_md5(param.value.as_str());
             if let Some(s) = param.config_type {
                 v.config_type = Some(s);
             }
             if let Some(s) = param.desc {
                 v.desc = Some(s);
             }
-            if !v.tmp && v.md5.as_str() == md5 {
+            if let Some(tags) = param.tags {
+                self.tenant_index
+                    .update_config_tags(&param.key, &v.tags, &tags);
+                v.tags = tags;
+            }
+            if !v.tmp && v.md5 == md5 {
+                if v.content != param.value {
+                    //密钥轮换重新加密，明文不变只替换存储内容
+                    v.content = param.value.clone();
+                    if let Some(item) = v.histories.last_mut() {
+                        item.content = param.value;
+                    }
+                }
                 return Ok(ConfigResult::NULL);
             }
             if v.histories.is_empty() {
                 self.tenant_index.insert_config(param.key.clone());
+                changed_type = ADD_CONFIG;
             }
-            v.update_value(
+            let overflow = v.update_value(
                 param.value,
                 param.history_id,
                 param.op_time,
-                Some(Arc::new(md5)),
+                Some(md5.clone()),
                 param.op_user,
             );
+            if let Some(item) = v.histories.last_mut() {
+                item.note = param.note;
+            }
+            if let Some(item) = overflow {
+                self.history_archive.archive(&param.key, item);
+            }
+            self.history_archive
+                .apply_retention(&param.key, &mut v.histories, param.op_time);
         } else {
             let mut v = ConfigValue::init(
                 param.value,
                 param.history_id,
                 param.op_time,
-                None,
+                Some(md5.clone()),
                 param.op_user,
             );
+            if let Some(item) = v.histories.last_mut() {
+                item.note = param.note;
+            }
             v.config_type = param.config_type;
             v.desc = param.desc;
-            self.cache.insert(param.key.clone(), v);
+            v.tags = param.tags.unwrap_or_default();
             self.tenant_index.insert_config(param.key.clone());
+            self.tenant_index
+                .update_config_tags(&param.key, &[], &v.tags);
+            self.cache.insert(param.key.clone(), v);
+            changed_type = ADD_CONFIG;
         }
+        self.notify_webhook(param.key.clone(), Some(md5), false);
         self.listener.notify(param.key.clone());
+        self.fuzzy_watcher.notify(&param.key, changed_type);
         self.subscriber.notify(param.key);
         Ok(ConfigResult::NULL)
     }
 
+    fn notify_webhook(&self, key: ConfigKey, md5: Option<Arc<String>>, deleted: bool) {
+        if let Some(webhook_manager) = self.webhook_manager.as_ref() {
+            webhook_manager.do_send(WebhookCmd::ConfigChanged { key, md5, deleted });
+        }
+    }
+
+    fn set_gray_config(&mut self, key: ConfigKey, gray: Option<ConfigGrayValue>) {
+        if let Some(v) = self.cache.get_mut(&key) {
+            if v.gray.is_none() && gray.is_none() {
+                return;
+            }
+            v.gray = gray;
+            self.listener.notify(key.clone());
+            self.subscriber.notify(key);
+        }
+    }
+
+    ///
+    /// 灰度配置转为正式配置
+    fn promote_gray_config(
+        &mut self,
+        key: ConfigKey,
+        history_id: u64,
+        history_table_id: Option<u64>,
+        op_time: i64,
+        op_user: Option<Arc<String>>,
+    ) -> anyhow::Result<()> {
+        let gray = if let Some(v) = self.cache.get_mut(&key) {
+            v.gray.take().map(|e| (e.md5 == v.md5, e))
+        } else {
+            None
+        };
+        if let Some((same_content, gray)) = gray {
+            let param = SetConfigParam {
+                key: key.clone(),
+                value: gray.content,
+                config_type: None,
+                desc: None,
+                history_id,
+                history_table_id,
+                op_time,
+                op_user,
+                cas_md5: None,
+                tags: None,
+                note: None,
+            };
+            self.set_config(param)?;
+            if same_content {
+                //内容未变化时set_config不会通知,需要通知灰度客户端切回正式配置
+                self.listener.notify(key.clone());
+                self.subscriber.notify(key);
+            }
+        }
+        Ok(())
+    }
+
+    fn get_gray_info(&self, key: &ConfigKey) -> anyhow::Result<Option<ConfigGrayInfoDto>> {
+        if let Some(e) = self.cache.get(key).and_then(|v| v.gray.as_ref()) {
+            let mut dto: ConfigGrayInfoDto = e.into();
+            dto.content = self.crypto.decrypt_content(key, &dto.content)?;
+            return Ok(Some(dto));
+        }
+        Ok(None)
+    }
+
     fn del_config(&mut self, key: ConfigKey) -> anyhow::Result<()> {
-        self.cache.remove(&key);
+        if let Some(v) = self.cache.remove(&key) {
+            self.tenant_index.update_config_tags(&key, &v.tags, &[]);
+            self.notify_webhook(key.clone(), None, true);
+            if !v.tmp {
+                self.fuzzy_watcher.notify(&key, DELETE_CONFIG);
+            }
+        }
+        self.history_archive.remove(&key);
         //self.config_db.del_config(&key).ok();
         self.tenant_index.remove_config(&key);
         self.listener.notify(key.clone());
@@ -532,21 +801,25 @@ impl ConfigActor {
     async fn send_raft_request(
         raft: &Option<Weak<NacosRaft>>,
         req: ClientRequest,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<ClientResponse> {
         if let Some(weak_raft) = raft {
             if let Some(raft) = weak_raft.upgrade() {
                 //TODO换成feature,非wait的方式
-                raft.client_write(ClientWriteRequest::new(req)).await?;
+                let resp = raft.client_write(ClientWriteRequest::new(req)).await?;
+                return Ok(resp.data);
             }
         }
-        Ok(())
+        Ok(ClientResponse::Success)
     }
 
-    pub fn get_config_info_page(&self, param: &ConfigQueryParam) -> (usize, Vec<ConfigInfoDto>) {
+    pub fn get_config_info_page(
+        &self,
+        param: &ConfigQueryParam,
+    ) -> anyhow::Result<(usize, Vec<ConfigInfoDto>)> {
         let (size, list) = self.tenant_index.query_config_page(param);
 
         if size == 0 {
-            return (size, Vec::new());
+            return Ok((size, Vec::new()));
         }
 
         let mut info_list = Vec::with_capacity(size);
@@ -556,22 +829,93 @@ impl ConfigActor {
                     tenant: item.tenant.clone(),
                     group: item.group.clone(),
                     data_id: item.data_id.clone(),
+                    config_type: value.config_type.clone(),
                     desc: value.desc.clone(),
+                    tags: value.tags.clone(),
                     //md5:Some(value.md5.clone()),
                     //content:Some(value.content.clone()),
                     ..Default::default()
                 };
                 if param.query_context {
-                    info.content = Some(value.content.clone());
+                    info.content = Some(self.crypto.decrypt_content(item, &value.content)?);
                     info.md5 = Some(value.md5.clone());
                 }
                 info_list.push(info);
             }
         }
-        (size, info_list)
+        Ok((size, info_list))
+    }
+
+    ///
+    /// 查询本节点的配置监听客户端，包含grpc订阅与http长轮询
+    pub(crate) fn query_listener_info(
+        &self,
+        param: &ConfigListenerQueryParam,
+    ) -> Vec<ConfigListenerInfo> {
+        let ip = param.get_ip();
+        let match_ip = |client: &ConfigClientInfo| ip.is_none_or(|v| v == &client.ip);
+        let mut list = vec![];
+        if let Some(key) = param.to_key() {
+            for (conn_id, client, md5) in self.subscriber.get_key_clients(&key) {
+                if match_ip(client) {
+                    list.push(self.build_listener_info(&key, Some(conn_id), client, md5));
+                }
+            }
+            for (client, md5) in self.listener.get_key_clients(&key) {
+                if match_ip(client) {
+                    list.push(self.build_listener_info(&key, None, client, md5));
+                }
+            }
+        } else if let Some(ip) = ip {
+            for (conn_id, client, key, md5) in self.subscriber.get_ip_keys(ip) {
+                list.push(self.build_listener_info(&key, Some(conn_id), client, md5));
+            }
+            for (client, key, md5) in self.listener.get_ip_keys(ip) {
+                list.push(self.build_listener_info(&key, None, client, md5));
+            }
+        }
+        list
     }
 
-    pub fn get_config_info_by_keys(&self, keys: &[ConfigKey]) -> (usize, Vec<ConfigInfoDto>) {
+    fn build_listener_info(
+        &self,
+        key: &ConfigKey,
+        connection_id: Option<Arc<String>>,
+        client: &ConfigClientInfo,
+        md5: Arc<String>,
+    ) -> ConfigListenerInfo {
+        let current_md5 = self
+            .cache
+            .get(key)
+            .map(|v| v.get_md5_by_client(client).clone());
+        let stale = match &current_md5 {
+            Some(v) => v != &md5,
+            None => !md5.is_empty(),
+        };
+        ConfigListenerInfo {
+            tenant: key.tenant.clone(),
+            group: key.group.clone(),
+            data_id: key.data_id.clone(),
+            listen_type: if connection_id.is_some() {
+                "grpc".to_owned()
+            } else {
+                "http".to_owned()
+            },
+            connection_id,
+            ip: client.ip.clone(),
+            sdk_version: client.sdk_version(),
+            app_name: client.app_name(),
+            md5,
+            current_md5,
+            stale,
+            node_id: 0,
+        }
+    }
+
+    pub fn get_config_info_by_keys(
+        &self,
+        keys: &[ConfigKey],
+    ) -> anyhow::Result<(usize, Vec<ConfigInfoDto>)> {
         let mut info_list = Vec::with_capacity(keys.len());
 
         for key in keys.iter() {
@@ -586,16 +930,18 @@ impl ConfigActor {
                     tenant: key.tenant.clone(),
                     group: key.group.clone(),
                     data_id: key.data_id.clone(),
+                    config_type: value.config_type.clone(),
                     desc: value.desc.clone(),
-                    content: Some(value.content.clone()),
+                    content: Some(self.crypto.decrypt_content(&key, &value.content)?),
                     md5: Some(value.md5.clone()),
+                    tags: value.tags.clone(),
                 };
                 info_list.push(info);
             }
         }
 
         let size = info_list.len();
-        (size, info_list)
+        Ok((size, info_list))
     }
     /*
     pub(crate) fn get_history_info_page_old(
@@ -619,33 +965,73 @@ impl ConfigActor {
      */
 
     ///
-    /// 从内存列表中直接查询历史记录
+    /// 查询历史记录，先取内存中的近期记录，再取归档记录
     pub(crate) fn get_history_info_page(
         &self,
         param: &ConfigHistoryParam,
-    ) -> (usize, Vec<ConfigHistoryInfoDto>) {
+    ) -> anyhow::Result<(usize, Vec<ConfigHistoryInfoDto>)> {
         if let (Some(t), Some(g), Some(id)) = (&param.tenant, &param.group, &param.data_id) {
             let key = ConfigKey::new(id, g, t);
             if let Some(v) = self.cache.get(&key) {
                 let mut ret = vec![];
-                let iter = v.histories.iter().rev();
+                let iter = v
+                    .histories
+                    .iter()
+                    .rev()
+                    .chain(self.history_archive.iter_rev(&key));
                 if let Some(offset) = param.offset {
                     let n_i = iter.skip(offset as usize);
                     if let Some(limit) = param.limit {
                         let t = n_i.take(limit as usize);
                         for item in t {
-                            ret.push(item.to_dto(&key));
+                            ret.push(self.history_to_dto(item, &key)?);
                         }
                     } else {
                         for item in n_i {
-                            ret.push(item.to_dto(&key));
+                            ret.push(self.history_to_dto(item, &key)?);
                         }
                     }
                 }
-                return (v.histories.len(), ret);
+                return Ok((v.histories.len() + self.history_archive.len(&key), ret));
             };
         };
-        (0, vec![])
+        Ok((0, vec![]))
+    }
+
+    fn get_history_info(
+        &self,
+        key: &ConfigKey,
+        id: u64,
+    ) -> anyhow::Result<Option<ConfigHistoryInfoDto>> {
+        let v = if let Some(v) = self.cache.get(key) {
+            v
+        } else {
+            return Ok(None);
+        };
+        v.histories
+            .iter()
+            .find(|e| e.id == id)
+            .or_else(|| self.history_archive.get(key, id))
+            .map(|item| self.history_to_dto(item, key))
+            .transpose()
+    }
+
+    fn history_to_dto(
+        &self,
+        item: &HistoryItem,
+        key: &ConfigKey,
+    ) -> anyhow::Result<ConfigHistoryInfoDto> {
+        let mut dto = item.to_dto(key);
+        if let Some(content) = dto.content.as_ref() {
+            if ConfigCryptoManager::is_encrypted(content) {
+                dto.content = Some(
+                    self.crypto
+                        .decrypt_content(key, &Arc::new(content.to_owned()))?
+                        .to_string(),
+                );
+            }
+        }
+        Ok(dto)
     }
 
     ///
@@ -662,6 +1048,7 @@ impl ConfigActor {
             };
             writer.do_send(SnapshotWriterRequest::Record(record));
         }
+        self.history_archive.build_snapshot(&writer)?;
         let seq_record = SnapshotRecordDto {
             tree: SEQUENCE_TREE_NAME.clone(),
             key: SEQ_KEY_CONFIG.as_bytes().to_vec(),
@@ -697,6 +1084,93 @@ impl ConfigActor {
         Ok(())
     }
 
+    fn check_gray_req(&self, req: &ConfigGrayReq) -> anyhow::Result<()> {
+        match req {
+            ConfigGrayReq::Publish { key, rule, .. } => {
+                if rule.is_empty() {
+                    return Err(anyhow::anyhow!("the gray rule is empty"));
+                }
+                let key: ConfigKey = (key as &str).into();
+                if !self.cache.contains_key(&key) {
+                    return Err(anyhow::anyhow!("the config is not exist"));
+                }
+            }
+            ConfigGrayReq::Promote { key, .. } => {
+                let key: ConfigKey = (key as &str).into();
+                if self.cache.get(&key).and_then(|v| v.gray.as_ref()).is_none() {
+                    return Err(anyhow::anyhow!("the gray config is not exist"));
+                }
+            }
+            ConfigGrayReq::Stop { .. } => {}
+        }
+        Ok(())
+    }
+
+    ///
+    /// 构建密钥轮换的raft请求，只处理需要重新加密的配置
+    fn build_rotate_encrypt_requests(
+        &mut self,
+        op_user: Option<Arc<String>>,
+    ) -> Vec<ClientRequest> {
+        let mut requests = vec![];
+        if !self.crypto.is_enable() {
+            return requests;
+        }
+        let op_time = now_millis_i64();
+        for (key, v) in &self.cache {
+            if self.crypto.need_rotate(&key.data_id, &v.content) {
+                match (
+                    self.crypto.re_encrypt(key, &v.content),
+                    self.sequence.next_state(),
+                ) {
+                    (Ok(value), Ok((history_id, history_table_id))) => {
+                        requests.push(ClientRequest::ConfigSet {
+                            key: key.build_key(),
+                            value,
+                            config_type: None,
+                            desc: None,
+                            history_id,
+                            history_table_id,
+                            op_time,
+                            op_user: op_user.clone(),
+                            cas_md5: Some(v.md5.clone()),
+                            tags: None,
+                            note: None,
+                        });
+                    }
+                    (Err(err), _) | (_, Err(err)) => {
+                        log::warn!(
+                            "rotate config {} encrypt key error,{}",
+                            key.build_key(),
+                            err
+                        );
+                    }
+                }
+            }
+            if let Some(gray) = &v.gray {
+                if self.crypto.need_rotate(&key.data_id, &gray.content) {
+                    match self.crypto.re_encrypt(key, &gray.content) {
+                        Ok(value) => requests.push(ClientRequest::ConfigGraySet {
+                            key: key.build_key(),
+                            value,
+                            rule: gray.rule.as_ref().clone(),
+                            op_time: gray.last_modified,
+                            op_user: gray.op_user.clone(),
+                        }),
+                        Err(err) => {
+                            log::warn!(
+                                "rotate gray config {} encrypt key error,{}",
+                                key.build_key(),
+                                err
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        requests
+    }
+
     pub fn hb(&self, ctx: &mut actix::Context<Self>) {
         ctx.run_later(Duration::from_millis(500), |act, ctx| {
             act.listener.timeout();
@@ -714,15 +1188,28 @@ pub enum ConfigCmd {
     SetFullValue(ConfigKey, ConfigValue),
     InnerSetLastId(u64),
     GET(ConfigKey),
+    GetByClient(ConfigKey, ConfigClientInfo),
+    GetGrayInfo(ConfigKey),
     QueryPageInfo(Box<ConfigQueryParam>),
     QueryInfoByKeys(Box<Vec<ConfigKey>>),
+    /// 查询本节点的配置监听客户端
+    QueryListenerInfo(Box<ConfigListenerQueryParam>),
     QueryHistoryPageInfo(Box<ConfigHistoryParam>),
-    LISTENER(Vec<ListenerItem>, ListenerSenderType, i64),
-    Subscribe(Vec<ListenerItem>, Arc<String>),
+    GetHistoryInfo(ConfigKey, u64),
+    /// 命名空间历史记录保留策略变更
+    SetHistoryRetention(Arc<String>, ConfigHistoryRetention),
+    LoadHistorySnapshotRecord(SnapshotRecordDto),
+    LISTENER(Vec<ListenerItem>, ListenerSenderType, i64, ConfigClientInfo),
+    Subscribe(Vec<ListenerItem>, Arc<String>, ConfigClientInfo),
     RemoveSubscribe(Vec<ListenerItem>, Arc<String>),
     RemoveSubscribeClient(Arc<String>),
+    /// 模糊监听或取消模糊监听
+    FuzzyWatch(Box<FuzzyWatchParam>, Arc<String>),
+    QueryClientSubscribeCount,
     BuildSnapshot(Addr<SnapshotWriterActor>),
     GetSequenceSection(u64),
+    /// 查询命名空间下的配置数量
+    QueryTenantConfigCount(Arc<String>),
 }
 
 #[derive(Message)]
@@ -734,8 +1221,16 @@ pub enum ConfigAsyncCmd {
         op_user: Option<Arc<String>>,
         config_type: Option<Arc<String>>,
         desc: Option<Arc<String>>,
+        cas_md5: Option<Arc<String>>,
+        tags: Option<Vec<Arc<String>>>,
+        note: Option<Arc<String>>,
     },
     Delete(ConfigKey),
+    Gray(ConfigGrayReq),
+    /// 使用当前密钥重新加密所有匹配规则的配置
+    RotateEncryptKey {
+        op_user: Option<Arc<String>>,
+    },
 }
 
 pub enum ConfigResult {
@@ -744,12 +1239,19 @@ pub enum ConfigResult {
         md5: Arc<String>,
         config_type: Option<Arc<String>>,
         desc: Option<Arc<String>>,
+        tags: Vec<Arc<String>>,
         last_modified: i64,
+        beta: bool,
     },
     NULL,
+    GrayInfo(Option<ConfigGrayInfoDto>),
     ChangeKey(Vec<ConfigKey>),
     ConfigInfoPage(usize, Vec<ConfigInfoDto>),
+    ListenerInfoList(Vec<ConfigListenerInfo>),
+    ClientSubscribeCount(Vec<(Arc<String>, usize)>),
     ConfigHistoryInfoPage(usize, Vec<ConfigHistoryInfoDto>),
+    ConfigHistoryInfo(Option<ConfigHistoryInfoDto>),
+    Count(usize),
     SequenceSection {
         //id包含start值
         start: u64,
@@ -790,19 +1292,49 @@ impl Handler<ConfigCmd> for ConfigActor {
             ConfigCmd::GET(key) => {
                 if let Some(v) = self.cache.get(&key) {
                     return Ok(ConfigResult::Data {
-                        value: v.content.clone(),
+                        value: self.crypto.decrypt_content(&key, &v.content)?,
                         md5: v.md5.clone(),
                         config_type: v.config_type.clone(),
                         desc: v.desc.clone(),
+                        tags: v.tags.clone(),
                         last_modified: v.last_modified,
+                        beta: false,
                     });
                 }
             }
-            ConfigCmd::LISTENER(items, sender, time) => {
+            ConfigCmd::GetByClient(key, client) => {
+                if let Some(v) = self.cache.get(&key) {
+                    if let Some(gray) = v.get_gray_by_client(&client) {
+                        return Ok(ConfigResult::Data {
+                            value: self.crypto.decrypt_content(&key, &gray.content)?,
+                            md5: gray.md5.clone(),
+                            config_type: v.config_type.clone(),
+                            desc: v.desc.clone(),
+                            tags: v.tags.clone(),
+                            last_modified: gray.last_modified,
+                            beta: true,
+                        });
+                    }
+                    return Ok(ConfigResult::Data {
+                        value: self.crypto.decrypt_content(&key, &v.content)?,
+                        md5: v.md5.clone(),
+                        config_type: v.config_type.clone(),
+                        desc: v.desc.clone(),
+                        tags: v.tags.clone(),
+                        last_modified: v.last_modified,
+                        beta: false,
+                    });
+                }
+            }
+            ConfigCmd::GetGrayInfo(key) => {
+                return Ok(ConfigResult::GrayInfo(self.get_gray_info(&key)?));
+            }
+            ConfigCmd::LISTENER(items, sender, time, client) => {
+                self.listener.record_client(&items, &client);
                 let mut changes = vec![];
                 for item in &items {
                     if let Some(v) = self.cache.get(&item.key) {
-                        if v.md5 != item.md5 {
+                        if v.get_md5_by_client(&client) != &item.md5 {
                             changes.push(item.key.clone());
                         }
                     } else if !item.md5.is_empty() {
@@ -817,18 +1349,18 @@ impl Handler<ConfigCmd> for ConfigActor {
                     return Ok(ConfigResult::NULL);
                 }
             }
-            ConfigCmd::Subscribe(items, client_id) => {
+            ConfigCmd::Subscribe(items, client_id, client) => {
                 let mut changes = vec![];
                 for item in &items {
                     if let Some(v) = self.cache.get(&item.key) {
-                        if v.md5 != item.md5 {
+                        if v.get_md5_by_client(&client) != &item.md5 {
                             changes.push(item.key.clone());
                         }
                     } else if !item.md5.is_empty() {
                         changes.push(item.key.clone());
                     }
                 }
-                self.subscriber.add_subscribe(client_id, items);
+                self.subscriber.add_subscribe(client_id, client, items);
                 if !changes.is_empty() {
                     return Ok(ConfigResult::ChangeKey(changes));
                 }
@@ -837,20 +1369,50 @@ impl Handler<ConfigCmd> for ConfigActor {
                 self.subscriber.remove_subscribe(client_id, items);
             }
             ConfigCmd::RemoveSubscribeClient(client_id) => {
+                self.fuzzy_watcher.remove_client(&client_id);
                 self.subscriber.remove_client_subscribe(client_id);
             }
+            ConfigCmd::FuzzyWatch(param, client_id) => {
+                if param.watch {
+                    let keys = self.cache.iter().filter(|(_, v)| !v.tmp).map(|(k, _)| k);
+                    self.fuzzy_watcher.watch(client_id, *param, keys)?;
+                } else {
+                    self.fuzzy_watcher.cancel_watch(&client_id, &param.pattern);
+                }
+            }
+            ConfigCmd::QueryClientSubscribeCount => {
+                return Ok(ConfigResult::ClientSubscribeCount(
+                    self.subscriber.get_client_key_sizes(),
+                ));
+            }
             ConfigCmd::QueryPageInfo(config_query_param) => {
-                let (size, list) = self.get_config_info_page(config_query_param.as_ref());
+                let (size, list) = self.get_config_info_page(config_query_param.as_ref())?;
                 return Ok(ConfigResult::ConfigInfoPage(size, list));
             }
             ConfigCmd::QueryInfoByKeys(config_keys) => {
-                let (size, list) = self.get_config_info_by_keys(config_keys.as_ref());
+                let (size, list) = self.get_config_info_by_keys(config_keys.as_ref())?;
                 return Ok(ConfigResult::ConfigInfoPage(size, list));
             }
+            ConfigCmd::QueryListenerInfo(param) => {
+                return Ok(ConfigResult::ListenerInfoList(
+                    self.query_listener_info(&param),
+                ));
+            }
             ConfigCmd::QueryHistoryPageInfo(query_param) => {
-                let (size, list) = self.get_history_info_page(query_param.as_ref());
+                let (size, list) = self.get_history_info_page(query_param.as_ref())?;
                 return Ok(ConfigResult::ConfigHistoryInfoPage(size, list));
             }
+            ConfigCmd::GetHistoryInfo(key, id) => {
+                return Ok(ConfigResult::ConfigHistoryInfo(
+                    self.get_history_info(&key, id)?,
+                ));
+            }
+            ConfigCmd::SetHistoryRetention(tenant, retention) => {
+                self.history_archive.set_retention(tenant, retention);
+            }
+            ConfigCmd::LoadHistorySnapshotRecord(record) => {
+                self.history_archive.load_snapshot_record(record)?;
+            }
             ConfigCmd::BuildSnapshot(writer) => {
                 self.build_snapshot(writer).ok();
             }
@@ -858,6 +1420,15 @@ impl Handler<ConfigCmd> for ConfigActor {
                 let (start, end) = self.sequence.next_section(size)?;
                 return Ok(ConfigResult::SequenceSection { start, end });
             }
+            ConfigCmd::QueryTenantConfigCount(tenant) => {
+                let count = self
+                    .tenant_index
+                    .tenant_group
+                    .get(&tenant)
+                    .map(|v| v.get_config_count())
+                    .unwrap_or_default();
+                return Ok(ConfigResult::Count(count));
+            }
         }
         Ok(ConfigResult::NULL)
     }
@@ -868,12 +1439,24 @@ impl Handler<ConfigAsyncCmd> for ConfigActor {
 
     fn handle(&mut self, msg: ConfigAsyncCmd, _ctx: &mut Context<Self>) -> Self::Result {
         let raft = self.raft.clone();
-        let history_info = if let ConfigAsyncCmd::Add { .. } = &msg {
-            self.sequence.next_state().ok()
+        let history_info = match &msg {
+            ConfigAsyncCmd::Add { .. } => self.sequence.next_state().ok(),
+            ConfigAsyncCmd::Gray(ConfigGrayReq::Promote { .. }) => self.sequence.next_state().ok(),
+            _ => None,
+        };
+        let check_result = if let ConfigAsyncCmd::Gray(req) = &msg {
+            self.check_gray_req(req)
         } else {
-            None
+            Ok(())
         };
+        let rotate_requests = if let ConfigAsyncCmd::RotateEncryptKey { op_user } = &msg {
+            self.build_rotate_encrypt_requests(op_user.clone())
+        } else {
+            vec![]
+        };
+        let crypto = self.crypto.clone();
         let fut = async move {
+            check_result?;
             match msg {
                 ConfigAsyncCmd::Add {
                     key,
@@ -881,20 +1464,27 @@ impl Handler<ConfigAsyncCmd> for ConfigActor {
                     op_user,
                     config_type,
                     desc,
+                    cas_md5,
+                    tags,
+                    note,
                 } => {
-                    if let Some((history_id, history_table_id)) = history_info {
-                        let req = ClientRequest::ConfigSet {
-                            key: key.build_key(),
-                            value,
-                            config_type,
-                            desc,
-                            history_id,
-                            history_table_id,
-                            op_time: now_millis_i64(),
-                            op_user,
-                        };
-                        Self::send_raft_request(&raft, req).await.ok();
-                    }
+                    let value = crypto.encrypt_content(&key, value)?;
+                    let (history_id, history_table_id) =
+                        history_info.ok_or_else(|| anyhow::anyhow!("get history id error"))?;
+                    let req = ClientRequest::ConfigSet {
+                        key: key.build_key(),
+                        value,
+                        config_type,
+                        desc,
+                        history_id,
+                        history_table_id,
+                        op_time: now_millis_i64(),
+                        op_user,
+                        cas_md5,
+                        tags,
+                        note,
+                    };
+                    check_config_set_response(Self::send_raft_request(&raft, req).await)?;
                 }
                 ConfigAsyncCmd::Delete(key) => {
                     let req = ClientRequest::ConfigRemove {
@@ -902,6 +1492,53 @@ impl Handler<ConfigAsyncCmd> for ConfigActor {
                     };
                     Self::send_raft_request(&raft, req).await.ok();
                 }
+                ConfigAsyncCmd::Gray(gray_req) => {
+                    let op_time = now_millis_i64();
+                    let req = match gray_req {
+                        ConfigGrayReq::Publish {
+                            key,
+                            value,
+                            rule,
+                            op_user,
+                        } => {
+                            let config_key: ConfigKey = (&key as &str).into();
+                            let value = crypto.encrypt_content(&config_key, value)?;
+                            ClientRequest::ConfigGraySet {
+                                key,
+                                value,
+                                rule,
+                                op_time,
+                                op_user,
+                            }
+                        }
+                        ConfigGrayReq::Promote { key, op_user } => {
+                            let (history_id, history_table_id) = history_info
+                                .ok_or_else(|| anyhow::anyhow!("get history id error"))?;
+                            ClientRequest::ConfigGrayPromote {
+                                key,
+                                history_id,
+                                history_table_id,
+                                op_time,
+                                op_user,
+                            }
+                        }
+                        ConfigGrayReq::Stop { key } => ClientRequest::ConfigGrayRemove { key },
+                    };
+                    Self::send_raft_request(&raft, req).await?;
+                }
+                ConfigAsyncCmd::RotateEncryptKey { .. } => {
+                    let mut count = 0;
+                    for req in rotate_requests {
+                        match Self::send_raft_request(&raft, req).await {
+                            Ok(ClientResponse::ConfigCasConflict) => {}
+                            Ok(_) => count += 1,
+                            Err(err) => {
+                                log::warn!("rotate config encrypt key error,{}", err);
+                            }
+                        }
+                    }
+                    return Ok(ConfigResult::Count(count));
+                }
             }
             Ok(ConfigResult::NULL)
         }
@@ -911,6 +1548,15 @@ impl Handler<ConfigAsyncCmd> for ConfigActor {
     }
 }
 
+///
+/// 配置写入raft的结果，cas冲突与raft写入失败都需要返回给调用方
+fn check_config_set_response(res: anyhow::Result<ClientResponse>) -> anyhow::Result<()> {
+    match res? {
+        ClientResponse::ConfigCasConflict => Err(ConfigCasConflictError.into()),
+        _ => Ok(()),
+    }
+}
+
 impl Handler<ConfigRaftCmd> for ConfigActor {
     type Result = anyhow::Result<ConfigRaftResult>;
 
@@ -925,6 +1571,9 @@ impl Handler<ConfigRaftCmd> for ConfigActor {
                 history_table_id,
                 op_time,
                 op_user,
+                cas_md5,
+                tags,
+                note,
             } => {
                 let key: ConfigKey = (&key as &str).into();
                 let param = SetConfigParam {
@@ -937,8 +1586,15 @@ impl Handler<ConfigRaftCmd> for ConfigActor {
                     history_table_id,
                     op_time,
                     op_user,
+                    cas_md5,
+                    tags,
+                    note,
                 };
-                self.set_config(param).ok();
+                if let Err(err) = self.set_config(param) {
+                    if err.is::<ConfigCasConflictError>() {
+                        return Ok(ConfigRaftResult::CasConflict);
+                    }
+                }
             }
             ConfigRaftCmd::SetFullValue {
                 key,
@@ -954,6 +1610,39 @@ impl Handler<ConfigRaftCmd> for ConfigActor {
                 let config_key: ConfigKey = (&key as &str).into();
                 self.del_config(config_key).ok();
             }
+            ConfigRaftCmd::ConfigGraySet {
+                key,
+                value,
+                rule,
+                op_time,
+                op_user,
+            } => {
+                let config_key: ConfigKey = (&key as &str).into();
+                let mut gray = ConfigGrayValue::new(value, rule, op_user, op_time);
+                gray.md5 = self.crypto.version_md5(&config_key, &gray.content);
+                self.set_gray_config(config_key, Some(gray));
+            }
+            ConfigRaftCmd::ConfigGrayRemove { key } => {
+                let config_key: ConfigKey = (&key as &str).into();
+                self.set_gray_config(config_key, None);
+            }
+            ConfigRaftCmd::ConfigGrayPromote {
+                key,
+                history_id,
+                history_table_id,
+                op_time,
+                op_user,
+            } => {
+                let config_key: ConfigKey = (&key as &str).into();
+                self.promote_gray_config(
+                    config_key,
+                    history_id,
+                    history_table_id,
+                    op_time,
+                    op_user,
+                )
+                .ok();
+            }
         }
         Ok(ConfigRaftResult::None)
     }
@@ -973,3 +1662,317 @@ impl Handler<TransferDataRequest> for ConfigActor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::model::ConfigGrayRule;
+
+    fn add_config_cmd(key: &ConfigKey, value: &str, history_id: u64) -> ConfigRaftCmd {
+        ConfigRaftCmd::ConfigAdd {
+            key: key.build_key(),
+            value: Arc::new(value.to_owned()),
+            config_type: None,
+            desc: None,
+            history_id,
+            history_table_id: None,
+            op_time: now_millis_i64(),
+            op_user: None,
+            cas_md5: None,
+            tags: None,
+            note: None,
+        }
+    }
+
+    fn gray_set_cmd(key: &ConfigKey, value: &str, ips: &str) -> ConfigRaftCmd {
+        ConfigRaftCmd::ConfigGraySet {
+            key: key.build_key(),
+            value: Arc::new(value.to_owned()),
+            rule: ConfigGrayRule::new_by_ips(ips),
+            op_time: now_millis_i64(),
+            op_user: None,
+        }
+    }
+
+    async fn get_by_client(
+        addr: &Addr<ConfigActor>,
+        key: &ConfigKey,
+        ip: &str,
+    ) -> (Arc<String>, bool) {
+        let client = ConfigClientInfo::new_by_ip(ip.to_owned());
+        match addr
+            .send(ConfigCmd::GetByClient(key.clone(), client))
+            .await
+            .unwrap()
+            .unwrap()
+        {
+            ConfigResult::Data { value, beta, .. } => (value, beta),
+            _ => panic!("config not found"),
+        }
+    }
+
+    async fn has_gray(addr: &Addr<ConfigActor>, key: &ConfigKey) -> bool {
+        match addr
+            .send(ConfigCmd::GetGrayInfo(key.clone()))
+            .await
+            .unwrap()
+            .unwrap()
+        {
+            ConfigResult::GrayInfo(v) => v.is_some(),
+            _ => panic!("unexpected result"),
+        }
+    }
+
+    #[actix::test]
+    async fn gray_config_promote() {
+        let addr = ConfigActor::new().start();
+        let key = ConfigKey::new("app.yaml", "DEFAULT_GROUP", "");
+        addr.send(add_config_cmd(&key, "v1", 1))
+            .await
+            .unwrap()
+            .unwrap();
+        addr.send(gray_set_cmd(&key, "v2", "10.0.0.1"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(has_gray(&addr, &key).await);
+        let (value, beta) = get_by_client(&addr, &key, "10.0.0.1").await;
+        assert_eq!(value.as_str(), "v2");
+        assert!(beta);
+        let (value, beta) = get_by_client(&addr, &key, "10.0.0.2").await;
+        assert_eq!(value.as_str(), "v1");
+        assert!(!beta);
+
+        addr.send(ConfigRaftCmd::ConfigGrayPromote {
+            key: key.build_key(),
+            history_id: 2,
+            history_table_id: None,
+            op_time: now_millis_i64(),
+            op_user: None,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(!has_gray(&addr, &key).await);
+        for ip in ["10.0.0.1", "10.0.0.2"].iter() {
+            let (value, beta) = get_by_client(&addr, &key, ip).await;
+            assert_eq!(value.as_str(), "v2");
+            assert!(!beta);
+        }
+    }
+
+    #[actix::test]
+    async fn gray_config_stop() {
+        let addr = ConfigActor::new().start();
+        let key = ConfigKey::new("app.yaml", "DEFAULT_GROUP", "");
+        addr.send(add_config_cmd(&key, "v1", 1))
+            .await
+            .unwrap()
+            .unwrap();
+        addr.send(gray_set_cmd(&key, "v2", "10.0.0.1"))
+            .await
+            .unwrap()
+            .unwrap();
+        addr.send(ConfigRaftCmd::ConfigGrayRemove {
+            key: key.build_key(),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(!has_gray(&addr, &key).await);
+        let (value, beta) = get_by_client(&addr, &key, "10.0.0.1").await;
+        assert_eq!(value.as_str(), "v1");
+        assert!(!beta);
+    }
+
+    fn cas_add_config_cmd(
+        key: &ConfigKey,
+        value: &str,
+        history_id: u64,
+        cas_md5: &str,
+    ) -> ConfigRaftCmd {
+        let mut cmd = add_config_cmd(key, value, history_id);
+        if let ConfigRaftCmd::ConfigAdd { cas_md5: v, .. } = &mut cmd {
+            *v = Some(Arc::new(cas_md5.to_owned()));
+        }
+        cmd
+    }
+
+    #[actix::test]
+    async fn cas_config_conflict() {
+        let addr = ConfigActor::new().start();
+        let key = ConfigKey::new("app.yaml", "DEFAULT_GROUP", "");
+        addr.send(add_config_cmd(&key, "v1", 1))
+            .await
+            .unwrap()
+            .unwrap();
+        //两个写入方基于同一个版本更新
+        let cas_md5 = get_md5("v1");
+        let first = addr
+            .send(cas_add_config_cmd(&key, "v2", 2, &cas_md5))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(first, ConfigRaftResult::None));
+        let second = addr
+            .send(cas_add_config_cmd(&key, "v3", 3, &cas_md5))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(second, ConfigRaftResult::CasConflict));
+        let (value, _) = get_by_client(&addr, &key, "10.0.0.1").await;
+        assert_eq!(value.as_str(), "v2");
+        //raft apply的冲突结果转为ConfigCasConflictError返回
+        let err = check_config_set_response(Ok(ClientResponse::ConfigCasConflict)).unwrap_err();
+        assert!(err.is::<ConfigCasConflictError>());
+    }
+
+    #[test]
+    fn config_set_response_raft_error() {
+        assert!(check_config_set_response(Ok(ClientResponse::Success)).is_ok());
+        let err =
+            check_config_set_response(Err(anyhow::anyhow!("forward to leader error"))).unwrap_err();
+        assert!(!err.is::<ConfigCasConflictError>());
+    }
+
+    fn tags_add_config_cmd(
+        key: &ConfigKey,
+        value: &str,
+        history_id: u64,
+        tags: Option<&[&str]>,
+    ) -> ConfigRaftCmd {
+        let mut cmd = add_config_cmd(key, value, history_id);
+        if let ConfigRaftCmd::ConfigAdd { tags: v, .. } = &mut cmd {
+            *v = tags.map(|list| list.iter().map(|e| Arc::new(e.to_string())).collect());
+        }
+        cmd
+    }
+
+    async fn query_data_ids_by_tag(addr: &Addr<ConfigActor>, tag: &str) -> Vec<String> {
+        let param = ConfigQueryParam {
+            tenant: Some(Arc::new("".to_owned())),
+            tags: vec![Arc::new(tag.to_owned())],
+            limit: 100,
+            ..ConfigQueryParam::default()
+        };
+        match addr
+            .send(ConfigCmd::QueryPageInfo(Box::new(param)))
+            .await
+            .unwrap()
+            .unwrap()
+        {
+            ConfigResult::ConfigInfoPage(_, list) => {
+                let mut ids: Vec<String> = list.iter().map(|v| v.data_id.to_string()).collect();
+                ids.sort();
+                ids
+            }
+            _ => panic!("unexpected result"),
+        }
+    }
+
+    #[actix::test]
+    async fn config_tags_query() {
+        let addr = ConfigActor::new().start();
+        let key1 = ConfigKey::new("a.yaml", "DEFAULT_GROUP", "");
+        let key2 = ConfigKey::new("b.yaml", "DEFAULT_GROUP", "");
+        addr.send(tags_add_config_cmd(&key1, "v1", 1, Some(&["order"])))
+            .await
+            .unwrap()
+            .unwrap();
+        addr.send(tags_add_config_cmd(&key2, "v1", 2, Some(&["order", "pay"])))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            query_data_ids_by_tag(&addr, "order").await,
+            vec!["a.yaml", "b.yaml"]
+        );
+        assert_eq!(query_data_ids_by_tag(&addr, "pay").await, vec!["b.yaml"]);
+
+        //未传标签时保留原标签
+        addr.send(tags_add_config_cmd(&key2, "v2", 3, None))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(query_data_ids_by_tag(&addr, "pay").await, vec!["b.yaml"]);
+        //传入标签时替换原标签
+        addr.send(tags_add_config_cmd(&key2, "v3", 4, Some(&["user"])))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(query_data_ids_by_tag(&addr, "pay").await.is_empty());
+        assert_eq!(query_data_ids_by_tag(&addr, "user").await, vec!["b.yaml"]);
+        match addr
+            .send(ConfigCmd::GET(key2.clone()))
+            .await
+            .unwrap()
+            .unwrap()
+        {
+            ConfigResult::Data { tags, .. } => {
+                assert_eq!(tags, vec![Arc::new("user".to_owned())])
+            }
+            _ => panic!("config not found"),
+        }
+
+        addr.send(ConfigRaftCmd::ConfigRemove {
+            key: key1.build_key(),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            query_data_ids_by_tag(&addr, "order").await,
+            Vec::<String>::new()
+        );
+    }
+
+    #[actix::test]
+    async fn listener_query_stale() {
+        let addr = ConfigActor::new().start();
+        let key = ConfigKey::new("app.yaml", "DEFAULT_GROUP", "");
+        addr.send(add_config_cmd(&key, "v1", 1))
+            .await
+            .unwrap()
+            .unwrap();
+        addr.send(gray_set_cmd(&key, "v2", "10.0.0.3"))
+            .await
+            .unwrap()
+            .unwrap();
+        let subscribe = |conn_id: &str, ip: &str, md5: &str| {
+            ConfigCmd::Subscribe(
+                vec![ListenerItem::new(key.clone(), Arc::new(md5.to_owned()))],
+                Arc::new(conn_id.to_owned()),
+                ConfigClientInfo::new_by_ip(ip.to_owned()),
+            )
+        };
+        for cmd in [
+            subscribe("c1", "10.0.0.1", &get_md5("v1")),
+            subscribe("c2", "10.0.0.2", &get_md5("v0")),
+            //命中灰度的客户端按灰度配置md5比较
+            subscribe("c3", "10.0.0.3", &get_md5("v1")),
+        ] {
+            addr.send(cmd).await.unwrap().unwrap();
+        }
+        let param = ConfigListenerQueryParam {
+            data_id: Some(key.data_id.clone()),
+            group: Some(key.group.clone()),
+            ..Default::default()
+        };
+        let mut list = match addr
+            .send(ConfigCmd::QueryListenerInfo(Box::new(param)))
+            .await
+            .unwrap()
+            .unwrap()
+        {
+            ConfigResult::ListenerInfoList(list) => list,
+            _ => panic!("unexpected result"),
+        };
+        list.sort_by(|a, b| a.ip.cmp(&b.ip));
+        let stale: Vec<bool> = list.iter().map(|v| v.stale).collect();
+        assert_eq!(stale, vec![false, true, true]);
+        assert_eq!(
+            list[2].current_md5.as_ref().map(|v| v.to_string()),
+            Some(get_md5("v2"))
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use crate::common::appdata::AppShareData;
+use crate::config::core::{ConfigCmd, ConfigResult};
+use crate::config::model::{
+    ConfigListenerInfo, ConfigListenerQueryParam, ConfigListenerQueryResult,
+};
+use crate::raft::cluster::model::{RouterRequest, RouterResponse};
+use crate::raft::cluster::router_request;
+
+///
+/// 汇总集群所有节点的配置监听客户端；客户端只连接一个节点，需要逐个节点查询
+pub async fn query_cluster_listener_info(
+    app: &Arc<AppShareData>,
+    param: ConfigListenerQueryParam,
+) -> anyhow::Result<ConfigListenerQueryResult> {
+    let nodes = app.naming_node_manage.get_all_valid_nodes().await?;
+    let tasks = nodes.into_iter().map(|node| {
+        let param = param.clone();
+        async move {
+            let result = if node.is_local {
+                query_local_listener_info(app, param).await
+            } else {
+                query_remote_listener_info(app, param, node.addr).await
+            };
+            (node.id, result)
+        }
+    });
+    let node_results = futures_util::future::join_all(tasks).await;
+    Ok(merge_node_listener_info(node_results))
+}
+
+///
+/// 合并各节点的查询结果，查询失败的节点记录到fail_nodes
+fn merge_node_listener_info(
+    node_results: Vec<(u64, anyhow::Result<Vec<ConfigListenerInfo>>)>,
+) -> ConfigListenerQueryResult {
+    let mut result = ConfigListenerQueryResult::default();
+    for (node_id, node_result) in node_results {
+        match node_result {
+            Ok(list) => {
+                result.list.extend(list.into_iter().map(|mut e| {
+                    e.node_id = node_id;
+                    e
+                }));
+            }
+            Err(err) => {
+                log::warn!("query config listener from node {} error,{}", node_id, err);
+                result.fail_nodes.push(node_id);
+            }
+        }
+    }
+    result.stale_count = result.list.iter().filter(|e| e.stale).count();
+    result
+}
+
+async fn query_local_listener_info(
+    app: &Arc<AppShareData>,
+    param: ConfigListenerQueryParam,
+) -> anyhow::Result<Vec<ConfigListenerInfo>> {
+    match app
+        .config_addr
+        .send(ConfigCmd::QueryListenerInfo(Box::new(param)))
+        .await??
+    {
+        ConfigResult::ListenerInfoList(list) => Ok(list),
+        _ => Err(anyhow::anyhow!("query config listener error")),
+    }
+}
+
+async fn query_remote_listener_info(
+    app: &Arc<AppShareData>,
+    param: ConfigListenerQueryParam,
+    addr: Arc<String>,
+) -> anyhow::Result<Vec<ConfigListenerInfo>> {
+    let req = RouterRequest::ConfigListenerQuery { param };
+    match router_request(req, addr, &app.cluster_sender).await? {
+        RouterResponse::ConfigListenerResult { list } => Ok(list),
+        _ => Err(anyhow::anyhow!("query config listener error")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_info(ip: &str, stale: bool) -> ConfigListenerInfo {
+        ConfigListenerInfo {
+            data_id: Arc::new("app.yaml".to_owned()),
+            ip: Arc::new(ip.to_owned()),
+            stale,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn merge_cluster_listener_info() {
+        let node_results = vec![
+            (
+                1,
+                Ok(vec![
+                    build_info("10.0.0.1", true),
+                    build_info("10.0.0.2", false),
+                ]),
+            ),
+            (2, Ok(vec![build_info("10.0.0.3", true)])),
+            (3, Err(anyhow::anyhow!("timeout"))),
+        ];
+        let result = merge_node_listener_info(node_results);
+        assert_eq!(result.list.len(), 3);
+        assert_eq!(result.stale_count, 2);
+        assert_eq!(result.fail_nodes, vec![3]);
+        let stale_nodes: Vec<u64> = result
+            .list
+            .iter()
+            .filter(|e| e.stale)
+            .map(|e| e.node_id)
+            .collect();
+        assert_eq!(stale_nodes, vec![1, 2]);
+    }
+}
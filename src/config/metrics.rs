@@ -1,6 +1,8 @@
 use crate::config::core::ConfigActor;
 use crate::metrics::metrics_key::MetricsKey;
-use crate::metrics::model::{MetricsItem, MetricsQuery, MetricsRecord};
+use crate::metrics::model::{
+    LabeledMetricsItem, LabeledMetricsQuery, MetricsItem, MetricsQuery, MetricsRecord,
+};
 use actix::prelude::*;
 
 impl Handler<MetricsQuery> for ConfigActor {
@@ -48,3 +50,23 @@ impl Handler<MetricsQuery> for ConfigActor {
         Ok(list)
     }
 }
+
+impl Handler<LabeledMetricsQuery> for ConfigActor {
+    type Result = anyhow::Result<Vec<LabeledMetricsItem>>;
+
+    fn handle(&mut self, _msg: LabeledMetricsQuery, _ctx: &mut Self::Context) -> Self::Result {
+        let list = self
+            .tenant_index
+            .tenant_group
+            .iter()
+            .map(|(tenant, index)| {
+                LabeledMetricsItem::new(
+                    MetricsKey::ConfigNamespaceConfigSize,
+                    vec![tenant.as_ref().to_owned()],
+                    MetricsRecord::Gauge(index.get_config_count() as f32),
+                )
+            })
+            .collect();
+        Ok(list)
+    }
+}
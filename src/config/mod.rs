@@ -1,13 +1,20 @@
 use crate::common::constant::EMPTY_ARC_STRING;
+use std::collections::BTreeSet;
 use std::sync::Arc;
 
+pub mod config_archive;
+pub mod config_crypto;
 pub mod config_db;
+pub mod config_fuzzy_watch;
+pub mod config_history;
 pub mod config_index;
 pub mod config_sled;
 pub mod config_subscribe;
 pub mod config_type;
+pub mod config_validate;
 pub mod core;
 pub mod dal;
+pub mod listener_query;
 pub mod metrics;
 pub mod model;
 pub mod utils;
@@ -36,4 +43,22 @@ impl ConfigUtils {
     pub fn is_default_tenant(val: &str) -> bool {
         val == DEFAULT_TENANT
     }
+
+    ///
+    /// 解析逗号分隔的配置标签，去重并排序
+    pub fn build_tags(val: &str) -> Vec<Arc<String>> {
+        let tags: BTreeSet<&str> = val
+            .split(',')
+            .map(|e| e.trim())
+            .filter(|e| !e.is_empty())
+            .collect();
+        tags.into_iter().map(|e| Arc::new(e.to_owned())).collect()
+    }
+
+    pub fn tags_to_string(tags: &[Arc<String>]) -> String {
+        tags.iter()
+            .map(|e| e.as_str())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
 }
@@ -1,9 +1,13 @@
+use crate::common::model::ClientVersion;
 use crate::config::config_type::ConfigType;
 use crate::config::core::{ConfigHistoryInfoDto, ConfigKey, ConfigValue};
+use crate::config::ConfigUtils;
 use crate::utils::get_md5;
 use actix::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
+use thiserror::Error;
 
 #[derive(Message)]
 #[rtype(result = "anyhow::Result<ConfigRaftResult>")]
@@ -17,6 +21,9 @@ pub enum ConfigRaftCmd {
         history_table_id: Option<u64>,
         op_time: i64,
         op_user: Option<Arc<String>>,
+        cas_md5: Option<Arc<String>>,
+        tags: Option<Vec<Arc<String>>>,
+        note: Option<Arc<String>>,
     },
     ConfigRemove {
         key: String,
@@ -26,6 +33,23 @@ pub enum ConfigRaftCmd {
         value: ConfigValue,
         last_id: Option<u64>,
     },
+    ConfigGraySet {
+        key: String,
+        value: Arc<String>,
+        rule: ConfigGrayRule,
+        op_time: i64,
+        op_user: Option<Arc<String>>,
+    },
+    ConfigGrayRemove {
+        key: String,
+    },
+    ConfigGrayPromote {
+        key: String,
+        history_id: u64,
+        history_table_id: Option<u64>,
+        op_time: i64,
+        op_user: Option<Arc<String>>,
+    },
 }
 
 #[derive(Debug)]
@@ -38,6 +62,229 @@ pub struct SetConfigParam {
     pub history_table_id: Option<u64>,
     pub op_time: i64,
     pub op_user: Option<Arc<String>>,
+    /// 期望的当前配置md5,不一致时不更新
+    pub cas_md5: Option<Arc<String>>,
+    /// 配置标签，None表示不变更
+    pub tags: Option<Vec<Arc<String>>>,
+    /// 历史记录备注，如回滚说明
+    pub note: Option<Arc<String>>,
+}
+
+///
+/// 灰度(beta)发布规则
+/// ip列表或连接标签任一匹配即命中灰度
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigGrayRule {
+    #[serde(default)]
+    pub ips: Vec<String>,
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+}
+
+impl ConfigGrayRule {
+    pub fn new_by_ips(ips: &str) -> Self {
+        Self {
+            ips: ips
+                .split(',')
+                .map(|e| e.trim())
+                .filter(|e| !e.is_empty())
+                .map(|e| e.to_owned())
+                .collect(),
+            labels: Default::default(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ips.is_empty() && self.labels.is_empty()
+    }
+
+    pub fn match_client(&self, client: &ConfigClientInfo) -> bool {
+        if !client.ip.is_empty() && self.ips.iter().any(|e| e == client.ip.as_str()) {
+            return true;
+        }
+        if self.labels.is_empty() {
+            return false;
+        }
+        for (k, v) in &self.labels {
+            if client.labels.get(k) != Some(v) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+///
+/// 配置客户端信息，用于匹配灰度规则及查询监听客户端
+#[derive(Debug, Clone, Default)]
+pub struct ConfigClientInfo {
+    pub ip: Arc<String>,
+    pub labels: Arc<HashMap<String, String>>,
+    pub client_version: Arc<ClientVersion>,
+}
+
+impl ConfigClientInfo {
+    pub fn new(ip: Arc<String>, labels: Arc<HashMap<String, String>>) -> Self {
+        Self {
+            ip,
+            labels,
+            client_version: Default::default(),
+        }
+    }
+
+    pub fn new_by_ip(ip: String) -> Self {
+        Self {
+            ip: Arc::new(ip),
+            labels: Default::default(),
+            client_version: Default::default(),
+        }
+    }
+
+    pub fn app_name(&self) -> Option<String> {
+        self.labels
+            .get(CLIENT_APP_NAME_LABEL)
+            .filter(|v| !v.is_empty())
+            .cloned()
+    }
+
+    pub fn sdk_version(&self) -> Option<String> {
+        if self.client_version.version.is_empty() {
+            None
+        } else {
+            Some(self.client_version.to_string())
+        }
+    }
+}
+
+/// 客户端应用名标签，nacos sdk建立连接时上报
+pub const CLIENT_APP_NAME_LABEL: &str = "AppName";
+
+///
+/// 配置监听客户端查询条件，按配置查询或按客户端ip查询
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigListenerQueryParam {
+    pub tenant: Option<Arc<String>>,
+    pub group: Option<Arc<String>>,
+    pub data_id: Option<Arc<String>>,
+    pub ip: Option<Arc<String>>,
+}
+
+impl ConfigListenerQueryParam {
+    pub fn to_key(&self) -> Option<ConfigKey> {
+        let data_id = self.data_id.as_ref().filter(|v| !v.is_empty())?;
+        let group = self
+            .group
+            .clone()
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| Arc::new("DEFAULT_GROUP".to_owned()));
+        let tenant = ConfigUtils::default_tenant_arc(self.tenant.clone().unwrap_or_default());
+        Some(ConfigKey::new_by_arc(data_id.clone(), group, tenant))
+    }
+
+    pub fn get_ip(&self) -> Option<&Arc<String>> {
+        self.ip.as_ref().filter(|v| !v.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigListenerInfo {
+    pub tenant: Arc<String>,
+    pub group: Arc<String>,
+    pub data_id: Arc<String>,
+    /// 监听方式：grpc、http
+    pub listen_type: String,
+    /// grpc连接id，http长轮询为空
+    pub connection_id: Option<Arc<String>>,
+    pub ip: Arc<String>,
+    pub sdk_version: Option<String>,
+    pub app_name: Option<String>,
+    /// 客户端最近一次上报的md5
+    pub md5: Arc<String>,
+    /// 客户端应拿到的md5，命中灰度时为灰度配置md5；配置不存在时为空
+    pub current_md5: Option<Arc<String>>,
+    /// 客户端上报的md5与当前配置不一致
+    pub stale: bool,
+    pub node_id: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigListenerQueryResult {
+    pub list: Vec<ConfigListenerInfo>,
+    pub stale_count: usize,
+    /// 查询失败的节点
+    pub fail_nodes: Vec<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigGrayValue {
+    pub content: Arc<String>,
+    pub md5: Arc<String>,
+    pub rule: Arc<ConfigGrayRule>,
+    pub op_user: Option<Arc<String>>,
+    pub last_modified: i64,
+}
+
+impl ConfigGrayValue {
+    pub fn new(
+        content: Arc<String>,
+        rule: ConfigGrayRule,
+        op_user: Option<Arc<String>>,
+        last_modified: i64,
+    ) -> Self {
+        let md5 = Arc::new(get_md5(&content));
+        Self {
+            content,
+            md5,
+            rule: Arc::new(rule),
+            op_user,
+            last_modified,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigGrayInfoDto {
+    pub content: Arc<String>,
+    pub md5: Arc<String>,
+    pub rule: Arc<ConfigGrayRule>,
+    pub op_user: Option<Arc<String>>,
+    pub last_modified: i64,
+}
+
+impl From<&ConfigGrayValue> for ConfigGrayInfoDto {
+    fn from(value: &ConfigGrayValue) -> Self {
+        Self {
+            content: value.content.clone(),
+            md5: value.md5.clone(),
+            rule: value.rule.clone(),
+            op_user: value.op_user.clone(),
+            last_modified: value.last_modified,
+        }
+    }
+}
+
+///
+/// 灰度发布操作请求，由leader节点转换为raft请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ConfigGrayReq {
+    Publish {
+        key: String,
+        value: Arc<String>,
+        rule: ConfigGrayRule,
+        op_user: Option<Arc<String>>,
+    },
+    Promote {
+        key: String,
+        op_user: Option<Arc<String>>,
+    },
+    Stop {
+        key: String,
+    },
 }
 
 pub enum ConfigRaftResult {
@@ -45,15 +292,23 @@ pub enum ConfigRaftResult {
         data: Vec<(ConfigKey, Arc<String>)>,
         history_table_id: u64,
     },
+    CasConflict,
     None,
 }
 
+///
+/// casMd5与当前配置md5不一致
+#[derive(Debug, Clone, Error)]
+#[error("config cas md5 conflict, the config has been modified")]
+pub struct ConfigCasConflictError;
+
 #[derive(Clone)]
 pub struct HistoryItem {
     pub id: u64,
     pub content: Arc<String>,
     pub modified_time: i64,
     pub op_user: Option<Arc<String>>,
+    pub note: Option<Arc<String>>,
 }
 
 impl HistoryItem {
@@ -66,6 +321,7 @@ impl HistoryItem {
             content: Some(self.content.to_string()),
             modified_time: Some(self.modified_time),
             op_user: self.op_user.as_ref().map(|e| e.to_string()),
+            note: self.note.as_ref().map(|e| e.to_string()),
         }
     }
 }
@@ -80,6 +336,23 @@ pub struct ConfigHistoryItemDO {
     pub last_time: Option<i64>,
     #[prost(string, optional, tag = "4")]
     pub op_user: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub note: Option<String>,
+}
+
+impl ConfigHistoryItemDO {
+    pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        use prost::Message;
+        let mut v = Vec::new();
+        self.encode(&mut v)?;
+        Ok(v)
+    }
+
+    pub fn from_bytes(data: &[u8]) -> anyhow::Result<Self> {
+        use prost::Message;
+        let s = Self::decode(data)?;
+        Ok(s)
+    }
 }
 
 impl From<HistoryItem> for ConfigHistoryItemDO {
@@ -89,6 +362,7 @@ impl From<HistoryItem> for ConfigHistoryItemDO {
             content: Some(value.content.as_ref().to_string()),
             last_time: Some(value.modified_time),
             op_user: value.op_user.map(|e| e.as_ref().to_string()),
+            note: value.note.map(|e| e.as_ref().to_string()),
         }
     }
 }
@@ -100,9 +374,52 @@ impl From<ConfigHistoryItemDO> for HistoryItem {
             content: Arc::new(value.content.unwrap_or_default()),
             modified_time: value.last_time.unwrap_or_default(),
             op_user: value.op_user.map(Arc::new),
+            note: value.note.map(Arc::new),
+        }
+    }
+}
+#[derive(Clone, PartialEq, prost_derive::Message, Deserialize, Serialize)]
+pub struct ConfigGrayDO {
+    #[prost(string, optional, tag = "1")]
+    pub content: Option<String>,
+    #[prost(string, repeated, tag = "2")]
+    pub ips: Vec<String>,
+    #[prost(map = "string, string", tag = "3")]
+    pub labels: HashMap<String, String>,
+    #[prost(string, optional, tag = "4")]
+    pub op_user: Option<String>,
+    #[prost(int64, optional, tag = "5")]
+    pub last_time: Option<i64>,
+}
+
+impl From<ConfigGrayValue> for ConfigGrayDO {
+    fn from(value: ConfigGrayValue) -> Self {
+        let rule = value.rule.as_ref().to_owned();
+        Self {
+            content: Some(value.content.as_ref().to_owned()),
+            ips: rule.ips,
+            labels: rule.labels,
+            op_user: value.op_user.map(|e| e.as_ref().to_owned()),
+            last_time: Some(value.last_modified),
         }
     }
 }
+
+impl From<ConfigGrayDO> for ConfigGrayValue {
+    fn from(value: ConfigGrayDO) -> Self {
+        let rule = ConfigGrayRule {
+            ips: value.ips,
+            labels: value.labels,
+        };
+        ConfigGrayValue::new(
+            Arc::new(value.content.unwrap_or_default()),
+            rule,
+            value.op_user.map(Arc::new),
+            value.last_time.unwrap_or_default(),
+        )
+    }
+}
+
 #[derive(Clone, PartialEq, prost_derive::Message, Deserialize, Serialize)]
 pub struct ConfigValueDO {
     #[prost(string, optional, tag = "1")]
@@ -113,6 +430,10 @@ pub struct ConfigValueDO {
     pub config_type: Option<String>,
     #[prost(string, optional, tag = "4")]
     pub desc: Option<String>,
+    #[prost(message, optional, tag = "5")]
+    pub gray: Option<ConfigGrayDO>,
+    #[prost(string, repeated, tag = "6")]
+    pub tags: Vec<String>,
 }
 
 impl ConfigValueDO {
@@ -137,6 +458,8 @@ impl From<ConfigValue> for ConfigValueDO {
             histories: value.histories.into_iter().map(|e| e.into()).collect(),
             config_type: value.config_type.map(|e| e.as_ref().to_owned()),
             desc: value.desc.map(|e| e.as_ref().to_owned()),
+            gray: value.gray.map(|e| e.into()),
+            tags: value.tags.iter().map(|e| e.as_ref().to_owned()).collect(),
         }
     }
 }
@@ -161,6 +484,64 @@ impl From<ConfigValueDO> for ConfigValue {
                 .map(|v| ConfigType::new_by_value(&v).get_value()),
             desc: value.desc.map(Arc::new),
             last_modified,
+            gray: value.gray.map(|e| e.into()),
+            tags: value.tags.into_iter().map(Arc::new).collect(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_client(ip: &str, labels: &[(&str, &str)]) -> ConfigClientInfo {
+        let labels: HashMap<String, String> = labels
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        ConfigClientInfo::new(Arc::new(ip.to_owned()), Arc::new(labels))
+    }
+
+    #[test]
+    fn gray_rule_match_ip() {
+        let rule = ConfigGrayRule::new_by_ips("10.0.0.1, 10.0.0.2,");
+        assert_eq!(rule.ips.len(), 2);
+        assert!(rule.match_client(&build_client("10.0.0.2", &[])));
+        assert!(!rule.match_client(&build_client("10.0.0.3", &[])));
+        assert!(!rule.match_client(&build_client("", &[])));
+        assert!(ConfigGrayRule::new_by_ips(" , ").is_empty());
+    }
+
+    #[test]
+    fn gray_rule_match_labels() {
+        let mut rule = ConfigGrayRule::default();
+        rule.labels.insert("app".to_owned(), "order".to_owned());
+        rule.labels.insert("env".to_owned(), "test".to_owned());
+        assert!(rule.match_client(&build_client(
+            "10.0.0.1",
+            &[("app", "order"), ("env", "test"), ("zone", "a")]
+        )));
+        //需要所有标签都匹配
+        assert!(!rule.match_client(&build_client("10.0.0.1", &[("app", "order")])));
+        assert!(!rule.match_client(&build_client(
+            "10.0.0.1",
+            &[("app", "order"), ("env", "prod")]
+        )));
+        //ip与标签任一匹配即命中
+        rule.ips.push("10.0.0.9".to_owned());
+        assert!(rule.match_client(&build_client("10.0.0.9", &[])));
+    }
+
+    #[test]
+    fn config_value_tags_persist() {
+        let mut value = ConfigValue::init(Arc::new("v1".to_owned()), 1, 1, None, None);
+        value.tags = vec![Arc::new("order".to_owned()), Arc::new("pay".to_owned())];
+        let bytes = ConfigValueDO::from(value).to_bytes().unwrap();
+        let value: ConfigValue = ConfigValueDO::from_bytes(&bytes).unwrap().into();
+        assert_eq!(value.content.as_str(), "v1");
+        assert_eq!(
+            value.tags,
+            vec![Arc::new("order".to_owned()), Arc::new("pay".to_owned())]
+        );
+    }
+}
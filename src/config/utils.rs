@@ -1,8 +1,6 @@
 pub mod param_utils {
     use anyhow::Ok;
 
-    use super::property_util;
-
     const VALID_CHARS: [char; 4] = ['_', '-', '.', ':'];
     const TENANT_MAX_LEN: usize = 128;
 
@@ -49,15 +47,11 @@ pub mod param_utils {
             }
             None => return Err(anyhow::anyhow!("invalid datumId : ")),
         }
+        // 内容大小在发布时按命名空间配额检查，命名空间未设置时使用全局的config_max_content
         match content {
             Some(content) => {
                 if content.is_empty() {
                     return Err(anyhow::anyhow!("content is blank : {}", content));
-                } else if content.len() > property_util::get_max_content() {
-                    return Err(anyhow::anyhow!(
-                        "invalid content, over {}",
-                        property_util::get_max_content()
-                    ));
                 }
             }
             None => return Err(anyhow::anyhow!("content is blank : ")),
@@ -18,7 +18,8 @@ use crate::openapi::naming::instance::{del_instance, get_instance, update_instan
 use crate::openapi::naming::service::{
     query_service, query_subscribers_list, remove_service, update_service,
 };
-use crate::user_namespace_privilege;
+use crate::user::permission::{PermissionAction, PermissionResource};
+use crate::{user_namespace_privilege, user_scope_filter};
 use actix_web::{http::header, web, HttpMessage, HttpRequest, HttpResponse, Responder};
 use uuid::Uuid;
 
@@ -33,12 +34,26 @@ pub async fn query_namespace_list(
     let namespaces = NamespaceUtils::get_namespaces(&app_data)
         .await
         .unwrap_or_default();
-    let namespaces = if namespace_privilege.is_all() {
+    let scope_filter =
+        user_scope_filter!(req, PermissionResource::Namespace, PermissionAction::Read);
+    let namespaces = if namespace_privilege.is_all() && scope_filter.is_none() {
         namespaces
     } else {
         namespaces
             .into_iter()
             .filter(|e| namespace_privilege.check_option_value_permission(&e.namespace_id, false))
+            .filter(|e| {
+                scope_filter.as_ref().is_none_or(|v| {
+                    v.is_match(
+                        e.namespace_id
+                            .as_ref()
+                            .map(|v| v.as_str())
+                            .unwrap_or_default(),
+                        "",
+                        "",
+                    )
+                })
+            })
             .collect()
     };
     let result = ConsoleResult::success(namespaces);
@@ -266,6 +281,38 @@ pub fn console_api_config_v2(config: &mut web::ServiceConfig) {
                 web::resource("/cluster/cluster_node_list")
                     .route(web::get().to(v2::cluster_api::query_cluster_info)),
             )
+            .service(
+                web::resource("/cluster/raft/members")
+                    .route(web::get().to(v2::cluster_api::query_raft_members)),
+            )
+            .service(
+                web::resource("/cluster/raft/promote")
+                    .route(web::post().to(v2::cluster_api::promote_raft_node)),
+            )
+            .service(
+                web::resource("/cluster/raft/remove")
+                    .route(web::post().to(v2::cluster_api::remove_raft_node)),
+            )
+            .service(
+                web::resource("/cluster/raft/snapshot")
+                    .route(web::post().to(v2::cluster_api::build_raft_snapshot)),
+            )
+            .service(
+                web::resource("/connection/list")
+                    .route(web::get().to(v2::connection_api::query_conn_list)),
+            )
+            .service(
+                web::resource("/connection/node_counts")
+                    .route(web::get().to(v2::connection_api::query_node_conn_counts)),
+            )
+            .service(
+                web::resource("/connection/eject")
+                    .route(web::post().to(v2::connection_api::eject_connection)),
+            )
+            .service(
+                web::resource("/connection/rebalance")
+                    .route(web::post().to(v2::connection_api::rebalance_connection)),
+            )
             .service(
                 web::resource("/config/import")
                     .route(web::post().to(v2::config_api::import_config)),
@@ -291,6 +338,38 @@ pub fn console_api_config_v2(config: &mut web::ServiceConfig) {
                 web::resource("/config/history")
                     .route(web::get().to(v2::config_api::query_history_config_page)),
             )
+            .service(
+                web::resource("/config/history/diff")
+                    .route(web::get().to(v2::config_api::diff_history_config)),
+            )
+            .service(
+                web::resource("/config/history/rollback")
+                    .route(web::post().to(v2::config_api::rollback_history_config)),
+            )
+            .service(
+                web::resource("/config/encrypt/rotate")
+                    .route(web::post().to(v2::config_api::rotate_encrypt_key)),
+            )
+            .service(
+                web::resource("/config/gray/info")
+                    .route(web::get().to(v2::config_api::get_gray_config)),
+            )
+            .service(
+                web::resource("/config/gray/publish")
+                    .route(web::post().to(v2::config_api::publish_gray_config)),
+            )
+            .service(
+                web::resource("/config/gray/promote")
+                    .route(web::post().to(v2::config_api::promote_gray_config)),
+            )
+            .service(
+                web::resource("/config/gray/stop")
+                    .route(web::post().to(v2::config_api::stop_gray_config)),
+            )
+            .service(
+                web::resource("/config/listener/list")
+                    .route(web::get().to(v2::config_api::query_config_listeners)),
+            )
             .service(
                 web::resource("/service/list")
                     .route(web::get().to(v2::naming_api::query_service_list)),
@@ -335,6 +414,59 @@ pub fn console_api_config_v2(config: &mut web::ServiceConfig) {
                 web::resource("/transfer/import")
                     .route(web::post().to(transfer_api::import_transfer_file)),
             )
+            .service(
+                web::resource("/audit/list")
+                    .route(web::get().to(v2::audit_api::query_audit_log_list)),
+            )
+            .service(
+                web::resource("/audit/export")
+                    .route(web::get().to(v2::audit_api::download_audit_log)),
+            )
+            .service(
+                web::resource("/webhook/list")
+                    .route(web::get().to(v2::webhook_api::query_webhook_list)),
+            )
+            .service(
+                web::resource("/webhook/add").route(web::post().to(v2::webhook_api::set_webhook)),
+            )
+            .service(
+                web::resource("/webhook/update")
+                    .route(web::post().to(v2::webhook_api::set_webhook)),
+            )
+            .service(
+                web::resource("/webhook/remove")
+                    .route(web::post().to(v2::webhook_api::remove_webhook)),
+            )
+            .service(
+                web::resource("/webhook/deadletter/list")
+                    .route(web::get().to(v2::webhook_api::query_dead_letter_list)),
+            )
+            .service(
+                web::resource("/webhook/deadletter/retry")
+                    .route(web::post().to(v2::webhook_api::retry_dead_letter)),
+            )
+            .service(
+                web::resource("/webhook/deadletter/remove")
+                    .route(web::post().to(v2::webhook_api::remove_dead_letter)),
+            )
+            .service(
+                web::resource("/role/list").route(web::get().to(v2::role_api::query_role_list)),
+            )
+            .service(web::resource("/role/add").route(web::post().to(v2::role_api::add_role)))
+            .service(web::resource("/role/update").route(web::post().to(v2::role_api::update_role)))
+            .service(web::resource("/role/remove").route(web::post().to(v2::role_api::remove_role)))
+            .service(
+                web::resource("/accesskey/list")
+                    .route(web::get().to(v2::access_key_api::query_access_key_list)),
+            )
+            .service(
+                web::resource("/accesskey/add")
+                    .route(web::post().to(v2::access_key_api::create_access_key)),
+            )
+            .service(
+                web::resource("/accesskey/remove")
+                    .route(web::post().to(v2::access_key_api::revoke_access_key)),
+            )
             .service(
                 web::resource("/metrics/timeline")
                     .route(web::get().to(v2::metrics_api::query_metrics_timeline))
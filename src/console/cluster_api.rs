@@ -7,7 +7,12 @@ use crate::common::appdata::AppShareData;
 use super::model::{cluster_model::ClusterNodeInfo, ConsoleResult};
 
 pub async fn query_cluster_info(app: web::Data<Arc<AppShareData>>) -> impl Responder {
-    let nodes = app.naming_node_manage.get_all_valid_nodes().await.unwrap();
+    let nodes = match app.naming_node_manage.get_all_valid_nodes().await {
+        Ok(v) => v,
+        Err(err) => {
+            return HttpResponse::Ok().json(ConsoleResult::<()>::error(err.to_string()));
+        }
+    };
     let leader_node = app.raft.current_leader().await;
     let mut list = vec![];
     for node in nodes {
@@ -1,5 +1,6 @@
 #![allow(unused_imports)]
 
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::sync::Arc;
@@ -8,20 +9,25 @@ use actix_multipart::form::tempfile::TempFile;
 use actix_multipart::form::text::Text;
 use actix_multipart::form::MultipartForm;
 use actix_web::{http::header, web, Error, HttpMessage, HttpRequest, HttpResponse, Responder};
-use zip::write::FileOptions;
 
 use super::model::PageResult;
 use crate::common::appdata::AppShareData;
+use crate::common::model::{ApiResult, UserSession};
+use crate::config::config_archive::{
+    plan_config_import, read_config_archive, write_config_archive, ConfigImportItemResult,
+    ConfigImportPolicy, ConfigImportReport, ConfigImportResultType,
+};
 use crate::config::core::{ConfigActor, ConfigCmd, ConfigInfoDto, ConfigKey, ConfigResult};
 use crate::config::ConfigUtils;
 use crate::console::model::config_model::{
-    ConfigParams, OpsConfigOptQueryListResponse, OpsConfigQueryListRequest,
+    ConfigParams, OpsConfigImportInfo, OpsConfigOptQueryListResponse, OpsConfigQueryListRequest,
 };
+use crate::console::v2::{ERROR_CODE_PARAM_ERROR, ERROR_CODE_SYSTEM_ERROR};
 use crate::raft::cluster::model::SetConfigReq;
-use crate::{now_millis, user_namespace_privilege};
+use crate::user::permission::{PermissionAction, PermissionResource};
+use crate::{now_millis, user_namespace_privilege, user_scope_filter};
 use actix::prelude::Addr;
 use tokio_stream::StreamExt;
-use zip::ZipWriter;
 
 pub async fn query_config_list(
     req: HttpRequest,
@@ -39,23 +45,24 @@ pub async fn query_config_list(
         ));
     }
     let cmd = ConfigCmd::QueryPageInfo(Box::new(param));
-    match config_addr.send(cmd).await {
-        Ok(res) => {
-            let r: ConfigResult = res.unwrap();
-            match r {
-                ConfigResult::ConfigInfoPage(size, list) => {
-                    let response = OpsConfigOptQueryListResponse {
-                        count: size as u64,
-                        list,
-                    };
-                    let v = serde_json::to_string(&response).unwrap();
-                    HttpResponse::Ok()
-                        .insert_header(header::ContentType(mime::APPLICATION_JSON))
-                        .body(v)
-                }
-                _ => HttpResponse::InternalServerError().body("config result error"),
+    let res = match config_addr.send(cmd).await {
+        Ok(res) => res,
+        Err(err) => Err(err.into()),
+    };
+    match res {
+        Ok(r) => match r {
+            ConfigResult::ConfigInfoPage(size, list) => {
+                let response = OpsConfigOptQueryListResponse {
+                    count: size as u64,
+                    list,
+                };
+                let v = serde_json::to_string(&response).unwrap();
+                HttpResponse::Ok()
+                    .insert_header(header::ContentType(mime::APPLICATION_JSON))
+                    .body(v)
             }
-        }
+            _ => HttpResponse::InternalServerError().body("config result error"),
+        },
         Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
     }
 }
@@ -71,23 +78,24 @@ pub async fn query_history_config_page(
         }
     };
     let cmd = ConfigCmd::QueryHistoryPageInfo(Box::new(param));
-    match config_addr.send(cmd).await {
-        Ok(res) => {
-            let r: ConfigResult = res.unwrap();
-            match r {
-                ConfigResult::ConfigHistoryInfoPage(size, list) => {
-                    let response = PageResult {
-                        count: size as u64,
-                        list,
-                    };
-                    let v = serde_json::to_string(&response).unwrap();
-                    HttpResponse::Ok()
-                        .insert_header(header::ContentType(mime::APPLICATION_JSON))
-                        .body(v)
-                }
-                _ => HttpResponse::InternalServerError().body("config result error"),
+    let res = match config_addr.send(cmd).await {
+        Ok(res) => res,
+        Err(err) => Err(err.into()),
+    };
+    match res {
+        Ok(r) => match r {
+            ConfigResult::ConfigHistoryInfoPage(size, list) => {
+                let response = PageResult {
+                    count: size as u64,
+                    list,
+                };
+                let v = serde_json::to_string(&response).unwrap();
+                HttpResponse::Ok()
+                    .insert_header(header::ContentType(mime::APPLICATION_JSON))
+                    .body(v)
             }
-        }
+            _ => HttpResponse::InternalServerError().body("config result error"),
+        },
         Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
     }
 }
@@ -102,13 +110,18 @@ pub struct UploadForm {
 
 pub async fn import_config(
     req: HttpRequest,
+    web::Query(param): web::Query<OpsConfigImportInfo>,
     MultipartForm(form): MultipartForm<UploadForm>,
     app: web::Data<Arc<AppShareData>>,
 ) -> Result<impl Responder, Error> {
     let tenant = Arc::new(ConfigUtils::default_tenant(
         match req.headers().get("tenant") {
             Some(v) => String::from_utf8_lossy(v.as_bytes()).to_string(),
-            None => "".to_owned(),
+            None => param
+                .tenant
+                .clone()
+                .or(form.tenant.map(|v| v.0))
+                .unwrap_or_default(),
         },
     ));
     let namespace_privilege = user_namespace_privilege!(req);
@@ -118,72 +131,123 @@ pub async fn import_config(
             tenant.as_str()
         )));
     }
-    //let tenant = Arc::new(ConfigUtils::default_tenant(config_info.0.tenant.unwrap_or_default()));
+    let policy = match ConfigImportPolicy::from_name(param.policy.as_deref().unwrap_or_default()) {
+        Ok(v) => v,
+        Err(err) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                ERROR_CODE_PARAM_ERROR.to_string(),
+                Some(err.to_string()),
+            )));
+        }
+    };
+    let dry_run = param.dry_run.unwrap_or(false);
+    let mut items = vec![];
+    let mut results = vec![];
     for f in form.files {
-        match zip::ZipArchive::new(f.file) {
-            Ok(mut archive) => {
-                for i in 0..archive.len() {
-                    let mut file = archive.by_index(i).unwrap();
-                    /*
-                    let filepath = match file.enclosed_name() {
-                        Some(path) => path,
-                        None => continue,
-                    };
-                    */
-                    let filename = file.name();
-                    if !(*filename).ends_with('/') {
-                        let parts = filename.split('/').collect::<Vec<_>>();
-                        if parts.len() != 2 {
-                            continue;
-                        }
-                        assert!(parts.len() == 2);
-                        let config_key = ConfigKey::new_by_arc(
-                            Arc::new(parts[1].to_owned()),
-                            Arc::new(parts[0].to_owned()),
-                            tenant.clone(),
-                        );
-                        let value = match io::read_to_string(&mut file) {
-                            Ok(v) => v,
-                            Err(_) => continue,
-                        };
-                        //println!("update load, {:?}:{}",&config_key,&value);
-                        //config_addr.do_send(ConfigAsyncCmd::Add(config_key, Arc::new(value)));
-                        let mut req = SetConfigReq::new(config_key.clone(), Arc::new(value));
-                        let data_id_clone = config_key.data_id.clone();
-                        req.config_type = SetConfigReq::detect_config_type(data_id_clone);
-
-                        app.config_route.set_config(req).await.ok();
-                    }
-                }
+        match read_config_archive(f.file) {
+            Ok((list, failed)) => {
+                items.extend(list);
+                results.extend(failed);
+            }
+            Err(err) => {
+                return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                    ERROR_CODE_PARAM_ERROR.to_string(),
+                    Some(format!("import file is invalid,{}", err)),
+                )));
             }
-            Err(_) => todo!(),
         }
     }
-    Ok(HttpResponse::Ok().finish())
-}
-
-fn zip_file(mut zip: ZipWriter<&mut File>, list: Vec<ConfigInfoDto>) -> anyhow::Result<()> {
-    if list.is_empty() {
-        let options = FileOptions::default()
-            .compression_method(zip::CompressionMethod::Stored)
-            .unix_permissions(0o755);
-        zip.start_file(".ignore", options)?;
-        zip.write_all("empty config".as_bytes())?;
+    let scope_filter = user_scope_filter!(req, PermissionResource::Config, PermissionAction::Write);
+    let (items, no_permission): (Vec<_>, Vec<_>) = items.into_iter().partition(|e| {
+        scope_filter
+            .as_ref()
+            .is_none_or(|v| v.is_match(&tenant, &e.group, &e.data_id))
+    });
+    for item in no_permission {
+        results.push(ConfigImportItemResult::new(
+            item.group,
+            item.data_id,
+            ConfigImportResultType::Failed,
+            Some("no permission".to_owned()),
+        ));
     }
-    for item in &list {
-        zip.add_directory(item.group.as_str(), Default::default())
-            .ok();
-        let options = FileOptions::default()
-            .compression_method(zip::CompressionMethod::Stored)
-            .unix_permissions(0o755);
-        zip.start_file(
-            format!("{}/{}", &item.group.as_str(), &item.data_id.as_str()),
-            options,
-        )?;
-        zip.write_all(item.content.as_ref().unwrap().as_bytes())?;
+    let keys: Vec<ConfigKey> = items
+        .iter()
+        .map(|e| ConfigKey::new_by_arc(e.data_id.clone(), e.group.clone(), tenant.clone()))
+        .collect();
+    let exists = match app
+        .config_addr
+        .send(ConfigCmd::QueryInfoByKeys(Box::new(keys)))
+        .await
+    {
+        Ok(Ok(ConfigResult::ConfigInfoPage(_, list))) => list
+            .into_iter()
+            .map(|e| (e.group, e.data_id))
+            .collect::<HashSet<_>>(),
+        _ => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                ERROR_CODE_SYSTEM_ERROR.to_string(),
+                Some("query config error".to_owned()),
+            )));
+        }
+    };
+    let op_user = req
+        .extensions()
+        .get::<Arc<UserSession>>()
+        .map(|session| session.username.clone());
+    let plan = plan_config_import(&items, &exists, policy);
+    for (item, result) in items.into_iter().zip(plan) {
+        let message = match result {
+            ConfigImportResultType::Skipped if policy == ConfigImportPolicy::Abort => {
+                Some("import aborted".to_owned())
+            }
+            ConfigImportResultType::Skipped => Some("config already exists".to_owned()),
+            ConfigImportResultType::Failed => Some("config already exists".to_owned()),
+            _ => None,
+        };
+        let writable = matches!(
+            result,
+            ConfigImportResultType::Created | ConfigImportResultType::Updated
+        );
+        if !writable || dry_run {
+            results.push(ConfigImportItemResult::new(
+                item.group,
+                item.data_id,
+                result,
+                message,
+            ));
+            continue;
+        }
+        let config_key =
+            ConfigKey::new_by_arc(item.data_id.clone(), item.group.clone(), tenant.clone());
+        let mut set_req = SetConfigReq::new(config_key, item.content);
+        set_req.config_type = item
+            .config_type
+            .or_else(|| SetConfigReq::detect_config_type(set_req.config_key.data_id.clone()));
+        set_req.desc = item.desc;
+        set_req.tags = item.tags;
+        set_req.op_user = op_user.clone();
+        set_req.note = Some(Arc::new("import".to_owned()));
+        match app.config_route.set_config(set_req).await {
+            Ok(_) => results.push(ConfigImportItemResult::new(
+                item.group,
+                item.data_id,
+                result,
+                None,
+            )),
+            Err(err) => results.push(ConfigImportItemResult::new(
+                item.group,
+                item.data_id,
+                ConfigImportResultType::Failed,
+                Some(err.to_string()),
+            )),
+        }
     }
-    zip.finish()?;
-    Ok(())
+    Ok(
+        HttpResponse::Ok().json(ApiResult::success(Some(ConfigImportReport::new(
+            policy, dry_run, results,
+        )))),
+    )
 }
 
 ///
@@ -197,17 +261,16 @@ pub async fn download_config(
     param.limit = 0xffff_ffff;
     param.query_context = true;
     let cmd = ConfigCmd::QueryPageInfo(Box::new(param));
-    match config_addr.send(cmd).await {
-        Ok(res) => {
-            let r: ConfigResult = res.unwrap();
+    let res = match config_addr.send(cmd).await {
+        Ok(res) => res,
+        Err(err) => Err(err.into()),
+    };
+    match res {
+        Ok(r) => {
             match r {
                 ConfigResult::ConfigInfoPage(_, list) => {
                     let mut tmpfile: File = tempfile::tempfile().unwrap();
-                    {
-                        let write = std::io::Write::by_ref(&mut tmpfile);
-                        let zip = ZipWriter::new(write);
-                        zip_file(zip, list).ok();
-                    }
+                    write_config_archive(&mut tmpfile, &list).ok();
                     // Seek to start
                     tmpfile.seek(SeekFrom::Start(0)).unwrap();
                     let mut buf = vec![];
@@ -226,8 +289,9 @@ pub async fn download_config(
     }
 }
 
-/// 按 key 导出配置
+/// 按 key 导出配置，请求体为数组，权限按每个key单独校验
 pub async fn download_config_by_keys(
+    req: HttpRequest,
     request: web::Json<Vec<ConfigParams>>,
     config_addr: web::Data<Addr<ConfigActor>>,
 ) -> impl Responder {
@@ -236,7 +300,7 @@ pub async fn download_config_by_keys(
         return HttpResponse::BadRequest().body("keys cannot be empty");
     }
 
-    let keys = params
+    let keys: Vec<ConfigKey> = params
         .into_iter()
         .map(|k| {
             let k = k.to_key();
@@ -246,32 +310,48 @@ pub async fn download_config_by_keys(
             }
         })
         .collect();
+    let namespace_privilege = user_namespace_privilege!(req);
+    let scope_filter = user_scope_filter!(req, PermissionResource::Config, PermissionAction::Read);
+    for key in &keys {
+        if !namespace_privilege.check_permission(&key.tenant) {
+            return HttpResponse::Forbidden().body(format!(
+                "user no such namespace permission: {}",
+                &key.tenant
+            ));
+        }
+        if !scope_filter
+            .as_ref()
+            .is_none_or(|v| v.is_match(&key.tenant, &key.group, &key.data_id))
+        {
+            return HttpResponse::Forbidden().body(format!(
+                "no permission to download config: {}",
+                key.build_key()
+            ));
+        }
+    }
 
     let cmd = ConfigCmd::QueryInfoByKeys(Box::new(keys));
-    match config_addr.send(cmd).await {
-        Ok(res) => {
-            let r: ConfigResult = res.unwrap();
-            match r {
-                ConfigResult::ConfigInfoPage(_, list) => {
-                    let mut tmpfile: File = tempfile::tempfile().unwrap();
-                    {
-                        let write = std::io::Write::by_ref(&mut tmpfile);
-                        let zip = ZipWriter::new(write);
-                        zip_file(zip, list).ok();
-                    }
-                    tmpfile.seek(SeekFrom::Start(0)).unwrap();
-                    let mut buf = vec![];
-                    tmpfile.read_to_end(&mut buf).unwrap();
+    let res = match config_addr.send(cmd).await {
+        Ok(res) => res,
+        Err(err) => Err(err.into()),
+    };
+    match res {
+        Ok(r) => match r {
+            ConfigResult::ConfigInfoPage(_, list) => {
+                let mut tmpfile: File = tempfile::tempfile().unwrap();
+                write_config_archive(&mut tmpfile, &list).ok();
+                tmpfile.seek(SeekFrom::Start(0)).unwrap();
+                let mut buf = vec![];
+                tmpfile.read_to_end(&mut buf).unwrap();
 
-                    let filename = format!("rnacos_config_export_{}.zip", now_millis());
-                    HttpResponse::Ok()
-                        .insert_header(header::ContentType::octet_stream())
-                        .insert_header(header::ContentDisposition::attachment(filename))
-                        .body(buf)
-                }
-                _ => HttpResponse::InternalServerError().body("config result error"),
+                let filename = format!("rnacos_config_export_{}.zip", now_millis());
+                HttpResponse::Ok()
+                    .insert_header(header::ContentType::octet_stream())
+                    .insert_header(header::ContentDisposition::attachment(filename))
+                    .body(buf)
             }
-        }
+            _ => HttpResponse::InternalServerError().body("config result error"),
+        },
         Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
     }
 }
@@ -84,6 +84,7 @@ pub async fn login(
                     extend_infos: user.extend_info.unwrap_or_default(),
                     namespace_privilege: user.namespace_privilege,
                     refresh_time: now_second_i32() as u32,
+                    groups: vec![],
                 }));
             }
         }
@@ -157,10 +158,11 @@ pub async fn oauth2_callback(
         Ok(Ok(OAuth2MsgResult::UserMeta(meta))) => Some(Arc::new(UserSession {
             username: Arc::new(meta.user_name.clone()),
             nickname: Some(meta.user_name),
-            roles: vec![meta.role],
+            roles: [vec![meta.role], meta.custom_roles].concat(),
             namespace_privilege: meta.namespace_privilege,
             extend_infos: HashMap::default(),
             refresh_time: now_second_i32() as u32,
+            groups: meta.groups,
         })),
         Ok(Ok(OAuth2MsgResult::None)) => None,
         Ok(Ok(OAuth2MsgResult::AuthorizeUrl(_))) => {
@@ -303,10 +305,11 @@ async fn ldap_login(
         Some(Arc::new(UserSession {
             username: param.username.clone(),
             nickname: Some(meta.user_name),
-            roles: vec![meta.role],
+            roles: [vec![meta.role], meta.custom_roles].concat(),
             namespace_privilege: meta.namespace_privilege,
             extend_infos: HashMap::default(),
             refresh_time: now_second_i32() as u32,
+            groups: meta.groups.into_iter().map(Arc::new).collect(),
         }))
     } else {
         None
@@ -7,7 +7,8 @@ use crate::cache::model::{CacheKey, CacheType, CacheValue};
 use crate::common::appdata::AppShareData;
 use crate::common::model::{ApiResultOld, UserSession};
 use crate::raft::cluster::model::{RouterRequest, RouterResponse};
-use crate::user::permission::UserRole;
+use crate::user::permission::PermissionRequest;
+use crate::user::role::get_user_permission;
 use actix_http::{HttpMessage, StatusCode};
 use actix_web::{
     body::EitherBody,
@@ -77,7 +78,7 @@ where
 
     dev::forward_ready!(service);
 
-    fn call(&self, request: ServiceRequest) -> Self::Future {
+    fn call(&self, mut request: ServiceRequest) -> Self::Future {
         let path = request.path();
         let is_check_path = !IGNORE_CHECK_LOGIN.contains(&path) && !STATIC_FILE_PATH.is_match(path);
         let is_page = !API_PATH.is_match(path);
@@ -99,17 +100,32 @@ where
         Box::pin(async move {
             let mut is_login = true;
             let mut user_has_permission = true;
-            let path = request.path();
-            let method = request.method().as_str();
+            let path = request.path().to_owned();
+            let method = request.method().as_str().to_owned();
             if is_check_path {
                 is_login = if token.is_empty() {
                     false
                 } else if let Ok(Some(session)) =
                     get_user_session(&app_share_data, token.clone()).await
                 {
-                    user_has_permission =
-                        UserRole::match_url_by_roles(&session.roles, path, method);
+                    let permission = get_user_permission(
+                        &app_share_data.role_manager,
+                        session.roles.clone(),
+                        session.groups.clone(),
+                    )
+                    .await
+                    .unwrap_or_default();
+                    user_has_permission = permission.match_url(&path, &method);
+                    if user_has_permission {
+                        if let Some(mut permission_req) =
+                            PermissionRequest::from_console_path(&path, &method)
+                        {
+                            permission_req.fill_from_request(&mut request).await;
+                            user_has_permission = permission.check(&permission_req);
+                        }
+                    }
                     request.extensions_mut().insert(session);
+                    request.extensions_mut().insert(permission);
                     true
                 } else {
                     false
@@ -14,9 +14,12 @@ pub mod v2;
 use std::sync::Arc;
 
 use self::model::NamespaceInfo;
+use crate::config::config_validate::ConfigValidateUtils;
+use crate::grpc::bistream_manage::{BiStreamManageCmd, BiStreamManageResult};
 use crate::namespace::model::{
-    NamespaceFromFlags, NamespaceQueryReq, NamespaceQueryResult, NamespaceRaftReq,
+    NamespaceFromFlags, NamespaceQueryReq, NamespaceQueryResult, NamespaceRaftReq, NamespaceUsage,
 };
+use crate::naming::core::{NamingCmd, NamingResult};
 use crate::{
     common::appdata::AppShareData,
     config::core::{ConfigActor, ConfigCmd, ConfigKey, ConfigResult},
@@ -36,6 +39,11 @@ lazy_static::lazy_static! {
             namespace_id: Some(Arc::new("".to_owned())),
             namespace_name: Some(DEFAULT_NAMESPACE.to_owned()),
             r#type: Some("0".to_owned()),
+            config_schema: None,
+            config_history_max_count: None,
+            config_history_max_days: None,
+            quota: None,
+            usage: None,
     });
 }
 
@@ -47,14 +55,8 @@ impl NamespaceUtilsOld {
             SYSCONFIG_NAMESPACE,
         ));
         match config_addr.send(cmd).await {
-            Ok(res) => {
-                let r: ConfigResult = res.unwrap();
-                match r {
-                    ConfigResult::Data { value: v, .. } => v,
-                    _ => Arc::new("".to_string()),
-                }
-            }
-            Err(_) => Arc::new("".to_string()),
+            Ok(Ok(ConfigResult::Data { value: v, .. })) => v,
+            _ => Arc::new("".to_string()),
         }
     }
     pub fn get_namespaces_from_source(namespace_str: Arc<String>) -> Vec<Arc<NamespaceInfo>> {
@@ -123,6 +125,11 @@ impl NamespaceUtilsOld {
                 namespace_id: Some(namespace_id),
                 namespace_name: Some(namespace_name),
                 r#type: Some("2".to_owned()),
+                config_schema: None,
+                config_history_max_count: None,
+                config_history_max_days: None,
+                quota: None,
+                usage: None,
             };
             infos.push(new_info);
             Self::save_namespace(app_data, &infos).await
@@ -196,15 +203,62 @@ impl NamespaceUtils {
             .send(NamespaceQueryReq::List)
             .await??;
         if let NamespaceQueryResult::List(list) = res {
-            Ok(list
-                .into_iter()
-                .map(|e| e.as_ref().to_owned().into())
-                .collect())
+            let mut namespaces = Vec::with_capacity(list.len());
+            for item in list {
+                let mut info: NamespaceInfo = item.as_ref().to_owned().into();
+                info.usage =
+                    Some(Self::get_namespace_usage(app_share_data, &item.namespace_id).await?);
+                namespaces.push(info);
+            }
+            Ok(namespaces)
         } else {
             Err(anyhow::anyhow!("NamespaceQueryResult is error"))
         }
     }
 
+    ///
+    /// 查询命名空间资源使用量，gRPC连接数只统计当前节点
+    pub async fn get_namespace_usage(
+        app_share_data: &Arc<AppShareData>,
+        namespace_id: &Arc<String>,
+    ) -> anyhow::Result<NamespaceUsage> {
+        let mut usage = NamespaceUsage::default();
+        if let ConfigResult::Count(count) = app_share_data
+            .config_addr
+            .send(ConfigCmd::QueryTenantConfigCount(namespace_id.clone()))
+            .await??
+        {
+            usage.config_count = count;
+        }
+        // 注册中心中默认命名空间使用public
+        let naming_namespace_id = if namespace_id.is_empty() {
+            Arc::new(DEFAULT_NAMESPACE.to_owned())
+        } else {
+            namespace_id.clone()
+        };
+        if let NamingResult::NamespaceUsage {
+            service_count,
+            max_instance_count,
+        } = app_share_data
+            .naming_addr
+            .send(NamingCmd::QueryNamespaceUsage(naming_namespace_id))
+            .await??
+        {
+            usage.service_count = service_count;
+            usage.max_service_instance_count = max_instance_count;
+        }
+        if let BiStreamManageResult::ConnCount(count) = app_share_data
+            .bi_stream_manage
+            .send(BiStreamManageCmd::QueryNamespaceConnCount(
+                namespace_id.clone(),
+            ))
+            .await??
+        {
+            usage.grpc_connection_count = count;
+        }
+        Ok(usage)
+    }
+
     pub async fn get_namespace(
         app_share_data: &Arc<AppShareData>,
         namespace_id: Option<Arc<String>>,
@@ -215,7 +269,9 @@ impl NamespaceUtils {
             .send(NamespaceQueryReq::Info(namespace_id.clone()))
             .await??;
         if let NamespaceQueryResult::Info(info) = res {
-            Ok(info.as_ref().to_owned().into())
+            let mut value: NamespaceInfo = info.as_ref().to_owned().into();
+            value.usage = Some(Self::get_namespace_usage(app_share_data, &namespace_id).await?);
+            Ok(value)
         } else {
             Err(anyhow::anyhow!("NamespaceQueryResult is error"))
         }
@@ -225,6 +281,7 @@ impl NamespaceUtils {
         app_data: &Arc<AppShareData>,
         info: NamespaceInfo,
     ) -> anyhow::Result<()> {
+        Self::check_config_schema(&info)?;
         app_data
             .raft_request_route
             .request_namespace(NamespaceRaftReq::Set(info.into()))
@@ -236,6 +293,7 @@ impl NamespaceUtils {
         app_data: &Arc<AppShareData>,
         info: NamespaceInfo,
     ) -> anyhow::Result<()> {
+        Self::check_config_schema(&info)?;
         app_data
             .raft_request_route
             .request_namespace(NamespaceRaftReq::Update(info.into()))
@@ -264,4 +322,13 @@ impl NamespaceUtils {
             .await?;
         Ok(())
     }
+
+    fn check_config_schema(info: &NamespaceInfo) -> anyhow::Result<()> {
+        if let Some(schema) = &info.config_schema {
+            if !schema.is_empty() {
+                ConfigValidateUtils::check_schema(schema)?;
+            }
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::audit::model::AuditLogQueryParam;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogQueryRequest {
+    pub page_no: Option<usize>,
+    pub page_size: Option<usize>,
+    /// 开始时间，单位毫秒
+    pub start_time: Option<i64>,
+    /// 结束时间，单位毫秒
+    pub end_time: Option<i64>,
+    pub actor: Option<Arc<String>>,
+    pub source_ip: Option<Arc<String>>,
+    pub namespace_id: Option<Arc<String>>,
+    pub resource_type: Option<Arc<String>>,
+    pub resource_key: Option<Arc<String>>,
+    pub action: Option<Arc<String>>,
+    pub source: Option<Arc<String>>,
+    pub success: Option<bool>,
+}
+
+impl AuditLogQueryRequest {
+    pub fn to_param(&self) -> AuditLogQueryParam {
+        let limit = self.page_size.unwrap_or(20);
+        let offset = (self.page_no.unwrap_or(1).max(1) - 1) * limit;
+        AuditLogQueryParam {
+            start_time: self.start_time,
+            end_time: self.end_time,
+            actor: self.actor.clone(),
+            source_ip: self.source_ip.clone(),
+            namespace: self.namespace_id.clone(),
+            resource_type: self.resource_type.clone(),
+            resource_key: self.resource_key.clone().filter(|v| !v.is_empty()),
+            action: self.action.clone(),
+            source: self.source.clone(),
+            success: self.success,
+            offset,
+            limit,
+        }
+    }
+}
@@ -1,17 +1,24 @@
 use crate::config::config_index::ConfigQueryParam;
-use crate::config::core::{ConfigInfoDto, ConfigKey};
+use crate::config::core::{ConfigHistoryInfoDto, ConfigInfoDto, ConfigKey};
 use crate::config::dal::ConfigHistoryParam;
+use crate::config::model::ConfigGrayRule;
 use crate::config::ConfigUtils;
-use crate::user_namespace_privilege;
+use crate::user::permission::{PermissionAction, PermissionResource};
+use crate::{user_namespace_privilege, user_scope_filter};
 use actix_http::HttpMessage;
 use actix_web::HttpRequest;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct OpsConfigImportInfo {
     pub tenant: Option<String>,
+    /// 已存在配置的处理策略：ABORT、SKIP、OVERWRITE，默认OVERWRITE
+    pub policy: Option<String>,
+    /// 只返回导入预览结果，不写入配置
+    pub dry_run: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -24,6 +31,8 @@ pub struct OpsConfigQueryListRequest {
     pub data_param: Option<String>,
     pub group: Option<String>,
     pub data_id: Option<String>,
+    /// 按标签过滤，多个用逗号分隔
+    pub config_tags: Option<String>,
 }
 
 impl OpsConfigQueryListRequest {
@@ -36,7 +45,13 @@ impl OpsConfigQueryListRequest {
             offset,
             like_group: self.group_param,
             like_data_id: self.data_param,
+            tags: ConfigUtils::build_tags(self.config_tags.as_deref().unwrap_or_default()),
             namespace_privilege,
+            scope_filter: user_scope_filter!(
+                req,
+                PermissionResource::Config,
+                PermissionAction::Read
+            ),
             ..Default::default()
         };
         if let Some(tenant) = self.tenant {
@@ -91,6 +106,9 @@ pub struct ConfigParams {
     pub content: Option<Arc<String>>,
     pub config_type: Option<Arc<String>>,
     pub desc: Option<Arc<String>>,
+    pub cas_md5: Option<Arc<String>>,
+    /// 配置标签，多个用逗号分隔；为空串时清除标签
+    pub config_tags: Option<String>,
 }
 
 impl ConfigParams {
@@ -104,6 +122,35 @@ impl ConfigParams {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigGrayParams {
+    pub data_id: Arc<String>,
+    pub group: Option<Arc<String>>,
+    pub tenant: Option<String>,
+    pub content: Option<Arc<String>>,
+    pub ips: Option<Vec<String>>,
+    pub labels: Option<HashMap<String, String>>,
+}
+
+impl ConfigGrayParams {
+    pub fn to_key(&self) -> ConfigKey {
+        let group = self
+            .group
+            .clone()
+            .unwrap_or(Arc::new("DEFAULT_GROUP".to_owned()));
+        let tenant = ConfigUtils::default_tenant(self.tenant.clone().unwrap_or_default());
+        ConfigKey::new_by_arc(self.data_id.clone(), group, Arc::new(tenant))
+    }
+
+    pub fn to_rule(&self) -> ConfigGrayRule {
+        ConfigGrayRule {
+            ips: self.ips.clone().unwrap_or_default(),
+            labels: self.labels.clone().unwrap_or_default(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct ConfigInfo {
@@ -111,4 +158,66 @@ pub struct ConfigInfo {
     pub md5: Option<Arc<String>>,
     pub config_type: Option<Arc<String>>,
     pub desc: Option<Arc<String>>,
+    pub config_tags: Option<String>,
+}
+
+fn build_config_key(
+    data_id: &Arc<String>,
+    group: &Option<Arc<String>>,
+    tenant: &Option<String>,
+) -> ConfigKey {
+    let group = group
+        .clone()
+        .unwrap_or(Arc::new("DEFAULT_GROUP".to_owned()));
+    let tenant = ConfigUtils::default_tenant(tenant.clone().unwrap_or_default());
+    ConfigKey::new_by_arc(data_id.clone(), group, Arc::new(tenant))
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigHistoryDiffParams {
+    pub data_id: Arc<String>,
+    pub group: Option<Arc<String>>,
+    pub tenant: Option<String>,
+    pub from_id: u64,
+    pub to_id: u64,
+}
+
+impl ConfigHistoryDiffParams {
+    pub fn to_key(&self) -> ConfigKey {
+        build_config_key(&self.data_id, &self.group, &self.tenant)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigHistoryDiffInfo {
+    pub from: ConfigHistoryInfoDto,
+    pub to: ConfigHistoryInfoDto,
+    /// unified diff格式的内容差异
+    pub diff: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigHistoryRollbackParams {
+    pub data_id: Arc<String>,
+    pub group: Option<Arc<String>>,
+    pub tenant: Option<String>,
+    pub history_id: u64,
+    pub note: Option<String>,
+}
+
+impl ConfigHistoryRollbackParams {
+    pub fn to_key(&self) -> ConfigKey {
+        build_config_key(&self.data_id, &self.group, &self.tenant)
+    }
+
+    /// 回滚操作记录到新历史版本的备注
+    pub fn build_note(&self) -> String {
+        match self.note.as_ref().filter(|v| !v.is_empty()) {
+            Some(note) => format!("rollback to history {}: {}", self.history_id, note),
+            None => format!("rollback to history {}", self.history_id),
+        }
+    }
 }
@@ -32,6 +32,7 @@ impl McpServerQueryRequest {
             limit,
             namespace_id: Some(namespace_id),
             name_filter: self.name_filter.as_ref().map(|s| Arc::new(s.clone())),
+            scope_filter: None,
         }
     }
 
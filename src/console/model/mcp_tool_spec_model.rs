@@ -30,6 +30,7 @@ impl ToolSpecQueryRequest {
             namespace_id: Some(namespace_id),
             group_filter: self.group_filter.clone(),
             tool_name_filter: self.tool_name_filter.clone(),
+            scope_filter: None,
         }
     }
 
@@ -1,3 +1,4 @@
+pub mod audit_model;
 pub mod cluster_model;
 pub mod config_model;
 pub mod login_model;
@@ -7,8 +8,11 @@ pub mod metrics_model;
 pub mod naming_model;
 pub mod raft_model;
 pub mod user_model;
+pub mod webhook_model;
 
-use crate::namespace::model::{Namespace, NamespaceFromFlags, NamespaceParam};
+use crate::namespace::model::{
+    Namespace, NamespaceFromFlags, NamespaceParam, NamespaceQuota, NamespaceUsage,
+};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -18,6 +22,21 @@ pub struct NamespaceInfo {
     pub namespace_id: Option<Arc<String>>,
     pub namespace_name: Option<String>,
     pub r#type: Option<String>,
+    /// 命名空间配置内容的JSON Schema，空串表示清除
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_schema: Option<String>,
+    /// 配置历史记录保留条数，0表示不限制
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_history_max_count: Option<u32>,
+    /// 配置历史记录保留天数，0表示不限制
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_history_max_days: Option<u32>,
+    /// 资源配额，各项为0表示不限制
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quota: Option<NamespaceQuota>,
+    /// 资源使用量，只在列表查询时返回
+    #[serde(skip_serializing_if = "Option::is_none", skip_deserializing)]
+    pub usage: Option<NamespaceUsage>,
 }
 
 impl From<Namespace> for NamespaceInfo {
@@ -26,6 +45,11 @@ impl From<Namespace> for NamespaceInfo {
             namespace_id: Some(value.namespace_id),
             namespace_name: Some(value.namespace_name),
             r#type: Some(NamespaceFromFlags::get_api_type(value.flag)),
+            config_schema: value.config_schema.map(|v| v.as_ref().to_owned()),
+            config_history_max_count: value.config_history_max_count,
+            config_history_max_days: value.config_history_max_days,
+            quota: value.quota,
+            usage: None,
         }
     }
 }
@@ -36,6 +60,10 @@ impl From<NamespaceInfo> for NamespaceParam {
             namespace_id: value.namespace_id.unwrap_or_default(),
             namespace_name: value.namespace_name,
             r#type: value.r#type,
+            config_schema: value.config_schema,
+            config_history_max_count: value.config_history_max_count,
+            config_history_max_days: value.config_history_max_days,
+            quota: value.quota,
         }
     }
 }
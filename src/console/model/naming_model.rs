@@ -10,8 +10,9 @@ use crate::naming::{
     model::{Instance, ServiceKey},
     NamingUtils,
 };
-use crate::user_namespace_privilege;
+use crate::user::permission::{PermissionAction, PermissionResource};
 use crate::utils::get_bool_from_string;
+use crate::{user_namespace_privilege, user_scope_filter};
 
 /*
 #[derive(Debug,Serialize,Deserialize,Default)]
@@ -44,6 +45,11 @@ impl ServiceQueryListRequest {
             limit,
             offset,
             namespace_privilege,
+            scope_filter: user_scope_filter!(
+                req,
+                PermissionResource::Service,
+                PermissionAction::Read
+            ),
             ..Default::default()
         };
         if let Some(namespace_id) = self.namespace_id {
@@ -141,6 +147,8 @@ pub struct ServiceParam {
     pub group_name: Option<String>,
     pub metadata: Option<String>,
     pub protect_threshold: Option<f32>,
+    pub selector: Option<String>,
+    pub health_check: Option<String>,
 }
 
 impl ServiceParam {
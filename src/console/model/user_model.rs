@@ -74,3 +74,15 @@ impl UserPageParams {
         (limit, offset)
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleNameParam {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessKeyIdParam {
+    pub access_key: String,
+}
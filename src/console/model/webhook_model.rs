@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+use crate::webhook::model::WebhookDeadLetterQueryParam;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookIdParam {
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDeadLetterQueryRequest {
+    pub page_no: Option<usize>,
+    pub page_size: Option<usize>,
+    pub webhook_id: Option<String>,
+    pub event_type: Option<String>,
+}
+
+impl WebhookDeadLetterQueryRequest {
+    pub fn to_param(self) -> WebhookDeadLetterQueryParam {
+        let limit = self.page_size.unwrap_or(20);
+        let offset = (self.page_no.unwrap_or(1).max(1) - 1) * limit;
+        WebhookDeadLetterQueryParam {
+            webhook_id: self.webhook_id,
+            event_type: self.event_type,
+            offset,
+            limit,
+        }
+    }
+}
@@ -15,7 +15,11 @@ use crate::{
         constant::EMPTY_STR,
         model::{ApiResult, PageResultOld, UserSession},
     },
-    user::{model::UserDto, permission::UserRole, UserManagerReq, UserManagerResult},
+    user::{
+        model::UserDto,
+        permission::{UserPermission, UserRole},
+        UserManagerReq, UserManagerResult,
+    },
 };
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -45,9 +49,12 @@ pub async fn get_user_info(req: HttpRequest) -> actix_web::Result<impl Responder
 /// 这里把取不到UserSession当成旧控制台，后继可以考虑单独实现一个接口
 pub async fn get_user_web_resources(req: HttpRequest) -> actix_web::Result<impl Responder> {
     if let Some(session) = req.extensions().get::<Arc<UserSession>>() {
-        let resources = UserRole::get_web_resources_by_roles(
-            session.roles.iter().map(|e| e.as_str()).collect(),
-        );
+        let resources = match req.extensions().get::<Arc<UserPermission>>() {
+            Some(permission) => permission.get_web_resources(),
+            None => UserRole::get_web_resources_by_roles(
+                session.roles.iter().map(|e| e.as_str()).collect(),
+            ),
+        };
         let data = UserPermissions {
             resources,
             from: EMPTY_STR,
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use actix_web::{web, HttpResponse, Responder};
+
+use crate::common::appdata::AppShareData;
+use crate::common::model::ApiResult;
+use crate::console::model::user_model::AccessKeyIdParam;
+use crate::console::v2::{handle_system_error, handle_unexpected_response_error};
+use crate::user::access_key::{AccessKeyManagerReq, AccessKeyManagerResult};
+use crate::user::model::AccessKeyParam;
+
+pub async fn query_access_key_list(appdata: web::Data<Arc<AppShareData>>) -> impl Responder {
+    match appdata
+        .access_key_manager
+        .send(AccessKeyManagerReq::QueryList)
+        .await
+    {
+        Ok(Ok(AccessKeyManagerResult::List(list))) => {
+            HttpResponse::Ok().json(ApiResult::success(Some(list)))
+        }
+        Ok(Ok(_)) => handle_unexpected_response_error("query access key list"),
+        Ok(Err(err)) => handle_system_error(err, "query access key list error"),
+        Err(err) => handle_system_error(err, "query access key list error"),
+    }
+}
+
+pub async fn create_access_key(
+    appdata: web::Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<AccessKeyParam>,
+) -> impl Responder {
+    match appdata
+        .access_key_manager
+        .send(AccessKeyManagerReq::Create(param))
+        .await
+    {
+        Ok(Ok(AccessKeyManagerResult::Created(v))) => {
+            HttpResponse::Ok().json(ApiResult::success(Some(v)))
+        }
+        Ok(Ok(_)) => handle_unexpected_response_error("create access key"),
+        Ok(Err(err)) => handle_system_error(err, "create access key error"),
+        Err(err) => handle_system_error(err, "create access key error"),
+    }
+}
+
+pub async fn revoke_access_key(
+    appdata: web::Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<AccessKeyIdParam>,
+) -> impl Responder {
+    match appdata
+        .access_key_manager
+        .send(AccessKeyManagerReq::Revoke(param.access_key))
+        .await
+    {
+        Ok(Ok(_)) => HttpResponse::Ok().json(ApiResult::success(Some(true))),
+        Ok(Err(err)) => handle_system_error(err, "revoke access key error"),
+        Err(err) => handle_system_error(err, "revoke access key error"),
+    }
+}
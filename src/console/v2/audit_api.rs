@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use actix_web::http::header;
+use actix_web::{web, HttpResponse, Responder};
+
+use crate::audit::core::{AuditLogReq, AuditLogResult};
+use crate::audit::model::AuditLogDto;
+use crate::common::appdata::AppShareData;
+use crate::common::datetime_utils::get_timestamp_str;
+use crate::common::model::{ApiResult, PageResult};
+use crate::console::model::audit_model::AuditLogQueryRequest;
+use crate::console::v2::{handle_system_error, handle_unexpected_response_error};
+
+/// 单次导出的最大条数
+const MAX_EXPORT_SIZE: usize = 100_000;
+
+const CSV_HEADER: &str = "time,source,actor,sourceIp,namespace,resourceType,resourceKey,action,beforeDigest,afterDigest,success,resultCode,message,path";
+
+pub async fn query_audit_log_list(
+    appdata: web::Data<Arc<AppShareData>>,
+    web::Query(request): web::Query<AuditLogQueryRequest>,
+) -> impl Responder {
+    let cmd = AuditLogReq::Query(request.to_param());
+    match appdata.audit_log_manager.send(cmd).await {
+        Ok(Ok(AuditLogResult::PageInfo(total_count, list))) => {
+            HttpResponse::Ok().json(ApiResult::success(Some(PageResult { total_count, list })))
+        }
+        Ok(Ok(_)) => handle_unexpected_response_error("query audit log"),
+        Ok(Err(err)) => handle_system_error(err, "query audit log error"),
+        Err(err) => handle_system_error(err, "query audit log error"),
+    }
+}
+
+pub async fn download_audit_log(
+    appdata: web::Data<Arc<AppShareData>>,
+    web::Query(request): web::Query<AuditLogQueryRequest>,
+) -> impl Responder {
+    let mut param = request.to_param();
+    param.offset = 0;
+    param.limit = MAX_EXPORT_SIZE;
+    match appdata
+        .audit_log_manager
+        .send(AuditLogReq::Query(param))
+        .await
+    {
+        Ok(Ok(AuditLogResult::PageInfo(_, list))) => {
+            let body = build_csv(&list, &appdata.timezone_offset);
+            let filename = format!("rnacos_audit_log_{}.csv", crate::now_millis());
+            HttpResponse::Ok()
+                .insert_header((header::CONTENT_TYPE, "text/csv; charset=utf-8"))
+                .insert_header(header::ContentDisposition::attachment(filename))
+                .body(body)
+        }
+        Ok(Ok(_)) => handle_unexpected_response_error("download audit log"),
+        Ok(Err(err)) => handle_system_error(err, "download audit log error"),
+        Err(err) => handle_system_error(err, "download audit log error"),
+    }
+}
+
+fn build_csv(list: &[AuditLogDto], offset: &chrono::FixedOffset) -> String {
+    let mut buf = String::with_capacity((list.len() + 1) * 256);
+    buf.push_str(CSV_HEADER);
+    buf.push('\n');
+    for item in list {
+        let record = &item.record;
+        let success = if record.success { "true" } else { "false" };
+        let fields = [
+            get_timestamp_str(record.timestamp, offset),
+            record.source.clone(),
+            record.actor.clone(),
+            record.source_ip.clone(),
+            record.namespace.clone(),
+            record.resource_type.clone(),
+            record.resource_key.clone(),
+            record.action.clone(),
+            record.before_digest.clone(),
+            record.after_digest.clone(),
+            success.to_owned(),
+            record.result_code.clone(),
+            record.message.clone(),
+            record.path.clone(),
+        ];
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                buf.push(',');
+            }
+            push_csv_field(&mut buf, field);
+        }
+        buf.push('\n');
+    }
+    buf
+}
+
+///
+/// 按RFC 4180转义；以公式字符开头的值加前缀，避免在表格软件中被当作公式执行
+fn push_csv_field(buf: &mut String, value: &str) {
+    let formula = value.starts_with(['=', '+', '-', '@', '\t', '\r']);
+    let quote = formula || value.contains([',', '"', '\n', '\r']);
+    if quote {
+        buf.push('"');
+    }
+    if formula {
+        buf.push('\'');
+    }
+    for c in value.chars() {
+        if c == '"' {
+            buf.push('"');
+        }
+        buf.push(c);
+    }
+    if quote {
+        buf.push('"');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_field_escape() {
+        let mut buf = String::new();
+        push_csv_field(&mut buf, "a,\"b\"");
+        buf.push(',');
+        push_csv_field(&mut buf, "=cmd()");
+        buf.push(',');
+        push_csv_field(&mut buf, "plain");
+        assert_eq!(buf, "\"a,\"\"b\"\"\",\"'=cmd()\",plain");
+    }
+}
@@ -1,11 +1,19 @@
 use crate::common::appdata::AppShareData;
 use crate::common::model::ApiResult;
 use crate::console::model::cluster_model::ClusterNodeInfo;
+use crate::console::v2::{handle_param_error, handle_raft_error, handle_system_error};
+use crate::raft::cluster::membership::{
+    leader_membership_request, node_membership_request, query_membership, RaftMembershipParamError,
+    RaftMembershipReq, RaftMembershipResult, RaftNodeParam,
+};
 use actix_web::{web, HttpResponse, Responder};
 use std::sync::Arc;
 
 pub async fn query_cluster_info(app: web::Data<Arc<AppShareData>>) -> impl Responder {
-    let nodes = app.naming_node_manage.get_all_valid_nodes().await.unwrap();
+    let nodes = match app.naming_node_manage.get_all_valid_nodes().await {
+        Ok(v) => v,
+        Err(err) => return handle_system_error(err, "query cluster nodes"),
+    };
     let leader_node = app.raft.current_leader().await;
     let mut list = vec![];
     for node in nodes {
@@ -22,3 +30,56 @@ pub async fn query_cluster_info(app: web::Data<Arc<AppShareData>>) -> impl Respo
     }
     HttpResponse::Ok().json(ApiResult::success(Some(list)))
 }
+
+fn membership_response(res: anyhow::Result<RaftMembershipResult>, operation: &str) -> HttpResponse {
+    match res {
+        Ok(RaftMembershipResult::Snapshot(v)) => {
+            HttpResponse::Ok().json(ApiResult::success(Some(v)))
+        }
+        Ok(_) => HttpResponse::Ok().json(ApiResult::success(Some(true))),
+        Err(err) if err.is::<RaftMembershipParamError>() => handle_param_error(err, operation),
+        Err(err) => handle_raft_error(err, operation),
+    }
+}
+
+pub async fn query_raft_members(app: web::Data<Arc<AppShareData>>) -> impl Responder {
+    match query_membership(app.get_ref()).await {
+        Ok(v) => HttpResponse::Ok().json(ApiResult::success(Some(v))),
+        Err(err) => handle_raft_error(err, "query members"),
+    }
+}
+
+pub async fn promote_raft_node(
+    app: web::Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<RaftNodeParam>,
+) -> impl Responder {
+    let node_id = match param.node_id {
+        Some(v) => v,
+        None => return handle_param_error("nodeId is required", "promote node"),
+    };
+    let res =
+        leader_membership_request(app.get_ref(), RaftMembershipReq::PromoteNode { node_id }).await;
+    membership_response(res, "promote node")
+}
+
+pub async fn remove_raft_node(
+    app: web::Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<RaftNodeParam>,
+) -> impl Responder {
+    let node_id = match param.node_id {
+        Some(v) => v,
+        None => return handle_param_error("nodeId is required", "remove node"),
+    };
+    let res =
+        leader_membership_request(app.get_ref(), RaftMembershipReq::RemoveNode { node_id }).await;
+    membership_response(res, "remove node")
+}
+
+pub async fn build_raft_snapshot(
+    app: web::Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<RaftNodeParam>,
+) -> impl Responder {
+    let res =
+        node_membership_request(app.get_ref(), param.node_id, RaftMembershipReq::Snapshot).await;
+    membership_response(res, "build snapshot")
+}
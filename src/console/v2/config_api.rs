@@ -1,11 +1,24 @@
 use crate::common::appdata::AppShareData;
 use crate::common::model::{ApiResult, PageResult, UserSession};
-use crate::config::core::{ConfigActor, ConfigCmd, ConfigResult};
+use crate::config::config_history::build_unified_diff;
+use crate::config::config_validate::ConfigValidateError;
+use crate::config::core::{ConfigActor, ConfigCmd, ConfigHistoryInfoDto, ConfigKey, ConfigResult};
+use crate::config::listener_query::query_cluster_listener_info;
+use crate::config::model::{ConfigCasConflictError, ConfigGrayReq, ConfigListenerQueryParam};
+use crate::config::ConfigUtils;
 pub use crate::console::config_api::{download_config, import_config};
-use crate::console::model::config_model::{ConfigInfo, ConfigParams, OpsConfigQueryListRequest};
-use crate::console::v2::ERROR_CODE_SYSTEM_ERROR;
+use crate::console::model::config_model::{
+    ConfigGrayParams, ConfigHistoryDiffInfo, ConfigHistoryDiffParams, ConfigHistoryRollbackParams,
+    ConfigInfo, ConfigParams, OpsConfigQueryListRequest,
+};
+use crate::console::v2::{
+    ERROR_CODE_CONFIG_CAS_CONFLICT, ERROR_CODE_NAMESPACE_QUOTA_EXCEEDED, ERROR_CODE_NOT_FOUND,
+    ERROR_CODE_PARAM_ERROR, ERROR_CODE_SYSTEM_ERROR,
+};
+use crate::namespace::model::NamespaceQuotaError;
 use crate::raft::cluster::model::{DelConfigReq, SetConfigReq};
-use crate::{user_namespace_privilege, user_no_namespace_permission};
+use crate::user::permission::{PermissionAction, PermissionResource};
+use crate::{user_namespace_privilege, user_no_namespace_permission, user_scope_filter};
 use actix::Addr;
 use actix_web::web::Data;
 use actix_web::HttpMessage;
@@ -25,18 +38,20 @@ pub async fn query_config_list(
         user_no_namespace_permission!(&param.tenant);
     }
     let cmd = ConfigCmd::QueryPageInfo(Box::new(param));
-    match config_addr.send(cmd).await {
-        Ok(res) => {
-            let r: ConfigResult = res.unwrap();
-            match r {
-                ConfigResult::ConfigInfoPage(total_count, list) => HttpResponse::Ok()
-                    .json(ApiResult::success(Some(PageResult { total_count, list }))),
-                _ => HttpResponse::Ok().json(ApiResult::<()>::error(
-                    ERROR_CODE_SYSTEM_ERROR.to_string(),
-                    None,
-                )),
+    let res = match config_addr.send(cmd).await {
+        Ok(res) => res,
+        Err(err) => Err(err.into()),
+    };
+    match res {
+        Ok(r) => match r {
+            ConfigResult::ConfigInfoPage(total_count, list) => {
+                HttpResponse::Ok().json(ApiResult::success(Some(PageResult { total_count, list })))
             }
-        }
+            _ => HttpResponse::Ok().json(ApiResult::<()>::error(
+                ERROR_CODE_SYSTEM_ERROR.to_string(),
+                None,
+            )),
+        },
         Err(err) => HttpResponse::Ok().json(ApiResult::<()>::error(
             ERROR_CODE_SYSTEM_ERROR.to_string(),
             Some(err.to_string()),
@@ -65,17 +80,125 @@ pub async fn query_history_config_page(
         user_no_namespace_permission!(&param.tenant);
     }
     let cmd = ConfigCmd::QueryHistoryPageInfo(Box::new(param));
-    match config_addr.send(cmd).await {
-        Ok(res) => {
-            let r: ConfigResult = res.unwrap();
-            match r {
-                ConfigResult::ConfigHistoryInfoPage(total_count, list) => HttpResponse::Ok()
-                    .json(ApiResult::success(Some(PageResult { total_count, list }))),
-                _ => HttpResponse::Ok().json(ApiResult::<()>::error(
+    let res = match config_addr.send(cmd).await {
+        Ok(res) => res,
+        Err(err) => Err(err.into()),
+    };
+    match res {
+        Ok(r) => match r {
+            ConfigResult::ConfigHistoryInfoPage(total_count, list) => {
+                HttpResponse::Ok().json(ApiResult::success(Some(PageResult { total_count, list })))
+            }
+            _ => HttpResponse::Ok().json(ApiResult::<()>::error(
+                ERROR_CODE_SYSTEM_ERROR.to_string(),
+                None,
+            )),
+        },
+        Err(err) => HttpResponse::Ok().json(ApiResult::<()>::error(
+            ERROR_CODE_SYSTEM_ERROR.to_string(),
+            Some(err.to_string()),
+        )),
+    }
+}
+
+async fn get_history_info(
+    config_addr: &Addr<ConfigActor>,
+    key: ConfigKey,
+    id: u64,
+) -> anyhow::Result<Option<ConfigHistoryInfoDto>> {
+    match config_addr
+        .send(ConfigCmd::GetHistoryInfo(key, id))
+        .await??
+    {
+        ConfigResult::ConfigHistoryInfo(info) => Ok(info),
+        _ => Err(anyhow::anyhow!("config history result type is error")),
+    }
+}
+
+///
+/// 比较配置的两个历史版本
+pub async fn diff_history_config(
+    req: HttpRequest,
+    web::Query(param): web::Query<ConfigHistoryDiffParams>,
+    appdata: Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let config_key = param.to_key();
+    let namespace_privilege = user_namespace_privilege!(req);
+    if !namespace_privilege.check_permission(&config_key.tenant) {
+        user_no_namespace_permission!(&config_key.tenant);
+    }
+    let from = get_history_info(&appdata.config_addr, config_key.clone(), param.from_id).await;
+    let to = get_history_info(&appdata.config_addr, config_key, param.to_id).await;
+    match (from, to) {
+        (Ok(Some(from)), Ok(Some(to))) => {
+            let diff = build_unified_diff(
+                from.content.as_deref().unwrap_or_default(),
+                to.content.as_deref().unwrap_or_default(),
+                &param.from_id.to_string(),
+                &param.to_id.to_string(),
+            );
+            HttpResponse::Ok().json(ApiResult::success(Some(ConfigHistoryDiffInfo {
+                from,
+                to,
+                diff,
+            })))
+        }
+        (Err(err), _) | (_, Err(err)) => HttpResponse::Ok().json(ApiResult::<()>::error(
+            ERROR_CODE_SYSTEM_ERROR.to_string(),
+            Some(err.to_string()),
+        )),
+        _ => HttpResponse::Ok().json(ApiResult::<()>::error(
+            ERROR_CODE_NOT_FOUND.to_string(),
+            Some("config history not exist".to_string()),
+        )),
+    }
+}
+
+///
+/// 使用指定历史版本内容重新发布配置
+pub async fn rollback_history_config(
+    req: HttpRequest,
+    appdata: Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<ConfigHistoryRollbackParams>,
+) -> impl Responder {
+    let op_user = req
+        .extensions()
+        .get::<Arc<UserSession>>()
+        .map(|session| session.username.clone());
+    let config_key = param.to_key();
+    let namespace_privilege = user_namespace_privilege!(req);
+    if !namespace_privilege.check_permission(&config_key.tenant) {
+        user_no_namespace_permission!(&config_key.tenant);
+    }
+    let content =
+        match get_history_info(&appdata.config_addr, config_key.clone(), param.history_id).await {
+            Ok(Some(info)) => info.content.unwrap_or_default(),
+            Ok(None) => {
+                return HttpResponse::Ok().json(ApiResult::<()>::error(
+                    ERROR_CODE_NOT_FOUND.to_string(),
+                    Some("config history not exist".to_string()),
+                ));
+            }
+            Err(err) => {
+                return HttpResponse::Ok().json(ApiResult::<()>::error(
                     ERROR_CODE_SYSTEM_ERROR.to_string(),
-                    None,
-                )),
+                    Some(err.to_string()),
+                ));
             }
+        };
+    let mut set_req = SetConfigReq::new(config_key, Arc::new(content));
+    set_req.op_user = op_user;
+    set_req.note = Some(Arc::new(param.build_note()));
+    match appdata.config_route.set_config(set_req).await {
+        Ok(_) => HttpResponse::Ok().json(ApiResult::success(Some(true))),
+        Err(err) if err.is::<ConfigValidateError>() => HttpResponse::Ok().json(
+            ApiResult::<()>::error(ERROR_CODE_PARAM_ERROR.to_string(), Some(err.to_string())),
+        ),
+        Err(err) if err.is::<NamespaceQuotaError>() => {
+            HttpResponse::Ok().json(ApiResult::<()>::error(
+                ERROR_CODE_NAMESPACE_QUOTA_EXCEEDED.to_string(),
+                Some(err.to_string()),
+            ))
         }
         Err(err) => HttpResponse::Ok().json(ApiResult::<()>::error(
             ERROR_CODE_SYSTEM_ERROR.to_string(),
@@ -100,6 +223,7 @@ pub(crate) async fn get_config(
         md5,
         config_type,
         desc,
+        tags,
         ..
     })) = appdata.config_addr.send(cmd).await
     {
@@ -108,6 +232,7 @@ pub(crate) async fn get_config(
             md5: Some(md5),
             config_type,
             desc,
+            config_tags: Some(ConfigUtils::tags_to_string(&tags)),
         })))
     } else {
         HttpResponse::Ok().json(ApiResult::<()>::error(
@@ -143,13 +268,48 @@ pub async fn add_config(
     req.config_type = param.config_type;
     req.desc = param.desc;
     req.op_user = op_user;
-    if appdata.config_route.set_config(req).await.is_ok() {
-        HttpResponse::Ok().json(ApiResult::success(Some(true)))
-    } else {
-        HttpResponse::Ok().json(ApiResult::<()>::error(
+    req.cas_md5 = param.cas_md5.filter(|v| !v.is_empty());
+    req.tags = param.config_tags.map(|v| ConfigUtils::build_tags(&v));
+    match appdata.config_route.set_config(req).await {
+        Ok(_) => HttpResponse::Ok().json(ApiResult::success(Some(true))),
+        Err(err) if err.is::<ConfigCasConflictError>() => {
+            HttpResponse::Ok().json(ApiResult::<()>::error(
+                ERROR_CODE_CONFIG_CAS_CONFLICT.to_string(),
+                Some(err.to_string()),
+            ))
+        }
+        Err(err) if err.is::<ConfigValidateError>() => HttpResponse::Ok().json(
+            ApiResult::<()>::error(ERROR_CODE_PARAM_ERROR.to_string(), Some(err.to_string())),
+        ),
+        Err(err) if err.is::<NamespaceQuotaError>() => {
+            HttpResponse::Ok().json(ApiResult::<()>::error(
+                ERROR_CODE_NAMESPACE_QUOTA_EXCEEDED.to_string(),
+                Some(err.to_string()),
+            ))
+        }
+        Err(_) => HttpResponse::Ok().json(ApiResult::<()>::error(
             ERROR_CODE_SYSTEM_ERROR.to_string(),
             None,
-        ))
+        )),
+    }
+}
+
+///
+/// 配置加密密钥轮换，使用当前密钥重新加密所有匹配规则的配置
+pub async fn rotate_encrypt_key(
+    req: HttpRequest,
+    appdata: Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let op_user = req
+        .extensions()
+        .get::<Arc<UserSession>>()
+        .map(|session| session.username.clone());
+    match appdata.config_route.rotate_encrypt_key(op_user).await {
+        Ok(count) => HttpResponse::Ok().json(ApiResult::success(Some(count))),
+        Err(err) => HttpResponse::Ok().json(ApiResult::<()>::error(
+            ERROR_CODE_SYSTEM_ERROR.to_string(),
+            Some(err.to_string()),
+        )),
     }
 }
 
@@ -173,3 +333,142 @@ pub async fn remove_config(
         ))
     }
 }
+
+pub(crate) async fn get_gray_config(
+    req: HttpRequest,
+    web::Query(param): web::Query<ConfigParams>,
+    appdata: Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let config_key = param.to_key();
+    let namespace_privilege = user_namespace_privilege!(req);
+    if !namespace_privilege.check_permission(&config_key.tenant) {
+        user_no_namespace_permission!(&config_key.tenant);
+    }
+    let cmd = ConfigCmd::GetGrayInfo(config_key);
+    match appdata.config_addr.send(cmd).await {
+        Ok(Ok(ConfigResult::GrayInfo(Some(info)))) => {
+            HttpResponse::Ok().json(ApiResult::success(Some(info)))
+        }
+        Ok(Ok(_)) => HttpResponse::Ok().json(ApiResult::<()>::error(
+            ERROR_CODE_NOT_FOUND.to_string(),
+            Some("gray config not exist".to_string()),
+        )),
+        _ => HttpResponse::Ok().json(ApiResult::<()>::error(
+            ERROR_CODE_SYSTEM_ERROR.to_string(),
+            None,
+        )),
+    }
+}
+
+pub async fn publish_gray_config(
+    req: HttpRequest,
+    appdata: Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<ConfigGrayParams>,
+) -> impl Responder {
+    let op_user = req
+        .extensions()
+        .get::<Arc<UserSession>>()
+        .map(|session| session.username.clone());
+    let config_key = param.to_key();
+    let namespace_privilege = user_namespace_privilege!(req);
+    if !namespace_privilege.check_permission(&config_key.tenant) {
+        user_no_namespace_permission!(&config_key.tenant);
+    }
+    let gray_req = ConfigGrayReq::Publish {
+        key: config_key.build_key(),
+        value: param.content.clone().unwrap_or_default(),
+        rule: param.to_rule(),
+        op_user,
+    };
+    do_gray_config(gray_req, appdata).await
+}
+
+pub async fn promote_gray_config(
+    req: HttpRequest,
+    appdata: Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<ConfigGrayParams>,
+) -> impl Responder {
+    let op_user = req
+        .extensions()
+        .get::<Arc<UserSession>>()
+        .map(|session| session.username.clone());
+    let config_key = param.to_key();
+    let namespace_privilege = user_namespace_privilege!(req);
+    if !namespace_privilege.check_permission(&config_key.tenant) {
+        user_no_namespace_permission!(&config_key.tenant);
+    }
+    let gray_req = ConfigGrayReq::Promote {
+        key: config_key.build_key(),
+        op_user,
+    };
+    do_gray_config(gray_req, appdata).await
+}
+
+pub async fn stop_gray_config(
+    req: HttpRequest,
+    appdata: Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<ConfigGrayParams>,
+) -> impl Responder {
+    let config_key = param.to_key();
+    let namespace_privilege = user_namespace_privilege!(req);
+    if !namespace_privilege.check_permission(&config_key.tenant) {
+        user_no_namespace_permission!(&config_key.tenant);
+    }
+    let gray_req = ConfigGrayReq::Stop {
+        key: config_key.build_key(),
+    };
+    do_gray_config(gray_req, appdata).await
+}
+
+async fn do_gray_config(gray_req: ConfigGrayReq, appdata: Data<Arc<AppShareData>>) -> HttpResponse {
+    match appdata.config_route.gray_config(gray_req).await {
+        Ok(_) => HttpResponse::Ok().json(ApiResult::success(Some(true))),
+        Err(err) if err.is::<ConfigValidateError>() => HttpResponse::Ok().json(
+            ApiResult::<()>::error(ERROR_CODE_PARAM_ERROR.to_string(), Some(err.to_string())),
+        ),
+        Err(err) => HttpResponse::Ok().json(ApiResult::<()>::error(
+            ERROR_CODE_SYSTEM_ERROR.to_string(),
+            Some(err.to_string()),
+        )),
+    }
+}
+
+///
+/// 查询配置的监听客户端(按配置)或客户端监听的配置(按ip)，汇总集群所有节点
+pub async fn query_config_listeners(
+    req: HttpRequest,
+    web::Query(param): web::Query<ConfigListenerQueryParam>,
+    appdata: Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let key = param.to_key();
+    if key.is_none() && param.get_ip().is_none() {
+        return HttpResponse::Ok().json(ApiResult::<()>::error(
+            ERROR_CODE_PARAM_ERROR.to_string(),
+            Some("dataId or ip is required".to_string()),
+        ));
+    }
+    let namespace_privilege = user_namespace_privilege!(req);
+    if let Some(key) = &key {
+        if !namespace_privilege.check_permission(&key.tenant) {
+            user_no_namespace_permission!(&key.tenant);
+        }
+    }
+    let scope_filter = user_scope_filter!(req, PermissionResource::Config, PermissionAction::Read);
+    match query_cluster_listener_info(appdata.get_ref(), param).await {
+        Ok(mut result) => {
+            // 按ip查询时结果可能包含多个命名空间，过滤掉没有权限的配置
+            result.list.retain(|e| {
+                namespace_privilege.check_permission(&e.tenant)
+                    && scope_filter
+                        .as_ref()
+                        .is_none_or(|v| v.is_match(&e.tenant, &e.group, &e.data_id))
+            });
+            result.stale_count = result.list.iter().filter(|e| e.stale).count();
+            HttpResponse::Ok().json(ApiResult::success(Some(result)))
+        }
+        Err(err) => HttpResponse::Ok().json(ApiResult::<()>::error(
+            ERROR_CODE_SYSTEM_ERROR.to_string(),
+            Some(err.to_string()),
+        )),
+    }
+}
@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse, Responder};
+
+use crate::common::appdata::AppShareData;
+use crate::common::model::ApiResult;
+use crate::console::v2::{handle_error, handle_param_error};
+use crate::grpc::conn_manage::{
+    eject_conn, query_cluster_conn_counts, query_cluster_conn_list, rebalance_conns,
+    GrpcConnEjectParam, GrpcConnQueryParam, GrpcConnRebalanceParam,
+};
+use crate::user_namespace_privilege;
+
+pub async fn query_conn_list(
+    req: HttpRequest,
+    web::Query(param): web::Query<GrpcConnQueryParam>,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let namespace_privilege = user_namespace_privilege!(req);
+    let page_no = param.page_no.unwrap_or(1).max(1);
+    let page_size = param.page_size.unwrap_or(20).clamp(1, 1000);
+    match query_cluster_conn_list(appdata.get_ref(), param).await {
+        Ok(mut result) => {
+            result
+                .list
+                .retain(|e| namespace_privilege.check_permission(&e.namespace_id));
+            result.total_count = result.list.len();
+            result.list = result
+                .list
+                .into_iter()
+                .skip((page_no - 1) * page_size)
+                .take(page_size)
+                .collect();
+            HttpResponse::Ok().json(ApiResult::success(Some(result)))
+        }
+        Err(err) => handle_error(err),
+    }
+}
+
+pub async fn query_node_conn_counts(appdata: web::Data<Arc<AppShareData>>) -> impl Responder {
+    match query_cluster_conn_counts(
+        &appdata.naming_node_manage,
+        &appdata.cluster_sender,
+        &appdata.bi_stream_manage,
+        appdata.sys_config.http_port,
+    )
+    .await
+    {
+        Ok((list, _)) => HttpResponse::Ok().json(ApiResult::success(Some(list))),
+        Err(err) => handle_error(err),
+    }
+}
+
+pub async fn eject_connection(
+    appdata: web::Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<GrpcConnEjectParam>,
+) -> impl Responder {
+    let client_id = match param.client_id.filter(|e| !e.is_empty()) {
+        Some(v) => Arc::new(v),
+        None => return handle_param_error("clientId is required", "eject grpc connection"),
+    };
+    match eject_conn(appdata.get_ref(), client_id).await {
+        Ok(_) => HttpResponse::Ok().json(ApiResult::success(Some(true))),
+        Err(err) => handle_error(err),
+    }
+}
+
+pub async fn rebalance_connection(
+    appdata: web::Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<GrpcConnRebalanceParam>,
+) -> impl Responder {
+    match rebalance_conns(appdata.get_ref(), param).await {
+        Ok(result) => HttpResponse::Ok().json(ApiResult::success(Some(result))),
+        Err(err) => handle_error(err),
+    }
+}
@@ -26,7 +26,7 @@ use zip::ZipWriter;
 
 /// 查询McpServer列表
 pub async fn query_mcp_server_list(
-    _req: HttpRequest,
+    req: HttpRequest,
     request: web::Query<McpServerQueryRequest>,
     appdata: web::Data<Arc<AppShareData>>,
 ) -> impl Responder {
@@ -35,7 +35,12 @@ pub async fn query_mcp_server_list(
         return handle_param_error(err, "McpServer query parameter validation failed");
     }
     // 转换查询参数
-    let query_param = request.to_mcp_query_param();
+    let mut query_param = request.to_mcp_query_param();
+    query_param.scope_filter = crate::user_scope_filter!(
+        req,
+        crate::user::permission::PermissionResource::Mcp,
+        crate::user::permission::PermissionAction::Read
+    );
     // 发送查询请求到MCP Manager
     let cmd = McpManagerReq::QueryServer(query_param);
     match appdata.mcp_manager.send(cmd).await {
@@ -470,7 +475,7 @@ async fn do_publish_history_mcp_server(
 
 /// 批量导出McpServer
 pub async fn download_mcp_servers(
-    _req: HttpRequest,
+    req: HttpRequest,
     request: web::Query<McpServerQueryRequest>,
     appdata: web::Data<Arc<AppShareData>>,
 ) -> impl Responder {
@@ -483,6 +488,11 @@ pub async fn download_mcp_servers(
     let mut query_param = request.to_mcp_query_param();
     query_param.limit = 100_000;
     query_param.offset = 0;
+    query_param.scope_filter = crate::user_scope_filter!(
+        req,
+        crate::user::permission::PermissionResource::Mcp,
+        crate::user::permission::PermissionAction::Read
+    );
 
     // 发送查询请求到MCP Manager
     let cmd = McpManagerReq::QueryServer(query_param);
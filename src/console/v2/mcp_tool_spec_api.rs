@@ -25,7 +25,7 @@ use zip::ZipWriter;
 
 /// 查询ToolSpec列表
 pub async fn query_tool_spec_list(
-    _req: HttpRequest,
+    req: HttpRequest,
     request: web::Query<ToolSpecQueryRequest>,
     appdata: web::Data<Arc<AppShareData>>,
 ) -> impl Responder {
@@ -35,7 +35,12 @@ pub async fn query_tool_spec_list(
     }
 
     // 转换查询参数
-    let query_param = request.to_query_param();
+    let mut query_param = request.to_query_param();
+    query_param.scope_filter = crate::user_scope_filter!(
+        req,
+        crate::user::permission::PermissionResource::Mcp,
+        crate::user::permission::PermissionAction::Read
+    );
     // 发送查询请求到MCP Manager
     let cmd = McpManagerReq::QueryToolSpec(query_param);
     match appdata.mcp_manager.send(cmd).await {
@@ -260,7 +265,7 @@ pub async fn remove_tool_spec(
 
 /// 批量导出ToolSpec
 pub async fn download_tool_specs(
-    _req: HttpRequest,
+    req: HttpRequest,
     request: web::Query<ToolSpecQueryRequest>,
     appdata: web::Data<Arc<AppShareData>>,
 ) -> impl Responder {
@@ -273,6 +278,11 @@ pub async fn download_tool_specs(
     let mut query_param = request.to_query_param();
     query_param.limit = 100_000;
     query_param.offset = 0;
+    query_param.scope_filter = crate::user_scope_filter!(
+        req,
+        crate::user::permission::PermissionResource::Mcp,
+        crate::user::permission::PermissionAction::Read
+    );
 
     // 发送查询请求到MCP Manager
     let cmd = McpManagerReq::QueryToolSpec(query_param);
@@ -1,21 +1,28 @@
 use crate::common::model::ApiResult;
 use actix_web::HttpResponse;
 
+pub mod access_key_api;
+pub mod audit_api;
 pub mod cluster_api;
 pub mod config_api;
+pub mod connection_api;
 pub mod login_api;
 pub mod mcp_server_api;
 pub mod mcp_tool_spec_api;
 pub mod metrics_api;
 pub mod namespace_api;
 pub mod naming_api;
+pub mod role_api;
 pub mod user_api;
+pub mod webhook_api;
 
 pub const ERROR_CODE_SYSTEM_ERROR: &str = "SYSTEM_ERROR";
 pub const ERROR_CODE_PARAM_ERROR: &str = "PARAM_ERROR";
 pub const ERROR_CODE_NOT_FOUND: &str = "NOT_FOUND";
 pub const ERROR_CODE_MCP_MANAGER_ERROR: &str = "MCP_MANAGER_ERROR";
 pub const ERROR_CODE_RAFT_ERROR: &str = "RAFT_ERROR";
+pub const ERROR_CODE_CONFIG_CAS_CONFLICT: &str = "CONFIG_CAS_CONFLICT";
+pub const ERROR_CODE_NAMESPACE_QUOTA_EXCEEDED: &str = "NAMESPACE_QUOTA_EXCEEDED";
 
 pub enum ApiResponse<T>
 where
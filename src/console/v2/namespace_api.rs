@@ -3,7 +3,8 @@ use crate::common::model::ApiResult;
 use crate::common::string_utils::StringUtils;
 use crate::console::model::NamespaceInfo;
 use crate::console::NamespaceUtils;
-use crate::{user_namespace_privilege, user_no_namespace_permission};
+use crate::user::permission::{PermissionAction, PermissionResource};
+use crate::{user_namespace_privilege, user_no_namespace_permission, user_scope_filter};
 use actix_http::HttpMessage;
 use actix_web::{web, HttpRequest, HttpResponse, Responder};
 use std::sync::Arc;
@@ -17,12 +18,26 @@ pub async fn query_namespace_list(
     let namespaces = NamespaceUtils::get_namespaces(&app_data)
         .await
         .unwrap_or_default();
-    let namespaces = if namespace_privilege.is_all() {
+    let scope_filter =
+        user_scope_filter!(req, PermissionResource::Namespace, PermissionAction::Read);
+    let namespaces = if namespace_privilege.is_all() && scope_filter.is_none() {
         namespaces
     } else {
         namespaces
             .into_iter()
             .filter(|e| namespace_privilege.check_option_value_permission(&e.namespace_id, false))
+            .filter(|e| {
+                scope_filter.as_ref().is_none_or(|v| {
+                    v.is_match(
+                        e.namespace_id
+                            .as_ref()
+                            .map(|v| v.as_str())
+                            .unwrap_or_default(),
+                        "",
+                        "",
+                    )
+                })
+            })
             .collect()
     };
     HttpResponse::Ok().json(ApiResult::success(Some(namespaces)))
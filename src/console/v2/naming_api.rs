@@ -3,13 +3,18 @@ use crate::common::model::{ApiResult, PageResult};
 use crate::console::model::naming_model::{
     InstanceParams, ServiceDto, ServiceParam, ServiceQueryListRequest,
 };
-use crate::console::v2::ERROR_CODE_SYSTEM_ERROR;
+use crate::console::v2::{
+    ERROR_CODE_NAMESPACE_QUOTA_EXCEEDED, ERROR_CODE_PARAM_ERROR, ERROR_CODE_SYSTEM_ERROR,
+};
 use crate::grpc::handler::NAMING_ROUTE_REQUEST;
 use crate::grpc::PayloadUtils;
+use crate::namespace::model::NamespaceQuotaError;
 use crate::naming::api_model::InstanceVO;
 use crate::naming::cluster::model::{NamingRouteRequest, NamingRouterResponse};
 use crate::naming::core::{NamingActor, NamingCmd, NamingResult};
+use crate::naming::health_check::ServiceHealthCheck;
 use crate::naming::model::{InstanceUpdateTag, ServiceDetailDto};
+use crate::naming::selector::ServiceSelector;
 use crate::naming::service::SubscriberInfoDto;
 use crate::naming::service_index::ServiceQueryParam;
 use crate::naming::NamingUtils;
@@ -77,12 +82,30 @@ pub async fn add_service(
     } else {
         None
     };
+    if let Some(selector) = param.selector.as_ref() {
+        if let Err(err) = ServiceSelector::parse(selector) {
+            return HttpResponse::Ok().json(ApiResult::<()>::error(
+                ERROR_CODE_PARAM_ERROR.to_string(),
+                Some(err.to_string()),
+            ));
+        }
+    }
+    if let Some(health_check) = param.health_check.as_ref() {
+        if let Err(err) = ServiceHealthCheck::parse(health_check) {
+            return HttpResponse::Ok().json(ApiResult::<()>::error(
+                ERROR_CODE_PARAM_ERROR.to_string(),
+                Some(err.to_string()),
+            ));
+        }
+    }
     let service_info = ServiceDetailDto {
         namespace_id: service_key.namespace_id,
         service_name: service_key.service_name,
         group_name: service_key.group_name,
         metadata,
         protect_threshold: param.protect_threshold,
+        selector: param.selector.map(Arc::new),
+        health_check: param.health_check.map(Arc::new),
         ..Default::default()
     };
     if let Ok(res) = appdata
@@ -254,6 +277,12 @@ pub async fn add_instance(
                     .await
                 {
                     Ok(_) => HttpResponse::Ok().json(ApiResult::success(Some(true))),
+                    Err(err) if err.is::<NamespaceQuotaError>() => {
+                        HttpResponse::Ok().json(ApiResult::<()>::error(
+                            ERROR_CODE_NAMESPACE_QUOTA_EXCEEDED.to_string(),
+                            Some(err.to_string()),
+                        ))
+                    }
                     Err(err) => HttpResponse::Ok().json(ApiResult::<()>::error(
                         ERROR_CODE_SYSTEM_ERROR.to_string(),
                         Some(err.to_string()),
@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use actix_web::{web, HttpResponse, Responder};
+
+use crate::common::appdata::AppShareData;
+use crate::common::model::ApiResult;
+use crate::console::model::user_model::RoleNameParam;
+use crate::console::v2::{handle_system_error, handle_unexpected_response_error};
+use crate::user::model::RoleParam;
+use crate::user::role::{RoleManagerReq, RoleManagerResult};
+
+pub async fn query_role_list(appdata: web::Data<Arc<AppShareData>>) -> impl Responder {
+    match appdata.role_manager.send(RoleManagerReq::QueryList).await {
+        Ok(Ok(RoleManagerResult::List(list))) => {
+            HttpResponse::Ok().json(ApiResult::success(Some(list)))
+        }
+        Ok(Ok(_)) => handle_unexpected_response_error("query role list"),
+        Ok(Err(err)) => handle_system_error(err, "query role list error"),
+        Err(err) => handle_system_error(err, "query role list error"),
+    }
+}
+
+pub async fn add_role(
+    appdata: web::Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<RoleParam>,
+) -> impl Responder {
+    do_manage_request(&appdata, RoleManagerReq::Add(param), "add role").await
+}
+
+pub async fn update_role(
+    appdata: web::Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<RoleParam>,
+) -> impl Responder {
+    do_manage_request(&appdata, RoleManagerReq::Update(param), "update role").await
+}
+
+pub async fn remove_role(
+    appdata: web::Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<RoleNameParam>,
+) -> impl Responder {
+    do_manage_request(&appdata, RoleManagerReq::Remove(param.name), "remove role").await
+}
+
+async fn do_manage_request(
+    appdata: &Arc<AppShareData>,
+    req: RoleManagerReq,
+    context: &str,
+) -> HttpResponse {
+    match appdata.role_manager.send(req).await {
+        Ok(Ok(_)) => HttpResponse::Ok().json(ApiResult::success(Some(true))),
+        Ok(Err(err)) => handle_system_error(err, context),
+        Err(err) => handle_system_error(err, context),
+    }
+}
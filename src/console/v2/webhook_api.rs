@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use actix_web::{web, HttpResponse, Responder};
+
+use crate::common::appdata::AppShareData;
+use crate::common::model::{ApiResult, PageResult};
+use crate::console::model::webhook_model::{WebhookDeadLetterQueryRequest, WebhookIdParam};
+use crate::console::v2::{handle_system_error, handle_unexpected_response_error};
+use crate::webhook::core::{WebhookManageReq, WebhookResult};
+use crate::webhook::model::WebhookParam;
+
+pub async fn query_webhook_list(appdata: web::Data<Arc<AppShareData>>) -> impl Responder {
+    match appdata
+        .webhook_manager
+        .send(WebhookManageReq::QueryList)
+        .await
+    {
+        Ok(Ok(WebhookResult::List(list))) => {
+            HttpResponse::Ok().json(ApiResult::success(Some(list)))
+        }
+        Ok(Ok(_)) => handle_unexpected_response_error("query webhook list"),
+        Ok(Err(err)) => handle_system_error(err, "query webhook list error"),
+        Err(err) => handle_system_error(err, "query webhook list error"),
+    }
+}
+
+pub async fn set_webhook(
+    appdata: web::Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<WebhookParam>,
+) -> impl Responder {
+    match appdata
+        .webhook_manager
+        .send(WebhookManageReq::Set(param))
+        .await
+    {
+        Ok(Ok(WebhookResult::Id(id))) => HttpResponse::Ok().json(ApiResult::success(Some(id))),
+        Ok(Ok(_)) => handle_unexpected_response_error("set webhook"),
+        Ok(Err(err)) => handle_system_error(err, "set webhook error"),
+        Err(err) => handle_system_error(err, "set webhook error"),
+    }
+}
+
+pub async fn remove_webhook(
+    appdata: web::Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<WebhookIdParam>,
+) -> impl Responder {
+    do_manage_request(
+        &appdata,
+        WebhookManageReq::Remove(param.id),
+        "remove webhook",
+    )
+    .await
+}
+
+pub async fn query_dead_letter_list(
+    appdata: web::Data<Arc<AppShareData>>,
+    web::Query(request): web::Query<WebhookDeadLetterQueryRequest>,
+) -> impl Responder {
+    let cmd = WebhookManageReq::QueryDeadLetter(request.to_param());
+    match appdata.webhook_manager.send(cmd).await {
+        Ok(Ok(WebhookResult::DeadLetterPage(total_count, list))) => {
+            HttpResponse::Ok().json(ApiResult::success(Some(PageResult { total_count, list })))
+        }
+        Ok(Ok(_)) => handle_unexpected_response_error("query webhook dead letter"),
+        Ok(Err(err)) => handle_system_error(err, "query webhook dead letter error"),
+        Err(err) => handle_system_error(err, "query webhook dead letter error"),
+    }
+}
+
+pub async fn retry_dead_letter(
+    appdata: web::Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<WebhookIdParam>,
+) -> impl Responder {
+    do_manage_request(
+        &appdata,
+        WebhookManageReq::RetryDeadLetter(param.id),
+        "retry webhook dead letter",
+    )
+    .await
+}
+
+pub async fn remove_dead_letter(
+    appdata: web::Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<WebhookIdParam>,
+) -> impl Responder {
+    do_manage_request(
+        &appdata,
+        WebhookManageReq::RemoveDeadLetter(param.id),
+        "remove webhook dead letter",
+    )
+    .await
+}
+
+async fn do_manage_request(
+    appdata: &Arc<AppShareData>,
+    req: WebhookManageReq,
+    context: &str,
+) -> HttpResponse {
+    match appdata.webhook_manager.send(req).await {
+        Ok(Ok(_)) => HttpResponse::Ok().json(ApiResult::success(Some(true))),
+        Ok(Err(err)) => handle_system_error(err, context),
+        Err(err) => handle_system_error(err, context),
+    }
+}
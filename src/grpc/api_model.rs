@@ -1,4 +1,7 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use serde::{Deserialize, Serialize};
 
@@ -210,6 +213,62 @@ pub struct ConfigChangeNotifyRequest {
     pub tenant: Arc<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFuzzyWatchRequest {
+    pub module: Option<String>,
+    pub request_id: Option<String>,
+    pub headers: Option<HashMap<String, String>>,
+
+    pub group_key_pattern: Option<String>,
+    #[serde(default)]
+    pub received_group_keys: Option<HashSet<String>>,
+    pub watch_type: Option<String>,
+    #[serde(default, alias = "isInitializing")]
+    pub initializing: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFuzzyWatchResponse {
+    pub result_code: u16,
+    pub error_code: u16,
+    pub message: Option<String>,
+    pub request_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Default, Hash, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFuzzyWatchContext {
+    pub group_key: Arc<String>,
+    pub changed_type: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFuzzyWatchSyncRequest {
+    pub module: Option<String>,
+    pub request_id: Option<String>,
+    pub headers: HashMap<String, String>,
+
+    pub group_key_pattern: Arc<String>,
+    pub contexts: Vec<ConfigFuzzyWatchContext>,
+    pub total_batch: usize,
+    pub current_batch: usize,
+    pub sync_type: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFuzzyWatchChangeNotifyRequest {
+    pub module: Option<String>,
+    pub request_id: Option<String>,
+    pub headers: HashMap<String, String>,
+
+    pub group_key: Arc<String>,
+    pub change_type: &'static str,
+}
+
 // ----- naming model -----
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -399,3 +458,61 @@ pub struct ServiceListResponse {
     pub count: usize,
     pub service_names: Option<Vec<Arc<String>>>,
 }
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NamingFuzzyWatchRequest {
+    pub module: Option<String>,
+    pub request_id: Option<String>,
+    pub headers: Option<HashMap<String, String>>,
+
+    pub namespace: Option<String>,
+    pub group_key_pattern: Option<String>,
+    #[serde(default)]
+    pub received_group_keys: Option<HashSet<String>>,
+    pub watch_type: Option<String>,
+    #[serde(default, alias = "isInitializing")]
+    pub initializing: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NamingFuzzyWatchResponse {
+    pub result_code: u16,
+    pub error_code: u16,
+    pub message: Option<String>,
+    pub request_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Default, Hash, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct NamingFuzzyWatchContext {
+    pub service_key: Arc<String>,
+    pub changed_type: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NamingFuzzyWatchSyncRequest {
+    pub module: Option<String>,
+    pub request_id: Option<String>,
+    pub headers: HashMap<String, String>,
+
+    pub group_key_pattern: Arc<String>,
+    pub contexts: Vec<NamingFuzzyWatchContext>,
+    pub total_batch: usize,
+    pub current_batch: usize,
+    pub sync_type: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NamingFuzzyWatchChangeNotifyRequest {
+    pub module: Option<String>,
+    pub request_id: Option<String>,
+    pub headers: HashMap<String, String>,
+
+    pub service_key: Arc<String>,
+    pub changed_type: &'static str,
+    pub sync_type: &'static str,
+}
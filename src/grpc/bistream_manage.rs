@@ -14,34 +14,62 @@ use crate::{
 };
 
 use super::{
-    api_model::{ConfigChangeNotifyRequest, NotifySubscriberRequest, CONFIG_MODEL, NAMING_MODEL},
+    api_model::{
+        ConfigChangeNotifyRequest, ConfigFuzzyWatchChangeNotifyRequest,
+        ConfigFuzzyWatchSyncRequest, NamingFuzzyWatchChangeNotifyRequest,
+        NamingFuzzyWatchSyncRequest, NotifySubscriberRequest, CONFIG_MODEL, NAMING_MODEL,
+    },
     bistream_conn::{BiStreamConn, BiStreamSenderCmd},
+    conn_manage::{
+        parse_client_id, rebalance_local_conns, split_host_port, ConnRebalanceOption, GrpcConnInfo,
+        GrpcConnQueryParam, GrpcConnRebalanceParam,
+    },
     handler::converter::ModelConverter,
     nacos_proto::Payload,
     PayloadUtils,
 };
 use crate::common::constant::EMPTY_CLIENT_VERSION;
 use crate::common::model::ClientVersion;
+use crate::config::model::CLIENT_APP_NAME_LABEL;
 use crate::grpc::api_model::ConnectionSetupRequest;
 use crate::grpc::bistream_conn::NamespaceType;
+use crate::namespace::model::NamespaceQuota;
+use crate::naming::cluster::node_manage::NodeManage;
+use crate::raft::network::factory::RaftClusterRequestSender;
 use actix::prelude::*;
 use bean_factory::{bean, Inject};
 use inner_mem_cache::TimeoutSet;
 
+type ClientLabels = Arc<HashMap<String, String>>;
+
+/// 下发ConnectResetRequest后等待客户端迁移的时间，超时后可再次迁移
+const CONN_RESET_TIMEOUT_MILLIS: u64 = 60_000;
+
 pub(crate) struct ConnCacheItem {
     last_active_time: u64,
+    connect_time: u64,
+    /// 下发ConnectResetRequest的时间，0表示未下发
+    reset_time: u64,
     conn: Addr<BiStreamConn>,
     pub(crate) client_version: Arc<ClientVersion>,
     pub(crate) namespace: NamespaceType,
+    pub(crate) labels: ClientLabels,
 }
 
 impl ConnCacheItem {
+    fn is_resetting(&self, now: u64) -> bool {
+        self.reset_time > 0 && self.reset_time + CONN_RESET_TIMEOUT_MILLIS > now
+    }
+
     fn new(last_active_time: u64, conn: Addr<BiStreamConn>) -> Self {
         Self {
             last_active_time,
+            connect_time: last_active_time,
+            reset_time: 0,
             conn,
             client_version: EMPTY_CLIENT_VERSION.clone(),
             namespace: NamespaceType::Unknown,
+            labels: Default::default(),
         }
     }
 }
@@ -57,6 +85,14 @@ pub struct BiStreamManage {
     request_id: u64,
     config_addr: Option<Addr<ConfigActor>>,
     naming_addr: Option<Addr<NamingActor>>,
+    /// 命名空间gRPC连接数量上限，默认命名空间使用空串
+    namespace_conn_limit: HashMap<Arc<String>, usize>,
+    node_manage: Option<Arc<NodeManage>>,
+    cluster_sender: Option<Arc<RaftClusterRequestSender>>,
+    http_port: u16,
+    /// 连接自动均衡配置，为空表示不开启
+    rebalance_option: Option<ConnRebalanceOption>,
+    rebalance_running: bool,
 }
 
 impl BiStreamManage {
@@ -80,18 +116,224 @@ impl BiStreamManage {
             .add(now + self.detection_time_out, client_id);
     }
 
-    fn active_client(&mut self, client_id: Arc<String>) -> anyhow::Result<Arc<ClientVersion>> {
+    fn active_client(
+        &mut self,
+        client_id: Arc<String>,
+    ) -> anyhow::Result<(Arc<ClientVersion>, ClientLabels)> {
         let now = now_millis();
         if let Some(item) = self.conn_cache.get_mut(&client_id) {
             //log::info!("active_client success client_id:{}",&client_id);
             item.last_active_time = now;
-            Ok(item.client_version.clone())
+            Ok((item.client_version.clone(), item.labels.clone()))
         } else {
             //log::info!("active_client empty client_id:{}",&client_id);
             Err(anyhow::anyhow!("Connection is unregistered."))
         }
     }
 
+    fn get_namespace_conn_count(&self, namespace_id: &str) -> usize {
+        self.conn_cache
+            .values()
+            .filter(|item| Self::get_namespace_id(&item.namespace) == namespace_id)
+            .count()
+    }
+
+    fn get_namespace_id(namespace: &NamespaceType) -> &str {
+        if namespace.is_default() {
+            ""
+        } else {
+            namespace.to_str()
+        }
+    }
+
+    ///
+    /// 连接建立时检查命名空间连接数配额，超出时关闭连接
+    fn check_namespace_conn_quota(&mut self, client_id: &Arc<String>) -> bool {
+        let namespace_id = if let Some(item) = self.conn_cache.get(client_id) {
+            Self::get_namespace_id(&item.namespace).to_owned()
+        } else {
+            return true;
+        };
+        let limit = if let Some(limit) = self.namespace_conn_limit.get(&namespace_id) {
+            *limit
+        } else {
+            return true;
+        };
+        // 当前连接已计入缓存
+        let count = self.get_namespace_conn_count(&namespace_id);
+        if count <= limit {
+            return true;
+        }
+        log::warn!(
+            "close grpc connection {}, namespace {} connection count reached the limit {}",
+            client_id,
+            &namespace_id,
+            limit
+        );
+        self.close_conn(client_id);
+        false
+    }
+
+    ///
+    /// 关闭连接并清理连接上的订阅及注册实例
+    fn close_conn(&mut self, client_id: &Arc<String>) -> bool {
+        let exists = if let Some(item) = self.conn_cache.remove(client_id) {
+            item.conn.do_send(BiStreamSenderCmd::Close);
+            true
+        } else {
+            false
+        };
+        if let Some(config_addr) = &self.config_addr {
+            config_addr.do_send(ConfigCmd::RemoveSubscribeClient(client_id.clone()));
+        }
+        if let Some(naming_addr) = &self.naming_addr {
+            naming_addr.do_send(NamingCmd::RemoveClient(client_id.clone()));
+        }
+        exists
+    }
+
+    fn build_conn_info(client_id: &Arc<String>, item: &ConnCacheItem, now: u64) -> GrpcConnInfo {
+        let (_, addr) = parse_client_id(client_id);
+        let (ip, port) = split_host_port(addr);
+        GrpcConnInfo {
+            client_id: client_id.clone(),
+            ip: ip.to_owned(),
+            port,
+            client_version: if item.client_version.version.is_empty() {
+                None
+            } else {
+                Some(item.client_version.to_string())
+            },
+            namespace_id: Arc::new(Self::get_namespace_id(&item.namespace).to_owned()),
+            app_name: item.labels.get(CLIENT_APP_NAME_LABEL).cloned(),
+            labels: item.labels.as_ref().clone(),
+            connect_time: item.connect_time,
+            last_active_time: item.last_active_time,
+            resetting: item.is_resetting(now),
+            ..Default::default()
+        }
+    }
+
+    fn query_conn_info_list(&self, param: &GrpcConnQueryParam) -> Vec<GrpcConnInfo> {
+        let now = now_millis();
+        let mut list = vec![];
+        for (client_id, item) in &self.conn_cache {
+            if !param.match_namespace(Self::get_namespace_id(&item.namespace)) {
+                continue;
+            }
+            let info = Self::build_conn_info(client_id, item, now);
+            if let Some(ip) = param.ip.as_ref().filter(|e| !e.is_empty()) {
+                if &info.ip != ip {
+                    continue;
+                }
+            }
+            if let Some(app_name) = param.app_name.as_ref().filter(|e| !e.is_empty()) {
+                if info.app_name.as_ref() != Some(app_name) {
+                    continue;
+                }
+            }
+            list.push(info);
+        }
+        list.sort_by_key(|e| e.connect_time);
+        list
+    }
+
+    ///
+    /// 不包含迁移中的连接数，避免重复迁移
+    fn get_conn_count(&self) -> usize {
+        let now = now_millis();
+        self.conn_cache
+            .values()
+            .filter(|item| !item.is_resetting(now))
+            .count()
+    }
+
+    ///
+    /// 向连接下发ConnectResetRequest，未指定连接时按建立时间从早到晚选择count个未迁移的连接
+    fn reset_conns(
+        &mut self,
+        client_id: Option<Arc<String>>,
+        count: usize,
+        server_ip: Option<String>,
+        server_port: Option<String>,
+    ) -> usize {
+        let now = now_millis();
+        let client_ids = if let Some(client_id) = client_id {
+            if self.conn_cache.contains_key(&client_id) {
+                vec![client_id]
+            } else {
+                vec![]
+            }
+        } else {
+            let mut list: Vec<(u64, Arc<String>)> = self
+                .conn_cache
+                .iter()
+                .filter(|(_, item)| !item.is_resetting(now))
+                .map(|(k, item)| (item.connect_time, k.clone()))
+                .collect();
+            list.sort_by_key(|e| e.0);
+            list.into_iter().take(count).map(|e| e.1).collect()
+        };
+        let mut reset_count = 0;
+        for client_id in client_ids {
+            let request_id = self.next_request_id();
+            if let Some(item) = self.conn_cache.get_mut(&client_id) {
+                item.reset_time = now;
+                item.conn.do_send(BiStreamSenderCmd::Reset(
+                    request_id,
+                    server_ip.clone(),
+                    server_port.clone(),
+                ));
+                reset_count += 1;
+            }
+        }
+        if reset_count > 0 {
+            log::info!(
+                "reset grpc connection to {:?}:{:?}, size:{}",
+                &server_ip,
+                &server_port,
+                reset_count
+            );
+        }
+        reset_count
+    }
+
+    fn auto_rebalance(&mut self, ctx: &mut Context<Self>) {
+        if self.rebalance_running || self.conn_cache.is_empty() {
+            return;
+        }
+        let (node_manage, cluster_sender, option) = match (
+            self.node_manage.clone(),
+            self.cluster_sender.clone(),
+            self.rebalance_option.clone(),
+        ) {
+            (Some(a), Some(b), Some(c)) => (a, b, c),
+            _ => return,
+        };
+        self.rebalance_running = true;
+        let manage = ctx.address();
+        let http_port = self.http_port;
+        async move {
+            rebalance_local_conns(
+                &node_manage,
+                &cluster_sender,
+                &manage,
+                http_port,
+                GrpcConnRebalanceParam::default(),
+                Some(&option),
+            )
+            .await
+        }
+        .into_actor(self)
+        .map(|result, act, _ctx| {
+            act.rebalance_running = false;
+            if let Err(err) = result {
+                log::warn!("grpc connection rebalance error,{}", err);
+            }
+        })
+        .spawn(ctx);
+    }
+
     fn next_request_id(&mut self) -> String {
         if self.request_id >= 0x7fff_ffff_ffff_ffff {
             self.request_id = 0;
@@ -186,12 +428,25 @@ impl Inject for BiStreamManage {
         &mut self,
         factory_data: bean_factory::FactoryData,
         _factory: bean_factory::BeanFactory,
-        _ctx: &mut Self::Context,
+        ctx: &mut Self::Context,
     ) {
         self.config_addr = factory_data.get_actor();
         self.naming_addr = factory_data.get_actor();
+        self.node_manage = factory_data.get_bean();
+        self.cluster_sender = factory_data.get_bean();
         if let Some(sys_config) = factory_data.get_bean::<crate::common::AppSysConfig>() {
             self.detection_time_out = sys_config.grpc_detection_timeout;
+            self.http_port = sys_config.http_port;
+            if sys_config.grpc_conn_rebalance_enable {
+                self.rebalance_option = Some(ConnRebalanceOption {
+                    threshold_percent: sys_config.grpc_conn_rebalance_threshold_percent,
+                    batch_size: sys_config.grpc_conn_rebalance_batch_size,
+                });
+                ctx.run_interval(
+                    Duration::from_secs(sys_config.grpc_conn_rebalance_interval_second),
+                    |act, ctx| act.auto_rebalance(ctx),
+                );
+            }
             log::info!(
                 "BiStreamManage inject complete, detection_time_out:{}",
                 self.detection_time_out
@@ -217,12 +472,77 @@ pub enum BiStreamManageCmd {
     ActiveClinet(Arc<String>),
     NotifyConfig(ConfigKey, HashSet<Arc<String>>),
     NotifyNaming(ServiceKey, HashSet<Arc<String>>, ServiceInfo),
+    /// 模糊监听通知
+    NotifyFuzzyWatch(HashSet<Arc<String>>, FuzzyWatchNotify),
     QueryConnList,
+    QueryConnInfoList(Box<GrpcConnQueryParam>),
+    QueryConnCount,
+    /// 关闭指定连接
+    EjectConn(Arc<String>),
+    /// 通知客户端重连到指定节点
+    ResetConns {
+        client_id: Option<Arc<String>>,
+        count: usize,
+        server_ip: Option<String>,
+        server_port: Option<String>,
+    },
+    /// 命名空间配额变更，由命名空间模块在raft apply时同步
+    SetNamespaceQuota(Arc<String>, Option<NamespaceQuota>),
+    QueryNamespaceConnCount(Arc<String>),
+}
+
+pub enum FuzzyWatchNotify {
+    ConfigSync(ConfigFuzzyWatchSyncRequest),
+    ConfigChange(ConfigFuzzyWatchChangeNotifyRequest),
+    NamingSync(NamingFuzzyWatchSyncRequest),
+    NamingChange(NamingFuzzyWatchChangeNotifyRequest),
+}
+
+impl FuzzyWatchNotify {
+    fn build_payload(self, request_id: String) -> anyhow::Result<Payload> {
+        let payload = match self {
+            FuzzyWatchNotify::ConfigSync(mut request) => {
+                request.request_id = Some(request_id);
+                request.module = Some(CONFIG_MODEL.to_string());
+                PayloadUtils::build_payload(
+                    "ConfigFuzzyWatchSyncRequest",
+                    serde_json::to_string(&request)?,
+                )
+            }
+            FuzzyWatchNotify::ConfigChange(mut request) => {
+                request.request_id = Some(request_id);
+                request.module = Some(CONFIG_MODEL.to_string());
+                PayloadUtils::build_payload(
+                    "ConfigFuzzyWatchChangeNotifyRequest",
+                    serde_json::to_string(&request)?,
+                )
+            }
+            FuzzyWatchNotify::NamingSync(mut request) => {
+                request.request_id = Some(request_id);
+                request.module = Some(NAMING_MODEL.to_string());
+                PayloadUtils::build_payload(
+                    "NamingFuzzyWatchSyncRequest",
+                    serde_json::to_string(&request)?,
+                )
+            }
+            FuzzyWatchNotify::NamingChange(mut request) => {
+                request.request_id = Some(request_id);
+                request.module = Some(NAMING_MODEL.to_string());
+                PayloadUtils::build_payload(
+                    "NamingFuzzyWatchChangeNotifyRequest",
+                    serde_json::to_string(&request)?,
+                )
+            }
+        };
+        Ok(payload)
+    }
 }
 
 pub enum BiStreamManageResult {
     ConnList(Vec<Arc<String>>),
-    ClientInfo(Arc<ClientVersion>),
+    ConnInfoList(Vec<GrpcConnInfo>),
+    ClientInfo(Arc<ClientVersion>, ClientLabels),
+    ConnCount(usize),
     None,
 }
 
@@ -248,6 +568,12 @@ impl Handler<BiStreamManageCmd> for BiStreamManage {
                                     Arc::new(ClientVersion::from_string(&client_version));
                             }
                             item.namespace = NamespaceType::from_option(request.tenant);
+                            if let Some(labels) = request.labels {
+                                item.labels = Arc::new(labels);
+                            }
+                        }
+                        if !self.check_namespace_conn_quota(&client_id) {
+                            return Ok(BiStreamManageResult::None);
                         }
                     }
                     self.active_client(client_id).ok();
@@ -268,8 +594,8 @@ impl Handler<BiStreamManageCmd> for BiStreamManage {
                 //println!("|AddConn|conn size: {}",self.conn_cache.len());
             }
             BiStreamManageCmd::ActiveClinet(client_id) => {
-                let client_version = self.active_client(client_id)?;
-                return Ok(BiStreamManageResult::ClientInfo(client_version));
+                let (client_version, labels) = self.active_client(client_id)?;
+                return Ok(BiStreamManageResult::ClientInfo(client_version, labels));
             }
             BiStreamManageCmd::NotifyConfig(config_key, client_id_set) => {
                 let tenant = config_key.tenant.clone();
@@ -329,6 +655,14 @@ impl Handler<BiStreamManageCmd> for BiStreamManage {
                     }
                 }
             }
+            BiStreamManageCmd::NotifyFuzzyWatch(client_id_set, notify) => {
+                let payload = Arc::new(notify.build_payload(self.next_request_id())?);
+                for item in &client_id_set {
+                    if let Some(item) = self.conn_cache.get(item) {
+                        item.conn.do_send(BiStreamSenderCmd::Send(payload.clone()));
+                    }
+                }
+            }
             BiStreamManageCmd::QueryConnList => {
                 let mut list = Vec::with_capacity(self.conn_cache.len());
                 for key in self.conn_cache.keys() {
@@ -336,6 +670,43 @@ impl Handler<BiStreamManageCmd> for BiStreamManage {
                 }
                 return Ok(BiStreamManageResult::ConnList(list));
             }
+            BiStreamManageCmd::QueryConnInfoList(param) => {
+                return Ok(BiStreamManageResult::ConnInfoList(
+                    self.query_conn_info_list(&param),
+                ));
+            }
+            BiStreamManageCmd::QueryConnCount => {
+                return Ok(BiStreamManageResult::ConnCount(self.get_conn_count()));
+            }
+            BiStreamManageCmd::EjectConn(client_id) => {
+                if !self.close_conn(&client_id) {
+                    return Err(anyhow::anyhow!("Connection {} not found.", &client_id));
+                }
+                log::info!("eject grpc connection {}", &client_id);
+            }
+            BiStreamManageCmd::ResetConns {
+                client_id,
+                count,
+                server_ip,
+                server_port,
+            } => {
+                let count = self.reset_conns(client_id, count, server_ip, server_port);
+                return Ok(BiStreamManageResult::ConnCount(count));
+            }
+            BiStreamManageCmd::SetNamespaceQuota(namespace_id, quota) => {
+                match quota.and_then(|v| NamespaceQuota::limit(v.max_grpc_connection)) {
+                    Some(limit) => {
+                        self.namespace_conn_limit.insert(namespace_id, limit);
+                    }
+                    None => {
+                        self.namespace_conn_limit.remove(&namespace_id);
+                    }
+                }
+            }
+            BiStreamManageCmd::QueryNamespaceConnCount(namespace_id) => {
+                let count = self.get_namespace_conn_count(&namespace_id);
+                return Ok(BiStreamManageResult::ConnCount(count));
+            }
         }
         Ok(BiStreamManageResult::None)
     }
@@ -0,0 +1,483 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use actix::Addr;
+use serde::{Deserialize, Serialize};
+
+use crate::common::appdata::AppShareData;
+use crate::config::core::{ConfigCmd, ConfigResult};
+use crate::naming::cluster::node_manage::NodeManage;
+use crate::naming::core::{NamingCmd, NamingResult};
+use crate::raft::cluster::model::{RouterRequest, RouterResponse};
+use crate::raft::cluster::router_request;
+use crate::raft::network::factory::RaftClusterRequestSender;
+
+use super::bistream_manage::{BiStreamManage, BiStreamManageCmd, BiStreamManageResult};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcConnQueryParam {
+    pub node_id: Option<u64>,
+    pub namespace_id: Option<String>,
+    pub ip: Option<String>,
+    pub app_name: Option<String>,
+    pub page_no: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
+impl GrpcConnQueryParam {
+    pub fn match_namespace(&self, namespace_id: &str) -> bool {
+        match self.namespace_id.as_deref() {
+            None => true,
+            Some("") | Some("public") => namespace_id.is_empty() || namespace_id == "public",
+            Some(v) => v == namespace_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcConnInfo {
+    pub client_id: Arc<String>,
+    pub node_id: u64,
+    pub ip: String,
+    pub port: u16,
+    pub client_version: Option<String>,
+    pub namespace_id: Arc<String>,
+    pub app_name: Option<String>,
+    pub labels: HashMap<String, String>,
+    pub connect_time: u64,
+    pub last_active_time: u64,
+    /// 已下发ConnectResetRequest，等待客户端迁移
+    pub resetting: bool,
+    pub config_listen_count: usize,
+    pub naming_subscribe_count: usize,
+    pub naming_instance_count: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcNodeConnCount {
+    pub node_id: u64,
+    pub addr: Arc<String>,
+    pub is_local: bool,
+    /// 不包含迁移中的连接
+    pub count: usize,
+    /// 节点http端口，客户端按http端口+1000连接gRPC
+    pub http_port: u16,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcConnQueryResult {
+    pub total_count: usize,
+    pub list: Vec<GrpcConnInfo>,
+    pub node_counts: Vec<GrpcNodeConnCount>,
+    pub fail_nodes: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcConnEjectParam {
+    pub client_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcConnRebalanceParam {
+    /// 需要迁出连接的节点，为空时按client_id所在节点或本节点
+    pub node_id: Option<u64>,
+    /// 迁入节点，为空时选择连接数最少的节点
+    pub target_node_id: Option<u64>,
+    /// 迁移连接数，为空时迁移超出集群平均值的部分
+    pub count: Option<usize>,
+    /// 只迁移指定连接
+    pub client_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcConnRebalanceResult {
+    pub node_id: u64,
+    pub target_node_id: u64,
+    pub target_addr: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConnRebalanceOption {
+    pub threshold_percent: u64,
+    pub batch_size: usize,
+}
+
+///
+/// client_id格式为`{node_id}_{ip}:{port}`
+pub fn parse_client_id(client_id: &str) -> (Option<u64>, &str) {
+    match client_id.split_once('_') {
+        Some((node_id, addr)) => (node_id.parse().ok(), addr),
+        None => (None, client_id),
+    }
+}
+
+pub fn split_host_port(addr: &str) -> (&str, u16) {
+    match addr.rsplit_once(':') {
+        Some((host, port)) => (
+            host.trim_start_matches('[').trim_end_matches(']'),
+            port.parse().unwrap_or_default(),
+        ),
+        None => (addr, 0),
+    }
+}
+
+///
+/// 计算本节点需要迁出的连接数及迁入节点；
+/// 本节点连接数超过平均值的threshold_percent时，迁出超出平均值的部分，且不让迁入节点超过平均值
+pub fn compute_rebalance(
+    nodes: &[GrpcNodeConnCount],
+    threshold_percent: u64,
+) -> Option<(usize, usize)> {
+    if nodes.len() < 2 {
+        return None;
+    }
+    let local = nodes.iter().find(|e| e.is_local)?;
+    let total: usize = nodes.iter().map(|e| e.count).sum();
+    let avg = total / nodes.len();
+    if (local.count as u64) * 100 <= (avg as u64) * (100 + threshold_percent) {
+        return None;
+    }
+    let (target_index, target) = nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| !e.is_local)
+        .min_by_key(|(_, e)| e.count)?;
+    let count = (local.count - avg).min(avg.saturating_sub(target.count));
+    if count == 0 {
+        None
+    } else {
+        Some((target_index, count))
+    }
+}
+
+pub async fn query_local_conn_count(manage: &Addr<BiStreamManage>) -> anyhow::Result<usize> {
+    match manage.send(BiStreamManageCmd::QueryConnCount).await?? {
+        BiStreamManageResult::ConnCount(count) => Ok(count),
+        _ => Err(anyhow::anyhow!("query grpc connection count error")),
+    }
+}
+
+///
+/// 查询集群各节点的gRPC连接数，查询失败的节点不参与均衡
+pub async fn query_cluster_conn_counts(
+    node_manage: &NodeManage,
+    cluster_sender: &Arc<RaftClusterRequestSender>,
+    manage: &Addr<BiStreamManage>,
+    local_http_port: u16,
+) -> anyhow::Result<(Vec<GrpcNodeConnCount>, Vec<u64>)> {
+    let nodes = node_manage.get_all_valid_nodes().await?;
+    let tasks = nodes.into_iter().map(|node| async move {
+        let result = if node.is_local {
+            query_local_conn_count(manage)
+                .await
+                .map(|count| (count, local_http_port))
+        } else {
+            match router_request(
+                RouterRequest::GrpcConnCount,
+                node.addr.clone(),
+                cluster_sender,
+            )
+            .await
+            {
+                Ok(RouterResponse::GrpcConnCount { count, http_port }) => Ok((count, http_port)),
+                Ok(_) => Err(anyhow::anyhow!("query grpc connection count error")),
+                Err(err) => Err(err),
+            }
+        };
+        (node, result)
+    });
+    let mut list = vec![];
+    let mut fail_nodes = vec![];
+    for (node, result) in futures_util::future::join_all(tasks).await {
+        match result {
+            Ok((count, http_port)) => list.push(GrpcNodeConnCount {
+                node_id: node.id,
+                addr: node.addr,
+                is_local: node.is_local,
+                count,
+                http_port,
+            }),
+            Err(err) => {
+                log::warn!(
+                    "query grpc connection count from node {} error,{}",
+                    node.id,
+                    err
+                );
+                fail_nodes.push(node.id);
+            }
+        }
+    }
+    Ok((list, fail_nodes))
+}
+
+///
+/// 向本节点的连接下发ConnectResetRequest，让客户端迁移到目标节点
+pub async fn rebalance_local_conns(
+    node_manage: &NodeManage,
+    cluster_sender: &Arc<RaftClusterRequestSender>,
+    manage: &Addr<BiStreamManage>,
+    local_http_port: u16,
+    param: GrpcConnRebalanceParam,
+    option: Option<&ConnRebalanceOption>,
+) -> anyhow::Result<GrpcConnRebalanceResult> {
+    let (nodes, _) =
+        query_cluster_conn_counts(node_manage, cluster_sender, manage, local_http_port).await?;
+    let local = nodes
+        .iter()
+        .find(|e| e.is_local)
+        .ok_or_else(|| anyhow::anyhow!("local node is not valid"))?;
+    let (target, mut count) = if let Some(target_node_id) = param.target_node_id {
+        let target = nodes
+            .iter()
+            .find(|e| e.node_id == target_node_id)
+            .ok_or_else(|| anyhow::anyhow!("target node {} is not valid", target_node_id))?;
+        if target.is_local {
+            return Err(anyhow::anyhow!("target node can't be the source node"));
+        }
+        let total: usize = nodes.iter().map(|e| e.count).sum();
+        let count = local.count.saturating_sub(total / nodes.len());
+        (target, count)
+    } else {
+        let threshold_percent = option.map(|e| e.threshold_percent).unwrap_or_default();
+        match compute_rebalance(&nodes, threshold_percent) {
+            Some((index, count)) => (&nodes[index], count),
+            None => {
+                // 手动迁移指定连接或数量时，仍选择连接数最少的节点
+                let target = nodes
+                    .iter()
+                    .filter(|e| !e.is_local)
+                    .min_by_key(|e| e.count)
+                    .ok_or_else(|| anyhow::anyhow!("no other valid node"))?;
+                (target, 0)
+            }
+        }
+    };
+    if let Some(v) = param.count {
+        count = v;
+    }
+    if let Some(option) = option {
+        count = count.min(option.batch_size);
+    }
+    let client_id = param.client_id.filter(|e| !e.is_empty()).map(Arc::new);
+    if client_id.is_some() {
+        count = 1;
+    }
+    let mut result = GrpcConnRebalanceResult {
+        node_id: local.node_id,
+        target_node_id: target.node_id,
+        target_addr: String::new(),
+        count: 0,
+    };
+    let (ip, _) = split_host_port(&target.addr);
+    result.target_addr = format!("{}:{}", ip, target.http_port);
+    if count == 0 {
+        return Ok(result);
+    }
+    let cmd = BiStreamManageCmd::ResetConns {
+        client_id,
+        count,
+        server_ip: Some(ip.to_owned()),
+        server_port: Some(target.http_port.to_string()),
+    };
+    if let BiStreamManageResult::ConnCount(count) = manage.send(cmd).await?? {
+        result.count = count;
+    }
+    Ok(result)
+}
+
+///
+/// 查询本节点连接详情，并补充配置监听、服务订阅及注册实例数量
+pub async fn query_local_conn_list(
+    app: &Arc<AppShareData>,
+    param: GrpcConnQueryParam,
+) -> anyhow::Result<Vec<GrpcConnInfo>> {
+    let mut list = match app
+        .bi_stream_manage
+        .send(BiStreamManageCmd::QueryConnInfoList(Box::new(param)))
+        .await??
+    {
+        BiStreamManageResult::ConnInfoList(list) => list,
+        _ => return Err(anyhow::anyhow!("query grpc connection error")),
+    };
+    let config_counts: HashMap<Arc<String>, usize> = match app
+        .config_addr
+        .send(ConfigCmd::QueryClientSubscribeCount)
+        .await??
+    {
+        ConfigResult::ClientSubscribeCount(v) => v.into_iter().collect(),
+        _ => HashMap::new(),
+    };
+    let naming_counts: HashMap<Arc<String>, usize> = match app
+        .naming_addr
+        .send(NamingCmd::QueryClientSubscribeCount)
+        .await??
+    {
+        NamingResult::ClientSubscribeCount(v) => v.into_iter().collect(),
+        _ => HashMap::new(),
+    };
+    let instance_counts: HashMap<Arc<String>, usize> = match app
+        .naming_addr
+        .send(NamingCmd::QueryClientInstanceCount)
+        .await??
+    {
+        NamingResult::ClientInstanceCount(v) => v.into_iter().collect(),
+        _ => HashMap::new(),
+    };
+    for item in list.iter_mut() {
+        item.node_id = app.sys_config.raft_node_id;
+        item.config_listen_count = config_counts.get(&item.client_id).copied().unwrap_or(0);
+        item.naming_subscribe_count = naming_counts.get(&item.client_id).copied().unwrap_or(0);
+        item.naming_instance_count = instance_counts.get(&item.client_id).copied().unwrap_or(0);
+    }
+    Ok(list)
+}
+
+///
+/// 汇总集群所有节点的gRPC连接，指定node_id时只查询该节点
+pub async fn query_cluster_conn_list(
+    app: &Arc<AppShareData>,
+    param: GrpcConnQueryParam,
+) -> anyhow::Result<GrpcConnQueryResult> {
+    let nodes = app.naming_node_manage.get_all_valid_nodes().await?;
+    let tasks = nodes
+        .into_iter()
+        .filter(|node| param.node_id.is_none_or(|id| id == node.id))
+        .map(|node| {
+            let param = param.clone();
+            async move {
+                let result = if node.is_local {
+                    query_local_conn_list(app, param).await
+                } else {
+                    let req = RouterRequest::GrpcConnQuery { param };
+                    match router_request(req, node.addr.clone(), &app.cluster_sender).await {
+                        Ok(RouterResponse::GrpcConnList { list }) => Ok(list),
+                        Ok(_) => Err(anyhow::anyhow!("query grpc connection error")),
+                        Err(err) => Err(err),
+                    }
+                };
+                (node, result)
+            }
+        });
+    let mut result = GrpcConnQueryResult::default();
+    for (node, node_result) in futures_util::future::join_all(tasks).await {
+        match node_result {
+            Ok(list) => {
+                result.node_counts.push(GrpcNodeConnCount {
+                    node_id: node.id,
+                    addr: node.addr,
+                    is_local: node.is_local,
+                    count: list.len(),
+                    http_port: 0,
+                });
+                result.list.extend(list);
+            }
+            Err(err) => {
+                log::warn!("query grpc connection from node {} error,{}", node.id, err);
+                result.fail_nodes.push(node.id);
+            }
+        }
+    }
+    result.list.sort_by_key(|e| e.connect_time);
+    result.total_count = result.list.len();
+    Ok(result)
+}
+
+///
+/// 关闭指定连接，按client_id前缀路由到连接所在节点
+pub async fn eject_conn(app: &Arc<AppShareData>, client_id: Arc<String>) -> anyhow::Result<()> {
+    let (node_id, _) = parse_client_id(&client_id);
+    let node_id = node_id.unwrap_or(app.sys_config.raft_node_id);
+    if node_id == app.sys_config.raft_node_id {
+        app.bi_stream_manage
+            .send(BiStreamManageCmd::EjectConn(client_id))
+            .await??;
+        return Ok(());
+    }
+    let addr = app.naming_node_manage.get_node_addr(node_id).await?;
+    let req = RouterRequest::GrpcConnEject { client_id };
+    router_request(req, addr, &app.cluster_sender).await?;
+    Ok(())
+}
+
+///
+/// 迁移连接，需要在连接所在节点执行
+pub async fn rebalance_conns(
+    app: &Arc<AppShareData>,
+    mut param: GrpcConnRebalanceParam,
+) -> anyhow::Result<GrpcConnRebalanceResult> {
+    let node_id = match (param.node_id, param.client_id.as_ref()) {
+        (Some(node_id), _) => node_id,
+        (None, Some(client_id)) => parse_client_id(client_id)
+            .0
+            .unwrap_or(app.sys_config.raft_node_id),
+        (None, None) => app.sys_config.raft_node_id,
+    };
+    if node_id == app.sys_config.raft_node_id {
+        return rebalance_local_conns(
+            &app.naming_node_manage,
+            &app.cluster_sender,
+            &app.bi_stream_manage,
+            app.sys_config.http_port,
+            param,
+            None,
+        )
+        .await;
+    }
+    param.node_id = Some(node_id);
+    let addr = app.naming_node_manage.get_node_addr(node_id).await?;
+    let req = RouterRequest::GrpcConnRebalance { param };
+    match router_request(req, addr, &app.cluster_sender).await? {
+        RouterResponse::GrpcConnRebalanceResult { result } => Ok(result),
+        _ => Err(anyhow::anyhow!("rebalance grpc connection error")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(node_id: u64, is_local: bool, count: usize) -> GrpcNodeConnCount {
+        GrpcNodeConnCount {
+            node_id,
+            addr: Arc::new(format!("127.0.0.{}:9848", node_id)),
+            is_local,
+            count,
+            http_port: 8848,
+        }
+    }
+
+    #[test]
+    fn compute_rebalance_count() {
+        // 单节点不迁移
+        assert_eq!(compute_rebalance(&[node(1, true, 100)], 20), None);
+        // 未超过阈值
+        let nodes = vec![node(1, true, 110), node(2, false, 90), node(3, false, 100)];
+        assert_eq!(compute_rebalance(&nodes, 20), None);
+        // 迁移超出平均值部分到连接数最少的节点
+        let nodes = vec![node(1, true, 200), node(2, false, 40), node(3, false, 60)];
+        assert_eq!(compute_rebalance(&nodes, 20), Some((1, 60)));
+        // 迁入节点不超过平均值
+        let nodes = vec![node(1, true, 200), node(2, false, 90), node(3, false, 10)];
+        assert_eq!(compute_rebalance(&nodes, 20), Some((2, 90)));
+        let nodes = vec![node(1, false, 0), node(2, true, 10)];
+        assert_eq!(compute_rebalance(&nodes, 0), Some((0, 5)));
+    }
+
+    #[test]
+    fn parse_client_id_and_addr() {
+        assert_eq!(
+            parse_client_id("2_127.0.0.1:52341"),
+            (Some(2), "127.0.0.1:52341")
+        );
+        assert_eq!(split_host_port("127.0.0.1:52341"), ("127.0.0.1", 52341));
+        assert_eq!(split_host_port("[::1]:9848"), ("::1", 9848));
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::sync::Arc;
 
+use crate::config::model::ConfigClientInfo;
 use crate::config::{ConfigUtils, DEFAULT_TENANT};
 use crate::grpc::HandlerResult;
 use crate::{
@@ -54,7 +55,14 @@ impl PayloadHandler for ConfigChangeBatchListenRequestHandler {
             listener_items.push(ListenerItem::new(key, item.md5.unwrap_or_default()));
         }
         let cmd = if request.listen {
-            ConfigCmd::Subscribe(listener_items, request_meta.connection_id)
+            let client = ConfigClientInfo {
+                client_version: request_meta.client_version.clone(),
+                ..ConfigClientInfo::new(
+                    Arc::new(request_meta.client_ip.clone()),
+                    request_meta.labels.clone(),
+                )
+            };
+            ConfigCmd::Subscribe(listener_items, request_meta.connection_id, client)
         } else {
             ConfigCmd::RemoveSubscribe(listener_items, request_meta.connection_id)
         };
@@ -3,9 +3,13 @@
 use std::sync::Arc;
 
 use crate::common::string_utils::StringUtils;
+use crate::config::config_crypto::ENCRYPTED_DATA_KEY_UNSUPPORTED;
 use crate::config::config_type::ConfigType;
+use crate::config::config_validate::ConfigValidateError;
+use crate::config::model::ConfigCasConflictError;
 use crate::config::ConfigUtils;
 use crate::grpc::HandlerResult;
+use crate::namespace::model::{NamespaceQuotaError, NAMESPACE_QUOTA_EXCEEDED_CODE};
 use crate::{
     common::appdata::AppShareData,
     config::core::{ConfigActor, ConfigAsyncCmd, ConfigCmd, ConfigKey, ConfigResult},
@@ -38,10 +42,28 @@ impl PayloadHandler for ConfigPublishRequestHandler {
     ) -> anyhow::Result<HandlerResult> {
         let body_vec = request_payload.body.unwrap_or_default().value;
         let request: ConfigPublishRequest = serde_json::from_slice(&body_vec)?;
+        //配置由服务端落盘加密，不接收客户端加密插件处理过的内容
+        if request
+            .get_addition_param("encryptedDataKey")
+            .is_some_and(|v| !v.is_empty())
+        {
+            let mut response = BaseResponse::build_error_response(
+                400u16,
+                ENCRYPTED_DATA_KEY_UNSUPPORTED.to_owned(),
+            );
+            response.request_id = request.request_id;
+            return Ok(HandlerResult::success(PayloadUtils::build_payload(
+                "ErrorResponse",
+                serde_json::to_string(&response)?,
+            )));
+        }
         let config_type = StringUtils::map_not_empty(request.get_addition_param("type").cloned())
             .map(|v| ConfigType::new_by_value(v.as_ref()).get_value());
         let desc =
             StringUtils::map_not_empty(request.get_addition_param("desc").cloned()).map(Arc::new);
+        let tags = request
+            .get_addition_param("config_tags")
+            .map(|v| ConfigUtils::build_tags(v));
         let mut req = SetConfigReq::new(
             ConfigKey::new(
                 &request.data_id,
@@ -52,6 +74,8 @@ impl PayloadHandler for ConfigPublishRequestHandler {
         );
         req.config_type = config_type;
         req.desc = desc;
+        req.tags = tags;
+        req.cas_md5 = StringUtils::map_not_empty(request.cas_md5).map(Arc::new);
         match self.app_data.config_route.set_config(req).await {
             Ok(_res) => {
                 //let res:ConfigResult = res.unwrap();
@@ -63,7 +87,16 @@ impl PayloadHandler for ConfigPublishRequestHandler {
                 )))
             }
             Err(err) => {
-                let mut response = BaseResponse::build_error_response(500u16, err.to_string());
+                let code = if err.is::<ConfigCasConflictError>() {
+                    409u16
+                } else if err.is::<ConfigValidateError>() {
+                    400u16
+                } else if err.is::<NamespaceQuotaError>() {
+                    NAMESPACE_QUOTA_EXCEEDED_CODE
+                } else {
+                    500u16
+                };
+                let mut response = BaseResponse::build_error_response(code, err.to_string());
                 response.request_id = request.request_id;
                 Ok(HandlerResult::success(PayloadUtils::build_payload(
                     "ErrorResponse",
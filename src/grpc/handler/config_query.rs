@@ -4,6 +4,7 @@ use std::sync::Arc;
 
 use crate::common::model::client_version::ClientNameType;
 use crate::config::config_type::ConfigType;
+use crate::config::model::ConfigClientInfo;
 use crate::config::ConfigUtils;
 use crate::grpc::api_model::NOT_FOUND;
 use crate::grpc::HandlerResult;
@@ -41,11 +42,18 @@ impl PayloadHandler for ConfigQueryRequestHandler {
     ) -> anyhow::Result<HandlerResult> {
         let body_vec = request_payload.body.unwrap_or_default().value;
         let request: ConfigQueryRequest = serde_json::from_slice(&body_vec)?;
-        let cmd = ConfigCmd::GET(ConfigKey::new(
-            &request.data_id,
-            &request.group,
-            &ConfigUtils::default_tenant(request.tenant),
-        ));
+        let client = ConfigClientInfo::new(
+            Arc::new(request_meta.client_ip.clone()),
+            request_meta.labels.clone(),
+        );
+        let cmd = ConfigCmd::GetByClient(
+            ConfigKey::new(
+                &request.data_id,
+                &request.group,
+                &ConfigUtils::default_tenant(request.tenant),
+            ),
+            client,
+        );
         let mut response = ConfigQueryResponse {
             request_id: request.request_id,
             ..Default::default()
@@ -56,16 +64,20 @@ impl PayloadHandler for ConfigQueryRequestHandler {
             response.encrypted_data_key = Some("".to_string());
             response.beta = false;
         }
-        match self.app_data.config_addr.send(cmd).await {
-            Ok(res) => {
-                //let res:ConfigResult = res.unwrap();
-                let r: ConfigResult = res.unwrap();
+        let res = match self.app_data.config_addr.send(cmd).await {
+            Ok(res) => res,
+            Err(err) => Err(err.into()),
+        };
+        //配置内容解密失败时返回错误
+        match res {
+            Ok(r) => {
                 match r {
                     ConfigResult::Data {
                         value: content,
                         md5,
                         config_type,
                         last_modified,
+                        beta,
                         ..
                     } => {
                         //v.to_owned()
@@ -86,6 +98,7 @@ impl PayloadHandler for ConfigQueryRequestHandler {
                         }
                         response.last_modified = last_modified;
                         response.md5 = Some(md5);
+                        response.beta = beta;
                     }
                     _ => {
                         response.result_code = ERROR_CODE;
@@ -1,13 +1,15 @@
 use std::sync::Arc;
 
 use crate::common::appdata::AppShareData;
+use crate::common::fuzzy_watch::FuzzyWatchPattern;
 
 use self::{
     config_change_batch_listen::ConfigChangeBatchListenRequestHandler,
+    config_fuzzy_watch::ConfigFuzzyWatchRequestHandler,
     config_publish::ConfigPublishRequestHandler, config_query::ConfigQueryRequestHandler,
     config_remove::ConfigRemoveRequestHandler, naming_batch_instance::BatchInstanceRequestHandler,
-    naming_instance::InstanceRequestHandler, naming_route::NamingRouteRequestHandler,
-    naming_service_list::ServiceListRequestHandler,
+    naming_fuzzy_watch::NamingFuzzyWatchRequestHandler, naming_instance::InstanceRequestHandler,
+    naming_route::NamingRouteRequestHandler, naming_service_list::ServiceListRequestHandler,
     naming_service_query::ServiceQueryRequestHandler,
     naming_subscribe_service::SubscribeServiceRequestHandler, raft_route::RaftRouteRequestHandler,
 };
@@ -20,15 +22,21 @@ use super::{
 use crate::grpc::handler::raft_append::RaftAppendRequestHandler;
 use crate::grpc::handler::raft_snapshot::RaftSnapshotRequestHandler;
 use crate::grpc::handler::raft_vote::RaftVoteRequestHandler;
+use crate::user::permission::{
+    PermissionAction, PermissionRequest, PermissionResource, PermissionScopeMode,
+};
+use crate::user::role::get_user_permission;
 use async_trait::async_trait;
 
 pub mod config_change_batch_listen;
+pub mod config_fuzzy_watch;
 pub mod config_publish;
 pub mod config_query;
 pub mod config_remove;
 
 pub mod converter;
 pub mod naming_batch_instance;
+pub mod naming_fuzzy_watch;
 pub mod naming_instance;
 pub mod naming_route;
 pub mod naming_service_list;
@@ -53,12 +61,14 @@ pub(crate) const CONFIG_QUERY_REQUEST: &str = "ConfigQueryRequest";
 pub(crate) const CONFIG_PUBLISH_REQUEST: &str = "ConfigPublishRequest";
 pub(crate) const CONFIG_REMOVE_REQUEST: &str = "ConfigRemoveRequest";
 pub(crate) const CONFIG_BATCH_LISTEN_REQUEST: &str = "ConfigBatchListenRequest";
+pub(crate) const CONFIG_FUZZY_WATCH_REQUEST: &str = "ConfigFuzzyWatchRequest";
 
 pub(crate) const INSTANCE_REQUEST: &str = "InstanceRequest";
 pub(crate) const BATCH_INSTANCE_REQUEST: &str = "BatchInstanceRequest";
 pub(crate) const SUBSCRIBE_SERVICE_REQUEST: &str = "SubscribeServiceRequest";
 pub(crate) const SERVICE_QUERY_REQUEST: &str = "ServiceQueryRequest";
 pub(crate) const SERVICE_LIST_REQUEST: &str = "ServiceListRequest";
+pub(crate) const NAMING_FUZZY_WATCH_REQUEST: &str = "NamingFuzzyWatchRequest";
 
 pub struct InvokerHandler {
     app: Arc<AppShareData>,
@@ -96,6 +106,11 @@ impl InvokerHandler {
         None
     }
 
+    /// 是否为已注册的请求类型，用于限制监控指标的标签取值
+    pub fn is_known_request(&self, t: &str) -> bool {
+        SERVER_CHECK_REQUEST.eq(t) || self.match_handler(t).is_some()
+    }
+
     pub fn ignore_active_err(&self, t: &str) -> bool {
         SERVER_CHECK_REQUEST.eq(t)
             || RAFT_APPEND_REQUEST.eq(t)
@@ -163,6 +178,10 @@ impl InvokerHandler {
             CONFIG_BATCH_LISTEN_REQUEST,
             Box::new(ConfigChangeBatchListenRequestHandler::new(app_data.clone())),
         );
+        self.add_handler(
+            CONFIG_FUZZY_WATCH_REQUEST,
+            Box::new(ConfigFuzzyWatchRequestHandler::new(app_data.clone())),
+        );
     }
 
     pub fn add_naming_handler(&mut self, app_data: &Arc<AppShareData>) {
@@ -186,6 +205,10 @@ impl InvokerHandler {
             SERVICE_LIST_REQUEST,
             Box::new(ServiceListRequestHandler::new(app_data.clone())),
         );
+        self.add_handler(
+            NAMING_FUZZY_WATCH_REQUEST,
+            Box::new(NamingFuzzyWatchRequestHandler::new(app_data.clone())),
+        );
     }
 }
 
@@ -239,6 +262,25 @@ impl PayloadHandler for InvokerHandler {
                     "request cluster token is invalid".to_string(),
                 ));
             }
+            if self.app.sys_config.openapi_enable_auth {
+                if let Some(session) = &request_meta.token_session {
+                    let permission_reqs = build_permission_requests(url, &request_payload);
+                    if !permission_reqs.is_empty() {
+                        let permission = get_user_permission(
+                            &self.app.role_manager,
+                            session.roles.clone(),
+                            session.groups.clone(),
+                        )
+                        .await?;
+                        if !permission_reqs
+                            .iter()
+                            .all(|e| permission.check_with_scopes(e, &session.scopes))
+                        {
+                            return Ok(HandlerResult::error(403u16, "no permission!".to_string()));
+                        }
+                    }
+                }
+            }
             //println!("InvokerHandler type:{}",url);
             if let Some(handler) = self.match_handler(url) {
                 return handler.handle(request_payload, request_meta).await;
@@ -253,6 +295,81 @@ impl PayloadHandler for InvokerHandler {
     }
 }
 
+///
+/// gRPC请求需要校验的资源权限，批量监听请求按每个监听的配置分别校验
+fn build_permission_requests(url: &str, payload: &Payload) -> Vec<PermissionRequest> {
+    use PermissionAction::*;
+    use PermissionResource::*;
+    use PermissionScopeMode::{Item, List};
+    let body: serde_json::Value = payload
+        .body
+        .as_ref()
+        .and_then(|v| serde_json::from_slice(&v.value).ok())
+        .unwrap_or_default();
+    let (resource, action, mode) = match url {
+        CONFIG_QUERY_REQUEST | CONFIG_BATCH_LISTEN_REQUEST => (Config, Read, Item),
+        CONFIG_FUZZY_WATCH_REQUEST => (Config, Read, Item),
+        CONFIG_PUBLISH_REQUEST => (Config, Write, Item),
+        CONFIG_REMOVE_REQUEST => (Config, Delete, Item),
+        INSTANCE_REQUEST | BATCH_INSTANCE_REQUEST => {
+            if body.get("type").and_then(|v| v.as_str()) == Some("deregisterInstance") {
+                (Service, Delete, Item)
+            } else {
+                (Service, Write, Item)
+            }
+        }
+        SUBSCRIBE_SERVICE_REQUEST | SERVICE_QUERY_REQUEST => (Service, Read, Item),
+        SERVICE_LIST_REQUEST => (Service, Read, List),
+        NAMING_FUZZY_WATCH_REQUEST => (Service, Read, Item),
+        _ => return vec![],
+    };
+    if url == CONFIG_FUZZY_WATCH_REQUEST || url == NAMING_FUZZY_WATCH_REQUEST {
+        return vec![build_fuzzy_watch_permission_request(
+            resource, action, mode, &body,
+        )];
+    }
+    let items: Vec<&serde_json::Value> = if url == CONFIG_BATCH_LISTEN_REQUEST {
+        body.get("configListenContexts")
+            .and_then(|v| v.as_array())
+            .map(|v| v.iter().collect())
+            .unwrap_or_default()
+    } else {
+        vec![&body]
+    };
+    items
+        .into_iter()
+        .map(|item| {
+            let mut req = PermissionRequest::new(resource, action, mode);
+            if let Some(map) = item.as_object() {
+                req.set_json_params(map);
+            }
+            req
+        })
+        .collect()
+}
+
+///
+/// 模糊监听按监听模式校验权限，模式中的通配符需被授权范围覆盖
+fn build_fuzzy_watch_permission_request(
+    resource: PermissionResource,
+    action: PermissionAction,
+    mode: PermissionScopeMode,
+    body: &serde_json::Value,
+) -> PermissionRequest {
+    let mut req = PermissionRequest::new(resource, action, mode);
+    req.wildcard_pattern = true;
+    let pattern = body
+        .get("groupKeyPattern")
+        .and_then(|v| v.as_str())
+        .and_then(|v| FuzzyWatchPattern::parse(Arc::new(v.to_owned())));
+    if let Some(pattern) = pattern {
+        req.set_param("namespace", &pattern.namespace);
+        req.set_param("group", &pattern.group);
+        req.set_param("dataId", &pattern.resource);
+    }
+    req
+}
+
 #[async_trait]
 impl PayloadHandler for HealthCheckRequestHandler {
     fn get_log_args(
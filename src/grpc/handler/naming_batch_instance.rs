@@ -3,6 +3,7 @@
 use std::sync::Arc;
 
 use crate::grpc::HandlerResult;
+use crate::namespace::model::{NamespaceQuotaError, NAMESPACE_QUOTA_EXCEEDED_CODE};
 use crate::{
     common::appdata::AppShareData,
     grpc::{
@@ -124,21 +125,27 @@ impl PayloadHandler for BatchInstanceRequestHandler {
             message: Some("".to_string()),
             ..Default::default()
         };
-        for instance in instances {
-            let cmd = if is_de_register {
-                NamingCmd::Delete(instance)
-            } else {
-                let update_tag = InstanceUpdateTag {
-                    weight: instance.weight != 1.0f32,
-                    metadata: true,
-                    enabled: !instance.enabled,
-                    ephemeral: false,
-                    from_update: false,
-                };
-                NamingCmd::Update(instance, Some(update_tag))
-            };
+        let cmds = if is_de_register {
+            instances.into_iter().map(NamingCmd::Delete).collect()
+        } else {
+            let list = instances
+                .into_iter()
+                .map(|instance| {
+                    let update_tag = InstanceUpdateTag {
+                        weight: instance.weight != 1.0f32,
+                        metadata: true,
+                        enabled: !instance.enabled,
+                        ephemeral: false,
+                        from_update: false,
+                    };
+                    (instance, Some(update_tag))
+                })
+                .collect();
+            vec![NamingCmd::RegisterBatch(list)]
+        };
+        for cmd in cmds {
             match self.app_data.naming_addr.send(cmd).await {
-                Ok(_res) => {
+                Ok(Ok(_res)) => {
                     //let res:ConfigResult = res.unwrap();
                     response.result_code = SUCCESS_CODE;
                     if is_de_register {
@@ -147,6 +154,19 @@ impl PayloadHandler for BatchInstanceRequestHandler {
                         response.r#type = Some(REGISTER_INSTANCE.to_string());
                     }
                 }
+                Ok(Err(err)) => {
+                    response.result_code = ERROR_CODE;
+                    response.error_code = if err.is::<NamespaceQuotaError>() {
+                        NAMESPACE_QUOTA_EXCEEDED_CODE
+                    } else {
+                        500u16
+                    };
+                    response.message = Some(err.to_string());
+                    return Ok(HandlerResult::success(PayloadUtils::build_payload(
+                        "ErrorResponse",
+                        serde_json::to_string(&response)?,
+                    )));
+                }
                 Err(err) => {
                     response.result_code = ERROR_CODE;
                     response.error_code = 500u16;
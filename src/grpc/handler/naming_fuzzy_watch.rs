@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use crate::common::fuzzy_watch::{FuzzyWatchParam, WATCH_TYPE_CANCEL_WATCH};
+use crate::grpc::HandlerResult;
+use crate::{
+    common::appdata::AppShareData,
+    grpc::{
+        api_model::{NamingFuzzyWatchRequest, NamingFuzzyWatchResponse, ERROR_CODE, SUCCESS_CODE},
+        PayloadHandler, PayloadUtils,
+    },
+    naming::core::NamingCmd,
+};
+use async_trait::async_trait;
+
+pub struct NamingFuzzyWatchRequestHandler {
+    app_data: Arc<AppShareData>,
+}
+
+impl NamingFuzzyWatchRequestHandler {
+    pub fn new(app_data: Arc<AppShareData>) -> Self {
+        Self { app_data }
+    }
+}
+
+#[async_trait]
+impl PayloadHandler for NamingFuzzyWatchRequestHandler {
+    async fn handle(
+        &self,
+        request_payload: crate::grpc::nacos_proto::Payload,
+        request_meta: crate::grpc::RequestMeta,
+    ) -> anyhow::Result<HandlerResult> {
+        let body_vec = request_payload.body.unwrap_or_default().value;
+        let request: NamingFuzzyWatchRequest = serde_json::from_slice(&body_vec)?;
+        let param = FuzzyWatchParam {
+            pattern: Arc::new(request.group_key_pattern.unwrap_or_default()),
+            received_keys: request.received_group_keys.unwrap_or_default(),
+            initializing: request.initializing,
+            watch: request.watch_type.as_deref() != Some(WATCH_TYPE_CANCEL_WATCH),
+        };
+        let cmd = NamingCmd::FuzzyWatch(Box::new(param), request_meta.connection_id);
+        let mut response = NamingFuzzyWatchResponse {
+            request_id: request.request_id,
+            message: Some("".to_string()),
+            ..Default::default()
+        };
+        let res = match self.app_data.naming_addr.send(cmd).await {
+            Ok(res) => res.map(|_| ()),
+            Err(err) => Err(err.into()),
+        };
+        match res {
+            Ok(_) => {
+                response.result_code = SUCCESS_CODE;
+                Ok(HandlerResult::success(PayloadUtils::build_payload(
+                    "NamingFuzzyWatchResponse",
+                    serde_json::to_string(&response)?,
+                )))
+            }
+            Err(err) => {
+                response.result_code = ERROR_CODE;
+                response.error_code = ERROR_CODE;
+                response.message = Some(err.to_string());
+                Ok(HandlerResult::success(PayloadUtils::build_payload(
+                    "ErrorResponse",
+                    serde_json::to_string(&response)?,
+                )))
+            }
+        }
+    }
+}
@@ -6,6 +6,7 @@ use std::sync::{
 };
 
 use crate::grpc::HandlerResult;
+use crate::namespace::model::{NamespaceQuotaError, NAMESPACE_QUOTA_EXCEEDED_CODE};
 use crate::{
     common::appdata::AppShareData,
     grpc::{
@@ -129,7 +130,7 @@ impl PayloadHandler for InstanceRequestHandler {
             ..Default::default()
         };
         match self.app_data.naming_addr.send(cmd).await {
-            Ok(_res) => {
+            Ok(Ok(_res)) => {
                 //let res:ConfigResult = res.unwrap();
                 response.result_code = SUCCESS_CODE;
                 if is_de_register {
@@ -138,6 +139,19 @@ impl PayloadHandler for InstanceRequestHandler {
                     response.r#type = Some(REGISTER_INSTANCE.to_string());
                 }
             }
+            Ok(Err(err)) => {
+                response.result_code = ERROR_CODE;
+                response.error_code = if err.is::<NamespaceQuotaError>() {
+                    NAMESPACE_QUOTA_EXCEEDED_CODE
+                } else {
+                    500u16
+                };
+                response.message = Some(err.to_string());
+                return Ok(HandlerResult::success(PayloadUtils::build_payload(
+                    "ErrorResponse",
+                    serde_json::to_string(&response)?,
+                )));
+            }
             Err(err) => {
                 response.result_code = ERROR_CODE;
                 response.error_code = 500u16;
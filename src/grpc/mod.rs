@@ -7,6 +7,7 @@ use std::{collections::HashMap, sync::Arc};
 pub mod api_model;
 pub mod bistream_conn;
 pub mod bistream_manage;
+pub mod conn_manage;
 pub mod handler;
 pub mod metrics;
 pub mod nacos_proto;
@@ -16,7 +17,7 @@ pub mod server;
 pub struct RequestMeta {
     pub connection_id: Arc<String>,
     pub client_ip: String,
-    pub labels: HashMap<String, String>,
+    pub labels: Arc<HashMap<String, String>>,
     pub token_session: Option<Arc<TokenSession>>,
     pub cluster_token_is_valid: bool,
     pub client_version: Arc<ClientVersion>,
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 use std::time::SystemTime;
 
+use crate::audit::grpc::build_audit_context;
 use crate::cache::actor_model::CacheManagerRaftResult;
 use crate::common::appdata::AppShareData;
 use crate::common::constant::{
@@ -12,13 +13,16 @@ use super::bistream_conn::BiStreamConn;
 use super::bistream_manage::BiStreamManageCmd;
 use super::handler::{InvokerHandler, CLUSTER_TOKEN};
 use super::nacos_proto::bi_request_stream_server::BiRequestStream;
+use crate::grpc::api_model::BaseResponse;
 use crate::grpc::bistream_manage::BiStreamManageResult;
 use crate::grpc::nacos_proto::{request_server, Payload};
 use crate::grpc::{PayloadHandler, PayloadUtils, RequestMeta};
 use crate::metrics::metrics_key::MetricsKey;
-use crate::metrics::model::{MetricsItem, MetricsRecord, MetricsRequest};
+use crate::metrics::model::{LabeledMetricsItem, MetricsItem, MetricsRecord, MetricsRequest};
 use crate::raft::cache::model::{CacheKey, CacheType};
 use crate::raft::cluster::model::{RouterRequest, RouterResponse};
+use crate::user::access_key::{get_access_key_session, is_access_key_token};
+use crate::user::jwt_auth::is_jwt_token;
 
 pub struct RequestServerImpl {
     app: Arc<AppShareData>,
@@ -38,14 +42,33 @@ impl RequestServerImpl {
             if let Some(v) = meta.headers.get(ACCESS_TOKEN_HEADER) {
                 Arc::new(v.to_owned())
             } else if let Some(v) = meta.headers.get(AUTHORIZATION_HEADER) {
-                Arc::new(v.to_owned())
+                let token = v
+                    .split_once(char::is_whitespace)
+                    .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("Bearer"))
+                    .map_or(v.as_str(), |(_, token)| token.trim());
+                Arc::new(token.to_owned())
             } else {
                 EMPTY_ARC_STRING.clone()
             }
         } else {
             EMPTY_ARC_STRING.clone()
         };
-        if self.app.sys_config.openapi_enable_auth && !token.is_empty() {
+        if self.app.sys_config.openapi_enable_auth && is_access_key_token(&token) {
+            if let Ok(Some(session)) =
+                get_access_key_session(&self.app.access_key_manager, token).await
+            {
+                request_meta.token_session = Some(session);
+            }
+        } else if self.app.sys_config.openapi_enable_auth
+            && is_jwt_token(&self.app.jwt_auth_manager, &token)
+        {
+            if let Some(manager) = &self.app.jwt_auth_manager {
+                match manager.get_session(&token).await {
+                    Ok(session) => request_meta.token_session = Some(session),
+                    Err(err) => log::warn!("grpc jwt auth failed,{}", err),
+                }
+            }
+        } else if self.app.sys_config.openapi_enable_auth && !token.is_empty() {
             if let Ok(Some(session)) = get_user_session(
                 &self.app,
                 CacheKey::new(CacheType::ApiTokenSession, token.clone()),
@@ -67,7 +90,16 @@ impl RequestServerImpl {
         Ok(())
     }
 
-    fn record_req_metrics(&self, duration: f64, _success: bool) {
+    fn get_error_code(payload: &Payload) -> u16 {
+        payload
+            .body
+            .as_ref()
+            .and_then(|body| serde_json::from_slice::<BaseResponse>(&body.value).ok())
+            .map(|v| v.error_code)
+            .unwrap_or(500)
+    }
+
+    fn record_req_metrics(&self, duration: f64, _success: bool, request_type: &str, code: u16) {
         self.app
             .metrics_manager
             .do_send(MetricsRequest::BatchRecord(vec![
@@ -80,6 +112,26 @@ impl RequestServerImpl {
                     MetricsRecord::CounterInc(1),
                 ),
             ]));
+        //未注册的请求类型统一归为unknown，避免客户端传入任意类型导致指标序列膨胀
+        let request_type = if self.invoker.is_known_request(request_type) {
+            request_type
+        } else {
+            "unknown"
+        };
+        self.app
+            .metrics_manager
+            .do_send(MetricsRequest::LabeledBatchRecord(vec![
+                LabeledMetricsItem::new(
+                    MetricsKey::GrpcRequestTypeHandleRtHistogram,
+                    vec![request_type.to_owned()],
+                    MetricsRecord::HistogramRecord(duration as f32 * 1000f32),
+                ),
+                LabeledMetricsItem::new(
+                    MetricsKey::GrpcRequestTypeTotalCount,
+                    vec![request_type.to_owned(), code.to_string()],
+                    MetricsRecord::CounterInc(1),
+                ),
+            ]));
     }
 }
 
@@ -102,6 +154,7 @@ impl request_server::Request for RequestServerImpl {
             ..Default::default()
         };
         let request_type = PayloadUtils::get_payload_type(&payload).unwrap();
+        let metrics_request_type = request_type.to_owned();
         let request_log_info = format!(
             "|grpc|client_request|{}|{}",
             &request_meta.connection_id, &request_type
@@ -120,8 +173,11 @@ impl request_server::Request for RequestServerImpl {
                 let result: anyhow::Result<BiStreamManageResult> = result;
                 match result {
                     Ok(conn_result) => {
-                        if let BiStreamManageResult::ClientInfo(client_version) = conn_result {
+                        if let BiStreamManageResult::ClientInfo(client_version, labels) =
+                            conn_result
+                        {
                             request_meta.client_version = client_version;
+                            request_meta.labels = labels;
                         }
                     }
                     Err(err) => {
@@ -134,7 +190,7 @@ impl request_server::Request for RequestServerImpl {
                                 .unwrap_or_default()
                                 .as_secs_f64();
                             log::error!("{}|err|{}|{}", request_log_info, duration, &err_msg);
-                            self.record_req_metrics(duration, false);
+                            self.record_req_metrics(duration, false, &metrics_request_type, 301);
                             return Ok(tonic::Response::new(PayloadUtils::build_error_payload(
                                 301, err_msg,
                             )));
@@ -152,7 +208,7 @@ impl request_server::Request for RequestServerImpl {
                         .unwrap_or_default()
                         .as_secs_f64();
                     log::error!("{}|err|{}|{}", request_log_info, duration, &err_msg);
-                    self.record_req_metrics(duration, false);
+                    self.record_req_metrics(duration, false, &metrics_request_type, 301);
                     return Ok(tonic::Response::new(PayloadUtils::build_error_payload(
                         301, err_msg,
                     )));
@@ -169,6 +225,24 @@ impl request_server::Request for RequestServerImpl {
         self.fill_token_session(&payload, &mut request_meta)
             .await
             .ok();
+        let mut audit = if self.app.sys_config.audit_log_enable {
+            build_audit_context(
+                request_type,
+                &payload,
+                self.app.sys_config.audit_log_grpc_instance_enable,
+            )
+        } else {
+            None
+        };
+        if let Some(audit) = audit.as_mut() {
+            let actor = request_meta
+                .token_session
+                .as_ref()
+                .map(|session| session.username.as_str())
+                .unwrap_or_default();
+            audit.set_actor(actor, request_meta.client_ip.clone());
+            audit.fill_before_digest(&self.app).await;
+        }
         let args = self.invoker.get_log_args(&payload, &request_meta);
         let handle_result = self.invoker.handle(payload, request_meta).await;
         let duration = SystemTime::now()
@@ -187,18 +261,30 @@ impl request_server::Request for RequestServerImpl {
                         ""
                     };
                     log::error!("{}|err|{}|{}|{}", request_log_info, duration, &args, msg);
-                    self.record_req_metrics(duration, false);
+                    let code = Self::get_error_code(&res.payload);
+                    self.record_req_metrics(duration, false, &metrics_request_type, code);
                 } else if duration < 1f64 {
                     if args.enable_log() {
                         log::info!("{}|ok|{}|{}", request_log_info, duration, &args);
                     }
-                    self.record_req_metrics(duration, true);
+                    self.record_req_metrics(duration, true, &metrics_request_type, 200);
                 } else {
                     if args.enable_log() {
                         //slow request handle
                         log::warn!("{}|ok|{}|{}", request_log_info, duration, &args);
                     }
-                    self.record_req_metrics(duration, true);
+                    self.record_req_metrics(duration, true, &metrics_request_type, 200);
+                }
+                if let Some(audit) = audit {
+                    let code = if res.success {
+                        200
+                    } else {
+                        Self::get_error_code(&res.payload)
+                    };
+                    let msg = res.message.as_deref().unwrap_or_default();
+                    audit
+                        .finish(&self.app, res.success, code.to_string(), msg)
+                        .await;
                 }
                 Ok(tonic::Response::new(res.payload))
             }
@@ -206,7 +292,12 @@ impl request_server::Request for RequestServerImpl {
                 //Err(tonic::Status::aborted(e.to_string()))
                 //log::error!("request_server handler error:{:?}",e);
                 log::error!("{}|err|{}|{}|{}", request_log_info, duration, &args, e);
-                self.record_req_metrics(duration, false);
+                self.record_req_metrics(duration, false, &metrics_request_type, 500);
+                if let Some(audit) = audit {
+                    audit
+                        .finish(&self.app, false, "500".to_owned(), &e.to_string())
+                        .await;
+                }
                 Ok(tonic::Response::new(PayloadUtils::build_error_payload(
                     500u16,
                     e.to_string(),
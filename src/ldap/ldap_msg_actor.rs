@@ -86,6 +86,7 @@ impl LdapMsgActor {
                     let mut entry = SearchEntry::construct(entry);
                     let mut role = ldap_config.ldap_user_default_role.clone();
                     let mut namespace_privilege = None;
+                    let mut custom_roles = vec![];
                     let groups = entry.attrs.remove("memberOf").unwrap_or_default();
                     let groups = groups
                         .iter()
@@ -122,11 +123,13 @@ impl LdapMsgActor {
                                 })
                                 .await
                         {
+                            custom_roles = user_dto.get_custom_roles();
                             namespace_privilege = user_dto.namespace_privilege;
                         }
                     }
-                    let meta =
+                    let mut meta =
                         LdapUserMeta::new(bind_req.user_name, groups, role, namespace_privilege);
+                    meta.custom_roles = custom_roles;
                     Ok(LdapMsgResult::UserMeta(meta))
                 } else {
                     let meta = Self::get_default_user_meta(bind_req.user_name, ldap_config);
@@ -33,6 +33,8 @@ pub struct LdapUserMeta {
     pub groups: Vec<String>,
     pub role: Arc<String>,
     pub namespace_privilege: Option<PrivilegeGroup<Arc<String>>>,
+    /// 用户已分配的自定义角色
+    pub custom_roles: Vec<Arc<String>>,
 }
 
 impl LdapUserMeta {
@@ -47,6 +49,7 @@ impl LdapUserMeta {
             groups,
             role,
             namespace_privilege,
+            custom_roles: vec![],
         }
     }
 }
@@ -1,3 +1,5 @@
+pub mod audit;
+pub mod backup;
 pub mod common;
 pub mod config;
 pub mod console;
@@ -20,6 +22,7 @@ pub mod ldap;
 pub mod mcp;
 pub mod oauth2;
 pub mod sequence;
+pub mod webhook;
 
 pub use inner_mem_cache::TimeoutSet;
 
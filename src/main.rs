@@ -6,6 +6,8 @@ use actix::Actor;
 use actix_web::{web::Data, App};
 use async_raft_ext::raft::ClientWriteRequest;
 use async_raft_ext::{Config, Raft, RaftStorage};
+use rnacos::audit::middle::AuditLog;
+use rnacos::common::tls::{build_server_config, grpc_tls_incoming};
 use rnacos::common::{get_app_version, AppSysConfig};
 use rnacos::config::core::{ConfigActor, ConfigCmd};
 use rnacos::console::middle::login_middle::CheckLogin;
@@ -90,22 +92,40 @@ async fn main() -> Result<(), Box<dyn Error>> {
     invoker.add_naming_handler(&app_data);
     invoker.add_raft_handler(&app_data);
 
+    let tls_server_config = if sys_config.tls_http_enable
+        || sys_config.tls_console_enable
+        || sys_config.tls_grpc_enable
+    {
+        Some(build_server_config(&sys_config)?)
+    } else {
+        None
+    };
+    let grpc_tls_config = tls_server_config
+        .clone()
+        .filter(|_| sys_config.tls_grpc_enable);
     let grpc_app_data = app_data.clone();
 
     tokio::spawn(async move {
         let addr = grpc_addr.parse().unwrap();
         let request_server = RequestServerImpl::new(grpc_app_data.clone(), invoker);
         let bi_request_stream_server = BiRequestStreamServerImpl::new(grpc_app_data.clone());
-        Server::builder()
+        let router = Server::builder()
             .add_service(RequestServer::new(request_server))
-            .add_service(BiRequestStreamServer::new(bi_request_stream_server))
-            .serve(addr)
-            .await
-            .unwrap();
+            .add_service(BiRequestStreamServer::new(bi_request_stream_server));
+        if let Some(tls_config) = grpc_tls_config {
+            log::info!("grpc server tls enabled");
+            let incoming = grpc_tls_incoming(addr, tls_config).await.unwrap();
+            router.serve_with_incoming(incoming).await.unwrap();
+        } else {
+            router.serve(addr).await.unwrap();
+        }
     });
 
     if sys_config.http_console_port > 0 {
         let app_console_data = app_data.clone();
+        let console_tls_config = tls_server_config
+            .clone()
+            .filter(|_| sys_config.tls_console_enable);
 
         std::thread::spawn(move || {
             actix_rt::System::with_tokio_rt(|| {
@@ -114,7 +134,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
                     .build()
                     .unwrap()
             })
-            .block_on(run_console_web(app_console_data));
+            .block_on(run_console_web(app_console_data, console_tls_config));
         });
     }
 
@@ -130,6 +150,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
             .app_data(Data::new(config_addr))
             .app_data(Data::new(naming_addr))
             .app_data(Data::new(bistream_manage_http_addr))
+            .wrap(AuditLog::new(source_app_data.clone()))
             .wrap(ApiCheckAuth::new(source_app_data))
             .wrap(middleware::Logger::default())
             .configure(app_config(app_config_shard))
@@ -139,7 +160,16 @@ async fn main() -> Result<(), Box<dyn Error>> {
     }
     // 这里不使用log:info避免日志等级高于info时不打印
     println!("rnacos started");
-    server.bind(http_addr)?.run().await?;
+    match tls_server_config.filter(|_| sys_config.tls_http_enable) {
+        Some(tls_config) => {
+            log::info!("http server tls enabled");
+            server
+                .bind_rustls_0_23(http_addr, tls_config)?
+                .run()
+                .await?;
+        }
+        None => server.bind(http_addr)?.run().await?,
+    }
     Ok(())
 }
 
@@ -179,11 +209,14 @@ async fn run_subcommand(commands: Commands) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-async fn run_console_web(source_app_data: Arc<AppShareData>) {
+async fn run_console_web(
+    source_app_data: Arc<AppShareData>,
+    tls_config: Option<rustls::ServerConfig>,
+) {
     let http_console_addr = source_app_data.sys_config.get_http_console_addr();
     log::info!("new console server http addr:{}", &http_console_addr);
     let app_data = Data::new(source_app_data.clone());
-    HttpServer::new(move || {
+    let server = HttpServer::new(move || {
         let source_app_data = source_app_data.clone();
         let config_addr = app_data.config_addr.clone();
         let naming_addr = app_data.naming_addr.clone();
@@ -194,15 +227,19 @@ async fn run_console_web(source_app_data: Arc<AppShareData>) {
             .app_data(Data::new(config_addr))
             .app_data(Data::new(naming_addr))
             .app_data(Data::new(bistream_manage_http_addr))
+            .wrap(AuditLog::new(source_app_data.clone()))
             .wrap(CheckLogin::new(source_app_data))
             .wrap(middleware::Logger::default())
             .wrap(middleware::Compress::default())
             .configure(console_config)
     })
-    .workers(2)
-    .bind(http_console_addr)
-    .unwrap()
-    .run()
-    .await
-    .ok();
+    .workers(2);
+    let server = match tls_config {
+        Some(tls_config) => {
+            log::info!("console server tls enabled");
+            server.bind_rustls_0_23(http_console_addr, tls_config)
+        }
+        None => server.bind(http_console_addr),
+    };
+    server.unwrap().run().await.ok();
 }
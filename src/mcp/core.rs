@@ -276,6 +276,7 @@ impl McpManager {
         for server in self.server_map.values() {
             if query_param.match_namespace(&server.namespace)
                 && query_param.match_name(&server.name)
+                && query_param.match_scope(&server.namespace, &server.name)
             {
                 if index >= query_param.offset && index < end_index {
                     rlist.push(McpServerDto::new_from(server));
@@ -367,6 +368,15 @@ impl McpManager {
                 return false;
             }
         }
+        if let Some(ref scope_filter) = query_param.scope_filter {
+            if !scope_filter.is_match(
+                &tool_spec.key.namespace,
+                &tool_spec.key.group,
+                &tool_spec.key.tool_name,
+            ) {
+                return false;
+            }
+        }
         true
     }
 
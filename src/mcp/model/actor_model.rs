@@ -2,6 +2,7 @@ use crate::mcp::model::mcp::{
     McpQueryParam, McpServer, McpServerDto, McpServerParam, McpServerValue,
 };
 use crate::mcp::model::tools::{ToolFunctionValue, ToolKey, ToolSpec, ToolSpecParam};
+use crate::user::permission::PermissionScopeFilter;
 use actix::Message;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -63,6 +64,9 @@ pub struct McpToolSpecQueryParam {
     pub namespace_id: Option<String>,
     pub group_filter: Option<String>,
     pub tool_name_filter: Option<String>,
+    /// 自定义角色授权的数据范围，为None时不限制
+    #[serde(skip)]
+    pub scope_filter: Option<Arc<PermissionScopeFilter>>,
 }
 
 /// MCP 工具规范 DTO
@@ -1,6 +1,7 @@
 use crate::common::pb::data_object::{McpServerDo, McpServerValueDo};
 use crate::mcp::model::tools::{McpSimpleTool, McpTool, ToolKey, ToolSpec};
 use crate::mcp::utils::ToolSpecUtils;
+use crate::user::permission::PermissionScopeFilter;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::collections::{BTreeMap, HashMap};
@@ -328,6 +329,9 @@ pub struct McpQueryParam {
     pub limit: usize,
     pub namespace_id: Option<Arc<String>>,
     pub name_filter: Option<Arc<String>>,
+    /// 自定义角色授权的数据范围，为None时不限制
+    #[serde(skip)]
+    pub scope_filter: Option<Arc<PermissionScopeFilter>>,
 }
 
 impl McpQueryParam {
@@ -339,6 +343,12 @@ impl McpQueryParam {
         }
     }
 
+    pub fn match_scope(&self, namespace: &str, name: &str) -> bool {
+        self.scope_filter
+            .as_ref()
+            .is_none_or(|v| v.is_match(namespace, "", name))
+    }
+
     pub fn match_name(&self, name: &Arc<String>) -> bool {
         if let Some(ref filter) = self.name_filter {
             name.contains(filter.as_str())
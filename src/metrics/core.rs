@@ -4,9 +4,11 @@ use crate::grpc::bistream_manage::BiStreamManage;
 use crate::metrics::counter::CounterManager;
 use crate::metrics::gauge::GaugeManager;
 use crate::metrics::histogram::HistogramManager;
+use crate::metrics::labeled::LabeledMetricsManager;
 use crate::metrics::metrics_key::MetricsKey;
 use crate::metrics::model::{
-    MetricsItem, MetricsQuery, MetricsRecord, MetricsRequest, MetricsResponse,
+    LabeledMetricsItem, LabeledMetricsQuery, MetricsItem, MetricsQuery, MetricsRecord,
+    MetricsRequest, MetricsResponse,
 };
 use crate::metrics::summary::SummaryManager;
 use crate::metrics::timeline::core::MetricsTimelineManager;
@@ -28,6 +30,7 @@ pub struct MetricsManager {
     histogram_manager: HistogramManager,
     summary_manager: SummaryManager,
     summary_key_config: Vec<(MetricsKey, MetricsKey)>,
+    labeled_manager: LabeledMetricsManager,
     naming_actor: Option<Addr<NamingActor>>,
     config_actor: Option<Addr<ConfigActor>>,
     bi_stream_manage: Option<Addr<BiStreamManage>>,
@@ -56,6 +59,7 @@ impl MetricsManager {
             histogram_manager: Default::default(),
             summary_manager: Default::default(),
             summary_key_config: Default::default(),
+            labeled_manager: LabeledMetricsManager::new(app_sys_config.metrics_label_max_series),
             naming_actor: None,
             config_actor: None,
             bi_stream_manage: None,
@@ -98,6 +102,20 @@ impl MetricsManager {
             &[0.5f32, 0.6f32, 0.7f32, 0.8f32, 0.9f32, 0.95f32, 1f32],
         );
 
+        // 单位毫秒ms
+        self.labeled_manager.init_histogram(
+            MetricsKey::GrpcRequestTypeHandleRtHistogram,
+            &[
+                0.25f32, 0.5f32, 1f32, 3f32, 5f32, 10f32, 25f32, 50f32, 100f32, 300f32, 500f32,
+            ],
+        );
+        self.labeled_manager.init_histogram(
+            MetricsKey::HttpRequestRouteHandleRtHistogram,
+            &[
+                0.25f32, 0.5f32, 1f32, 3f32, 5f32, 10f32, 25f32, 50f32, 100f32, 300f32, 500f32,
+            ],
+        );
+
         //summary from histogram
         self.summary_key_config.push((
             MetricsKey::HttpRequestHandleRtSummary,
@@ -122,28 +140,40 @@ impl MetricsManager {
         naming_actor: Option<Addr<NamingActor>>,
         config_actor: Option<Addr<ConfigActor>>,
         bi_stream_manage: Option<Addr<BiStreamManage>>,
-    ) -> anyhow::Result<Vec<MetricsItem>> {
+    ) -> anyhow::Result<(Vec<MetricsItem>, Vec<LabeledMetricsItem>)> {
         let mut list = vec![];
+        let mut labeled_list = vec![];
         if let Some(naming_actor) = naming_actor {
             let mut t = naming_actor.send(MetricsQuery).await??;
             list.append(&mut t);
+            let mut t = naming_actor.send(LabeledMetricsQuery).await??;
+            labeled_list.append(&mut t);
         }
         if let Some(config_actor) = config_actor {
             let mut t = config_actor.send(MetricsQuery).await??;
             list.append(&mut t);
+            let mut t = config_actor.send(LabeledMetricsQuery).await??;
+            labeled_list.append(&mut t);
         }
         if let Some(bi_stream_manage) = bi_stream_manage {
             let mut t = bi_stream_manage.send(MetricsQuery).await??;
             list.append(&mut t);
         }
-        Ok(list)
+        Ok((list, labeled_list))
     }
 
-    fn update_peek_metrics(&mut self, r: anyhow::Result<Vec<MetricsItem>>) {
-        if let Ok(list) = r {
+    fn update_peek_metrics(
+        &mut self,
+        r: anyhow::Result<(Vec<MetricsItem>, Vec<LabeledMetricsItem>)>,
+    ) {
+        if let Ok((list, labeled_list)) = r {
             for item in list {
                 self.update_item_record(item);
             }
+            self.labeled_manager.clear_gauges();
+            for item in labeled_list {
+                self.labeled_manager.record(item);
+            }
         }
     }
 
@@ -284,6 +314,7 @@ impl MetricsManager {
         self.histogram_manager.export(&mut bytes_mut)?;
         self.reset_summary();
         self.summary_manager.export(&mut bytes_mut)?;
+        self.labeled_manager.export(&mut bytes_mut)?;
         Ok(String::from_utf8(bytes_mut.to_vec())?)
     }
 }
@@ -339,6 +370,12 @@ impl Handler<MetricsRequest> for MetricsManager {
                 }
                 Ok(MetricsResponse::None)
             }
+            MetricsRequest::LabeledBatchRecord(items) => {
+                for item in items {
+                    self.labeled_manager.record(item);
+                }
+                Ok(MetricsResponse::None)
+            }
             MetricsRequest::Export => {
                 let v = self.export()?;
                 Ok(MetricsResponse::ExportInfo(v))
@@ -0,0 +1,257 @@
+use crate::metrics::metrics_key::MetricsKey;
+use crate::metrics::model::{
+    CounterValue, GaugeValue, HistogramValue, LabeledMetricsItem, MetricsRecord, MetricsType,
+};
+use bytes::BytesMut;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+/// 超过序列数上限后，新的标签组合统一归入该标签值
+pub const OVERFLOW_LABEL_VALUE: &str = "__other__";
+const MAX_LABEL_VALUE_LEN: usize = 128;
+
+type Series<V> = BTreeMap<Vec<String>, V>;
+
+///
+/// 带标签的指标
+/// 每个指标的标签组合数量受max_series限制，避免请求参数等外部输入导致序列数无限增长
+#[derive(Debug)]
+pub struct LabeledMetricsManager {
+    max_series: usize,
+    counter_map: HashMap<MetricsKey, Series<CounterValue>>,
+    gauge_map: HashMap<MetricsKey, Series<GaugeValue>>,
+    histogram_map: HashMap<MetricsKey, Series<HistogramValue>>,
+    histogram_bounds: HashMap<MetricsKey, Vec<f32>>,
+}
+
+impl LabeledMetricsManager {
+    pub fn new(max_series: usize) -> Self {
+        Self {
+            max_series: max_series.max(1),
+            counter_map: Default::default(),
+            gauge_map: Default::default(),
+            histogram_map: Default::default(),
+            histogram_bounds: Default::default(),
+        }
+    }
+
+    pub fn init_histogram(&mut self, key: MetricsKey, bounds: &[f32]) {
+        self.histogram_bounds.insert(key, bounds.to_vec());
+    }
+
+    fn limit_labels<V>(max_series: usize, series: &Series<V>, labels: Vec<String>) -> Vec<String> {
+        if series.len() < max_series || series.contains_key(&labels) {
+            labels
+        } else {
+            vec![OVERFLOW_LABEL_VALUE.to_owned(); labels.len()]
+        }
+    }
+
+    fn normalize_labels(key: &MetricsKey, mut labels: Vec<String>) -> Vec<String> {
+        labels.resize(key.get_label_names().len(), String::new());
+        for value in labels.iter_mut() {
+            if value.len() > MAX_LABEL_VALUE_LEN {
+                let mut end = MAX_LABEL_VALUE_LEN;
+                while !value.is_char_boundary(end) {
+                    end -= 1;
+                }
+                value.truncate(end);
+            }
+        }
+        labels
+    }
+
+    pub fn record(&mut self, item: LabeledMetricsItem) {
+        let key = item.metrics_type;
+        let labels = Self::normalize_labels(&key, item.labels);
+        match item.record {
+            MetricsRecord::CounterInc(v) => {
+                let series = self.counter_map.entry(key).or_default();
+                let labels = Self::limit_labels(self.max_series, series, labels);
+                series.entry(labels).or_default().increment(v);
+            }
+            MetricsRecord::Gauge(v) => {
+                let series = self.gauge_map.entry(key).or_default();
+                let labels = Self::limit_labels(self.max_series, series, labels);
+                series.entry(labels).or_default().set(v);
+            }
+            MetricsRecord::HistogramRecord(v) => {
+                if let Some(value) = self.get_histogram_mut(key, labels) {
+                    value.record(v);
+                }
+            }
+            MetricsRecord::HistogramRecords(batch_value) => {
+                if let Some(value) = self.get_histogram_mut(key, labels) {
+                    value.record_many(&batch_value);
+                }
+            }
+        }
+    }
+
+    fn get_histogram_mut(
+        &mut self,
+        key: MetricsKey,
+        labels: Vec<String>,
+    ) -> Option<&mut HistogramValue> {
+        let bounds = self.histogram_bounds.get(&key)?;
+        let series = self.histogram_map.entry(key).or_default();
+        let labels = Self::limit_labels(self.max_series, series, labels);
+        if !series.contains_key(&labels) {
+            series.insert(labels.clone(), HistogramValue::new(bounds)?);
+        }
+        series.get_mut(&labels)
+    }
+
+    /// 按周期全量采集的gauge指标，采集前清空，避免已删除的命名空间一直保留
+    pub fn clear_gauges(&mut self) {
+        self.gauge_map.clear();
+    }
+
+    pub fn export(&self, bytes_mut: &mut BytesMut) -> anyhow::Result<()> {
+        for (key, series) in &self.counter_map {
+            write_header(bytes_mut, key, MetricsType::Counter)?;
+            for (labels, value) in series {
+                writeln!(
+                    bytes_mut,
+                    "{}{} {}",
+                    key.get_key(),
+                    fmt_labels(key, labels, None),
+                    value.0
+                )?;
+            }
+        }
+        for (key, series) in &self.gauge_map {
+            write_header(bytes_mut, key, MetricsType::Gauge)?;
+            for (labels, value) in series {
+                writeln!(
+                    bytes_mut,
+                    "{}{} {:.3}",
+                    key.get_key(),
+                    fmt_labels(key, labels, None),
+                    value.0
+                )?;
+            }
+        }
+        for (key, series) in &self.histogram_map {
+            write_header(bytes_mut, key, MetricsType::Histogram)?;
+            let key_name = key.get_key();
+            for (labels, value) in series {
+                for (bound, count) in value.buckets() {
+                    let le = bound.to_string();
+                    writeln!(
+                        bytes_mut,
+                        "{}_bucket{} {}",
+                        key_name,
+                        fmt_labels(key, labels, Some(&le)),
+                        count
+                    )?;
+                }
+                writeln!(
+                    bytes_mut,
+                    "{}_bucket{} {}",
+                    key_name,
+                    fmt_labels(key, labels, Some("+Inf")),
+                    value.count
+                )?;
+                let label_str = fmt_labels(key, labels, None);
+                writeln!(bytes_mut, "{}_sum{} {:.3}", key_name, &label_str, value.sum)?;
+                writeln!(
+                    bytes_mut,
+                    "{}_count{} {}",
+                    key_name, &label_str, value.count
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_header(
+    bytes_mut: &mut BytesMut,
+    key: &MetricsKey,
+    metrics_type: MetricsType,
+) -> std::fmt::Result {
+    let key_name = key.get_key();
+    writeln!(
+        bytes_mut,
+        "# HELP {} {}\n# TYPE {} {}",
+        key_name,
+        key.get_describe(),
+        key_name,
+        metrics_type.get_name()
+    )
+}
+
+fn fmt_labels(key: &MetricsKey, labels: &[String], le: Option<&str>) -> String {
+    let mut buf = String::from("{");
+    for (i, (name, value)) in key.get_label_names().iter().zip(labels).enumerate() {
+        if i > 0 {
+            buf.push(',');
+        }
+        buf.push_str(name);
+        buf.push_str("=\"");
+        escape_label_value(&mut buf, value);
+        buf.push('"');
+    }
+    if let Some(le) = le {
+        if buf.len() > 1 {
+            buf.push(',');
+        }
+        buf.push_str("le=\"");
+        buf.push_str(le);
+        buf.push('"');
+    }
+    buf.push('}');
+    buf
+}
+
+fn escape_label_value(buf: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '\\' => buf.push_str("\\\\"),
+            '"' => buf.push_str("\\\""),
+            '\n' => buf.push_str("\\n"),
+            _ => buf.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_series_and_export() {
+        let mut manager = LabeledMetricsManager::new(2);
+        manager.init_histogram(MetricsKey::GrpcRequestTypeHandleRtHistogram, &[1f32, 10f32]);
+        for request_type in ["A", "B", "C", "D"] {
+            manager.record(LabeledMetricsItem::new(
+                MetricsKey::GrpcRequestTypeTotalCount,
+                vec![request_type.to_owned(), "200".to_owned()],
+                MetricsRecord::CounterInc(1),
+            ));
+        }
+        manager.record(LabeledMetricsItem::new(
+            MetricsKey::GrpcRequestTypeHandleRtHistogram,
+            vec!["A\"\n".to_owned()],
+            MetricsRecord::HistogramRecord(5f32),
+        ));
+        let series = manager
+            .counter_map
+            .get(&MetricsKey::GrpcRequestTypeTotalCount)
+            .unwrap();
+        assert_eq!(series.len(), 3);
+        let overflow = vec![OVERFLOW_LABEL_VALUE.to_owned(); 2];
+        assert_eq!(series.get(&overflow).unwrap().0, 2);
+
+        let mut bytes_mut = BytesMut::new();
+        manager.export(&mut bytes_mut).unwrap();
+        let text = String::from_utf8(bytes_mut.to_vec()).unwrap();
+        assert!(text.contains("grpc_request_type_total_count{request_type=\"A\",code=\"200\"} 1"));
+        assert!(text.contains(
+            "grpc_request_type_handle_rt_histogram_bucket{request_type=\"A\\\"\\n\",le=\"10\"} 1"
+        ));
+        assert!(text
+            .contains("grpc_request_type_handle_rt_histogram_count{request_type=\"A\\\"\\n\"} 1"));
+    }
+}
@@ -57,6 +57,20 @@ pub enum MetricsKey {
     HttpRequestHandleRtHistogram,
     HttpRequestHandleRtSummary,
     HttpRequestTotalCount,
+    //labeled
+    GrpcRequestTypeHandleRtHistogram,
+    GrpcRequestTypeTotalCount,
+    HttpRequestRouteHandleRtHistogram,
+    HttpRequestRouteTotalCount,
+    ConfigNamespaceConfigSize,
+    NamingNamespaceServiceSize,
+    NamingNamespaceInstanceSize,
+    //backup
+    BackupSuccessCount,
+    BackupFailCount,
+    BackupLastDuration,
+    BackupLastFileSize,
+    BackupSinceLastSuccessSeconds,
 }
 
 lazy_static! {
@@ -110,6 +124,12 @@ lazy_static! {
         MetricsKey::HttpRequestHandleRtHistogram,
         MetricsKey::HttpRequestHandleRtSummary,
         MetricsKey::HttpRequestTotalCount,
+        //backup
+        MetricsKey::BackupSuccessCount,
+        MetricsKey::BackupFailCount,
+        MetricsKey::BackupLastDuration,
+        MetricsKey::BackupLastFileSize,
+        MetricsKey::BackupSinceLastSuccessSeconds,
     ];
 
     pub static ref HISTOGRAM_SUMMARY_MAP: HashMap<MetricsKey,MetricsKey> = MetricsKey::build_histogram_summary_map();
@@ -173,6 +193,34 @@ impl MetricsKey {
             MetricsKey::HttpRequestHandleRtHistogram => "http_request_handle_rt_histogram",
             MetricsKey::HttpRequestHandleRtSummary => "http_request_handle_rt_summary",
             MetricsKey::HttpRequestTotalCount => "http_request_total_count",
+            MetricsKey::GrpcRequestTypeHandleRtHistogram => "grpc_request_type_handle_rt_histogram",
+            MetricsKey::GrpcRequestTypeTotalCount => "grpc_request_type_total_count",
+            MetricsKey::HttpRequestRouteHandleRtHistogram => {
+                "http_request_route_handle_rt_histogram"
+            }
+            MetricsKey::HttpRequestRouteTotalCount => "http_request_route_total_count",
+            MetricsKey::ConfigNamespaceConfigSize => "config_namespace_config_size",
+            MetricsKey::NamingNamespaceServiceSize => "naming_namespace_service_size",
+            MetricsKey::NamingNamespaceInstanceSize => "naming_namespace_instance_size",
+            MetricsKey::BackupSuccessCount => "backup_success_count",
+            MetricsKey::BackupFailCount => "backup_fail_count",
+            MetricsKey::BackupLastDuration => "backup_last_duration",
+            MetricsKey::BackupLastFileSize => "backup_last_file_size",
+            MetricsKey::BackupSinceLastSuccessSeconds => "backup_since_last_success_seconds",
+        }
+    }
+
+    /// 带标签指标的标签名，标签值按相同顺序记录
+    pub fn get_label_names(&self) -> &'static [&'static str] {
+        match &self {
+            MetricsKey::GrpcRequestTypeHandleRtHistogram => &["request_type"],
+            MetricsKey::GrpcRequestTypeTotalCount => &["request_type", "code"],
+            MetricsKey::HttpRequestRouteHandleRtHistogram => &["method", "route"],
+            MetricsKey::HttpRequestRouteTotalCount => &["method", "route", "status"],
+            MetricsKey::ConfigNamespaceConfigSize
+            | MetricsKey::NamingNamespaceServiceSize
+            | MetricsKey::NamingNamespaceInstanceSize => &["namespace"],
+            _ => &[],
         }
     }
 
@@ -251,8 +299,29 @@ impl MetricsKey {
             }
             MetricsKey::HttpRequestHandleRtSummary => "Http request handle rt summary,unit is ms",
             MetricsKey::HttpRequestTotalCount => "Http request total count",
-            //default describe
-            //_ => "Some help info",
+            MetricsKey::GrpcRequestTypeHandleRtHistogram => {
+                "Grpc request handle rt histogram by request type,unit is ms"
+            }
+            MetricsKey::GrpcRequestTypeTotalCount => {
+                "Grpc request total count by request type and code"
+            }
+            MetricsKey::HttpRequestRouteHandleRtHistogram => {
+                "Http request handle rt histogram by route,unit is ms"
+            }
+            MetricsKey::HttpRequestRouteTotalCount => {
+                "Http request total count by route and status"
+            }
+            MetricsKey::ConfigNamespaceConfigSize => "Config size by namespace",
+            MetricsKey::NamingNamespaceServiceSize => "Naming service size by namespace",
+            MetricsKey::NamingNamespaceInstanceSize => "Naming instance size by namespace",
+            MetricsKey::BackupSuccessCount => "Backup success count",
+            MetricsKey::BackupFailCount => "Backup fail count",
+            MetricsKey::BackupLastDuration => "Backup last duration,unit is ms",
+            MetricsKey::BackupLastFileSize => "Backup last file size,unit is byte",
+            MetricsKey::BackupSinceLastSuccessSeconds => {
+                "Seconds since last backup success,-1 means never"
+            } //default describe
+              //_ => "Some help info",
         }
     }
 
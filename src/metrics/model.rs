@@ -430,11 +430,18 @@ impl Display for SummaryValueFmtWrap<'_> {
 #[rtype(result = "anyhow::Result<Vec<MetricsItem>>")]
 pub struct MetricsQuery;
 
+///
+/// 查询带标签的统计指标，如按命名空间统计的配置、服务数量
+#[derive(Message)]
+#[rtype(result = "anyhow::Result<Vec<LabeledMetricsItem>>")]
+pub struct LabeledMetricsQuery;
+
 #[derive(Message, Clone, Debug)]
 #[rtype(result = "anyhow::Result<MetricsResponse>")]
 pub enum MetricsRequest {
     Record(MetricsItem),
     BatchRecord(Vec<MetricsItem>),
+    LabeledBatchRecord(Vec<LabeledMetricsItem>),
     TimelineQuery(TimelineQueryParam),
     Export,
 }
@@ -469,6 +476,25 @@ impl MetricsItem {
     }
 }
 
+///
+/// 带标签的指标记录，labels与MetricsKey::get_label_names一一对应
+#[derive(Clone, Debug)]
+pub struct LabeledMetricsItem {
+    pub metrics_type: MetricsKey,
+    pub labels: Vec<String>,
+    pub record: MetricsRecord,
+}
+
+impl LabeledMetricsItem {
+    pub fn new(metrics_type: MetricsKey, labels: Vec<String>, record: MetricsRecord) -> Self {
+        Self {
+            metrics_type,
+            labels,
+            record,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::metrics::model::{HistogramValue, SummaryValue};
@@ -1,14 +1,16 @@
 pub mod model;
 
 use crate::common::constant::{EMPTY_ARC_STRING, NAMESPACE_TREE_NAME};
-use crate::config::core::ConfigActor;
+use crate::config::config_history::ConfigHistoryRetention;
+use crate::config::core::{ConfigActor, ConfigCmd};
 use crate::console::NamespaceUtilsOld;
+use crate::grpc::bistream_manage::{BiStreamManage, BiStreamManageCmd};
 use crate::namespace::model::{
     Namespace, NamespaceActorReq, NamespaceActorResult, NamespaceDO, NamespaceFromFlags,
-    NamespaceParam, NamespaceQueryReq, NamespaceQueryResult, NamespaceRaftReq, NamespaceRaftResult,
-    WeakNamespaceFromType, FROM_SYSTEM_VALUE,
+    NamespaceParam, NamespaceQueryReq, NamespaceQueryResult, NamespaceQuota, NamespaceRaftReq,
+    NamespaceRaftResult, WeakNamespaceFromType, FROM_SYSTEM_VALUE,
 };
-use crate::naming::core::NamingActor;
+use crate::naming::core::{NamingActor, NamingCmd};
 use crate::raft::filestore::model::SnapshotRecordDto;
 use crate::raft::filestore::raftapply::{RaftApplyDataRequest, RaftApplyDataResponse};
 use crate::raft::filestore::raftsnapshot::{SnapshotWriterActor, SnapshotWriterRequest};
@@ -34,6 +36,10 @@ pub fn build_already_mark_param() -> NamespaceParam {
         namespace_id: Arc::new(ALREADY_SYNC_FROM_CONFIG_KEY.to_string()),
         namespace_name: None,
         r#type: None,
+        config_schema: None,
+        config_history_max_count: None,
+        config_history_max_days: None,
+        quota: None,
     }
 }
 
@@ -57,6 +63,7 @@ pub struct NamespaceActor {
     id_order_list: Vec<Arc<String>>,
     config_addr: Option<Addr<ConfigActor>>,
     naming_addr: Option<Addr<NamingActor>>,
+    conn_manage: Option<Addr<BiStreamManage>>,
     raft: Option<Arc<NacosRaft>>,
     raft_node_id: u64,
     already_sync_from_config: bool,
@@ -80,6 +87,7 @@ impl Inject for NamespaceActor {
     ) {
         self.config_addr = factory_data.get_actor();
         self.naming_addr = factory_data.get_actor();
+        self.conn_manage = factory_data.get_actor();
         self.raft = factory_data.get_bean();
         self.init(ctx);
     }
@@ -92,6 +100,7 @@ impl NamespaceActor {
             id_order_list: Default::default(),
             config_addr: None,
             naming_addr: None,
+            conn_manage: None,
             raft: None,
             already_sync_from_config: false,
             raft_node_id,
@@ -104,6 +113,10 @@ impl NamespaceActor {
                 namespace_id: EMPTY_ARC_STRING.clone(),
                 namespace_name: Some(DEFAULT_NAMESPACE.to_owned()),
                 r#type: Some(FROM_SYSTEM_VALUE.to_owned()),
+                config_schema: None,
+                config_history_max_count: None,
+                config_history_max_days: None,
+                quota: None,
             },
             false,
             false,
@@ -143,6 +156,20 @@ impl NamespaceActor {
                 v.namespace_name.to_owned()
             };
             value.flag = v.flag | param_flag;
+            value.config_schema = match param.config_schema {
+                Some(schema) if schema.is_empty() => None,
+                Some(schema) => Some(Arc::new(schema)),
+                None => v.config_schema.clone(),
+            };
+            value.config_history_max_count = param
+                .config_history_max_count
+                .or(v.config_history_max_count);
+            value.config_history_max_days =
+                param.config_history_max_days.or(v.config_history_max_days);
+            value.quota = match param.quota {
+                Some(quota) => Some(quota.merge(v.quota.as_ref())).filter(|v| !v.is_empty()),
+                None => v.quota.clone(),
+            };
             value
         } else {
             if only_update {
@@ -153,12 +180,50 @@ impl NamespaceActor {
                 namespace_id: param.namespace_id,
                 namespace_name: param.namespace_name.unwrap_or_default(),
                 flag: param_flag,
+                config_schema: param.config_schema.filter(|v| !v.is_empty()).map(Arc::new),
+                config_history_max_count: param.config_history_max_count,
+                config_history_max_days: param.config_history_max_days,
+                quota: param.quota.filter(|v| !v.is_empty()),
             }
         };
+        self.notify_namespace_quota(value.namespace_id.clone(), value.quota.clone());
+        self.notify_config_history_retention(
+            value.namespace_id.clone(),
+            ConfigHistoryRetention::new(
+                value.config_history_max_count,
+                value.config_history_max_days,
+            ),
+        );
         self.data
             .insert(value.namespace_id.clone(), Arc::new(value));
     }
 
+    ///
+    /// 历史记录保留策略同步到配置中心，在raft apply中同步发送保证各节点一致
+    fn notify_config_history_retention(
+        &self,
+        namespace_id: Arc<String>,
+        retention: ConfigHistoryRetention,
+    ) {
+        if let Some(config_addr) = &self.config_addr {
+            config_addr.do_send(ConfigCmd::SetHistoryRetention(namespace_id, retention));
+        }
+    }
+
+    ///
+    /// 配额同步到注册中心与长链接管理，在raft apply中同步发送保证各节点一致
+    fn notify_namespace_quota(&self, namespace_id: Arc<String>, quota: Option<NamespaceQuota>) {
+        if let Some(naming_addr) = &self.naming_addr {
+            naming_addr.do_send(NamingCmd::SetNamespaceQuota(
+                namespace_id.clone(),
+                quota.clone(),
+            ));
+        }
+        if let Some(conn_manage) = &self.conn_manage {
+            conn_manage.do_send(BiStreamManageCmd::SetNamespaceQuota(namespace_id, quota));
+        }
+    }
+
     fn set_weak_namespace(&mut self, namespace_id: Arc<String>, from_type: WeakNamespaceFromType) {
         if namespace_id.is_empty() || namespace_id.as_str() == DEFAULT_NAMESPACE {
             return;
@@ -179,6 +244,10 @@ impl NamespaceActor {
                 namespace_id: namespace_id.clone(),
                 namespace_name: namespace_id.as_str().to_owned(),
                 flag,
+                config_schema: None,
+                config_history_max_count: None,
+                config_history_max_days: None,
+                quota: None,
             };
             self.data.insert(namespace_id.clone(), Arc::new(value));
         }
@@ -212,7 +281,12 @@ impl NamespaceActor {
     }
 
     fn remove_id(&mut self, id: &Arc<String>) {
-        self.data.remove(id);
+        if let Some(v) = self.data.remove(id) {
+            if v.quota.is_some() {
+                self.notify_namespace_quota(id.clone(), None);
+            }
+        }
+        self.notify_config_history_retention(id.clone(), ConfigHistoryRetention::default());
         for (i, item) in self.id_order_list.iter().enumerate() {
             if id == item {
                 self.id_order_list.remove(i);
@@ -252,6 +326,10 @@ impl NamespaceActor {
                 namespace_id: param.namespace_id,
                 namespace_name: param.namespace_name.unwrap_or_default(),
                 flag: NamespaceFromFlags::USER.bits(),
+                config_schema: None,
+                config_history_max_count: None,
+                config_history_max_days: None,
+                quota: None,
             };
             let key = value.namespace_id.clone();
             let value_db: NamespaceDO = value.into();
@@ -294,6 +372,10 @@ impl NamespaceActor {
                 namespace_id: value.namespace_id,
                 namespace_name: Some(value.namespace_name),
                 r#type: Some(NamespaceFromFlags::get_db_type(value.flag)),
+                config_schema: value.config_schema.map(|v| v.as_ref().to_owned()),
+                config_history_max_count: value.config_history_max_count,
+                config_history_max_days: value.config_history_max_days,
+                quota: value.quota,
             },
             false,
             false,
@@ -317,6 +399,10 @@ impl NamespaceActor {
                     namespace_id,
                     namespace_name: item.namespace_name,
                     r#type: item.r#type,
+                    config_schema: None,
+                    config_history_max_count: None,
+                    config_history_max_days: None,
+                    quota: None,
                 },
                 true,
                 false,
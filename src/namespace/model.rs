@@ -2,6 +2,7 @@ use actix::prelude::*;
 use bitflags::bitflags;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use thiserror::Error;
 
 /*
 lazy_static::lazy_static! {
@@ -13,12 +14,27 @@ lazy_static::lazy_static! {
 pub(crate) const FROM_SYSTEM_VALUE: &str = "0";
 pub(crate) const FROM_USER_VALUE: &str = "2";
 
+/// 超出命名空间资源配额时http接口的状态码与gRPC接口的错误码
+pub const NAMESPACE_QUOTA_EXCEEDED_CODE: u16 = 429;
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct Namespace {
     pub namespace_id: Arc<String>,
     pub namespace_name: String,
     //pub r#type: String,
     pub flag: u32,
+    /// 命名空间下json、yaml配置需要满足的JSON Schema
+    #[serde(default)]
+    pub config_schema: Option<Arc<String>>,
+    /// 配置历史记录保留条数，None表示默认100条，0表示不限制
+    #[serde(default)]
+    pub config_history_max_count: Option<u32>,
+    /// 配置历史记录保留天数，None或0表示不限制
+    #[serde(default)]
+    pub config_history_max_days: Option<u32>,
+    /// 命名空间资源配额
+    #[serde(default)]
+    pub quota: Option<NamespaceQuota>,
 }
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +42,17 @@ pub struct NamespaceParam {
     pub namespace_id: Arc<String>,
     pub namespace_name: Option<String>,
     pub r#type: Option<String>,
+    /// None表示不变更，空串表示清除
+    #[serde(default)]
+    pub config_schema: Option<String>,
+    /// None表示不变更
+    #[serde(default)]
+    pub config_history_max_count: Option<u32>,
+    #[serde(default)]
+    pub config_history_max_days: Option<u32>,
+    /// None表示不变更，各项配额单独合并
+    #[serde(default)]
+    pub quota: Option<NamespaceQuota>,
 }
 
 #[derive(Clone, PartialEq, prost_derive::Message, Deserialize, Serialize)]
@@ -36,6 +63,14 @@ pub struct NamespaceDO {
     pub namespace_name: Option<String>,
     #[prost(string, optional, tag = "3")]
     pub r#type: Option<String>,
+    #[prost(string, optional, tag = "4")]
+    pub config_schema: Option<String>,
+    #[prost(uint32, optional, tag = "5")]
+    pub config_history_max_count: Option<u32>,
+    #[prost(uint32, optional, tag = "6")]
+    pub config_history_max_days: Option<u32>,
+    #[prost(message, optional, tag = "7")]
+    pub quota: Option<NamespaceQuota>,
 }
 
 impl NamespaceDO {
@@ -64,6 +99,10 @@ impl From<NamespaceDO> for Namespace {
             namespace_id: Arc::new(value.namespace_id.unwrap_or_default()),
             namespace_name: value.namespace_name.unwrap_or_default(),
             flag,
+            config_schema: value.config_schema.map(Arc::new),
+            config_history_max_count: value.config_history_max_count,
+            config_history_max_days: value.config_history_max_days,
+            quota: value.quota,
         }
     }
 }
@@ -75,7 +114,132 @@ impl From<Namespace> for NamespaceDO {
             namespace_id: Some(value.namespace_id.as_str().to_string()),
             namespace_name: Some(value.namespace_name),
             r#type: Some(t),
+            config_schema: value.config_schema.map(|v| v.as_ref().to_owned()),
+            config_history_max_count: value.config_history_max_count,
+            config_history_max_days: value.config_history_max_days,
+            quota: value.quota,
+        }
+    }
+}
+
+///
+/// 命名空间资源配额，None或0表示不限制
+#[derive(Clone, PartialEq, Eq, prost_derive::Message, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamespaceQuota {
+    /// 配置数量上限
+    #[prost(uint32, optional, tag = "1")]
+    pub max_config_count: Option<u32>,
+    /// 单个配置内容大小上限(字节)，设置后覆盖全局的config_max_content
+    #[prost(uint32, optional, tag = "2")]
+    pub max_config_content: Option<u32>,
+    /// 服务数量上限
+    #[prost(uint32, optional, tag = "3")]
+    pub max_service_count: Option<u32>,
+    /// 单个服务的实例数量上限
+    #[prost(uint32, optional, tag = "4")]
+    pub max_instance_per_service: Option<u32>,
+    /// 单个节点上的gRPC连接数量上限
+    #[prost(uint32, optional, tag = "5")]
+    pub max_grpc_connection: Option<u32>,
+}
+
+impl NamespaceQuota {
+    ///
+    /// 以当前值覆盖旧值，当前值未设置的项保留旧值
+    pub fn merge(self, old: Option<&NamespaceQuota>) -> Self {
+        let old = if let Some(old) = old {
+            old.to_owned()
+        } else {
+            return self;
+        };
+        Self {
+            max_config_count: self.max_config_count.or(old.max_config_count),
+            max_config_content: self.max_config_content.or(old.max_config_content),
+            max_service_count: self.max_service_count.or(old.max_service_count),
+            max_instance_per_service: self
+                .max_instance_per_service
+                .or(old.max_instance_per_service),
+            max_grpc_connection: self.max_grpc_connection.or(old.max_grpc_connection),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        Self::limit(self.max_config_count).is_none()
+            && Self::limit(self.max_config_content).is_none()
+            && Self::limit(self.max_service_count).is_none()
+            && Self::limit(self.max_instance_per_service).is_none()
+            && Self::limit(self.max_grpc_connection).is_none()
+    }
+
+    ///
+    /// 0表示不限制
+    pub fn limit(v: Option<u32>) -> Option<usize> {
+        v.filter(|v| *v > 0).map(|v| v as usize)
+    }
+
+    ///
+    /// 新增一项资源前检查，当前数量已达到上限时返回NamespaceQuotaError
+    pub fn check_add(
+        namespace: &str,
+        name: &str,
+        limit: Option<u32>,
+        current: usize,
+    ) -> Result<(), NamespaceQuotaError> {
+        Self::check_add_batch(namespace, name, limit, current, 1)
+    }
+
+    ///
+    /// 批量新增资源前检查，新增后超出上限时返回NamespaceQuotaError
+    pub fn check_add_batch(
+        namespace: &str,
+        name: &str,
+        limit: Option<u32>,
+        current: usize,
+        count: usize,
+    ) -> Result<(), NamespaceQuotaError> {
+        if let Some(limit) = Self::limit(limit) {
+            if current + count > limit {
+                return Err(NamespaceQuotaError::new(
+                    namespace,
+                    format!("{} reached the limit {}", name, limit),
+                ));
+            }
         }
+        Ok(())
+    }
+}
+
+///
+/// 命名空间资源使用量，与NamespaceQuota对应
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamespaceUsage {
+    pub config_count: usize,
+    pub service_count: usize,
+    /// 实例数最多的服务的实例数量
+    pub max_service_instance_count: usize,
+    /// 当前节点上的gRPC连接数量
+    pub grpc_connection_count: usize,
+}
+
+///
+/// 超出命名空间资源配额
+#[derive(Debug, Clone, Error)]
+#[error("namespace quota exceeded, namespace:{namespace}, {message}")]
+pub struct NamespaceQuotaError {
+    pub namespace: String,
+    pub message: String,
+}
+
+impl NamespaceQuotaError {
+    pub fn new(namespace: &str, message: String) -> Self {
+        let namespace = if namespace.is_empty() {
+            "public".to_owned()
+        } else {
+            namespace.to_owned()
+        };
+        Self { namespace, message }
     }
 }
 
@@ -191,3 +355,42 @@ pub enum NamespaceQueryResult {
     Info(Arc<Namespace>),
     None,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quota_merge_and_check() {
+        let old = NamespaceQuota {
+            max_config_count: Some(10),
+            max_service_count: Some(2),
+            ..Default::default()
+        };
+        let quota = NamespaceQuota {
+            max_service_count: Some(0),
+            max_grpc_connection: Some(5),
+            ..Default::default()
+        }
+        .merge(Some(&old));
+        assert_eq!(quota.max_config_count, Some(10));
+        assert_eq!(NamespaceQuota::limit(quota.max_service_count), None);
+        assert_eq!(NamespaceQuota::limit(quota.max_grpc_connection), Some(5));
+        assert!(!quota.is_empty());
+        assert!(NamespaceQuota {
+            max_config_count: Some(0),
+            ..Default::default()
+        }
+        .is_empty());
+
+        assert!(
+            NamespaceQuota::check_add("dev", "config count", quota.max_config_count, 9).is_ok()
+        );
+        let err =
+            NamespaceQuota::check_add("", "config count", quota.max_config_count, 10).unwrap_err();
+        assert_eq!(err.namespace, "public");
+        assert!(
+            NamespaceQuota::check_add("dev", "service count", quota.max_service_count, 100).is_ok()
+        );
+    }
+}
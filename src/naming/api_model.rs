@@ -1,6 +1,8 @@
 use crate::now_millis_i64;
 
+use super::health_check::ServiceHealthCheck;
 use super::model::{Instance, ServiceDetailDto, ServiceKey};
+use super::selector::ServiceSelector;
 use super::NamingUtils;
 use crate::common::option_utils::OptionUtils;
 use chrono::Local;
@@ -132,6 +134,7 @@ pub struct ServiceInfoParam {
     pub protect_threshold: Option<f32>,
     pub metadata: Option<String>,
     pub selector: Option<String>,
+    pub health_check: Option<String>,
 }
 
 impl ServiceInfoParam {
@@ -143,6 +146,7 @@ impl ServiceInfoParam {
             protect_threshold: OptionUtils::select(self.protect_threshold, b.protect_threshold),
             metadata: OptionUtils::select(self.metadata, b.metadata),
             selector: OptionUtils::select(self.selector, b.selector),
+            health_check: OptionUtils::select(self.health_check, b.health_check),
         }
     }
 
@@ -159,6 +163,18 @@ impl ServiceInfoParam {
             } else {
                 None
             };
+            let selector = if let Some(selector) = self.selector {
+                ServiceSelector::parse(&selector)?;
+                Some(Arc::new(selector))
+            } else {
+                None
+            };
+            let health_check = if let Some(health_check) = self.health_check {
+                ServiceHealthCheck::parse(&health_check)?;
+                Some(Arc::new(health_check))
+            } else {
+                None
+            };
 
             Ok(ServiceDetailDto {
                 namespace_id: Arc::new(NamingUtils::default_namespace(
@@ -170,6 +186,8 @@ impl ServiceInfoParam {
                 )),
                 metadata,
                 protect_threshold: self.protect_threshold,
+                selector,
+                health_check,
                 ..Default::default()
             })
         } else {
@@ -241,6 +241,7 @@ pub async fn handle_naming_route(
             session_id,
             request,
             headers,
+            client_ip,
         } => {
             let mcp_server = if let Ok(Ok(McpManagerResult::ServerInfo(Some(server)))) = app
                 .mcp_manager
@@ -261,6 +262,7 @@ pub async fn handle_naming_route(
                 &mcp_server,
                 session_id.as_ref(),
                 ref_headers,
+                client_ip.and_then(|v| v.parse().ok()),
             )
             .await
             {
@@ -56,6 +56,8 @@ pub enum NamingRouteRequest {
         server_key: Arc<String>,
         request: JsonRpcRequest,
         headers: HashMap<String, String>,
+        #[serde(default)]
+        client_ip: Option<String>,
     },
 }
 
@@ -26,6 +26,7 @@ use super::model::{DistroData, Instance};
 use super::model::{InstanceKey, UpdatePerpetualType};
 use super::naming_delay_nofity::DelayNotifyActor;
 use super::naming_delay_nofity::DelayNotifyCmd;
+use super::naming_fuzzy_watch::NamingFuzzyWatcher;
 use super::naming_subscriber::NamingListenerItem;
 use super::naming_subscriber::Subscriber;
 use super::service::ServiceInfoDto;
@@ -34,6 +35,7 @@ use super::service::{Service, SubscriberInfoDto};
 use super::service_index::NamespaceIndex;
 use super::service_index::ServiceQueryParam;
 use super::NamingUtils;
+use crate::common::fuzzy_watch::{FuzzyWatchParam, ADD_SERVICE, DELETE_SERVICE, INSTANCE_CHANGED};
 use crate::common::hash_utils::get_hash_value;
 use crate::common::NamingSysConfig;
 use crate::common::{delay_notify, AppSysConfig};
@@ -51,7 +53,7 @@ use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::LinkedList;
 use std::default::Default;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -61,6 +63,7 @@ use crate::common::model::privilege::NamespacePrivilegeGroup;
 use crate::common::pb::data_object::InstanceDo;
 use crate::metrics::metrics_key::MetricsKey;
 use crate::metrics::model::{MetricsItem, MetricsQuery, MetricsRecord};
+use crate::namespace::model::NamespaceQuota;
 use crate::namespace::NamespaceActor;
 use crate::naming::instance_meta_manager::{InstanceMetaManager, InstanceMetaManagerReq};
 use crate::naming::instance_meta_repository::InstanceMetaDto;
@@ -88,6 +91,7 @@ pub struct NamingActor {
     pub(crate) listener_addr: Option<Addr<InnerNamingListener>>,
     pub(crate) delay_notify_addr: Option<Addr<DelayNotifyActor>>,
     pub(crate) subscriber: Subscriber,
+    pub(crate) fuzzy_watcher: NamingFuzzyWatcher,
     pub(crate) sys_config: NamingSysConfig,
     pub(crate) empty_service_set: TimeoutSet<ServiceKey>,
     pub(crate) instance_metadate_set: TimeoutSet<InstanceKey>,
@@ -105,6 +109,8 @@ pub struct NamingActor {
     //dal_addr: Addr<ServiceDalActor>,
     pub(crate) raft_router: Option<Arc<RaftRequestRoute>>,
     pub(crate) meta_manager_addr: Option<Addr<InstanceMetaManager>>,
+    /// 命名空间资源配额，只保存有设置配额的命名空间
+    pub(crate) namespace_quota: HashMap<Arc<String>, NamespaceQuota>,
 }
 
 impl Actor for NamingActor {
@@ -129,6 +135,9 @@ impl Inject for NamingActor {
         if let Some(notify_addr) = self.delay_notify_addr.as_ref() {
             self.subscriber.set_notify_addr(notify_addr.clone());
         }
+        if let Some(conn_manage) = factory_data.get_actor() {
+            self.fuzzy_watcher.set_conn_manage(conn_manage);
+        }
         self.cluster_node_manage = factory_data.get_actor();
         self.cluster_delay_notify = factory_data.get_actor();
         self.namespace_actor = factory_data.get_actor();
@@ -141,6 +150,9 @@ impl Inject for NamingActor {
             self.sys_config.instance_timeout_millis =
                 sys_config.naming_instance_timeout as i64 + 3000;
             self.node_id = sys_config.raft_node_id;
+            self.subscriber.set_notify_without_listener(
+                sys_config.naming_dns_enable || sys_config.webhook_enable,
+            );
             log::info!("NamingActor change naming timeout info from env,health_timeout:{},instance_timeout:{}"
                 ,self.sys_config.instance_health_timeout_millis,self.sys_config.instance_timeout_millis);
             if sys_config.naming_perpetual_instance_probe_interval > 0 {
@@ -170,6 +182,7 @@ impl NamingActor {
             last_id: 0u64,
             listener_addr: None,
             subscriber,
+            fuzzy_watcher: NamingFuzzyWatcher::new(),
             delay_notify_addr: None,
             sys_config: NamingSysConfig::new(),
             empty_service_set: Default::default(),
@@ -186,6 +199,7 @@ impl NamingActor {
             last_perpetual_instance_probe_time: 0,
             raft_router: None,
             meta_manager_addr: None,
+            namespace_quota: Default::default(),
         }
     }
 
@@ -227,6 +241,7 @@ impl NamingActor {
                 self.namespace_index.insert_service(key.clone());
                 //self.dal_addr.do_send(ServiceDalMsg::AddService(service.get_service_do()));
                 self.service_map.insert(key.clone(), service);
+                self.fuzzy_watcher.notify(key, ADD_SERVICE);
                 self.empty_service_set.add(
                     now_millis() + self.sys_config.service_time_out_millis,
                     key.clone(),
@@ -246,9 +261,11 @@ impl NamingActor {
                 if let Some(protect_threshold) = service_info.protect_threshold {
                     service.protect_threshold = protect_threshold;
                 }
-                if let Some(metadata) = service_info.metadata {
-                    service.metadata = metadata;
-                }
+                service.update_metadata(
+                    service_info.metadata,
+                    service_info.selector,
+                    service_info.health_check,
+                );
             }
             None => {
                 let mut service = Service::default();
@@ -264,13 +281,16 @@ impl NamingActor {
                 if let Some(protect_threshold) = service_info.protect_threshold {
                     service.protect_threshold = protect_threshold;
                 }
-                if let Some(metadata) = service_info.metadata {
-                    service.metadata = metadata;
-                }
+                service.update_metadata(
+                    service_info.metadata,
+                    service_info.selector,
+                    service_info.health_check,
+                );
                 service.recalculate_checksum();
                 self.namespace_index.insert_service(key.clone());
                 //self.dal_addr.do_send(ServiceDalMsg::AddService(service.get_service_do()));
                 self.service_map.insert(key.clone(), service);
+                self.fuzzy_watcher.notify(&key, ADD_SERVICE);
                 self.empty_service_set.add(
                     now_millis() + self.sys_config.service_time_out_millis,
                     key.clone(),
@@ -355,6 +375,7 @@ impl NamingActor {
         }
         match tag {
             UpdateInstanceType::New => {
+                self.fuzzy_watcher.notify(&key, INSTANCE_CHANGED);
                 self.subscriber.notify(key);
                 if let (Some(cluster_delay_notify), Some(instance)) =
                     (&self.cluster_delay_notify, instance)
@@ -364,6 +385,7 @@ impl NamingActor {
                 }
             }
             UpdateInstanceType::Remove => {
+                self.fuzzy_watcher.notify(&key, INSTANCE_CHANGED);
                 self.subscriber.notify(key);
                 if let (Some(cluster_delay_notify), Some(instance)) =
                     (&self.cluster_delay_notify, instance)
@@ -375,6 +397,7 @@ impl NamingActor {
                 }
             }
             UpdateInstanceType::UpdateValue => {
+                self.fuzzy_watcher.notify(&key, INSTANCE_CHANGED);
                 self.subscriber.notify(key);
                 if let (Some(cluster_delay_notify), Some(instance)) =
                     (&self.cluster_delay_notify, instance)
@@ -544,6 +567,109 @@ impl NamingActor {
         tag
     }
 
+    ///
+    /// 注册新服务或新实例前检查命名空间配额，已存在的实例更新不受限制
+    /// 批量注册时整批检查，避免部分实例写入后才超出配额
+    fn check_namespace_quota(&self, instances: &[&Instance]) -> anyhow::Result<()> {
+        let mut new_instances: HashMap<ServiceKey, HashSet<InstanceShortKey>> = HashMap::new();
+        for instance in instances {
+            if !self.namespace_quota.contains_key(&instance.namespace_id) {
+                continue;
+            }
+            let key = instance.get_service_key();
+            let short_key = instance.get_short_key();
+            let exists = self
+                .service_map
+                .get(&key)
+                .map(|v| v.instances.contains_key(&short_key))
+                .unwrap_or(false);
+            let item = new_instances.entry(key).or_default();
+            if !exists {
+                item.insert(short_key);
+            }
+        }
+        let mut new_services: HashMap<Arc<String>, usize> = HashMap::new();
+        for (key, short_keys) in &new_instances {
+            let quota = if let Some(quota) = self.namespace_quota.get(&key.namespace_id) {
+                quota
+            } else {
+                continue;
+            };
+            let current = if let Some(service) = self.service_map.get(key) {
+                service.instances.len()
+            } else {
+                *new_services.entry(key.namespace_id.clone()).or_default() += 1;
+                0
+            };
+            NamespaceQuota::check_add_batch(
+                &key.namespace_id,
+                "instance count of service",
+                quota.max_instance_per_service,
+                current,
+                short_keys.len(),
+            )?;
+        }
+        for (namespace_id, count) in new_services {
+            let quota = if let Some(quota) = self.namespace_quota.get(&namespace_id) {
+                quota
+            } else {
+                continue;
+            };
+            let service_count = self
+                .namespace_index
+                .namespace_group
+                .get(&namespace_id)
+                .map(|v| v.service_size)
+                .unwrap_or_default();
+            NamespaceQuota::check_add_batch(
+                &namespace_id,
+                "service count",
+                quota.max_service_count,
+                service_count,
+                count,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn set_namespace_quota(&mut self, namespace_id: Arc<String>, quota: Option<NamespaceQuota>) {
+        // 注册中心中默认命名空间使用public
+        let namespace_id = if namespace_id.is_empty() {
+            Arc::new(super::DEFAULT_NAMESPACE.to_owned())
+        } else {
+            namespace_id
+        };
+        if let Some(quota) = quota {
+            self.namespace_quota.insert(namespace_id, quota);
+        } else {
+            self.namespace_quota.remove(&namespace_id);
+        }
+    }
+
+    /// 返回命名空间的服务数量与单个服务的最大实例数量
+    fn get_namespace_usage(&self, namespace_id: &Arc<String>) -> (usize, usize) {
+        let service_index = if let Some(v) = self.namespace_index.namespace_group.get(namespace_id)
+        {
+            v
+        } else {
+            return (0, 0);
+        };
+        let mut max_instance_count = 0;
+        for (group, set) in &service_index.group_service {
+            for service_name in set {
+                let key = ServiceKey::new_by_arc(
+                    namespace_id.clone(),
+                    group.clone(),
+                    service_name.clone(),
+                );
+                if let Some(service) = self.service_map.get(&key) {
+                    max_instance_count = max(max_instance_count, service.instances.len());
+                }
+            }
+        }
+        (service_index.service_size, max_instance_count)
+    }
+
     pub(crate) fn remove_client_instance(&mut self, client_id: &Arc<String>) {
         if let Some(keys) = self.client_instance_set.remove(client_id) {
             for instance_key in keys {
@@ -577,6 +703,7 @@ impl NamingActor {
         key: &ServiceKey,
         cluster_str: &str,
         only_healthy: bool,
+        client_ip: Option<&IpAddr>,
     ) -> Vec<Arc<Instance>> {
         let cluster_names = NamingUtils::split_filters(cluster_str);
         if let Some(service) = self.service_map.get(key) {
@@ -584,11 +711,30 @@ impl NamingActor {
                 service.get_instance_list(cluster_names, false, true),
                 Some(service.get_metadata()),
                 only_healthy,
+                client_ip,
             );
         }
         vec![]
     }
 
+    ///
+    /// DNS名称不区分大小写(解析器可能随机改变查询名大小写)，优先精确匹配；
+    /// 只有一个服务忽略大小写后匹配时使用该服务，存在多个时无法确定，返回None
+    pub fn find_service_key_ignore_case(&self, key: &ServiceKey) -> Option<ServiceKey> {
+        if self.service_map.contains_key(key) {
+            return Some(key.clone());
+        }
+        let mut iter = self.service_map.keys().filter(|e| {
+            e.service_name.eq_ignore_ascii_case(&key.service_name)
+                && e.group_name.eq_ignore_ascii_case(&key.group_name)
+                && e.namespace_id.eq_ignore_ascii_case(&key.namespace_id)
+        });
+        match (iter.next(), iter.next()) {
+            (Some(v), None) => Some(v.clone()),
+            _ => None,
+        }
+    }
+
     pub fn get_instance_page(
         &self,
         key: &ServiceKey,
@@ -597,7 +743,7 @@ impl NamingActor {
         page_size: usize,
         page_index: usize,
     ) -> (usize, Vec<Arc<Instance>>) {
-        let mut all_instances = self.get_instance_list(key, cluster_str, only_healthy);
+        let mut all_instances = self.get_instance_list(key, cluster_str, only_healthy, None);
         let total = all_instances.len();
 
         let offset = if page_index == 0 {
@@ -678,8 +824,9 @@ impl NamingActor {
         key: &ServiceKey,
         cluster_str: String,
         only_healthy: bool,
+        client_ip: Option<&IpAddr>,
     ) -> String {
-        let list = self.get_instance_list(key, &cluster_str, only_healthy);
+        let list = self.get_instance_list(key, &cluster_str, only_healthy, client_ip);
         QueryListResult::get_instance_list_string(cluster_str, key, list)
     }
 
@@ -727,20 +874,24 @@ impl NamingActor {
 
     fn trigger_perpetual_health_check(&mut self) {
         //TODO 可考虑增加是否主动检测永久实例状态的配置
+        let sniffing_addr = if let Some(v) = &self.net_sniffing_addr {
+            v.clone()
+        } else {
+            return;
+        };
         let now = now_second_i32();
+        self.trigger_service_health_check(&sniffing_addr, now);
         if now - self.last_perpetual_instance_probe_time
             < self.sys_config.perpetual_instance_probe_interval
         {
             return;
         }
-        let sniffing_addr = if let Some(v) = &self.net_sniffing_addr {
-            v.clone()
-        } else {
-            return;
-        };
         self.last_perpetual_instance_probe_time = now;
         let mut host_servers: HashMap<InstanceShortKey, Vec<ServiceKey>> = HashMap::new();
         for service in self.service_map.values() {
+            if service.health_check.is_some() {
+                continue;
+            }
             for key in &service.perpetual_host_set {
                 let service_key = service.get_service_key();
                 if let Some(list) = host_servers.get_mut(key) {
@@ -755,6 +906,31 @@ impl NamingActor {
         }
     }
 
+    ///
+    /// 按服务配置的健康检查方式与间隔检测永久实例
+    fn trigger_service_health_check(&mut self, sniffing_addr: &Addr<NetSniffing>, now: i32) {
+        for service in self.service_map.values_mut() {
+            let check = match &service.health_check {
+                Some(v) => v.clone(),
+                None => continue,
+            };
+            if service.perpetual_host_set.is_empty()
+                || now - service.last_health_check_time < check.interval as i32
+            {
+                continue;
+            }
+            service.last_health_check_time = now;
+            let service_key = service.get_service_key();
+            for key in &service.perpetual_host_set {
+                sniffing_addr.do_send(NetSniffingCmd::CheckServiceHost(
+                    key.clone(),
+                    service_key.clone(),
+                    check.clone(),
+                ));
+            }
+        }
+    }
+
     fn update_perpetual_health(
         &mut self,
         host: InstanceShortKey,
@@ -770,11 +946,7 @@ impl NamingActor {
         );
         for service_key in service_keys {
             if let Some(server) = self.service_map.get_mut(&service_key) {
-                if sniffing_result {
-                    server.update_perpetual_instance_healthy_valid(&host);
-                } else {
-                    server.update_instance_healthy_invalid(&host);
-                }
+                server.update_perpetual_health_check(&host, sniffing_result);
             }
         }
     }
@@ -985,6 +1157,7 @@ impl NamingActor {
                 self.namespace_index
                     .remove_service(&service.get_service_key());
                 self.service_map.remove(&service_map_key);
+                self.fuzzy_watcher.notify(&service_map_key, DELETE_SERVICE);
                 log::info!("clear_empty_service:{:?}", &service_map_key);
             }
         }
@@ -1409,10 +1582,12 @@ pub enum NamingCmd {
     Update(Instance, Option<InstanceUpdateTag>),
     UpdateFromSync(Instance, Option<InstanceUpdateTag>),
     UpdateBatch(Vec<Instance>),
+    /// 客户端批量注册，整批检查配额通过后再写入
+    RegisterBatch(Vec<(Instance, Option<InstanceUpdateTag>)>),
     Delete(Instance),
     DeleteBatch(Vec<Instance>),
     Query(Instance),
-    QueryList(ServiceKey, String, bool, Option<SocketAddr>),
+    QueryList(ServiceKey, String, bool, Option<SocketAddr>, Option<IpAddr>),
     QueryInstancePage {
         service_key: ServiceKey,
         cluster: String,
@@ -1420,9 +1595,11 @@ pub enum NamingCmd {
         page_size: usize,
         page_index: usize,
     },
-    SelectOneInstance(ServiceKey),
+    SelectOneInstance(ServiceKey, Option<IpAddr>),
     QueryAllInstanceList(ServiceKey),
-    QueryListString(ServiceKey, String, bool, Option<SocketAddr>),
+    /// DNS查询健康实例，服务名不区分大小写
+    QueryDnsInstanceList(ServiceKey),
+    QueryListString(ServiceKey, String, bool, Option<SocketAddr>, Option<IpAddr>),
     QueryServiceInfo(ServiceKey, String, bool),
     QueryServicePage(ServiceKey, usize, usize),
     QueryServiceSubscribersPage(ServiceKey, usize, usize),
@@ -1440,9 +1617,14 @@ pub enum NamingCmd {
     Subscribe(Vec<NamingListenerItem>, Arc<String>),
     RemoveSubscribe(Vec<NamingListenerItem>, Arc<String>),
     RemoveClient(Arc<String>),
+    /// 模糊监听或取消模糊监听
+    FuzzyWatch(Box<FuzzyWatchParam>, Arc<String>),
     RemoveClientsFromCluster(Vec<Arc<String>>),
     RemoveClientFromCluster(Arc<String>),
     QueryClientInstanceCount,
+    QueryClientSubscribeCount,
+    QueryClientInstanceList(Arc<String>),
+    QueryClientSubscribeKeys(Arc<String>),
     QueryDalAddr,
     QuerySnapshot(Vec<ProcessRange>),
     ClusterRefreshProcessRange(ProcessRange),
@@ -1462,6 +1644,9 @@ pub enum NamingCmd {
     NotifyRemoveRaftInstance(InstanceKey),
     InitInstanceMeta(ServiceKey, Vec<InstanceMetaDto>),
     QueryAllServiceInstanceMetaData,
+    /// 命名空间配额变更，由命名空间模块在raft apply时同步
+    SetNamespaceQuota(Arc<String>, Option<NamespaceQuota>),
+    QueryNamespaceUsage(Arc<String>),
 }
 
 pub enum NamingResult {
@@ -1477,12 +1662,18 @@ pub enum NamingResult {
     InstanceInfoPage((usize, Vec<Arc<Instance>>)),
     ServiceDto(Option<ServiceInfoDto>),
     ClientInstanceCount(Vec<(Arc<String>, usize)>),
+    ClientSubscribeCount(Vec<(Arc<String>, usize)>),
+    ServiceKeyList(Vec<ServiceKey>),
     RewriteToCluster(u64, Instance),
     Snapshot(SnapshotForSend),
     GrpcDistroData(DistroData),
     DiffDistroData(DistroData),
     DistroInstancesSnapshot(Vec<Arc<Instance>>),
     AllServiceInstanceMetaData(Vec<(ServiceKey, Vec<InstanceMetaDto>)>),
+    NamespaceUsage {
+        service_count: usize,
+        max_instance_count: usize,
+    },
 }
 
 impl Supervised for NamingActor {
@@ -1499,6 +1690,9 @@ impl Handler<NamingCmd> for NamingActor {
         //log::info!("NamingActor handle:{:?}", &msg);
         match msg {
             NamingCmd::Update(instance, tag) => {
+                if !instance.is_from_cluster() {
+                    self.check_namespace_quota(&[&instance])?;
+                }
                 let tag = self.update_instance(
                     &instance.get_service_key(),
                     instance,
@@ -1512,6 +1706,24 @@ impl Handler<NamingCmd> for NamingActor {
                     Ok(NamingResult::NULL)
                 }
             }
+            NamingCmd::RegisterBatch(list) => {
+                let instances: Vec<&Instance> = list
+                    .iter()
+                    .map(|(instance, _)| instance)
+                    .filter(|instance| !instance.is_from_cluster())
+                    .collect();
+                self.check_namespace_quota(&instances)?;
+                for (instance, tag) in list {
+                    self.update_instance(
+                        &instance.get_service_key(),
+                        instance,
+                        tag,
+                        false,
+                        Some(ctx.address()),
+                    );
+                }
+                Ok(NamingResult::NULL)
+            }
             NamingCmd::UpdateFromSync(instance, tag) => {
                 let tag =
                     self.update_instance(&instance.get_service_key(), instance, tag, true, None);
@@ -1557,12 +1769,17 @@ impl Handler<NamingCmd> for NamingActor {
                 }
                 Ok(NamingResult::NULL)
             }
-            NamingCmd::QueryList(service_key, cluster_str, only_healthy, addr) => {
+            NamingCmd::QueryList(service_key, cluster_str, only_healthy, addr, client_ip) => {
                 let cluster_names = NamingUtils::split_filters(&cluster_str);
                 if let Some(addr) = addr {
                     self.update_listener(&service_key, &cluster_names, addr, only_healthy);
                 }
-                let list = self.get_instance_list(&service_key, &cluster_str, only_healthy);
+                let list = self.get_instance_list(
+                    &service_key,
+                    &cluster_str,
+                    only_healthy,
+                    client_ip.as_ref(),
+                );
                 Ok(NamingResult::InstanceList(list))
             }
             NamingCmd::QueryInstancePage {
@@ -1581,13 +1798,18 @@ impl Handler<NamingCmd> for NamingActor {
                     page_index,
                 )))
             }
-            NamingCmd::QueryListString(service_key, cluster_str, only_healthy, addr) => {
+            NamingCmd::QueryListString(service_key, cluster_str, only_healthy, addr, client_ip) => {
                 //println!("QUERY_LIST_STRING addr: {:?}",&addr);
                 let cluster_names = NamingUtils::split_filters(&cluster_str);
                 if let Some(addr) = addr {
                     self.update_listener(&service_key, &cluster_names, addr, only_healthy);
                 }
-                let data = self.get_instance_list_string(&service_key, cluster_str, only_healthy);
+                let data = self.get_instance_list_string(
+                    &service_key,
+                    cluster_str,
+                    only_healthy,
+                    client_ip.as_ref(),
+                );
                 Ok(NamingResult::InstanceListString(data))
             }
             NamingCmd::QueryServiceInfo(service_key, cluster_str, only_healthy) => {
@@ -1624,8 +1846,13 @@ impl Handler<NamingCmd> for NamingActor {
             NamingCmd::NotifyListener(service_key, id) => {
                 if let Some(listener_addr) = self.listener_addr.as_ref() {
                     let map = self.get_instance_map(&service_key, vec![], false);
+                    let selector = self
+                        .service_map
+                        .get(&service_key)
+                        .and_then(|s| s.selector.clone());
                     //notify listener
-                    let msg = NamingListenerCmd::Notify(service_key, "".to_string(), map, id);
+                    let msg =
+                        NamingListenerCmd::Notify(service_key, "".to_string(), map, selector, id);
                     listener_addr.do_send(msg);
                 }
                 Ok(NamingResult::NULL)
@@ -1643,11 +1870,21 @@ impl Handler<NamingCmd> for NamingActor {
                 Ok(NamingResult::NULL)
             }
             NamingCmd::RemoveClient(client_id) => {
+                self.fuzzy_watcher.remove_client(&client_id);
                 self.subscriber.remove_client_subscribe(client_id.clone());
                 self.remove_client_instance(&client_id);
                 self.notify_cluster_remove_client_id(client_id);
                 Ok(NamingResult::NULL)
             }
+            NamingCmd::FuzzyWatch(param, client_id) => {
+                if param.watch {
+                    let keys = self.service_map.keys();
+                    self.fuzzy_watcher.watch(client_id, *param, keys)?;
+                } else {
+                    self.fuzzy_watcher.cancel_watch(&client_id, &param.pattern);
+                }
+                Ok(NamingResult::NULL)
+            }
             NamingCmd::RemoveClientsFromCluster(client_ids) => {
                 for client_id in client_ids {
                     self.subscriber.remove_client_subscribe(client_id.clone());
@@ -1685,6 +1922,13 @@ impl Handler<NamingCmd> for NamingActor {
                 self.remove_empty_service(service_key)?;
                 Ok(NamingResult::NULL)
             }
+            NamingCmd::QueryDnsInstanceList(key) => {
+                let list = match self.find_service_key_ignore_case(&key) {
+                    Some(key) => self.get_instance_list(&key, "", true, None),
+                    None => vec![],
+                };
+                Ok(NamingResult::InstanceList(list))
+            }
             NamingCmd::QueryAllInstanceList(key) => {
                 if let Some(service) = self.service_map.get(&key) {
                     Ok(NamingResult::InstanceList(service.get_instance_list(
@@ -1696,9 +1940,9 @@ impl Handler<NamingCmd> for NamingActor {
                     Ok(NamingResult::InstanceList(vec![]))
                 }
             }
-            NamingCmd::SelectOneInstance(service_key) => {
+            NamingCmd::SelectOneInstance(service_key, client_ip) => {
                 let v = if let Some(service) = self.service_map.get(&service_key) {
-                    service.select_one_instance(true, true)
+                    service.select_one_instance(true, true, client_ip.as_ref())
                 } else {
                     None
                 };
@@ -1711,6 +1955,22 @@ impl Handler<NamingCmd> for NamingActor {
                 }
                 Ok(NamingResult::ClientInstanceCount(client_instance_count))
             }
+            NamingCmd::QueryClientSubscribeCount => Ok(NamingResult::ClientSubscribeCount(
+                self.subscriber.get_client_key_sizes(),
+            )),
+            NamingCmd::QueryClientInstanceList(client_id) => {
+                let list = if let Some(keys) = self.client_instance_set.get(&client_id) {
+                    keys.iter()
+                        .filter_map(|k| self.get_instance(&k.get_service_key(), &k.get_short_key()))
+                        .collect()
+                } else {
+                    vec![]
+                };
+                Ok(NamingResult::InstanceList(list))
+            }
+            NamingCmd::QueryClientSubscribeKeys(client_id) => Ok(NamingResult::ServiceKeyList(
+                self.subscriber.get_client_subscribe_keys(&client_id),
+            )),
             NamingCmd::QuerySnapshot(ranges) => {
                 let res = self.build_snapshot_data(ranges);
                 Ok(NamingResult::Snapshot(res))
@@ -1770,6 +2030,17 @@ impl Handler<NamingCmd> for NamingActor {
                 let data = self.get_service_metadata_list();
                 Ok(NamingResult::AllServiceInstanceMetaData(data))
             }
+            NamingCmd::SetNamespaceQuota(namespace_id, quota) => {
+                self.set_namespace_quota(namespace_id, quota);
+                Ok(NamingResult::NULL)
+            }
+            NamingCmd::QueryNamespaceUsage(namespace_id) => {
+                let (service_count, max_instance_count) = self.get_namespace_usage(&namespace_id);
+                Ok(NamingResult::NamespaceUsage {
+                    service_count,
+                    max_instance_count,
+                })
+            }
         }
     }
 }
@@ -1822,10 +2093,10 @@ async fn query_healthy_instances() {
     }
 
     println!("-------------");
-    let items = naming.get_instance_list(&key, "", true);
+    let items = naming.get_instance_list(&key, "", true, None);
     assert!(!items.is_empty());
     println!("DEFUALT list:{}", serde_json::to_string(&items).unwrap());
-    let items = naming.get_instance_list(&key, "", true);
+    let items = naming.get_instance_list(&key, "", true, None);
     assert!(!items.is_empty());
     println!(
         "empty cluster list:{}",
@@ -1834,7 +2105,7 @@ async fn query_healthy_instances() {
     tokio::time::sleep(Duration::from_millis(2100)).await;
     naming.time_check();
     println!("-------------");
-    let items = naming.get_instance_list(&key, "", false);
+    let items = naming.get_instance_list(&key, "", false, None);
     assert!(!items.is_empty());
     println!(
         "empty cluster list:{}",
@@ -1843,7 +2114,7 @@ async fn query_healthy_instances() {
     tokio::time::sleep(Duration::from_millis(2100)).await;
     naming.time_check();
     println!("-------------");
-    let items = naming.get_instance_list(&key, "", false);
+    let items = naming.get_instance_list(&key, "", false, None);
     assert!(items.is_empty());
     println!(
         "empty cluster list:{}",
@@ -1900,3 +2171,74 @@ fn test_remove_has_instance_service() {
     assert!(naming.remove_empty_service(service_key.clone()).is_ok());
     assert!(naming.namespace_index.service_size == 0);
 }
+
+#[test]
+fn test_batch_register_quota() {
+    let build_instance = |service_name: &str, port: u32| {
+        let mut instance = Instance::new("127.0.0.1".to_owned(), port);
+        instance.namespace_id = Arc::new("public".to_owned());
+        instance.service_name = Arc::new(service_name.to_owned());
+        instance.group_name = Arc::new("DEFAULT_GROUP".to_owned());
+        instance.cluster_name = "DEFAULT".to_owned();
+        instance.init();
+        instance
+    };
+    let mut naming = NamingActor::new();
+    let quota = NamespaceQuota {
+        max_service_count: Some(2),
+        max_instance_per_service: Some(2),
+        ..Default::default()
+    };
+    naming.set_namespace_quota(Arc::new("public".to_owned()), Some(quota));
+    let instance = build_instance("foo", 8080);
+    naming.update_instance(&instance.get_service_key(), instance, None, false, None);
+
+    //已存在的实例不占用配额
+    let batch = [
+        build_instance("foo", 8080),
+        build_instance("foo", 8081),
+        build_instance("bar", 8080),
+    ];
+    let list: Vec<&Instance> = batch.iter().collect();
+    assert!(naming.check_namespace_quota(&list).is_ok());
+
+    //整批超出单服务实例数
+    let batch = [build_instance("foo", 8081), build_instance("foo", 8082)];
+    let list: Vec<&Instance> = batch.iter().collect();
+    assert!(naming.check_namespace_quota(&list).is_err());
+
+    //整批超出服务数
+    let batch = [build_instance("bar", 8080), build_instance("baz", 8080)];
+    let list: Vec<&Instance> = batch.iter().collect();
+    assert!(naming.check_namespace_quota(&list).is_err());
+}
+
+#[test]
+fn test_find_service_key_ignore_case() {
+    let build_instance = |service_name: &str| {
+        let mut instance = Instance::new("127.0.0.1".to_owned(), 8080);
+        instance.namespace_id = Arc::new("public".to_owned());
+        instance.service_name = Arc::new(service_name.to_owned());
+        instance.group_name = Arc::new("DEFAULT_GROUP".to_owned());
+        instance.cluster_name = "DEFAULT".to_owned();
+        instance.init();
+        instance
+    };
+    let mut naming = NamingActor::new();
+    let instance = build_instance("UserApi");
+    naming.update_instance(&instance.get_service_key(), instance, None, false, None);
+    let key = ServiceKey::new("public", "DEFAULT_GROUP", "UserApi");
+    let query_key = ServiceKey::new("PUBLIC", "default_group", "userapi");
+    assert_eq!(naming.find_service_key_ignore_case(&key), Some(key.clone()));
+    assert_eq!(
+        naming.find_service_key_ignore_case(&query_key),
+        Some(key.clone())
+    );
+    //忽略大小写后匹配多个服务时无法确定
+    let instance = build_instance("userapi");
+    naming.update_instance(&instance.get_service_key(), instance, None, false, None);
+    assert!(naming
+        .find_service_key_ignore_case(&ServiceKey::new("public", "DEFAULT_GROUP", "USERAPI"))
+        .is_none());
+    assert_eq!(naming.find_service_key_ignore_case(&key), Some(key));
+}
@@ -0,0 +1,454 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix::prelude::*;
+use bean_factory::{bean, BeanFactory, FactoryData, Inject};
+use hickory_proto::op::{Message, MessageType, OpCode, ResponseCode};
+use hickory_proto::rr::rdata::{A, AAAA, SRV};
+use hickory_proto::rr::{Name, RData, Record, RecordType};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+
+use crate::common::AppSysConfig;
+use crate::naming::core::{NamingActor, NamingCmd, NamingResult};
+use crate::naming::model::{Instance, ServiceKey};
+use crate::now_millis;
+
+const MAX_DATAGRAM_SIZE: usize = 4096;
+const SRV_WEIGHT_SCALE: f32 = 100f32;
+
+#[derive(Debug, Clone)]
+pub struct NamingDnsConfig {
+    pub addr: String,
+    pub suffix: Arc<String>,
+    pub ttl: u32,
+    pub changed_ttl: u32,
+}
+
+impl NamingDnsConfig {
+    pub fn new(sys_config: &AppSysConfig) -> Self {
+        Self {
+            addr: format!(
+                "{}:{}",
+                &sys_config.naming_dns_host, sys_config.naming_dns_port
+            ),
+            suffix: sys_config.naming_dns_suffix.clone(),
+            ttl: sys_config.naming_dns_ttl,
+            changed_ttl: sys_config.naming_dns_changed_ttl,
+        }
+    }
+}
+
+///
+/// DNS查询的服务域名
+/// service.group.namespace.后缀 查询服务实例
+/// ip.service.group.namespace.后缀 查询SRV记录中的实例主机
+/// 按DNS规范名称不区分大小写，查询实例时忽略大小写匹配服务
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnsServiceName {
+    pub service_key: ServiceKey,
+    pub host: Option<IpAddr>,
+}
+
+impl DnsServiceName {
+    pub fn parse(name: &Name, suffix: &str) -> Option<Self> {
+        let mut labels: Vec<String> = name
+            .iter()
+            .map(|l| String::from_utf8_lossy(l).to_string())
+            .collect();
+        let suffix_labels: Vec<&str> = suffix.split('.').filter(|v| !v.is_empty()).collect();
+        if labels.len() < suffix_labels.len() + 3 {
+            return None;
+        }
+        let service_len = labels.len() - suffix_labels.len();
+        for (label, suffix_label) in labels[service_len..].iter().zip(suffix_labels.iter()) {
+            if !label.eq_ignore_ascii_case(suffix_label) {
+                return None;
+            }
+        }
+        labels.truncate(service_len);
+        let mut host = None;
+        if labels.len() > 3 {
+            host = Self::parse_host_label(&labels[0]);
+            if host.is_some() {
+                labels.remove(0);
+            }
+        }
+        let namespace_id = labels.pop()?;
+        let group_name = labels.pop()?;
+        let service_name = labels.join(".");
+        Some(Self {
+            service_key: ServiceKey::new(&namespace_id, &group_name, &service_name),
+            host,
+        })
+    }
+
+    fn parse_host_label(label: &str) -> Option<IpAddr> {
+        if let Ok(ip) = label.replace('-', ".").parse::<IpAddr>() {
+            return Some(ip);
+        }
+        label.replace('-', ":").parse::<IpAddr>().ok()
+    }
+
+    pub fn build_host_label(ip: &IpAddr) -> String {
+        ip.to_string().replace(['.', ':'], "-")
+    }
+}
+
+///
+/// 服务发现DNS接口
+/// 从NamingActor查询健康实例，服务变更后一个TTL周期内返回短TTL
+#[bean(inject)]
+pub struct NamingDnsServer {
+    config: NamingDnsConfig,
+    naming_addr: Option<Addr<NamingActor>>,
+    /// 服务最近一次变更时间
+    changed_map: HashMap<ServiceKey, u64>,
+}
+
+impl NamingDnsServer {
+    pub fn new(config: NamingDnsConfig) -> Self {
+        Self {
+            config,
+            naming_addr: None,
+            changed_map: HashMap::new(),
+        }
+    }
+
+    /// 查询名不区分大小写，变更记录统一按小写服务名索引
+    fn ttl_key(key: &ServiceKey) -> ServiceKey {
+        ServiceKey::new(
+            &key.namespace_id.to_ascii_lowercase(),
+            &key.group_name.to_ascii_lowercase(),
+            &key.service_name.to_ascii_lowercase(),
+        )
+    }
+
+    fn get_ttl(&self, key: &ServiceKey) -> u32 {
+        if let Some(changed_time) = self.changed_map.get(&Self::ttl_key(key)) {
+            if now_millis() < changed_time + self.config.ttl as u64 * 1000 {
+                return self.config.changed_ttl;
+            }
+        }
+        self.config.ttl
+    }
+
+    fn service_changed(&mut self, key: ServiceKey) {
+        let now = now_millis();
+        let expire_time = now.saturating_sub(self.config.ttl as u64 * 1000);
+        self.changed_map.retain(|_, v| *v > expire_time);
+        self.changed_map.insert(Self::ttl_key(&key), now);
+    }
+
+    fn start_udp(&self, ctx: &mut Context<Self>) {
+        let addr = self.config.addr.clone();
+        let dns_addr = ctx.address();
+        async move {
+            let socket = match UdpSocket::bind(&addr).await {
+                Ok(v) => Arc::new(v),
+                Err(err) => {
+                    log::error!("naming dns udp bind error,{},{}", &addr, err);
+                    return;
+                }
+            };
+            log::info!("naming dns udp listen on {}", &addr);
+            let mut buf = vec![0u8; MAX_DATAGRAM_SIZE];
+            while let Ok((len, peer)) = socket.recv_from(&mut buf).await {
+                let socket = socket.clone();
+                let dns_addr = dns_addr.clone();
+                let data = buf[..len].to_vec();
+                actix::spawn(async move {
+                    if let Ok(Ok(data)) = dns_addr.send(NamingDnsCmd::Query(data, true)).await {
+                        socket.send_to(&data, peer).await.ok();
+                    }
+                });
+            }
+        }
+        .into_actor(self)
+        .map(|_, _, _| {})
+        .spawn(ctx);
+    }
+
+    fn start_tcp(&self, ctx: &mut Context<Self>) {
+        let addr = self.config.addr.clone();
+        let dns_addr = ctx.address();
+        async move {
+            let listener = match TcpListener::bind(&addr).await {
+                Ok(v) => v,
+                Err(err) => {
+                    log::error!("naming dns tcp bind error,{},{}", &addr, err);
+                    return;
+                }
+            };
+            log::info!("naming dns tcp listen on {}", &addr);
+            while let Ok((stream, _)) = listener.accept().await {
+                let dns_addr = dns_addr.clone();
+                actix::spawn(async move {
+                    Self::handle_tcp_stream(stream, dns_addr).await.ok();
+                });
+            }
+        }
+        .into_actor(self)
+        .map(|_, _, _| {})
+        .spawn(ctx);
+    }
+
+    async fn handle_tcp_stream(
+        mut stream: TcpStream,
+        dns_addr: Addr<NamingDnsServer>,
+    ) -> anyhow::Result<()> {
+        loop {
+            let len = match tokio::time::timeout(Duration::from_secs(10), stream.read_u16()).await {
+                Ok(Ok(len)) => len as usize,
+                _ => return Ok(()),
+            };
+            let mut data = vec![0u8; len];
+            stream.read_exact(&mut data).await?;
+            let data = dns_addr.send(NamingDnsCmd::Query(data, false)).await??;
+            stream.write_u16(data.len() as u16).await?;
+            stream.write_all(&data).await?;
+        }
+    }
+
+    fn build_records(
+        name: &Name,
+        query_type: RecordType,
+        dns_name: &DnsServiceName,
+        instances: &[Arc<Instance>],
+        ttl: u32,
+    ) -> (Vec<Record>, Vec<Record>) {
+        let mut answers = vec![];
+        let mut additionals = vec![];
+        for instance in instances {
+            let ip = instance.ip.parse::<IpAddr>().ok();
+            if let Some(host) = dns_name.host.as_ref() {
+                if ip.as_ref() != Some(host) {
+                    continue;
+                }
+            }
+            match query_type {
+                RecordType::A | RecordType::AAAA | RecordType::ANY => {
+                    if let Some(record) = Self::build_ip_record(name.clone(), ip, query_type, ttl) {
+                        answers.push(record);
+                    }
+                }
+                RecordType::SRV if dns_name.host.is_none() => {
+                    let target = match ip.as_ref() {
+                        Some(ip) => Name::from_ascii(DnsServiceName::build_host_label(ip))
+                            .and_then(|v| v.append_domain(name)),
+                        None => Name::from_ascii(instance.ip.as_str()),
+                    };
+                    let target = match target {
+                        Ok(v) => v,
+                        Err(_) => continue,
+                    };
+                    let weight = (instance.weight * SRV_WEIGHT_SCALE)
+                        .round()
+                        .clamp(0f32, u16::MAX as f32) as u16;
+                    // 端口超出范围的实例不生成SRV记录
+                    let port = match u16::try_from(instance.port) {
+                        Ok(v) => v,
+                        Err(_) => continue,
+                    };
+                    let srv = SRV::new(0, weight, port, target.clone());
+                    answers.push(Record::from_rdata(name.clone(), ttl, RData::SRV(srv)));
+                    if let Some(record) = Self::build_ip_record(target, ip, RecordType::ANY, ttl) {
+                        additionals.push(record);
+                    }
+                }
+                _ => {}
+            }
+        }
+        (answers, additionals)
+    }
+
+    fn build_ip_record(
+        name: Name,
+        ip: Option<IpAddr>,
+        query_type: RecordType,
+        ttl: u32,
+    ) -> Option<Record> {
+        match (ip?, query_type) {
+            (IpAddr::V4(ip), RecordType::A | RecordType::ANY) => {
+                Some(Record::from_rdata(name, ttl, RData::A(A(ip))))
+            }
+            (IpAddr::V6(ip), RecordType::AAAA | RecordType::ANY) => {
+                Some(Record::from_rdata(name, ttl, RData::AAAA(AAAA(ip))))
+            }
+            _ => None,
+        }
+    }
+
+    fn handle_query(
+        &mut self,
+        data: Vec<u8>,
+        is_udp: bool,
+    ) -> ResponseActFuture<Self, anyhow::Result<Vec<u8>>> {
+        let request = match Message::from_vec(&data) {
+            Ok(v) => v,
+            Err(err) => {
+                return Box::pin(
+                    async move { Err(anyhow::anyhow!("dns request is invalid,{}", err)) }
+                        .into_actor(self),
+                )
+            }
+        };
+        let mut response = Message::new();
+        response
+            .set_id(request.id())
+            .set_message_type(MessageType::Response)
+            .set_op_code(OpCode::Query)
+            .set_recursion_desired(request.recursion_desired())
+            .set_authoritative(true);
+        let max_payload = request.max_payload() as usize;
+        let query = match (request.op_code(), request.queries().first()) {
+            (OpCode::Query, Some(query)) => query.clone(),
+            _ => {
+                response.set_response_code(ResponseCode::NotImp);
+                return Box::pin(async move { Ok(response.to_vec()?) }.into_actor(self));
+            }
+        };
+        response.add_query(query.clone());
+        let dns_name = match DnsServiceName::parse(query.name(), &self.config.suffix) {
+            Some(v) => v,
+            None => {
+                response.set_response_code(ResponseCode::NXDomain);
+                return Box::pin(async move { Ok(response.to_vec()?) }.into_actor(self));
+            }
+        };
+        let ttl = self.get_ttl(&dns_name.service_key);
+        let naming_addr = self.naming_addr.clone();
+        let fut = async move {
+            let instances = match naming_addr {
+                Some(naming_addr) => {
+                    let cmd = NamingCmd::QueryDnsInstanceList(dns_name.service_key.clone());
+                    match naming_addr.send(cmd).await?? {
+                        NamingResult::InstanceList(list) => list,
+                        _ => vec![],
+                    }
+                }
+                None => vec![],
+            };
+            if instances.is_empty() {
+                response.set_response_code(ResponseCode::NXDomain);
+                return Ok(response.to_vec()?);
+            }
+            let (answers, additionals) =
+                Self::build_records(query.name(), query.query_type(), &dns_name, &instances, ttl);
+            response.add_answers(answers);
+            response.add_additionals(additionals);
+            let data = response.to_vec()?;
+            if is_udp && data.len() > max_payload {
+                response.take_answers();
+                response.take_additionals();
+                response.set_truncated(true);
+                return Ok(response.to_vec()?);
+            }
+            Ok(data)
+        }
+        .into_actor(self);
+        Box::pin(fut)
+    }
+}
+
+impl Actor for NamingDnsServer {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        log::info!("NamingDnsServer started");
+        self.start_udp(ctx);
+        self.start_tcp(ctx);
+    }
+}
+
+impl Inject for NamingDnsServer {
+    type Context = Context<Self>;
+
+    fn inject(
+        &mut self,
+        factory_data: FactoryData,
+        _factory: BeanFactory,
+        _ctx: &mut Self::Context,
+    ) {
+        self.naming_addr = factory_data.get_actor();
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "anyhow::Result<Vec<u8>>")]
+pub enum NamingDnsCmd {
+    /// 请求报文，是否为udp请求
+    Query(Vec<u8>, bool),
+}
+
+///
+/// 服务实例变更通知，用于计算DNS记录TTL
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct NamingDnsServiceChanged(pub ServiceKey);
+
+impl Handler<NamingDnsCmd> for NamingDnsServer {
+    type Result = ResponseActFuture<Self, anyhow::Result<Vec<u8>>>;
+
+    fn handle(&mut self, msg: NamingDnsCmd, _ctx: &mut Context<Self>) -> Self::Result {
+        match msg {
+            NamingDnsCmd::Query(data, is_udp) => self.handle_query(data, is_udp),
+        }
+    }
+}
+
+impl Handler<NamingDnsServiceChanged> for NamingDnsServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: NamingDnsServiceChanged, _ctx: &mut Context<Self>) -> Self::Result {
+        self.service_changed(msg.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_dns_service_name() {
+        let name = Name::from_ascii("user-api.DEFAULT_GROUP.public.rnacos.").unwrap();
+        let dns_name = DnsServiceName::parse(&name, "rnacos").unwrap();
+        assert_eq!(
+            dns_name.service_key,
+            ServiceKey::new("public", "DEFAULT_GROUP", "user-api")
+        );
+        assert!(dns_name.host.is_none());
+
+        let name = Name::from_ascii("10-0-0-1.user-api.DEFAULT_GROUP.dev.svc.local.").unwrap();
+        let dns_name = DnsServiceName::parse(&name, "svc.local").unwrap();
+        assert_eq!(dns_name.service_key.namespace_id.as_str(), "dev");
+        assert_eq!(dns_name.host, Some("10.0.0.1".parse().unwrap()));
+
+        let name = Name::from_ascii("user-api.DEFAULT_GROUP.rnacos.").unwrap();
+        assert!(DnsServiceName::parse(&name, "rnacos").is_none());
+        let name = Name::from_ascii("a.b.c.example.com.").unwrap();
+        assert!(DnsServiceName::parse(&name, "rnacos").is_none());
+    }
+
+    #[test]
+    fn skip_invalid_srv_port() {
+        let name = Name::from_ascii("user-api.DEFAULT_GROUP.public.rnacos.").unwrap();
+        let dns_name = DnsServiceName::parse(&name, "rnacos").unwrap();
+        let mut valid = Instance::new("10.0.0.1".to_owned(), 8080);
+        valid.weight = 1f32;
+        let mut invalid = Instance::new("10.0.0.2".to_owned(), 70000);
+        invalid.weight = 1f32;
+        let instances = vec![Arc::new(valid), Arc::new(invalid)];
+        let (answers, additionals) =
+            NamingDnsServer::build_records(&name, RecordType::SRV, &dns_name, &instances, 10);
+        assert_eq!(answers.len(), 1);
+        assert_eq!(additionals.len(), 1);
+        match answers[0].data() {
+            Some(RData::SRV(srv)) => assert_eq!(srv.port(), 8080),
+            _ => panic!("expect srv record"),
+        }
+    }
+}
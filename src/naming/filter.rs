@@ -1,3 +1,4 @@
+use std::net::IpAddr;
 use std::sync::Arc;
 
 use super::{
@@ -15,8 +16,13 @@ impl InstanceFilterUtils {
         all_instances: Vec<Arc<Instance>>,
         metadata: Option<ServiceMetadata>,
         filter_headlthy: bool,
+        client_ip: Option<&IpAddr>,
     ) -> Vec<Arc<Instance>> {
+        let mut all_instances = all_instances;
         if let Some(metadata) = metadata {
+            if let Some(selector) = metadata.selector.as_ref() {
+                all_instances = selector.select(all_instances, client_ip);
+            }
             let original_total = all_instances.len();
             // all_instances = from metadata select
             let mut healthy_count = 0;
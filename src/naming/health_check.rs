@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// 服务健康检查配置在服务元数据中保存的key，值为配置json
+pub const SERVICE_HEALTH_CHECK_METADATA_KEY: &str = "__healthCheck";
+
+const DEFAULT_INTERVAL_SECONDS: u32 = 10;
+const MIN_INTERVAL_SECONDS: u32 = 2;
+const DEFAULT_TIMEOUT_MILLIS: u64 = 2000;
+const DEFAULT_HTTP_PATH: &str = "/";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthCheckType {
+    Tcp,
+    Http,
+    Grpc,
+    /// 不主动检测
+    None,
+}
+
+///
+/// 永久实例主动健康检查配置
+/// tcp: {"type":"tcp","interval":10,"timeout":2000,"rise":1,"fall":2}
+/// http: {"type":"http","path":"/actuator/health","expectedStatus":[200],"headers":{"Host":"app"}}
+/// grpc: {"type":"grpc","service":"app.Service"}
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceHealthCheck {
+    #[serde(rename = "type")]
+    pub check_type: HealthCheckType,
+    /// 检测间隔，单位秒
+    #[serde(default = "default_interval")]
+    pub interval: u32,
+    /// 单次检测超时，单位毫秒
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
+    /// 连续成功次数达到rise后标记为健康
+    #[serde(default = "default_threshold")]
+    pub rise: u32,
+    /// 连续失败次数达到fall后标记为不健康
+    #[serde(default = "default_threshold")]
+    pub fall: u32,
+    /// 检测端口，为空时使用实例端口
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// http检测使用https
+    #[serde(default)]
+    pub https: bool,
+    #[serde(default)]
+    pub path: Option<String>,
+    /// http检测期望的状态码，为空时2xx视为成功
+    #[serde(default)]
+    pub expected_status: Vec<u16>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// grpc.health.v1检测的服务名，为空时检测整个服务器
+    #[serde(default)]
+    pub service: Option<String>,
+}
+
+fn default_interval() -> u32 {
+    DEFAULT_INTERVAL_SECONDS
+}
+
+fn default_timeout() -> u64 {
+    DEFAULT_TIMEOUT_MILLIS
+}
+
+fn default_threshold() -> u32 {
+    1
+}
+
+impl ServiceHealthCheck {
+    ///
+    /// 解析健康检查配置json；type为none或内容为空时返回None
+    pub fn parse(value: &str) -> anyhow::Result<Option<Self>> {
+        if value.trim().is_empty() {
+            return Ok(None);
+        }
+        let mut check: Self = serde_json::from_str(value)
+            .map_err(|e| anyhow::anyhow!("service health check is invalid json, {}", e))?;
+        if check.check_type == HealthCheckType::None {
+            return Ok(None);
+        }
+        if check.timeout == 0 {
+            return Err(anyhow::anyhow!("service health check timeout is invalid"));
+        }
+        check.interval = check.interval.max(MIN_INTERVAL_SECONDS);
+        check.rise = check.rise.max(1);
+        check.fall = check.fall.max(1);
+        Ok(Some(check))
+    }
+
+    pub fn timeout_duration(&self) -> Duration {
+        Duration::from_millis(self.timeout)
+    }
+
+    pub fn get_path(&self) -> &str {
+        match self.path.as_ref() {
+            Some(path) if !path.is_empty() => path,
+            _ => DEFAULT_HTTP_PATH,
+        }
+    }
+
+    pub fn is_expected_status(&self, status: u16) -> bool {
+        if self.expected_status.is_empty() {
+            (200..300).contains(&status)
+        } else {
+            self.expected_status.contains(&status)
+        }
+    }
+}
+
+///
+/// 实例连续检测结果计数，用于rise/fall阈值判断
+#[derive(Debug, Clone, Default)]
+pub struct HealthCheckCounter {
+    pub success_count: u32,
+    pub fail_count: u32,
+}
+
+impl HealthCheckCounter {
+    ///
+    /// 记录一次检测结果，返回需要变更的健康状态
+    pub fn record(&mut self, success: bool, rise: u32, fall: u32) -> Option<bool> {
+        if success {
+            self.fail_count = 0;
+            self.success_count = self.success_count.saturating_add(1);
+            if self.success_count >= rise {
+                return Some(true);
+            }
+        } else {
+            self.success_count = 0;
+            self.fail_count = self.fail_count.saturating_add(1);
+            if self.fail_count >= fall {
+                return Some(false);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_health_check() {
+        let check = ServiceHealthCheck::parse(
+            r#"{"type":"http","path":"/health","expectedStatus":[200,204],"rise":2,"fall":3}"#,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(check.check_type, HealthCheckType::Http);
+        assert_eq!(check.interval, DEFAULT_INTERVAL_SECONDS);
+        assert!(check.is_expected_status(204));
+        assert!(!check.is_expected_status(201));
+        assert!(ServiceHealthCheck::parse(r#"{"type":"none"}"#)
+            .unwrap()
+            .is_none());
+        assert!(ServiceHealthCheck::parse(r#"{"type":"udp"}"#).is_err());
+
+        let mut counter = HealthCheckCounter::default();
+        assert_eq!(counter.record(true, check.rise, check.fall), None);
+        assert_eq!(counter.record(true, check.rise, check.fall), Some(true));
+        assert_eq!(counter.record(false, check.rise, check.fall), None);
+        assert_eq!(counter.record(false, check.rise, check.fall), None);
+        assert_eq!(counter.record(false, check.rise, check.fall), Some(false));
+    }
+}
@@ -11,6 +11,7 @@ use crate::utils::gz_encode;
 use bean_factory::{bean, Inject};
 use std::cmp::max;
 use std::collections::HashSet;
+use std::net::IpAddr;
 use std::sync::Arc;
 use std::time::Duration;
 use std::{collections::HashMap, net::SocketAddr};
@@ -22,6 +23,7 @@ use crate::{now_millis, TimeoutSet};
 
 use super::core::{NamingActor, NamingCmd};
 use super::model::{Instance, ServiceKey};
+use super::selector::ServiceSelector;
 use super::udp_actor::{UdpSenderCmd, UdpWorker};
 
 #[derive(Debug)]
@@ -108,15 +110,22 @@ impl ListenerValue {
         cluster_names: Vec<String>,
         only_healthy: bool,
         instances: &HashMap<String, Vec<Arc<Instance>>>,
+        selector: Option<&ServiceSelector>,
+        client_ip: &IpAddr,
     ) -> String {
         let clusters = cluster_names.join(",");
         let list = Self::get_instance_list(cluster_names, only_healthy, instances);
+        if let Some(selector) = selector {
+            let list = selector.select(list.into_iter().cloned().collect(), Some(client_ip));
+            return QueryListResult::get_instance_list_string(clusters, key, list);
+        }
         QueryListResult::get_ref_instance_list_string(clusters, key, list)
     }
 
     fn build_msg(
         service_key: &ServiceKey,
         instances: &HashMap<String, Vec<Arc<Instance>>>,
+        selector: Option<&ServiceSelector>,
         item: &ListenerItem,
     ) -> Vec<u8> {
         let mut cluster_names = vec![];
@@ -136,6 +145,8 @@ impl ListenerValue {
             cluster_names,
             item.only_healthy,
             instances,
+            selector,
+            &item.listener_addr.ip(),
         );
         response.insert("data", res);
         let msg_str = serde_json::to_string(&response).unwrap_or_default();
@@ -147,23 +158,34 @@ impl ListenerValue {
         service_key: &ServiceKey,
         sign: String,
         instances: &HashMap<String, Vec<Arc<Instance>>>,
+        selector: Option<&ServiceSelector>,
         period: u64,
     ) -> HashMap<String, Arc<Vec<u8>>> {
         let mut cache = HashMap::new();
         for item in self.items.values() {
-            if !cache.contains_key(&item.clusters_key) {
-                let msg = Self::build_msg(service_key, instances, item);
-                cache.insert(item.clusters_key.to_owned(), Arc::new(msg));
-            }
+            let cache_key = Self::get_cache_key(item, selector);
+            cache.entry(cache_key).or_insert_with(|| {
+                Arc::new(Self::build_msg(service_key, instances, selector, item))
+            });
         }
         cache
     }
 
+    /// 服务设置了选择器时，不同客户端ip的推送内容可能不同
+    fn get_cache_key(item: &ListenerItem, selector: Option<&ServiceSelector>) -> String {
+        if selector.is_some() {
+            format!("{}#{}", &item.clusters_key, item.listener_addr.ip())
+        } else {
+            item.clusters_key.to_owned()
+        }
+    }
+
     fn notify(
         &mut self,
         service_key: ServiceKey,
         sign: String,
         instances: &HashMap<String, Vec<Arc<Instance>>>,
+        selector: Option<&ServiceSelector>,
         period: u64,
         sender: &Addr<UdpWorker>,
     ) -> Vec<SocketAddr> {
@@ -179,9 +201,9 @@ impl ListenerValue {
         for key in &removes {
             self.items.remove(key);
         }
-        let cache = self.build_cache(&service_key, sign, instances, period);
+        let cache = self.build_cache(&service_key, sign, instances, selector, period);
         for item in self.items.values_mut() {
-            if let Some(data) = cache.get(&item.clusters_key) {
+            if let Some(data) = cache.get(&Self::get_cache_key(item, selector)) {
                 let msg = UdpSenderCmd::new(data.clone(), item.listener_addr.to_owned());
                 sender.do_send(msg);
                 item.last_modified = now;
@@ -283,12 +305,20 @@ impl InnerNamingListener {
         service_key: ServiceKey,
         sign: String,
         instances: HashMap<String, Vec<Arc<Instance>>>,
+        selector: Option<Arc<ServiceSelector>>,
     ) {
         let listener_key = Self::get_listener_key(&service_key);
         let mut is_empty = false;
         let mut clients = vec![];
         if let Some(value) = self.listeners.get_mut(&listener_key) {
-            clients = value.notify(service_key, sign, &instances, self.period, &self.sender);
+            clients = value.notify(
+                service_key,
+                sign,
+                &instances,
+                selector.as_deref(),
+                self.period,
+                &self.sender,
+            );
             if value.is_empty() {
                 is_empty = true;
             }
@@ -379,7 +409,13 @@ impl Supervised for InnerNamingListener {
 pub enum NamingListenerCmd {
     Add(ServiceKey, ListenerItem),
     Response(SocketAddr),
-    Notify(ServiceKey, String, HashMap<String, Vec<Arc<Instance>>>, u64),
+    Notify(
+        ServiceKey,
+        String,
+        HashMap<String, Vec<Arc<Instance>>>,
+        Option<Arc<ServiceSelector>>,
+        u64,
+    ),
     AddHeartbeat(ServiceKey, u64),
 }
 
@@ -400,9 +436,9 @@ impl Handler<NamingListenerCmd> for InnerNamingListener {
                 log::info!("naming-listener response,{:?}", &socket_addr);
                 self.client_response(&socket_addr);
             }
-            NamingListenerCmd::Notify(service_key, sign, instances, id) => {
+            NamingListenerCmd::Notify(service_key, sign, instances, selector, id) => {
                 log::info!("naming-listener notify,{:?},{}", &service_key, id);
-                self.notify(service_key, sign, instances);
+                self.notify(service_key, sign, instances, selector);
             }
             NamingListenerCmd::AddHeartbeat(service_key, id) => {
                 self.add_hb(service_key, id);
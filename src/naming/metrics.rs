@@ -1,7 +1,11 @@
 use crate::metrics::metrics_key::MetricsKey;
-use crate::metrics::model::{MetricsItem, MetricsQuery, MetricsRecord};
+use crate::metrics::model::{
+    LabeledMetricsItem, LabeledMetricsQuery, MetricsItem, MetricsQuery, MetricsRecord,
+};
 use crate::naming::core::NamingActor;
 use actix::Handler;
+use std::collections::BTreeMap;
+use std::sync::Arc;
 
 impl Handler<MetricsQuery> for NamingActor {
     type Result = anyhow::Result<Vec<MetricsItem>>;
@@ -89,3 +93,32 @@ impl Handler<MetricsQuery> for NamingActor {
         Ok(list)
     }
 }
+
+impl Handler<LabeledMetricsQuery> for NamingActor {
+    type Result = anyhow::Result<Vec<LabeledMetricsItem>>;
+
+    fn handle(&mut self, _: LabeledMetricsQuery, _ctx: &mut Self::Context) -> Self::Result {
+        let mut instance_size_map: BTreeMap<Arc<String>, usize> = BTreeMap::new();
+        for (key, service) in &self.service_map {
+            *instance_size_map
+                .entry(key.namespace_id.clone())
+                .or_default() += service.instances.len();
+        }
+        let mut list = Vec::with_capacity(instance_size_map.len() * 2);
+        for (namespace, index) in &self.namespace_index.namespace_group {
+            list.push(LabeledMetricsItem::new(
+                MetricsKey::NamingNamespaceServiceSize,
+                vec![namespace.as_ref().to_owned()],
+                MetricsRecord::Gauge(index.get_service_count() as f32),
+            ));
+        }
+        for (namespace, size) in instance_size_map {
+            list.push(LabeledMetricsItem::new(
+                MetricsKey::NamingNamespaceInstanceSize,
+                vec![namespace.as_ref().to_owned()],
+                MetricsRecord::Gauge(size as f32),
+            ));
+        }
+        Ok(list)
+    }
+}
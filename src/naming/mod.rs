@@ -2,13 +2,17 @@ use std::collections::HashMap;
 
 pub mod api_model;
 pub mod core;
+pub mod dns;
 pub(crate) mod filter;
+pub mod health_check;
 pub mod instance_meta_manager;
 pub mod instance_meta_repository;
 pub mod listener;
 pub mod model;
 pub mod naming_delay_nofity;
+pub mod naming_fuzzy_watch;
 pub mod naming_subscriber;
+pub mod selector;
 pub mod service;
 pub mod transfer;
 pub mod udp_actor;
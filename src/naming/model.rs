@@ -220,6 +220,12 @@ pub struct ServiceDetailDto {
     pub metadata: Option<Arc<HashMap<String, String>>>,
     pub protect_threshold: Option<f32>,
     pub grpc_instance_count: Option<i32>,
+    /// 服务选择器json，None表示不变更，type为none时清除
+    #[serde(default)]
+    pub selector: Option<Arc<String>>,
+    /// 永久实例健康检查配置json，None表示不变更，type为none时清除
+    #[serde(default)]
+    pub health_check: Option<Arc<String>>,
 }
 
 impl ServiceDetailDto {
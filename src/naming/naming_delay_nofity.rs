@@ -9,10 +9,12 @@ use crate::{
     common::delay_notify::{DelayNotify, NotifyEvent},
     grpc::bistream_manage::{BiStreamManage, BiStreamManageCmd},
     now_millis,
+    webhook::core::{WebhookCmd, WebhookManager},
 };
 
 use super::{
     core::{NamingActor, NamingCmd, NamingResult},
+    dns::{NamingDnsServer, NamingDnsServiceChanged},
     model::{ServiceInfo, ServiceKey},
 };
 
@@ -22,10 +24,21 @@ pub struct NamingDelayEvent {
     pub client_id_set: HashSet<Arc<String>>,
     pub service_info: Option<ServiceInfo>,
     pub conn_manage: Option<Addr<BiStreamManage>>,
+    pub dns_addr: Option<Addr<NamingDnsServer>>,
+    pub webhook_addr: Option<Addr<WebhookManager>>,
 }
 
 impl NotifyEvent for NamingDelayEvent {
     fn on_event(self) -> anyhow::Result<()> {
+        if let Some(dns_addr) = self.dns_addr.as_ref() {
+            dns_addr.do_send(NamingDnsServiceChanged(self.key.clone()));
+        }
+        if let Some(webhook_addr) = self.webhook_addr.as_ref() {
+            webhook_addr.do_send(WebhookCmd::ServiceChanged(self.key.clone()));
+        }
+        if self.client_id_set.is_empty() {
+            return Ok(());
+        }
         if let (Some(conn_manage), Some(service_info)) =
             (self.conn_manage.as_ref(), self.service_info)
         {
@@ -42,6 +55,8 @@ impl NotifyEvent for NamingDelayEvent {
         self.service_info = other.service_info;
         self.client_id_set = other.client_id_set;
         self.conn_manage = other.conn_manage;
+        self.dns_addr = other.dns_addr;
+        self.webhook_addr = other.webhook_addr;
         Ok(())
     }
 }
@@ -51,6 +66,8 @@ pub struct DelayNotifyActor {
     inner_delay_notify: DelayNotify<ServiceKey, NamingDelayEvent>,
     conn_manage: Option<Addr<BiStreamManage>>,
     naming_addr: Option<Addr<NamingActor>>,
+    dns_addr: Option<Addr<NamingDnsServer>>,
+    webhook_addr: Option<Addr<WebhookManager>>,
     delay: u64,
 }
 
@@ -66,6 +83,8 @@ impl DelayNotifyActor {
             inner_delay_notify: Default::default(),
             conn_manage: None,
             naming_addr: None,
+            dns_addr: None,
+            webhook_addr: None,
             delay: 500,
         }
     }
@@ -91,6 +110,11 @@ impl DelayNotifyActor {
     ) {
         if let Some(naming_addr) = naming_addr {
             for mut event in events {
+                if event.client_id_set.is_empty() {
+                    //没有订阅者，只通知DNS接口与webhook
+                    event.on_event().ok();
+                    continue;
+                }
                 //println!("fill_event_data_and_notify, {:?}",&event.key);
                 let cmd = NamingCmd::QueryServiceInfo(event.key.clone(), "".to_owned(), true);
                 match naming_addr.send(cmd).await {
@@ -131,6 +155,8 @@ impl Inject for DelayNotifyActor {
     ) {
         self.conn_manage = factory_data.get_actor();
         self.naming_addr = factory_data.get_actor();
+        self.dns_addr = factory_data.get_actor();
+        self.webhook_addr = factory_data.get_actor();
         log::info!(" DelayNotifyActor inject complete");
     }
 }
@@ -162,6 +188,8 @@ impl Handler<DelayNotifyCmd> for DelayNotifyActor {
                     client_id_set,
                     service_info: None,
                     conn_manage: self.conn_manage.to_owned(),
+                    dns_addr: self.dns_addr.to_owned(),
+                    webhook_addr: self.webhook_addr.to_owned(),
                 };
                 self.inner_delay_notify
                     .add_event(self.delay, event.key.clone(), event)?;
@@ -0,0 +1,189 @@
+use std::{collections::HashSet, sync::Arc};
+
+use super::model::ServiceKey;
+use crate::common::fuzzy_watch::{
+    FuzzyWatchIndex, FuzzyWatchParam, FuzzyWatchPattern, ADD_SERVICE, DELETE_SERVICE,
+    FINISH_FUZZY_WATCH_INIT_NOTIFY, FUZZY_WATCH_DIFF_SYNC_NOTIFY, FUZZY_WATCH_INIT_NOTIFY,
+    FUZZY_WATCH_RESOURCE_CHANGED, FUZZY_WATCH_SYNC_BATCH_SIZE,
+};
+use crate::grpc::api_model::{
+    NamingFuzzyWatchChangeNotifyRequest, NamingFuzzyWatchContext, NamingFuzzyWatchSyncRequest,
+};
+use crate::grpc::bistream_manage::{BiStreamManage, BiStreamManageCmd, FuzzyWatchNotify};
+use actix::prelude::*;
+
+const DEFAULT_NAMESPACE: &str = "public";
+
+///
+/// 服务模糊监听，按模式匹配服务的新增、删除与实例变更
+#[derive(Default)]
+pub struct NamingFuzzyWatcher {
+    index: FuzzyWatchIndex,
+    conn_manage: Option<Addr<BiStreamManage>>,
+}
+
+impl NamingFuzzyWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_conn_manage(&mut self, conn_manage: Addr<BiStreamManage>) {
+        self.conn_manage = Some(conn_manage);
+    }
+
+    ///
+    /// 添加模糊监听，并把当前匹配的服务与客户端已有的服务差异同步给客户端
+    pub fn watch<'a>(
+        &mut self,
+        client_id: Arc<String>,
+        param: FuzzyWatchParam,
+        keys: impl Iterator<Item = &'a ServiceKey>,
+    ) -> anyhow::Result<()> {
+        let pattern = FuzzyWatchPattern::parse(param.pattern.clone())
+            .ok_or_else(|| anyhow::anyhow!("invalid groupKeyPattern: {}", &param.pattern))?;
+        self.index.add_watch(client_id.clone(), pattern.clone())?;
+        let mut server_keys = HashSet::new();
+        let mut contexts = vec![];
+        for key in keys {
+            if !pattern.is_match(&key.namespace_id, &key.group_name, &key.service_name) {
+                continue;
+            }
+            let service_key = build_service_key(key, &pattern.namespace);
+            if !param.received_keys.contains(&service_key) {
+                contexts.push(NamingFuzzyWatchContext {
+                    service_key: Arc::new(service_key.clone()),
+                    changed_type: ADD_SERVICE,
+                });
+            }
+            server_keys.insert(service_key);
+        }
+        for service_key in &param.received_keys {
+            if !server_keys.contains(service_key) {
+                contexts.push(NamingFuzzyWatchContext {
+                    service_key: Arc::new(service_key.to_owned()),
+                    changed_type: DELETE_SERVICE,
+                });
+            }
+        }
+        self.sync(client_id, pattern.pattern, contexts, param.initializing);
+        Ok(())
+    }
+
+    fn sync(
+        &self,
+        client_id: Arc<String>,
+        pattern: Arc<String>,
+        contexts: Vec<NamingFuzzyWatchContext>,
+        initializing: bool,
+    ) {
+        let conn_manage = if let Some(v) = &self.conn_manage {
+            v
+        } else {
+            return;
+        };
+        let mut client_set = HashSet::new();
+        client_set.insert(client_id);
+        let sync_type = if initializing {
+            FUZZY_WATCH_INIT_NOTIFY
+        } else {
+            FUZZY_WATCH_DIFF_SYNC_NOTIFY
+        };
+        let total_batch = contexts.len().div_ceil(FUZZY_WATCH_SYNC_BATCH_SIZE);
+        for (i, batch) in contexts.chunks(FUZZY_WATCH_SYNC_BATCH_SIZE).enumerate() {
+            let request = NamingFuzzyWatchSyncRequest {
+                group_key_pattern: pattern.clone(),
+                contexts: batch.to_vec(),
+                total_batch,
+                current_batch: i + 1,
+                sync_type,
+                ..Default::default()
+            };
+            conn_manage.do_send(BiStreamManageCmd::NotifyFuzzyWatch(
+                client_set.clone(),
+                FuzzyWatchNotify::NamingSync(request),
+            ));
+        }
+        if initializing {
+            let request = NamingFuzzyWatchSyncRequest {
+                group_key_pattern: pattern,
+                sync_type: FINISH_FUZZY_WATCH_INIT_NOTIFY,
+                ..Default::default()
+            };
+            conn_manage.do_send(BiStreamManageCmd::NotifyFuzzyWatch(
+                client_set,
+                FuzzyWatchNotify::NamingSync(request),
+            ));
+        }
+    }
+
+    pub fn cancel_watch(&mut self, client_id: &Arc<String>, pattern: &Arc<String>) {
+        self.index.remove_watch(client_id, pattern);
+    }
+
+    pub fn remove_client(&mut self, client_id: &Arc<String>) {
+        self.index.remove_client(client_id);
+    }
+
+    pub fn notify(&self, key: &ServiceKey, changed_type: &'static str) {
+        if self.index.is_empty() {
+            return;
+        }
+        if let Some(conn_manage) = &self.conn_manage {
+            for (namespace, client_set) in
+                self.index
+                    .match_clients(&key.namespace_id, &key.group_name, &key.service_name)
+            {
+                let request = NamingFuzzyWatchChangeNotifyRequest {
+                    service_key: Arc::new(build_service_key(key, &namespace)),
+                    changed_type,
+                    sync_type: FUZZY_WATCH_RESOURCE_CHANGED,
+                    ..Default::default()
+                };
+                conn_manage.do_send(BiStreamManageCmd::NotifyFuzzyWatch(
+                    client_set,
+                    FuzzyWatchNotify::NamingChange(request),
+                ));
+            }
+        }
+    }
+
+    pub fn get_pattern_size(&self) -> usize {
+        self.index.get_pattern_size()
+    }
+
+    pub fn get_client_size(&self) -> usize {
+        self.index.get_client_size()
+    }
+}
+
+///
+/// 按nacos格式生成服务key: `namespace@@group@@service`，默认命名空间优先使用客户端监听时的值
+pub fn build_service_key(key: &ServiceKey, namespace: &str) -> String {
+    let namespace = if !key.namespace_id.is_empty() {
+        key.namespace_id.as_str()
+    } else if !namespace.is_empty() {
+        namespace
+    } else {
+        DEFAULT_NAMESPACE
+    };
+    format!("{}@@{}@@{}", namespace, &key.group_name, &key.service_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_service_key_test() {
+        let key = ServiceKey::new("", "DEFAULT_GROUP", "pay-order");
+        assert_eq!(
+            build_service_key(&key, ""),
+            "public@@DEFAULT_GROUP@@pay-order"
+        );
+        let key = ServiceKey::new("dev", "DEFAULT_GROUP", "pay-order");
+        assert_eq!(
+            build_service_key(&key, "dev"),
+            "dev@@DEFAULT_GROUP@@pay-order"
+        );
+    }
+}
@@ -45,6 +45,8 @@ pub struct Subscriber {
     listener: BTreeMap<ServiceKey, BTreeMap<Arc<String>, Option<HashSet<String>>>>,
     client_keys: HashMap<Arc<String>, HashSet<ServiceKey>>,
     notify_addr: Option<Addr<DelayNotifyActor>>,
+    /// 没有订阅者时也发出变更通知(DNS接口需要)
+    notify_without_listener: bool,
 }
 
 impl Subscriber {
@@ -53,6 +55,7 @@ impl Subscriber {
             listener: Default::default(),
             client_keys: Default::default(),
             notify_addr: Default::default(),
+            notify_without_listener: false,
         }
     }
 
@@ -60,6 +63,10 @@ impl Subscriber {
         self.notify_addr = Some(notify_addr);
     }
 
+    pub fn set_notify_without_listener(&mut self, notify_without_listener: bool) {
+        self.notify_without_listener = notify_without_listener;
+    }
+
     pub fn add_subscribe(&mut self, client_id: Arc<String>, items: Vec<NamingListenerItem>) {
         match self.client_keys.get_mut(&client_id) {
             Some(set) => {
@@ -160,10 +167,20 @@ impl Subscriber {
                     client_id_set.insert(item.clone());
                 }
                 notify_addr.do_send(DelayNotifyCmd::Notify(key, client_id_set));
+            } else if self.notify_without_listener {
+                notify_addr.do_send(DelayNotifyCmd::Notify(key, HashSet::new()));
             }
         }
     }
 
+    pub fn get_client_subscribe_keys(&self, client_id: &Arc<String>) -> Vec<ServiceKey> {
+        if let Some(set) = self.client_keys.get(client_id) {
+            set.iter().cloned().collect()
+        } else {
+            vec![]
+        }
+    }
+
     pub fn get_listener_key_size(&self) -> usize {
         self.listener.len()
     }
@@ -188,6 +205,15 @@ impl Subscriber {
         sum
     }
 
+    ///
+    /// 各客户端的订阅数量
+    pub fn get_client_key_sizes(&self) -> Vec<(Arc<String>, usize)> {
+        self.client_keys
+            .iter()
+            .map(|(k, v)| (k.clone(), v.len()))
+            .collect()
+    }
+
     pub fn fuzzy_match_listener(
         &self,
         group_name: &str,
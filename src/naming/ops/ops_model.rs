@@ -8,7 +8,8 @@ use std::{collections::HashMap, sync::Arc};
 use crate::naming::api_model::InstanceVO;
 use crate::naming::model::ServiceKey;
 use crate::naming::{service::ServiceInfoDto, service_index::ServiceQueryParam, NamingUtils};
-use crate::user_namespace_privilege;
+use crate::user::permission::{PermissionAction, PermissionResource};
+use crate::{user_namespace_privilege, user_scope_filter};
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -29,6 +30,11 @@ impl OpsServiceQueryListRequest {
             limit,
             offset,
             namespace_privilege,
+            scope_filter: user_scope_filter!(
+                req,
+                PermissionResource::Service,
+                PermissionAction::Read
+            ),
             ..Default::default()
         };
         if let Some(namespace_id) = self.namespace_id {
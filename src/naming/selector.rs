@@ -0,0 +1,402 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::model::Instance;
+
+/// 服务选择器在服务元数据中保存的key，值为选择器json
+pub const SERVICE_SELECTOR_METADATA_KEY: &str = "__selector";
+
+const SELECTOR_TYPE_NONE: &str = "none";
+const SELECTOR_TYPE_LABEL: &str = "label";
+const SELECTOR_TYPE_CIDR: &str = "cidr";
+
+const CONSUMER_LABEL_PREFIX: &str = "CONSUMER.label.";
+const PROVIDER_LABEL_PREFIX: &str = "PROVIDER.label.";
+/// 内置标签，值为消费者或提供者的ip
+const LABEL_IP: &str = "ip";
+
+const DEFAULT_IPV4_PREFIX_LENGTH: u8 = 24;
+const DEFAULT_IPV6_PREFIX_LENGTH: u8 = 64;
+
+///
+/// 服务选择器参数，兼容nacos selector格式
+/// label: {"type":"label","expression":"CONSUMER.label.ip = PROVIDER.label.ip & PROVIDER.label.env = prod"}
+/// cidr: {"type":"cidr","prefixLength":24,"zoneKey":"zone","zones":{"zone-a":["10.1.0.0/16"]}}
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceSelectorParam {
+    #[serde(rename = "type")]
+    pub selector_type: String,
+    pub expression: Option<String>,
+    pub prefix_length: Option<u8>,
+    pub zone_key: Option<String>,
+    pub zones: Option<HashMap<String, Vec<String>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LabelOperand {
+    Provider(String),
+    Consumer(String),
+    Value(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct LabelTerm {
+    left: LabelOperand,
+    right: LabelOperand,
+    equal: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct IpCidr {
+    addr: IpAddr,
+    prefix_length: u8,
+}
+
+impl IpCidr {
+    fn parse(value: &str) -> anyhow::Result<Self> {
+        let value = value.trim();
+        let (addr, prefix_length) = match value.split_once('/') {
+            Some((addr, len)) => (addr.trim().parse::<IpAddr>()?, Some(len.trim().parse()?)),
+            None => (value.parse::<IpAddr>()?, None),
+        };
+        let max_length = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix_length = prefix_length.unwrap_or(max_length);
+        if prefix_length > max_length {
+            return Err(anyhow::anyhow!("cidr prefix length is invalid: {}", value));
+        }
+        Ok(Self {
+            addr,
+            prefix_length,
+        })
+    }
+
+    fn contains(&self, ip: &IpAddr) -> bool {
+        same_prefix(&self.addr, ip, self.prefix_length)
+    }
+}
+
+fn same_prefix(a: &IpAddr, b: &IpAddr, prefix_length: u8) -> bool {
+    match (a, b) {
+        (IpAddr::V4(a), IpAddr::V4(b)) => {
+            let len = prefix_length.min(32) as u32;
+            let mask = u32::MAX.checked_shl(32 - len).unwrap_or(0);
+            u32::from(*a) & mask == u32::from(*b) & mask
+        }
+        (IpAddr::V6(a), IpAddr::V6(b)) => {
+            let len = prefix_length.min(128) as u32;
+            let mask = u128::MAX.checked_shl(128 - len).unwrap_or(0);
+            u128::from(*a) & mask == u128::from(*b) & mask
+        }
+        _ => false,
+    }
+}
+
+///
+/// 服务选择器
+/// Label: 按表达式匹配实例元数据，所有条件都满足才保留
+/// Cidr: 按消费者ip优先选择同区域(zone)或同网段的实例
+/// 选择结果为空时返回全部实例，与nacos行为一致
+#[derive(Debug, Clone)]
+pub enum ServiceSelector {
+    Label {
+        expression: String,
+        terms: Vec<LabelTerm>,
+    },
+    Cidr {
+        prefix_length: Option<u8>,
+        zone_key: Option<String>,
+        zones: Vec<(String, IpCidr)>,
+    },
+}
+
+impl ServiceSelector {
+    ///
+    /// 解析选择器json；type为none或内容为空时返回None
+    pub fn parse(value: &str) -> anyhow::Result<Option<Self>> {
+        if value.trim().is_empty() {
+            return Ok(None);
+        }
+        let param: ServiceSelectorParam = serde_json::from_str(value)
+            .map_err(|e| anyhow::anyhow!("service selector is invalid json, {}", e))?;
+        Self::from_param(param)
+    }
+
+    pub fn from_param(param: ServiceSelectorParam) -> anyhow::Result<Option<Self>> {
+        match param.selector_type.to_lowercase().as_str() {
+            "" | SELECTOR_TYPE_NONE => Ok(None),
+            SELECTOR_TYPE_LABEL => {
+                let expression = param.expression.unwrap_or_default();
+                let terms = Self::parse_label_expression(&expression)?;
+                Ok(Some(Self::Label { expression, terms }))
+            }
+            SELECTOR_TYPE_CIDR => {
+                let mut zones = vec![];
+                for (zone, cidrs) in param.zones.unwrap_or_default() {
+                    for cidr in cidrs {
+                        zones.push((zone.clone(), IpCidr::parse(&cidr)?));
+                    }
+                }
+                //按网段从小到大匹配，保证更精确的网段优先
+                zones.sort_by_key(|v| std::cmp::Reverse(v.1.prefix_length));
+                Ok(Some(Self::Cidr {
+                    prefix_length: param.prefix_length,
+                    zone_key: param.zone_key.filter(|v| !v.is_empty()),
+                    zones,
+                }))
+            }
+            t => Err(anyhow::anyhow!(
+                "service selector type is not supported: {}",
+                t
+            )),
+        }
+    }
+
+    fn parse_label_expression(expression: &str) -> anyhow::Result<Vec<LabelTerm>> {
+        let mut terms = vec![];
+        for item in expression.split(['&', ',']) {
+            let item = item.trim();
+            if item.is_empty() {
+                continue;
+            }
+            let (left, right, equal) = if let Some((l, r)) = item.split_once("!=") {
+                (l, r, false)
+            } else if let Some((l, r)) = item.split_once("==") {
+                (l, r, true)
+            } else if let Some((l, r)) = item.split_once('=') {
+                (l, r, true)
+            } else {
+                return Err(anyhow::anyhow!(
+                    "service selector expression is invalid: {}",
+                    item
+                ));
+            };
+            let left = Self::parse_operand(left, true);
+            let right = Self::parse_operand(right, false);
+            if let (LabelOperand::Value(_), LabelOperand::Value(_)) = (&left, &right) {
+                return Err(anyhow::anyhow!(
+                    "service selector expression need a label: {}",
+                    item
+                ));
+            }
+            terms.push(LabelTerm { left, right, equal });
+        }
+        if terms.is_empty() {
+            return Err(anyhow::anyhow!("service selector expression is empty"));
+        }
+        Ok(terms)
+    }
+
+    /// 左值不带前缀时视为服务提供者标签
+    fn parse_operand(value: &str, is_left: bool) -> LabelOperand {
+        let value = value.trim();
+        if let Some(key) = value.strip_prefix(PROVIDER_LABEL_PREFIX) {
+            LabelOperand::Provider(key.to_owned())
+        } else if let Some(key) = value.strip_prefix(CONSUMER_LABEL_PREFIX) {
+            LabelOperand::Consumer(key.to_owned())
+        } else if is_left {
+            LabelOperand::Provider(value.to_owned())
+        } else {
+            LabelOperand::Value(value.to_owned())
+        }
+    }
+
+    pub fn select(
+        &self,
+        instances: Vec<Arc<Instance>>,
+        client_ip: Option<&IpAddr>,
+    ) -> Vec<Arc<Instance>> {
+        if instances.is_empty() {
+            return instances;
+        }
+        let selected: Vec<Arc<Instance>> = match self {
+            Self::Label { terms, .. } => instances
+                .iter()
+                .filter(|i| Self::match_terms(terms, i, client_ip))
+                .cloned()
+                .collect(),
+            Self::Cidr {
+                prefix_length,
+                zone_key,
+                zones,
+            } => {
+                let client_ip = match client_ip {
+                    Some(v) => v,
+                    None => return instances,
+                };
+                if let Some(client_zone) = Self::get_zone(zones, client_ip) {
+                    instances
+                        .iter()
+                        .filter(|i| {
+                            Self::get_instance_zone(zones, zone_key, i) == Some(client_zone)
+                        })
+                        .cloned()
+                        .collect()
+                } else {
+                    let prefix_length = prefix_length.unwrap_or(if client_ip.is_ipv4() {
+                        DEFAULT_IPV4_PREFIX_LENGTH
+                    } else {
+                        DEFAULT_IPV6_PREFIX_LENGTH
+                    });
+                    instances
+                        .iter()
+                        .filter(|i| {
+                            i.ip.parse::<IpAddr>()
+                                .map(|ip| same_prefix(client_ip, &ip, prefix_length))
+                                .unwrap_or(false)
+                        })
+                        .cloned()
+                        .collect()
+                }
+            }
+        };
+        if selected.is_empty() {
+            instances
+        } else {
+            selected
+        }
+    }
+
+    pub fn select_one(
+        &self,
+        instances: Vec<Arc<Instance>>,
+        client_ip: Option<&IpAddr>,
+    ) -> Option<Arc<Instance>> {
+        use rand::prelude::IteratorRandom;
+        self.select(instances, client_ip)
+            .into_iter()
+            .choose(&mut rand::thread_rng())
+    }
+
+    fn match_terms(terms: &[LabelTerm], instance: &Instance, client_ip: Option<&IpAddr>) -> bool {
+        for term in terms {
+            let left = Self::operand_value(&term.left, instance, client_ip);
+            let right = Self::operand_value(&term.right, instance, client_ip);
+            //消费者标签不存在时忽略该条件
+            let (left, right) = match (left, right) {
+                (Some(l), Some(r)) => (l, r),
+                _ => continue,
+            };
+            if (left == right) != term.equal {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn operand_value<'a>(
+        operand: &'a LabelOperand,
+        instance: &'a Instance,
+        client_ip: Option<&IpAddr>,
+    ) -> Option<std::borrow::Cow<'a, str>> {
+        match operand {
+            LabelOperand::Provider(key) => {
+                let v = if key == LABEL_IP {
+                    instance.ip.as_str()
+                } else {
+                    instance.metadata.get(key).map(|v| v.as_str()).unwrap_or("")
+                };
+                Some(v.into())
+            }
+            LabelOperand::Consumer(key) if key == LABEL_IP => {
+                client_ip.map(|ip| ip.to_string().into())
+            }
+            LabelOperand::Consumer(_) => None,
+            LabelOperand::Value(v) => Some(v.as_str().into()),
+        }
+    }
+
+    fn get_zone<'a>(zones: &'a [(String, IpCidr)], ip: &IpAddr) -> Option<&'a String> {
+        zones
+            .iter()
+            .find(|(_, cidr)| cidr.contains(ip))
+            .map(|(zone, _)| zone)
+    }
+
+    fn get_instance_zone<'a>(
+        zones: &'a [(String, IpCidr)],
+        zone_key: &Option<String>,
+        instance: &'a Instance,
+    ) -> Option<&'a String> {
+        if let Some(zone) = zone_key.as_ref().and_then(|k| instance.metadata.get(k)) {
+            return Some(zone);
+        }
+        let ip = instance.ip.parse::<IpAddr>().ok()?;
+        Self::get_zone(zones, &ip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_instance(ip: &str, labels: &[(&str, &str)]) -> Arc<Instance> {
+        let mut instance = Instance::new(ip.to_owned(), 8080);
+        let metadata: HashMap<String, String> = labels
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        instance.metadata = Arc::new(metadata);
+        Arc::new(instance)
+    }
+
+    #[test]
+    fn label_selector() {
+        let selector = ServiceSelector::parse(
+            r#"{"type":"label","expression":"PROVIDER.label.env = prod & version != 1.0"}"#,
+        )
+        .unwrap()
+        .unwrap();
+        let instances = vec![
+            build_instance("10.0.0.1", &[("env", "prod"), ("version", "1.0")]),
+            build_instance("10.0.0.2", &[("env", "prod"), ("version", "2.0")]),
+            build_instance("10.0.0.3", &[("env", "test")]),
+        ];
+        let list = selector.select(instances.clone(), None);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].ip.as_str(), "10.0.0.2");
+
+        let selector =
+            ServiceSelector::parse(r#"{"type":"label","expression":"PROVIDER.label.env = dev"}"#)
+                .unwrap()
+                .unwrap();
+        assert_eq!(selector.select(instances, None).len(), 3);
+        assert!(ServiceSelector::parse(r#"{"type":"none"}"#)
+            .unwrap()
+            .is_none());
+        assert!(ServiceSelector::parse(r#"{"type":"label","expression":"a"}"#).is_err());
+    }
+
+    #[test]
+    fn cidr_selector() {
+        let selector = ServiceSelector::parse(
+            r#"{"type":"cidr","zoneKey":"zone","zones":{"a":["10.1.0.0/16"],"b":["10.2.0.0/16"]}}"#,
+        )
+        .unwrap()
+        .unwrap();
+        let instances = vec![
+            build_instance("10.1.0.1", &[]),
+            build_instance("10.2.0.1", &[]),
+            build_instance("192.168.0.1", &[("zone", "a")]),
+        ];
+        let client_ip: IpAddr = "10.1.3.3".parse().unwrap();
+        let list = selector.select(instances.clone(), Some(&client_ip));
+        assert_eq!(list.len(), 2);
+        let client_ip: IpAddr = "172.16.0.1".parse().unwrap();
+        assert_eq!(
+            selector.select(instances.clone(), Some(&client_ip)).len(),
+            3
+        );
+
+        let selector = ServiceSelector::parse(r#"{"type":"cidr","prefixLength":16}"#)
+            .unwrap()
+            .unwrap();
+        let client_ip: IpAddr = "10.2.9.9".parse().unwrap();
+        let list = selector.select(instances, Some(&client_ip));
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].ip.as_str(), "10.2.0.1");
+    }
+}
@@ -9,9 +9,13 @@ use super::{
 };
 use crate::common::constant::EMPTY_ARC_STRING;
 use crate::naming::cluster::model::ProcessRange;
+use crate::naming::health_check::{
+    HealthCheckCounter, ServiceHealthCheck, SERVICE_HEALTH_CHECK_METADATA_KEY,
+};
 use crate::naming::instance_meta_manager::{InstanceMetaManager, InstanceMetaManagerReq};
 use crate::naming::instance_meta_repository::InstanceMetaDto;
 use crate::naming::model::UpdatePerpetualType;
+use crate::naming::selector::{ServiceSelector, SERVICE_SELECTOR_METADATA_KEY};
 use crate::now_millis;
 use actix::Addr;
 use actix_web::rt;
@@ -19,6 +23,7 @@ use inner_mem_cache::TimeoutSet;
 use rand::prelude::IteratorRandom;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
+use std::net::IpAddr;
 use std::{
     collections::{HashMap, LinkedList},
     hash::Hash,
@@ -28,6 +33,7 @@ use std::{
 #[derive(Debug, Clone, Default)]
 pub struct ServiceMetadata {
     pub protect_threshold: f32,
+    pub selector: Option<Arc<ServiceSelector>>,
 }
 
 type InstanceMetaData = Arc<HashMap<String, String>>;
@@ -55,6 +61,13 @@ pub struct Service {
     /// 不健康状态过期记录，过期后反实例删除
     pub(crate) unhealthy_timeout_set: TimeoutSet<InstanceShortKey>,
     pub(crate) perpetual_host_set: HashSet<InstanceShortKey>,
+    /// 由元数据中的选择器解析得到
+    pub(crate) selector: Option<Arc<ServiceSelector>>,
+    /// 由元数据中的健康检查配置解析得到，为空时使用默认tcp探测
+    pub(crate) health_check: Option<Arc<ServiceHealthCheck>>,
+    pub(crate) health_check_counter: HashMap<InstanceShortKey, HealthCheckCounter>,
+    /// 上次按服务配置主动检测的时间，单位秒
+    pub(crate) last_health_check_time: i32,
 }
 
 impl Service {
@@ -62,6 +75,108 @@ impl Service {
         "".clone_into(&mut self.check_sum);
     }
 
+    ///
+    /// 更新服务元数据，selector、health_check不为空时同步更新元数据中的对应配置
+    pub(crate) fn update_metadata(
+        &mut self,
+        metadata: Option<Arc<HashMap<String, String>>>,
+        selector: Option<Arc<String>>,
+        health_check: Option<Arc<String>>,
+    ) {
+        if metadata.is_none() && selector.is_none() && health_check.is_none() {
+            return;
+        }
+        let old_metadata = self.metadata.clone();
+        let mut new_metadata = metadata.unwrap_or_else(|| old_metadata.clone());
+        let new_metadata_mut = Arc::make_mut(&mut new_metadata);
+        Self::merge_reserved_metadata(
+            new_metadata_mut,
+            &old_metadata,
+            SERVICE_SELECTOR_METADATA_KEY,
+            selector,
+            |v| ServiceSelector::parse(v).ok().flatten().is_some(),
+        );
+        Self::merge_reserved_metadata(
+            new_metadata_mut,
+            &old_metadata,
+            SERVICE_HEALTH_CHECK_METADATA_KEY,
+            health_check,
+            |v| ServiceHealthCheck::parse(v).ok().flatten().is_some(),
+        );
+        self.metadata = new_metadata;
+        self.selector = match self.metadata.get(SERVICE_SELECTOR_METADATA_KEY) {
+            Some(v) => match ServiceSelector::parse(v) {
+                Ok(selector) => selector.map(Arc::new),
+                Err(err) => {
+                    log::warn!("service selector parse error,{}", err);
+                    None
+                }
+            },
+            None => None,
+        };
+        let health_check = match self.metadata.get(SERVICE_HEALTH_CHECK_METADATA_KEY) {
+            Some(v) => match ServiceHealthCheck::parse(v) {
+                Ok(check) => check.map(Arc::new),
+                Err(err) => {
+                    log::warn!("service health check parse error,{}", err);
+                    None
+                }
+            },
+            None => None,
+        };
+        if health_check != self.health_check {
+            self.health_check_counter.clear();
+            self.health_check = health_check;
+        }
+    }
+
+    /// value为空时保留原有配置，值无效(含type为none)时清除
+    fn merge_reserved_metadata(
+        metadata: &mut HashMap<String, String>,
+        old_metadata: &HashMap<String, String>,
+        key: &str,
+        value: Option<Arc<String>>,
+        is_valid: impl Fn(&str) -> bool,
+    ) {
+        if let Some(value) = value {
+            if is_valid(&value) {
+                metadata.insert(key.to_owned(), value.as_ref().to_owned());
+            } else {
+                metadata.remove(key);
+            }
+        } else if !metadata.contains_key(key) {
+            if let Some(old_value) = old_metadata.get(key) {
+                metadata.insert(key.to_owned(), old_value.to_owned());
+            }
+        }
+    }
+
+    ///
+    /// 记录永久实例健康检查结果，按rise/fall阈值更新实例健康状态
+    pub(crate) fn update_perpetual_health_check(
+        &mut self,
+        instance_id: &InstanceShortKey,
+        success: bool,
+    ) {
+        if !self.perpetual_host_set.contains(instance_id) {
+            return;
+        }
+        let (rise, fall) = match &self.health_check {
+            Some(check) => (check.rise, check.fall),
+            None => (1, 1),
+        };
+        let healthy = self
+            .health_check_counter
+            .entry(instance_id.clone())
+            .or_default()
+            .record(success, rise, fall);
+        match healthy {
+            Some(true) => self.update_perpetual_instance_healthy_valid(instance_id),
+            Some(false) => self.update_instance_healthy_invalid(instance_id),
+            None => {}
+        }
+    }
+
     /*
     pub(crate) fn remove_instance(&mut self,cluster_name:&str,instance_id:&str) -> UpdateInstanceType {
         if let Some(cluster) = self.cluster_map.get_mut(cluster_name){
@@ -227,6 +342,7 @@ impl Service {
         } else if mark_remove_perpetual_instance {
             let short_key = new_instance.get_short_key();
             self.perpetual_host_set.remove(&short_key);
+            self.health_check_counter.remove(&short_key);
             perpetua_type = UpdatePerpetualType::Remove;
         }
         self.instances.insert(key, new_instance);
@@ -336,6 +452,7 @@ impl Service {
             if !old.ephemeral {
                 // 删除永久实例
                 self.perpetual_host_set.remove(instance_key);
+                self.health_check_counter.remove(instance_key);
             }
             self.instance_size -= 1;
             if self.instance_size == 0 {
@@ -403,7 +520,12 @@ impl Service {
         &self,
         only_healthy: bool,
         only_enable: bool,
+        client_ip: Option<&IpAddr>,
     ) -> Option<Arc<Instance>> {
+        if let Some(selector) = &self.selector {
+            return selector
+                .select_one(self.get_all_instances(only_healthy, only_enable), client_ip);
+        }
         //后续可以考虑支持按权重随机选择
         self.instances
             .values()
@@ -446,6 +568,7 @@ impl Service {
     pub fn get_metadata(&self) -> ServiceMetadata {
         ServiceMetadata {
             protect_threshold: self.protect_threshold,
+            selector: self.selector.clone(),
         }
     }
 
@@ -3,6 +3,7 @@ use crate::common::model::privilege::NamespacePrivilegeGroup;
 use crate::common::string_utils::StringUtils;
 use crate::namespace::model::{NamespaceActorReq, WeakNamespaceFromType, WeakNamespaceParam};
 use crate::namespace::NamespaceActor;
+use crate::user::permission::PermissionScopeFilter;
 use actix::Addr;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -18,11 +19,20 @@ pub struct ServiceQueryParam {
     pub like_group: Option<String>,
     pub like_service: Option<String>,
     pub namespace_privilege: NamespacePrivilegeGroup,
+    /// 自定义角色授权的数据范围，为None时不限制
+    #[serde(skip)]
+    pub scope_filter: Option<Arc<PermissionScopeFilter>>,
     pub offset: usize,
     pub limit: usize,
 }
 
 impl ServiceQueryParam {
+    pub fn match_scope(&self, namespace_id: &str, group: &str, service: &str) -> bool {
+        self.scope_filter
+            .as_ref()
+            .is_none_or(|v| v.is_match(namespace_id, group, service))
+    }
+
     pub fn match_namespace_id(&self, g: &Arc<String>) -> bool {
         if let Some(namespace_id) = &self.namespace_id {
             namespace_id.is_empty() || StringUtils::eq(g, namespace_id)
@@ -129,7 +139,7 @@ impl ServiceIndex {
         for (g, set) in &self.group_service {
             if param.match_group(g) {
                 for s in set {
-                    if param.match_service(s) {
+                    if param.match_service(s) && param.match_scope(namespace_id, g, s) {
                         if index >= param.offset && index < end_index {
                             let service_key =
                                 ServiceKey::new_by_arc(namespace_id.clone(), g.clone(), s.clone());
@@ -1,23 +1,110 @@
 use crate::naming::core::{NamingActor, NamingCmd};
+use crate::naming::health_check::{HealthCheckType, ServiceHealthCheck};
 use crate::naming::model::{InstanceShortKey, ServiceKey};
 use actix::prelude::*;
 use bean_factory::{bean, BeanFactory, FactoryData, Inject};
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::net::TcpStream;
 use tokio::time::timeout;
 
+const GRPC_HEALTH_CHECK_PATH: &str = "/grpc.health.v1.Health/Check";
+/// grpc.health.v1.HealthCheckResponse.ServingStatus.SERVING
+const GRPC_HEALTH_SERVING: i32 = 1;
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct GrpcHealthCheckRequest {
+    #[prost(string, tag = "1")]
+    service: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct GrpcHealthCheckResponse {
+    #[prost(int32, tag = "1")]
+    status: i32,
+}
+
 pub async fn probe_tcp(host: &str, port: u16, timeout_duration: Duration) -> anyhow::Result<()> {
     let addr = format!("{}:{}", host, port);
     timeout(timeout_duration, TcpStream::connect(&addr)).await??;
     Ok(())
 }
 
+fn build_authority(host: &str, port: u16) -> String {
+    if host.contains(':') && !host.starts_with('[') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+pub async fn probe_http(
+    client: &reqwest::Client,
+    host: &str,
+    port: u16,
+    check: &ServiceHealthCheck,
+) -> anyhow::Result<()> {
+    let scheme = if check.https { "https" } else { "http" };
+    let url = format!(
+        "{}://{}{}",
+        scheme,
+        build_authority(host, port),
+        check.get_path()
+    );
+    let mut request = client.get(&url).timeout(check.timeout_duration());
+    for (key, value) in &check.headers {
+        request = request.header(key.as_str(), value.as_str());
+    }
+    let status = request.send().await?.status().as_u16();
+    if check.is_expected_status(status) {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("unexpected http status: {}", status))
+    }
+}
+
+pub async fn probe_grpc(host: &str, port: u16, check: &ServiceHealthCheck) -> anyhow::Result<()> {
+    let uri = format!("http://{}", build_authority(host, port));
+    let request = GrpcHealthCheckRequest {
+        service: check.service.clone().unwrap_or_default(),
+    };
+    let fut = async move {
+        let channel = tonic::transport::Endpoint::from_shared(uri)?
+            .connect()
+            .await?;
+        let mut client = tonic::client::Grpc::new(channel);
+        client
+            .ready()
+            .await
+            .map_err(|e| anyhow::anyhow!("grpc service was not ready: {}", e))?;
+        let path = tonic::codegen::http::uri::PathAndQuery::from_static(GRPC_HEALTH_CHECK_PATH);
+        let response: tonic::Response<GrpcHealthCheckResponse> = client
+            .unary(
+                tonic::Request::new(request),
+                path,
+                tonic::codec::ProstCodec::default(),
+            )
+            .await?;
+        anyhow::Ok(response.into_inner().status)
+    };
+    let status = timeout(check.timeout_duration(), fut).await??;
+    if status == GRPC_HEALTH_SERVING {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "grpc health status is not serving: {}",
+            status
+        ))
+    }
+}
+
 #[derive(Debug, Clone)]
 #[bean(inject)]
 pub struct NetSniffing {
     pub naming_actor: Option<Addr<NamingActor>>,
     pub timeout_duration: Duration,
     pub retry_interval: Duration,
+    http_client: reqwest::Client,
 }
 
 impl Actor for NetSniffing {
@@ -45,6 +132,7 @@ impl NetSniffing {
             timeout_duration,
             retry_interval,
             naming_actor: None,
+            http_client: reqwest::Client::new(),
         }
     }
 
@@ -72,6 +160,35 @@ impl NetSniffing {
                 }
                 Ok(NetSniffingResult::None)
             }
+            NetSniffingCmd::CheckServiceHost(host, service_key, check) => {
+                let success = self.check(&host, &check).await.is_ok();
+                if let Some(naming_actor) = &self.naming_actor {
+                    naming_actor.do_send(NamingCmd::PerpetualHostSniffing {
+                        host,
+                        service_keys: vec![service_key],
+                        success,
+                    })
+                }
+                Ok(NetSniffingResult::None)
+            }
+        }
+    }
+
+    async fn check(
+        &self,
+        host: &InstanceShortKey,
+        check: &ServiceHealthCheck,
+    ) -> anyhow::Result<()> {
+        let port = check.port.unwrap_or(host.port as u16);
+        match check.check_type {
+            HealthCheckType::Tcp => {
+                probe_tcp(host.ip.as_ref(), port, check.timeout_duration()).await
+            }
+            HealthCheckType::Http => {
+                probe_http(&self.http_client, host.ip.as_ref(), port, check).await
+            }
+            HealthCheckType::Grpc => probe_grpc(host.ip.as_ref(), port, check).await,
+            HealthCheckType::None => Ok(()),
         }
     }
 
@@ -88,6 +205,8 @@ impl NetSniffing {
 pub enum NetSniffingCmd {
     ProbeHost(InstanceShortKey),
     ProbeServiceHost(InstanceShortKey, Vec<ServiceKey>),
+    /// 按服务配置的健康检查方式检测实例
+    CheckServiceHost(InstanceShortKey, ServiceKey, Arc<ServiceHealthCheck>),
 }
 
 #[derive(Debug)]
@@ -1,4 +1,5 @@
 use crate::common::model::privilege::PrivilegeGroup;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 pub mod actor_model;
@@ -15,7 +16,43 @@ pub struct OAuth2Config {
     pub oauth2_scopes: Arc<String>,
     pub oauth2_username_claim_name: Arc<String>,
     pub oauth2_nickname_claim_name: Arc<String>,
+    /// 用户组claim字段名，用户组用于匹配绑定的自定义角色
+    pub oauth2_groups_claim_name: Arc<String>,
     pub oauth2_user_default_role: Arc<String>,
+    /// 开发者角色包含的用户组
+    pub oauth2_user_developer_groups: Arc<HashSet<String>>,
+    /// 管理员角色包含的用户组
+    pub oauth2_user_admin_groups: Arc<HashSet<String>>,
+    /// 用户组可访问的命名空间，`*`表示全部
+    pub oauth2_group_namespaces: Arc<HashMap<String, HashSet<String>>>,
+    /// 启用OIDC，通过issuer发现服务端点并校验ID Token
+    pub oauth2_oidc_enable: bool,
+    pub oauth2_issuer: Arc<String>,
+    pub oauth2_pkce_enable: bool,
+}
+
+impl OAuth2Config {
+    ///
+    /// 解析用户组与命名空间映射，格式为`group1:ns1|ns2,group2:*`
+    pub fn parse_group_namespaces(value: &str) -> HashMap<String, HashSet<String>> {
+        let mut map: HashMap<String, HashSet<String>> = HashMap::new();
+        for item in value.split(',') {
+            if let Some((group, namespaces)) = item.split_once(':') {
+                let group = group.trim();
+                if group.is_empty() {
+                    continue;
+                }
+                map.entry(group.to_owned()).or_default().extend(
+                    namespaces
+                        .split('|')
+                        .map(|s| s.trim())
+                        .filter(|s| !s.is_empty())
+                        .map(|s| s.to_owned()),
+                );
+            }
+        }
+        map
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -28,6 +65,9 @@ pub struct OAuth2UserParam {
 pub struct OAuth2UserMeta {
     pub user_name: String,
     pub role: Arc<String>,
+    pub groups: Vec<Arc<String>>,
+    /// 用户已分配的自定义角色
+    pub custom_roles: Vec<Arc<String>>,
     pub namespace_privilege: Option<PrivilegeGroup<Arc<String>>>,
 }
 
@@ -40,6 +80,8 @@ impl OAuth2UserMeta {
         Self {
             user_name,
             role,
+            groups: vec![],
+            custom_roles: vec![],
             namespace_privilege,
         }
     }
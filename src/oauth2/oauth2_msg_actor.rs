@@ -1,24 +1,38 @@
 use crate::common::get_app_version;
+use crate::common::model::privilege::{PrivilegeGroup, PrivilegeGroupOptionParam};
+use crate::now_second_i32;
 use crate::oauth2::model::actor_model::{OAuth2MsgReq, OAuth2MsgResult};
-use crate::oauth2::model::OAuth2Config;
+use crate::oauth2::model::{OAuth2Config, OAuth2UserMeta};
+use crate::oauth2::oidc::{OAuth2Client, OAuth2StateHelper, OidcProvider, OidcTokenResponse};
 use crate::user::model::{UserDto, UserSourceType};
+use crate::user::permission::{self, UserRoleHelper};
 use crate::user::{UserManager, UserManagerReq, UserManagerResult};
 use actix::prelude::*;
 use oauth2::{
-    basic::BasicClient, reqwest::async_http_client, AuthorizationCode, ClientId, ClientSecret,
-    RedirectUrl, Scope, TokenResponse,
+    reqwest::async_http_client, AuthorizationCode, ClientId, ClientSecret, CsrfToken,
+    PkceCodeChallenge, PkceCodeVerifier, RedirectUrl, Scope, TokenResponse,
 };
 use reqwest::Client;
-use serde_json::Value;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// OIDC发现失败后的重试间隔
+const DISCOVERY_RETRY_INTERVAL: Duration = Duration::from_secs(10);
 
 #[derive(Clone)]
 pub struct OAuth2MsgActor {
     oauth2_config: Arc<OAuth2Config>,
     user_manager_addr: Option<Addr<UserManager>>,
     http_client: Client,
-    oauth2_client: BasicClient,
+    /// OIDC发现与公钥拉取需要校验服务端证书
+    oidc_http_client: Client,
+    oauth2_client: OAuth2Client,
     scopes: Vec<Scope>,
+    userinfo_url: String,
+    state_helper: Arc<OAuth2StateHelper>,
+    oidc_provider: Option<Arc<OidcProvider>>,
 }
 
 impl OAuth2MsgActor {
@@ -26,21 +40,12 @@ impl OAuth2MsgActor {
         oauth2_config: Arc<OAuth2Config>,
         user_manager_addr: Option<Addr<UserManager>>,
     ) -> anyhow::Result<Self> {
-        let client_id = ClientId::new(oauth2_config.oauth2_client_id.as_ref().clone());
-        let client_secret = ClientSecret::new(oauth2_config.oauth2_client_secret.as_ref().clone());
-        // Use full URLs directly
-        let auth_url = oauth2_config.oauth2_authorization_url.as_ref().clone();
-        let token_url = oauth2_config.oauth2_token_url.as_ref().clone();
-
-        let redirect_url = RedirectUrl::new(oauth2_config.oauth2_redirect_uri.as_ref().clone())?;
-
-        let oauth2_client = BasicClient::new(
-            client_id,
-            Some(client_secret),
-            oauth2::AuthUrl::new(auth_url)?,
-            Some(oauth2::TokenUrl::new(token_url)?),
-        )
-        .set_redirect_uri(redirect_url);
+        // Use full URLs directly, endpoints are replaced by oidc discovery when enabled
+        let oauth2_client = Self::build_client(
+            &oauth2_config,
+            oauth2_config.oauth2_authorization_url.as_ref().clone(),
+            oauth2_config.oauth2_token_url.as_ref().clone(),
+        )?;
 
         // Parse scopes
         let scopes: Vec<Scope> = oauth2_config
@@ -48,6 +53,8 @@ impl OAuth2MsgActor {
             .split_whitespace()
             .map(|s| Scope::new(s.to_string()))
             .collect();
+        let userinfo_url = oauth2_config.oauth2_userinfo_url.as_ref().clone();
+        let state_helper = Arc::new(OAuth2StateHelper::new(&oauth2_config.oauth2_client_secret));
 
         Ok(Self {
             oauth2_config,
@@ -56,85 +63,126 @@ impl OAuth2MsgActor {
                 .danger_accept_invalid_certs(true)
                 .build()
                 .unwrap_or_else(|_| Client::new()),
+            oidc_http_client: Client::new(),
             oauth2_client,
             scopes,
+            userinfo_url,
+            state_helper,
+            oidc_provider: None,
         })
     }
 
+    fn build_client(
+        oauth2_config: &OAuth2Config,
+        auth_url: String,
+        token_url: String,
+    ) -> anyhow::Result<OAuth2Client> {
+        let client_id = ClientId::new(oauth2_config.oauth2_client_id.as_ref().clone());
+        let client_secret = if oauth2_config.oauth2_client_secret.is_empty() {
+            None
+        } else {
+            Some(ClientSecret::new(
+                oauth2_config.oauth2_client_secret.as_ref().clone(),
+            ))
+        };
+        let redirect_url = RedirectUrl::new(oauth2_config.oauth2_redirect_uri.as_ref().clone())?;
+        Ok(OAuth2Client::new(
+            client_id,
+            client_secret,
+            oauth2::AuthUrl::new(auth_url)?,
+            Some(oauth2::TokenUrl::new(token_url)?),
+        )
+        .set_redirect_uri(redirect_url))
+    }
+
+    fn discover(&mut self, ctx: &mut Context<Self>) {
+        let http_client = self.oidc_http_client.clone();
+        let issuer = self.oauth2_config.oauth2_issuer.clone();
+        async move { OidcProvider::discover(&http_client, &issuer).await }
+            .into_actor(self)
+            .map(|r, act, ctx| match r.and_then(|v| act.apply_provider(v)) {
+                Ok(_) => log::info!("OAuth2 oidc discovery success"),
+                Err(err) => {
+                    log::error!("OAuth2 oidc discovery error:{}", err);
+                    ctx.run_later(DISCOVERY_RETRY_INTERVAL, |act, ctx| act.discover(ctx));
+                }
+            })
+            .spawn(ctx);
+    }
+
+    fn apply_provider(&mut self, provider: OidcProvider) -> anyhow::Result<()> {
+        self.oauth2_client = Self::build_client(
+            &self.oauth2_config,
+            provider.metadata.authorization_endpoint.clone(),
+            provider.metadata.token_endpoint.clone(),
+        )?;
+        if let Some(userinfo_url) = &provider.metadata.userinfo_endpoint {
+            self.userinfo_url = userinfo_url.to_owned();
+        }
+        self.oidc_provider = Some(Arc::new(provider));
+        Ok(())
+    }
+
+    fn check_ready(&self) -> anyhow::Result<()> {
+        if self.oauth2_config.oauth2_oidc_enable && self.oidc_provider.is_none() {
+            return Err(anyhow::anyhow!("OAuth2 oidc provider is not ready"));
+        }
+        Ok(())
+    }
+
     async fn handle_req(&self, msg: OAuth2MsgReq) -> anyhow::Result<OAuth2MsgResult> {
+        self.check_ready()?;
+        let now = now_second_i32() as i64;
+        let use_state =
+            self.oauth2_config.oauth2_pkce_enable || self.oauth2_config.oauth2_oidc_enable;
         match msg {
             OAuth2MsgReq::GetAuthorizeUrl => {
-                let (auth_url, _csrf_state) = self
+                let state = self.state_helper.build_state(now);
+                let mut request = self
                     .oauth2_client
-                    .authorize_url(oauth2::CsrfToken::new_random)
-                    .add_scopes(self.scopes.clone())
-                    .url();
-
+                    .authorize_url(|| CsrfToken::new(state.clone()))
+                    .add_scopes(self.scopes.clone());
+                if self.oauth2_config.oauth2_pkce_enable {
+                    let verifier = PkceCodeVerifier::new(self.state_helper.pkce_verifier(&state));
+                    request = request.set_pkce_challenge(
+                        PkceCodeChallenge::from_code_verifier_sha256(&verifier),
+                    );
+                }
+                if self.oauth2_config.oauth2_oidc_enable {
+                    request = request.add_extra_param("nonce", self.state_helper.nonce(&state));
+                }
+                let (auth_url, _csrf_state) = request.url();
                 Ok(OAuth2MsgResult::AuthorizeUrl(auth_url.to_string()))
             }
             OAuth2MsgReq::Authenticate(param) => {
+                let state = if use_state {
+                    let state = param.state.as_deref().unwrap_or_default();
+                    self.state_helper.verify_state(state, now)?;
+                    Some(state)
+                } else {
+                    None
+                };
                 // Exchange authorization code for token
-                let token = self
+                let mut request = self
                     .oauth2_client
-                    .exchange_code(AuthorizationCode::new(param.code.clone()))
+                    .exchange_code(AuthorizationCode::new(param.code.clone()));
+                if let (true, Some(state)) = (self.oauth2_config.oauth2_pkce_enable, state) {
+                    request = request.set_pkce_verifier(PkceCodeVerifier::new(
+                        self.state_helper.pkce_verifier(state),
+                    ));
+                }
+                let token = request
                     .request_async(async_http_client)
                     .await
                     .map_err(|e| {
                         log::error!("OAuth2 token exchange failed: {}", e);
                         anyhow::anyhow!("OAuth2 token exchange failed: {}", e)
                     })?;
-
-                let access_token = token.access_token().secret();
-
-                // Get user info - use full URL directly
-                let userinfo_url = self.oauth2_config.oauth2_userinfo_url.as_ref().clone();
-
-                // Build request with bearer token
-                // Use bearer_auth which sets: Authorization: Bearer <token>
-                // GitHub and some OAuth2 providers require User-Agent header
-                let user_agent = format!("r-nacos/{}", get_app_version());
-                let request = self
-                    .http_client
-                    .get(&userinfo_url)
-                    .bearer_auth(access_token.clone())
-                    .header("Accept", "application/json")
-                    .header("User-Agent", user_agent);
-
-                let userinfo_response = request.send().await?;
-
-                // Check response status
-                let status = userinfo_response.status();
-                if !status.is_success() {
-                    let error_text = userinfo_response.text().await.unwrap_or_default();
-                    log::error!(
-                        "OAuth2 userinfo request failed: status={}, url={}, body={}",
-                        status,
-                        userinfo_url,
-                        error_text
-                    );
-                    return Err(anyhow::anyhow!(
-                        "OAuth2 userinfo request failed: status={}, body={}",
-                        status,
-                        error_text
-                    ));
-                }
-
-                // Parse JSON directly
-                let userinfo: Value = userinfo_response.json().await.map_err(|e| {
-                    log::error!("OAuth2 userinfo JSON parse error: {}", e);
-                    anyhow::anyhow!("Failed to parse OAuth2 userinfo response: {}", e)
-                })?;
-
-                // Log the complete userinfo response for debugging
-                log::info!(
-                    "OAuth2 userinfo response: {}",
-                    serde_json::to_string_pretty(&userinfo)
-                        .unwrap_or_else(|_| "Failed to serialize".to_string())
-                );
+                let claims = self.load_claims(&token, state).await?;
 
                 // Extract username from claims
                 let username_claim_name = self.oauth2_config.oauth2_username_claim_name.as_ref();
-                let user_name = userinfo
+                let user_name = claims
                     .get(username_claim_name)
                     .and_then(|v| v.as_str())
                     .ok_or_else(|| {
@@ -143,54 +191,259 @@ impl OAuth2MsgActor {
                     })?
                     .to_string();
 
-                // Extract nickname from claims
+                // Extract nickname from claims, fallback to username
                 let nickname_claim_name = self.oauth2_config.oauth2_nickname_claim_name.as_ref();
-                let nickname = userinfo
+                let nickname = claims
                     .get(nickname_claim_name)
                     .and_then(|v| v.as_str())
                     .map(|s| s.to_string())
-                    .ok_or_else(|| {
-                        log::error!("OAuth2 nickname claim '{}' not found", nickname_claim_name);
-                        anyhow::anyhow!("nickname claim '{}' not found", nickname_claim_name)
-                    })?;
+                    .unwrap_or_else(|| user_name.clone());
+
+                // Extract groups from claims, groups are mapped to roles and namespaces
+                let groups_claim_name = self.oauth2_config.oauth2_groups_claim_name.as_ref();
+                let groups: Vec<Arc<String>> = claims
+                    .get(groups_claim_name)
+                    .and_then(|v| v.as_array())
+                    .map(|list| {
+                        list.iter()
+                            .filter_map(|v| v.as_str())
+                            .map(|v| Arc::new(v.to_owned()))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                let meta = self.sync_user(user_name, nickname, groups).await?;
+                Ok(OAuth2MsgResult::UserMeta(meta))
+            }
+        }
+    }
+
+    ///
+    /// 启用OIDC时以校验通过的ID Token为准，缺少的claim再从userinfo补充；
+    /// 未启用时使用userinfo
+    async fn load_claims(
+        &self,
+        token: &OidcTokenResponse,
+        state: Option<&str>,
+    ) -> anyhow::Result<Map<String, Value>> {
+        let access_token = token.access_token().secret();
+        let provider = if let Some(provider) = &self.oidc_provider {
+            provider
+        } else {
+            return self.fetch_userinfo(access_token).await;
+        };
+        let id_token = token
+            .extra_fields()
+            .id_token
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("OAuth2 id token not found"))?;
+        let nonce = state.map(|v| self.state_helper.nonce(v));
+        let mut claims = provider
+            .verify_id_token(
+                &self.oidc_http_client,
+                id_token,
+                &self.oauth2_config.oauth2_client_id,
+                nonce.as_deref(),
+            )
+            .await?;
+        let missing_claims = [
+            &self.oauth2_config.oauth2_username_claim_name,
+            &self.oauth2_config.oauth2_groups_claim_name,
+        ]
+        .iter()
+        .any(|e| !claims.contains_key(e.as_str()));
+        if missing_claims && !self.userinfo_url.is_empty() {
+            let userinfo = self.fetch_userinfo(access_token).await?;
+            if userinfo.get("sub") != claims.get("sub") {
+                return Err(anyhow::anyhow!("OAuth2 userinfo subject mismatch"));
+            }
+            for (key, value) in userinfo {
+                claims.entry(key).or_insert(value);
+            }
+        }
+        Ok(claims)
+    }
+
+    async fn fetch_userinfo(&self, access_token: &str) -> anyhow::Result<Map<String, Value>> {
+        let userinfo_url = &self.userinfo_url;
+
+        // Build request with bearer token
+        // Use bearer_auth which sets: Authorization: Bearer <token>
+        // GitHub and some OAuth2 providers require User-Agent header
+        let user_agent = format!("r-nacos/{}", get_app_version());
+        let request = self
+            .http_client
+            .get(userinfo_url)
+            .bearer_auth(access_token)
+            .header("Accept", "application/json")
+            .header("User-Agent", user_agent);
+
+        let userinfo_response = request.send().await?;
+
+        // Check response status
+        let status = userinfo_response.status();
+        if !status.is_success() {
+            let error_text = userinfo_response.text().await.unwrap_or_default();
+            log::error!(
+                "OAuth2 userinfo request failed: status={}, url={}, body={}",
+                status,
+                userinfo_url,
+                error_text
+            );
+            return Err(anyhow::anyhow!(
+                "OAuth2 userinfo request failed: status={}, body={}",
+                status,
+                error_text
+            ));
+        }
 
-                // Use configured default role
-                let role = self.oauth2_config.oauth2_user_default_role.clone();
-                let mut namespace_privilege = None;
+        // Parse JSON directly
+        let userinfo: Value = userinfo_response.json().await.map_err(|e| {
+            log::error!("OAuth2 userinfo JSON parse error: {}", e);
+            anyhow::anyhow!("Failed to parse OAuth2 userinfo response: {}", e)
+        })?;
 
-                // Initialize user
-                if let Some(user_manager_addr) = &self.user_manager_addr {
+        // Log the complete userinfo response for debugging
+        log::info!(
+            "OAuth2 userinfo response: {}",
+            serde_json::to_string_pretty(&userinfo)
+                .unwrap_or_else(|_| "Failed to serialize".to_string())
+        );
+        match userinfo {
+            Value::Object(v) => Ok(v),
+            _ => Err(anyhow::anyhow!("OAuth2 userinfo response is not an object")),
+        }
+    }
+
+    fn resolve_role(&self, groups: &[Arc<String>]) -> Arc<String> {
+        let config = &self.oauth2_config;
+        if groups
+            .iter()
+            .any(|e| config.oauth2_user_admin_groups.contains(e.as_str()))
+        {
+            permission::USER_ROLE_MANAGER.clone()
+        } else if groups
+            .iter()
+            .any(|e| config.oauth2_user_developer_groups.contains(e.as_str()))
+        {
+            permission::USER_ROLE_DEVELOPER.clone()
+        } else {
+            config.oauth2_user_default_role.clone()
+        }
+    }
+
+    ///
+    /// 配置了用户组与命名空间映射时，用户可访问的命名空间为所在用户组映射的并集
+    fn resolve_namespace_privilege(
+        &self,
+        groups: &[Arc<String>],
+    ) -> Option<PrivilegeGroupOptionParam<Arc<String>>> {
+        let group_namespaces = &self.oauth2_config.oauth2_group_namespaces;
+        if group_namespaces.is_empty() {
+            return None;
+        }
+        let mut whitelist_is_all = false;
+        let mut whitelist = HashSet::new();
+        for namespaces in groups
+            .iter()
+            .filter_map(|e| group_namespaces.get(e.as_str()))
+        {
+            for namespace in namespaces {
+                if namespace == "*" {
+                    whitelist_is_all = true;
+                } else {
+                    whitelist.insert(Arc::new(namespace.to_owned()));
+                }
+            }
+        }
+        Some(PrivilegeGroupOptionParam {
+            whitelist_is_all: Some(whitelist_is_all),
+            whitelist: Some(Arc::new(whitelist)),
+            blacklist_is_all: Some(false),
+            blacklist: Some(Arc::new(HashSet::new())),
+        })
+    }
+
+    ///
+    /// 初始化用户；已存在的用户每次登录按用户组刷新内置角色与命名空间权限，保留已分配的自定义角色
+    async fn sync_user(
+        &self,
+        user_name: String,
+        nickname: String,
+        groups: Vec<Arc<String>>,
+    ) -> anyhow::Result<OAuth2UserMeta> {
+        let role = self.resolve_role(&groups);
+        let namespace_privilege_param = self.resolve_namespace_privilege(&groups);
+        let mut namespace_privilege = namespace_privilege_param.as_ref().map(|v| PrivilegeGroup {
+            enabled: true,
+            whitelist_is_all: v.whitelist_is_all.unwrap_or_default(),
+            whitelist: v.whitelist.clone(),
+            blacklist_is_all: false,
+            blacklist: None,
+        });
+        let mut custom_roles = vec![];
+        if let Some(user_manager_addr) = &self.user_manager_addr {
+            let username = Arc::new(user_name.clone());
+            let user = UserDto {
+                username: username.clone(),
+                nickname: Some(nickname),
+                source: Some(UserSourceType::Inner.to_str().to_owned()),
+                roles: Some(vec![role.clone()]),
+                ..Default::default()
+            };
+            if let Ok(Ok(UserManagerResult::QueryUser(Some(user_dto)))) = user_manager_addr
+                .send(UserManagerReq::InitUser {
+                    user,
+                    namespace_privilege_param: namespace_privilege_param.clone(),
+                })
+                .await
+            {
+                if !user_dto.enable.unwrap_or(true) {
+                    return Err(anyhow::anyhow!("user {} is disabled", &user_name));
+                }
+                custom_roles = user_dto.get_custom_roles();
+                let builtin_roles: Vec<&Arc<String>> = user_dto
+                    .roles
+                    .iter()
+                    .flatten()
+                    .filter(|e| UserRoleHelper::is_builtin_role(e))
+                    .collect();
+                if builtin_roles != [&role] || namespace_privilege_param.is_some() {
                     let user = UserDto {
-                        username: Arc::new(user_name.clone()),
-                        nickname: Some(nickname),
-                        source: Some(UserSourceType::Inner.to_str().to_owned()),
-                        roles: Some(vec![role.clone()]),
+                        username,
+                        roles: Some([vec![role.clone()], custom_roles.clone()].concat()),
                         ..Default::default()
                     };
-                    if let Ok(Ok(UserManagerResult::QueryUser(Some(user_dto)))) = user_manager_addr
-                        .send(UserManagerReq::InitUser {
+                    if let Err(err) = user_manager_addr
+                        .send(UserManagerReq::UpdateUser {
                             user,
-                            namespace_privilege_param: None,
+                            namespace_privilege_param,
                         })
-                        .await
+                        .await?
                     {
-                        namespace_privilege = user_dto.namespace_privilege;
+                        log::warn!("OAuth2 refresh user {} error:{}", &user_name, err);
                     }
                 }
-
-                let meta =
-                    crate::oauth2::model::OAuth2UserMeta::new(user_name, role, namespace_privilege);
-                Ok(OAuth2MsgResult::UserMeta(meta))
+                if namespace_privilege.is_none() {
+                    namespace_privilege = user_dto.namespace_privilege;
+                }
             }
         }
+        let mut meta = OAuth2UserMeta::new(user_name, role, namespace_privilege);
+        meta.groups = groups;
+        meta.custom_roles = custom_roles;
+        Ok(meta)
     }
 }
 
 impl Actor for OAuth2MsgActor {
     type Context = Context<Self>;
 
-    fn started(&mut self, _ctx: &mut Self::Context) {
+    fn started(&mut self, ctx: &mut Self::Context) {
         log::info!("OAuth2MsgActor started");
+        if self.oauth2_config.oauth2_oidc_enable {
+            self.discover(ctx);
+        }
     }
 }
 
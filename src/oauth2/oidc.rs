@@ -0,0 +1,204 @@
+use base64::{engine::general_purpose, Engine};
+use hmac::{Hmac, Mac};
+use oauth2::basic::{
+    BasicErrorResponse, BasicRevocationErrorResponse, BasicTokenIntrospectionResponse,
+    BasicTokenType,
+};
+use oauth2::{ExtraTokenFields, StandardRevocableToken, StandardTokenResponse};
+use rand::Rng;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::Sha256;
+
+use crate::common::jwt_utils::{
+    decode_base64_url, is_same_issuer, validate_claims, JwksKeySet, JwksSource, CLOCK_SKEW_SECONDS,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 授权请求state的有效期，单位秒
+const STATE_TTL_SECONDS: i64 = 600;
+
+///
+/// token响应中的OIDC扩展字段
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct IdTokenFields {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
+}
+
+impl ExtraTokenFields for IdTokenFields {}
+
+pub type OidcTokenResponse = StandardTokenResponse<IdTokenFields, BasicTokenType>;
+
+pub type OAuth2Client = oauth2::Client<
+    BasicErrorResponse,
+    OidcTokenResponse,
+    BasicTokenType,
+    BasicTokenIntrospectionResponse,
+    StandardRevocableToken,
+    BasicRevocationErrorResponse,
+>;
+
+///
+/// OIDC发现文档中使用到的字段
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct OidcProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    #[serde(default)]
+    pub userinfo_endpoint: Option<String>,
+    pub jwks_uri: String,
+}
+
+///
+/// OIDC服务端，缓存发现文档与签名公钥
+pub struct OidcProvider {
+    pub metadata: OidcProviderMetadata,
+    key_set: JwksKeySet,
+}
+
+impl OidcProvider {
+    pub async fn discover(http_client: &Client, issuer: &str) -> anyhow::Result<Self> {
+        let url = format!(
+            "{}/.well-known/openid-configuration",
+            issuer.trim_end_matches('/')
+        );
+        let metadata: OidcProviderMetadata = http_client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if !is_same_issuer(&metadata.issuer, issuer) {
+            return Err(anyhow::anyhow!(
+                "oidc issuer mismatch, expected {}, discovered {}",
+                issuer,
+                &metadata.issuer
+            ));
+        }
+        let key_set =
+            JwksKeySet::load(JwksSource::Url(metadata.jwks_uri.clone()), http_client).await?;
+        Ok(Self { metadata, key_set })
+    }
+
+    ///
+    /// 校验ID Token的签名与标准claims，返回claims
+    pub async fn verify_id_token(
+        &self,
+        http_client: &Client,
+        id_token: &str,
+        client_id: &str,
+        nonce: Option<&str>,
+    ) -> anyhow::Result<Map<String, Value>> {
+        let claims = self.key_set.verify(http_client, id_token).await?;
+        validate_claims(
+            &claims,
+            &self.metadata.issuer,
+            client_id,
+            nonce,
+            crate::now_second_i32() as i64,
+        )?;
+        Ok(claims)
+    }
+}
+
+///
+/// 授权请求的state签名，并由state派生PKCE code_verifier与OIDC nonce；
+/// 回调时不需要在节点间共享授权请求的临时数据
+pub struct OAuth2StateHelper {
+    key: Vec<u8>,
+}
+
+impl OAuth2StateHelper {
+    pub fn new(key: &str) -> Self {
+        let key = if key.is_empty() {
+            log::warn!("oauth2 client secret is empty, state key is generated randomly");
+            let mut bytes = vec![0u8; 32];
+            rand::thread_rng().fill(bytes.as_mut_slice());
+            bytes
+        } else {
+            key.as_bytes().to_vec()
+        };
+        Self { key }
+    }
+
+    /// state格式：`{timestamp}.{random}.{signature}`
+    pub fn build_state(&self, now: i64) -> String {
+        let mut random = [0u8; 16];
+        rand::thread_rng().fill(&mut random);
+        let payload = format!(
+            "{}.{}",
+            now,
+            general_purpose::URL_SAFE_NO_PAD.encode(random)
+        );
+        let signature = self.sign("state", &payload);
+        format!("{}.{}", payload, signature)
+    }
+
+    pub fn verify_state(&self, state: &str, now: i64) -> anyhow::Result<()> {
+        let (payload, signature) = state
+            .rsplit_once('.')
+            .ok_or_else(|| anyhow::anyhow!("oauth2 state is invalid"))?;
+        let signature = decode_base64_url(signature)?;
+        self.new_mac("state", payload)
+            .verify_slice(&signature)
+            .map_err(|_| anyhow::anyhow!("oauth2 state is invalid"))?;
+        let timestamp: i64 = payload
+            .split('.')
+            .next()
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| anyhow::anyhow!("oauth2 state is invalid"))?;
+        if timestamp + STATE_TTL_SECONDS < now || timestamp > now + CLOCK_SKEW_SECONDS {
+            return Err(anyhow::anyhow!("oauth2 state is expired"));
+        }
+        Ok(())
+    }
+
+    pub fn pkce_verifier(&self, state: &str) -> String {
+        self.sign("pkce", state)
+    }
+
+    pub fn nonce(&self, state: &str) -> String {
+        self.sign("nonce", state)
+    }
+
+    fn new_mac(&self, label: &str, value: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC can take key of any size");
+        mac.update(label.as_bytes());
+        mac.update(b".");
+        mac.update(value.as_bytes());
+        mac
+    }
+
+    fn sign(&self, label: &str, value: &str) -> String {
+        general_purpose::URL_SAFE_NO_PAD.encode(self.new_mac(label, value).finalize().into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oauth2_state_sign() {
+        let helper = OAuth2StateHelper::new("secret");
+        let now = 1_700_000_000;
+        let state = helper.build_state(now);
+        assert!(helper.verify_state(&state, now + 10).is_ok());
+        assert!(helper
+            .verify_state(&state, now + STATE_TTL_SECONDS + 1)
+            .is_err());
+        assert!(OAuth2StateHelper::new("other")
+            .verify_state(&state, now)
+            .is_err());
+        let tampered = format!("2{}", &state[1..]);
+        assert!(helper.verify_state(&tampered, now).is_err());
+        let verifier = helper.pkce_verifier(&state);
+        assert_eq!(verifier.len(), 43);
+        assert_ne!(verifier, helper.nonce(&state));
+    }
+}
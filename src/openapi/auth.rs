@@ -104,6 +104,8 @@ async fn do_login(
                 username: user.username.clone(),
                 roles: user.roles.unwrap_or_default(),
                 extend_infos: user.extend_info.unwrap_or_default(),
+                scopes: vec![],
+                groups: vec![],
             });
             /*
             let cache_req = CacheManagerReq::Set {
@@ -7,19 +7,27 @@ use chrono::Local;
 use serde::{Deserialize, Serialize};
 
 use crate::common::appdata::AppShareData;
-use crate::common::model::ApiResult;
+use crate::common::model::{ApiResult, ClientVersion};
 use crate::common::option_utils::OptionUtils;
 use crate::common::string_utils::StringUtils;
-use crate::common::web_utils::get_req_body;
+use crate::common::web_utils::{get_client_ip, get_req_body};
+use crate::config::config_crypto::ENCRYPTED_DATA_KEY_UNSUPPORTED;
 use crate::config::config_index::ConfigQueryParam;
 use crate::config::config_type::ConfigType;
+use crate::config::config_validate::ConfigValidateError;
 use crate::config::core::{
     ConfigActor, ConfigCmd, ConfigInfoDto, ConfigKey, ConfigResult, ListenerItem, ListenerResult,
 };
+use crate::config::listener_query::query_cluster_listener_info;
+use crate::config::model::{
+    ConfigCasConflictError, ConfigClientInfo, ConfigGrayReq, ConfigGrayRule,
+    ConfigListenerQueryParam, CLIENT_APP_NAME_LABEL,
+};
 use crate::config::utils::param_utils;
 use crate::config::ConfigUtils;
 use crate::console::v2::ERROR_CODE_SYSTEM_ERROR;
 use crate::merge_web_param;
+use crate::namespace::model::NamespaceQuotaError;
 use crate::openapi::constant::EMPTY;
 use crate::raft::cluster::model::{DelConfigReq, SetConfigReq};
 use crate::utils::select_option_by_clone;
@@ -33,7 +41,11 @@ pub(super) fn service() -> Scope {
                 .route(web::put().to(add_config))
                 .route(web::delete().to(del_config)),
         )
-        .service(web::resource("/listener").route(web::post().to(listener_config)))
+        .service(
+            web::resource("/listener")
+                .route(web::post().to(listener_config))
+                .route(web::get().to(query_config_listeners)),
+        )
 }
 
 #[derive(Serialize, Deserialize)]
@@ -48,6 +60,14 @@ pub struct ConfigWebParams {
     pub search: Option<String>,   //search type
     pub page_no: Option<usize>,   //use at search
     pub page_size: Option<usize>, //use at search
+    pub beta: Option<bool>,
+    pub cas_md5: Option<String>,
+    /// 配置标签，多个用逗号分隔
+    #[serde(rename = "config_tags", alias = "configTags")]
+    pub config_tags: Option<String>,
+    /// 客户端加密插件的数据密钥；配置由服务端落盘加密，不支持客户端加密内容
+    #[serde(alias = "encryptedDataKey")]
+    pub encrypted_data_key: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -64,6 +84,7 @@ pub struct ConfigSearchPage<T> {
 #[allow(dead_code)]
 pub struct ConfigInfo {
     pub id: Option<String>,
+    /// 兼容nacos返回字段；内容已由服务端解密，固定为空
     pub encrypted_data_key: Option<String>,
     pub app_name: Option<String>,
     pub r#type: Option<String>,
@@ -100,6 +121,13 @@ impl ConfigWebParams {
             search: OptionUtils::select(self.search, other.search),
             page_no: OptionUtils::select(self.page_no, other.page_no),
             page_size: OptionUtils::select(self.page_size, other.page_size),
+            beta: OptionUtils::select(self.beta, other.beta),
+            cas_md5: OptionUtils::select(self.cas_md5, other.cas_md5),
+            config_tags: OptionUtils::select(self.config_tags, other.config_tags),
+            encrypted_data_key: OptionUtils::select(
+                self.encrypted_data_key,
+                other.encrypted_data_key,
+            ),
         }
     }
 
@@ -139,6 +167,7 @@ impl ConfigWebParams {
             offset,
             like_group: self.group,
             like_data_id: self.data_id,
+            tags: ConfigUtils::build_tags(self.config_tags.as_deref().unwrap_or_default()),
             query_context: true,
             ..Default::default()
         };
@@ -156,6 +185,7 @@ impl ConfigWebParams {
             offset,
             group: self.group.map(Arc::new),
             data_id: self.data_id.map(Arc::new),
+            tags: ConfigUtils::build_tags(self.config_tags.as_deref().unwrap_or_default()),
             query_context: true,
             ..Default::default()
         };
@@ -175,6 +205,7 @@ pub struct ConfigWebConfirmedParam {
 }
 
 pub(crate) async fn add_config(
+    req: HttpRequest,
     a: web::Query<ConfigWebParams>,
     payload: web::Payload,
     appdata: web::Data<Arc<AppShareData>>,
@@ -199,10 +230,41 @@ pub(crate) async fn add_config(
         }
     }
 
+    if !StringUtils::is_option_empty(&selected_param.encrypted_data_key) {
+        return HttpResponse::BadRequest().body(ENCRYPTED_DATA_KEY_UNSUPPORTED);
+    }
     let config_type = StringUtils::map_not_empty(selected_param.r#type.clone());
     let desc = StringUtils::map_not_empty(selected_param.desc.clone());
+    let cas_md5 = StringUtils::map_not_empty(selected_param.cas_md5.clone());
+    let tags = selected_param
+        .config_tags
+        .as_ref()
+        .map(|v| ConfigUtils::build_tags(v));
+    let beta_ips = req
+        .headers()
+        .get("betaIps")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_owned())
+        .unwrap_or_default();
     let param = selected_param.to_confirmed_param();
     match param {
+        Ok(p) if !beta_ips.is_empty() => {
+            let gray_req = ConfigGrayReq::Publish {
+                key: ConfigKey::new(&p.data_id, &p.group, &p.tenant).build_key(),
+                value: Arc::new(p.content),
+                rule: ConfigGrayRule::new_by_ips(&beta_ips),
+                op_user: None,
+            };
+            match appdata.config_route.gray_config(gray_req).await {
+                Ok(_) => HttpResponse::Ok()
+                    .content_type("text/html; charset=utf-8")
+                    .body("true"),
+                Err(err) if err.is::<ConfigValidateError>() => {
+                    HttpResponse::BadRequest().body(err.to_string())
+                }
+                Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+            }
+        }
         Ok(p) => {
             let mut req = SetConfigReq::new(
                 ConfigKey::new(&p.data_id, &p.group, &p.tenant),
@@ -210,10 +272,21 @@ pub(crate) async fn add_config(
             );
             req.config_type = config_type.map(|v| ConfigType::new_by_value(v.as_ref()).get_value());
             req.desc = desc.map(Arc::new);
+            req.cas_md5 = cas_md5.map(Arc::new);
+            req.tags = tags;
             match appdata.config_route.set_config(req).await {
                 Ok(_) => HttpResponse::Ok()
                     .content_type("text/html; charset=utf-8")
                     .body("true"),
+                Err(err) if err.is::<ConfigCasConflictError>() => {
+                    HttpResponse::Conflict().body(err.to_string())
+                }
+                Err(err) if err.is::<ConfigValidateError>() => {
+                    HttpResponse::BadRequest().body(err.to_string())
+                }
+                Err(err) if err.is::<NamespaceQuotaError>() => {
+                    HttpResponse::TooManyRequests().body(err.to_string())
+                }
                 Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
             }
         }
@@ -248,6 +321,20 @@ pub(crate) async fn del_config(
 
     let param = selected_param.to_confirmed_param();
     match param {
+        Ok(p) if selected_param.beta.unwrap_or(false) => {
+            let gray_req = ConfigGrayReq::Stop {
+                key: ConfigKey::new(&p.data_id, &p.group, &p.tenant).build_key(),
+            };
+            match appdata.config_route.gray_config(gray_req).await {
+                Ok(_) => HttpResponse::Ok()
+                    .content_type("text/html; charset=utf-8")
+                    .body("true"),
+                Err(err) if err.is::<ConfigValidateError>() => {
+                    HttpResponse::BadRequest().body(err.to_string())
+                }
+                Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+            }
+        }
         Ok(p) => {
             let req = DelConfigReq::new(ConfigKey::new(&p.data_id, &p.group, &p.tenant));
             match appdata.config_route.del_config(req).await {
@@ -262,6 +349,7 @@ pub(crate) async fn del_config(
 }
 
 pub(crate) async fn get_config(
+    req: HttpRequest,
     web_param: web::Query<ConfigWebParams>,
     appdata: web::Data<Arc<AppShareData>>,
 ) -> impl Responder {
@@ -277,28 +365,33 @@ pub(crate) async fn get_config(
     let param = web_param.to_confirmed_param();
     match param {
         Ok(p) => {
-            let cmd = ConfigCmd::GET(ConfigKey::new(&p.data_id, &p.group, &p.tenant));
-            match appdata.config_addr.send(cmd).await {
-                Ok(res) => {
-                    let r: ConfigResult = res.unwrap();
-                    match r {
-                        ConfigResult::Data {
-                            value: v,
-                            md5,
-                            config_type,
-                            ..
-                        } => HttpResponse::Ok()
-                            .content_type(
-                                config_type
-                                    .map(|v| ConfigType::new_by_value(&v))
-                                    .unwrap_or_default()
-                                    .get_media_type(),
-                            )
-                            .insert_header(("content-md5", md5.as_ref().to_string()))
-                            .body(v.as_ref().as_bytes().to_vec()),
-                        _ => HttpResponse::NotFound().body("config data not exist"),
-                    }
-                }
+            let client = ConfigClientInfo::new_by_ip(get_client_ip(&req));
+            let cmd =
+                ConfigCmd::GetByClient(ConfigKey::new(&p.data_id, &p.group, &p.tenant), client);
+            let res = match appdata.config_addr.send(cmd).await {
+                Ok(res) => res,
+                Err(err) => Err(err.into()),
+            };
+            match res {
+                Ok(r) => match r {
+                    ConfigResult::Data {
+                        value: v,
+                        md5,
+                        config_type,
+                        beta,
+                        ..
+                    } => HttpResponse::Ok()
+                        .content_type(
+                            config_type
+                                .map(|v| ConfigType::new_by_value(&v))
+                                .unwrap_or_default()
+                                .get_media_type(),
+                        )
+                        .insert_header(("content-md5", md5.as_ref().to_string()))
+                        .insert_header(("isBeta", beta.to_string()))
+                        .body(v.as_ref().as_bytes().to_vec()),
+                    _ => HttpResponse::NotFound().body("config data not exist"),
+                },
                 Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
             }
         }
@@ -313,25 +406,26 @@ async fn do_search_config(
     let page_size = query_param.limit;
     let page_number = query_param.offset / query_param.limit + 1;
     let cmd = ConfigCmd::QueryPageInfo(Box::new(query_param));
-    match appdata.config_addr.send(cmd).await {
-        Ok(res) => {
-            let r: ConfigResult = res.unwrap();
-            match r {
-                ConfigResult::ConfigInfoPage(total_count, list) => {
-                    let page = ConfigSearchPage {
-                        total_count: Some(total_count),
-                        page_number: Some(page_number),
-                        pages_available: Some(total_count.div_ceil(page_size)),
-                        page_items: Some(list),
-                    };
-                    HttpResponse::Ok().json(page)
-                }
-                _ => HttpResponse::Ok().json(ApiResult::<()>::error(
-                    ERROR_CODE_SYSTEM_ERROR.to_string(),
-                    None,
-                )),
+    let res = match appdata.config_addr.send(cmd).await {
+        Ok(res) => res,
+        Err(err) => Err(err.into()),
+    };
+    match res {
+        Ok(r) => match r {
+            ConfigResult::ConfigInfoPage(total_count, list) => {
+                let page = ConfigSearchPage {
+                    total_count: Some(total_count),
+                    page_number: Some(page_number),
+                    pages_available: Some(total_count.div_ceil(page_size)),
+                    page_items: Some(list),
+                };
+                HttpResponse::Ok().json(page)
             }
-        }
+            _ => HttpResponse::Ok().json(ApiResult::<()>::error(
+                ERROR_CODE_SYSTEM_ERROR.to_string(),
+                None,
+            )),
+        },
         Err(err) => HttpResponse::Ok().json(ApiResult::<()>::error(
             ERROR_CODE_SYSTEM_ERROR.to_string(),
             Some(err.to_string()),
@@ -358,6 +452,27 @@ impl ListenerParams {
     }
 }
 
+///
+/// http客户端通过请求头上报sdk版本与应用名
+fn build_http_client_info(req: &HttpRequest) -> ConfigClientInfo {
+    let mut client = ConfigClientInfo::new_by_ip(get_client_ip(req));
+    let header_value = |name: &str| {
+        req.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .filter(|v| !v.is_empty())
+    };
+    if let Some(v) = header_value("Client-Version") {
+        client.client_version = Arc::new(ClientVersion::from_string(v));
+    }
+    if let Some(v) = header_value("Client-AppName") {
+        let mut labels = HashMap::new();
+        labels.insert(CLIENT_APP_NAME_LABEL.to_owned(), v.to_owned());
+        client.labels = Arc::new(labels);
+    }
+    client
+}
+
 pub(super) async fn listener_config(
     _req: HttpRequest,
     a: web::Query<ListenerParams>,
@@ -397,7 +512,8 @@ pub(super) async fn listener_config(
         }
     }
     //println!("timeout header:{:?},time_out:{}",_req.headers().get("Long-Pulling-Timeout") ,time_out);
-    let cmd = ConfigCmd::LISTENER(list, tx, time_out);
+    let client = build_http_client_info(&_req);
+    let cmd = ConfigCmd::LISTENER(list, tx, time_out, client);
     let _ = config_addr.send(cmd).await;
     let res = rx.await.unwrap();
     let v = match res {
@@ -418,3 +534,84 @@ pub(super) async fn listener_config(
         .content_type("text/html; charset=utf-8")
         .body(v)
 }
+
+///
+/// nacos配置监听查询结果，key为客户端ip或配置groupKey，value为客户端上报的md5
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupkeyListenserStatus {
+    pub collect_status: u16,
+    pub lisenters_groupkey_status: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientListenerParams {
+    pub ip: Option<String>,
+    pub tenant: Option<String>,
+}
+
+///
+/// 查询监听指定配置的客户端，汇总集群所有节点
+pub(super) async fn query_config_listeners(
+    web::Query(param): web::Query<ConfigWebParams>,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let query_param = ConfigListenerQueryParam {
+        tenant: param.tenant.map(Arc::new),
+        group: param.group.map(Arc::new),
+        data_id: param.data_id.filter(|v| !v.is_empty()).map(Arc::new),
+        ip: None,
+    };
+    if query_param.data_id.is_none() {
+        return HttpResponse::BadRequest().body("dataId is empty");
+    }
+    match query_cluster_listener_info(appdata.get_ref(), query_param).await {
+        Ok(result) => HttpResponse::Ok().json(GroupkeyListenserStatus {
+            collect_status: 200,
+            lisenters_groupkey_status: result
+                .list
+                .into_iter()
+                .map(|e| (e.ip.as_ref().to_owned(), e.md5.as_ref().to_owned()))
+                .collect(),
+        }),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+///
+/// 查询指定ip的客户端在命名空间下监听的配置，汇总集群所有节点
+pub(super) async fn query_client_listeners(
+    web::Query(param): web::Query<ClientListenerParams>,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let ip = match param.ip.filter(|v| !v.is_empty()) {
+        Some(v) => Arc::new(v),
+        None => return HttpResponse::BadRequest().body("ip is empty"),
+    };
+    let tenant = ConfigUtils::default_tenant(param.tenant.unwrap_or_default());
+    let query_param = ConfigListenerQueryParam {
+        ip: Some(ip),
+        ..Default::default()
+    };
+    match query_cluster_listener_info(appdata.get_ref(), query_param).await {
+        Ok(result) => HttpResponse::Ok().json(GroupkeyListenserStatus {
+            collect_status: 200,
+            lisenters_groupkey_status: result
+                .list
+                .into_iter()
+                .filter(|e| e.tenant.as_str() == tenant)
+                .map(|e| {
+                    // 与nacos的groupKey格式一致
+                    let key = if e.tenant.is_empty() {
+                        format!("{}+{}", e.data_id, e.group)
+                    } else {
+                        format!("{}+{}+{}", e.data_id, e.group, e.tenant)
+                    };
+                    (key, e.md5.as_ref().to_owned())
+                })
+                .collect(),
+        }),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
@@ -1,4 +1,7 @@
-use crate::openapi::config::api::{add_config, del_config, get_config, listener_config};
+use crate::openapi::config::api::{
+    add_config, del_config, get_config, listener_config, query_client_listeners,
+    query_config_listeners,
+};
 use crate::openapi::constant::{CONFIG_V1_BASE_PATH, EMPTY};
 use crate::openapi::RouteConf;
 use actix_web::web::{scope, ServiceConfig};
@@ -7,6 +10,8 @@ use actix_web::{web, Scope};
 pub mod api;
 pub mod v2;
 
+pub use v2::config_v2_route;
+
 /// current implement for version 1
 pub fn openapi_service(conf: RouteConf) -> Vec<Scope> {
     vec![openapi_v1_route(conf)]
@@ -17,15 +22,23 @@ pub fn openapi_v1_route(_conf: RouteConf) -> Scope {
 }
 
 pub fn config_v1_route(config: &mut ServiceConfig) {
-    config.service(
-        scope("/nacos/v1/cs/configs")
-            .service(
-                web::resource(EMPTY)
-                    .route(web::get().to(get_config))
-                    .route(web::post().to(add_config))
-                    .route(web::put().to(add_config))
-                    .route(web::delete().to(del_config)),
-            )
-            .service(web::resource("/listener").route(web::post().to(listener_config))),
-    );
+    config
+        .service(
+            scope("/nacos/v1/cs/configs")
+                .service(
+                    web::resource(EMPTY)
+                        .route(web::get().to(get_config))
+                        .route(web::post().to(add_config))
+                        .route(web::put().to(add_config))
+                        .route(web::delete().to(del_config)),
+                )
+                .service(
+                    web::resource("/listener")
+                        .route(web::post().to(listener_config))
+                        .route(web::get().to(query_config_listeners)),
+                ),
+        )
+        .service(
+            web::resource("/nacos/v1/cs/listener").route(web::get().to(query_client_listeners)),
+        );
 }
@@ -1 +1,476 @@
+use std::sync::Arc;
 
+use actix::Addr;
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+
+use crate::common::appdata::AppShareData;
+use crate::common::model::TokenSession;
+use crate::common::option_utils::OptionUtils;
+use crate::common::string_utils::StringUtils;
+use crate::common::web_utils::get_client_ip;
+use crate::config::config_index::ConfigQueryParam;
+use crate::config::config_type::ConfigType;
+use crate::config::config_validate::ConfigValidateError;
+use crate::config::core::{ConfigActor, ConfigCmd, ConfigHistoryInfoDto, ConfigKey, ConfigResult};
+use crate::config::dal::ConfigHistoryParam;
+use crate::config::model::ConfigClientInfo;
+use crate::config::utils::param_utils;
+use crate::config::ConfigUtils;
+use crate::merge_web_param;
+use crate::namespace::model::NamespaceQuotaError;
+use crate::openapi::v2::model::{ApiResult, PARAMETER_MISSING};
+use crate::raft::cluster::model::{DelConfigReq, SetConfigReq};
+use crate::utils::get_md5;
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigV2Params {
+    pub data_id: Option<String>,
+    pub group: Option<String>,
+    pub namespace_id: Option<String>,
+    pub tenant: Option<String>,
+    pub content: Option<String>,
+    pub desc: Option<String>,
+    pub r#type: Option<String>,
+    pub tag: Option<String>,
+    pub config_tags: Option<String>,
+    pub nid: Option<u64>,
+    pub id: Option<u64>,
+    pub page_no: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
+impl ConfigV2Params {
+    pub fn merge(self, o: Self) -> Self {
+        Self {
+            data_id: OptionUtils::select(self.data_id, o.data_id),
+            group: OptionUtils::select(self.group, o.group),
+            namespace_id: OptionUtils::select(self.namespace_id, o.namespace_id),
+            tenant: OptionUtils::select(self.tenant, o.tenant),
+            content: OptionUtils::select(self.content, o.content),
+            desc: OptionUtils::select(self.desc, o.desc),
+            r#type: OptionUtils::select(self.r#type, o.r#type),
+            tag: OptionUtils::select(self.tag, o.tag),
+            config_tags: OptionUtils::select(self.config_tags, o.config_tags),
+            nid: OptionUtils::select(self.nid, o.nid),
+            id: OptionUtils::select(self.id, o.id),
+            page_no: OptionUtils::select(self.page_no, o.page_no),
+            page_size: OptionUtils::select(self.page_size, o.page_size),
+        }
+    }
+
+    fn get_tenant(&self) -> String {
+        ConfigUtils::default_tenant(
+            self.namespace_id
+                .clone()
+                .or_else(|| self.tenant.clone())
+                .unwrap_or_default(),
+        )
+    }
+
+    fn to_key(&self) -> anyhow::Result<ConfigKey> {
+        let tenant = self.get_tenant();
+        param_utils::check_tenant(&Some(tenant.clone()))?;
+        param_utils::check_param(
+            &self.data_id,
+            &self.group,
+            &Some(String::from("datumId")),
+            &Some(String::from("content")),
+        )?;
+        Ok(ConfigKey::new(
+            self.data_id.as_ref().unwrap(),
+            self.group.as_ref().unwrap(),
+            &tenant,
+        ))
+    }
+
+    fn to_history_param(&self) -> anyhow::Result<ConfigHistoryParam> {
+        let key = self.to_key()?;
+        let limit = self.page_size.unwrap_or(100) as i64;
+        let offset = (self.page_no.unwrap_or(1).max(1) as i64 - 1) * limit;
+        Ok(ConfigHistoryParam {
+            data_id: Some(key.data_id.to_string()),
+            group: Some(key.group.to_string()),
+            tenant: Some(key.tenant.to_string()),
+            limit: Some(limit),
+            offset: Some(offset),
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigHistoryV2Vo {
+    pub id: String,
+    pub last_id: i64,
+    pub data_id: String,
+    pub group: String,
+    pub tenant: String,
+    pub md5: String,
+    pub content: String,
+    pub src_user: Option<String>,
+    pub op_type: String,
+    pub created_time: i64,
+    pub last_modified_time: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigHistoryPageV2Vo {
+    pub total_count: usize,
+    pub page_number: usize,
+    pub pages_available: usize,
+    pub page_items: Vec<ConfigHistoryV2Vo>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigBasicInfoV2Vo {
+    pub data_id: Arc<String>,
+    pub group: Arc<String>,
+    pub tenant: Arc<String>,
+}
+
+fn param_error(msg: String) -> HttpResponse {
+    HttpResponse::BadRequest().json(ApiResult::param_error(msg))
+}
+
+fn get_op_user(req: &HttpRequest) -> Option<Arc<String>> {
+    req.extensions()
+        .get::<Arc<TokenSession>>()
+        .map(|session| session.username.clone())
+}
+
+pub async fn get_config(
+    req: HttpRequest,
+    web::Query(param): web::Query<ConfigV2Params>,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let key = match param.to_key() {
+        Ok(v) => v,
+        Err(err) => return param_error(err.to_string()),
+    };
+    let client = ConfigClientInfo::new_by_ip(get_client_ip(&req));
+    do_get_config(&appdata.config_addr, key, client).await
+}
+
+async fn do_get_config(
+    config_addr: &Addr<ConfigActor>,
+    key: ConfigKey,
+    client: ConfigClientInfo,
+) -> HttpResponse {
+    match config_addr.send(ConfigCmd::GetByClient(key, client)).await {
+        Ok(Ok(ConfigResult::Data { value, .. })) => {
+            HttpResponse::Ok().json(ApiResult::success(value.as_ref().to_owned()))
+        }
+        Ok(Ok(_)) => {
+            HttpResponse::NotFound().json(ApiResult::not_found("config data not exist".to_owned()))
+        }
+        Ok(Err(err)) => {
+            HttpResponse::InternalServerError().json(ApiResult::server_error(err.to_string()))
+        }
+        Err(err) => {
+            HttpResponse::InternalServerError().json(ApiResult::server_error(err.to_string()))
+        }
+    }
+}
+
+pub async fn publish_config(
+    req: HttpRequest,
+    web::Query(param): web::Query<ConfigV2Params>,
+    payload: web::Payload,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let param = merge_web_param!(param, payload);
+    let key = match param.to_key() {
+        Ok(v) => v,
+        Err(err) => return param_error(err.to_string()),
+    };
+    let content = match &param.content {
+        Some(v) if !v.is_empty() => v.to_owned(),
+        _ => {
+            return HttpResponse::BadRequest().json(ApiResult::error(
+                PARAMETER_MISSING,
+                "parameter missing".to_owned(),
+                "content is empty".to_owned(),
+            ))
+        }
+    };
+    let mut set_req = SetConfigReq::new(key, Arc::new(content));
+    set_req.config_type = StringUtils::map_not_empty(param.r#type)
+        .map(|v| ConfigType::new_by_value(v.as_ref()).get_value());
+    set_req.desc = StringUtils::map_not_empty(param.desc).map(Arc::new);
+    set_req.tags = param.config_tags.map(|v| ConfigUtils::build_tags(&v));
+    set_req.op_user = get_op_user(&req);
+    match appdata.config_route.set_config(set_req).await {
+        Ok(_) => HttpResponse::Ok().json(ApiResult::success(true)),
+        Err(err) if err.is::<ConfigValidateError>() => param_error(err.to_string()),
+        Err(err) if err.is::<NamespaceQuotaError>() => {
+            HttpResponse::TooManyRequests().json(ApiResult::quota_exceeded(err.to_string()))
+        }
+        Err(err) => {
+            HttpResponse::InternalServerError().json(ApiResult::server_error(err.to_string()))
+        }
+    }
+}
+
+pub async fn delete_config(
+    web::Query(param): web::Query<ConfigV2Params>,
+    payload: web::Payload,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let param = merge_web_param!(param, payload);
+    let key = match param.to_key() {
+        Ok(v) => v,
+        Err(err) => return param_error(err.to_string()),
+    };
+    match appdata
+        .config_route
+        .del_config(DelConfigReq::new(key))
+        .await
+    {
+        Ok(_) => HttpResponse::Ok().json(ApiResult::success(true)),
+        Err(err) => {
+            HttpResponse::InternalServerError().json(ApiResult::server_error(err.to_string()))
+        }
+    }
+}
+
+pub async fn query_history_list(
+    web::Query(param): web::Query<ConfigV2Params>,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let history_param = match param.to_history_param() {
+        Ok(v) => v,
+        Err(err) => return param_error(err.to_string()),
+    };
+    do_query_history_list(&appdata.config_addr, history_param).await
+}
+
+async fn do_query_history_list(
+    config_addr: &Addr<ConfigActor>,
+    history_param: ConfigHistoryParam,
+) -> HttpResponse {
+    let page_size = history_param.limit.unwrap_or(100).max(1) as usize;
+    let page_number = history_param.offset.unwrap_or(0) as usize / page_size + 1;
+    let cmd = ConfigCmd::QueryHistoryPageInfo(Box::new(history_param));
+    match config_addr.send(cmd).await {
+        Ok(Ok(ConfigResult::ConfigHistoryInfoPage(total_count, list))) => {
+            let page = ConfigHistoryPageV2Vo {
+                total_count,
+                page_number,
+                pages_available: total_count.div_ceil(page_size),
+                page_items: list.into_iter().map(build_history_vo).collect(),
+            };
+            HttpResponse::Ok().json(ApiResult::success(page))
+        }
+        Ok(Ok(_)) => {
+            HttpResponse::InternalServerError().json(ApiResult::server_error(String::new()))
+        }
+        Ok(Err(err)) => {
+            HttpResponse::InternalServerError().json(ApiResult::server_error(err.to_string()))
+        }
+        Err(err) => {
+            HttpResponse::InternalServerError().json(ApiResult::server_error(err.to_string()))
+        }
+    }
+}
+
+///
+/// 查询指定历史记录;
+/// 参数nid为历史记录id
+pub async fn query_history(
+    web::Query(param): web::Query<ConfigV2Params>,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let nid = param.nid.unwrap_or_default() as i64;
+    query_history_by(param, appdata, |list| {
+        list.into_iter().find(|e| e.id.unwrap_or_default() == nid)
+    })
+    .await
+}
+
+///
+/// 查询指定历史记录的上一个版本;
+/// 参数id为历史记录id
+pub async fn query_previous_history(
+    web::Query(param): web::Query<ConfigV2Params>,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let id = param.id.unwrap_or_default() as i64;
+    query_history_by(param, appdata, |list| {
+        list.into_iter()
+            .filter(|e| e.id.unwrap_or_default() < id)
+            .max_by_key(|e| e.id.unwrap_or_default())
+    })
+    .await
+}
+
+async fn query_history_by<F>(
+    param: ConfigV2Params,
+    appdata: web::Data<Arc<AppShareData>>,
+    select: F,
+) -> HttpResponse
+where
+    F: FnOnce(Vec<ConfigHistoryInfoDto>) -> Option<ConfigHistoryInfoDto>,
+{
+    let mut history_param = match param.to_history_param() {
+        Ok(v) => v,
+        Err(err) => return param_error(err.to_string()),
+    };
+    history_param.offset = Some(0);
+    history_param.limit = None;
+    let cmd = ConfigCmd::QueryHistoryPageInfo(Box::new(history_param));
+    match appdata.config_addr.send(cmd).await {
+        Ok(Ok(ConfigResult::ConfigHistoryInfoPage(_, list))) => {
+            if let Some(item) = select(list) {
+                HttpResponse::Ok().json(ApiResult::success(build_history_vo(item)))
+            } else {
+                HttpResponse::NotFound()
+                    .json(ApiResult::not_found("history data not exist".to_owned()))
+            }
+        }
+        Ok(Ok(_)) => {
+            HttpResponse::InternalServerError().json(ApiResult::server_error(String::new()))
+        }
+        Ok(Err(err)) => {
+            HttpResponse::InternalServerError().json(ApiResult::server_error(err.to_string()))
+        }
+        Err(err) => {
+            HttpResponse::InternalServerError().json(ApiResult::server_error(err.to_string()))
+        }
+    }
+}
+
+///
+/// 查询命名空间下的配置列表
+pub async fn query_namespace_configs(
+    web::Query(param): web::Query<ConfigV2Params>,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let query_param = ConfigQueryParam {
+        tenant: Some(Arc::new(param.get_tenant())),
+        tags: ConfigUtils::build_tags(param.config_tags.as_deref().unwrap_or_default()),
+        limit: 0xffff_ffff,
+        ..Default::default()
+    };
+    let cmd = ConfigCmd::QueryPageInfo(Box::new(query_param));
+    match appdata.config_addr.send(cmd).await {
+        Ok(Ok(ConfigResult::ConfigInfoPage(_, list))) => {
+            let list: Vec<ConfigBasicInfoV2Vo> = list
+                .into_iter()
+                .map(|e| ConfigBasicInfoV2Vo {
+                    data_id: e.data_id,
+                    group: e.group,
+                    tenant: e.tenant,
+                })
+                .collect();
+            HttpResponse::Ok().json(ApiResult::success(list))
+        }
+        Ok(Ok(_)) => {
+            HttpResponse::InternalServerError().json(ApiResult::server_error(String::new()))
+        }
+        Ok(Err(err)) => {
+            HttpResponse::InternalServerError().json(ApiResult::server_error(err.to_string()))
+        }
+        Err(err) => {
+            HttpResponse::InternalServerError().json(ApiResult::server_error(err.to_string()))
+        }
+    }
+}
+
+fn build_history_vo(item: ConfigHistoryInfoDto) -> ConfigHistoryV2Vo {
+    let content = item.content.unwrap_or_default();
+    let modified_time = item.modified_time.unwrap_or_default();
+    ConfigHistoryV2Vo {
+        id: item.id.unwrap_or_default().to_string(),
+        last_id: -1,
+        data_id: item.data_id.unwrap_or_default(),
+        group: item.group.unwrap_or_default(),
+        tenant: item.tenant.unwrap_or_default(),
+        md5: get_md5(&content),
+        content,
+        src_user: item.op_user,
+        op_type: "U".to_owned(),
+        created_time: modified_time,
+        last_modified_time: modified_time,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::model::ConfigRaftCmd;
+    use crate::now_millis_i64;
+    use actix::Actor;
+
+    async fn add_config(addr: &Addr<ConfigActor>, key: &ConfigKey, value: &str, history_id: u64) {
+        addr.send(ConfigRaftCmd::ConfigAdd {
+            key: key.build_key(),
+            value: Arc::new(value.to_owned()),
+            config_type: None,
+            desc: None,
+            history_id,
+            history_table_id: None,
+            op_time: now_millis_i64(),
+            op_user: None,
+            cas_md5: None,
+            tags: None,
+            note: None,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    }
+
+    async fn response_json(res: HttpResponse) -> serde_json::Value {
+        let body = actix_web::body::to_bytes(res.into_body()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[actix::test]
+    async fn get_config_v2_envelope() {
+        let addr = ConfigActor::new().start();
+        let key = ConfigKey::new("app.yaml", "DEFAULT_GROUP", "");
+        add_config(&addr, &key, "v1", 1).await;
+        let client = ConfigClientInfo::new_by_ip("127.0.0.1".to_owned());
+
+        let res = do_get_config(&addr, key, client.clone()).await;
+        assert_eq!(res.status(), 200);
+        let body = response_json(res).await;
+        assert_eq!(body["code"], 0);
+        assert_eq!(body["data"], "v1");
+
+        let missing = ConfigKey::new("none.yaml", "DEFAULT_GROUP", "");
+        let res = do_get_config(&addr, missing, client).await;
+        assert_eq!(res.status(), 404);
+        let body = response_json(res).await;
+        assert_ne!(body["code"], 0);
+    }
+
+    #[actix::test]
+    async fn query_history_list_v2_page() {
+        let addr = ConfigActor::new().start();
+        let key = ConfigKey::new("app.yaml", "DEFAULT_GROUP", "");
+        for (i, value) in ["v1", "v2", "v3"].iter().enumerate() {
+            add_config(&addr, &key, value, i as u64 + 1).await;
+        }
+        let param = ConfigV2Params {
+            data_id: Some("app.yaml".to_owned()),
+            group: Some("DEFAULT_GROUP".to_owned()),
+            page_no: Some(2),
+            page_size: Some(2),
+            ..Default::default()
+        };
+        let res = do_query_history_list(&addr, param.to_history_param().unwrap()).await;
+        assert_eq!(res.status(), 200);
+        let body = response_json(res).await;
+        assert_eq!(body["code"], 0);
+        assert_eq!(body["data"]["totalCount"], 3);
+        assert_eq!(body["data"]["pageNumber"], 2);
+        assert_eq!(body["data"]["pagesAvailable"], 2);
+        assert_eq!(body["data"]["pageItems"].as_array().unwrap().len(), 1);
+    }
+}
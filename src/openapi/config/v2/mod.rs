@@ -1 +1,32 @@
+use actix_web::web;
+use actix_web::web::{scope, ServiceConfig};
+
+use crate::openapi::constant::EMPTY;
+
 mod api;
+
+pub fn config_v2_route(config: &mut ServiceConfig) {
+    config.service(
+        scope("/nacos/v2/cs")
+            .service(
+                web::resource("/config")
+                    .route(web::get().to(api::get_config))
+                    .route(web::post().to(api::publish_config))
+                    .route(web::put().to(api::publish_config))
+                    .route(web::delete().to(api::delete_config)),
+            )
+            .service(
+                scope("/history")
+                    .service(web::resource(EMPTY).route(web::get().to(api::query_history)))
+                    .service(web::resource("/list").route(web::get().to(api::query_history_list)))
+                    .service(
+                        web::resource("/previous")
+                            .route(web::get().to(api::query_previous_history)),
+                    )
+                    .service(
+                        web::resource("/configs")
+                            .route(web::get().to(api::query_namespace_configs)),
+                    ),
+            ),
+    );
+}
@@ -1,3 +1,4 @@
+use crate::backup::model::{BackupManagerReq, BackupManagerResult};
 use crate::common::appdata::AppShareData;
 use crate::health::model::{CheckHealthResult, HealthManagerRequest, HealthManagerResponse};
 use actix_web::{web, HttpResponse, Responder};
@@ -20,9 +21,29 @@ pub(crate) async fn health_info(appdata: web::Data<Arc<AppShareData>>) -> impl R
     }
 }
 
+///
+/// 定时备份状态，最近一次备份失败时返回503
+pub(crate) async fn backup_health_info(appdata: web::Data<Arc<AppShareData>>) -> impl Responder {
+    if let Ok(Ok(BackupManagerResult::Status(status))) = appdata
+        .backup_manager
+        .send(BackupManagerReq::QueryStatus)
+        .await
+    {
+        if status.is_healthy() {
+            HttpResponse::Ok().json(status)
+        } else {
+            HttpResponse::ServiceUnavailable().json(status)
+        }
+    } else {
+        HttpResponse::InternalServerError().body("request backup_manager error")
+    }
+}
+
 pub fn health_config(config: &mut web::ServiceConfig) {
     config
         .service(web::resource("/health").route(web::get().to(health_info)))
         .service(web::resource("/nacos/health").route(web::get().to(health_info)))
-        .service(web::resource("/rnacos/health").route(web::get().to(health_info)));
+        .service(web::resource("/rnacos/health").route(web::get().to(health_info)))
+        .service(web::resource("/nacos/health/backup").route(web::get().to(backup_health_info)))
+        .service(web::resource("/rnacos/health/backup").route(web::get().to(backup_health_info)));
 }
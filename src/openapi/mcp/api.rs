@@ -1,6 +1,7 @@
 use super::model::{JsonRpcError, JsonRpcRequest, JsonRpcResponse, McpPath};
 use crate::common::appdata::AppShareData;
 use crate::common::get_app_version;
+use crate::common::web_utils::get_client_ip;
 use crate::mcp::model::actor_model::{McpManagerReq, McpManagerResult};
 use crate::mcp::model::mcp::McpServer;
 use crate::mcp::model::tools::{ConvertType, McpTool, ToolFunctionValue};
@@ -12,6 +13,7 @@ use serde_json::{json, Value};
 
 use std::collections::HashMap;
 use std::fmt::Display;
+use std::net::IpAddr;
 use std::sync::Arc;
 use std::time::SystemTime;
 use uuid::Uuid;
@@ -148,20 +150,28 @@ pub async fn mcp_handler(
         }
         headers.insert(key.as_str(), value.as_bytes());
     }
-    let rpc_response =
-        match handle_request(&app_share_data, request, &mcp_server, &session_id, headers).await {
-            Ok(value) => value,
-            Err(e) => {
-                match e {
-                    HandleOtherResult::Accepted => {
-                        return Ok(HttpResponse::Accepted()
-                            //.content_type("application/json")
-                            .insert_header(("mcp-session-id", session_id))
-                            .body(""));
-                    }
-                };
-            }
-        };
+    let rpc_response = match handle_request(
+        &app_share_data,
+        request,
+        &mcp_server,
+        &session_id,
+        headers,
+        get_client_ip(&req).parse().ok(),
+    )
+    .await
+    {
+        Ok(value) => value,
+        Err(e) => {
+            match e {
+                HandleOtherResult::Accepted => {
+                    return Ok(HttpResponse::Accepted()
+                        //.content_type("application/json")
+                        .insert_header(("mcp-session-id", session_id))
+                        .body(""));
+                }
+            };
+        }
+    };
     Ok(HttpResponse::Ok()
         .content_type("application/json")
         .insert_header(("mcp-session-id", session_id))
@@ -174,6 +184,7 @@ pub async fn handle_request(
     mcp_server: &Arc<McpServer>,
     session_id: &String,
     headers: HashMap<&str, &[u8]>,
+    client_ip: Option<IpAddr>,
 ) -> Result<JsonRpcResponse, HandleOtherResult> {
     let start = SystemTime::now();
     let request_log_info = format!("|mcp|client_request|{}|{}", session_id, &request.method);
@@ -232,6 +243,7 @@ pub async fn handle_request(
                 &mcp_server,
                 &app_share_data,
                 headers,
+                client_ip,
                 &mut log_args,
             )
             .await
@@ -391,6 +403,7 @@ async fn handle_tools_call(
     mcp_server: &Arc<McpServer>,
     app_share_data: &Arc<AppShareData>,
     headers: HashMap<&str, &[u8]>,
+    client_ip: Option<IpAddr>,
     log_args: &mut McpHandleLogArgs,
 ) -> anyhow::Result<JsonRpcResponse> {
     if let Some(params_value) = params {
@@ -401,7 +414,9 @@ async fn handle_tools_call(
             *log_args = McpHandleLogArgs::Arg(format!("tool:{}", tool_name));
 
             let (tool, url) =
-                match select_tool_and_url(tool_name, &mcp_server, &args, app_share_data).await {
+                match select_tool_and_url(tool_name, &mcp_server, &args, app_share_data, client_ip)
+                    .await
+                {
                     Ok(result) => result,
                     Err(error) => {
                         *log_args =
@@ -543,6 +558,7 @@ async fn select_tool_and_url<'a>(
     server: &'a Arc<McpServer>,
     value: &'a serde_json::Value,
     app_share_data: &Arc<AppShareData>,
+    client_ip: Option<IpAddr>,
 ) -> anyhow::Result<(&'a McpTool, String)> {
     for tool in server.release_value.tools.iter() {
         if tool.tool_name.as_str() == tool_name {
@@ -553,7 +569,7 @@ async fn select_tool_and_url<'a>(
             );
             if let Ok(Ok(NamingResult::SelectInstance(instance))) = app_share_data
                 .naming_addr
-                .send(NamingCmd::SelectOneInstance(service_key))
+                .send(NamingCmd::SelectOneInstance(service_key, client_ip))
                 .await
             {
                 let host = instance.map(|i| (i.ip.clone(), i.port as u16));
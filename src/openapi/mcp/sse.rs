@@ -1,4 +1,5 @@
 use crate::common::appdata::AppShareData;
+use crate::common::web_utils::get_client_ip;
 use crate::grpc::handler::NAMING_ROUTE_REQUEST;
 use crate::grpc::PayloadUtils;
 use crate::mcp::model::actor_model::{McpManagerReq, McpManagerResult};
@@ -110,7 +111,16 @@ pub async fn sse_message(
                 String::from_utf8_lossy(value.as_bytes()).to_string(),
             );
         }
-        return match post_to_remote(&app_share_data, &path, body.into_inner(), headers).await {
+        let client_ip = get_client_ip(&req);
+        return match post_to_remote(
+            &app_share_data,
+            &path,
+            body.into_inner(),
+            headers,
+            client_ip,
+        )
+        .await
+        {
             Ok(_) => Ok(HttpResponse::Accepted().body("Accepted")),
             Err(e) => Ok(HttpResponse::InternalServerError().body(format!("error: {}", e))),
         };
@@ -128,6 +138,7 @@ pub async fn sse_message(
         &mcp_server,
         &path.session_id,
         headers,
+        get_client_ip(&req).parse().ok(),
     )
     .await
     {
@@ -156,6 +167,7 @@ async fn post_to_remote(
     path: &SseMessagePath,
     request: JsonRpcRequest,
     headers: HashMap<String, String>,
+    client_ip: String,
 ) -> anyhow::Result<()> {
     let addr = app_share_data
         .naming_node_manage
@@ -167,6 +179,7 @@ async fn post_to_remote(
         session_id: path.session_id.clone(),
         request,
         headers,
+        client_ip: Some(client_ip),
     };
     let request = serde_json::to_string(&req).unwrap_or_default();
     let payload = PayloadUtils::build_payload(NAMING_ROUTE_REQUEST, request);
@@ -52,6 +52,7 @@ impl OpenMcpServerQueryParam {
             limit,
             namespace_id: Some(namespace_id),
             name_filter: self.name_filter.as_ref().map(|s| Arc::new(s.clone())),
+            scope_filter: None,
         }
     }
 }
@@ -98,6 +99,7 @@ impl OpenToolSpecQueryParam {
             namespace_id: Some(namespace_id),
             group_filter: self.group_filter.clone(),
             tool_name_filter: self.tool_name_filter.clone(),
+            scope_filter: None,
         }
     }
 }
@@ -5,9 +5,13 @@ use crate::common::datetime_utils;
 use crate::common::model::TokenSession;
 use crate::metrics::core::MetricsManager;
 use crate::metrics::metrics_key::MetricsKey;
-use crate::metrics::model::{MetricsItem, MetricsRecord, MetricsRequest};
+use crate::metrics::model::{LabeledMetricsItem, MetricsItem, MetricsRecord, MetricsRequest};
 use crate::raft::cache::model::{CacheKey, CacheType};
 use crate::raft::cluster::model::{RouterRequest, RouterResponse};
+use crate::user::access_key::{get_access_key_session, is_access_key_token};
+use crate::user::jwt_auth::is_jwt_token;
+use crate::user::permission::PermissionRequest;
+use crate::user::role::get_user_permission;
 use actix::Addr;
 use actix_http::body::EitherBody;
 use actix_http::HttpMessage;
@@ -99,6 +103,7 @@ where
             true
         };
         let ignore_metrics = IGNORE_METRICS_PATH.contains(&path);
+        let route_labels = RouteLabels::new(&request);
         let app_share_data = self.app_share_data.clone();
         let service = self.service.clone();
         Box::pin(async move {
@@ -116,18 +121,35 @@ where
             } else {
                 EMPTY_ARC_STRING.clone()
             };
+            let mut message = "unknown user!";
             let pass = if !enable_auth || !is_check_path {
                 true
             } else if token.is_empty() {
                 false
-            } else if let Ok(Some(session)) = get_user_session(
-                &app_share_data,
-                CacheKey::new(CacheType::ApiTokenSession, token.clone()),
-            )
-            .await
+            } else if let Ok(Some(session)) =
+                get_token_session(&app_share_data, token.clone()).await
             {
+                let path = request.path().to_owned();
+                let method = request.method().as_str().to_owned();
+                let has_permission = if let Some(mut permission_req) =
+                    PermissionRequest::from_openapi_path(&path, &method)
+                {
+                    permission_req.fill_from_request(&mut request).await;
+                    get_user_permission(
+                        &app_share_data.role_manager,
+                        session.roles.clone(),
+                        session.groups.clone(),
+                    )
+                    .await
+                    .is_ok_and(|v| v.check_with_scopes(&permission_req, &session.scopes))
+                } else {
+                    true
+                };
                 request.extensions_mut().insert(session);
-                true
+                if !has_permission {
+                    message = "no permission!";
+                }
+                has_permission
             } else {
                 false
             };
@@ -144,14 +166,20 @@ where
                         .unwrap_or_default()
                         .as_secs_f64();
                     if !ignore_metrics {
-                        record_req_metrics(&app_share_data.metrics_manager, duration, success);
+                        record_req_metrics(
+                            &app_share_data.metrics_manager,
+                            duration,
+                            success,
+                            route_labels,
+                            item.response().status().as_u16(),
+                        );
                     }
                     ServiceResponse::map_into_left_body(item)
                 })
             } else {
-                //没有登录
-                let body=format!("{{\"timestamp\":\"{}\",\"status\":403,\"error\":\"Forbidden\",\"message\":\"unknown user!\",\"path\":\"{}\"}}"
-                                 ,datetime_utils::get_now_timestamp_str(offset),request.path());
+                //没有登录或没有权限
+                let body=format!("{{\"timestamp\":\"{}\",\"status\":403,\"error\":\"Forbidden\",\"message\":\"{}\",\"path\":\"{}\"}}"
+                                 ,datetime_utils::get_now_timestamp_str(offset),message,request.path());
                 let response = HttpResponse::Forbidden()
                     .insert_header(("Content-Type", "application/json;charset=UTF-8"))
                     .body(body)
@@ -163,7 +191,13 @@ where
                     .duration_since(start)
                     .unwrap_or_default()
                     .as_secs_f64();
-                record_req_metrics(&app_share_data.metrics_manager, duration, false);
+                record_req_metrics(
+                    &app_share_data.metrics_manager,
+                    duration,
+                    false,
+                    route_labels,
+                    403,
+                );
                 Ok(res)
             }
         })
@@ -209,12 +243,41 @@ async fn peek_body_token(request: &mut ServiceRequest) -> Arc<String> {
     result
 }
 
-fn bytes_to_payload(buf: web::Bytes) -> dev::Payload {
+pub(crate) fn bytes_to_payload(buf: web::Bytes) -> dev::Payload {
     let (_, mut pl) = actix_http::h1::Payload::create(true);
     pl.unread_data(buf);
     dev::Payload::from(pl)
 }
 
+///
+/// 访问密钥格式的token走密钥鉴权，开启JWT鉴权时JWT格式的token校验签名，其它token查询登录会话
+async fn get_token_session(
+    app_share_data: &Arc<AppShareData>,
+    token: Arc<String>,
+) -> anyhow::Result<Option<Arc<TokenSession>>> {
+    if is_access_key_token(&token) {
+        get_access_key_session(&app_share_data.access_key_manager, token).await
+    } else if let Some(manager) = app_share_data
+        .jwt_auth_manager
+        .as_ref()
+        .filter(|_| is_jwt_token(&app_share_data.jwt_auth_manager, &token))
+    {
+        match manager.get_session(&token).await {
+            Ok(session) => Ok(Some(session)),
+            Err(err) => {
+                log::warn!("jwt auth failed,{}", err);
+                Ok(None)
+            }
+        }
+    } else {
+        get_user_session(
+            app_share_data,
+            CacheKey::new(CacheType::ApiTokenSession, token),
+        )
+        .await
+    }
+}
+
 async fn get_user_session(
     app_share_data: &Arc<AppShareData>,
     key: CacheKey,
@@ -252,7 +315,39 @@ async fn get_user_session(
     }
 }
 
-fn record_req_metrics(metrics_manager: &Addr<MetricsManager>, duration: f64, _success: bool) {
+///
+/// http指标标签，路由使用匹配到的路径模板，未匹配时为unmatched，避免按原始路径产生大量序列
+struct RouteLabels {
+    method: &'static str,
+    route: String,
+}
+
+impl RouteLabels {
+    fn new(request: &ServiceRequest) -> Self {
+        let method = match request.method().as_str() {
+            "GET" => "GET",
+            "POST" => "POST",
+            "PUT" => "PUT",
+            "DELETE" => "DELETE",
+            "PATCH" => "PATCH",
+            "HEAD" => "HEAD",
+            "OPTIONS" => "OPTIONS",
+            _ => "OTHER",
+        };
+        let route = request
+            .match_pattern()
+            .unwrap_or_else(|| "unmatched".to_owned());
+        Self { method, route }
+    }
+}
+
+fn record_req_metrics(
+    metrics_manager: &Addr<MetricsManager>,
+    duration: f64,
+    _success: bool,
+    route_labels: RouteLabels,
+    status: u16,
+) {
     metrics_manager.do_send(MetricsRequest::BatchRecord(vec![
         MetricsItem::new(
             MetricsKey::HttpRequestHandleRtHistogram,
@@ -263,6 +358,19 @@ fn record_req_metrics(metrics_manager: &Addr<MetricsManager>, duration: f64, _su
             MetricsRecord::CounterInc(1),
         ),
     ]));
+    let RouteLabels { method, route } = route_labels;
+    metrics_manager.do_send(MetricsRequest::LabeledBatchRecord(vec![
+        LabeledMetricsItem::new(
+            MetricsKey::HttpRequestRouteHandleRtHistogram,
+            vec![method.to_owned(), route.clone()],
+            MetricsRecord::HistogramRecord(duration as f32 * 1000f32),
+        ),
+        LabeledMetricsItem::new(
+            MetricsKey::HttpRequestRouteTotalCount,
+            vec![method.to_owned(), route, status.to_string()],
+            MetricsRecord::CounterInc(1),
+        ),
+    ]));
 }
 
 #[cfg(test)]
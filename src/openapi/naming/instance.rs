@@ -5,12 +5,13 @@ use std::sync::Arc;
 
 use actix::prelude::*;
 use actix_web::dev::HttpServiceFactory;
-use actix_web::{get, http::header, put, web, HttpResponse, Responder, Scope};
+use actix_web::{get, http::header, put, web, HttpRequest, HttpResponse, Responder, Scope};
 use serde::{Deserialize, Serialize};
 
 use crate::common::appdata::AppShareData;
-use crate::common::web_utils::get_req_body;
+use crate::common::web_utils::{get_client_ip, get_req_body};
 use crate::merge_web_param;
+use crate::namespace::model::NamespaceQuotaError;
 use crate::naming::api_model::InstanceVO;
 use crate::naming::core::{NamingActor, NamingCmd, NamingResult};
 use crate::naming::model::{Instance, InstanceUpdateTag, ServiceKey};
@@ -98,6 +99,9 @@ pub async fn update_instance(
                     .await
                 {
                     Ok(_) => HttpResponse::Ok().body("ok"),
+                    Err(e) if e.is::<NamespaceQuotaError>() => {
+                        HttpResponse::TooManyRequests().body(e.to_string())
+                    }
                     Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
                 }
             }
@@ -165,6 +169,9 @@ pub async fn beat_instance(
                             .insert_header(header::ContentType(mime::APPLICATION_JSON))
                             .body(v)
                     }
+                    Err(e) if e.is::<NamespaceQuotaError>() => {
+                        HttpResponse::TooManyRequests().body(e.to_string())
+                    }
                     Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
                 }
             }
@@ -175,11 +182,15 @@ pub async fn beat_instance(
 
 #[get("/list")]
 pub async fn get_instance_list(
+    req: HttpRequest,
     param: web::Query<InstanceWebQueryListParams>,
     naming_addr: web::Data<Addr<NamingActor>>,
 ) -> impl Responder {
     let only_healthy = get_bool_from_string(&param.healthy_only, true);
     let addr = param.get_addr();
+    let client_ip = param
+        .get_client_ip()
+        .or_else(|| get_client_ip(&req).parse().ok());
     match param.to_clusters_key() {
         Ok((key, clusters)) => {
             match naming_addr
@@ -188,6 +199,7 @@ pub async fn get_instance_list(
                     clusters,
                     only_healthy,
                     addr,
+                    client_ip,
                 ))
                 .await
             {
@@ -20,6 +20,8 @@ mod operator;
 pub(crate) mod service;
 mod v2;
 
+pub use v2::naming_v2_route;
+
 pub fn openapi_service(conf: RouteConf) -> Vec<Scope> {
     vec![openapi_v1_route(conf)]
 }
@@ -2,12 +2,13 @@
 use crate::common::option_utils::OptionUtils;
 use crate::common::string_utils::StringUtils;
 use crate::naming::model::{Instance, ServiceKey};
+use crate::naming::selector::SERVICE_SELECTOR_METADATA_KEY;
 use crate::naming::service::{ServiceInfoDto, SubscriberInfoDto};
 use crate::naming::NamingUtils;
 use crate::utils::get_bool_from_string;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -172,6 +173,10 @@ impl InstanceWebQueryListParams {
         }
         None
     }
+
+    pub(crate) fn get_client_ip(&self) -> Option<IpAddr> {
+        self.client_ip.as_ref().and_then(|v| v.parse().ok())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -371,7 +376,7 @@ pub struct ServiceInfoVo {
     pub name: Arc<String>,
     pub protect_threshold: f32,
     pub metadata: Option<Arc<HashMap<String, String>>>,
-    pub selector: HashMap<String, String>,
+    pub selector: serde_json::Value,
     pub clusters: Vec<ClusterVo>,
 }
 
@@ -386,9 +391,12 @@ pub struct ClusterVo {
 impl ServiceInfoVo {
     /// 从 ServiceInfoDto 创建 ServiceInfoVo，使用默认的命名空间和集群信息
     pub fn from_dto(dto: ServiceInfoDto, namespace_id: Arc<String>) -> Self {
-        let mut selector = HashMap::new();
-        selector.insert("type".to_owned(), "none".to_string());
-        selector.insert("contextType".to_owned(), "NONE".to_string());
+        let selector = dto
+            .metadata
+            .as_ref()
+            .and_then(|m| m.get(SERVICE_SELECTOR_METADATA_KEY))
+            .and_then(|v| serde_json::from_str(v).ok())
+            .unwrap_or_else(|| serde_json::json!({"type":"none","contextType":"NONE"}));
         let mut health_checker = HashMap::new();
         health_checker.insert("type".to_owned(), "TCP".to_string());
 
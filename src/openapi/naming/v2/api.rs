@@ -1 +1,622 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
 
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+
+use crate::common::appdata::AppShareData;
+use crate::common::option_utils::OptionUtils;
+use crate::common::web_utils::get_client_ip;
+use crate::grpc::bistream_manage::{BiStreamManageCmd, BiStreamManageResult};
+use crate::merge_web_param;
+use crate::namespace::model::NamespaceQuotaError;
+use crate::naming::api_model::{InstanceVO, ServiceInfoParam};
+use crate::naming::core::{NamingCmd, NamingResult};
+use crate::naming::model::{Instance, InstanceUpdateTag, ServiceKey};
+use crate::naming::NamingUtils;
+use crate::now_millis_i64;
+use crate::openapi::naming::model::InstanceWebParams;
+use crate::openapi::v2::model::{ApiResult, PARAMETER_MISSING};
+use crate::utils::get_bool_from_string;
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceV2Params {
+    pub namespace_id: Option<String>,
+    pub group_name: Option<String>,
+    pub service_name: Option<String>,
+    pub clusters: Option<String>,
+    pub cluster_name: Option<String>,
+    pub healthy_only: Option<String>,
+    pub protect_threshold: Option<f32>,
+    pub metadata: Option<String>,
+    pub selector: Option<String>,
+    pub health_check: Option<String>,
+    pub page_no: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
+impl ServiceV2Params {
+    pub fn merge(self, o: Self) -> Self {
+        Self {
+            namespace_id: OptionUtils::select(self.namespace_id, o.namespace_id),
+            group_name: OptionUtils::select(self.group_name, o.group_name),
+            service_name: OptionUtils::select(self.service_name, o.service_name),
+            clusters: OptionUtils::select(self.clusters, o.clusters),
+            cluster_name: OptionUtils::select(self.cluster_name, o.cluster_name),
+            healthy_only: OptionUtils::select(self.healthy_only, o.healthy_only),
+            protect_threshold: OptionUtils::select(self.protect_threshold, o.protect_threshold),
+            metadata: OptionUtils::select(self.metadata, o.metadata),
+            selector: OptionUtils::select(self.selector, o.selector),
+            health_check: OptionUtils::select(self.health_check, o.health_check),
+            page_no: OptionUtils::select(self.page_no, o.page_no),
+            page_size: OptionUtils::select(self.page_size, o.page_size),
+        }
+    }
+
+    fn to_service_key(&self) -> anyhow::Result<ServiceKey> {
+        let service_name = self.service_name.clone().unwrap_or_default();
+        if service_name.is_empty() {
+            return Err(anyhow::anyhow!("serviceName is empty"));
+        }
+        let namespace_id =
+            NamingUtils::default_namespace(self.namespace_id.clone().unwrap_or_default());
+        let group_name = NamingUtils::default_group(self.group_name.clone().unwrap_or_default());
+        Ok(ServiceKey::new(&namespace_id, &group_name, &service_name))
+    }
+
+    fn into_service_info_param(self) -> ServiceInfoParam {
+        ServiceInfoParam {
+            namespace_id: self.namespace_id,
+            group_name: self.group_name,
+            service_name: self.service_name,
+            protect_threshold: self.protect_threshold,
+            metadata: self.metadata,
+            selector: self.selector,
+            health_check: self.health_check,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceMetadataBatchParams {
+    pub namespace_id: Option<String>,
+    pub group_name: Option<String>,
+    pub service_name: Option<String>,
+    pub consistency_type: Option<String>,
+    pub instances: Option<String>,
+    pub metadata: Option<String>,
+}
+
+impl InstanceMetadataBatchParams {
+    pub fn merge(self, o: Self) -> Self {
+        Self {
+            namespace_id: OptionUtils::select(self.namespace_id, o.namespace_id),
+            group_name: OptionUtils::select(self.group_name, o.group_name),
+            service_name: OptionUtils::select(self.service_name, o.service_name),
+            consistency_type: OptionUtils::select(self.consistency_type, o.consistency_type),
+            instances: OptionUtils::select(self.instances, o.instances),
+            metadata: OptionUtils::select(self.metadata, o.metadata),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceShortParams {
+    pub ip: String,
+    pub port: u32,
+    pub cluster_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientV2Params {
+    pub client_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceInfoV2Vo {
+    pub name: String,
+    pub group_name: Arc<String>,
+    pub clusters: String,
+    pub cache_millis: i64,
+    pub hosts: Vec<InstanceVO>,
+    pub last_ref_time: i64,
+    pub checksum: String,
+    #[serde(rename = "allIPs")]
+    pub all_ips: bool,
+    pub reach_protection_threshold: bool,
+    pub valid: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceDetailV2Vo {
+    pub namespace: Arc<String>,
+    pub group_name: Arc<String>,
+    pub service_name: Arc<String>,
+    pub protect_threshold: f32,
+    pub metadata: Option<Arc<HashMap<String, String>>>,
+    pub ephemeral: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceListV2Vo {
+    pub count: usize,
+    pub services: Vec<Arc<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataBatchV2Vo {
+    pub updated: Vec<Arc<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientListV2Vo {
+    pub count: usize,
+    pub client_ids: Vec<Arc<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientServiceV2Vo {
+    pub namespace: Arc<String>,
+    pub group: Arc<String>,
+    pub service_name: Arc<String>,
+    pub registered_instance: Option<ClientInstanceV2Vo>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientInstanceV2Vo {
+    pub ip: Arc<String>,
+    pub port: u32,
+    pub cluster: String,
+}
+
+fn param_error(msg: String) -> HttpResponse {
+    HttpResponse::BadRequest().json(ApiResult::param_error(msg))
+}
+
+fn server_error(msg: String) -> HttpResponse {
+    HttpResponse::InternalServerError().json(ApiResult::server_error(msg))
+}
+
+fn build_instance(param: InstanceWebParams) -> Result<Instance, String> {
+    let instance = param.convert_to_instance()?;
+    if !instance.check_valid() {
+        return Err("instance check is invalid".to_owned());
+    }
+    Ok(instance)
+}
+
+pub async fn register_instance(
+    web::Query(param): web::Query<InstanceWebParams>,
+    payload: web::Payload,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let param = merge_web_param!(param, payload);
+    let update_tag = InstanceUpdateTag {
+        weight: param.weight.is_some(),
+        metadata: param.metadata.is_some(),
+        enabled: param.enabled.is_some(),
+        ephemeral: param.ephemeral.is_some(),
+        from_update: false,
+    };
+    do_update_instance(param, update_tag, appdata).await
+}
+
+pub async fn update_instance(
+    web::Query(param): web::Query<InstanceWebParams>,
+    payload: web::Payload,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let param = merge_web_param!(param, payload);
+    let update_tag = InstanceUpdateTag {
+        weight: param.weight.is_some(),
+        metadata: param.metadata.is_some(),
+        enabled: param.enabled.is_some(),
+        ephemeral: param.ephemeral.is_some(),
+        from_update: true,
+    };
+    do_update_instance(param, update_tag, appdata).await
+}
+
+async fn do_update_instance(
+    param: InstanceWebParams,
+    update_tag: InstanceUpdateTag,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> HttpResponse {
+    let instance = match build_instance(param) {
+        Ok(v) => v,
+        Err(err) => return param_error(err),
+    };
+    match appdata
+        .naming_route
+        .update_instance(instance, Some(update_tag))
+        .await
+    {
+        Ok(_) => HttpResponse::Ok().json(ApiResult::success("ok".to_owned())),
+        Err(err) if err.is::<NamespaceQuotaError>() => {
+            HttpResponse::TooManyRequests().json(ApiResult::quota_exceeded(err.to_string()))
+        }
+        Err(err) => server_error(err.to_string()),
+    }
+}
+
+pub async fn deregister_instance(
+    web::Query(param): web::Query<InstanceWebParams>,
+    payload: web::Payload,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let param = merge_web_param!(param, payload);
+    let instance = match build_instance(param) {
+        Ok(v) => v,
+        Err(err) => return param_error(err),
+    };
+    match appdata.naming_route.delete_instance(instance).await {
+        Ok(_) => HttpResponse::Ok().json(ApiResult::success("ok".to_owned())),
+        Err(err) => server_error(err.to_string()),
+    }
+}
+
+pub async fn get_instance(
+    web::Query(param): web::Query<InstanceWebParams>,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let instance = match build_instance(param) {
+        Ok(v) => v,
+        Err(err) => return param_error(err),
+    };
+    match appdata.naming_addr.send(NamingCmd::Query(instance)).await {
+        Ok(Ok(NamingResult::Instance(v))) => {
+            HttpResponse::Ok().json(ApiResult::success(InstanceVO::from_instance(&v)))
+        }
+        Ok(Ok(_)) => {
+            HttpResponse::NotFound().json(ApiResult::not_found("instance not exist".to_owned()))
+        }
+        Ok(Err(err)) => server_error(err.to_string()),
+        Err(err) => server_error(err.to_string()),
+    }
+}
+
+pub async fn get_instance_list(
+    req: HttpRequest,
+    web::Query(param): web::Query<ServiceV2Params>,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let key = match param.to_service_key() {
+        Ok(v) => v,
+        Err(err) => return param_error(err.to_string()),
+    };
+    let clusters = param
+        .cluster_name
+        .clone()
+        .or(param.clusters.clone())
+        .unwrap_or_default();
+    let only_healthy = get_bool_from_string(&param.healthy_only, false);
+    let client_ip = get_client_ip(&req).parse().ok();
+    let cmd = NamingCmd::QueryList(key.clone(), clusters.clone(), only_healthy, None, client_ip);
+    match appdata.naming_addr.send(cmd).await {
+        Ok(Ok(NamingResult::InstanceList(list))) => {
+            let now = now_millis_i64();
+            let vo = ServiceInfoV2Vo {
+                name: key.get_join_service_name(),
+                group_name: key.group_name.clone(),
+                clusters,
+                cache_millis: 10000,
+                hosts: list.iter().map(|e| InstanceVO::from_instance(e)).collect(),
+                last_ref_time: now,
+                checksum: now.to_string(),
+                all_ips: false,
+                reach_protection_threshold: false,
+                valid: true,
+            };
+            HttpResponse::Ok().json(ApiResult::success(vo))
+        }
+        Ok(Ok(_)) => server_error(String::new()),
+        Ok(Err(err)) => server_error(err.to_string()),
+        Err(err) => server_error(err.to_string()),
+    }
+}
+
+///
+/// 批量更新或删除实例元数据
+async fn do_batch_metadata(
+    param: InstanceMetadataBatchParams,
+    appdata: web::Data<Arc<AppShareData>>,
+    is_remove: bool,
+) -> HttpResponse {
+    let service_param = ServiceV2Params {
+        namespace_id: param.namespace_id.clone(),
+        group_name: param.group_name.clone(),
+        service_name: param.service_name.clone(),
+        ..Default::default()
+    };
+    let key = match service_param.to_service_key() {
+        Ok(v) => v,
+        Err(err) => return param_error(err.to_string()),
+    };
+    let metadata = match NamingUtils::parse_metadata(&param.metadata.unwrap_or_default()) {
+        Ok(v) if !v.is_empty() => v,
+        _ => {
+            return HttpResponse::BadRequest().json(ApiResult::error(
+                PARAMETER_MISSING,
+                "parameter missing".to_owned(),
+                "metadata is empty".to_owned(),
+            ))
+        }
+    };
+    let targets: Vec<InstanceShortParams> = match &param.instances {
+        Some(v) if !v.is_empty() => match serde_json::from_str(v) {
+            Ok(v) => v,
+            Err(err) => return param_error(err.to_string()),
+        },
+        _ => vec![],
+    };
+    let instances = match appdata
+        .naming_addr
+        .send(NamingCmd::QueryAllInstanceList(key))
+        .await
+    {
+        Ok(Ok(NamingResult::InstanceList(list))) => list,
+        Ok(Ok(_)) => vec![],
+        Ok(Err(err)) => return server_error(err.to_string()),
+        Err(err) => return server_error(err.to_string()),
+    };
+    let mut updated = vec![];
+    for instance in instances {
+        //instances为空时更新服务下全部实例
+        let matched = targets.is_empty()
+            || targets.iter().any(|e| {
+                e.ip == instance.ip.as_str()
+                    && e.port == instance.port
+                    && e.cluster_name
+                        .as_ref()
+                        .map(|c| c.is_empty() || c == &instance.cluster_name)
+                        .unwrap_or(true)
+            });
+        if !matched {
+            continue;
+        }
+        let mut new_instance = instance.as_ref().clone();
+        let mut new_metadata = instance.metadata.as_ref().clone();
+        for (k, v) in &metadata {
+            if is_remove {
+                new_metadata.remove(k);
+            } else {
+                new_metadata.insert(k.to_owned(), v.to_owned());
+            }
+        }
+        new_instance.metadata = Arc::new(new_metadata);
+        let tag = InstanceUpdateTag {
+            weight: false,
+            metadata: true,
+            enabled: false,
+            ephemeral: false,
+            from_update: true,
+        };
+        if let Err(err) = appdata
+            .naming_route
+            .update_instance(new_instance, Some(tag))
+            .await
+        {
+            return server_error(err.to_string());
+        }
+        updated.push(instance.id.clone());
+    }
+    HttpResponse::Ok().json(ApiResult::success(MetadataBatchV2Vo { updated }))
+}
+
+pub async fn update_metadata_batch(
+    web::Query(param): web::Query<InstanceMetadataBatchParams>,
+    payload: web::Payload,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let param = merge_web_param!(param, payload);
+    do_batch_metadata(param, appdata, false).await
+}
+
+pub async fn remove_metadata_batch(
+    web::Query(param): web::Query<InstanceMetadataBatchParams>,
+    payload: web::Payload,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let param = merge_web_param!(param, payload);
+    do_batch_metadata(param, appdata, true).await
+}
+
+pub async fn update_service(
+    web::Query(param): web::Query<ServiceV2Params>,
+    payload: web::Payload,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let param = merge_web_param!(param, payload);
+    match param.into_service_info_param().build_service_info() {
+        Ok(service_info) => {
+            match appdata
+                .naming_addr
+                .send(NamingCmd::UpdateService(service_info))
+                .await
+            {
+                Ok(Ok(_)) => HttpResponse::Ok().json(ApiResult::success("ok".to_owned())),
+                Ok(Err(err)) => server_error(err.to_string()),
+                Err(err) => server_error(err.to_string()),
+            }
+        }
+        Err(err) => param_error(err.to_string()),
+    }
+}
+
+pub async fn remove_service(
+    web::Query(param): web::Query<ServiceV2Params>,
+    payload: web::Payload,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let param = merge_web_param!(param, payload);
+    let key = match param.to_service_key() {
+        Ok(v) => v,
+        Err(err) => return param_error(err.to_string()),
+    };
+    match appdata
+        .naming_addr
+        .send(NamingCmd::RemoveService(key))
+        .await
+    {
+        Ok(Ok(_)) => HttpResponse::Ok().json(ApiResult::success("ok".to_owned())),
+        Ok(Err(err)) => server_error(err.to_string()),
+        Err(err) => server_error(err.to_string()),
+    }
+}
+
+pub async fn get_service(
+    web::Query(param): web::Query<ServiceV2Params>,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let key = match param.to_service_key() {
+        Ok(v) => v,
+        Err(err) => return param_error(err.to_string()),
+    };
+    match appdata
+        .naming_addr
+        .send(NamingCmd::QueryServiceOnly(key.clone()))
+        .await
+    {
+        Ok(Ok(NamingResult::ServiceDto(Some(dto)))) => {
+            let vo = ServiceDetailV2Vo {
+                namespace: key.namespace_id,
+                group_name: dto.group_name,
+                service_name: dto.service_name,
+                protect_threshold: dto.protect_threshold.unwrap_or_default(),
+                metadata: dto.metadata,
+                ephemeral: true,
+            };
+            HttpResponse::Ok().json(ApiResult::success(vo))
+        }
+        Ok(Ok(_)) => {
+            HttpResponse::NotFound().json(ApiResult::not_found("service not exist".to_owned()))
+        }
+        Ok(Err(err)) => server_error(err.to_string()),
+        Err(err) => server_error(err.to_string()),
+    }
+}
+
+pub async fn query_service_list(
+    web::Query(param): web::Query<ServiceV2Params>,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let page_size = param.page_size.unwrap_or(20);
+    let page_index = param.page_no.unwrap_or(1);
+    let namespace_id = NamingUtils::default_namespace(param.namespace_id.unwrap_or_default());
+    let group_name = NamingUtils::default_group(param.group_name.unwrap_or_default());
+    let key = ServiceKey::new(&namespace_id, &group_name, "");
+    match appdata
+        .naming_addr
+        .send(NamingCmd::QueryServicePage(key, page_size, page_index))
+        .await
+    {
+        Ok(Ok(NamingResult::ServicePage((count, services)))) => {
+            HttpResponse::Ok().json(ApiResult::success(ServiceListV2Vo { count, services }))
+        }
+        Ok(Ok(_)) => server_error(String::new()),
+        Ok(Err(err)) => server_error(err.to_string()),
+        Err(err) => server_error(err.to_string()),
+    }
+}
+
+///
+/// 查询客户端列表，包含grpc长链接客户端与注册了实例的客户端
+pub async fn query_client_list(appdata: web::Data<Arc<AppShareData>>) -> impl Responder {
+    let mut client_ids = BTreeSet::new();
+    if let Ok(Ok(BiStreamManageResult::ConnList(list))) = appdata
+        .bi_stream_manage
+        .send(BiStreamManageCmd::QueryConnList)
+        .await
+    {
+        client_ids.extend(list);
+    }
+    if let Ok(Ok(NamingResult::ClientInstanceCount(list))) = appdata
+        .naming_addr
+        .send(NamingCmd::QueryClientInstanceCount)
+        .await
+    {
+        client_ids.extend(list.into_iter().map(|(k, _)| k).filter(|k| !k.is_empty()));
+    }
+    let client_ids: Vec<Arc<String>> = client_ids.into_iter().collect();
+    HttpResponse::Ok().json(ApiResult::success(ClientListV2Vo {
+        count: client_ids.len(),
+        client_ids,
+    }))
+}
+
+pub async fn query_client_publish_list(
+    web::Query(param): web::Query<ClientV2Params>,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let client_id = match param.client_id {
+        Some(v) if !v.is_empty() => Arc::new(v),
+        _ => return param_error("clientId is empty".to_owned()),
+    };
+    match appdata
+        .naming_addr
+        .send(NamingCmd::QueryClientInstanceList(client_id))
+        .await
+    {
+        Ok(Ok(NamingResult::InstanceList(list))) => {
+            let list: Vec<ClientServiceV2Vo> = list
+                .into_iter()
+                .map(|e| ClientServiceV2Vo {
+                    namespace: e.namespace_id.clone(),
+                    group: e.group_name.clone(),
+                    service_name: e.service_name.clone(),
+                    registered_instance: Some(ClientInstanceV2Vo {
+                        ip: e.ip.clone(),
+                        port: e.port,
+                        cluster: e.cluster_name.clone(),
+                    }),
+                })
+                .collect();
+            HttpResponse::Ok().json(ApiResult::success(list))
+        }
+        Ok(Ok(_)) => server_error(String::new()),
+        Ok(Err(err)) => server_error(err.to_string()),
+        Err(err) => server_error(err.to_string()),
+    }
+}
+
+pub async fn query_client_subscribe_list(
+    web::Query(param): web::Query<ClientV2Params>,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let client_id = match param.client_id {
+        Some(v) if !v.is_empty() => Arc::new(v),
+        _ => return param_error("clientId is empty".to_owned()),
+    };
+    match appdata
+        .naming_addr
+        .send(NamingCmd::QueryClientSubscribeKeys(client_id))
+        .await
+    {
+        Ok(Ok(NamingResult::ServiceKeyList(list))) => {
+            let list: Vec<ClientServiceV2Vo> = list
+                .into_iter()
+                .map(|e| ClientServiceV2Vo {
+                    namespace: e.namespace_id,
+                    group: e.group_name,
+                    service_name: e.service_name,
+                    registered_instance: None,
+                })
+                .collect();
+            HttpResponse::Ok().json(ApiResult::success(list))
+        }
+        Ok(Ok(_)) => server_error(String::new()),
+        Ok(Err(err)) => server_error(err.to_string()),
+        Err(err) => server_error(err.to_string()),
+    }
+}
@@ -1 +1,57 @@
+use actix_web::web;
+use actix_web::web::{scope, ServiceConfig};
+
+use crate::openapi::constant::EMPTY;
+use crate::openapi::naming::operator::{mock_get_switches, mock_operator_metrics};
+
 mod api;
+
+pub fn naming_v2_route(config: &mut ServiceConfig) {
+    config.service(
+        scope("/nacos/v2/ns")
+            .service(
+                scope("/instance")
+                    .service(
+                        web::resource(EMPTY)
+                            .route(web::get().to(api::get_instance))
+                            .route(web::post().to(api::register_instance))
+                            .route(web::put().to(api::update_instance))
+                            .route(web::delete().to(api::deregister_instance)),
+                    )
+                    .service(web::resource("/list").route(web::get().to(api::get_instance_list)))
+                    .service(
+                        web::resource("/metadata/batch")
+                            .route(web::put().to(api::update_metadata_batch))
+                            .route(web::delete().to(api::remove_metadata_batch)),
+                    ),
+            )
+            .service(
+                scope("/service")
+                    .service(
+                        web::resource(EMPTY)
+                            .route(web::get().to(api::get_service))
+                            .route(web::post().to(api::update_service))
+                            .route(web::put().to(api::update_service))
+                            .route(web::delete().to(api::remove_service)),
+                    )
+                    .service(web::resource("/list").route(web::get().to(api::query_service_list))),
+            )
+            .service(
+                scope("/client")
+                    .service(web::resource("/list").route(web::get().to(api::query_client_list)))
+                    .service(
+                        web::resource("/publish/list")
+                            .route(web::get().to(api::query_client_publish_list)),
+                    )
+                    .service(
+                        web::resource("/subscribe/list")
+                            .route(web::get().to(api::query_client_subscribe_list)),
+                    ),
+            )
+            .service(
+                scope("/operator")
+                    .service(web::resource("/switches").route(web::get().to(mock_get_switches)))
+                    .service(mock_operator_metrics),
+            ),
+    );
+}
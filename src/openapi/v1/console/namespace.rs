@@ -9,6 +9,9 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// 未设置配置数量配额时返回nacos的默认值
+const DEFAULT_CONFIG_QUOTA: u32 = 200;
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct NamespaceVO {
@@ -26,8 +29,15 @@ impl From<NamespaceInfo> for NamespaceVO {
             namespace: value.namespace_id,
             namespace_show_name: value.namespace_name,
             namespace_desc: None,
-            quota: 200,
-            config_count: 0,
+            quota: value
+                .quota
+                .and_then(|v| v.max_config_count)
+                .filter(|v| *v > 0)
+                .unwrap_or(DEFAULT_CONFIG_QUOTA),
+            config_count: value
+                .usage
+                .map(|v| v.config_count as u32)
+                .unwrap_or_default(),
             r#type: value
                 .r#type
                 .unwrap_or("2".to_string())
@@ -85,6 +95,11 @@ impl From<NamespaceParam> for NamespaceInfo {
             ),
             namespace_name: OptionUtils::select(value.namespace_show_name, value.namespace_name),
             r#type: None,
+            config_schema: None,
+            config_history_max_count: None,
+            config_history_max_days: None,
+            quota: None,
+            usage: None,
         }
     }
 }
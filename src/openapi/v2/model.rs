@@ -1,5 +1,13 @@
 use serde::{Deserialize, Serialize};
 
+/// nacos v2 openapi 错误码
+pub const PARAMETER_MISSING: i32 = 10000;
+pub const PARAMETER_VALIDATE_ERROR: i32 = 20002;
+pub const RESOURCE_NOT_FOUND: i32 = 20004;
+pub const SERVER_ERROR: i32 = 30000;
+/// rnacos扩展错误码，超出命名空间资源配额
+pub const NAMESPACE_QUOTA_EXCEEDED: i32 = 22010;
+
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct ApiResult<T>
 where
@@ -31,6 +39,26 @@ where
     }
 
     pub fn server_error(data: T) -> Self {
-        Self::error(30000, "server error".into(), data)
+        Self::error(SERVER_ERROR, "server error".into(), data)
+    }
+
+    pub fn param_error(data: T) -> Self {
+        Self::error(
+            PARAMETER_VALIDATE_ERROR,
+            "parameter validate error".into(),
+            data,
+        )
+    }
+
+    pub fn quota_exceeded(data: T) -> Self {
+        Self::error(
+            NAMESPACE_QUOTA_EXCEEDED,
+            "namespace quota exceeded".into(),
+            data,
+        )
+    }
+
+    pub fn not_found(data: T) -> Self {
+        Self::error(RESOURCE_NOT_FOUND, "resource not found".into(), data)
     }
 }
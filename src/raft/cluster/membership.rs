@@ -0,0 +1,411 @@
+use std::collections::{BTreeSet, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_raft_ext::raft::ClientWriteRequest;
+use async_raft_ext::RaftStorage;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::common::appdata::AppShareData;
+use crate::raft::cluster::model::{RouteAddr, RouterRequest, RouterResponse};
+use crate::raft::cluster::route::RaftAddrRouter;
+use crate::raft::cluster::router_request;
+use crate::raft::store::ClientRequest;
+
+/// 远程节点状态查询超时时间
+const NODE_STATUS_TIMEOUT: Duration = Duration::from_secs(3);
+
+///
+/// 成员管理的参数校验错误
+#[derive(Debug, Clone, Error)]
+#[error("{0}")]
+pub struct RaftMembershipParamError(pub String);
+
+fn param_error(msg: impl Into<String>) -> anyhow::Error {
+    RaftMembershipParamError(msg.into()).into()
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RaftNodeStatus {
+    pub node_id: u64,
+    pub state: String,
+    pub current_term: u64,
+    pub last_log_index: u64,
+    pub last_applied: u64,
+    pub current_leader: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RaftMemberInfo {
+    pub node_id: u64,
+    pub addr: Option<Arc<String>>,
+    /// voter | learner
+    pub role: String,
+    pub leader: bool,
+    pub current_node: bool,
+    pub reachable: bool,
+    pub state: Option<String>,
+    pub current_term: Option<u64>,
+    /// 节点自身上报的最后日志位置(非主节点记录的复制匹配位置)
+    pub last_log_index: Option<u64>,
+    pub last_applied: Option<u64>,
+    /// 主节点last_log_index与该节点last_log_index的差值
+    pub lag: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RaftMembershipInfo {
+    pub leader_id: Option<u64>,
+    pub current_term: u64,
+    pub leader_last_log_index: Option<u64>,
+    /// 是否处于成员变更的联合共识阶段
+    pub joint_consensus: bool,
+    pub members: Vec<RaftMemberInfo>,
+    pub learners: Vec<RaftMemberInfo>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RaftNodeParam {
+    pub node_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RaftSnapshotResult {
+    pub node_id: u64,
+    pub index: u64,
+    pub term: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum RaftMembershipReq {
+    NodeStatus,
+    PromoteNode { node_id: u64 },
+    RemoveNode { node_id: u64 },
+    Snapshot,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum RaftMembershipResult {
+    None,
+    NodeStatus(RaftNodeStatus),
+    Snapshot(RaftSnapshotResult),
+    ParamError(String),
+    Error(String),
+}
+
+/// 在本节点执行成员管理请求；需要主节点执行的请求在非主节点上返回错误
+pub async fn handle_membership_request(
+    app: &Arc<AppShareData>,
+    req: RaftMembershipReq,
+) -> anyhow::Result<RaftMembershipResult> {
+    match req {
+        RaftMembershipReq::NodeStatus => {
+            Ok(RaftMembershipResult::NodeStatus(local_node_status(app)))
+        }
+        RaftMembershipReq::PromoteNode { node_id } => {
+            check_local_leader(app)?;
+            promote_node(app, node_id).await?;
+            Ok(RaftMembershipResult::None)
+        }
+        RaftMembershipReq::RemoveNode { node_id } => {
+            check_local_leader(app)?;
+            remove_node(app, node_id).await?;
+            Ok(RaftMembershipResult::None)
+        }
+        RaftMembershipReq::Snapshot => {
+            let snapshot = app.raft_store.do_log_compaction().await?;
+            Ok(RaftMembershipResult::Snapshot(RaftSnapshotResult {
+                node_id: app.sys_config.raft_node_id,
+                index: snapshot.index,
+                term: snapshot.term,
+            }))
+        }
+    }
+}
+
+/// 路由请求处理时把错误包装到结果中，避免远程调用丢失错误信息
+pub async fn handle_route_membership_request(
+    app: &Arc<AppShareData>,
+    req: RaftMembershipReq,
+) -> RaftMembershipResult {
+    match handle_membership_request(app, req).await {
+        Ok(v) => v,
+        Err(err) if err.is::<RaftMembershipParamError>() => {
+            RaftMembershipResult::ParamError(err.to_string())
+        }
+        Err(err) => RaftMembershipResult::Error(err.to_string()),
+    }
+}
+
+async fn remote_membership_request(
+    app: &Arc<AppShareData>,
+    addr: Arc<String>,
+    req: RaftMembershipReq,
+) -> anyhow::Result<RaftMembershipResult> {
+    let resp = router_request(
+        RouterRequest::RaftMembershipReq { req },
+        addr,
+        &app.cluster_sender,
+    )
+    .await?;
+    match resp {
+        RouterResponse::RaftMembershipResult {
+            result: RaftMembershipResult::ParamError(msg),
+        } => Err(param_error(msg)),
+        RouterResponse::RaftMembershipResult {
+            result: RaftMembershipResult::Error(msg),
+        } => Err(anyhow::anyhow!(msg)),
+        RouterResponse::RaftMembershipResult { result } => Ok(result),
+        _ => Err(anyhow::anyhow!("raft membership response type error")),
+    }
+}
+
+/// 把请求转发到主节点执行
+pub async fn leader_membership_request(
+    app: &Arc<AppShareData>,
+    req: RaftMembershipReq,
+) -> anyhow::Result<RaftMembershipResult> {
+    let router = RaftAddrRouter::new(
+        app.raft.clone(),
+        app.raft_store.clone(),
+        app.sys_config.raft_node_id,
+    );
+    match router.get_route_addr().await? {
+        RouteAddr::Local => handle_membership_request(app, req).await,
+        RouteAddr::Remote(_, addr) => remote_membership_request(app, addr, req).await,
+        RouteAddr::Unknown => Err(anyhow::anyhow!("raft leader is unknown")),
+    }
+}
+
+/// 在指定节点执行请求，未指定节点时在本节点执行
+pub async fn node_membership_request(
+    app: &Arc<AppShareData>,
+    node_id: Option<u64>,
+    req: RaftMembershipReq,
+) -> anyhow::Result<RaftMembershipResult> {
+    match node_id {
+        Some(node_id) if node_id != app.sys_config.raft_node_id => {
+            let addr = app
+                .raft_store
+                .get_node_addrs()
+                .await?
+                .remove(&node_id)
+                .ok_or_else(|| param_error(format!("node {} not found", node_id)))?;
+            remote_membership_request(app, addr, req).await
+        }
+        _ => handle_membership_request(app, req).await,
+    }
+}
+
+fn local_node_status(app: &Arc<AppShareData>) -> RaftNodeStatus {
+    let metrics = app.raft.metrics().borrow().clone();
+    RaftNodeStatus {
+        node_id: metrics.id,
+        state: format!("{:?}", metrics.state),
+        current_term: metrics.current_term,
+        last_log_index: metrics.last_log_index,
+        last_applied: metrics.last_applied,
+        current_leader: metrics.current_leader,
+    }
+}
+
+fn check_local_leader(app: &Arc<AppShareData>) -> anyhow::Result<()> {
+    let leader = app.raft.metrics().borrow().current_leader;
+    if leader == Some(app.sys_config.raft_node_id) {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "node {} is not the raft leader, current leader: {:?}",
+            app.sys_config.raft_node_id,
+            leader
+        ))
+    }
+}
+
+async fn query_node_status(
+    app: &Arc<AppShareData>,
+    node_id: u64,
+    addr: Option<Arc<String>>,
+) -> Option<RaftNodeStatus> {
+    if node_id == app.sys_config.raft_node_id {
+        return Some(local_node_status(app));
+    }
+    let addr = addr?;
+    let req = remote_membership_request(app, addr, RaftMembershipReq::NodeStatus);
+    match tokio::time::timeout(NODE_STATUS_TIMEOUT, req).await {
+        Ok(Ok(RaftMembershipResult::NodeStatus(status))) => Some(status),
+        Ok(Err(err)) => {
+            log::warn!("query raft node {} status error,{}", node_id, err);
+            None
+        }
+        _ => None,
+    }
+}
+
+/// 查询集群成员与学习者列表，及各节点的日志复制进度
+pub async fn query_membership(app: &Arc<AppShareData>) -> anyhow::Result<RaftMembershipInfo> {
+    let metrics = app.raft.metrics().borrow().clone();
+    let node_addrs = app.raft_store.get_node_addrs().await?;
+    let voters: BTreeSet<u64> = metrics.membership_config.all_nodes().into_iter().collect();
+    let learners: BTreeSet<u64> = node_addrs
+        .keys()
+        .filter(|id| !voters.contains(id))
+        .copied()
+        .collect();
+    let mut members = Vec::with_capacity(voters.len());
+    for (node_id, role) in voters
+        .iter()
+        .map(|id| (*id, "voter"))
+        .chain(learners.iter().map(|id| (*id, "learner")))
+    {
+        let addr = node_addrs.get(&node_id).cloned();
+        let status = query_node_status(app, node_id, addr.clone()).await;
+        members.push(RaftMemberInfo {
+            node_id,
+            addr,
+            role: role.to_owned(),
+            leader: metrics.current_leader == Some(node_id),
+            current_node: node_id == app.sys_config.raft_node_id,
+            reachable: status.is_some(),
+            state: status.as_ref().map(|e| e.state.clone()),
+            current_term: status.as_ref().map(|e| e.current_term),
+            last_log_index: status.as_ref().map(|e| e.last_log_index),
+            last_applied: status.as_ref().map(|e| e.last_applied),
+            lag: None,
+        });
+    }
+    let leader_last_log_index = members
+        .iter()
+        .find(|e| e.leader)
+        .and_then(|e| e.last_log_index);
+    if let Some(leader_index) = leader_last_log_index {
+        for item in members.iter_mut() {
+            item.lag = item.last_log_index.map(|e| leader_index.saturating_sub(e));
+        }
+    }
+    let learners = members.split_off(voters.len());
+    Ok(RaftMembershipInfo {
+        leader_id: metrics.current_leader,
+        current_term: metrics.current_term,
+        leader_last_log_index,
+        joint_consensus: metrics.membership_config.members_after_consensus.is_some(),
+        members,
+        learners,
+    })
+}
+
+fn current_voters(app: &Arc<AppShareData>) -> anyhow::Result<HashSet<u64>> {
+    let membership = app.raft.metrics().borrow().membership_config.clone();
+    if membership.members_after_consensus.is_some() {
+        return Err(param_error(
+            "the cluster is already undergoing a membership change",
+        ));
+    }
+    Ok(membership.members)
+}
+
+async fn change_voters(app: &Arc<AppShareData>, voters: HashSet<u64>) -> anyhow::Result<()> {
+    log::info!("raft change membership,{:?}", &voters);
+    let members: Vec<u64> = voters.iter().copied().collect();
+    app.raft.change_membership(voters).await?;
+    app.raft
+        .client_write(ClientWriteRequest::new(ClientRequest::Members(members)))
+        .await?;
+    Ok(())
+}
+
+/// 剩余可达的投票节点需要超过半数，否则变更后集群无法继续选主与写入
+pub(crate) fn check_quorum(voters: &HashSet<u64>, reachable: &HashSet<u64>) -> bool {
+    let count = voters.iter().filter(|id| reachable.contains(id)).count();
+    !voters.is_empty() && count > voters.len() / 2
+}
+
+async fn reachable_nodes(app: &Arc<AppShareData>, voters: &HashSet<u64>) -> HashSet<u64> {
+    let node_addrs = app.raft_store.get_node_addrs().await.unwrap_or_default();
+    let mut reachable = HashSet::new();
+    for node_id in voters {
+        let addr = node_addrs.get(node_id).cloned();
+        if query_node_status(app, *node_id, addr).await.is_some() {
+            reachable.insert(*node_id);
+        }
+    }
+    reachable
+}
+
+/// 把学习者节点提升为投票节点
+async fn promote_node(app: &Arc<AppShareData>, node_id: u64) -> anyhow::Result<()> {
+    let mut voters = current_voters(app)?;
+    if voters.contains(&node_id) {
+        return Err(param_error(format!("node {} is already a voter", node_id)));
+    }
+    if !app
+        .raft_store
+        .get_node_addrs()
+        .await?
+        .contains_key(&node_id)
+    {
+        return Err(param_error(format!(
+            "node {} address not found, join it as a learner first",
+            node_id
+        )));
+    }
+    voters.insert(node_id);
+    change_voters(app, voters).await
+}
+
+/// 移除节点并清理节点地址；投票节点需要先从成员中移除
+async fn remove_node(app: &Arc<AppShareData>, node_id: u64) -> anyhow::Result<()> {
+    if node_id == app.sys_config.raft_node_id {
+        return Err(param_error(
+            "can't remove the current leader, restart it to elect a new leader first",
+        ));
+    }
+    let voters = current_voters(app)?;
+    let node_addrs = app.raft_store.get_node_addrs().await?;
+    if !voters.contains(&node_id) && !node_addrs.contains_key(&node_id) {
+        return Err(param_error(format!("node {} not found", node_id)));
+    }
+    if voters.contains(&node_id) {
+        let remaining: HashSet<u64> = voters
+            .iter()
+            .filter(|id| **id != node_id)
+            .copied()
+            .collect();
+        let reachable = reachable_nodes(app, &remaining).await;
+        if !check_quorum(&remaining, &reachable) {
+            return Err(param_error(format!(
+                "removing node {} would leave the cluster without a reachable quorum",
+                node_id
+            )));
+        }
+        change_voters(app, remaining).await?;
+    }
+    app.raft
+        .client_write(ClientWriteRequest::new(ClientRequest::RemoveNodeAddr {
+            id: node_id,
+        }))
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_quorum_test() {
+        let voters: HashSet<u64> = [1, 2, 3].iter().copied().collect();
+        assert!(check_quorum(&voters, &[1, 2].iter().copied().collect()));
+        assert!(!check_quorum(&voters, &[1].iter().copied().collect()));
+        let voters: HashSet<u64> = [1, 2].iter().copied().collect();
+        assert!(!check_quorum(&voters, &[1].iter().copied().collect()));
+        assert!(check_quorum(&voters, &voters));
+        assert!(!check_quorum(&HashSet::new(), &HashSet::new()));
+    }
+}
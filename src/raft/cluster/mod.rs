@@ -2,8 +2,14 @@ use std::sync::Arc;
 
 use async_raft_ext::raft::ClientWriteRequest;
 
+use self::membership::handle_route_membership_request;
 use self::model::{RouterRequest, RouterResponse};
 use super::{db::table::TableManagerAsyncReq, join_node, store::ClientRequest};
+use crate::config::model::ConfigCasConflictError;
+use crate::grpc::bistream_manage::BiStreamManageCmd;
+use crate::grpc::conn_manage::{
+    query_local_conn_count, query_local_conn_list, rebalance_local_conns,
+};
 use crate::grpc::handler::RAFT_ROUTE_REQUEST;
 use crate::grpc::PayloadUtils;
 use crate::namespace::model::NamespaceRaftResult;
@@ -12,9 +18,10 @@ use crate::raft::store::ClientResponse;
 use crate::transfer::model::TransferImportRequest;
 use crate::{
     common::appdata::AppShareData,
-    config::core::{ConfigAsyncCmd, ConfigKey},
+    config::core::{ConfigAsyncCmd, ConfigCmd, ConfigKey, ConfigResult},
 };
 
+pub mod membership;
 pub mod model;
 pub mod route;
 pub mod routeapi;
@@ -35,18 +42,31 @@ pub async fn handle_route(
             config_type,
             desc,
             extend_info: _,
+            cas_md5,
+            tags,
+            note,
         } => {
             let config_key: ConfigKey = (&key as &str).into();
-            app.config_addr
+            let res = app
+                .config_addr
                 .send(ConfigAsyncCmd::Add {
                     key: config_key,
                     value,
                     op_user,
                     config_type,
                     desc,
+                    cas_md5,
+                    tags,
+                    note,
                 })
-                .await??;
-            Ok(RouterResponse::None)
+                .await?;
+            match res {
+                Ok(_) => Ok(RouterResponse::None),
+                Err(err) if err.is::<ConfigCasConflictError>() => Ok(RouterResponse::RaftResponse(
+                    ClientResponse::ConfigCasConflict,
+                )),
+                Err(err) => Err(err),
+            }
         }
         RouterRequest::ConfigDel {
             key,
@@ -58,6 +78,65 @@ pub async fn handle_route(
                 .await??;
             Ok(RouterResponse::None)
         }
+        RouterRequest::ConfigGrayReq { req } => {
+            app.config_addr.send(ConfigAsyncCmd::Gray(req)).await??;
+            Ok(RouterResponse::None)
+        }
+        RouterRequest::ConfigRotateEncryptKey { op_user } => {
+            let count = match app
+                .config_addr
+                .send(ConfigAsyncCmd::RotateEncryptKey { op_user })
+                .await??
+            {
+                ConfigResult::Count(count) => count,
+                _ => 0,
+            };
+            Ok(RouterResponse::CountResult { count })
+        }
+        RouterRequest::ConfigListenerQuery { param } => {
+            let list = match app
+                .config_addr
+                .send(ConfigCmd::QueryListenerInfo(Box::new(param)))
+                .await??
+            {
+                ConfigResult::ListenerInfoList(list) => list,
+                _ => vec![],
+            };
+            Ok(RouterResponse::ConfigListenerResult { list })
+        }
+        RouterRequest::GrpcConnQuery { param } => {
+            let list = query_local_conn_list(app, param).await?;
+            Ok(RouterResponse::GrpcConnList { list })
+        }
+        RouterRequest::GrpcConnCount => {
+            let count = query_local_conn_count(&app.bi_stream_manage).await?;
+            Ok(RouterResponse::GrpcConnCount {
+                count,
+                http_port: app.sys_config.http_port,
+            })
+        }
+        RouterRequest::GrpcConnEject { client_id } => {
+            app.bi_stream_manage
+                .send(BiStreamManageCmd::EjectConn(client_id))
+                .await??;
+            Ok(RouterResponse::None)
+        }
+        RouterRequest::GrpcConnRebalance { param } => {
+            let result = rebalance_local_conns(
+                &app.naming_node_manage,
+                &app.cluster_sender,
+                &app.bi_stream_manage,
+                app.sys_config.http_port,
+                param,
+                None,
+            )
+            .await?;
+            Ok(RouterResponse::GrpcConnRebalanceResult { result })
+        }
+        RouterRequest::RaftMembershipReq { req } => {
+            let result = handle_route_membership_request(app, req).await;
+            Ok(RouterResponse::RaftMembershipResult { result })
+        }
         RouterRequest::JoinNode {
             node_id,
             node_addr: addr,
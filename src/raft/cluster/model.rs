@@ -4,7 +4,12 @@ use std::{collections::HashMap, sync::Arc};
 
 use crate::cache::actor_model::{CacheManagerLocalReq, DirectCacheManagerResult};
 use crate::config::config_type::ConfigType;
+use crate::config::model::{ConfigGrayReq, ConfigListenerInfo, ConfigListenerQueryParam};
+use crate::grpc::conn_manage::{
+    GrpcConnInfo, GrpcConnQueryParam, GrpcConnRebalanceParam, GrpcConnRebalanceResult,
+};
 use crate::namespace::model::{NamespaceRaftReq, NamespaceRaftResult};
+use crate::raft::cluster::membership::{RaftMembershipReq, RaftMembershipResult};
 use crate::raft::store::{ClientRequest, ClientResponse};
 use crate::transfer::model::{TransferImportParam, TransferImportResponse};
 use crate::{
@@ -28,6 +33,9 @@ pub struct SetConfigReq {
     pub op_user: Option<Arc<String>>,
     pub config_type: Option<Arc<String>>,
     pub desc: Option<Arc<String>>,
+    pub cas_md5: Option<Arc<String>>,
+    pub tags: Option<Vec<Arc<String>>>,
+    pub note: Option<Arc<String>>,
     //pub can_route_to_remote: bool,
     //pub extend_info: Option<HashMap<String,String>>,
 }
@@ -40,6 +48,9 @@ impl SetConfigReq {
             op_user: None,
             config_type: None,
             desc: None,
+            cas_md5: None,
+            tags: None,
+            note: None,
         }
     }
 
@@ -54,6 +65,9 @@ impl SetConfigReq {
             op_user: Some(op_user),
             config_type: None,
             desc: None,
+            cas_md5: None,
+            tags: None,
+            note: None,
         }
     }
 
@@ -93,6 +107,9 @@ pub enum RouterRequest {
         config_type: Option<Arc<String>>,
         desc: Option<Arc<String>>,
         extend_info: HashMap<String, String>,
+        cas_md5: Option<Arc<String>>,
+        tags: Option<Vec<Arc<String>>>,
+        note: Option<Arc<String>>,
     },
     ConfigDel {
         key: String,
@@ -121,6 +138,31 @@ pub enum RouterRequest {
     CacheQuery {
         req: CacheManagerLocalReq,
     },
+    ConfigGrayReq {
+        req: ConfigGrayReq,
+    },
+    ConfigRotateEncryptKey {
+        op_user: Option<Arc<String>>,
+    },
+    /// 查询节点本地的配置监听客户端
+    ConfigListenerQuery {
+        param: ConfigListenerQueryParam,
+    },
+    /// 查询节点的gRPC连接详情
+    GrpcConnQuery {
+        param: GrpcConnQueryParam,
+    },
+    GrpcConnCount,
+    GrpcConnEject {
+        client_id: Arc<String>,
+    },
+    GrpcConnRebalance {
+        param: GrpcConnRebalanceParam,
+    },
+    /// raft成员管理请求
+    RaftMembershipReq {
+        req: RaftMembershipReq,
+    },
 }
 
 impl From<SetConfigReq> for RouterRequest {
@@ -132,6 +174,9 @@ impl From<SetConfigReq> for RouterRequest {
             config_type: req.config_type,
             desc: req.desc,
             extend_info: Default::default(),
+            cas_md5: req.cas_md5,
+            tags: req.tags,
+            note: req.note,
         }
     }
 }
@@ -145,6 +190,12 @@ impl From<DelConfigReq> for RouterRequest {
     }
 }
 
+impl From<ConfigGrayReq> for RouterRequest {
+    fn from(req: ConfigGrayReq) -> Self {
+        Self::ConfigGrayReq { req }
+    }
+}
+
 impl From<CacheLimiterReq> for RouterRequest {
     fn from(req: CacheLimiterReq) -> Self {
         Self::CacheLimiterReq { req }
@@ -172,6 +223,12 @@ pub enum RouterResponse {
     NamespaceResult { result: NamespaceRaftResult },
     ImportResult { result: TransferImportResponse },
     CacheQueryResult { result: DirectCacheManagerResult },
+    CountResult { count: usize },
+    ConfigListenerResult { list: Vec<ConfigListenerInfo> },
+    GrpcConnList { list: Vec<GrpcConnInfo> },
+    GrpcConnCount { count: usize, http_port: u16 },
+    GrpcConnRebalanceResult { result: GrpcConnRebalanceResult },
+    RaftMembershipResult { result: RaftMembershipResult },
 }
 
 impl From<ClientResponse> for RouterResponse {
@@ -1,14 +1,22 @@
 use super::model::{DelConfigReq, RouteAddr, RouterRequest, RouterResponse, SetConfigReq};
 use crate::common::appdata::AppShareData;
+use crate::config::config_crypto::{ConfigCryptoManager, ENCRYPT_CONTENT_PREFIX};
+use crate::config::config_type::ConfigType;
+use crate::config::config_validate::{ConfigValidateError, ConfigValidateUtils};
+use crate::config::model::{ConfigCasConflictError, ConfigGrayReq};
 use crate::grpc::handler::RAFT_ROUTE_REQUEST;
-use crate::namespace::model::{NamespaceRaftReq, NamespaceRaftResult};
+use crate::namespace::model::{
+    NamespaceQueryReq, NamespaceQueryResult, NamespaceQuota, NamespaceQuotaError, NamespaceRaftReq,
+    NamespaceRaftResult,
+};
+use crate::namespace::NamespaceActor;
 use crate::raft::cluster::router_request;
 use crate::raft::filestore::core::FileStore;
 use crate::raft::store::{ClientRequest, ClientResponse};
 use crate::transfer::model::{TransferImportParam, TransferImportRequest, TransferImportResponse};
 use crate::transfer::reader::TransferImportManager;
 use crate::{
-    config::core::{ConfigActor, ConfigAsyncCmd, ConfigCmd},
+    config::core::{ConfigActor, ConfigAsyncCmd, ConfigCmd, ConfigKey, ConfigResult},
     grpc::PayloadUtils,
     raft::{network::factory::RaftClusterRequestSender, NacosRaft},
 };
@@ -59,20 +67,29 @@ impl RaftAddrRouter {
 #[derive(Clone, Debug)]
 pub struct ConfigRoute {
     config_addr: Addr<ConfigActor>,
+    namespace_addr: Addr<NamespaceActor>,
     raft_addr_route: Arc<RaftAddrRouter>,
     cluster_sender: Arc<RaftClusterRequestSender>,
+    validate_enable: bool,
+    max_content: usize,
 }
 
 impl ConfigRoute {
     pub fn new(
         config_addr: Addr<ConfigActor>,
+        namespace_addr: Addr<NamespaceActor>,
         raft_addr_route: Arc<RaftAddrRouter>,
         cluster_sender: Arc<RaftClusterRequestSender>,
+        validate_enable: bool,
+        max_content: usize,
     ) -> Self {
         Self {
             config_addr,
+            namespace_addr,
             raft_addr_route,
             cluster_sender,
+            validate_enable,
+            max_content,
         }
     }
 
@@ -80,7 +97,128 @@ impl ConfigRoute {
         anyhow::anyhow!("unknown the raft leader addr!")
     }
 
+    ///
+    /// 发布前检查命名空间配额，超出时返回NamespaceQuotaError
+    /// 配置内容大小优先使用命名空间配额，未设置时使用全局的config_max_content
+    /// 配置数量按本节点当前数据检查，不在raft apply时校验；并发新增时可能略超出上限，为近似限制
+    async fn check_namespace_quota(
+        &self,
+        req: &SetConfigReq,
+        quota: Option<&NamespaceQuota>,
+    ) -> anyhow::Result<()> {
+        let tenant = req.config_key.tenant.as_str();
+        let max_content = quota
+            .and_then(|v| NamespaceQuota::limit(v.max_config_content))
+            .unwrap_or(self.max_content);
+        if req.value.len() > max_content {
+            return Err(NamespaceQuotaError::new(
+                tenant,
+                format!(
+                    "config content size {} over the limit {}",
+                    req.value.len(),
+                    max_content
+                ),
+            )
+            .into());
+        }
+        let max_config_count = quota.and_then(|v| v.max_config_count);
+        if NamespaceQuota::limit(max_config_count).is_none() {
+            return Ok(());
+        }
+        if let ConfigResult::Data { .. } = self
+            .config_addr
+            .send(ConfigCmd::GET(req.config_key.clone()))
+            .await??
+        {
+            // 更新已存在的配置不受数量限制
+            return Ok(());
+        }
+        if let ConfigResult::Count(count) = self
+            .config_addr
+            .send(ConfigCmd::QueryTenantConfigCount(
+                req.config_key.tenant.clone(),
+            ))
+            .await??
+        {
+            NamespaceQuota::check_add(tenant, "config count", max_config_count, count)?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// 查询命名空间的配置schema与配额
+    async fn query_namespace_rule(
+        &self,
+        tenant: &Arc<String>,
+    ) -> anyhow::Result<(Option<Arc<String>>, Option<NamespaceQuota>)> {
+        match self
+            .namespace_addr
+            .send(NamespaceQueryReq::Info(tenant.clone()))
+            .await??
+        {
+            NamespaceQueryResult::Info(v) => Ok((v.config_schema.clone(), v.quota.clone())),
+            _ => Ok((None, None)),
+        }
+    }
+
+    ///
+    /// 发布前校验配置内容(正式配置、灰度配置共用)，校验失败返回ConfigValidateError
+    /// 带加密前缀的内容总是拒绝；类型校验需要开启开关；命名空间设置了schema时，json、yaml配置总是按schema校验
+    async fn validate_config(
+        &self,
+        config_key: &ConfigKey,
+        config_type: Option<&Arc<String>>,
+        value: &str,
+        schema: Option<Arc<String>>,
+    ) -> anyhow::Result<()> {
+        // 加密前缀保留给服务端落盘加密，不接受客户端直接提交密文
+        if ConfigCryptoManager::is_encrypted(value) {
+            return Err(ConfigValidateError::new(
+                format!(
+                    "config content can not start with {}",
+                    ENCRYPT_CONTENT_PREFIX
+                ),
+                None,
+                None,
+            )
+            .into());
+        }
+        if !self.validate_enable && schema.is_none() {
+            return Ok(());
+        }
+        let config_type = if let Some(v) = config_type {
+            ConfigType::new_by_value(v)
+        } else if let ConfigResult::Data {
+            config_type: Some(v),
+            ..
+        } = self
+            .config_addr
+            .send(ConfigCmd::GET(config_key.clone()))
+            .await??
+        {
+            ConfigType::new_by_value(&v)
+        } else {
+            ConfigType::default()
+        };
+        if self.validate_enable {
+            ConfigValidateUtils::validate_content(&config_type, value)?;
+        }
+        if let Some(schema) = schema {
+            ConfigValidateUtils::validate_schema(&config_type, value, &schema)?;
+        }
+        Ok(())
+    }
+
     pub async fn set_config(&self, req: SetConfigReq) -> anyhow::Result<()> {
+        let (schema, quota) = self.query_namespace_rule(&req.config_key.tenant).await?;
+        self.check_namespace_quota(&req, quota.as_ref()).await?;
+        self.validate_config(
+            &req.config_key,
+            req.config_type.as_ref(),
+            &req.value,
+            schema,
+        )
+        .await?;
         match self.raft_addr_route.get_route_addr().await? {
             RouteAddr::Local => {
                 let cmd = ConfigAsyncCmd::Add {
@@ -89,8 +227,11 @@ impl ConfigRoute {
                     op_user: req.op_user,
                     config_type: req.config_type,
                     desc: req.desc,
+                    cas_md5: req.cas_md5,
+                    tags: req.tags,
+                    note: req.note,
                 };
-                self.config_addr.send(cmd).await?.ok();
+                self.config_addr.send(cmd).await??;
             }
             RouteAddr::Remote(_, addr) => {
                 let source_req = req.clone();
@@ -99,7 +240,10 @@ impl ConfigRoute {
                 let payload = PayloadUtils::build_payload(RAFT_ROUTE_REQUEST, request);
                 let resp_payload = self.cluster_sender.send_request(addr, payload).await?;
                 let body_vec = resp_payload.body.unwrap_or_default().value;
-                let _: RouterResponse = serde_json::from_slice(&body_vec)?;
+                let resp: RouterResponse = serde_json::from_slice(&body_vec)?;
+                if let RouterResponse::RaftResponse(ClientResponse::ConfigCasConflict) = resp {
+                    return Err(ConfigCasConflictError.into());
+                }
                 self.config_addr.do_send(ConfigCmd::SetTmpValue(
                     source_req.config_key,
                     source_req.value,
@@ -112,6 +256,62 @@ impl ConfigRoute {
         Ok(())
     }
 
+    ///
+    /// 灰度配置发布、转正、停止
+    pub async fn gray_config(&self, req: ConfigGrayReq) -> anyhow::Result<()> {
+        if let ConfigGrayReq::Publish { key, value, .. } = &req {
+            let config_key: ConfigKey = (key as &str).into();
+            let (schema, _) = self.query_namespace_rule(&config_key.tenant).await?;
+            self.validate_config(&config_key, None, value, schema)
+                .await?;
+        }
+        match self.raft_addr_route.get_route_addr().await? {
+            RouteAddr::Local => {
+                let cmd = ConfigAsyncCmd::Gray(req);
+                self.config_addr.send(cmd).await??;
+            }
+            RouteAddr::Remote(_, addr) => {
+                let req: RouterRequest = req.into();
+                let request = serde_json::to_string(&req).unwrap_or_default();
+                let payload = PayloadUtils::build_payload(RAFT_ROUTE_REQUEST, request);
+                let resp_payload = self.cluster_sender.send_request(addr, payload).await?;
+                let body_vec = resp_payload.body.unwrap_or_default().value;
+                let _: RouterResponse = serde_json::from_slice(&body_vec)?;
+            }
+            RouteAddr::Unknown => {
+                return Err(self.unknown_err());
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// 配置加密密钥轮换，返回重新加密的配置数量
+    pub async fn rotate_encrypt_key(&self, op_user: Option<Arc<String>>) -> anyhow::Result<usize> {
+        match self.raft_addr_route.get_route_addr().await? {
+            RouteAddr::Local => {
+                let cmd = ConfigAsyncCmd::RotateEncryptKey { op_user };
+                if let ConfigResult::Count(count) = self.config_addr.send(cmd).await?? {
+                    return Ok(count);
+                }
+                Ok(0)
+            }
+            RouteAddr::Remote(_, addr) => {
+                let req = RouterRequest::ConfigRotateEncryptKey { op_user };
+                let request = serde_json::to_string(&req).unwrap_or_default();
+                let payload = PayloadUtils::build_payload(RAFT_ROUTE_REQUEST, request);
+                let resp_payload = self.cluster_sender.send_request(addr, payload).await?;
+                let body_vec = resp_payload.body.unwrap_or_default().value;
+                let resp: RouterResponse = serde_json::from_slice(&body_vec)?;
+                if let RouterResponse::CountResult { count } = resp {
+                    return Ok(count);
+                }
+                Ok(0)
+            }
+            RouteAddr::Unknown => Err(self.unknown_err()),
+        }
+    }
+
     pub async fn del_config(&self, req: DelConfigReq) -> anyhow::Result<()> {
         match self.raft_addr_route.get_route_addr().await? {
             RouteAddr::Local => {
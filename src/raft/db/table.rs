@@ -10,11 +10,16 @@ use serde::{Deserialize, Serialize};
 
 use actix::prelude::*;
 
+use crate::audit::model::apply_retention;
 use crate::cache::actor_model::CacheManagerRaftReq;
 use crate::cache::adaptation::AdaptationUtils;
 use crate::cache::core::DirectCacheManager;
-use crate::common::constant::{CACHE_TREE_NAME, USER_TREE_NAME};
+use crate::common::constant::{
+    ACCESS_KEY_TABLE_NAME, AUDIT_LOG_TABLE_NAME, CACHE_TREE_NAME, ROLE_TABLE_NAME, USER_TREE_NAME,
+    WEBHOOK_DEAD_LETTER_TABLE_NAME, WEBHOOK_TABLE_NAME,
+};
 use crate::common::sequence_utils::SimpleSequence;
+use crate::common::AppSysConfig;
 use crate::raft::filestore::model::SnapshotRecordDto;
 use crate::raft::filestore::raftsnapshot::{SnapshotWriterActor, SnapshotWriterRequest};
 use crate::transfer::model::{
@@ -22,8 +27,11 @@ use crate::transfer::model::{
     TransferWriterRequest,
 };
 use crate::transfer::writer::TransferWriterActor;
+use crate::user::access_key::{AccessKeyCmd, AccessKeyManager};
 use crate::user::build_password_hash;
 use crate::user::model::UserDo;
+use crate::user::role::{RoleCmd, RoleManager};
+use crate::webhook::core::{WebhookCmd, WebhookManager};
 use crate::{
     common::string_utils::StringUtils,
     raft::{
@@ -36,6 +44,9 @@ use crate::{
 
 type TableKV = (Vec<u8>, Vec<u8>);
 
+/// 本地按key、value过滤表数据
+pub type TableFilter = Box<dyn Fn(&[u8], &[u8]) -> bool + Send>;
+
 #[derive(Clone, prost::Message, Serialize, Deserialize)]
 pub struct TableDefinition {
     #[prost(string, tag = "1")]
@@ -90,6 +101,10 @@ pub struct TableManager {
     raft: Option<Weak<NacosRaft>>,
     cache_manager: Option<Addr<CacheManager>>,
     direct_cache_manager: Option<Addr<DirectCacheManager>>,
+    webhook_manager: Option<Addr<WebhookManager>>,
+    role_manager: Option<Addr<RoleManager>>,
+    access_key_manager: Option<Addr<AccessKeyManager>>,
+    sys_config: Option<Arc<AppSysConfig>>,
 }
 
 impl TableManager {
@@ -155,6 +170,32 @@ impl TableManager {
         }
     }
 
+    ///
+    /// 审计日志、webhook死信写入后按保留策略清理旧记录
+    fn apply_table_retention(&mut self, name: &Arc<String>) {
+        let sys_config = if let Some(sys_config) = self.sys_config.as_ref() {
+            sys_config
+        } else {
+            return;
+        };
+        let (max_count, retention_days) = if name.as_str() == AUDIT_LOG_TABLE_NAME.as_str() {
+            (
+                sys_config.audit_log_max_count,
+                sys_config.audit_log_retention_days,
+            )
+        } else if name.as_str() == WEBHOOK_DEAD_LETTER_TABLE_NAME.as_str() {
+            (
+                sys_config.webhook_dead_letter_max_count,
+                sys_config.webhook_dead_letter_retention_days,
+            )
+        } else {
+            return;
+        };
+        if let Some(table_info) = self.table_map.get_mut(name) {
+            apply_retention(&mut table_info.table_data, max_count, retention_days);
+        }
+    }
+
     pub fn remove(&mut self, name: Arc<String>, key: Vec<u8>) -> Option<Vec<u8>> {
         if let Some(table_info) = self.table_map.get_mut(&name) {
             table_info.table_data.remove(&key)
@@ -216,6 +257,40 @@ impl TableManager {
         }
     }
 
+    ///
+    /// 按过滤条件分页查询，返回匹配总数与当前页数据
+    pub(crate) fn query_filter_page_list(
+        &self,
+        name: &Arc<String>,
+        filter: TableFilter,
+        offset: usize,
+        limit: usize,
+        is_rev: bool,
+    ) -> (usize, Vec<TableKV>) {
+        let table_info = if let Some(table_info) = self.table_map.get(name) {
+            table_info
+        } else {
+            return (0, vec![]);
+        };
+        let iter: Box<dyn Iterator<Item = (&Vec<u8>, &Vec<u8>)>> = if is_rev {
+            Box::new(table_info.table_data.iter().rev())
+        } else {
+            Box::new(table_info.table_data.iter())
+        };
+        let mut total = 0;
+        let mut ret = vec![];
+        for (k, v) in iter {
+            if !filter(k, v) {
+                continue;
+            }
+            if total >= offset && ret.len() < limit {
+                ret.push((k.to_owned(), v.to_owned()));
+            }
+            total += 1;
+        }
+        (total, ret)
+    }
+
     pub(crate) fn query_list_count(&self, name: &Arc<String>, like_key: &Option<String>) -> usize {
         if let Some(table_info) = self.table_map.get(name) {
             if let Some(like_key) = like_key {
@@ -345,6 +420,10 @@ impl Inject for TableManager {
         self.raft = raft.map(|e| Arc::downgrade(&e));
         self.cache_manager = factory_data.get_actor();
         self.direct_cache_manager = factory_data.get_actor();
+        self.webhook_manager = factory_data.get_actor();
+        self.role_manager = factory_data.get_actor();
+        self.access_key_manager = factory_data.get_actor();
+        self.sys_config = factory_data.get_bean();
     }
 }
 
@@ -379,12 +458,24 @@ pub enum TableManagerReq {
         last_seq_id: u64,
     },
     ReloadTable,
+    BatchSet {
+        table_name: Arc<String>,
+        items: Vec<TableKV>,
+    },
 }
 
 #[derive(Message)]
 #[rtype(result = "anyhow::Result<TableManagerResult>")]
 pub enum TableManagerInnerReq {
     BuildSnapshot(Addr<SnapshotWriterActor>),
+    /// 只查询本节点数据
+    QueryFilterPageList {
+        table_name: Arc<String>,
+        filter: TableFilter,
+        offset: usize,
+        limit: usize,
+        is_rev: bool,
+    },
 }
 
 impl From<TableManagerReq> for RouterRequest {
@@ -478,7 +569,37 @@ impl Handler<TableManagerReq> for TableManager {
                         }
                     }
                 }
-                self.insert(table_name, key, value, last_seq_id);
+                if table_name.as_str() == WEBHOOK_TABLE_NAME.as_str() {
+                    if let Some(webhook_manager) = &self.webhook_manager {
+                        webhook_manager.do_send(WebhookCmd::NotifyChange {
+                            key: key.clone(),
+                            value: value.clone(),
+                        });
+                    }
+                } else if table_name.as_str() == ROLE_TABLE_NAME.as_str() {
+                    if let Some(role_manager) = &self.role_manager {
+                        role_manager.do_send(RoleCmd::NotifyChange {
+                            key: key.clone(),
+                            value: value.clone(),
+                        });
+                    }
+                } else if table_name.as_str() == ACCESS_KEY_TABLE_NAME.as_str() {
+                    if let Some(access_key_manager) = &self.access_key_manager {
+                        access_key_manager.do_send(AccessKeyCmd::NotifyChange {
+                            key: key.clone(),
+                            value: value.clone(),
+                        });
+                    }
+                }
+                self.insert(table_name.clone(), key, value, last_seq_id);
+                self.apply_table_retention(&table_name);
+                Ok(TableManagerResult::None)
+            }
+            TableManagerReq::BatchSet { table_name, items } => {
+                for (key, value) in items {
+                    self.insert(table_name.clone(), key, value, None);
+                }
+                self.apply_table_retention(&table_name);
                 Ok(TableManagerResult::None)
             }
             TableManagerReq::Remove { table_name, key } => {
@@ -495,6 +616,19 @@ impl Handler<TableManagerReq> for TableManager {
                         }
                     }
                 }
+                if table_name.as_str() == WEBHOOK_TABLE_NAME.as_str() {
+                    if let Some(webhook_manager) = &self.webhook_manager {
+                        webhook_manager.do_send(WebhookCmd::NotifyRemove { key: key.clone() });
+                    }
+                } else if table_name.as_str() == ROLE_TABLE_NAME.as_str() {
+                    if let Some(role_manager) = &self.role_manager {
+                        role_manager.do_send(RoleCmd::NotifyRemove { key: key.clone() });
+                    }
+                } else if table_name.as_str() == ACCESS_KEY_TABLE_NAME.as_str() {
+                    if let Some(access_key_manager) = &self.access_key_manager {
+                        access_key_manager.do_send(AccessKeyCmd::NotifyRemove { key: key.clone() });
+                    }
+                }
                 match self.remove(table_name, key) {
                     Some(v) => Ok(TableManagerResult::Value(v.to_vec())),
                     None => Ok(TableManagerResult::None),
@@ -541,6 +675,17 @@ impl Handler<TableManagerInnerReq> for TableManager {
                 self.build_snapshot(writer).ok();
                 Ok(TableManagerResult::None)
             }
+            TableManagerInnerReq::QueryFilterPageList {
+                table_name,
+                filter,
+                offset,
+                limit,
+                is_rev,
+            } => {
+                let (size, list) =
+                    self.query_filter_page_list(&table_name, filter, offset, limit, is_rev);
+                Ok(TableManagerResult::PageListResult(size, list))
+            }
         }
     }
 }
@@ -20,7 +20,7 @@ use async_raft_ext::raft::{Entry, MembershipConfig};
 use async_raft_ext::storage::{CurrentSnapshotData, HardState, InitialState};
 use async_raft_ext::RaftStorage;
 use async_trait::async_trait;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
@@ -126,6 +126,17 @@ impl FileStore {
         }
     }
 
+    pub async fn get_node_addrs(&self) -> anyhow::Result<HashMap<u64, Arc<String>>> {
+        match self
+            .index_manager
+            .send(RaftIndexRequest::LoadMember)
+            .await??
+        {
+            RaftIndexResponse::MemberShip { node_addrs, .. } => Ok(node_addrs),
+            _ => Ok(HashMap::new()),
+        }
+    }
+
     /// Map the oneshot-delivered write result to the RaftStorage return value.
     fn write_log_result_to_result(r: WriteLogResult) -> anyhow::Result<()> {
         match r {
@@ -186,6 +186,9 @@ impl StateApplyManager {
 
     fn load_log(&mut self, ctx: &mut Context<Self>) {
         if self.last_applied_log == 0 || self.log_manager.is_none() || self.data_wrap.is_none() {
+            if let Some(data_wrap) = self.data_wrap.as_ref() {
+                data_wrap.load_empty();
+            }
             return;
         }
         let start_index = self.snapshot_next_index;
@@ -1,12 +1,14 @@
 use crate::cache::core::DirectCacheManager;
 use crate::common::byte_utils::bin_to_id;
 use crate::common::constant::{
-    CACHE_TREE_NAME, CONFIG_TREE_NAME, DIRECT_CACHE_TABLE_NAME, MCP_SERVER_TABLE_NAME,
-    MCP_TOOL_SPEC_TABLE_NAME, NAMESPACE_TREE_NAME, NAMING_INSTANCE_TABLE, SEQUENCE_TREE_NAME,
-    SEQ_KEY_CONFIG, USER_TREE_NAME,
+    ACCESS_KEY_TABLE_NAME, ACCESS_KEY_USAGE_TABLE_NAME, AUDIT_LOG_TABLE_NAME, CACHE_TREE_NAME,
+    CONFIG_HISTORY_TREE_NAME, CONFIG_TREE_NAME, DIRECT_CACHE_TABLE_NAME, MCP_SERVER_TABLE_NAME,
+    MCP_TOOL_SPEC_TABLE_NAME, NAMESPACE_TREE_NAME, NAMING_INSTANCE_TABLE, ROLE_TABLE_NAME,
+    SEQUENCE_TREE_NAME, SEQ_KEY_CONFIG, USER_TREE_NAME, WEBHOOK_DEAD_LETTER_TABLE_NAME,
+    WEBHOOK_TABLE_NAME,
 };
 use crate::config::core::{ConfigActor, ConfigCmd, ConfigKey, ConfigValue};
-use crate::config::model::{ConfigRaftCmd, ConfigValueDO};
+use crate::config::model::{ConfigRaftCmd, ConfigRaftResult, ConfigValueDO};
 use crate::mcp::core::McpManager;
 use crate::namespace::NamespaceActor;
 use crate::naming::core::NamingActor;
@@ -17,6 +19,9 @@ use crate::raft::filestore::raftindex::{RaftIndexManager, RaftIndexRequest};
 use crate::raft::filestore::raftsnapshot::SnapshotWriterActor;
 use crate::raft::store::{ClientRequest, ClientResponse};
 use crate::sequence::core::SequenceDbManager;
+use crate::user::access_key::{AccessKeyCmd, AccessKeyManager};
+use crate::user::role::{RoleCmd, RoleManager};
+use crate::webhook::core::{WebhookCmd, WebhookManager};
 use actix::prelude::*;
 
 #[derive(Clone)]
@@ -28,6 +33,9 @@ pub struct RaftDataHandler {
     pub mcp_manager: Addr<McpManager>,
     pub naming_actor: Addr<NamingActor>,
     pub direct_cache_manager: Addr<DirectCacheManager>,
+    pub webhook_manager: Addr<WebhookManager>,
+    pub role_manager: Addr<RoleManager>,
+    pub access_key_manager: Addr<AccessKeyManager>,
 }
 
 impl RaftDataHandler {
@@ -64,6 +72,10 @@ impl RaftDataHandler {
             self.config
                 .send(ConfigCmd::SetFullValue(config_key, value_do.into()))
                 .await??;
+        } else if record.tree.as_str() == CONFIG_HISTORY_TREE_NAME.as_str() {
+            self.config
+                .send(ConfigCmd::LoadHistorySnapshotRecord(record))
+                .await??;
         } else if record.tree.as_str() == SEQUENCE_TREE_NAME.as_str() {
             let key = String::from_utf8_lossy(&record.key);
             let last_id = bin_to_id(&record.value);
@@ -85,6 +97,20 @@ impl RaftDataHandler {
                 last_seq_id: None,
             };
             self.table.send(req).await??;
+        } else if record.tree.as_str() == AUDIT_LOG_TABLE_NAME.as_str()
+            || record.tree.as_str() == WEBHOOK_TABLE_NAME.as_str()
+            || record.tree.as_str() == WEBHOOK_DEAD_LETTER_TABLE_NAME.as_str()
+            || record.tree.as_str() == ROLE_TABLE_NAME.as_str()
+            || record.tree.as_str() == ACCESS_KEY_TABLE_NAME.as_str()
+            || record.tree.as_str() == ACCESS_KEY_USAGE_TABLE_NAME.as_str()
+        {
+            let req = TableManagerReq::Set {
+                table_name: record.tree.clone(),
+                key: record.key,
+                value: record.value,
+                last_seq_id: None,
+            };
+            self.table.send(req).await??;
         } else if record.tree.as_str() == CACHE_TREE_NAME.as_str() {
             let key = record.key;
             let value = record.value;
@@ -133,9 +159,20 @@ impl RaftDataHandler {
             .do_send(RaftApplyDataRequest::LoadCompleted);
         self.direct_cache_manager
             .do_send(RaftApplyDataRequest::LoadCompleted);
+        self.webhook_manager.do_send(WebhookCmd::LoadCompleted);
+        self.role_manager.do_send(RoleCmd::LoadCompleted);
+        self.access_key_manager.do_send(AccessKeyCmd::LoadCompleted);
         Ok(())
     }
 
+    ///
+    /// 启动时没有需要加载的数据
+    pub fn load_empty(&self) {
+        self.webhook_manager.do_send(WebhookCmd::LoadCompleted);
+        self.role_manager.do_send(RoleCmd::LoadCompleted);
+        self.access_key_manager.do_send(AccessKeyCmd::LoadCompleted);
+    }
+
     /// 启动时加载日志
     pub async fn load_log(
         &self,
@@ -149,6 +186,12 @@ impl RaftDataHandler {
                     .await
                     .ok();
             }
+            ClientRequest::RemoveNodeAddr { id } => {
+                index_manager
+                    .send(RaftIndexRequest::RemoveNodeAddr(id))
+                    .await
+                    .ok();
+            }
             ClientRequest::Members(member) => {
                 index_manager
                     .send(RaftIndexRequest::SaveMember {
@@ -171,6 +214,9 @@ impl RaftDataHandler {
                 history_table_id,
                 op_time,
                 op_user,
+                cas_md5,
+                tags,
+                note,
             } => {
                 let cmd = ConfigRaftCmd::ConfigAdd {
                     key,
@@ -181,6 +227,9 @@ impl RaftDataHandler {
                     history_table_id,
                     op_time,
                     op_user,
+                    cas_md5,
+                    tags,
+                    note,
                 };
                 self.config.send(cmd).await.ok();
             }
@@ -204,6 +253,42 @@ impl RaftDataHandler {
                 let cmd = ConfigRaftCmd::ConfigRemove { key };
                 self.config.send(cmd).await.ok();
             }
+            ClientRequest::ConfigGraySet {
+                key,
+                value,
+                rule,
+                op_time,
+                op_user,
+            } => {
+                let cmd = ConfigRaftCmd::ConfigGraySet {
+                    key,
+                    value,
+                    rule,
+                    op_time,
+                    op_user,
+                };
+                self.config.send(cmd).await.ok();
+            }
+            ClientRequest::ConfigGrayRemove { key } => {
+                let cmd = ConfigRaftCmd::ConfigGrayRemove { key };
+                self.config.send(cmd).await.ok();
+            }
+            ClientRequest::ConfigGrayPromote {
+                key,
+                history_id,
+                history_table_id,
+                op_time,
+                op_user,
+            } => {
+                let cmd = ConfigRaftCmd::ConfigGrayPromote {
+                    key,
+                    history_id,
+                    history_table_id,
+                    op_time,
+                    op_user,
+                };
+                self.config.send(cmd).await.ok();
+            }
             ClientRequest::TableManagerReq(req) => {
                 self.table.send(req).await.ok();
             }
@@ -234,6 +319,10 @@ impl RaftDataHandler {
                 index_manager.do_send(RaftIndexRequest::AddNodeAddr(id, addr));
                 Ok(ClientResponse::Success)
             }
+            ClientRequest::RemoveNodeAddr { id } => {
+                index_manager.do_send(RaftIndexRequest::RemoveNodeAddr(id));
+                Ok(ClientResponse::Success)
+            }
             ClientRequest::Members(member) => {
                 index_manager.do_send(RaftIndexRequest::SaveMember {
                     member: member.clone(),
@@ -255,6 +344,9 @@ impl RaftDataHandler {
                 history_table_id,
                 op_time,
                 op_user,
+                cas_md5,
+                tags,
+                note,
             } => {
                 let cmd = ConfigRaftCmd::ConfigAdd {
                     key,
@@ -265,9 +357,14 @@ impl RaftDataHandler {
                     history_table_id,
                     op_time,
                     op_user,
+                    cas_md5,
+                    tags,
+                    note,
                 };
-                self.config.send(cmd).await??;
-                Ok(ClientResponse::Success)
+                match self.config.send(cmd).await?? {
+                    ConfigRaftResult::CasConflict => Ok(ClientResponse::ConfigCasConflict),
+                    _ => Ok(ClientResponse::Success),
+                }
             }
             ClientRequest::ConfigFullValue {
                 key,
@@ -291,6 +388,45 @@ impl RaftDataHandler {
                 self.config.send(cmd).await??;
                 Ok(ClientResponse::Success)
             }
+            ClientRequest::ConfigGraySet {
+                key,
+                value,
+                rule,
+                op_time,
+                op_user,
+            } => {
+                let cmd = ConfigRaftCmd::ConfigGraySet {
+                    key,
+                    value,
+                    rule,
+                    op_time,
+                    op_user,
+                };
+                self.config.send(cmd).await??;
+                Ok(ClientResponse::Success)
+            }
+            ClientRequest::ConfigGrayRemove { key } => {
+                let cmd = ConfigRaftCmd::ConfigGrayRemove { key };
+                self.config.send(cmd).await??;
+                Ok(ClientResponse::Success)
+            }
+            ClientRequest::ConfigGrayPromote {
+                key,
+                history_id,
+                history_table_id,
+                op_time,
+                op_user,
+            } => {
+                let cmd = ConfigRaftCmd::ConfigGrayPromote {
+                    key,
+                    history_id,
+                    history_table_id,
+                    op_time,
+                    op_user,
+                };
+                self.config.send(cmd).await??;
+                Ok(ClientResponse::Success)
+            }
             ClientRequest::TableManagerReq(req) => {
                 self.table.send(req).await??;
                 Ok(ClientResponse::Success)
@@ -323,6 +459,9 @@ impl RaftDataHandler {
             ClientRequest::NodeAddr { id, addr } => {
                 index_manager.do_send(RaftIndexRequest::AddNodeAddr(id, addr));
             }
+            ClientRequest::RemoveNodeAddr { id } => {
+                index_manager.do_send(RaftIndexRequest::RemoveNodeAddr(id));
+            }
             ClientRequest::Members(member) => {
                 index_manager.do_send(RaftIndexRequest::SaveMember {
                     member: member.clone(),
@@ -342,6 +481,9 @@ impl RaftDataHandler {
                 history_table_id,
                 op_time,
                 op_user,
+                cas_md5,
+                tags,
+                note,
             } => {
                 let cmd = ConfigRaftCmd::ConfigAdd {
                     key,
@@ -352,6 +494,9 @@ impl RaftDataHandler {
                     history_table_id,
                     op_time,
                     op_user,
+                    cas_md5,
+                    tags,
+                    note,
                 };
                 self.config.do_send(cmd);
             }
@@ -375,6 +520,42 @@ impl RaftDataHandler {
                 let cmd = ConfigRaftCmd::ConfigRemove { key };
                 self.config.do_send(cmd);
             }
+            ClientRequest::ConfigGraySet {
+                key,
+                value,
+                rule,
+                op_time,
+                op_user,
+            } => {
+                let cmd = ConfigRaftCmd::ConfigGraySet {
+                    key,
+                    value,
+                    rule,
+                    op_time,
+                    op_user,
+                };
+                self.config.do_send(cmd);
+            }
+            ClientRequest::ConfigGrayRemove { key } => {
+                let cmd = ConfigRaftCmd::ConfigGrayRemove { key };
+                self.config.do_send(cmd);
+            }
+            ClientRequest::ConfigGrayPromote {
+                key,
+                history_id,
+                history_table_id,
+                op_time,
+                op_user,
+            } => {
+                let cmd = ConfigRaftCmd::ConfigGrayPromote {
+                    key,
+                    history_id,
+                    history_table_id,
+                    op_time,
+                    op_user,
+                };
+                self.config.do_send(cmd);
+            }
             ClientRequest::TableManagerReq(req) => {
                 self.table.do_send(req);
             }
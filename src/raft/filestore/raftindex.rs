@@ -353,6 +353,22 @@ impl RaftIndexManager {
         }
     }
 
+    pub fn remove_node_addr(
+        &mut self,
+        ctx: &mut Context<Self>,
+        id: u64,
+    ) -> anyhow::Result<RaftIndexResponse> {
+        if let Some(inner) = self.inner.as_mut() {
+            if inner.raft_index.node_addrs.remove(&id).is_none() {
+                return Ok(RaftIndexResponse::None);
+            }
+            let index_info = inner.raft_index.clone();
+            self.write_index(ctx, index_info, true)
+        } else {
+            Err(Self::inner_is_empty_error())
+        }
+    }
+
     pub fn write_hard_state(
         &mut self,
         ctx: &mut Context<Self>,
@@ -419,6 +435,7 @@ pub enum RaftIndexRequest {
     },
     //SaveNodeAddr(HashMap<u64, Arc<String>>),
     AddNodeAddr(u64, Arc<String>),
+    RemoveNodeAddr(u64),
     SaveHardState {
         current_term: u64,
         voted_for: u64,
@@ -462,6 +479,7 @@ impl Handler<RaftIndexRequest> for RaftIndexManager {
             } => self.write_member(ctx, member, member_after_consensus, node_addr),
             //RaftIndexRequest::SaveNodeAddr(node_addr) => self.write_node_addr(ctx, node_addr),
             RaftIndexRequest::AddNodeAddr(id, node_addr) => self.add_node_addr(ctx, id, node_addr),
+            RaftIndexRequest::RemoveNodeAddr(id) => self.remove_node_addr(ctx, id),
             RaftIndexRequest::SaveHardState {
                 current_term,
                 voted_for,
@@ -30,8 +30,7 @@ pub async fn join_node(
         log::info!("join_node membership,{:?}", &all_node);
         raft.change_membership(all_node).await.ok();
         raft.client_write(ClientWriteRequest::new(ClientRequest::Members(members)))
-            .await
-            .unwrap();
+            .await?;
     }
     Ok(())
 }
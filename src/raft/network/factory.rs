@@ -1,10 +1,11 @@
 use std::sync::Arc;
 
+use crate::common::tls::ClusterTlsConnector;
 use crate::common::AppSysConfig;
 use crate::grpc::handler::CLUSTER_TOKEN;
 use actix::prelude::*;
 use inner_mem_cache::MemCache;
-use tonic::transport::Channel;
+use tonic::transport::{Channel, Endpoint};
 
 use crate::grpc::nacos_proto::{request_client::RequestClient, Payload};
 
@@ -77,19 +78,25 @@ impl RaftClusterRequestSender {
 pub struct RaftConnectionFactory {
     channel_cache: MemCache<Arc<String>, Arc<Channel>>,
     cache_ses: i32,
+    tls_connector: Option<ClusterTlsConnector>,
 }
 
 impl RaftConnectionFactory {
-    pub fn new(cache_ses: i32) -> Self {
+    pub fn new(cache_ses: i32, tls_connector: Option<ClusterTlsConnector>) -> Self {
         Self {
             channel_cache: MemCache::<Arc<String>, Arc<Channel>>::new(),
             cache_ses,
+            tls_connector,
         }
     }
 
-    fn build_channel(&mut self, key: Arc<String>) -> anyhow::Result<Arc<Channel>> {
+    fn get_cache_channel(&mut self, key: &Arc<String>) -> Option<Arc<Channel>> {
         self.channel_cache.clear_time_out();
-        if let Ok(channel) = self.channel_cache.get(&key) {
+        self.channel_cache.get(key).ok()
+    }
+
+    fn build_channel(&mut self, key: Arc<String>) -> anyhow::Result<Arc<Channel>> {
+        if let Some(channel) = self.get_cache_channel(&key) {
             Ok(channel)
         } else {
             let addr = format!("http://{}", &key);
@@ -99,6 +106,18 @@ impl RaftConnectionFactory {
         }
     }
 
+    /// tonic自定义连接器只支持建立连接后返回channel，TLS连接需要异步创建
+    async fn build_tls_channel(
+        key: Arc<String>,
+        connector: ClusterTlsConnector,
+    ) -> anyhow::Result<Arc<Channel>> {
+        let addr = format!("https://{}", &key);
+        let channel = Endpoint::from_shared(addr)?
+            .connect_with_connector(connector)
+            .await?;
+        Ok(Arc::new(channel))
+    }
+
     fn update_channel_status(&mut self, key: Arc<String>, is_active: bool) {
         if let Ok(channel) = self.channel_cache.get(&key) {
             if is_active {
@@ -134,18 +153,38 @@ pub enum RaftConnResponse {
 }
 
 impl Handler<RaftConnRequest> for RaftConnectionFactory {
-    type Result = anyhow::Result<RaftConnResponse>;
+    type Result = ResponseActFuture<Self, anyhow::Result<RaftConnResponse>>;
 
     fn handle(&mut self, msg: RaftConnRequest, _ctx: &mut Self::Context) -> Self::Result {
-        match msg {
-            RaftConnRequest::GetChannel(key) => {
-                let c = self.build_channel(key)?;
-                Ok(RaftConnResponse::Channel(c))
-            }
+        let res = match msg {
+            RaftConnRequest::GetChannel(key) => match self.tls_connector.clone() {
+                Some(connector) => {
+                    if let Some(channel) = self.get_cache_channel(&key) {
+                        Ok(RaftConnResponse::Channel(channel))
+                    } else {
+                        let cache_key = key.clone();
+                        return Box::pin(
+                            Self::build_tls_channel(key, connector)
+                                .into_actor(self)
+                                .map(move |res, act, _ctx| {
+                                    let channel = res?;
+                                    act.channel_cache.set(
+                                        cache_key,
+                                        channel.clone(),
+                                        act.cache_ses,
+                                    );
+                                    Ok(RaftConnResponse::Channel(channel))
+                                }),
+                        );
+                    }
+                }
+                None => self.build_channel(key).map(RaftConnResponse::Channel),
+            },
             RaftConnRequest::UpdateChannel { key, is_active } => {
                 self.update_channel_status(key, is_active);
                 Ok(RaftConnResponse::None)
             }
-        }
+        };
+        Box::pin(actix::fut::ready(res))
     }
 }
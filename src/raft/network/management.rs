@@ -14,24 +14,48 @@ use crate::raft::store::NodeId;
 
 // --- Cluster management
 
-pub async fn join_learner(
-    app: Data<Arc<AppShareData>>,
-    req: Json<(NodeId, String)>,
-) -> actix_web::Result<impl Responder> {
-    let node_id = req.0 .0;
-    let addr = Arc::new(req.0 .1);
+/// 参数校验错误
+fn param_error_response(msg: impl std::fmt::Display) -> HttpResponse {
+    HttpResponse::BadRequest().json(json!({ "ok": 0, "msg": msg.to_string() }))
+}
+
+/// raft执行错误
+fn error_response(msg: impl std::fmt::Display) -> HttpResponse {
+    HttpResponse::InternalServerError().json(json!({ "ok": 0, "msg": msg.to_string() }))
+}
+
+fn ok_response() -> HttpResponse {
+    HttpResponse::Ok().json(json!({ "ok": 1 }))
+}
+
+async fn write_node_addr(app: &AppShareData, node_id: NodeId, addr: String) -> anyhow::Result<()> {
     app.raft
         .client_write(ClientWriteRequest::new(ClientRequest::NodeAddr {
             id: node_id,
-            addr,
+            addr: Arc::new(addr),
         }))
-        .await
-        .unwrap();
-    app.raft.add_non_voter(node_id).await.unwrap();
-    join_node(app.raft.as_ref(), app.raft_store.as_ref(), node_id)
-        .await
-        .ok();
-    Ok("{\"ok\":1}")
+        .await?;
+    app.raft.add_non_voter(node_id).await?;
+    Ok(())
+}
+
+pub async fn join_learner(
+    app: Data<Arc<AppShareData>>,
+    req: Json<(NodeId, String)>,
+) -> actix_web::Result<impl Responder> {
+    let (node_id, addr) = req.0;
+    if addr.trim().is_empty() {
+        return Ok(param_error_response("node addr is empty"));
+    }
+    if let Err(err) = write_node_addr(&app, node_id, addr).await {
+        log::warn!("join_learner {} error,{}", node_id, err);
+        return Ok(error_response(err));
+    }
+    if let Err(err) = join_node(app.raft.as_ref(), app.raft_store.as_ref(), node_id).await {
+        log::warn!("join_node {} error,{}", node_id, err);
+        return Ok(error_response(err));
+    }
+    Ok(ok_response())
 }
 
 /// Add a node as **Learner**.
@@ -44,17 +68,15 @@ pub async fn add_learner(
     app: Data<Arc<AppShareData>>,
     req: Json<(NodeId, String)>,
 ) -> actix_web::Result<impl Responder> {
-    let node_id = req.0 .0;
-    let addr = Arc::new(req.0 .1);
-    app.raft
-        .client_write(ClientWriteRequest::new(ClientRequest::NodeAddr {
-            id: node_id,
-            addr,
-        }))
-        .await
-        .unwrap();
-    app.raft.add_non_voter(node_id).await.unwrap();
-    Ok("{\"ok\":1}")
+    let (node_id, addr) = req.0;
+    if addr.trim().is_empty() {
+        return Ok(param_error_response("node addr is empty"));
+    }
+    if let Err(err) = write_node_addr(&app, node_id, addr).await {
+        log::warn!("add_learner {} error,{}", node_id, err);
+        return Ok(error_response(err));
+    }
+    Ok(ok_response())
 }
 
 /// Changes specified learners to members, or remove members.
@@ -63,8 +85,33 @@ pub async fn change_membership(
     app: Data<Arc<AppShareData>>,
     req: Json<HashSet<NodeId>>,
 ) -> actix_web::Result<impl Responder> {
-    app.raft.change_membership(req.0).await.unwrap();
-    Ok("{\"ok\":1}")
+    let members = req.0;
+    if members.is_empty() {
+        return Ok(param_error_response("members is empty"));
+    }
+    let node_addrs = match app.raft_store.get_node_addrs().await {
+        Ok(v) => v,
+        Err(err) => return Ok(error_response(err)),
+    };
+    if let Some(node_id) = members.iter().find(|id| !node_addrs.contains_key(id)) {
+        return Ok(param_error_response(format!(
+            "node {} address not found",
+            node_id
+        )));
+    }
+    let list: Vec<NodeId> = members.iter().copied().collect();
+    if let Err(err) = app.raft.change_membership(members).await {
+        log::warn!("change_membership error,{}", err);
+        return Ok(error_response(err));
+    }
+    if let Err(err) = app
+        .raft
+        .client_write(ClientWriteRequest::new(ClientRequest::Members(list)))
+        .await
+    {
+        return Ok(error_response(err));
+    }
+    Ok(ok_response())
 }
 
 /// Initialize a single-node cluster.
@@ -74,14 +121,18 @@ pub async fn init(app: Data<Arc<AppShareData>>) -> actix_web::Result<impl Respon
     let node_id = app.sys_config.raft_node_id.to_owned();
     members.insert(node_id);
     app.raft.initialize(members).await.ok();
-    app.raft
+    if let Err(err) = app
+        .raft
         .client_write(ClientWriteRequest::new(ClientRequest::NodeAddr {
             id: node_id,
             addr: Arc::new(app.sys_config.raft_node_addr.to_owned()),
         }))
         .await
-        .unwrap();
-    Ok("{\"ok\":1}")
+    {
+        log::warn!("init raft node addr error,{}", err);
+        return Ok(error_response(err));
+    }
+    Ok(ok_response())
 }
 
 /// Get the latest metrics of the cluster
@@ -167,9 +218,22 @@ fn close_raft_mark_exists(local_db_dir: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::close_raft_mark_exists;
+    use super::{close_raft_mark_exists, error_response, param_error_response};
+    use actix_web::http::StatusCode;
     use tempfile::tempdir;
 
+    #[test]
+    fn error_response_status() {
+        assert_eq!(
+            param_error_response("members is empty").status(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            error_response("raft error").status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
     #[test]
     fn close_raft_mark_not_exists_when_absent() {
         let dir = tempdir().unwrap();
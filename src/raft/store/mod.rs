@@ -5,6 +5,7 @@ use std::sync::Arc;
 
 use super::db::table::TableManagerReq;
 use crate::cache::actor_model::{CacheManagerRaftReq, CacheManagerRaftResult};
+use crate::config::model::ConfigGrayRule;
 use crate::mcp::model::actor_model::{McpManagerRaftReq, McpManagerRaftResult};
 use crate::namespace::model::NamespaceRaftReq;
 use crate::naming::model::actor_model::{NamingRaftReq, NamingRaftResult};
@@ -33,6 +34,9 @@ pub enum ClientRequest {
         history_table_id: Option<u64>,
         op_time: i64,
         op_user: Option<Arc<String>>,
+        cas_md5: Option<Arc<String>>,
+        tags: Option<Vec<Arc<String>>>,
+        note: Option<Arc<String>>,
     },
     ConfigFullValue {
         key: Vec<u8>,
@@ -42,6 +46,23 @@ pub enum ClientRequest {
     ConfigRemove {
         key: String,
     },
+    ConfigGraySet {
+        key: String,
+        value: Arc<String>,
+        rule: ConfigGrayRule,
+        op_time: i64,
+        op_user: Option<Arc<String>>,
+    },
+    ConfigGrayRemove {
+        key: String,
+    },
+    ConfigGrayPromote {
+        key: String,
+        history_id: u64,
+        history_table_id: Option<u64>,
+        op_time: i64,
+        op_user: Option<Arc<String>>,
+    },
     TableManagerReq(TableManagerReq),
     NamespaceReq(NamespaceRaftReq),
     SequenceReq {
@@ -56,6 +77,10 @@ pub enum ClientRequest {
     CacheReq {
         req: CacheManagerRaftReq,
     },
+    /// 移除已下线节点的地址
+    RemoveNodeAddr {
+        id: u64,
+    },
 }
 
 impl AppData for ClientRequest {}
@@ -64,6 +89,7 @@ impl AppData for ClientRequest {}
 pub enum ClientResponse {
     Success,
     Fail,
+    ConfigCasConflict,
     SequenceResp { resp: SequenceRaftResult },
     McpResp { resp: McpManagerRaftResult },
     NamingResp { resp: NamingRaftResult },
@@ -1,6 +1,9 @@
 use std::{collections::HashSet, sync::Arc, time::Duration};
 
+use crate::audit::core::AuditLogManager;
+use crate::backup::core::BackupManager;
 use crate::common::actor_utils::{create_actor_at_thread, create_actor_at_thread2};
+use crate::common::tls::ClusterTlsConnector;
 use crate::grpc::handler::RAFT_ROUTE_REQUEST;
 use crate::health::core::HealthManager;
 use crate::ldap::core::LdapManager;
@@ -8,6 +11,7 @@ use crate::mcp::core::McpManager;
 use crate::mcp::sse_manage::SseStreamManager;
 use crate::metrics::core::MetricsManager;
 use crate::namespace::NamespaceActor;
+use crate::naming::dns::{NamingDnsConfig, NamingDnsServer};
 use crate::naming::instance_meta_manager::InstanceMetaManager;
 use crate::naming::sniffing::NetSniffing;
 use crate::oauth2::core::OAuth2Manager;
@@ -22,8 +26,13 @@ use crate::sequence::core::SequenceDbManager;
 use crate::sequence::SequenceManager;
 use crate::transfer::reader::TransferImportManager;
 use crate::transfer::writer::TransferWriterManager;
+use crate::user::access_key::AccessKeyManager;
+use crate::user::jwt_auth::JwtAuthManager;
+use crate::user::role::RoleManager;
+use crate::webhook::core::WebhookManager;
 use crate::{
     common::{appdata::AppShareData, AppSysConfig},
+    config::config_crypto::ConfigCryptoManager,
     config::core::ConfigActor,
     grpc::{bistream_manage::BiStreamManage, PayloadUtils},
     naming::{
@@ -65,7 +74,9 @@ pub async fn config_factory(sys_config: Arc<AppSysConfig>) -> anyhow::Result<Fac
     factory.register(BeanDefinition::from_obj(sys_config.clone()));
 
     let index_manager = RaftIndexManager::new(base_path.clone());
-    let (index_manager, config_addr) = create_actor_at_thread2(index_manager, ConfigActor::new());
+    let config_crypto = Arc::new(ConfigCryptoManager::new_by_sys_config(&sys_config));
+    let (index_manager, config_addr) =
+        create_actor_at_thread2(index_manager, ConfigActor::new_with_crypto(config_crypto));
     factory.register(BeanDefinition::actor_with_inject_from_obj::<ConfigActor>(
         config_addr.clone(),
     ));
@@ -78,7 +89,12 @@ pub async fn config_factory(sys_config: Arc<AppSysConfig>) -> anyhow::Result<Fac
     ));
 
     //raft
-    let conn_factory = RaftConnectionFactory::new(60).start();
+    let cluster_tls_connector = if sys_config.tls_cluster_enable {
+        Some(ClusterTlsConnector::new(&sys_config)?)
+    } else {
+        None
+    };
+    let conn_factory = RaftConnectionFactory::new(60, cluster_tls_connector).start();
     factory.register(BeanDefinition::actor_from_obj(conn_factory.clone()));
     let cluster_sender = Arc::new(RaftClusterRequestSender::new(
         conn_factory,
@@ -132,10 +148,17 @@ pub async fn config_factory(sys_config: Arc<AppSysConfig>) -> anyhow::Result<Fac
         cluster_sender.clone(),
     ));
     factory.register(BeanDefinition::from_obj(table_route));
+    let namespace_addr = NamespaceActor::new(sys_config.raft_node_id).start();
+    factory.register(BeanDefinition::actor_with_inject_from_obj(
+        namespace_addr.clone(),
+    ));
     let config_route = Arc::new(ConfigRoute::new(
         config_addr.clone(),
+        namespace_addr.clone(),
         raft_addr_router.clone(),
         cluster_sender.clone(),
+        sys_config.config_validate_enable,
+        sys_config.config_max_content,
     ));
     factory.register(BeanDefinition::from_obj(config_route.clone()));
 
@@ -160,6 +183,12 @@ pub async fn config_factory(sys_config: Arc<AppSysConfig>) -> anyhow::Result<Fac
     let net_sniffing =
         NetSniffing::new(Duration::from_millis(1000), Duration::from_millis(3000)).start();
     factory.register(BeanDefinition::actor_with_inject_from_obj(net_sniffing));
+    if sys_config.naming_dns_enable {
+        let naming_dns_server = NamingDnsServer::new(NamingDnsConfig::new(&sys_config)).start();
+        factory.register(BeanDefinition::actor_with_inject_from_obj(
+            naming_dns_server,
+        ));
+    }
 
     let bistream_manage_addr = BiStreamManage::new().start();
     factory.register(BeanDefinition::actor_with_inject_from_obj(
@@ -178,10 +207,6 @@ pub async fn config_factory(sys_config: Arc<AppSysConfig>) -> anyhow::Result<Fac
         cluster_sender.clone(),
     ));
     factory.register(BeanDefinition::from_obj(cache_route));
-    let namespace_addr = NamespaceActor::new(sys_config.raft_node_id).start();
-    factory.register(BeanDefinition::actor_with_inject_from_obj(
-        namespace_addr.clone(),
-    ));
     let transfer_import_addr = TransferImportManager::new().start();
     factory.register(BeanDefinition::actor_with_inject_from_obj(
         transfer_import_addr.clone(),
@@ -208,6 +233,19 @@ pub async fn config_factory(sys_config: Arc<AppSysConfig>) -> anyhow::Result<Fac
         direct_cache_manager.clone(),
     ));
 
+    let webhook_manager = WebhookManager::new(&sys_config).start();
+    factory.register(BeanDefinition::actor_with_inject_from_obj(
+        webhook_manager.clone(),
+    ));
+    let role_manager = RoleManager::new().start();
+    factory.register(BeanDefinition::actor_with_inject_from_obj(
+        role_manager.clone(),
+    ));
+    let access_key_manager = AccessKeyManager::new().start();
+    factory.register(BeanDefinition::actor_with_inject_from_obj(
+        access_key_manager.clone(),
+    ));
+
     let raft_data_wrap = Arc::new(RaftDataHandler {
         sequence_db: sequence_db_addr,
         config: config_addr.clone(),
@@ -216,6 +254,9 @@ pub async fn config_factory(sys_config: Arc<AppSysConfig>) -> anyhow::Result<Fac
         mcp_manager: mcp_manager.clone(),
         naming_actor: naming_addr.clone(),
         direct_cache_manager: direct_cache_manager.clone(),
+        webhook_manager,
+        role_manager,
+        access_key_manager,
     });
     factory.register(BeanDefinition::from_obj(raft_data_wrap));
     let metrics_manager = MetricsManager::new(sys_config.clone()).start();
@@ -224,6 +265,8 @@ pub async fn config_factory(sys_config: Arc<AppSysConfig>) -> anyhow::Result<Fac
     factory.register(BeanDefinition::actor_with_inject_from_obj(
         transfer_writer_addr,
     ));
+    let backup_manager = BackupManager::new(&sys_config).start();
+    factory.register(BeanDefinition::actor_with_inject_from_obj(backup_manager));
     let health_manager = HealthManager::new().start();
     factory.register(BeanDefinition::actor_with_inject_from_obj(health_manager));
     let ldap_manager =
@@ -234,6 +277,10 @@ pub async fn config_factory(sys_config: Arc<AppSysConfig>) -> anyhow::Result<Fac
     factory.register(BeanDefinition::actor_with_inject_from_obj(oauth2_manager));
     let sse_manager = SseStreamManager::new().start();
     factory.register(BeanDefinition::actor_with_inject_from_obj(sse_manager));
+    let audit_log_manager = AuditLogManager::new(&sys_config).start();
+    factory.register(BeanDefinition::actor_with_inject_from_obj(
+        audit_log_manager,
+    ));
     if sys_config.naming_instance_metadata_persistence_enable {
         if let Ok(instance_meta_manager) = InstanceMetaManager::new(&base_path).await {
             factory.register(BeanDefinition::actor_with_inject_from_obj(
@@ -261,6 +308,9 @@ pub fn build_share_data(factory_data: FactoryData) -> anyhow::Result<Arc<AppShar
         .redirect(reqwest::redirect::Policy::none())
         .build()
         .unwrap();
+    let jwt_auth_manager = sys_config
+        .get_jwt_auth_config()
+        .map(|config| Arc::new(JwtAuthManager::new(config)));
     let app_data = Arc::new(AppShareData {
         config_addr: factory_data.get_actor().unwrap(),
         naming_addr: factory_data.get_actor().unwrap(),
@@ -292,6 +342,12 @@ pub fn build_share_data(factory_data: FactoryData) -> anyhow::Result<Arc<AppShar
         sequence_db_manager: factory_data.get_actor().unwrap(),
         mcp_manager: factory_data.get_actor().unwrap(),
         sse_stream_manager: factory_data.get_actor().unwrap(),
+        audit_log_manager: factory_data.get_actor().unwrap(),
+        webhook_manager: factory_data.get_actor().unwrap(),
+        role_manager: factory_data.get_actor().unwrap(),
+        access_key_manager: factory_data.get_actor().unwrap(),
+        backup_manager: factory_data.get_actor().unwrap(),
+        jwt_auth_manager,
         factory_data,
         common_client: reqwest_client,
     });
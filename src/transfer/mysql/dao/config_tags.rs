@@ -0,0 +1,92 @@
+use crate::common::sqlx_utils::MySqlExecutor;
+use rsql_builder::B;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Debug, Default, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ConfigTagsRelationDO {
+    pub id: Option<i64>,
+    pub tag_name: Option<String>,
+    pub tag_type: Option<String>,
+    pub data_id: Option<String>,
+    pub group_id: Option<String>,
+    pub tenant_id: Option<String>,
+    pub nid: Option<i64>,
+}
+
+#[derive(Debug, Default)]
+pub struct ConfigTagsRelationParam {
+    pub id: Option<i64>,
+    pub data_id: Option<Arc<String>>,
+    pub group_id: Option<Arc<String>>,
+    pub tenant_id: Option<Arc<String>>,
+}
+
+pub struct ConfigTagsRelationSql {}
+
+impl ConfigTagsRelationSql {
+    fn conditions(&self, param: &ConfigTagsRelationParam) -> B<'_> {
+        let mut whr = B::new_where();
+        if let Some(id) = &param.id {
+            whr.eq("id", id);
+        }
+        if let Some(data_id) = &param.data_id {
+            whr.eq("data_id", data_id);
+        }
+        if let Some(group_id) = &param.group_id {
+            whr.eq("group_id", group_id);
+        }
+        if let Some(tenant_id) = &param.tenant_id {
+            whr.eq("tenant_id", tenant_id);
+        }
+        whr
+    }
+
+    pub fn query_prepare(
+        &self,
+        param: &ConfigTagsRelationParam,
+    ) -> (String, Vec<serde_json::Value>) {
+        B::prepare(
+            B::new_sql(
+                "select id, tag_name, tag_type, data_id, group_id, tenant_id, nid \
+            from config_tags_relation",
+            )
+            .push_build(&mut self.conditions(param))
+            .push_fn(|| {
+                let mut b = B::new();
+                b.order_by("nid", false);
+                b
+            }),
+        )
+    }
+}
+
+pub struct ConfigTagsRelationDao<'a> {
+    executor: MySqlExecutor<'a>,
+    inner: ConfigTagsRelationSql,
+}
+
+impl<'a> ConfigTagsRelationDao<'a> {
+    pub fn new(executor: MySqlExecutor<'a>) -> Self {
+        Self {
+            executor,
+            inner: ConfigTagsRelationSql {},
+        }
+    }
+
+    pub async fn fetch(
+        &mut self,
+        sql: &str,
+        args: &[serde_json::Value],
+    ) -> anyhow::Result<Vec<ConfigTagsRelationDO>> {
+        self.executor.fetch(sql, args).await
+    }
+
+    pub async fn query(
+        &mut self,
+        param: &ConfigTagsRelationParam,
+    ) -> anyhow::Result<Vec<ConfigTagsRelationDO>> {
+        let (sql, args) = self.inner.query_prepare(param);
+        self.fetch(&sql, &args).await
+    }
+}
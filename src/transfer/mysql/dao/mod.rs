@@ -9,6 +9,7 @@ use std::collections::HashMap;
 
 pub mod config;
 pub mod config_history;
+pub mod config_tags;
 pub mod tenant;
 pub mod user;
 
@@ -13,6 +13,7 @@ use crate::transfer::mysql::dao::config::{ConfigInfoDO, ConfigInfoDao, ConfigInf
 use crate::transfer::mysql::dao::config_history::{
     ConfigHistoryDO, ConfigHistoryDao, ConfigHistoryParam,
 };
+use crate::transfer::mysql::dao::config_tags::{ConfigTagsRelationDao, ConfigTagsRelationParam};
 use crate::transfer::mysql::dao::tenant::{TenantDao, TenantParam};
 use crate::transfer::mysql::dao::user::{UserDao, UserParam};
 use crate::transfer::sqlite::TableSeq;
@@ -46,9 +47,12 @@ async fn apply_config(
 ) -> anyhow::Result<()> {
     let mut config_pool = pool.clone();
     let mut config_history_pool = pool.clone();
+    let mut config_tags_pool = pool.clone();
     let mut config_dao = ConfigInfoDao::new(MySqlExecutor::new_by_pool(&mut config_pool));
     let mut config_history_dao =
         ConfigHistoryDao::new(MySqlExecutor::new_by_pool(&mut config_history_pool));
+    let mut config_tags_dao =
+        ConfigTagsRelationDao::new(MySqlExecutor::new_by_pool(&mut config_tags_pool));
     let mut count = 0;
     let mut offset = 0;
     let limit = 100;
@@ -80,7 +84,22 @@ async fn apply_config(
                 order_by_gmt_create_desc: true,
             };
             let histories = config_history_dao.query(&history_query_param).await?;
-            let record = build_config_record(table_seq, key, item, histories)?;
+            let tags = if item.id.is_some() {
+                let tags_query_param = ConfigTagsRelationParam {
+                    id: item.id,
+                    ..Default::default()
+                };
+                config_tags_dao
+                    .query(&tags_query_param)
+                    .await?
+                    .into_iter()
+                    .filter_map(|e| e.tag_name)
+                    .collect::<Vec<_>>()
+                    .join(",")
+            } else {
+                String::new()
+            };
+            let record = build_config_record(table_seq, key, item, histories, &tags)?;
             count += 1;
             writer_actor.do_send(TransferWriterRequest::AddRecord(record));
         }
@@ -98,9 +117,11 @@ fn build_config_record(
     key: ConfigKey,
     config_do: ConfigInfoDO,
     histories: Vec<ConfigHistoryDO>,
+    tags: &str,
 ) -> anyhow::Result<TransferRecordDto> {
     let current_content = config_do.content.unwrap_or_default();
     let mut config_value = ConfigValue::new(Arc::new(current_content.clone()));
+    config_value.tags = ConfigUtils::build_tags(tags);
     let mut last_content = None;
     let mut use_histories = vec![];
     for item in histories {
@@ -177,6 +198,10 @@ async fn apply_tenant(
             namespace_id: item.tenant_id,
             namespace_name: item.tenant_name,
             r#type: Some(FROM_USER_VALUE.to_string()),
+            config_schema: None,
+            config_history_max_count: None,
+            config_history_max_days: None,
+            quota: None,
         };
         let record = TransferRecordDto {
             table_name: Some(NAMESPACE_TREE_NAME.clone()),
@@ -61,6 +61,10 @@ fn apply_tenant(
             namespace_id: item.namespace.clone(),
             namespace_name: item.namespace_show_name.clone(),
             r#type: Some(FROM_USER_VALUE.to_string()),
+            config_schema: None,
+            config_history_max_count: None,
+            config_history_max_days: None,
+            quota: None,
         };
         let record = TransferRecordDto {
             table_name: Some(NAMESPACE_TREE_NAME.clone()),
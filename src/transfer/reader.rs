@@ -266,6 +266,10 @@ impl TransferImportManager {
             namespace_id: value.namespace_id,
             namespace_name: Some(value.namespace_name),
             r#type: Some(NamespaceFromFlags::get_db_type(value.flag)),
+            config_schema: value.config_schema.map(|v| v.as_ref().to_owned()),
+            config_history_max_count: value.config_history_max_count,
+            config_history_max_days: value.config_history_max_days,
+            quota: value.quota,
         };
         let req = ClientRequest::NamespaceReq(NamespaceRaftReq::Update(param));
         Self::send_raft_request(raft, req).await?;
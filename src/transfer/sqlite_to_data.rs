@@ -136,6 +136,10 @@ fn apply_tenant(conn: &Connection, writer_actor: &Addr<TransferWriterActor>) ->
             namespace_id: item.tenant_id,
             namespace_name: item.tenant_name,
             r#type: Some(FROM_USER_VALUE.to_string()),
+            config_schema: None,
+            config_history_max_count: None,
+            config_history_max_days: None,
+            quota: None,
         };
         let record = TransferRecordDto {
             table_name: Some(NAMESPACE_TREE_NAME.clone()),
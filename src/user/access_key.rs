@@ -0,0 +1,477 @@
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix::prelude::*;
+use bean_factory::{bean, BeanFactory, FactoryData, Inject};
+use futures_util::FutureExt;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+use crate::common::constant::{ACCESS_KEY_TABLE_NAME, ACCESS_KEY_USAGE_TABLE_NAME};
+use crate::common::model::TokenSession;
+use crate::now_millis_i64;
+use crate::raft::db::route::TableRoute;
+use crate::raft::db::table::{
+    TableManager, TableManagerQueryReq, TableManagerReq, TableManagerResult,
+};
+use crate::user::model::{AccessKeyCreated, AccessKeyDo, AccessKeyDto, AccessKeyParam};
+use crate::user::{UserManager, UserManagerReq, UserManagerResult};
+
+/// 访问密钥token格式：`{ACCESS_KEY_PREFIX}{id}.{secret}`
+pub const ACCESS_KEY_PREFIX: &str = "rak_";
+const ACCESS_KEY_SEPARATOR: char = '.';
+/// 最近使用时间的刷盘间隔
+const USAGE_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+///
+/// 访问密钥管理
+/// 密钥数据通过raft表同步，各节点在内存中维护密钥索引用于请求鉴权；
+/// 最近使用时间先记录在内存，定时写入独立的使用记录表，避免每次请求都触发raft写入
+#[bean(inject)]
+#[derive(Default)]
+pub struct AccessKeyManager {
+    keys: BTreeMap<String, Arc<AccessKeyDo>>,
+    /// 未刷盘的最近使用时间
+    usage_dirty: HashMap<String, i64>,
+    ready: bool,
+    /// 注入完成前收到数据加载完成通知，注入后再加载
+    wait_load: bool,
+    table_route: Option<Arc<TableRoute>>,
+    table_manager: Option<Addr<TableManager>>,
+    user_manager: Option<Addr<UserManager>>,
+}
+
+impl AccessKeyManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn update_key(&mut self, value: &[u8]) {
+        match AccessKeyDo::from_bytes(value) {
+            Ok(v) => {
+                self.keys.insert(v.access_key.clone(), Arc::new(v));
+            }
+            Err(err) => log::warn!("decode access key error,{}", err),
+        }
+    }
+
+    fn load_keys(&mut self, ctx: &mut Context<Self>) {
+        let table_manager = if let Some(table_manager) = self.table_manager.clone() {
+            table_manager
+        } else {
+            self.wait_load = true;
+            return;
+        };
+        self.wait_load = false;
+        let req = TableManagerQueryReq::QueryPageList {
+            table_name: ACCESS_KEY_TABLE_NAME.clone(),
+            like_key: None,
+            offset: None,
+            limit: None,
+            is_rev: false,
+        };
+        async move { table_manager.send(req).await }
+            .into_actor(self)
+            .map(|r, act, _ctx| {
+                if let Ok(Ok(TableManagerResult::PageListResult(_, list))) = r {
+                    act.keys.clear();
+                    for (_, value) in list {
+                        if let Ok(v) = AccessKeyDo::from_bytes(&value) {
+                            act.keys.insert(v.access_key.clone(), Arc::new(v));
+                        }
+                    }
+                }
+                act.ready = true;
+                log::info!("AccessKeyManager load complete, size:{}", act.keys.len());
+            })
+            .wait(ctx);
+    }
+
+    fn hb(&self, ctx: &mut Context<Self>) {
+        ctx.run_later(USAGE_FLUSH_INTERVAL, |act, ctx| {
+            act.flush_usage(ctx);
+            act.hb(ctx);
+        });
+    }
+
+    fn flush_usage(&mut self, ctx: &mut Context<Self>) {
+        let table_route = if let Some(table_route) = self.table_route.clone() {
+            table_route
+        } else {
+            return;
+        };
+        let keys = &self.keys;
+        let reqs: Vec<TableManagerReq> = self
+            .usage_dirty
+            .drain()
+            .filter(|(key, _)| keys.contains_key(key))
+            .map(|(key, time)| TableManagerReq::Set {
+                table_name: ACCESS_KEY_USAGE_TABLE_NAME.clone(),
+                key: key.into_bytes(),
+                value: time.to_be_bytes().to_vec(),
+                last_seq_id: None,
+            })
+            .collect();
+        if reqs.is_empty() {
+            return;
+        }
+        async move {
+            for req in reqs {
+                if let Err(err) = table_route.request(req).await {
+                    log::warn!("flush access key usage error,{}", err);
+                }
+            }
+        }
+        .into_actor(self)
+        .map(|_, _, _| {})
+        .spawn(ctx);
+    }
+
+    fn create_key(&self, param: AccessKeyParam) -> anyhow::Result<(AccessKeyDo, AccessKeyCreated)> {
+        let now = now_millis_i64();
+        param.check_valid(now)?;
+        let access_key = format!("{}{}", ACCESS_KEY_PREFIX, random_hex(8));
+        if self.keys.contains_key(&access_key) {
+            return Err(anyhow::anyhow!("access key conflict, please retry"));
+        }
+        let secret = random_hex(16);
+        let token = format!("{}{}{}", &access_key, ACCESS_KEY_SEPARATOR, &secret);
+        let value = param.build_do(access_key.clone(), hash_secret(&secret), now);
+        Ok((value, AccessKeyCreated { access_key, token }))
+    }
+
+    fn verify(&mut self, token: &str) -> Option<Arc<AccessKeyDo>> {
+        let (access_key, secret) = parse_token(token)?;
+        let value = self.keys.get(access_key)?;
+        let now = now_millis_i64();
+        if value.is_expired(now) || !constant_time_eq(&hash_secret(secret), &value.secret_hash) {
+            return None;
+        }
+        let value = value.clone();
+        self.usage_dirty.insert(value.access_key.clone(), now);
+        Some(value)
+    }
+
+    fn query_list(&self) -> ResponseActFuture<Self, anyhow::Result<AccessKeyManagerResult>> {
+        let table_manager = self.table_manager.clone();
+        let keys: Vec<Arc<AccessKeyDo>> = self.keys.values().cloned().collect();
+        let dirty = self.usage_dirty.clone();
+        let fut = async move {
+            let table_manager =
+                table_manager.ok_or_else(|| anyhow::anyhow!("access key table is not ready"))?;
+            let req = TableManagerQueryReq::QueryPageList {
+                table_name: ACCESS_KEY_USAGE_TABLE_NAME.clone(),
+                like_key: None,
+                offset: None,
+                limit: None,
+                is_rev: false,
+            };
+            let mut usage: HashMap<String, i64> = HashMap::new();
+            if let TableManagerResult::PageListResult(_, list) = table_manager.send(req).await?? {
+                for (key, value) in list {
+                    if let Ok(v) = <[u8; 8]>::try_from(value.as_slice()) {
+                        usage.insert(
+                            String::from_utf8_lossy(&key).to_string(),
+                            i64::from_be_bytes(v),
+                        );
+                    }
+                }
+            }
+            for (key, time) in dirty {
+                let entry = usage.entry(key).or_default();
+                *entry = (*entry).max(time);
+            }
+            let list = keys
+                .into_iter()
+                .map(|info| AccessKeyDto {
+                    last_used_time: usage.get(&info.access_key).copied(),
+                    info,
+                })
+                .collect();
+            Ok(AccessKeyManagerResult::List(list))
+        };
+        Box::pin(fut.into_actor(self).map(|r, _act, _ctx| r))
+    }
+}
+
+///
+/// 是否为访问密钥格式的token，用于和登录会话token区分
+pub fn is_access_key_token(token: &str) -> bool {
+    parse_token(token).is_some()
+}
+
+fn parse_token(token: &str) -> Option<(&str, &str)> {
+    if !token.starts_with(ACCESS_KEY_PREFIX) {
+        return None;
+    }
+    token
+        .split_once(ACCESS_KEY_SEPARATOR)
+        .filter(|(access_key, secret)| {
+            access_key.len() > ACCESS_KEY_PREFIX.len() && !secret.is_empty()
+        })
+}
+
+fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    rand::thread_rng().fill(bytes.as_mut_slice());
+    bytes_to_hex(&bytes)
+}
+
+fn bytes_to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hash_secret(secret: &str) -> String {
+    bytes_to_hex(&Sha256::digest(secret.as_bytes()))
+}
+
+/// 比较耗时与不匹配的位置无关
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+            == 0
+}
+
+///
+/// 访问密钥鉴权，通过后返回与登录会话相同结构的TokenSession
+pub async fn get_access_key_session(
+    access_key_manager: &Addr<AccessKeyManager>,
+    token: Arc<String>,
+) -> anyhow::Result<Option<Arc<TokenSession>>> {
+    match access_key_manager
+        .send(AccessKeyManagerReq::Authenticate(token))
+        .await??
+    {
+        AccessKeyManagerResult::Session(v) => Ok(v),
+        _ => Err(anyhow::anyhow!("AccessKeyManagerResult is error")),
+    }
+}
+
+async fn build_session(
+    user_manager: Option<Addr<UserManager>>,
+    value: Arc<AccessKeyDo>,
+) -> anyhow::Result<Option<Arc<TokenSession>>> {
+    let mut extend_infos = HashMap::new();
+    extend_infos.insert("accessKey".to_owned(), value.access_key.clone());
+    if value.username.is_empty() {
+        // 服务账号
+        return Ok(Some(Arc::new(TokenSession {
+            username: Arc::new(format!("sa:{}", &value.name)),
+            roles: value.roles.iter().map(|e| Arc::new(e.to_owned())).collect(),
+            extend_infos,
+            scopes: value.scopes.clone(),
+            groups: vec![],
+        })));
+    }
+    let user_manager = user_manager.ok_or_else(|| anyhow::anyhow!("user manager is not ready"))?;
+    let req = UserManagerReq::Query {
+        name: Arc::new(value.username.clone()),
+    };
+    match user_manager.send(req).await?? {
+        UserManagerResult::QueryUser(Some(user)) if user.enable.unwrap_or(true) => {
+            extend_infos.extend(user.extend_info.unwrap_or_default());
+            Ok(Some(Arc::new(TokenSession {
+                username: user.username,
+                roles: user.roles.unwrap_or_default(),
+                extend_infos,
+                scopes: value.scopes.clone(),
+                groups: vec![],
+            })))
+        }
+        _ => Ok(None),
+    }
+}
+
+impl Actor for AccessKeyManager {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        log::info!("AccessKeyManager actor started");
+        self.hb(ctx);
+    }
+}
+
+impl Inject for AccessKeyManager {
+    type Context = Context<Self>;
+
+    fn inject(
+        &mut self,
+        factory_data: FactoryData,
+        _factory: BeanFactory,
+        ctx: &mut Self::Context,
+    ) {
+        self.table_route = factory_data.get_bean();
+        self.table_manager = factory_data.get_actor();
+        self.user_manager = factory_data.get_actor();
+        if self.wait_load {
+            self.load_keys(ctx);
+        }
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub enum AccessKeyCmd {
+    /// 密钥表数据变更，由TableManager在raft apply时通知
+    NotifyChange {
+        key: Vec<u8>,
+        value: Vec<u8>,
+    },
+    NotifyRemove {
+        key: Vec<u8>,
+    },
+    LoadCompleted,
+}
+
+impl Handler<AccessKeyCmd> for AccessKeyManager {
+    type Result = ();
+
+    fn handle(&mut self, msg: AccessKeyCmd, ctx: &mut Self::Context) -> Self::Result {
+        match msg {
+            AccessKeyCmd::NotifyChange { key: _, value } => {
+                self.update_key(&value);
+            }
+            AccessKeyCmd::NotifyRemove { key } => {
+                let key = String::from_utf8_lossy(&key);
+                self.keys.remove(key.as_ref());
+                self.usage_dirty.remove(key.as_ref());
+            }
+            AccessKeyCmd::LoadCompleted => {
+                if !self.ready {
+                    self.load_keys(ctx);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "anyhow::Result<AccessKeyManagerResult>")]
+pub enum AccessKeyManagerReq {
+    QueryList,
+    Create(AccessKeyParam),
+    Revoke(String),
+    Authenticate(Arc<String>),
+}
+
+pub enum AccessKeyManagerResult {
+    None,
+    List(Vec<AccessKeyDto>),
+    Created(AccessKeyCreated),
+    Session(Option<Arc<TokenSession>>),
+}
+
+impl Handler<AccessKeyManagerReq> for AccessKeyManager {
+    type Result = ResponseActFuture<Self, anyhow::Result<AccessKeyManagerResult>>;
+
+    fn handle(&mut self, msg: AccessKeyManagerReq, _ctx: &mut Self::Context) -> Self::Result {
+        let table_route = self.table_route.clone();
+        let fut = match msg {
+            AccessKeyManagerReq::QueryList => return self.query_list(),
+            AccessKeyManagerReq::Authenticate(token) => {
+                let value = self.verify(&token);
+                let user_manager = self.user_manager.clone();
+                async move {
+                    let session = match value {
+                        Some(value) => build_session(user_manager, value).await?,
+                        None => None,
+                    };
+                    Ok(AccessKeyManagerResult::Session(session))
+                }
+                .boxed_local()
+            }
+            AccessKeyManagerReq::Create(param) => {
+                let created = self.create_key(param);
+                let user_manager = self.user_manager.clone();
+                async move {
+                    let (value, created) = created?;
+                    if !value.username.is_empty() {
+                        check_user_exists(user_manager, &value.username).await?;
+                    }
+                    let table_route = table_route
+                        .ok_or_else(|| anyhow::anyhow!("access key table is not ready"))?;
+                    table_route
+                        .request(TableManagerReq::Set {
+                            table_name: ACCESS_KEY_TABLE_NAME.clone(),
+                            key: value.access_key.as_bytes().to_vec(),
+                            value: value.to_bytes(),
+                            last_seq_id: None,
+                        })
+                        .await?;
+                    Ok(AccessKeyManagerResult::Created(created))
+                }
+                .boxed_local()
+            }
+            AccessKeyManagerReq::Revoke(access_key) => {
+                let exists = self.keys.contains_key(&access_key);
+                async move {
+                    if !exists {
+                        return Err(anyhow::anyhow!("access key not found: {}", &access_key));
+                    }
+                    let table_route = table_route
+                        .ok_or_else(|| anyhow::anyhow!("access key table is not ready"))?;
+                    table_route
+                        .request(TableManagerReq::Remove {
+                            table_name: ACCESS_KEY_TABLE_NAME.clone(),
+                            key: access_key.as_bytes().to_vec(),
+                        })
+                        .await?;
+                    table_route
+                        .request(TableManagerReq::Remove {
+                            table_name: ACCESS_KEY_USAGE_TABLE_NAME.clone(),
+                            key: access_key.into_bytes(),
+                        })
+                        .await?;
+                    Ok(AccessKeyManagerResult::None)
+                }
+                .boxed_local()
+            }
+        };
+        Box::pin(fut.into_actor(self).map(|r, _act, _ctx| r))
+    }
+}
+
+async fn check_user_exists(
+    user_manager: Option<Addr<UserManager>>,
+    username: &str,
+) -> anyhow::Result<()> {
+    let user_manager = user_manager.ok_or_else(|| anyhow::anyhow!("user manager is not ready"))?;
+    let req = UserManagerReq::Query {
+        name: Arc::new(username.to_owned()),
+    };
+    match user_manager.send(req).await?? {
+        UserManagerResult::QueryUser(Some(_)) => Ok(()),
+        _ => Err(anyhow::anyhow!("user not found: {}", username)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn access_key_token_parse() {
+        assert_eq!(
+            parse_token("rak_0123abcd.secret"),
+            Some(("rak_0123abcd", "secret"))
+        );
+        assert!(!is_access_key_token("rak_.secret"));
+        assert!(!is_access_key_token("rak_0123abcd."));
+        assert!(!is_access_key_token("0123456789abcdef0123456789abcdef"));
+    }
+
+    #[test]
+    fn access_key_secret_hash() {
+        let secret = random_hex(16);
+        assert_eq!(secret.len(), 32);
+        let hash = hash_secret(&secret);
+        assert_eq!(hash.len(), 64);
+        assert!(constant_time_eq(&hash, &hash_secret(&secret)));
+        assert!(!constant_time_eq(&hash, &hash_secret("other")));
+        assert!(!constant_time_eq(&hash, &hash[1..]));
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use reqwest::Client;
+use serde_json::{Map, Value};
+
+use crate::common::jwt_utils::{is_jwt_format, validate_claims, JwksKeySet, JwksSource};
+use crate::common::model::TokenSession;
+use crate::user::permission::{RolePermission, PERMISSION_ALL};
+
+///
+/// 外部身份提供方签发的JWT鉴权配置
+#[derive(Clone, Debug, Default)]
+pub struct JwtAuthConfig {
+    /// 公钥来源，JWKS文件路径或url
+    pub jwks: Arc<String>,
+    pub issuer: Arc<String>,
+    /// 为空时不校验aud
+    pub audience: Arc<String>,
+    pub username_claim_name: Arc<String>,
+    pub groups_claim_name: Arc<String>,
+    pub default_role: Arc<String>,
+    pub developer_groups: Arc<HashSet<String>>,
+    pub admin_groups: Arc<HashSet<String>>,
+    /// 用户组可访问的命名空间，为空时不按命名空间限制
+    pub group_namespaces: Arc<HashMap<String, HashSet<String>>>,
+}
+
+///
+/// 校验外部JWT并映射为openapi、gRPC使用的会话，不需要先调用登录接口
+pub struct JwtAuthManager {
+    config: Arc<JwtAuthConfig>,
+    key_set: JwksKeySet,
+    http_client: Client,
+}
+
+impl JwtAuthManager {
+    pub fn new(config: Arc<JwtAuthConfig>) -> Self {
+        let key_set = JwksKeySet::new(JwksSource::parse(&config.jwks));
+        Self {
+            config,
+            key_set,
+            http_client: Client::new(),
+        }
+    }
+
+    pub async fn get_session(&self, token: &str) -> anyhow::Result<Arc<TokenSession>> {
+        let claims = self.key_set.verify(&self.http_client, token).await?;
+        validate_claims(
+            &claims,
+            &self.config.issuer,
+            &self.config.audience,
+            None,
+            crate::now_second_i32() as i64,
+        )?;
+        Ok(Arc::new(build_session(&self.config, &claims)?))
+    }
+}
+
+///
+/// 开启JWT鉴权且token为JWT格式时走JWT鉴权
+pub fn is_jwt_token(manager: &Option<Arc<JwtAuthManager>>, token: &str) -> bool {
+    manager.is_some() && is_jwt_format(token)
+}
+
+///
+/// 会话用户名为`jwt:{username}`，避免与本地用户混淆；
+/// 角色按用户组映射内置角色，自定义角色通过用户组绑定，命名空间映射转为会话的授权范围
+fn build_session(
+    config: &JwtAuthConfig,
+    claims: &Map<String, Value>,
+) -> anyhow::Result<TokenSession> {
+    let username = claims
+        .get(config.username_claim_name.as_str())
+        .and_then(|v| v.as_str())
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("jwt username claim is missing"))?;
+    let groups: Vec<Arc<String>> = match claims.get(config.groups_claim_name.as_str()) {
+        Some(Value::Array(list)) => list
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(|v| Arc::new(v.to_owned()))
+            .collect(),
+        Some(Value::String(v)) => v
+            .split([',', ' '])
+            .filter(|v| !v.is_empty())
+            .map(|v| Arc::new(v.to_owned()))
+            .collect(),
+        _ => vec![],
+    };
+    let role = if groups
+        .iter()
+        .any(|e| config.admin_groups.contains(e.as_str()))
+    {
+        crate::user::permission::USER_ROLE_MANAGER.clone()
+    } else if groups
+        .iter()
+        .any(|e| config.developer_groups.contains(e.as_str()))
+    {
+        crate::user::permission::USER_ROLE_DEVELOPER.clone()
+    } else {
+        config.default_role.clone()
+    };
+    let scopes = build_namespace_scopes(config, &groups)?;
+    let mut extend_infos = HashMap::new();
+    extend_infos.insert("source".to_owned(), "jwt".to_owned());
+    Ok(TokenSession {
+        username: Arc::new(format!("jwt:{}", username)),
+        roles: vec![role],
+        extend_infos,
+        scopes,
+        groups,
+    })
+}
+
+fn build_namespace_scopes(
+    config: &JwtAuthConfig,
+    groups: &[Arc<String>],
+) -> anyhow::Result<Vec<RolePermission>> {
+    if config.group_namespaces.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut namespaces = HashSet::new();
+    for item in groups
+        .iter()
+        .filter_map(|e| config.group_namespaces.get(e.as_str()))
+    {
+        if item.contains(PERMISSION_ALL) {
+            return Ok(vec![]);
+        }
+        namespaces.extend(item.iter());
+    }
+    if namespaces.is_empty() {
+        return Err(anyhow::anyhow!("jwt groups has no namespace privilege"));
+    }
+    Ok(namespaces
+        .into_iter()
+        .map(|namespace| RolePermission {
+            namespace: namespace.to_owned(),
+            ..RolePermission::new(PERMISSION_ALL, &[PERMISSION_ALL])
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oauth2::model::OAuth2Config;
+    use crate::user::permission::{USER_ROLE_DEVELOPER, USER_ROLE_MANAGER, USER_ROLE_VISITOR};
+
+    fn build_config() -> JwtAuthConfig {
+        JwtAuthConfig {
+            username_claim_name: Arc::new("sub".to_owned()),
+            groups_claim_name: Arc::new("groups".to_owned()),
+            default_role: USER_ROLE_VISITOR.clone(),
+            developer_groups: Arc::new(HashSet::from(["devs".to_owned()])),
+            admin_groups: Arc::new(HashSet::from(["admins".to_owned()])),
+            group_namespaces: Arc::new(OAuth2Config::parse_group_namespaces(
+                "devs:dev|test,admins:*",
+            )),
+            ..Default::default()
+        }
+    }
+
+    fn build_claims(groups: Value) -> Map<String, Value> {
+        serde_json::json!({"sub": "order-service", "groups": groups})
+            .as_object()
+            .cloned()
+            .unwrap()
+    }
+
+    #[test]
+    fn jwt_claims_to_session() {
+        let config = build_config();
+        let session = build_session(&config, &build_claims(serde_json::json!(["devs"]))).unwrap();
+        assert_eq!(session.username.as_str(), "jwt:order-service");
+        assert_eq!(session.roles, vec![USER_ROLE_DEVELOPER.clone()]);
+        let mut namespaces: Vec<&str> = session
+            .scopes
+            .iter()
+            .map(|e| e.namespace.as_str())
+            .collect();
+        namespaces.sort();
+        assert_eq!(namespaces, vec!["dev", "test"]);
+
+        let session =
+            build_session(&config, &build_claims(serde_json::json!("devs admins"))).unwrap();
+        assert_eq!(session.roles, vec![USER_ROLE_MANAGER.clone()]);
+        assert!(session.scopes.is_empty());
+
+        assert!(build_session(&config, &build_claims(serde_json::json!(["others"]))).is_err());
+        assert!(build_session(&config, &Map::new()).is_err());
+    }
+}
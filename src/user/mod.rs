@@ -26,9 +26,12 @@ use crate::{
     },
 };
 
+pub mod access_key;
 pub mod api;
+pub mod jwt_auth;
 pub mod model;
 pub mod permission;
+pub mod role;
 
 pub(crate) fn build_password_hash(password: &str) -> anyhow::Result<String> {
     Ok(bcrypt::hash(password, 10u32)?)
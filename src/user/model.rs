@@ -1,5 +1,6 @@
 use crate::common::model::privilege::{PrivilegeGroup, PrivilegeGroupFlags};
-use crate::user::permission::UserRoleHelper;
+use crate::common::string_utils::StringUtils;
+use crate::user::permission::{RolePermission, UserRoleHelper};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::{collections::HashMap, sync::Arc};
@@ -119,6 +120,22 @@ pub struct UserDto {
     pub source: Option<String>,
 }
 
+impl UserDto {
+    /// 用户已分配的自定义角色
+    pub fn get_custom_roles(&self) -> Vec<Arc<String>> {
+        self.roles
+            .as_ref()
+            .map(|roles| {
+                roles
+                    .iter()
+                    .filter(|e| !UserRoleHelper::is_builtin_role(e))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
 impl From<UserDo> for UserDto {
     fn from(value: UserDo) -> Self {
         let mut roles = vec![];
@@ -143,3 +160,201 @@ impl From<UserDo> for UserDto {
         }
     }
 }
+
+///
+/// 自定义角色，groups为绑定该角色的LDAP/OAuth2用户组
+#[derive(Clone, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleDo {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub description: String,
+    #[prost(message, repeated, tag = "3")]
+    pub permissions: Vec<RolePermission>,
+    #[prost(string, repeated, tag = "4")]
+    pub groups: Vec<String>,
+    /// 时间戳，单位毫秒
+    #[prost(int64, tag = "5")]
+    pub gmt_create: i64,
+    #[prost(int64, tag = "6")]
+    pub gmt_modified: i64,
+}
+
+impl RoleDo {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::new();
+        prost::Message::encode(self, &mut v).unwrap_or_default();
+        v
+    }
+
+    pub fn from_bytes(v: &[u8]) -> anyhow::Result<Self> {
+        Ok(prost::Message::decode(v)?)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleParam {
+    pub name: String,
+    pub description: Option<String>,
+    pub permissions: Option<Vec<RolePermission>>,
+    pub groups: Option<Vec<String>>,
+}
+
+const MAX_ROLE_NAME_LEN: usize = 64;
+
+impl RoleParam {
+    pub fn check_valid(&self) -> anyhow::Result<()> {
+        if self.name.is_empty() || self.name.len() > MAX_ROLE_NAME_LEN {
+            return Err(anyhow::anyhow!("role name length must be in 1..=64"));
+        }
+        // 用户角色以逗号分隔传递
+        if self.name.contains(',') {
+            return Err(anyhow::anyhow!("role name can not contain ','"));
+        }
+        if UserRoleHelper::is_builtin_role(&self.name) {
+            return Err(anyhow::anyhow!(
+                "role name conflicts with builtin role: {}",
+                &self.name
+            ));
+        }
+        if let Some(permissions) = &self.permissions {
+            for permission in permissions {
+                permission.validate()?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn build_do(self, old: Option<&RoleDo>, now: i64) -> RoleDo {
+        let old = old.cloned().unwrap_or_else(|| RoleDo {
+            gmt_create: now,
+            ..Default::default()
+        });
+        RoleDo {
+            name: self.name,
+            description: self.description.unwrap_or(old.description),
+            permissions: self.permissions.unwrap_or(old.permissions),
+            groups: self
+                .groups
+                .map(|v| v.into_iter().filter(|e| !e.is_empty()).collect())
+                .unwrap_or(old.groups),
+            gmt_create: old.gmt_create,
+            gmt_modified: now,
+        }
+    }
+}
+
+///
+/// 访问密钥，供CI等机器客户端调用openapi与gRPC接口
+/// username不为空时使用该用户的角色，为空时作为服务账号使用密钥自身配置的角色；
+/// 密钥只保存sha256摘要，明文只在创建时返回一次
+#[derive(Clone, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessKeyDo {
+    #[prost(string, tag = "1")]
+    pub access_key: String,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(string, tag = "3")]
+    #[serde(skip)]
+    pub secret_hash: String,
+    #[prost(string, tag = "4")]
+    pub username: String,
+    #[prost(string, repeated, tag = "5")]
+    pub roles: Vec<String>,
+    #[prost(message, repeated, tag = "6")]
+    pub scopes: Vec<RolePermission>,
+    /// 过期时间戳，单位毫秒，0表示不过期
+    #[prost(int64, tag = "7")]
+    pub expire_time: i64,
+    #[prost(int64, tag = "8")]
+    pub gmt_create: i64,
+}
+
+impl AccessKeyDo {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::new();
+        prost::Message::encode(self, &mut v).unwrap_or_default();
+        v
+    }
+
+    pub fn from_bytes(v: &[u8]) -> anyhow::Result<Self> {
+        Ok(prost::Message::decode(v)?)
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expire_time > 0 && self.expire_time <= now
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessKeyParam {
+    pub name: String,
+    pub username: Option<String>,
+    pub roles: Option<Vec<String>>,
+    pub scopes: Option<Vec<RolePermission>>,
+    pub expire_time: Option<i64>,
+}
+
+const MAX_ACCESS_KEY_NAME_LEN: usize = 64;
+
+impl AccessKeyParam {
+    pub fn check_valid(&self, now: i64) -> anyhow::Result<()> {
+        if self.name.is_empty() || self.name.len() > MAX_ACCESS_KEY_NAME_LEN {
+            return Err(anyhow::anyhow!("access key name length must be in 1..=64"));
+        }
+        let has_user = !StringUtils::is_option_empty(&self.username);
+        let has_roles = self.roles.as_ref().is_some_and(|v| !v.is_empty());
+        if has_user == has_roles {
+            return Err(anyhow::anyhow!(
+                "access key must be bound to either a username or roles"
+            ));
+        }
+        if let Some(scopes) = &self.scopes {
+            for scope in scopes {
+                scope.validate()?;
+            }
+        }
+        if self.expire_time.is_some_and(|v| v > 0 && v <= now) {
+            return Err(anyhow::anyhow!("access key expire time is in the past"));
+        }
+        Ok(())
+    }
+
+    pub fn build_do(self, access_key: String, secret_hash: String, now: i64) -> AccessKeyDo {
+        AccessKeyDo {
+            access_key,
+            name: self.name,
+            secret_hash,
+            username: self.username.unwrap_or_default(),
+            roles: self
+                .roles
+                .map(|v| v.into_iter().filter(|e| !e.is_empty()).collect())
+                .unwrap_or_default(),
+            scopes: self.scopes.unwrap_or_default(),
+            expire_time: self.expire_time.unwrap_or_default(),
+            gmt_create: now,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessKeyDto {
+    #[serde(flatten)]
+    pub info: Arc<AccessKeyDo>,
+    /// 最近使用时间戳，单位毫秒
+    pub last_used_time: Option<i64>,
+}
+
+///
+/// 创建访问密钥的返回值，token只返回一次
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessKeyCreated {
+    pub access_key: String,
+    pub token: String,
+}
@@ -3,9 +3,18 @@
 /// 权限资源分为两类：
 /// 1）web资源，由前端控制页面是否支持访问；
 /// 2）http请求路径，由后端拦截器控制否支持请求；
-use std::{collections::HashSet, hash::Hash, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    sync::Arc,
+};
+
+use serde::{Deserialize, Serialize};
 
 use crate::common::constant::{EMPTY_STR, HTTP_METHOD_ALL, HTTP_METHOD_GET, HTTP_METHOD_POST};
+use crate::common::string_utils::StringUtils;
+use crate::common::web_utils::{get_content_type, parse_params, peek_request_body};
+use actix_web::dev::ServiceRequest;
 
 pub enum Resource {
     WebResource(&'static str),
@@ -164,6 +173,36 @@ lazy_static::lazy_static! {
         R::Path("/rnacos/manage/cluster",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/cluster/cluster_node_list",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/v2/cluster/cluster_node_list",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/cluster/raft/members",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/connection/list",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/connection/node_counts",HTTP_METHOD_GET),
+    ]);
+
+    static ref M_CONNECTION_MANAGE: ModuleResource = ModuleResource::new(vec![
+        //path
+        R::Path("/rnacos/api/console/connections",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/connection/list",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/connection/node_counts",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/connection/eject",HTTP_METHOD_POST),
+        R::Path("/rnacos/api/console/v2/connection/rebalance",HTTP_METHOD_POST),
+    ]);
+
+    static ref M_CLUSTER_MANAGE: ModuleResource = ModuleResource::new(vec![
+        //path
+        R::Path("/rnacos/api/console/v2/cluster/raft/members",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/cluster/raft/promote",HTTP_METHOD_POST),
+        R::Path("/rnacos/api/console/v2/cluster/raft/remove",HTTP_METHOD_POST),
+        R::Path("/rnacos/api/console/v2/cluster/raft/snapshot",HTTP_METHOD_POST),
+    ]);
+
+    static ref M_USER_VISITOR: ModuleResource = ModuleResource::new(vec![
+        //WebResource
+        R::WebResource("/manage/user"),
+        R::WebResource("/rnacos/manage/user"),
+        //path
+        R::Path("/rnacos/manage/user",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/user/list",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/user/list",HTTP_METHOD_GET),
     ]);
 
     static ref M_NAMESPACE_VISITOR: ModuleResource = ModuleResource::new(vec![
@@ -225,9 +264,12 @@ lazy_static::lazy_static! {
         R::Path("/rnacos/api/console/config/history",HTTP_METHOD_GET),
 
         R::Path("/rnacos/api/console/v2/config/list",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/config/listener/list",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/v2/config/download",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/v2/config/info",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/v2/config/history",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/config/history/diff",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/config/gray/info",HTTP_METHOD_GET),
     ]);
 
     static ref M_CONFIG_MANAGE: ModuleResource = ModuleResource::new(vec![
@@ -249,13 +291,25 @@ lazy_static::lazy_static! {
         R::Path("/rnacos/api/console/config/history",HTTP_METHOD_GET),
 
         R::Path("/rnacos/api/console/v2/config/list",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/config/listener/list",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/v2/config/download",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/v2/config/info",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/v2/config/history",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/config/history/diff",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/config/history/rollback",HTTP_METHOD_ALL),
         R::Path("/rnacos/api/console/v2/config/import",HTTP_METHOD_ALL),
         R::Path("/rnacos/api/console/v2/config/add",HTTP_METHOD_ALL),
         R::Path("/rnacos/api/console/v2/config/update",HTTP_METHOD_ALL),
         R::Path("/rnacos/api/console/v2/config/remove",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/v2/config/gray/info",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/config/gray/publish",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/v2/config/gray/promote",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/v2/config/gray/stop",HTTP_METHOD_ALL),
+    ]);
+
+    static ref M_CONFIG_ENCRYPT_MANAGE: ModuleResource = ModuleResource::new(vec![
+        //path
+        R::Path("/rnacos/api/console/v2/config/encrypt/rotate",HTTP_METHOD_POST),
     ]);
 
     static ref M_NAMING_VISITOR: ModuleResource = ModuleResource::new(vec![
@@ -339,6 +393,54 @@ lazy_static::lazy_static! {
         R::Path("/rnacos/api/console/transfer/import",HTTP_METHOD_ALL),
     ]);
 
+    static ref M_AUDIT_MANAGE: ModuleResource = ModuleResource::new(vec![
+        //WebResource
+        R::WebResource("/manage/audit"),
+        R::WebResource("/rnacos/manage/audit"),
+        //path
+        R::Path("/rnacos/manage/audit",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/audit/list",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/audit/export",HTTP_METHOD_GET),
+    ]);
+
+    static ref M_WEBHOOK_MANAGE: ModuleResource = ModuleResource::new(vec![
+        //WebResource
+        R::WebResource("/manage/webhook"),
+        R::WebResource("/rnacos/manage/webhook"),
+        //path
+        R::Path("/rnacos/manage/webhook",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/webhook/list",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/webhook/add",HTTP_METHOD_POST),
+        R::Path("/rnacos/api/console/v2/webhook/update",HTTP_METHOD_POST),
+        R::Path("/rnacos/api/console/v2/webhook/remove",HTTP_METHOD_POST),
+        R::Path("/rnacos/api/console/v2/webhook/deadletter/list",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/webhook/deadletter/retry",HTTP_METHOD_POST),
+        R::Path("/rnacos/api/console/v2/webhook/deadletter/remove",HTTP_METHOD_POST),
+    ]);
+
+    static ref M_ROLE_MANAGE: ModuleResource = ModuleResource::new(vec![
+        //WebResource
+        R::WebResource("/manage/role"),
+        R::WebResource("/rnacos/manage/role"),
+        //path
+        R::Path("/rnacos/manage/role",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/role/list",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/role/add",HTTP_METHOD_POST),
+        R::Path("/rnacos/api/console/v2/role/update",HTTP_METHOD_POST),
+        R::Path("/rnacos/api/console/v2/role/remove",HTTP_METHOD_POST),
+    ]);
+
+    static ref M_ACCESS_KEY_MANAGE: ModuleResource = ModuleResource::new(vec![
+        //WebResource
+        R::WebResource("/manage/accesskey"),
+        R::WebResource("/rnacos/manage/accesskey"),
+        //path
+        R::Path("/rnacos/manage/accesskey",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/accesskey/list",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/accesskey/add",HTTP_METHOD_POST),
+        R::Path("/rnacos/api/console/v2/accesskey/remove",HTTP_METHOD_POST),
+    ]);
+
     static ref M_MCP_TOOL_SPEC_VISITOR: ModuleResource = ModuleResource::new(vec![
         //WebResource
         R::WebResource("/manage/mcptoolspec"),
@@ -431,9 +533,16 @@ lazy_static::lazy_static! {
         &M_NAMESPACE_MANAGE,
         &M_CONFIG_MANAGE,
         &M_NAMING_MANAGE,
+        &M_CONFIG_ENCRYPT_MANAGE,
+        &M_CONNECTION_MANAGE,
+        &M_CLUSTER_MANAGE,
         &M_USER_MANAGE,
         &M_METRICS_VISITOR,
         &M_TRASFER_DATE_MANAGE,
+        &M_AUDIT_MANAGE,
+        &M_WEBHOOK_MANAGE,
+        &M_ROLE_MANAGE,
+        &M_ACCESS_KEY_MANAGE,
         &M_MCP_TOOL_SPEC_MANAGE,
         &M_MCP_SERVER_MANAGE,
     ]));
@@ -565,4 +674,913 @@ impl UserRoleHelper {
             _ => default,
         }
     }
+
+    /// 非内置角色为自定义角色
+    pub fn is_builtin_role(role_value: &str) -> bool {
+        !matches!(UserRole::new(role_value), UserRole::None)
+    }
+}
+
+pub const PERMISSION_ALL: &str = "*";
+const PUBLIC_NAMESPACE: &str = "public";
+const DEFAULT_GROUP: &str = "DEFAULT_GROUP";
+
+/// 自定义角色可授权的资源类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PermissionResource {
+    Config,
+    Service,
+    User,
+    Namespace,
+    Mcp,
+}
+
+impl PermissionResource {
+    pub const ALL: [PermissionResource; 5] = [
+        PermissionResource::Config,
+        PermissionResource::Service,
+        PermissionResource::User,
+        PermissionResource::Namespace,
+        PermissionResource::Mcp,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "config" => Some(Self::Config),
+            "service" => Some(Self::Service),
+            "user" => Some(Self::User),
+            "namespace" => Some(Self::Namespace),
+            "mcp" => Some(Self::Mcp),
+            _ => None,
+        }
+    }
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            Self::Config => "config",
+            Self::Service => "service",
+            Self::User => "user",
+            Self::Namespace => "namespace",
+            Self::Mcp => "mcp",
+        }
+    }
+
+    /// 自定义角色在控制台可访问的功能模块
+    fn get_modules(&self, action: PermissionAction) -> Vec<&'static ModuleResource> {
+        let read = action == PermissionAction::Read;
+        match self {
+            Self::Config if read => vec![&*M_CONFIG_VISITOR],
+            Self::Config => vec![&*M_CONFIG_MANAGE],
+            Self::Service if read => vec![&*M_NAMING_VISITOR],
+            Self::Service => vec![&*M_NAMING_MANAGE],
+            Self::User if read => vec![&*M_USER_VISITOR],
+            Self::User => vec![&*M_USER_MANAGE],
+            Self::Namespace if read => vec![&*M_NAMESPACE_VISITOR],
+            Self::Namespace => vec![&*M_NAMESPACE_MANAGE],
+            Self::Mcp if read => vec![&*M_MCP_TOOL_SPEC_VISITOR, &*M_MCP_SERVER_VISITOR],
+            Self::Mcp => vec![&*M_MCP_TOOL_SPEC_MANAGE, &*M_MCP_SERVER_MANAGE],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PermissionAction {
+    Read,
+    Write,
+    Delete,
+    Publish,
+}
+
+impl PermissionAction {
+    pub const ALL: [PermissionAction; 4] = [
+        PermissionAction::Read,
+        PermissionAction::Write,
+        PermissionAction::Delete,
+        PermissionAction::Publish,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "read" => Some(Self::Read),
+            "write" => Some(Self::Write),
+            "delete" => Some(Self::Delete),
+            "publish" => Some(Self::Publish),
+            _ => None,
+        }
+    }
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::Delete => "delete",
+            Self::Publish => "publish",
+        }
+    }
+
+    /// restful接口按http方法区分操作
+    pub fn from_method(method: &str) -> Self {
+        match method {
+            "GET" | "HEAD" => Self::Read,
+            "DELETE" => Self::Delete,
+            _ => Self::Write,
+        }
+    }
+}
+
+///
+/// 角色中的一条授权，resource与actions支持`*`表示全部；
+/// namespace、group、data_id为通配符表达式(支持`*`与`?`)，为空时不限制，服务授权中data_id对应服务名
+#[derive(Clone, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RolePermission {
+    #[prost(string, tag = "1")]
+    pub resource: String,
+    #[prost(string, repeated, tag = "2")]
+    pub actions: Vec<String>,
+    #[prost(string, tag = "3")]
+    pub namespace: String,
+    #[prost(string, tag = "4")]
+    pub group: String,
+    #[prost(string, tag = "5")]
+    pub data_id: String,
+}
+
+impl RolePermission {
+    pub fn new(resource: &str, actions: &[&str]) -> Self {
+        Self {
+            resource: resource.to_owned(),
+            actions: actions.iter().map(|e| (*e).to_owned()).collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.resource != PERMISSION_ALL
+            && PermissionResource::from_name(&self.resource).is_none()
+        {
+            return Err(anyhow::anyhow!(
+                "unknown permission resource: {}",
+                &self.resource
+            ));
+        }
+        if self.actions.is_empty() {
+            return Err(anyhow::anyhow!("permission actions is empty"));
+        }
+        for action in &self.actions {
+            if action != PERMISSION_ALL && PermissionAction::from_name(action).is_none() {
+                return Err(anyhow::anyhow!("unknown permission action: {}", action));
+            }
+        }
+        Ok(())
+    }
+
+    fn match_resource(&self, resource: PermissionResource) -> bool {
+        self.resource == PERMISSION_ALL || self.resource == resource.to_str()
+    }
+
+    fn match_action(&self, action: PermissionAction) -> bool {
+        self.actions
+            .iter()
+            .any(|e| e == PERMISSION_ALL || e == action.to_str())
+    }
+
+    fn is_unrestricted(&self) -> bool {
+        is_any_pattern(&self.namespace)
+            && is_any_pattern(&self.group)
+            && is_any_pattern(&self.data_id)
+    }
+
+    /// partial为true时，未指定的范围视为匹配(列表查询的结果再按权限过滤)
+    pub fn match_scope(
+        &self,
+        namespace: Option<&str>,
+        group: Option<&str>,
+        data_id: Option<&str>,
+        partial: bool,
+    ) -> bool {
+        match_pattern(&self.namespace, namespace.map(normalize_namespace), partial)
+            && match_pattern(&self.group, group, partial)
+            && match_pattern(&self.data_id, data_id, partial)
+    }
+
+    pub fn is_match(&self, req: &PermissionRequest) -> bool {
+        if !self.match_resource(req.resource) || !self.match_action(req.action) {
+            return false;
+        }
+        let (namespace, group, data_id) = req.get_scope();
+        if req.wildcard_pattern {
+            return match_pattern(&self.namespace, namespace.map(normalize_namespace), false)
+                && match_wildcard_subset(&self.group, group)
+                && match_wildcard_subset(&self.data_id, data_id);
+        }
+        self.match_scope(
+            namespace,
+            group,
+            data_id,
+            req.mode == PermissionScopeMode::List,
+        )
+    }
+
+    /// 可访问命名空间下任意资源时，允许查看该命名空间
+    fn match_namespace_read(&self, req: &PermissionRequest) -> bool {
+        let (namespace, _, _) = req.get_scope();
+        match_pattern(
+            &self.namespace,
+            namespace.map(normalize_namespace),
+            req.mode == PermissionScopeMode::List,
+        )
+    }
+
+    /// 展开为具体的资源与操作
+    fn expand(&self) -> Vec<(PermissionResource, PermissionAction)> {
+        let mut list = vec![];
+        for resource in PermissionResource::ALL.iter() {
+            if !self.match_resource(*resource) {
+                continue;
+            }
+            for action in PermissionAction::ALL.iter() {
+                if self.match_action(*action) {
+                    list.push((*resource, *action));
+                }
+            }
+        }
+        list
+    }
+}
+
+fn is_any_pattern(pattern: &str) -> bool {
+    pattern.is_empty() || pattern == PERMISSION_ALL
+}
+
+fn match_pattern(pattern: &str, value: Option<&str>, partial: bool) -> bool {
+    if is_any_pattern(pattern) {
+        return true;
+    }
+    match value {
+        Some(value) => StringUtils::glob_match(pattern, value),
+        None => partial,
+    }
+}
+
+/// 请求值本身是通配模式(如模糊监听)时，需要保证模式匹配的范围是授权范围的子集：
+/// 带通配符的模式只允许授权范围为 前缀* 且模式以该前缀开头
+fn match_wildcard_subset(pattern: &str, value: Option<&str>) -> bool {
+    if is_any_pattern(pattern) {
+        return true;
+    }
+    let value = match value {
+        Some(v) => v,
+        None => return false,
+    };
+    let is_wildcard = |v: &str| v.contains(['*', '?']);
+    if !is_wildcard(value) {
+        return StringUtils::glob_match(pattern, value);
+    }
+    match pattern.strip_suffix('*') {
+        Some(prefix) if !is_wildcard(prefix) => value.starts_with(prefix),
+        _ => false,
+    }
+}
+
+/// 配置中默认命名空间为空字符串，服务中为public，授权统一按public匹配
+fn normalize_namespace(namespace: &str) -> &str {
+    if namespace.is_empty() {
+        PUBLIC_NAMESPACE
+    } else {
+        namespace
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionScopeMode {
+    /// 单个资源，未指定的命名空间按public、配置与服务未指定的分组按DEFAULT_GROUP处理
+    Item,
+    /// 列表查询，未指定的范围不限制，由查询结果按权限过滤
+    List,
+    /// 作用于整个命名空间的操作(如配置导入)，要求分组与dataId不受限
+    Namespace,
+}
+
+///
+/// 一次请求需要校验的权限
+#[derive(Debug, Clone)]
+pub struct PermissionRequest {
+    pub resource: PermissionResource,
+    pub action: PermissionAction,
+    pub mode: PermissionScopeMode,
+    pub namespace: Option<String>,
+    pub group: Option<String>,
+    pub data_id: Option<String>,
+    /// 参数别名取值冲突
+    pub conflict: bool,
+    /// group、dataId为通配模式(模糊监听)，需要被授权范围完全覆盖
+    pub wildcard_pattern: bool,
+}
+
+/// 资源范围参数名，同一范围内按优先级排列
+const SCOPE_PARAM_KEYS: [&str; 11] = [
+    "namespaceId",
+    "tenant",
+    "namespace",
+    "customNamespaceId",
+    "groupName",
+    "group",
+    "serviceName",
+    "dataId",
+    "name",
+    "toolName",
+    "username",
+];
+
+impl PermissionRequest {
+    pub fn new(
+        resource: PermissionResource,
+        action: PermissionAction,
+        mode: PermissionScopeMode,
+    ) -> Self {
+        Self {
+            resource,
+            action,
+            mode,
+            namespace: None,
+            group: None,
+            data_id: None,
+            conflict: false,
+            wildcard_pattern: false,
+        }
+    }
+
+    /// 从请求参数中识别资源范围，兼容控制台、openapi与gRPC的参数名；
+    /// 同一范围的多个别名取值不一致时标记为冲突，校验不通过
+    pub fn set_param(&mut self, key: &str, value: &str) {
+        if value.is_empty() {
+            return;
+        }
+        match key {
+            "tenant" | "namespaceId" | "namespace" | "customNamespaceId" => {
+                Self::set_scope_value(&mut self.namespace, value, &mut self.conflict);
+            }
+            "group" | "groupName" => {
+                Self::set_scope_value(&mut self.group, value, &mut self.conflict);
+            }
+            "serviceName" => {
+                // 兼容 group@@service 格式的服务名
+                if let Some((group, service)) = value.split_once("@@") {
+                    Self::set_scope_value(&mut self.group, group, &mut self.conflict);
+                    Self::set_scope_value(&mut self.data_id, service, &mut self.conflict);
+                } else {
+                    Self::set_scope_value(&mut self.data_id, value, &mut self.conflict);
+                }
+            }
+            "dataId" | "name" | "toolName" | "username" => {
+                Self::set_scope_value(&mut self.data_id, value, &mut self.conflict);
+            }
+            _ => {}
+        }
+    }
+
+    fn set_scope_value(slot: &mut Option<String>, value: &str, conflict: &mut bool) {
+        match slot {
+            Some(v) => {
+                if v != value {
+                    *conflict = true;
+                }
+            }
+            None => *slot = Some(value.to_owned()),
+        }
+    }
+
+    /// 按固定的别名优先级读取参数，与接口处理逻辑保持一致(如namespaceId优先于tenant)
+    pub fn set_params(&mut self, params: &HashMap<String, String>) {
+        for key in SCOPE_PARAM_KEYS.iter() {
+            if let Some(value) = params.get(*key) {
+                self.set_param(key, value);
+            }
+        }
+    }
+
+    pub fn set_json_params(&mut self, params: &serde_json::Map<String, serde_json::Value>) {
+        for key in SCOPE_PARAM_KEYS.iter() {
+            if let Some(value) = params.get(*key).and_then(|v| v.as_str()) {
+                self.set_param(key, value);
+            }
+        }
+    }
+
+    fn get_scope(&self) -> (Option<&str>, Option<&str>, Option<&str>) {
+        let namespace = self.namespace.as_deref();
+        let group = self.group.as_deref();
+        let data_id = self.data_id.as_deref();
+        match self.mode {
+            PermissionScopeMode::List => (namespace, group, data_id),
+            PermissionScopeMode::Namespace => {
+                (Some(namespace.unwrap_or(PUBLIC_NAMESPACE)), None, None)
+            }
+            PermissionScopeMode::Item => {
+                let group = match self.resource {
+                    PermissionResource::Config | PermissionResource::Service => {
+                        Some(group.unwrap_or(DEFAULT_GROUP))
+                    }
+                    _ => group,
+                };
+                (Some(namespace.unwrap_or(PUBLIC_NAMESPACE)), group, data_id)
+            }
+        }
+    }
+
+    ///
+    /// 从http请求的query、请求体及tenant/namespace请求头(文件导入)中读取资源范围
+    pub async fn fill_from_request(&mut self, request: &mut ServiceRequest) {
+        let content_type = get_content_type(request.headers());
+        let body = peek_request_body(request, &content_type).await;
+        self.set_params(&parse_params(request.query_string(), &content_type, &body));
+        for key in ["tenant", "namespace"].iter() {
+            if let Some(v) = request.headers().get(*key).and_then(|v| v.to_str().ok()) {
+                self.set_param(key, v);
+            }
+        }
+    }
+
+    pub fn from_console_path(path: &str, method: &str) -> Option<Self> {
+        ResourceRoute::match_route(&CONSOLE_RESOURCE_ROUTES, path, method)
+    }
+
+    pub fn from_openapi_path(path: &str, method: &str) -> Option<Self> {
+        ResourceRoute::match_route(&OPENAPI_RESOURCE_ROUTES, path, method)
+    }
+}
+
+///
+/// 请求路径与资源权限的对应关系
+struct ResourceRoute {
+    path: &'static str,
+    resource: PermissionResource,
+    /// 为None时按http方法区分读写删
+    action: Option<PermissionAction>,
+    mode: PermissionScopeMode,
+}
+
+impl ResourceRoute {
+    fn new(
+        path: &'static str,
+        resource: PermissionResource,
+        action: PermissionAction,
+        mode: PermissionScopeMode,
+    ) -> Self {
+        Self {
+            path,
+            resource,
+            action: Some(action),
+            mode,
+        }
+    }
+
+    /// restful接口，mode只作用于查询
+    fn rest(path: &'static str, resource: PermissionResource, mode: PermissionScopeMode) -> Self {
+        Self {
+            path,
+            resource,
+            action: None,
+            mode,
+        }
+    }
+
+    fn match_route(routes: &[Self], path: &str, method: &str) -> Option<PermissionRequest> {
+        let route = routes.iter().find(|e| e.path == path)?;
+        let (action, mode) = match route.action {
+            Some(action) => (action, route.mode),
+            None => {
+                let action = PermissionAction::from_method(method);
+                if action == PermissionAction::Read {
+                    (action, route.mode)
+                } else {
+                    (action, PermissionScopeMode::Item)
+                }
+            }
+        };
+        Some(PermissionRequest::new(route.resource, action, mode))
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref CONSOLE_RESOURCE_ROUTES: Vec<ResourceRoute> = {
+        use PermissionAction::*;
+        use PermissionResource::*;
+        use PermissionScopeMode::{Item, List, Namespace as NamespaceScope};
+        type RR = ResourceRoute;
+        vec![
+            RR::new("/rnacos/api/console/v2/config/list", Config, Read, List),
+            RR::new("/rnacos/api/console/v2/config/listener/list", Config, Read, List),
+            RR::new("/rnacos/api/console/v2/config/download", Config, Read, List),
+            RR::new("/rnacos/api/console/v2/config/info", Config, Read, Item),
+            RR::new("/rnacos/api/console/v2/config/history", Config, Read, Item),
+            RR::new("/rnacos/api/console/v2/config/history/diff", Config, Read, Item),
+            RR::new("/rnacos/api/console/v2/config/gray/info", Config, Read, Item),
+            RR::new("/rnacos/api/console/v2/config/add", Config, Write, Item),
+            RR::new("/rnacos/api/console/v2/config/update", Config, Write, Item),
+            RR::new("/rnacos/api/console/v2/config/history/rollback", Config, Write, Item),
+            RR::new("/rnacos/api/console/v2/config/import", Config, Write, NamespaceScope),
+            RR::new("/rnacos/api/console/v2/config/remove", Config, Delete, Item),
+            RR::new("/rnacos/api/console/v2/config/gray/publish", Config, Publish, Item),
+            RR::new("/rnacos/api/console/v2/config/gray/promote", Config, Publish, Item),
+            RR::new("/rnacos/api/console/v2/config/gray/stop", Config, Publish, Item),
+            RR::new("/rnacos/api/console/v2/service/list", Service, Read, List),
+            RR::new("/rnacos/api/console/v2/service/subscriber/list", Service, Read, Item),
+            RR::new("/rnacos/api/console/v2/service/add", Service, Write, Item),
+            RR::new("/rnacos/api/console/v2/service/update", Service, Write, Item),
+            RR::new("/rnacos/api/console/v2/service/remove", Service, Delete, Item),
+            RR::new("/rnacos/api/console/v2/instance/list", Service, Read, Item),
+            RR::new("/rnacos/api/console/v2/instance/info", Service, Read, Item),
+            RR::new("/rnacos/api/console/v2/instance/add", Service, Write, Item),
+            RR::new("/rnacos/api/console/v2/instance/update", Service, Write, Item),
+            RR::new("/rnacos/api/console/v2/instance/remove", Service, Delete, Item),
+            RR::new("/rnacos/api/console/v2/namespaces/list", Namespace, Read, List),
+            RR::new("/rnacos/api/console/v2/namespaces/add", Namespace, Write, Item),
+            RR::new("/rnacos/api/console/v2/namespaces/update", Namespace, Write, Item),
+            RR::new("/rnacos/api/console/v2/namespaces/remove", Namespace, Delete, Item),
+            RR::new("/rnacos/api/console/v2/user/list", User, Read, List),
+            RR::new("/rnacos/api/console/v2/user/add", User, Write, Item),
+            RR::new("/rnacos/api/console/v2/user/update", User, Write, Item),
+            RR::new("/rnacos/api/console/v2/user/remove", User, Delete, Item),
+            RR::new("/rnacos/api/console/v2/mcp/toolspec/list", Mcp, Read, List),
+            RR::new("/rnacos/api/console/v2/mcp/toolspec/info", Mcp, Read, Item),
+            RR::new("/rnacos/api/console/v2/mcp/toolspec/download", Mcp, Read, List),
+            RR::new("/rnacos/api/console/v2/mcp/toolspec/add", Mcp, Write, Item),
+            RR::new("/rnacos/api/console/v2/mcp/toolspec/update", Mcp, Write, Item),
+            RR::new("/rnacos/api/console/v2/mcp/toolspec/batch_update", Mcp, Write, NamespaceScope),
+            RR::new("/rnacos/api/console/v2/mcp/toolspec/import", Mcp, Write, NamespaceScope),
+            RR::new("/rnacos/api/console/v2/mcp/toolspec/remove", Mcp, Delete, Item),
+            // mcp服务按id操作，只校验命名空间
+            RR::new("/rnacos/api/console/v2/mcp/server/list", Mcp, Read, List),
+            RR::new("/rnacos/api/console/v2/mcp/server/info", Mcp, Read, NamespaceScope),
+            RR::new("/rnacos/api/console/v2/mcp/server/history", Mcp, Read, NamespaceScope),
+            RR::new("/rnacos/api/console/v2/mcp/server/download", Mcp, Read, List),
+            RR::new("/rnacos/api/console/v2/mcp/server/add", Mcp, Write, Item),
+            RR::new("/rnacos/api/console/v2/mcp/server/update", Mcp, Write, Item),
+            RR::new("/rnacos/api/console/v2/mcp/server/import", Mcp, Write, NamespaceScope),
+            RR::new("/rnacos/api/console/v2/mcp/server/remove", Mcp, Delete, NamespaceScope),
+            RR::new("/rnacos/api/console/v2/mcp/server/publish", Mcp, Publish, NamespaceScope),
+            RR::new("/rnacos/api/console/v2/mcp/server/publish/history", Mcp, Publish, NamespaceScope),
+            // 旧版控制台接口
+            RR::new("/rnacos/api/console/configs", Config, Read, List),
+            // POST按key导出时请求体为数组，无法在此识别范围，由接口逐个key校验
+            RR::new("/rnacos/api/console/config/download", Config, Read, List),
+            RR::new("/rnacos/api/console/config/history", Config, Read, Item),
+            RR::new("/rnacos/api/console/config/import", Config, Write, NamespaceScope),
+            RR::rest("/rnacos/api/console/cs/configs", Config, Item),
+            RR::new("/rnacos/api/console/ns/services", Service, Read, List),
+            RR::rest("/rnacos/api/console/ns/service", Service, Item),
+            RR::new("/rnacos/api/console/ns/service/subscribers", Service, Read, Item),
+            RR::new("/rnacos/api/console/instances", Service, Read, Item),
+            RR::rest("/rnacos/api/console/ns/instance", Service, Item),
+            RR::rest("/rnacos/api/console/namespaces", Namespace, List),
+            RR::new("/rnacos/api/console/user/list", User, Read, List),
+            RR::new("/rnacos/api/console/user/add", User, Write, Item),
+            RR::new("/rnacos/api/console/user/update", User, Write, Item),
+            RR::new("/rnacos/api/console/user/remove", User, Delete, Item),
+        ]
+    };
+
+    static ref OPENAPI_RESOURCE_ROUTES: Vec<ResourceRoute> = {
+        use PermissionAction::*;
+        use PermissionResource::*;
+        use PermissionScopeMode::{Item, List, Namespace as NamespaceScope};
+        type RR = ResourceRoute;
+        vec![
+            RR::rest("/nacos/v1/cs/configs", Config, Item),
+            RR::new("/nacos/v1/cs/configs/listener", Config, Read, List),
+            RR::new("/nacos/v1/cs/listener", Config, Read, List),
+            RR::rest("/nacos/v2/cs/config", Config, Item),
+            RR::new("/nacos/v2/cs/history", Config, Read, Item),
+            RR::new("/nacos/v2/cs/history/list", Config, Read, Item),
+            RR::new("/nacos/v2/cs/history/previous", Config, Read, Item),
+            RR::new("/nacos/v2/cs/history/configs", Config, Read, NamespaceScope),
+            RR::rest("/nacos/v1/ns/instance", Service, Item),
+            RR::new("/nacos/v1/ns/instance/beat", Service, Write, Item),
+            RR::new("/nacos/v1/ns/instance/list", Service, Read, Item),
+            RR::rest("/nacos/v1/ns/service", Service, Item),
+            RR::new("/nacos/v1/ns/service/list", Service, Read, List),
+            RR::new("/nacos/v1/ns/service/subscribers", Service, Read, Item),
+            RR::new("/nacos/v1/ns/catalog/services", Service, Read, List),
+            RR::new("/nacos/v1/ns/catalog/instances", Service, Read, Item),
+            RR::rest("/nacos/v2/ns/instance", Service, Item),
+            RR::new("/nacos/v2/ns/instance/list", Service, Read, Item),
+            RR::new("/nacos/v2/ns/instance/metadata/batch", Service, Write, Item),
+            RR::rest("/nacos/v2/ns/service", Service, Item),
+            RR::new("/nacos/v2/ns/service/list", Service, Read, List),
+            RR::new("/nacos/v2/ns/client/list", Service, Read, List),
+            RR::new("/nacos/v2/ns/client/publish/list", Service, Read, List),
+            RR::new("/nacos/v2/ns/client/subscribe/list", Service, Read, List),
+            RR::rest("/nacos/v1/console/namespaces", Namespace, List),
+            RR::new("/nacos/v2/console/namespace/list", Namespace, Read, List),
+            RR::rest("/nacos/v2/console/namespace", Namespace, Item),
+            RR::new("/rnacos/v1/mcp/server/list", Mcp, Read, NamespaceScope),
+            RR::new("/rnacos/v1/mcp/toolspec/list", Mcp, Read, NamespaceScope),
+        ]
+    };
+
+    static ref P_MANAGER: Vec<RolePermission> =
+        vec![RolePermission::new(PERMISSION_ALL, &[PERMISSION_ALL])];
+
+    static ref P_DEVELOPER: Vec<RolePermission> = vec![
+        RolePermission::new("config", &[PERMISSION_ALL]),
+        RolePermission::new("service", &[PERMISSION_ALL]),
+        RolePermission::new("namespace", &[PERMISSION_ALL]),
+        RolePermission::new("mcp", &[PERMISSION_ALL]),
+    ];
+
+    static ref P_VISITOR: Vec<RolePermission> = vec![
+        RolePermission::new("config", &["read"]),
+        RolePermission::new("service", &["read"]),
+        RolePermission::new("namespace", &["read"]),
+        RolePermission::new("mcp", &["read"]),
+    ];
+}
+
+impl UserRole {
+    /// 内置角色对应的资源权限
+    pub fn get_permissions(&self) -> &'static [RolePermission] {
+        match self {
+            UserRole::Visitor => &P_VISITOR,
+            UserRole::Developer | UserRole::OldConsole => &P_DEVELOPER,
+            UserRole::Manager => &P_MANAGER,
+            UserRole::None => &[],
+        }
+    }
+}
+
+///
+/// 用户的权限集合，由内置角色与自定义角色(含用户组绑定的角色)合并而成
+#[derive(Default)]
+pub struct UserPermission {
+    builtin_roles: Vec<Arc<String>>,
+    /// 自定义角色可访问的控制台模块
+    modules: Vec<&'static ModuleResource>,
+    permissions: Vec<RolePermission>,
+}
+
+impl UserPermission {
+    pub fn new(roles: &[Arc<String>], custom_permissions: Vec<RolePermission>) -> Self {
+        let builtin_roles: Vec<Arc<String>> = roles
+            .iter()
+            .filter(|e| UserRoleHelper::is_builtin_role(e))
+            .cloned()
+            .collect();
+        let mut permissions = vec![];
+        for role in &builtin_roles {
+            permissions.extend_from_slice(UserRole::new(role).get_permissions());
+        }
+        let mut modules: Vec<&'static ModuleResource> = vec![];
+        for permission in &custom_permissions {
+            for (resource, action) in permission.expand() {
+                for module in resource.get_modules(action) {
+                    if !modules.iter().any(|e| std::ptr::eq(*e, module)) {
+                        modules.push(module);
+                    }
+                }
+            }
+        }
+        if !modules.is_empty() {
+            modules.insert(0, &*M_BASE);
+        }
+        permissions.extend(custom_permissions);
+        Self {
+            builtin_roles,
+            modules,
+            permissions,
+        }
+    }
+
+    pub fn match_url(&self, path: &str, method: &str) -> bool {
+        UserRole::match_url_by_roles(&self.builtin_roles, path, method)
+            || self.modules.iter().any(|e| e.match_url(path, method))
+    }
+
+    pub fn get_web_resources(&self) -> Vec<&'static str> {
+        let mut set: HashSet<&'static str> = UserRole::get_web_resources_by_roles(
+            self.builtin_roles.iter().map(|e| e.as_str()).collect(),
+        )
+        .into_iter()
+        .collect();
+        for module in &self.modules {
+            set.extend(module.web_resources.iter().copied());
+        }
+        set.into_iter().collect()
+    }
+
+    pub fn check(&self, req: &PermissionRequest) -> bool {
+        match_permissions(&self.permissions, req)
+    }
+
+    ///
+    /// 访问密钥在用户权限之上还需满足密钥限定的授权范围
+    pub fn check_with_scopes(&self, req: &PermissionRequest, scopes: &[RolePermission]) -> bool {
+        self.check(req) && (scopes.is_empty() || match_permissions(scopes, req))
+    }
+
+    ///
+    /// 列表查询的数据范围过滤，拥有不受限的授权时返回None
+    pub fn build_scope_filter(
+        &self,
+        resource: PermissionResource,
+        action: PermissionAction,
+    ) -> Option<Arc<PermissionScopeFilter>> {
+        let permissions: Vec<RolePermission> =
+            if resource == PermissionResource::Namespace && action == PermissionAction::Read {
+                self.permissions
+                    .iter()
+                    .map(|e| RolePermission {
+                        namespace: e.namespace.clone(),
+                        ..Default::default()
+                    })
+                    .collect()
+            } else {
+                self.permissions
+                    .iter()
+                    .filter(|e| e.match_resource(resource) && e.match_action(action))
+                    .cloned()
+                    .collect()
+            };
+        if permissions.iter().any(|e| e.is_unrestricted()) {
+            None
+        } else {
+            Some(Arc::new(PermissionScopeFilter { permissions }))
+        }
+    }
+}
+
+fn match_permissions(permissions: &[RolePermission], req: &PermissionRequest) -> bool {
+    let namespace_read =
+        req.resource == PermissionResource::Namespace && req.action == PermissionAction::Read;
+    !req.conflict
+        && permissions
+            .iter()
+            .any(|e| e.is_match(req) || (namespace_read && e.match_namespace_read(req)))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PermissionScopeFilter {
+    permissions: Vec<RolePermission>,
+}
+
+impl PermissionScopeFilter {
+    pub fn is_match(&self, namespace: &str, group: &str, data_id: &str) -> bool {
+        self.permissions
+            .iter()
+            .any(|e| e.match_scope(Some(namespace), Some(group), Some(data_id), false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_permission_scope() {
+        let permission = RolePermission {
+            resource: "config".to_owned(),
+            actions: vec!["read".to_owned(), "write".to_owned()],
+            namespace: "dev*".to_owned(),
+            group: "APP_GROUP".to_owned(),
+            data_id: "app-*.yaml".to_owned(),
+        };
+        let user_permission =
+            UserPermission::new(&[Arc::new("custom".to_owned())], vec![permission]);
+        let mut req = PermissionRequest::new(
+            PermissionResource::Config,
+            PermissionAction::Write,
+            PermissionScopeMode::Item,
+        );
+        req.set_param("tenant", "dev-1");
+        req.set_param("group", "APP_GROUP");
+        req.set_param("dataId", "app-a.yaml");
+        assert!(user_permission.check(&req));
+        req.action = PermissionAction::Delete;
+        assert!(!user_permission.check(&req));
+        req.action = PermissionAction::Read;
+        req.data_id = Some("other.yaml".to_owned());
+        assert!(!user_permission.check(&req));
+
+        let mut list_req = PermissionRequest::from_console_path(
+            "/rnacos/api/console/v2/config/list",
+            HTTP_METHOD_GET,
+        )
+        .unwrap();
+        list_req.set_param("tenant", "dev-1");
+        assert!(user_permission.check(&list_req));
+        list_req.namespace = Some("prod".to_owned());
+        assert!(!user_permission.check(&list_req));
+
+        let filter = user_permission
+            .build_scope_filter(PermissionResource::Config, PermissionAction::Read)
+            .unwrap();
+        assert!(filter.is_match("dev", "APP_GROUP", "app-b.yaml"));
+        assert!(!filter.is_match("dev", "DEFAULT_GROUP", "app-b.yaml"));
+        assert!(user_permission.match_url("/rnacos/api/console/v2/config/update", "POST"));
+        assert!(!user_permission.match_url("/rnacos/api/console/v2/user/list", "GET"));
+    }
+
+    #[test]
+    fn conflicting_param_alias() {
+        let permission = RolePermission {
+            resource: "config".to_owned(),
+            actions: vec!["read".to_owned()],
+            namespace: "dev".to_owned(),
+            group: "*".to_owned(),
+            data_id: "*".to_owned(),
+        };
+        let user_permission =
+            UserPermission::new(&[Arc::new("custom".to_owned())], vec![permission]);
+        let mut params = HashMap::new();
+        params.insert("namespaceId".to_owned(), "dev".to_owned());
+        params.insert("tenant".to_owned(), "dev".to_owned());
+        params.insert("dataId".to_owned(), "app.yaml".to_owned());
+        let mut req = PermissionRequest::new(
+            PermissionResource::Config,
+            PermissionAction::Read,
+            PermissionScopeMode::Item,
+        );
+        req.set_params(&params);
+        assert!(!req.conflict);
+        assert!(user_permission.check(&req));
+
+        params.insert("tenant".to_owned(), "prod".to_owned());
+        let mut req = PermissionRequest::new(
+            PermissionResource::Config,
+            PermissionAction::Read,
+            PermissionScopeMode::Item,
+        );
+        req.set_params(&params);
+        assert!(req.conflict);
+        assert_eq!(req.namespace.as_deref(), Some("dev"));
+        assert!(!user_permission.check(&req));
+
+        let mut req =
+            PermissionRequest::from_openapi_path("/nacos/v1/ns/instance", "POST").unwrap();
+        req.set_param("groupName", "DEFAULT_GROUP");
+        req.set_param("serviceName", "OTHER_GROUP@@foo");
+        assert!(req.conflict);
+    }
+
+    #[test]
+    fn wildcard_pattern_subset() {
+        let build_user = |group: &str, data_id: &str| {
+            let permission = RolePermission {
+                resource: "config".to_owned(),
+                actions: vec!["read".to_owned()],
+                namespace: "dev".to_owned(),
+                group: group.to_owned(),
+                data_id: data_id.to_owned(),
+            };
+            UserPermission::new(&[Arc::new("custom".to_owned())], vec![permission])
+        };
+        let build_req = |group: &str, data_id: &str| {
+            let mut req = PermissionRequest::new(
+                PermissionResource::Config,
+                PermissionAction::Read,
+                PermissionScopeMode::Item,
+            );
+            req.wildcard_pattern = true;
+            req.set_param("namespace", "dev");
+            req.set_param("group", group);
+            req.set_param("dataId", data_id);
+            req
+        };
+        let user = build_user("APP_GROUP", "app-*");
+        assert!(user.check(&build_req("APP_GROUP", "app-*")));
+        assert!(user.check(&build_req("APP_GROUP", "app-order-*")));
+        assert!(user.check(&build_req("APP_GROUP", "app-a.yaml")));
+        assert!(!user.check(&build_req("APP_GROUP", "*")));
+        assert!(!user.check(&build_req("APP_GROUP", "ap*")));
+        assert!(!user.check(&build_req("APP_*", "app-*")));
+        //授权范围不是 前缀* 时不允许通配模式
+        let user = build_user("APP_GROUP", "app-?");
+        assert!(!user.check(&build_req("APP_GROUP", "app-*")));
+        assert!(user.check(&build_req("APP_GROUP", "app-a")));
+        let user = build_user("*", "*-prod");
+        assert!(!user.check(&build_req("DEFAULT_GROUP", "*-prod")));
+        assert!(user.check(&build_req("*", "order-prod")));
+    }
+
+    #[test]
+    fn builtin_role_permission() {
+        let visitor = UserPermission::new(std::slice::from_ref(&USER_ROLE_VISITOR), vec![]);
+        let mut req =
+            PermissionRequest::from_openapi_path("/nacos/v1/ns/instance", "POST").unwrap();
+        req.set_param("serviceName", "DEFAULT_GROUP@@foo");
+        assert!(!visitor.check(&req));
+        let developer = UserPermission::new(std::slice::from_ref(&USER_ROLE_DEVELOPER), vec![]);
+        assert!(developer.check(&req));
+        assert!(developer
+            .build_scope_filter(PermissionResource::Service, PermissionAction::Read)
+            .is_none());
+        assert!(!developer.check(&PermissionRequest::new(
+            PermissionResource::User,
+            PermissionAction::Read,
+            PermissionScopeMode::List
+        )));
+        assert!(StringUtils::glob_match("a?c*", "abcdef"));
+        assert!(!StringUtils::glob_match("a?c", "abcd"));
+    }
 }
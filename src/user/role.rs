@@ -0,0 +1,264 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use actix::prelude::*;
+use bean_factory::{bean, BeanFactory, FactoryData, Inject};
+
+use crate::common::constant::ROLE_TABLE_NAME;
+use crate::now_millis_i64;
+use crate::raft::db::route::TableRoute;
+use crate::raft::db::table::{
+    TableManager, TableManagerQueryReq, TableManagerReq, TableManagerResult,
+};
+use crate::user::model::{RoleDo, RoleParam};
+use crate::user::permission::{RolePermission, UserPermission, UserRoleHelper};
+
+///
+/// 自定义角色管理
+/// 角色数据通过raft表同步，各节点在内存中维护角色与用户组索引，用于计算用户权限
+#[bean(inject)]
+#[derive(Default)]
+pub struct RoleManager {
+    roles: BTreeMap<String, Arc<RoleDo>>,
+    /// 用户组绑定的角色
+    group_roles: HashMap<String, Vec<String>>,
+    ready: bool,
+    /// 注入完成前收到数据加载完成通知，注入后再加载
+    wait_load: bool,
+    table_route: Option<Arc<TableRoute>>,
+    table_manager: Option<Addr<TableManager>>,
+}
+
+impl RoleManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn update_role(&mut self, value: &[u8]) {
+        match RoleDo::from_bytes(value) {
+            Ok(role) => {
+                self.roles.insert(role.name.clone(), Arc::new(role));
+                self.rebuild_group_index();
+            }
+            Err(err) => log::warn!("decode role error,{}", err),
+        }
+    }
+
+    fn rebuild_group_index(&mut self) {
+        self.group_roles.clear();
+        for role in self.roles.values() {
+            for group in &role.groups {
+                self.group_roles
+                    .entry(group.to_owned())
+                    .or_default()
+                    .push(role.name.clone());
+            }
+        }
+    }
+
+    fn load_roles(&mut self, ctx: &mut Context<Self>) {
+        let table_manager = if let Some(table_manager) = self.table_manager.clone() {
+            table_manager
+        } else {
+            self.wait_load = true;
+            return;
+        };
+        self.wait_load = false;
+        let req = TableManagerQueryReq::QueryPageList {
+            table_name: ROLE_TABLE_NAME.clone(),
+            like_key: None,
+            offset: None,
+            limit: None,
+            is_rev: false,
+        };
+        async move { table_manager.send(req).await }
+            .into_actor(self)
+            .map(|r, act, _ctx| {
+                if let Ok(Ok(TableManagerResult::PageListResult(_, list))) = r {
+                    act.roles.clear();
+                    for (_, value) in list {
+                        if let Ok(role) = RoleDo::from_bytes(&value) {
+                            act.roles.insert(role.name.clone(), Arc::new(role));
+                        }
+                    }
+                    act.rebuild_group_index();
+                }
+                act.ready = true;
+                log::info!("RoleManager load complete, size:{}", act.roles.len());
+            })
+            .wait(ctx);
+    }
+
+    fn build_user_permission(
+        &self,
+        roles: &[Arc<String>],
+        groups: &[Arc<String>],
+    ) -> UserPermission {
+        let mut role_names: Vec<&str> = roles
+            .iter()
+            .filter(|e| !UserRoleHelper::is_builtin_role(e))
+            .map(|e| e.as_str())
+            .collect();
+        for group in groups {
+            if let Some(names) = self.group_roles.get(group.as_str()) {
+                role_names.extend(names.iter().map(|e| e.as_str()));
+            }
+        }
+        role_names.sort_unstable();
+        role_names.dedup();
+        let mut permissions: Vec<RolePermission> = vec![];
+        for name in role_names {
+            if let Some(role) = self.roles.get(name) {
+                permissions.extend(role.permissions.iter().cloned());
+            }
+        }
+        UserPermission::new(roles, permissions)
+    }
+
+    fn set_role(&self, param: RoleParam, is_add: bool) -> anyhow::Result<TableManagerReq> {
+        param.check_valid()?;
+        let old = self.roles.get(&param.name);
+        match (is_add, old.is_some()) {
+            (true, true) => return Err(anyhow::anyhow!("role already exists: {}", &param.name)),
+            (false, false) => return Err(anyhow::anyhow!("role not found: {}", &param.name)),
+            _ => {}
+        }
+        let key = param.name.as_bytes().to_vec();
+        let value = param.build_do(old.map(|v| v.as_ref()), now_millis_i64());
+        Ok(TableManagerReq::Set {
+            table_name: ROLE_TABLE_NAME.clone(),
+            key,
+            value: value.to_bytes(),
+            last_seq_id: None,
+        })
+    }
+}
+
+///
+/// 计算用户权限；只有内置角色且没有用户组时直接在本地计算
+pub async fn get_user_permission(
+    role_manager: &Addr<RoleManager>,
+    roles: Vec<Arc<String>>,
+    groups: Vec<Arc<String>>,
+) -> anyhow::Result<Arc<UserPermission>> {
+    if groups.is_empty() && roles.iter().all(|e| UserRoleHelper::is_builtin_role(e)) {
+        return Ok(Arc::new(UserPermission::new(&roles, vec![])));
+    }
+    match role_manager
+        .send(RoleManagerReq::GetUserPermission { roles, groups })
+        .await??
+    {
+        RoleManagerResult::Permission(v) => Ok(v),
+        _ => Err(anyhow::anyhow!("RoleManagerResult is error")),
+    }
+}
+
+impl Actor for RoleManager {
+    type Context = Context<Self>;
+
+    fn started(&mut self, _ctx: &mut Self::Context) {
+        log::info!("RoleManager actor started")
+    }
+}
+
+impl Inject for RoleManager {
+    type Context = Context<Self>;
+
+    fn inject(
+        &mut self,
+        factory_data: FactoryData,
+        _factory: BeanFactory,
+        ctx: &mut Self::Context,
+    ) {
+        self.table_route = factory_data.get_bean();
+        self.table_manager = factory_data.get_actor();
+        if self.wait_load {
+            self.load_roles(ctx);
+        }
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub enum RoleCmd {
+    /// 角色表数据变更，由TableManager在raft apply时通知
+    NotifyChange {
+        key: Vec<u8>,
+        value: Vec<u8>,
+    },
+    NotifyRemove {
+        key: Vec<u8>,
+    },
+    LoadCompleted,
+}
+
+impl Handler<RoleCmd> for RoleManager {
+    type Result = ();
+
+    fn handle(&mut self, msg: RoleCmd, ctx: &mut Self::Context) -> Self::Result {
+        match msg {
+            RoleCmd::NotifyChange { key: _, value } => {
+                self.update_role(&value);
+            }
+            RoleCmd::NotifyRemove { key } => {
+                self.roles.remove(String::from_utf8_lossy(&key).as_ref());
+                self.rebuild_group_index();
+            }
+            RoleCmd::LoadCompleted => {
+                if !self.ready {
+                    self.load_roles(ctx);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "anyhow::Result<RoleManagerResult>")]
+pub enum RoleManagerReq {
+    QueryList,
+    Add(RoleParam),
+    Update(RoleParam),
+    Remove(String),
+    GetUserPermission {
+        roles: Vec<Arc<String>>,
+        groups: Vec<Arc<String>>,
+    },
+}
+
+pub enum RoleManagerResult {
+    None,
+    List(Vec<Arc<RoleDo>>),
+    Permission(Arc<UserPermission>),
+}
+
+impl Handler<RoleManagerReq> for RoleManager {
+    type Result = ResponseActFuture<Self, anyhow::Result<RoleManagerResult>>;
+
+    fn handle(&mut self, msg: RoleManagerReq, _ctx: &mut Self::Context) -> Self::Result {
+        let req = match msg {
+            RoleManagerReq::QueryList => {
+                let list = self.roles.values().cloned().collect();
+                return Box::pin(fut::ready(Ok(RoleManagerResult::List(list))));
+            }
+            RoleManagerReq::GetUserPermission { roles, groups } => {
+                let permission = Arc::new(self.build_user_permission(&roles, &groups));
+                return Box::pin(fut::ready(Ok(RoleManagerResult::Permission(permission))));
+            }
+            RoleManagerReq::Add(param) => self.set_role(param, true),
+            RoleManagerReq::Update(param) => self.set_role(param, false),
+            RoleManagerReq::Remove(name) => Ok(TableManagerReq::Remove {
+                table_name: ROLE_TABLE_NAME.clone(),
+                key: name.into_bytes(),
+            }),
+        };
+        let table_route = self.table_route.clone();
+        let fut = async move {
+            let table_route =
+                table_route.ok_or_else(|| anyhow::anyhow!("role table is not ready"))?;
+            table_route.request(req?).await?;
+            Ok(RoleManagerResult::None)
+        };
+        Box::pin(fut.into_actor(self).map(|r, _act, _ctx| r))
+    }
+}
@@ -0,0 +1,696 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use actix::prelude::*;
+use bean_factory::{bean, BeanFactory, FactoryData, Inject};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::common::constant::{WEBHOOK_DEAD_LETTER_TABLE_NAME, WEBHOOK_TABLE_NAME};
+use crate::common::AppSysConfig;
+use crate::config::core::ConfigKey;
+use crate::naming::core::{NamingActor, NamingCmd, NamingResult};
+use crate::naming::model::ServiceKey;
+use crate::now_millis_i64;
+use crate::raft::db::route::TableRoute;
+use crate::raft::db::table::{
+    TableManager, TableManagerInnerReq, TableManagerQueryReq, TableManagerReq, TableManagerResult,
+};
+use crate::raft::NacosRaft;
+use crate::webhook::model::{
+    WebhookDeadLetterDo, WebhookDeadLetterDto, WebhookDeadLetterQueryParam, WebhookDo, WebhookDto,
+    WebhookEvent, WebhookParam, EVENT_CONFIG_DELETE, EVENT_CONFIG_PUBLISH, EVENT_LEADER_CHANGE,
+    EVENT_SERVICE_CHANGE, EVENT_SERVICE_NO_HEALTHY,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 待推送队列上限，超出后丢弃新事件，避免接收端长时间不可用时内存持续增长
+const MAX_QUEUE_SIZE: usize = 10000;
+const MAX_CONCURRENT_SEND: usize = 16;
+const MAX_RETRY_INTERVAL_MILLIS: i64 = 300_000;
+
+pub const HEADER_EVENT: &str = "X-Rnacos-Event";
+pub const HEADER_EVENT_ID: &str = "X-Rnacos-Event-Id";
+pub const HEADER_TIMESTAMP: &str = "X-Rnacos-Timestamp";
+pub const HEADER_SIGNATURE: &str = "X-Rnacos-Signature";
+
+struct WebhookDelivery {
+    hook: Arc<WebhookDo>,
+    event_id: String,
+    event_type: String,
+    payload: Arc<String>,
+    attempts: u32,
+    next_time: i64,
+}
+
+///
+/// webhook事件推送管理
+/// 各节点都会收到数据变更，只有raft主节点产生推送，失败后按指数退避重试，超过重试次数写入死信表
+#[bean(inject)]
+pub struct WebhookManager {
+    enable: bool,
+    node_id: u64,
+    max_retry: u32,
+    /// raft数据加载完成前的变更为历史数据回放，不产生事件
+    ready: bool,
+    /// 注入完成前收到数据加载完成通知，注入后再加载
+    wait_load: bool,
+    hooks: BTreeMap<String, Arc<WebhookDo>>,
+    queue: VecDeque<WebhookDelivery>,
+    sending: usize,
+    dead_letter_seq: u64,
+    service_healthy_map: HashMap<ServiceKey, usize>,
+    last_leader: Option<u64>,
+    client: reqwest::Client,
+    raft: Option<Weak<NacosRaft>>,
+    naming_addr: Option<Addr<NamingActor>>,
+    table_route: Option<Arc<TableRoute>>,
+    table_manager: Option<Addr<TableManager>>,
+}
+
+impl WebhookManager {
+    pub fn new(sys_config: &AppSysConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(sys_config.webhook_timeout_millis))
+            .build()
+            .unwrap_or_default();
+        Self {
+            enable: sys_config.webhook_enable,
+            node_id: sys_config.raft_node_id,
+            max_retry: sys_config.webhook_max_retry,
+            ready: false,
+            wait_load: false,
+            hooks: BTreeMap::new(),
+            queue: VecDeque::new(),
+            sending: 0,
+            dead_letter_seq: 0,
+            service_healthy_map: HashMap::new(),
+            last_leader: None,
+            client,
+            raft: None,
+            naming_addr: None,
+            table_route: None,
+            table_manager: None,
+        }
+    }
+
+    fn get_raft(&self) -> Option<Arc<NacosRaft>> {
+        self.raft.as_ref().and_then(|e| e.upgrade())
+    }
+
+    fn is_leader(&self) -> bool {
+        self.get_raft()
+            .map(|raft| raft.metrics().borrow().current_leader == Some(self.node_id))
+            .unwrap_or(false)
+    }
+
+    fn can_emit(&self) -> bool {
+        self.enable && self.ready && !self.hooks.is_empty() && self.is_leader()
+    }
+
+    fn emit(&mut self, event: WebhookEvent) {
+        let payload = match serde_json::to_string(&event) {
+            Ok(v) => Arc::new(v),
+            Err(_) => return,
+        };
+        let now = now_millis_i64();
+        for hook in self.hooks.values() {
+            if !hook.match_event(&event) {
+                continue;
+            }
+            if self.queue.len() >= MAX_QUEUE_SIZE {
+                log::warn!(
+                    "webhook queue is full, ignore event|{}|{}|{}",
+                    &hook.name,
+                    &event.event_type,
+                    &event.event_id
+                );
+                continue;
+            }
+            self.queue.push_back(WebhookDelivery {
+                hook: hook.clone(),
+                event_id: event.event_id.clone(),
+                event_type: event.event_type.clone(),
+                payload: payload.clone(),
+                attempts: 0,
+                next_time: now,
+            });
+        }
+    }
+
+    fn config_changed(&mut self, key: ConfigKey, md5: Option<Arc<String>>, deleted: bool) {
+        if !self.can_emit() {
+            return;
+        }
+        let event_type = if deleted {
+            EVENT_CONFIG_DELETE
+        } else {
+            EVENT_CONFIG_PUBLISH
+        };
+        let mut event = WebhookEvent::new(event_type, self.node_id);
+        event.namespace = Some(key.tenant);
+        event.group = Some(key.group);
+        event.data_id = Some(key.data_id);
+        event.md5 = md5;
+        self.emit(event);
+    }
+
+    fn build_service_event(&self, event_type: &str, key: &ServiceKey) -> WebhookEvent {
+        let mut event = WebhookEvent::new(event_type, self.node_id);
+        event.namespace = Some(key.namespace_id.clone());
+        event.group = Some(key.group_name.clone());
+        event.service_name = Some(key.service_name.clone());
+        event
+    }
+
+    fn service_changed(&mut self, key: ServiceKey, ctx: &mut Context<Self>) {
+        if !self.can_emit() {
+            return;
+        }
+        let subscribed =
+            [EVENT_SERVICE_CHANGE, EVENT_SERVICE_NO_HEALTHY]
+                .iter()
+                .any(|event_type| {
+                    let event = self.build_service_event(event_type, &key);
+                    self.hooks.values().any(|hook| hook.match_event(&event))
+                });
+        let naming_addr = match (subscribed, self.naming_addr.clone()) {
+            (true, Some(naming_addr)) => naming_addr,
+            _ => return,
+        };
+        let cmd = NamingCmd::QueryServiceInfo(key.clone(), "".to_owned(), false);
+        async move { naming_addr.send(cmd).await }
+            .into_actor(self)
+            .map(move |r, act, _ctx| {
+                if let Ok(Ok(NamingResult::ServiceInfo(service_info))) = r {
+                    let hosts = service_info.hosts.unwrap_or_default();
+                    let healthy = hosts.iter().filter(|e| e.healthy && e.enabled).count();
+                    act.notify_service_instances(key, hosts.len(), healthy);
+                }
+            })
+            .spawn(ctx);
+    }
+
+    fn notify_service_instances(&mut self, key: ServiceKey, total: usize, healthy: usize) {
+        let previous = if total == 0 {
+            self.service_healthy_map.remove(&key)
+        } else {
+            self.service_healthy_map.insert(key.clone(), healthy)
+        };
+        let mut event = self.build_service_event(EVENT_SERVICE_CHANGE, &key);
+        event.instance_count = Some(total);
+        event.healthy_instance_count = Some(healthy);
+        let lost_healthy = healthy == 0
+            && match previous {
+                Some(v) => v > 0,
+                None => total > 0,
+            };
+        self.emit(event);
+        if lost_healthy {
+            let mut event = self.build_service_event(EVENT_SERVICE_NO_HEALTHY, &key);
+            event.instance_count = Some(total);
+            event.healthy_instance_count = Some(healthy);
+            self.emit(event);
+        }
+    }
+
+    fn check_leader(&mut self) {
+        let metrics = if let Some(raft) = self.get_raft() {
+            raft.metrics().borrow().clone()
+        } else {
+            return;
+        };
+        if metrics.current_leader == self.last_leader {
+            return;
+        }
+        let previous = self.last_leader;
+        self.last_leader = metrics.current_leader;
+        if metrics.current_leader == Some(self.node_id) && self.can_emit() {
+            let mut event = WebhookEvent::new(EVENT_LEADER_CHANGE, self.node_id);
+            event.leader_id = metrics.current_leader;
+            event.previous_leader_id = previous;
+            event.term = Some(metrics.current_term);
+            self.emit(event);
+        }
+    }
+
+    fn dispatch(&mut self, ctx: &mut Context<Self>) {
+        if self.queue.is_empty() {
+            return;
+        }
+        let now = now_millis_i64();
+        let mut waiting = VecDeque::with_capacity(self.queue.len());
+        while let Some(delivery) = self.queue.pop_front() {
+            if self.sending >= MAX_CONCURRENT_SEND || delivery.next_time > now {
+                waiting.push_back(delivery);
+                continue;
+            }
+            self.sending += 1;
+            let client = self.client.clone();
+            let hook = delivery.hook.clone();
+            let event_type = delivery.event_type.clone();
+            let event_id = delivery.event_id.clone();
+            let payload = delivery.payload.clone();
+            async move { Self::post(&client, &hook, &event_type, &event_id, payload).await }
+                .into_actor(self)
+                .map(move |r, act, ctx| {
+                    act.sending -= 1;
+                    if let Err(err) = r {
+                        act.on_send_error(delivery, err.to_string(), ctx);
+                    }
+                })
+                .spawn(ctx);
+        }
+        self.queue = waiting;
+    }
+
+    fn on_send_error(
+        &mut self,
+        mut delivery: WebhookDelivery,
+        error: String,
+        ctx: &mut Context<Self>,
+    ) {
+        delivery.attempts += 1;
+        log::warn!(
+            "webhook send error|{}|{}|{}|attempts:{}|{}",
+            &delivery.hook.name,
+            &delivery.event_type,
+            &delivery.event_id,
+            delivery.attempts,
+            &error
+        );
+        if delivery.attempts <= self.max_retry {
+            delivery.next_time = now_millis_i64() + Self::retry_interval(delivery.attempts);
+            self.queue.push_back(delivery);
+            return;
+        }
+        let mut record = WebhookDeadLetterDo {
+            timestamp: now_millis_i64(),
+            node_id: self.node_id,
+            webhook_id: delivery.hook.id.clone(),
+            webhook_name: delivery.hook.name.clone(),
+            url: delivery.hook.url.clone(),
+            event_id: delivery.event_id,
+            event_type: delivery.event_type,
+            payload: delivery.payload.as_ref().to_owned(),
+            attempts: delivery.attempts,
+            last_error: String::new(),
+        };
+        record.set_last_error(&error);
+        self.dead_letter_seq += 1;
+        let req = TableManagerReq::Set {
+            table_name: WEBHOOK_DEAD_LETTER_TABLE_NAME.clone(),
+            key: record.build_key(self.dead_letter_seq).into_bytes(),
+            value: record.to_bytes(),
+            last_seq_id: None,
+        };
+        if let Some(table_route) = self.table_route.clone() {
+            async move { table_route.request(req).await }
+                .into_actor(self)
+                .map(|r, _act, _ctx| {
+                    if let Err(err) = r {
+                        log::error!("write webhook dead letter error,{}", err);
+                    }
+                })
+                .spawn(ctx);
+        }
+    }
+
+    /// 重试间隔按1秒起指数增长，最大5分钟
+    fn retry_interval(attempts: u32) -> i64 {
+        let shift = attempts.saturating_sub(1).min(16);
+        (1000i64 << shift).min(MAX_RETRY_INTERVAL_MILLIS)
+    }
+
+    async fn post(
+        client: &reqwest::Client,
+        hook: &WebhookDo,
+        event_type: &str,
+        event_id: &str,
+        payload: Arc<String>,
+    ) -> anyhow::Result<()> {
+        let timestamp = now_millis_i64();
+        let mut request = client
+            .post(&hook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(HEADER_EVENT, event_type)
+            .header(HEADER_EVENT_ID, event_id)
+            .header(HEADER_TIMESTAMP, timestamp.to_string());
+        if !hook.secret.is_empty() {
+            let signature = sign(&hook.secret, timestamp, &payload);
+            request = request.header(HEADER_SIGNATURE, format!("sha256={}", signature));
+        }
+        let response = request.body(payload.as_ref().to_owned()).send().await?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("response status {}", response.status()))
+        }
+    }
+
+    fn update_hook(&mut self, key: &[u8], value: &[u8]) {
+        match WebhookDo::from_bytes(value) {
+            Ok(hook) => {
+                self.hooks
+                    .insert(String::from_utf8_lossy(key).to_string(), Arc::new(hook));
+            }
+            Err(err) => log::warn!("decode webhook error,{}", err),
+        }
+    }
+
+    fn load_hooks(&mut self, ctx: &mut Context<Self>) {
+        let table_manager = if let Some(table_manager) = self.table_manager.clone() {
+            table_manager
+        } else {
+            self.wait_load = true;
+            return;
+        };
+        self.wait_load = false;
+        let req = TableManagerQueryReq::QueryPageList {
+            table_name: WEBHOOK_TABLE_NAME.clone(),
+            like_key: None,
+            offset: None,
+            limit: None,
+            is_rev: false,
+        };
+        async move { table_manager.send(req).await }
+            .into_actor(self)
+            .map(|r, act, _ctx| {
+                if let Ok(Ok(TableManagerResult::PageListResult(_, list))) = r {
+                    act.hooks.clear();
+                    for (key, value) in list {
+                        act.update_hook(&key, &value);
+                    }
+                }
+                act.ready = true;
+                log::info!("WebhookManager load complete, size:{}", act.hooks.len());
+            })
+            .wait(ctx);
+    }
+
+    fn heartbeat(&mut self, ctx: &mut Context<Self>) {
+        ctx.run_later(Duration::from_millis(500), |act, ctx| {
+            act.check_leader();
+            act.dispatch(ctx);
+            act.heartbeat(ctx);
+        });
+    }
+
+    fn set_webhook(&self, param: WebhookParam) -> anyhow::Result<(String, TableManagerReq)> {
+        param.check_valid()?;
+        let (id, old) = match param.id.as_ref().filter(|v| !v.is_empty()) {
+            Some(id) => match self.hooks.get(id) {
+                Some(old) => (id.to_owned(), Some(old.clone())),
+                None => return Err(anyhow::anyhow!("webhook not found: {}", id)),
+            },
+            None => (uuid::Uuid::new_v4().to_string().replace('-', ""), None),
+        };
+        let value = param.build_do(old.as_deref(), id.clone(), now_millis_i64());
+        let req = TableManagerReq::Set {
+            table_name: WEBHOOK_TABLE_NAME.clone(),
+            key: id.as_bytes().to_vec(),
+            value: value.to_bytes(),
+            last_seq_id: None,
+        };
+        Ok((id, req))
+    }
+
+    async fn query_dead_letter(
+        table_manager: Addr<TableManager>,
+        param: WebhookDeadLetterQueryParam,
+    ) -> anyhow::Result<WebhookResult> {
+        let offset = param.offset;
+        let limit = param.limit;
+        let filter = Box::new(move |_key: &[u8], value: &[u8]| {
+            WebhookDeadLetterDo::from_bytes(value)
+                .map(|record| param.match_record(&record))
+                .unwrap_or(false)
+        });
+        let req = TableManagerInnerReq::QueryFilterPageList {
+            table_name: WEBHOOK_DEAD_LETTER_TABLE_NAME.clone(),
+            filter,
+            offset,
+            limit,
+            is_rev: true,
+        };
+        if let TableManagerResult::PageListResult(total, list) = table_manager.send(req).await?? {
+            let list = list
+                .into_iter()
+                .filter_map(|(key, value)| {
+                    WebhookDeadLetterDo::from_bytes(&value).ok().map(|record| {
+                        WebhookDeadLetterDto {
+                            id: String::from_utf8_lossy(&key).to_string(),
+                            record,
+                        }
+                    })
+                })
+                .collect();
+            Ok(WebhookResult::DeadLetterPage(total, list))
+        } else {
+            Ok(WebhookResult::DeadLetterPage(0, vec![]))
+        }
+    }
+
+    async fn get_dead_letter(
+        table_manager: &Addr<TableManager>,
+        id: &str,
+    ) -> anyhow::Result<WebhookDeadLetterDo> {
+        let req = TableManagerQueryReq::GetByBytes {
+            table_name: WEBHOOK_DEAD_LETTER_TABLE_NAME.clone(),
+            key: id.as_bytes().to_vec(),
+        };
+        match table_manager.send(req).await?? {
+            TableManagerResult::Value(v) => WebhookDeadLetterDo::from_bytes(&v),
+            _ => Err(anyhow::anyhow!("dead letter not found: {}", id)),
+        }
+    }
+}
+
+///
+/// HMAC-SHA256签名，签名内容为`{timestamp}.{body}`，结果为小写十六进制
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = match HmacSha256::new_from_slice(secret.as_bytes()) {
+        Ok(v) => v,
+        Err(_) => return String::new(),
+    };
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+impl Actor for WebhookManager {
+    type Context = Context<Self>;
+
+    fn started(&mut self, _ctx: &mut Self::Context) {
+        log::info!("WebhookManager actor started")
+    }
+}
+
+impl Inject for WebhookManager {
+    type Context = Context<Self>;
+
+    fn inject(
+        &mut self,
+        factory_data: FactoryData,
+        _factory: BeanFactory,
+        ctx: &mut Self::Context,
+    ) {
+        let raft: Option<Arc<NacosRaft>> = factory_data.get_bean();
+        self.raft = raft.map(|e| Arc::downgrade(&e));
+        self.naming_addr = factory_data.get_actor();
+        self.table_route = factory_data.get_bean();
+        self.table_manager = factory_data.get_actor();
+        if self.wait_load {
+            self.load_hooks(ctx);
+        }
+        self.heartbeat(ctx);
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub enum WebhookCmd {
+    ConfigChanged {
+        key: ConfigKey,
+        md5: Option<Arc<String>>,
+        deleted: bool,
+    },
+    ServiceChanged(ServiceKey),
+    /// webhook表数据变更，由TableManager在raft apply时通知
+    NotifyChange {
+        key: Vec<u8>,
+        value: Vec<u8>,
+    },
+    NotifyRemove {
+        key: Vec<u8>,
+    },
+    LoadCompleted,
+}
+
+impl Handler<WebhookCmd> for WebhookManager {
+    type Result = ();
+
+    fn handle(&mut self, msg: WebhookCmd, ctx: &mut Self::Context) -> Self::Result {
+        match msg {
+            WebhookCmd::ConfigChanged { key, md5, deleted } => {
+                self.config_changed(key, md5, deleted);
+            }
+            WebhookCmd::ServiceChanged(key) => {
+                self.service_changed(key, ctx);
+            }
+            WebhookCmd::NotifyChange { key, value } => {
+                self.update_hook(&key, &value);
+            }
+            WebhookCmd::NotifyRemove { key } => {
+                self.hooks.remove(String::from_utf8_lossy(&key).as_ref());
+            }
+            WebhookCmd::LoadCompleted => {
+                if !self.ready {
+                    self.load_hooks(ctx);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "anyhow::Result<WebhookResult>")]
+pub enum WebhookManageReq {
+    QueryList,
+    /// id为空时新增
+    Set(WebhookParam),
+    Remove(String),
+    QueryDeadLetter(WebhookDeadLetterQueryParam),
+    /// 死信重新加入推送队列
+    RetryDeadLetter(String),
+    RemoveDeadLetter(String),
+}
+
+pub enum WebhookResult {
+    None,
+    Id(String),
+    List(Vec<WebhookDto>),
+    DeadLetterPage(usize, Vec<WebhookDeadLetterDto>),
+}
+
+impl Handler<WebhookManageReq> for WebhookManager {
+    type Result = ResponseActFuture<Self, anyhow::Result<WebhookResult>>;
+
+    fn handle(&mut self, msg: WebhookManageReq, _ctx: &mut Self::Context) -> Self::Result {
+        let (table_route, table_manager) =
+            match (self.table_route.clone(), self.table_manager.clone()) {
+                (Some(table_route), Some(table_manager)) => (table_route, table_manager),
+                _ => {
+                    return Box::pin(fut::ready(Err(anyhow::anyhow!(
+                        "webhook table is not ready"
+                    ))))
+                }
+            };
+        match msg {
+            WebhookManageReq::QueryList => {
+                let list = self.hooks.values().map(|v| v.as_ref().into()).collect();
+                Box::pin(fut::ready(Ok(WebhookResult::List(list))))
+            }
+            WebhookManageReq::Set(param) => {
+                let (id, req) = match self.set_webhook(param) {
+                    Ok(v) => v,
+                    Err(err) => return Box::pin(fut::ready(Err(err))),
+                };
+                let fut = async move {
+                    table_route.request(req).await?;
+                    Ok(WebhookResult::Id(id))
+                };
+                Box::pin(fut.into_actor(self).map(|r, _act, _ctx| r))
+            }
+            WebhookManageReq::Remove(id) => {
+                let req = TableManagerReq::Remove {
+                    table_name: WEBHOOK_TABLE_NAME.clone(),
+                    key: id.into_bytes(),
+                };
+                let fut = async move {
+                    table_route.request(req).await?;
+                    Ok(WebhookResult::None)
+                };
+                Box::pin(fut.into_actor(self).map(|r, _act, _ctx| r))
+            }
+            WebhookManageReq::QueryDeadLetter(param) => {
+                let fut = Self::query_dead_letter(table_manager, param);
+                Box::pin(fut.into_actor(self).map(|r, _act, _ctx| r))
+            }
+            WebhookManageReq::RetryDeadLetter(id) => {
+                let key = id.as_bytes().to_vec();
+                let fut = async move { Self::get_dead_letter(&table_manager, &id).await };
+                Box::pin(
+                    fut.into_actor(self)
+                        .map(|r: anyhow::Result<WebhookDeadLetterDo>, act, _ctx| {
+                            //webhook已删除时保留死信，不能重新推送
+                            let record = r?;
+                            let hook =
+                                act.hooks.get(&record.webhook_id).cloned().ok_or_else(|| {
+                                    anyhow::anyhow!("webhook not found: {}", &record.webhook_id)
+                                })?;
+                            act.queue.push_back(WebhookDelivery {
+                                hook,
+                                event_id: record.event_id,
+                                event_type: record.event_type,
+                                payload: Arc::new(record.payload),
+                                attempts: 0,
+                                next_time: now_millis_i64(),
+                            });
+                            Ok(())
+                        })
+                        .then(move |r: anyhow::Result<()>, act, _ctx| {
+                            //重新加入推送队列后再删除死信
+                            let req = TableManagerReq::Remove {
+                                table_name: WEBHOOK_DEAD_LETTER_TABLE_NAME.clone(),
+                                key,
+                            };
+                            async move {
+                                r?;
+                                table_route.request(req).await?;
+                                Ok(WebhookResult::None)
+                            }
+                            .into_actor(act)
+                        }),
+                )
+            }
+            WebhookManageReq::RemoveDeadLetter(id) => {
+                let req = TableManagerReq::Remove {
+                    table_name: WEBHOOK_DEAD_LETTER_TABLE_NAME.clone(),
+                    key: id.into_bytes(),
+                };
+                let fut = async move {
+                    table_route.request(req).await?;
+                    Ok(WebhookResult::None)
+                };
+                Box::pin(fut.into_actor(self).map(|r, _act, _ctx| r))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_and_retry_interval() {
+        assert_eq!(
+            sign("secret", 1700000000000, "{}"),
+            "8399216d111287e3bb28e25c0f4f31dffdf831c68c9ee2b96c2f67c9b81d341b"
+        );
+        assert_eq!(WebhookManager::retry_interval(1), 1000);
+        assert_eq!(WebhookManager::retry_interval(3), 4000);
+        assert_eq!(
+            WebhookManager::retry_interval(20),
+            MAX_RETRY_INTERVAL_MILLIS
+        );
+    }
+}
@@ -0,0 +1,389 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+pub const EVENT_CONFIG_PUBLISH: &str = "config_publish";
+pub const EVENT_CONFIG_DELETE: &str = "config_delete";
+/// 服务实例列表变更
+pub const EVENT_SERVICE_CHANGE: &str = "service_change";
+/// 服务从有健康实例变为没有健康实例
+pub const EVENT_SERVICE_NO_HEALTHY: &str = "service_no_healthy_instance";
+pub const EVENT_LEADER_CHANGE: &str = "leader_change";
+
+pub const ALL_EVENT_TYPES: [&str; 5] = [
+    EVENT_CONFIG_PUBLISH,
+    EVENT_CONFIG_DELETE,
+    EVENT_SERVICE_CHANGE,
+    EVENT_SERVICE_NO_HEALTHY,
+    EVENT_LEADER_CHANGE,
+];
+
+const MAX_ERROR_LEN: usize = 256;
+
+///
+/// webhook订阅配置
+/// 过滤规则支持`*`通配符，多个规则用逗号分隔，为空表示不过滤；
+/// 服务类事件的data_id_pattern匹配服务名
+#[derive(Clone, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDo {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(string, tag = "3")]
+    pub url: String,
+    /// 签名密钥，为空时不签名
+    #[prost(string, tag = "4")]
+    pub secret: String,
+    #[prost(bool, tag = "5")]
+    pub enable: bool,
+    /// 订阅的事件类型，为空表示订阅全部事件
+    #[prost(string, repeated, tag = "6")]
+    pub event_types: Vec<String>,
+    #[prost(string, tag = "7")]
+    pub namespace_pattern: String,
+    #[prost(string, tag = "8")]
+    pub group_pattern: String,
+    #[prost(string, tag = "9")]
+    pub data_id_pattern: String,
+    #[prost(int64, tag = "10")]
+    pub create_time: i64,
+    #[prost(int64, tag = "11")]
+    pub update_time: i64,
+}
+
+impl WebhookDo {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::new();
+        prost::Message::encode(self, &mut v).unwrap_or_default();
+        v
+    }
+
+    pub fn from_bytes(v: &[u8]) -> anyhow::Result<Self> {
+        Ok(prost::Message::decode(v)?)
+    }
+
+    pub fn subscribe_event_type(&self, event_type: &str) -> bool {
+        self.event_types.is_empty() || self.event_types.iter().any(|v| v == event_type)
+    }
+
+    pub fn match_event(&self, event: &WebhookEvent) -> bool {
+        if !self.enable || !self.subscribe_event_type(&event.event_type) {
+            return false;
+        }
+        let name = event.data_id.as_ref().or(event.service_name.as_ref());
+        match_option(&self.namespace_pattern, &event.namespace)
+            && match_option(&self.group_pattern, &event.group)
+            && match_option(&self.data_id_pattern, &name.cloned())
+    }
+}
+
+/// 事件不含对应字段时(如leader变更)不做过滤
+fn match_option(pattern: &str, value: &Option<Arc<String>>) -> bool {
+    match value {
+        Some(value) => match_patterns(pattern, value),
+        None => true,
+    }
+}
+
+///
+/// 逗号分隔的多个通配符规则，任一匹配即可
+pub fn match_patterns(patterns: &str, value: &str) -> bool {
+    if patterns.trim().is_empty() {
+        return true;
+    }
+    patterns
+        .split(',')
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+        .any(|pattern| wildcard_match(pattern, value))
+}
+
+///
+/// `*`匹配任意长度字符
+pub fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == value;
+    }
+    let first = parts[0];
+    let last = parts[parts.len() - 1];
+    if value.len() < first.len() + last.len() || !value.starts_with(first) || !value.ends_with(last)
+    {
+        return false;
+    }
+    let mut rest = &value[first.len()..value.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        if part.is_empty() {
+            continue;
+        }
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+///
+/// 推送的事件内容
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookEvent {
+    pub event_id: String,
+    pub event_type: String,
+    pub timestamp: i64,
+    pub node_id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<Arc<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<Arc<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_id: Option<Arc<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub md5: Option<Arc<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_name: Option<Arc<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub healthy_instance_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leader_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_leader_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub term: Option<u64>,
+}
+
+impl WebhookEvent {
+    pub fn new(event_type: &str, node_id: u64) -> Self {
+        Self {
+            event_id: uuid::Uuid::new_v4().to_string().replace('-', ""),
+            event_type: event_type.to_owned(),
+            timestamp: crate::now_millis_i64(),
+            node_id,
+            ..Default::default()
+        }
+    }
+}
+
+///
+/// 超过最大重试次数仍推送失败的事件
+#[derive(Clone, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDeadLetterDo {
+    /// 最后一次推送失败的时间，单位毫秒
+    #[prost(int64, tag = "1")]
+    pub timestamp: i64,
+    #[prost(uint64, tag = "2")]
+    pub node_id: u64,
+    #[prost(string, tag = "3")]
+    pub webhook_id: String,
+    #[prost(string, tag = "4")]
+    pub webhook_name: String,
+    #[prost(string, tag = "5")]
+    pub url: String,
+    #[prost(string, tag = "6")]
+    pub event_id: String,
+    #[prost(string, tag = "7")]
+    pub event_type: String,
+    /// 推送的事件json
+    #[prost(string, tag = "8")]
+    pub payload: String,
+    #[prost(uint32, tag = "9")]
+    pub attempts: u32,
+    #[prost(string, tag = "10")]
+    pub last_error: String,
+}
+
+impl WebhookDeadLetterDo {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::new();
+        prost::Message::encode(self, &mut v).unwrap_or_default();
+        v
+    }
+
+    pub fn from_bytes(v: &[u8]) -> anyhow::Result<Self> {
+        Ok(prost::Message::decode(v)?)
+    }
+
+    ///
+    /// key以时间戳开头，与审计日志共用按时间排序与清理的规则
+    pub fn build_key(&self, seq: u64) -> String {
+        format!("{:013}_{}_{:010}", self.timestamp, self.node_id, seq)
+    }
+
+    pub fn set_last_error(&mut self, error: &str) {
+        let mut end = error.len().min(MAX_ERROR_LEN);
+        while !error.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.last_error = error[..end].to_owned();
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDeadLetterDto {
+    pub id: String,
+    #[serde(flatten)]
+    pub record: WebhookDeadLetterDo,
+}
+
+///
+/// 控制台展示的webhook信息，不返回密钥
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDto {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub has_secret: bool,
+    pub enable: bool,
+    pub event_types: Vec<String>,
+    pub namespace_pattern: String,
+    pub group_pattern: String,
+    pub data_id_pattern: String,
+    pub create_time: i64,
+    pub update_time: i64,
+}
+
+impl From<&WebhookDo> for WebhookDto {
+    fn from(v: &WebhookDo) -> Self {
+        Self {
+            id: v.id.clone(),
+            name: v.name.clone(),
+            url: v.url.clone(),
+            has_secret: !v.secret.is_empty(),
+            enable: v.enable,
+            event_types: v.event_types.clone(),
+            namespace_pattern: v.namespace_pattern.clone(),
+            group_pattern: v.group_pattern.clone(),
+            data_id_pattern: v.data_id_pattern.clone(),
+            create_time: v.create_time,
+            update_time: v.update_time,
+        }
+    }
+}
+
+///
+/// 新增或更新webhook的参数，更新时secret为None表示保留原密钥
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookParam {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub url: Option<String>,
+    pub secret: Option<String>,
+    pub enable: Option<bool>,
+    pub event_types: Option<Vec<String>>,
+    pub namespace_pattern: Option<String>,
+    pub group_pattern: Option<String>,
+    pub data_id_pattern: Option<String>,
+}
+
+impl WebhookParam {
+    pub fn check_valid(&self) -> anyhow::Result<()> {
+        if self.name.as_ref().map(|v| v.is_empty()).unwrap_or(true) {
+            return Err(anyhow::anyhow!("name is empty"));
+        }
+        let url = self.url.as_deref().unwrap_or_default();
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(anyhow::anyhow!("url must start with http:// or https://"));
+        }
+        if let Some(event_types) = &self.event_types {
+            let all: HashSet<&str> = ALL_EVENT_TYPES.iter().copied().collect();
+            for item in event_types {
+                if !all.contains(item.as_str()) {
+                    return Err(anyhow::anyhow!("unknown event type: {}", item));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// 合并到已有配置，返回新的配置
+    pub fn build_do(self, old: Option<&WebhookDo>, id: String, now: i64) -> WebhookDo {
+        let mut value = old.cloned().unwrap_or_else(|| WebhookDo {
+            id,
+            enable: true,
+            create_time: now,
+            ..Default::default()
+        });
+        value.name = self.name.unwrap_or_default();
+        value.url = self.url.unwrap_or_default();
+        if let Some(secret) = self.secret {
+            value.secret = secret;
+        }
+        if let Some(enable) = self.enable {
+            value.enable = enable;
+        }
+        value.event_types = self.event_types.unwrap_or_default();
+        value.namespace_pattern = self.namespace_pattern.unwrap_or_default();
+        value.group_pattern = self.group_pattern.unwrap_or_default();
+        value.data_id_pattern = self.data_id_pattern.unwrap_or_default();
+        value.update_time = now;
+        value
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDeadLetterQueryParam {
+    pub webhook_id: Option<String>,
+    pub event_type: Option<String>,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl WebhookDeadLetterQueryParam {
+    pub fn match_record(&self, record: &WebhookDeadLetterDo) -> bool {
+        let eq = |v: &Option<String>, target: &str| {
+            v.as_ref()
+                .is_none_or(|v| v.is_empty() || v.as_str() == target)
+        };
+        eq(&self.webhook_id, &record.webhook_id) && eq(&self.event_type, &record.event_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcard_pattern() {
+        assert!(match_patterns("", "a.yml"));
+        assert!(match_patterns("*", "a.yml"));
+        assert!(match_patterns("*.yml", "a.yml"));
+        assert!(!match_patterns("*.yml", "a.yaml"));
+        assert!(match_patterns("app-*-prod", "app-order-prod"));
+        assert!(!match_patterns("app-*-prod", "app-prod"));
+        assert!(match_patterns("a*b*c", "axxbyyc"));
+        assert!(match_patterns("dev, a.yml", "a.yml"));
+        assert!(!match_patterns("dev,test", "prod"));
+    }
+
+    #[test]
+    fn match_event_filter() {
+        let hook = WebhookDo {
+            enable: true,
+            event_types: vec![EVENT_CONFIG_PUBLISH.to_owned()],
+            group_pattern: "DEFAULT_GROUP".to_owned(),
+            data_id_pattern: "*.yml".to_owned(),
+            ..Default::default()
+        };
+        let mut event = WebhookEvent::new(EVENT_CONFIG_PUBLISH, 1);
+        event.namespace = Some(Arc::new("dev".to_owned()));
+        event.group = Some(Arc::new("DEFAULT_GROUP".to_owned()));
+        event.data_id = Some(Arc::new("app.yml".to_owned()));
+        assert!(hook.match_event(&event));
+        event.data_id = Some(Arc::new("app.json".to_owned()));
+        assert!(!hook.match_event(&event));
+        let leader_event = WebhookEvent::new(EVENT_LEADER_CHANGE, 1);
+        assert!(!hook.match_event(&leader_event));
+    }
+}